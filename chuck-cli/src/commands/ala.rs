@@ -0,0 +1,87 @@
+use chuck_core::data_source::{AlaQuery, AlaSource};
+use chuck_core::downloader::{DownloadProgress, DownloadStage, Downloader};
+use chuck_core::output_name::unique_path;
+use crate::progress::ProgressManager;
+
+const DEFAULT_FILE: &str = "ala-occurrences.zip";
+
+#[derive(Default)]
+pub struct FetchAlaOptions {
+    pub taxon: Option<String>,
+    pub q: Option<String>,
+    pub filters: Vec<String>,
+    pub d1: Option<String>,
+    pub d2: Option<String>,
+    pub file: Option<String>,
+    pub overwrite: bool,
+}
+
+fn build_query(opts: &FetchAlaOptions) -> AlaQuery {
+    let mut query = match (&opts.q, &opts.taxon) {
+        (Some(q), _) => AlaQuery { q: q.clone(), filters: Vec::new() },
+        (None, Some(taxon)) => AlaQuery::for_taxon(taxon),
+        (None, None) => AlaQuery::default(),
+    };
+    query.filters.extend(opts.filters.iter().cloned());
+    query.with_date_range(opts.d1.as_deref(), opts.d2.as_deref())
+}
+
+/// Download occurrences from the Atlas of Living Australia to a DarwinCore
+/// Archive
+pub async fn fetch_ala(opts: FetchAlaOptions) -> Result<(), Box<dyn std::error::Error>> {
+    for date in [&opts.d1, &opts.d2].into_iter().flatten() {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{date}', expected YYYY-MM-DD"))?;
+    }
+    let query = build_query(&opts);
+    if query == AlaQuery::default() {
+        return Err("Specify --taxon, --q, --fq, or a date range to limit the download".into());
+    }
+
+    let path = opts.file.clone().unwrap_or_else(|| DEFAULT_FILE.to_string());
+    let output_path = if opts.overwrite {
+        path
+    } else {
+        let unique = unique_path(std::path::Path::new(&path));
+        if unique != std::path::Path::new(&path) {
+            eprintln!("{path} already exists, writing to {} instead", unique.display());
+        }
+        unique.to_string_lossy().into_owned()
+    };
+
+    let progress_manager = ProgressManager::new(true, false);
+    let progress_callback = move |progress: DownloadProgress| {
+        if progress.stage == DownloadStage::Fetching {
+            if progress.observations_total as u64 > progress_manager.observations_bar.length().unwrap_or(0) {
+                progress_manager.set_observations_total(progress.observations_total as u64);
+            }
+            progress_manager.observations_bar.set_position(progress.observations_current as u64);
+        }
+    };
+
+    let downloader = Downloader::from_source(AlaSource::new(query), Vec::new(), false);
+    downloader.execute(&output_path, progress_callback, None).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query_prefers_raw_query() {
+        let query = build_query(&FetchAlaOptions {
+            taxon: Some("Acacia".to_string()),
+            q: Some("genus:Eucalyptus".to_string()),
+            filters: vec!["state:\"Victoria\"".to_string()],
+            d2: Some("2020-12-31".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(query.q, "genus:Eucalyptus");
+        assert_eq!(
+            query.filters,
+            vec!["state:\"Victoria\"", "occurrence_date:[* TO 2020-12-31T23:59:59Z]"]
+        );
+    }
+}
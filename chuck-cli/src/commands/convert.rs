@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use chuck_core::convert::{convert_archive, ConvertFormat};
+use chuck_core::output_name::unique_path;
+
+pub struct ConvertArchiveOptions {
+    pub archive: String,
+    pub format: ConvertFormat,
+    pub flatten_extensions: bool,
+    pub output: Option<String>,
+    pub overwrite: bool,
+}
+
+/// Writes the core records of a DarwinCore Archive to a CSV or Parquet
+/// file, by default next to the archive with the format's extension
+pub fn convert(opts: ConvertArchiveOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let output = opts.output.map(Into::into).unwrap_or_else(|| {
+        Path::new(&opts.archive).with_extension(opts.format.extension())
+    });
+    let output_path = if opts.overwrite {
+        output
+    } else {
+        let unique = unique_path(&output);
+        if unique != output {
+            eprintln!("{} already exists, writing to {} instead", output.display(), unique.display());
+        }
+        unique
+    };
+
+    let count = convert_archive(
+        Path::new(&opts.archive),
+        &output_path,
+        opts.format,
+        opts.flatten_extensions,
+    )?;
+    println!(
+        "Wrote {count} record{} to {}",
+        if count == 1 { "" } else { "s" },
+        output_path.display()
+    );
+    Ok(())
+}
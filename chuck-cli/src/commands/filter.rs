@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use chuck_core::output_name::unique_path;
+use chuck_core::subset::{write_subset, SubsetFilter};
+
+pub struct FilterArchiveOptions {
+    pub archive: String,
+    pub scientific_name: Option<String>,
+    pub filters: Vec<(String, String)>,
+    pub d1: Option<String>,
+    pub d2: Option<String>,
+    pub output: String,
+    pub overwrite: bool,
+}
+
+/// Parses a --filter value like countryCode=US
+pub fn parse_term_filter(value: &str) -> Result<(String, String), String> {
+    let (term, term_value) = value
+        .split_once('=')
+        .filter(|(term, _)| !term.trim().is_empty())
+        .ok_or_else(|| format!("filter \"{value}\" should be a term and value like countryCode=US"))?;
+    Ok((term.trim().to_string(), term_value.to_string()))
+}
+
+/// Writes the records of a DarwinCore Archive that match the filters, with
+/// their extension rows and media, to a new archive
+pub fn filter_archive(opts: FilterArchiveOptions) -> Result<(), Box<dyn std::error::Error>> {
+    for date in [&opts.d1, &opts.d2].into_iter().flatten() {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{date}', expected YYYY-MM-DD"))?;
+    }
+    let mut filters = opts.filters;
+    if let Some(scientific_name) = opts.scientific_name {
+        filters.push(("scientificName".to_string(), scientific_name));
+    }
+
+    let output_path = if opts.overwrite {
+        Path::new(&opts.output).to_path_buf()
+    } else {
+        let unique = unique_path(Path::new(&opts.output));
+        if unique != Path::new(&opts.output) {
+            eprintln!("{} already exists, writing to {} instead", opts.output, unique.display());
+        }
+        unique
+    };
+    if std::fs::canonicalize(&output_path).ok() == std::fs::canonicalize(&opts.archive).ok()
+        && output_path.exists()
+    {
+        return Err("Output would replace the archive being filtered".into());
+    }
+
+    let summary = write_subset(
+        Path::new(&opts.archive),
+        &output_path,
+        &SubsetFilter { filters, d1: opts.d1, d2: opts.d2 },
+    )?;
+    println!(
+        "Wrote {} record{} and {} extension row{} to {}",
+        summary.core_records,
+        if summary.core_records == 1 { "" } else { "s" },
+        summary.extension_rows,
+        if summary.extension_rows == 1 { "" } else { "s" },
+        output_path.display(),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_term_filter() {
+        assert_eq!(
+            parse_term_filter("countryCode=US").unwrap(),
+            ("countryCode".to_string(), "US".to_string())
+        );
+        assert_eq!(
+            parse_term_filter("locality=Mt. Diablo = summit").unwrap(),
+            ("locality".to_string(), "Mt. Diablo = summit".to_string())
+        );
+        assert!(parse_term_filter("countryCode").is_err());
+        assert!(parse_term_filter("=US").is_err());
+    }
+}
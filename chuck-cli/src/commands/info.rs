@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use chuck_core::archive_summary::summarize_archive;
+
+/// Prints a summary of a DarwinCore Archive, or the summary as JSON if
+/// `json` is set
+pub fn info(archive: &str, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let summary = summarize_archive(Path::new(archive))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    println!("{archive}");
+    println!("Core: {} ({} records)", summary.core_type, summary.record_count);
+    if summary.extensions.is_empty() {
+        println!("Extensions: none");
+    } else {
+        println!("Extensions:");
+        for extension in &summary.extensions {
+            match extension.row_count {
+                Some(rows) => println!("  {} ({}): {rows} rows", extension.row_type, extension.location),
+                None => println!("  {} ({}): file missing", extension.row_type, extension.location),
+            }
+        }
+    }
+    match &summary.date_range {
+        Some((first, last)) => println!("Dates: {first} to {last}"),
+        None => println!("Dates: none"),
+    }
+    match &summary.bounding_box {
+        Some(b) => println!(
+            "Bounding box: {}, {} to {}, {} (lat, lng)",
+            b.min_lat, b.min_lng, b.max_lat, b.max_lng
+        ),
+        None => println!("Bounding box: none"),
+    }
+
+    println!("Columns:");
+    let width = summary.columns.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for column in &summary.columns {
+        println!(
+            "  {:width$}  {:>5.1}%",
+            column.name,
+            column.fill_rate(summary.record_count) * 100.0
+        );
+    }
+
+    if !summary.top_taxa.is_empty() {
+        println!("Top taxa:");
+        for (name, count) in &summary.top_taxa {
+            println!("  {count:>8}  {name}");
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,19 @@
+pub mod ala;
+pub mod convert;
+pub mod filter;
+pub mod info;
+pub mod obis;
 pub mod observations;
+pub mod publish;
+pub mod sql;
+pub mod validate;
 
+pub use ala::{fetch_ala, FetchAlaOptions};
+pub use convert::{convert, ConvertArchiveOptions};
+pub use filter::{filter_archive, FilterArchiveOptions};
+pub use info::info;
+pub use obis::{fetch_obis, FetchObisOptions};
 pub use observations::{fetch_observations, FetchObservationsOptions};
+pub use publish::publish;
+pub use sql::sql;
+pub use validate::validate;
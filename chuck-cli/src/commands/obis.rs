@@ -0,0 +1,64 @@
+use chuck_core::data_source::{ObisQuery, ObisSource};
+use chuck_core::downloader::{DownloadProgress, DownloadStage, Downloader};
+use chuck_core::output_name::unique_path;
+use crate::progress::ProgressManager;
+
+const DEFAULT_FILE: &str = "obis-occurrences.zip";
+
+#[derive(Default)]
+pub struct FetchObisOptions {
+    pub query: ObisQuery,
+    pub include_measurements: bool,
+    pub file: Option<String>,
+    pub overwrite: bool,
+}
+
+/// Download occurrences from OBIS to a DarwinCore Archive
+pub async fn fetch_obis(opts: FetchObisOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let query = &opts.query;
+    for date in [&query.start_date, &query.end_date].into_iter().flatten() {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{date}', expected YYYY-MM-DD"))?;
+    }
+    if let (Some(start), Some(end)) = (query.start_depth, query.end_depth)
+        && start > end
+    {
+        return Err("--min-depth must not be greater than --max-depth".into());
+    }
+    if query.is_empty() {
+        return Err(
+            "Specify a taxon, geometry, depth range, or date range to limit the download".into()
+        );
+    }
+
+    let path = opts.file.clone().unwrap_or_else(|| DEFAULT_FILE.to_string());
+    let output_path = if opts.overwrite {
+        path
+    } else {
+        let unique = unique_path(std::path::Path::new(&path));
+        if unique != std::path::Path::new(&path) {
+            eprintln!("{path} already exists, writing to {} instead", unique.display());
+        }
+        unique.to_string_lossy().into_owned()
+    };
+
+    let progress_manager = ProgressManager::new(true, false);
+    let progress_callback = move |progress: DownloadProgress| {
+        if progress.stage == DownloadStage::Fetching {
+            if progress.observations_total as u64 > progress_manager.observations_bar.length().unwrap_or(0) {
+                progress_manager.set_observations_total(progress.observations_total as u64);
+            }
+            progress_manager.observations_bar.set_position(progress.observations_current as u64);
+        }
+    };
+
+    let extensions = if opts.include_measurements {
+        vec![chuck_core::DwcaExtension::MeasurementOrFact]
+    } else {
+        Vec::new()
+    };
+    let source = ObisSource::new(opts.query, opts.include_measurements);
+    let downloader = Downloader::from_source(source, extensions, false);
+    downloader.execute(&output_path, progress_callback, None).await?;
+    Ok(())
+}
@@ -1,10 +1,19 @@
 use tokio::sync::mpsc;
 use inaturalist::models::ObservationsResponse;
+use inaturalist::apis::configuration::ApiKey;
 use inaturalist::apis::observations_api::ObservationsGetParams;
-use crate::output::{CsvOutput, ObservationWriter, csv::observation_to_row};
-use chuck_core::api::{client, params::{build_params, parse_url_params}, rate_limiter::get_rate_limiter};
+use crate::output::{CsvOutput, GeoJsonOutput, JsonOutput, ObservationWriter, csv::observation_to_row};
+use chuck_core::api::{
+    client,
+    params::{apply_filters, build_params, parse_url_params, ObservationFilters},
+    rate_limiter::get_rate_limiter,
+    taxon_suggestions::{fetch_taxon_suggestions, TaxonSuggestion},
+    validation::{validate_params, validate_params_remote},
+};
 use chuck_core::archive_updater::update_archive;
+use chuck_core::auth::TokenStorage;
 use chuck_core::downloader::Downloader;
+use chuck_core::output_name::{render_name_template, unique_path, DEFAULT_NAME};
 use crate::progress::ProgressManager;
 
 #[derive(Default)]
@@ -17,11 +26,25 @@ pub struct FetchObservationsOptions {
     pub d2: Option<String>,
     pub created_d1: Option<String>,
     pub created_d2: Option<String>,
+    pub filters: ObservationFilters,
     pub file: Option<String>,
+    pub name_template: Option<String>,
+    pub overwrite: bool,
     pub fetch_media: bool,
+    pub media_license_policy: chuck_core::media_license::MediaLicensePolicy,
     pub format: crate::OutputFormat,
+    /// DarwinCore terms to write as CSV columns instead of the default ones
+    pub fields: Vec<String>,
+    pub json_records: crate::JsonRecords,
     pub dwc_extensions: Vec<crate::DwcExtension>,
+    pub include_annotations: bool,
+    /// Link occurrences to their observations and taxa on iNat
+    pub record_links: bool,
     pub update: bool,
+    pub validate_only: bool,
+    /// Auth profile to download as, so observations it can see private
+    /// coordinates for are included
+    pub profile: Option<String>,
 }
 
 fn setup_progress_bar(
@@ -71,7 +94,7 @@ pub fn build_fetch_params(opts: &FetchObservationsOptions) -> ObservationsGetPar
         let query = url.find('?').map(|i| &url[i + 1..]).unwrap_or(url);
         parse_url_params(query)
     } else {
-        build_params(
+        let mut params = build_params(
             opts.taxon.clone(),
             opts.place_id,
             opts.user.clone(),
@@ -79,10 +102,47 @@ pub fn build_fetch_params(opts: &FetchObservationsOptions) -> ObservationsGetPar
             opts.d2.clone(),
             opts.created_d1.clone(),
             opts.created_d2.clone(),
-        )
+        );
+        apply_filters(&mut params, &opts.filters);
+        params
     }
 }
 
+/// Resolves where a new download should be written: --file, a rendered
+/// --name-template, or the format's default. Unless --overwrite is set, an
+/// existing file gets a numbered suffix rather than being replaced. None means
+/// CSV, JSON, or GeoJSON to stdout.
+fn resolve_output_path(
+    opts: &FetchObservationsOptions,
+    params: &ObservationsGetParams,
+) -> Option<String> {
+    let extension = match opts.format {
+        crate::OutputFormat::Csv => "csv",
+        crate::OutputFormat::Dwc => "zip",
+        crate::OutputFormat::Json => "json",
+        crate::OutputFormat::Ndjson => "ndjson",
+        crate::OutputFormat::Geojson => "geojson",
+    };
+    let path = if let Some(ref file) = opts.file {
+        file.clone()
+    } else if let Some(ref template) = opts.name_template {
+        let today = chrono::Local::now().date_naive();
+        format!("{}.{extension}", render_name_template(template, params, today))
+    } else if opts.format == crate::OutputFormat::Dwc {
+        format!("{DEFAULT_NAME}.{extension}")
+    } else {
+        return None;
+    };
+    if opts.overwrite {
+        return Some(path);
+    }
+    let unique = unique_path(std::path::Path::new(&path));
+    if unique != std::path::Path::new(&path) {
+        eprintln!("{path} already exists, writing to {} instead", unique.display());
+    }
+    Some(unique.to_string_lossy().into_owned())
+}
+
 fn has_filter_args(opts: &FetchObservationsOptions) -> bool {
     opts.url.is_some()
         || opts.taxon.is_some()
@@ -92,6 +152,141 @@ fn has_filter_args(opts: &FetchObservationsOptions) -> bool {
         || opts.d2.is_some()
         || opts.created_d1.is_some()
         || opts.created_d2.is_some()
+        || opts.filters != ObservationFilters::default()
+}
+
+/// Checks combinations of flags that clap can't express on its own
+fn validate_options(opts: &FetchObservationsOptions) -> Vec<String> {
+    let mut errors = Vec::new();
+    if opts.format != crate::OutputFormat::Dwc {
+        if opts.fetch_media {
+            errors.push("--fetch-media only applies to --format dwc".to_string());
+        }
+        if !opts.dwc_extensions.is_empty() {
+            errors.push("--dwc-ext only applies to --format dwc".to_string());
+        }
+        if opts.include_annotations {
+            errors.push("--include-annotations only applies to --format dwc".to_string());
+        }
+        if opts.record_links {
+            errors.push("--record-links only applies to --format dwc".to_string());
+        }
+    } else if opts.record_links && opts.update {
+        errors.push("--record-links can't be used with --update".to_string());
+    }
+    if !opts.fields.is_empty() {
+        if opts.format != crate::OutputFormat::Csv {
+            errors.push("--fields only applies to --format csv".to_string());
+        } else if opts.update {
+            errors.push("--fields can't be used with --update".to_string());
+        }
+        if let Err(e) = chuck_core::darwin_core::Occurrence::field_selection(&opts.fields) {
+            errors.push(e);
+        }
+    }
+    let is_json = matches!(opts.format, crate::OutputFormat::Json | crate::OutputFormat::Ndjson);
+    if !is_json && opts.json_records != crate::JsonRecords::Raw {
+        errors.push("--json-records only applies to --format json or ndjson".to_string());
+    }
+    if (is_json || opts.format == crate::OutputFormat::Geojson) && opts.update {
+        errors.push("--update only applies to --format csv or dwc".to_string());
+    }
+    if !opts.fetch_media
+        && opts.media_license_policy != chuck_core::media_license::MediaLicensePolicy::All
+    {
+        errors.push("--media-license-policy requires --fetch-media".to_string());
+    }
+    errors
+}
+
+/// Validates flags and search params before any paging starts, including
+/// checking that referenced taxa and places exist. Returns an error listing
+/// every problem found so they can all be fixed at once.
+async fn validate_fetch(
+    opts: &FetchObservationsOptions,
+    params: &ObservationsGetParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut errors = validate_options(opts);
+    errors.extend(validate_params(params));
+    let base_path = client::get_config().await.read().await.base_path.clone();
+    errors.extend(validate_params_remote(&base_path, params).await?);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let list = errors
+        .iter()
+        .map(|e| format!("  - {e}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(format!("Invalid parameters:\n{list}").into())
+}
+
+/// The suggestion to use without asking: the only one whose scientific or
+/// common name is exactly the query
+fn exact_taxon_match<'a>(
+    suggestions: &'a [TaxonSuggestion],
+    query: &str,
+) -> Option<&'a TaxonSuggestion> {
+    let mut exact = suggestions.iter().filter(|s| s.matches_exactly(query));
+    match (exact.next(), exact.next()) {
+        (Some(only), None) => Some(only),
+        _ => None,
+    }
+}
+
+/// Turns a --taxon name into a taxon ID so the download doesn't depend on
+/// how the API matches names. A single exact match is used as is; otherwise
+/// the candidates are listed to pick from when running in a terminal.
+/// Returns None to fall back to searching by name.
+async fn resolve_taxon_name(name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    let base_path = client::get_config().await.read().await.base_path.clone();
+    let suggestions = fetch_taxon_suggestions(&base_path, name, 10).await?;
+    if let Some(taxon) = exact_taxon_match(&suggestions, name) {
+        eprintln!("Using taxon {taxon} [ID {}]", taxon.id);
+        return Ok(Some(taxon.id.to_string()));
+    }
+    if suggestions.is_empty() || !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    eprintln!("Several taxa match \"{name}\":");
+    for (i, taxon) in suggestions.iter().enumerate() {
+        eprintln!("  {}. {taxon}", i + 1);
+    }
+    loop {
+        eprint!("Choose a taxon (1-{}), or press Enter to search by name: ", suggestions.len());
+        std::io::stderr().flush()?;
+        let mut input = String::new();
+        std::io::stdin().lock().read_line(&mut input)?;
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        match input.parse::<usize>() {
+            Ok(choice) if (1..=suggestions.len()).contains(&choice) => {
+                return Ok(Some(suggestions[choice - 1].id.to_string()));
+            }
+            _ => eprintln!("\"{input}\" isn't one of the choices"),
+        }
+    }
+}
+
+/// Makes requests with the shared API config as the account of an auth
+/// profile, returning the JWT so downloaders can use it too
+async fn authenticate_profile(profile: &str) -> Result<String, Box<dyn std::error::Error>> {
+    chuck_core::auth::set_active_profile(Some(profile))?;
+    let storage = chuck_core::auth::StorageFactory::create()?;
+    let token = storage.load_token()?.ok_or_else(|| {
+        format!("Not signed in to profile '{profile}'. Run `chuck auth --profile {profile}` first")
+    })?;
+    let jwt = chuck_core::auth::fetch_jwt(&token).await?;
+    client::get_config().await.write().await.api_key = Some(ApiKey {
+        prefix: None,
+        key: jwt.clone(),
+    });
+    Ok(jwt)
 }
 
 pub async fn fetch_observations(
@@ -106,6 +301,11 @@ pub async fn fetch_observations(
         opts.format = crate::OutputFormat::Dwc;
     }
 
+    let jwt = match opts.profile.as_deref() {
+        Some(profile) => Some(authenticate_profile(profile).await?),
+        None => None,
+    };
+
     // --- Validate --update constraints ---
     if opts.update {
         if opts.file.is_none() {
@@ -119,6 +319,34 @@ pub async fn fetch_observations(
         }
     }
 
+    // --- Validate search params (filters for a dwc update come from the archive) ---
+    if !(opts.update && opts.format == crate::OutputFormat::Dwc) {
+        if opts.url.is_none() {
+            if let Some(name) = opts.taxon.clone().filter(|t| t.parse::<i32>().is_err()) {
+                if let Some(taxon_id) = resolve_taxon_name(&name).await? {
+                    opts.taxon = Some(taxon_id);
+                }
+            }
+        }
+        let params = build_fetch_params(&opts);
+        validate_fetch(&opts, &params).await?;
+        if opts.validate_only {
+            let config = client::get_config().await;
+            let response = client::fetch_observations_with_retry(
+                config,
+                ObservationsGetParams { per_page: Some("0".to_string()), ..params },
+            ).await?;
+            println!(
+                "Parameters are valid; {} observations match",
+                response.total_results.unwrap_or(0)
+            );
+            return Ok(());
+        }
+    } else if opts.validate_only {
+        println!("Nothing to validate; filters are read from the archive");
+        return Ok(());
+    }
+
     // --- DwC update path ---
     if opts.update && opts.format == crate::OutputFormat::Dwc {
         let zip_path = opts.file.as_deref().unwrap();
@@ -149,7 +377,7 @@ pub async fn fetch_observations(
                 chuck_core::downloader::DownloadStage::Merging { .. } => {}
             }
         };
-        return update_archive(zip_path, progress_callback, None, None).await;
+        return update_archive(zip_path, progress_callback, jwt, None).await;
     }
 
     // --- CSV update path ---
@@ -160,6 +388,7 @@ pub async fn fetch_observations(
 
     let config = client::get_config().await;
     let params = build_fetch_params(&opts);
+    opts.file = resolve_output_path(&opts, &params);
 
     let show_progress = opts.file.is_some();
     let progress_manager = ProgressManager::new(show_progress, opts.fetch_media);
@@ -176,9 +405,30 @@ pub async fn fetch_observations(
 
     // Spawn writer task based on format
     match opts.format {
-        crate::OutputFormat::Csv => {
-            let writer = CsvOutput::new(opts.file).unwrap();
-            let writer_handle = spawn_observation_write_task(writer, rx, progress_manager_clone);
+        crate::OutputFormat::Csv
+        | crate::OutputFormat::Json
+        | crate::OutputFormat::Ndjson
+        | crate::OutputFormat::Geojson => {
+            let writer_handle = match opts.format {
+                crate::OutputFormat::Csv => {
+                    let fields = if opts.fields.is_empty() {
+                        None
+                    } else {
+                        Some(chuck_core::darwin_core::Occurrence::field_selection(&opts.fields)?)
+                    };
+                    let writer = CsvOutput::new(opts.file, fields).unwrap();
+                    spawn_observation_write_task(writer, rx, progress_manager_clone)
+                }
+                crate::OutputFormat::Geojson => {
+                    let writer = GeoJsonOutput::new(opts.file)?;
+                    spawn_observation_write_task(writer, rx, progress_manager_clone)
+                }
+                _ => {
+                    let ndjson = opts.format == crate::OutputFormat::Ndjson;
+                    let writer = JsonOutput::new(opts.file, ndjson, opts.json_records)?;
+                    spawn_observation_write_task(writer, rx, progress_manager_clone)
+                }
+            };
 
             // Spawn API fetcher task
             let fetcher_handle = tokio::spawn(async move {
@@ -228,15 +478,20 @@ pub async fn fetch_observations(
             fetcher_result.unwrap();
         }
         crate::OutputFormat::Dwc => {
-            let output_path = opts.file.unwrap_or_else(|| "observations.zip".to_string());
+            let output_path = opts.file.unwrap_or_else(|| format!("{DEFAULT_NAME}.zip"));
 
-            let core_extensions: Vec<chuck_core::DwcaExtension> = opts.dwc_extensions
+            let mut core_extensions: Vec<chuck_core::DwcaExtension> = opts.dwc_extensions
                 .iter()
                 .map(|e| e.clone().into())
                 .collect();
+            if opts.include_annotations {
+                core_extensions.push(chuck_core::DwcaExtension::MeasurementOrFact);
+            }
 
-            // Create downloader (CLI uses file-based auth, so no JWT needed)
-            let downloader = Downloader::new(params, core_extensions, opts.fetch_media, None);
+            // Create downloader, authenticated as the profile if there is one
+            let downloader = Downloader::new(params, core_extensions, opts.fetch_media, jwt)
+                .with_media_license_policy(opts.media_license_policy)
+                .with_record_links(opts.record_links);
 
             // Create progress callback
             let progress_callback = move |progress: chuck_core::downloader::DownloadProgress| {
@@ -404,6 +659,156 @@ mod tests {
         assert_eq!(p.place_id, Some(vec![1i32]));
     }
 
+    fn suggestion(id: i64, name: &str, common_name: Option<&str>) -> TaxonSuggestion {
+        TaxonSuggestion {
+            id,
+            name: name.to_string(),
+            rank: None,
+            preferred_common_name: common_name.map(String::from),
+            observations_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_exact_taxon_match() {
+        let suggestions = vec![
+            suggestion(1, "Centromadia", Some("tarweeds")),
+            suggestion(2, "Centromadia pungens", None),
+        ];
+        assert_eq!(exact_taxon_match(&suggestions, "centromadia").map(|s| s.id), Some(1));
+        assert_eq!(exact_taxon_match(&suggestions, "Tarweeds").map(|s| s.id), Some(1));
+        assert_eq!(exact_taxon_match(&suggestions, "Centro"), None);
+    }
+
+    #[test]
+    fn test_exact_taxon_match_is_none_when_ambiguous() {
+        // Homonyms, e.g. a plant and an animal genus with the same name
+        let suggestions = vec![
+            suggestion(1, "Morus", Some("mulberries")),
+            suggestion(2, "Morus", Some("gannets")),
+        ];
+        assert_eq!(exact_taxon_match(&suggestions, "Morus"), None);
+    }
+
+    #[test]
+    fn test_build_fetch_params_applies_filters() {
+        let opts = FetchObservationsOptions {
+            taxon: Some("47790".to_string()),
+            filters: ObservationFilters {
+                project_id: Some("1234".to_string()),
+                quality_grade: Some("research".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let p = build_fetch_params(&opts);
+        assert_eq!(p.taxon_id, Some(vec!["47790".to_string()]));
+        assert_eq!(p.project_id, Some(vec!["1234".to_string()]));
+        assert_eq!(p.quality_grade, Some("research".to_string()));
+        assert!(has_filter_args(&FetchObservationsOptions {
+            filters: opts.filters.clone(),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_validate_options_rejects_dwc_flags_for_csv() {
+        let errors = validate_options(&FetchObservationsOptions {
+            fetch_media: true,
+            dwc_extensions: vec![crate::DwcExtension::Comments],
+            include_annotations: true,
+            record_links: true,
+            ..Default::default()
+        });
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].contains("--fetch-media"));
+        assert!(errors[1].contains("--dwc-ext"));
+        assert!(errors[2].contains("--include-annotations"));
+        assert!(errors[3].contains("--record-links"));
+    }
+
+    #[test]
+    fn test_validate_options_license_policy_requires_fetch_media() {
+        let mut opts = FetchObservationsOptions {
+            format: crate::OutputFormat::Dwc,
+            media_license_policy:
+                chuck_core::media_license::MediaLicensePolicy::ExcludeNonCommercial,
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_options(&opts),
+            vec!["--media-license-policy requires --fetch-media".to_string()]
+        );
+        opts.fetch_media = true;
+        assert!(validate_options(&opts).is_empty());
+    }
+
+    #[test]
+    fn test_validate_options_checks_fields() {
+        let mut opts = FetchObservationsOptions {
+            fields: vec!["occurrenceID".to_string(), "colour".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_options(&opts),
+            vec!["Unknown occurrence fields: colour".to_string()]
+        );
+        opts.fields.pop();
+        assert!(validate_options(&opts).is_empty());
+        opts.format = crate::OutputFormat::Dwc;
+        assert_eq!(
+            validate_options(&opts),
+            vec!["--fields only applies to --format csv".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_validate_options_json_records_requires_json_format() {
+        let mut opts = FetchObservationsOptions {
+            json_records: crate::JsonRecords::Occurrence,
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_options(&opts),
+            vec!["--json-records only applies to --format json or ndjson".to_string()]
+        );
+        opts.format = crate::OutputFormat::Ndjson;
+        assert!(validate_options(&opts).is_empty());
+        opts.update = true;
+        assert_eq!(
+            validate_options(&opts),
+            vec!["--update only applies to --format csv or dwc".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_resolve_output_path_renders_template_without_clobbering() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("{place}").to_string_lossy().into_owned();
+        let opts = FetchObservationsOptions {
+            place_id: Some(14),
+            name_template: Some(template),
+            format: crate::OutputFormat::Dwc,
+            ..Default::default()
+        };
+        let params = build_fetch_params(&opts);
+        let expected = dir.path().join("14.zip");
+        assert_eq!(resolve_output_path(&opts, &params), Some(expected.to_string_lossy().into_owned()));
+
+        std::fs::write(&expected, "").unwrap();
+        let suffixed = dir.path().join("14-1.zip");
+        assert_eq!(resolve_output_path(&opts, &params), Some(suffixed.to_string_lossy().into_owned()));
+
+        let opts = FetchObservationsOptions { overwrite: true, ..opts };
+        assert_eq!(resolve_output_path(&opts, &params), Some(expected.to_string_lossy().into_owned()));
+    }
+
+    #[test]
+    fn test_resolve_output_path_csv_defaults_to_stdout() {
+        let opts = FetchObservationsOptions::default();
+        assert_eq!(resolve_output_path(&opts, &build_fetch_params(&opts)), None);
+    }
+
     #[test]
     fn test_build_fetch_params_url_without_scheme_still_works() {
         let p = build_fetch_params(&FetchObservationsOptions {
@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use chuck_core::archive_validator::validate_archive;
+use chuck_core::publish::{publish_to_zenodo, ZENODO_API_URL, ZENODO_SANDBOX_API_URL};
+
+/// Publishes a DarwinCore Archive to Zenodo, recording the DOI it gets in
+/// the archive's eml.xml. Archives with validation errors aren't published
+/// since a DOI can't be taken back.
+pub async fn publish(archive: &str, token: &str, sandbox: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let report = validate_archive(Path::new(archive))?;
+    if !report.is_valid() {
+        for issue in &report.issues {
+            println!("{issue}");
+        }
+        return Err(format!(
+            "{archive} has {} error{}. Fix them before publishing.",
+            report.error_count(),
+            if report.error_count() == 1 { "" } else { "s" },
+        )
+        .into());
+    }
+
+    let base_path = if sandbox { ZENODO_SANDBOX_API_URL } else { ZENODO_API_URL };
+    log::info!("Publishing {archive} to Zenodo...");
+    let published = publish_to_zenodo(base_path, token, Path::new(archive)).await?;
+    println!("DOI: {}", published.doi_url);
+    println!("Record: {}", published.record_url);
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use std::path::Path;
+
+use chuck_core::sql_console::execute_sql_in_file;
+
+/// Runs a SELECT query against a DuckDB database created by Chuck and prints
+/// the columns and rows as JSON
+pub fn sql(database: &str, query: &str, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let result = execute_sql_in_file(Path::new(database), query, limit)?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    if result.truncated {
+        log::warn!("Showing the first {limit} rows. Use --limit to see more.");
+    }
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use std::path::Path;
+
+use chuck_core::archive_validator::validate_archive;
+
+/// Prints problems with a DarwinCore Archive, returning whether it's free of
+/// errors
+pub fn validate(archive: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let report = validate_archive(Path::new(archive))?;
+    for issue in &report.issues {
+        println!("{issue}");
+    }
+    println!(
+        "{archive}: {} error{}, {} warning{}",
+        report.error_count(),
+        if report.error_count() == 1 { "" } else { "s" },
+        report.warning_count(),
+        if report.warning_count() == 1 { "" } else { "s" },
+    );
+    Ok(report.is_valid())
+}
@@ -23,6 +23,12 @@ enum OutputFormat {
     Csv,
     /// DarwinCore Archive
     Dwc,
+    /// JSON array
+    Json,
+    /// Newline-delimited JSON, one record per line
+    Ndjson,
+    /// GeoJSON FeatureCollection of observation points
+    Geojson,
 }
 
 impl Default for OutputFormat {
@@ -31,6 +37,15 @@ impl Default for OutputFormat {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, ValueEnum, PartialEq)]
+pub enum JsonRecords {
+    /// Observations as the iNaturalist API returns them
+    #[default]
+    Raw,
+    /// DarwinCore Occurrence records, as in an archive's occurrence.csv
+    Occurrence,
+}
+
 #[derive(Clone, Debug, ValueEnum, PartialEq)]
 pub enum DwcExtension {
     /// Simple Multimedia extension
@@ -54,6 +69,44 @@ impl From<DwcExtension> for chuck_core::DwcaExtension {
     }
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq)]
+pub enum ConvertFormat {
+    /// CSV, with extensions as JSON arrays
+    Csv,
+    /// Apache Parquet, with extensions as lists of structs
+    Parquet,
+}
+
+impl From<ConvertFormat> for chuck_core::convert::ConvertFormat {
+    fn from(format: ConvertFormat) -> Self {
+        match format {
+            ConvertFormat::Csv => Self::Csv,
+            ConvertFormat::Parquet => Self::Parquet,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, ValueEnum, PartialEq)]
+pub enum MediaLicensePolicy {
+    /// Download media regardless of license
+    #[default]
+    All,
+    /// Skip media with all rights reserved
+    ExcludeAllRightsReserved,
+    /// Skip all rights reserved and non-commercial media
+    ExcludeNonCommercial,
+}
+
+impl From<MediaLicensePolicy> for chuck_core::media_license::MediaLicensePolicy {
+    fn from(policy: MediaLicensePolicy) -> Self {
+        match policy {
+            MediaLicensePolicy::All => Self::All,
+            MediaLicensePolicy::ExcludeAllRightsReserved => Self::ExcludeAllRightsReserved,
+            MediaLicensePolicy::ExcludeNonCommercial => Self::ExcludeNonCommercial,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum AuthCommands {
     /// Clear stored authentication token
@@ -67,10 +120,16 @@ enum Commands {
     Auth {
         #[command(subcommand)]
         auth_command: Option<AuthCommands>,
+
+        /// Named profile to sign in to or clear, for keeping tokens for more
+        /// than one iNaturalist account. Defaults to the "default" profile.
+        #[arg(long, global = true)]
+        profile: Option<String>,
     },
     /// Download iNaturalist observations
     Obs {
-        /// Observations taxon (accepts name or ID)
+        /// Observations taxon (accepts name or ID). Names that match more
+        /// than one taxon are listed to choose from.
         #[arg(short, long)]
         taxon: Option<String>,
 
@@ -98,21 +157,59 @@ enum Commands {
         #[arg(long)]
         created_d2: Option<String>,
 
+        /// Observations project (accepts slug or ID)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Observations quality grade: research, needs_id, or casual.
+        /// Separate several with commas, e.g. research,needs_id
+        #[arg(long)]
+        quality_grade: Option<String>,
+
+        /// Observations with an annotation, as controlled term and value
+        /// IDs, e.g. 1/2 for Life Stage: Adult
+        #[arg(long, value_parser = chuck_core::api::params::parse_annotation)]
+        annotation: Option<(i32, i32)>,
+
+        /// Observations license, e.g. cc-by or cc0. Separate several with
+        /// commas
+        #[arg(long)]
+        license: Option<String>,
+
+        /// Only observations with obscured or private coordinates, or with
+        /// --obscured=false only observations with neither
+        #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+        obscured: Option<bool>,
+
         /// iNaturalist observations URL or query string; any recognized
         /// search params will be used as filters, e.g.
-        /// user_id=1&lrank=genus. Cannot be combined with --taxon,
-        /// --place-id, --user, --d1, --d2, --created-d1, or --created-d2.
+        /// user_id=1&lrank=genus. Cannot be combined with the other filter
+        /// flags, e.g. --taxon or --d1.
         #[arg(
             long,
-            conflicts_with_all = ["taxon", "place_id", "user", "d1", "d2", "created_d1", "created_d2"]
+            conflicts_with_all = [
+                "taxon", "place_id", "user", "d1", "d2", "created_d1", "created_d2",
+                "project", "quality_grade", "annotation", "license", "obscured",
+            ]
         )]
         url: Option<String>,
 
-        /// Path to write CSV if format is csv, path of DarwinCore Archive if
+        /// Path to write CSV, JSON, or GeoJSON to, or path of DarwinCore Archive if
         /// format is dwc
         #[arg(long)]
         file: Option<String>,
 
+        /// Name the output file from a template instead of --file, e.g.
+        /// "{taxon}-{place}-{date}". Tokens: {taxon}, {place}, {user}, {d1},
+        /// {d2}, {date} (today). The extension is added for you.
+        #[arg(long, conflicts_with = "file")]
+        name_template: Option<String>,
+
+        /// Replace the output file if it already exists instead of adding a
+        /// numbered suffix, e.g. observations-1.zip
+        #[arg(long)]
+        overwrite: bool,
+
         /// Update an existing archive or CSV with recently changed observations.
         /// Requires --file. For --format dwc, reads filter params from the archive
         /// and errors if any filter args are also provided.
@@ -123,17 +220,257 @@ enum Commands {
         #[arg(long)]
         fetch_media: bool,
 
+        /// Which media to download with --fetch-media based on license.
+        /// Skipped media are listed in the archive's report.csv
+        #[arg(long, value_enum, default_value_t = MediaLicensePolicy::default())]
+        media_license_policy: MediaLicensePolicy,
+
         #[arg(long, value_enum, default_value_t = OutputFormat::default())]
         format: OutputFormat,
 
+        /// Write these DarwinCore occurrence terms as the CSV columns instead
+        /// of the default observation columns, e.g.
+        /// occurrenceID,scientificName,eventDate
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
+
+        /// What to write for each observation when format is json or ndjson
+        #[arg(long, value_enum, default_value_t = JsonRecords::default())]
+        json_records: JsonRecords,
+
         /// DarwinCore extenions to include when format is dwc
         #[arg(long = "dwc-ext", value_enum)]
         dwc_extensions: Vec<DwcExtension>,
+
+        /// Include annotations like life stage, sex, and phenology in a
+        /// MeasurementOrFact extension when format is dwc
+        #[arg(long)]
+        include_annotations: bool,
+
+        /// Fill references and taxonConceptID with the URLs of each
+        /// observation and its taxon on iNaturalist when format is dwc
+        #[arg(long)]
+        record_links: bool,
+
+        /// Check the parameters (date formats, taxon and place lookups,
+        /// conflicting flags) and report how many observations match
+        /// without downloading anything
+        #[arg(long)]
+        validate_only: bool,
+
+        /// Stop with an error after this many iNaturalist API requests,
+        /// counting retries, e.g. to keep a large download from running
+        /// into the daily request limit
+        #[arg(long)]
+        max_requests: Option<usize>,
+
+        /// Download as the account signed in to this profile with
+        /// `chuck auth --profile`, including private coordinates it can see
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Download occurrences from the Atlas of Living Australia as a
+    /// DarwinCore Archive
+    Ala {
+        /// Taxon name, including everything below it, e.g. Acacia
+        #[arg(long)]
+        taxon: Option<String>,
+
+        /// ALA biocache query, used instead of --taxon, e.g.
+        /// 'genus:Eucalyptus'
+        #[arg(long)]
+        q: Option<String>,
+
+        /// ALA biocache filter query, e.g. 'state:"Victoria"'. Can be
+        /// repeated.
+        #[arg(long)]
+        fq: Vec<String>,
+
+        /// Earliest occurrence date, e.g. 2020-01-01
+        #[arg(long)]
+        d1: Option<String>,
+
+        /// Latest occurrence date, e.g. 2020-01-01
+        #[arg(long)]
+        d2: Option<String>,
+
+        /// Path of the DarwinCore Archive
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Replace the output file if it already exists instead of adding a
+        /// numbered suffix
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Download marine occurrences from the Ocean Biodiversity Information
+    /// System (OBIS) as a DarwinCore Archive
+    Obis {
+        /// Scientific name, including everything below it, e.g. Abra alba
+        #[arg(long)]
+        taxon: Option<String>,
+
+        /// WoRMS AphiaID of a taxon, including everything below it
+        #[arg(long)]
+        taxon_id: Option<i32>,
+
+        /// Area as WKT in WGS84, e.g. 'POLYGON((2 51, 3 51, 3 52, 2 52, 2 51))'
+        #[arg(long)]
+        geometry: Option<String>,
+
+        /// Shallowest depth in meters
+        #[arg(long)]
+        min_depth: Option<f64>,
+
+        /// Deepest depth in meters
+        #[arg(long)]
+        max_depth: Option<f64>,
+
+        /// Earliest occurrence date, e.g. 2020-01-01
+        #[arg(long)]
+        d1: Option<String>,
+
+        /// Latest occurrence date, e.g. 2020-01-01
+        #[arg(long)]
+        d2: Option<String>,
+
+        /// Include measurements like abundance and water temperature in a
+        /// MeasurementOrFact extension
+        #[arg(long)]
+        include_measurements: bool,
+
+        /// Path of the DarwinCore Archive
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Replace the output file if it already exists instead of adding a
+        /// numbered suffix
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Write the records of a DarwinCore Archive that match some filters to
+    /// a new archive, with their extension rows and media. Filters match
+    /// like they do in the app: text when it contains the value, ignoring
+    /// case, and numbers at the precision of the value, so 3 matches 3.5.
+    Filter {
+        /// Path to the archive zip file
+        archive: String,
+
+        /// Scientific name, e.g. Quercus
+        #[arg(long)]
+        scientific_name: Option<String>,
+
+        /// Records whose value of a Darwin Core term matches some text, as
+        /// term=value, e.g. countryCode=US. Can be repeated.
+        #[arg(long, value_parser = commands::filter::parse_term_filter)]
+        filter: Vec<(String, String)>,
+
+        /// Earliest eventDate, e.g. 2020-01-01
+        #[arg(long)]
+        d1: Option<String>,
+
+        /// Latest eventDate, e.g. 2020-12-31
+        #[arg(long)]
+        d2: Option<String>,
+
+        /// Path of the new archive
+        #[arg(short, long)]
+        output: String,
+
+        /// Replace the output file if it already exists instead of adding a
+        /// numbered suffix
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Convert a DarwinCore Archive to a single CSV or Parquet file of its
+    /// core records for analysis in R, Python, or a database
+    Convert {
+        /// Path to the archive zip file
+        archive: String,
+
+        /// Format to convert to
+        #[arg(long, value_enum)]
+        format: ConvertFormat,
+
+        /// Add each record's extension rows to it, as a column per extension
+        #[arg(long)]
+        flatten_extensions: bool,
+
+        /// Path of the converted file. Defaults to the archive's path with
+        /// the format's extension.
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Replace the output file if it already exists instead of adding a
+        /// numbered suffix
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Summarize a DarwinCore Archive: its core type, record count,
+    /// extensions, how often each column is filled in, date range, bounding
+    /// box, and most common taxa
+    Info {
+        /// Path to the archive zip file
+        archive: String,
+
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check a DarwinCore Archive for problems before publishing it, e.g.
+    /// missing files, duplicate IDs, or rows with the wrong number of fields
+    Validate {
+        /// Path to the archive zip file
+        archive: String,
+    },
+    /// Publish a DarwinCore Archive to Zenodo and mint a DOI for it. The DOI
+    /// is added to the archive's eml.xml.
+    Publish {
+        /// Path to the archive zip file
+        archive: String,
+
+        /// Zenodo personal access token with the deposit:write and
+        /// deposit:actions scopes
+        #[arg(long, env = "ZENODO_TOKEN", hide_env_values = true)]
+        token: String,
+
+        /// Publish to the Zenodo sandbox instead, which doesn't mint real
+        /// DOIs. Needs a sandbox token.
+        #[arg(long)]
+        sandbox: bool,
+    },
+    /// Run a read-only SELECT query against an archive database created by
+    /// the Chuck app and print the results as JSON
+    Sql {
+        /// Path to the DuckDB database file
+        database: String,
+
+        /// The query, e.g. "SELECT scientificName, COUNT(*) FROM occurrences GROUP BY 1"
+        query: String,
+
+        /// Maximum number of rows to print
+        #[arg(long, default_value_t = chuck_core::sql_console::DEFAULT_ROW_LIMIT)]
+        limit: usize,
     },
 }
 
 #[tokio::main(worker_threads = 5)]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    if let Err(e) = run().await {
+        // Known errors get their code so scripts can match on it
+        let report = chuck_core::error_code::ErrorReport::from_error(e.as_ref());
+        match report.code.as_str() {
+            chuck_core::error_code::UNKNOWN_ERROR_CODE => eprintln!("Error: {}", report.message),
+            code => eprintln!("Error ({code}): {}", report.message),
+        }
+        if let Some(guidance) = &report.guidance {
+            eprintln!("{guidance}");
+        }
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     let log_level = match cli.debug {
@@ -151,7 +488,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         })
         .init();
     match cli.command {
-        Commands::Auth { auth_command } => {
+        Commands::Auth { auth_command, profile } => {
+            chuck_core::auth::set_active_profile(profile.as_deref())?;
             match auth_command {
                 Some(AuthCommands::Clear) => {
                     match chuck_core::auth::StorageFactory::create() {
@@ -178,34 +516,137 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Commands::Obs {
+            annotation,
             created_d1,
             created_d2,
             d1,
             d2,
             dwc_extensions,
             fetch_media,
+            fields,
             file,
             format,
+            include_annotations,
+            json_records,
+            license,
+            max_requests,
+            media_license_policy,
+            name_template,
+            obscured,
+            overwrite,
             place_id,
+            profile,
+            project,
+            quality_grade,
+            record_links,
             taxon,
             update,
             url,
             user,
-        } => commands::fetch_observations(commands::FetchObservationsOptions {
-            file,
-            url,
+            validate_only,
+        } => {
+            chuck_core::api::retry::set_request_budget(max_requests);
+            commands::fetch_observations(commands::FetchObservationsOptions {
+                file,
+                name_template,
+                overwrite,
+                url,
+                taxon,
+                place_id,
+                user,
+                d1,
+                d2,
+                created_d1,
+                created_d2,
+                filters: chuck_core::api::params::ObservationFilters {
+                    project_id: project,
+                    quality_grade,
+                    term_id: annotation.map(|(term_id, _)| term_id),
+                    term_value_id: annotation.map(|(_, term_value_id)| term_value_id),
+                    license,
+                    obscured,
+                },
+                fetch_media,
+                media_license_policy: media_license_policy.into(),
+                format,
+                fields,
+                json_records,
+                dwc_extensions,
+                include_annotations,
+                record_links,
+                update,
+                validate_only,
+                profile,
+            }).await?
+        }
+        Commands::Ala { taxon, q, fq, d1, d2, file, overwrite } => {
+            commands::fetch_ala(commands::FetchAlaOptions {
+                taxon,
+                q,
+                filters: fq,
+                d1,
+                d2,
+                file,
+                overwrite,
+            }).await?
+        }
+        Commands::Obis {
             taxon,
-            place_id,
-            user,
+            taxon_id,
+            geometry,
+            min_depth,
+            max_depth,
             d1,
             d2,
-            created_d1,
-            created_d2,
-            fetch_media,
-            format,
-            dwc_extensions,
-            update,
-        }).await?,
+            include_measurements,
+            file,
+            overwrite,
+        } => {
+            commands::fetch_obis(commands::FetchObisOptions {
+                query: chuck_core::data_source::ObisQuery {
+                    scientific_name: taxon,
+                    taxon_id,
+                    geometry,
+                    start_depth: min_depth,
+                    end_depth: max_depth,
+                    start_date: d1,
+                    end_date: d2,
+                },
+                include_measurements,
+                file,
+                overwrite,
+            }).await?
+        }
+        Commands::Filter { archive, scientific_name, filter, d1, d2, output, overwrite } => {
+            commands::filter_archive(commands::FilterArchiveOptions {
+                archive,
+                scientific_name,
+                filters: filter,
+                d1,
+                d2,
+                output,
+                overwrite,
+            })?
+        }
+        Commands::Convert { archive, format, flatten_extensions, output, overwrite } => {
+            commands::convert(commands::ConvertArchiveOptions {
+                archive,
+                format: format.into(),
+                flatten_extensions,
+                output,
+                overwrite,
+            })?
+        }
+        Commands::Info { archive, json } => commands::info(&archive, json)?,
+        Commands::Validate { archive } => {
+            if !commands::validate(&archive)? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Publish { archive, token, sandbox } => {
+            commands::publish(&archive, &token, sandbox).await?
+        }
+        Commands::Sql { database, query, limit } => commands::sql(&database, &query, limit)?,
     }
     Ok(())
 }
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use chuck_core::darwin_core::{collect_taxon_ids, fetch_taxa_for_observations, Occurrence};
 use inaturalist::models::Observation;
 use super::ObservationWriter;
 use crate::progress::ProgressManager;
@@ -25,30 +28,41 @@ impl std::io::Write for CsvOutputStream {
 
 pub struct CsvOutput {
     writer: csv::Writer<CsvOutputStream>,
+    fields: Option<Vec<usize>>,
 }
 
 impl CsvOutput {
-    pub fn new(file: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
-        let header = [
-            "id",
-            "user_login",
-            "taxon_name",
-            "taxon_id",
-            "latitude",
-            "longitude",
-            "private_latitude",
-            "private_longitude",
-            "positional_accuracy",
-            "public_positional_accuracy",
-            "obscured",
-            "geoprivacy",
-            "taxon_geoprivacy",
-            "updated_at",
-            "captive",
-            "time_observed_at",
-            "observed_on_string",
-            "place_guess",
-        ];
+    /// Writes observations as CSV to `file`, or stdout. With `fields`, from
+    /// Occurrence::field_selection, each row is those columns of the
+    /// observation as a DarwinCore occurrence instead of the default
+    /// observation columns.
+    pub fn new(
+        file: Option<String>,
+        fields: Option<Vec<usize>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let header = match &fields {
+            Some(selection) => Occurrence::csv_headers_for(selection),
+            None => vec![
+                "id",
+                "user_login",
+                "taxon_name",
+                "taxon_id",
+                "latitude",
+                "longitude",
+                "private_latitude",
+                "private_longitude",
+                "positional_accuracy",
+                "public_positional_accuracy",
+                "obscured",
+                "geoprivacy",
+                "taxon_geoprivacy",
+                "updated_at",
+                "captive",
+                "time_observed_at",
+                "observed_on_string",
+                "place_guess",
+            ],
+        };
         let output_stream = if let Some(file_path) = file {
             // Create file and write header
             let mut wtr = csv::Writer::from_writer(CsvOutputStream::File(std::fs::File::create(&file_path)?));
@@ -70,6 +84,7 @@ impl CsvOutput {
 
         Ok(Self {
             writer: csv::Writer::from_writer(output_stream),
+            fields,
         })
     }
 }
@@ -122,8 +137,25 @@ impl ObservationWriter for CsvOutput {
         observations: &[Observation],
         progress_manager: &ProgressManager,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let taxa_hash = match self.fields {
+            // Taxa are needed for the higher classification
+            Some(_) => {
+                fetch_taxa_for_observations(
+                    &collect_taxon_ids(observations),
+                    None::<fn(usize, usize)>,
+                    None,
+                )
+                .await?
+            }
+            None => HashMap::new(),
+        };
         for obs in observations {
-            self.writer.write_record(observation_to_row(obs))?;
+            match &self.fields {
+                Some(selection) => self.writer.write_record(
+                    Occurrence::from((obs, &taxa_hash)).to_csv_record_for(selection),
+                )?,
+                None => self.writer.write_record(observation_to_row(obs))?,
+            }
 
             // If we're writing to stdout, just ignore the buffering and write each line as it gets processed
             if let CsvOutputStream::Stdout(_) = self.writer.get_ref() {
@@ -0,0 +1,120 @@
+use std::io::Write;
+
+use chuck_core::darwin_core::{collect_taxon_ids, fetch_taxa_for_observations, Occurrence};
+use inaturalist::models::Observation;
+use serde_json::{json, Map, Value};
+
+use super::ObservationWriter;
+use crate::progress::ProgressManager;
+
+/// DarwinCore terms included as properties of each feature, when they have a
+/// value
+pub const GEOJSON_PROPERTIES: &[&str] = &[
+    "occurrenceID",
+    "scientificName",
+    "vernacularName",
+    "taxonRank",
+    "eventDate",
+    "recordedBy",
+    "coordinateUncertaintyInMeters",
+    "informationWithheld",
+    "license",
+    "references",
+];
+
+/// Writes observations as a GeoJSON FeatureCollection of points. Observations
+/// without coordinates are included with a null geometry.
+pub struct GeoJsonOutput {
+    writer: Box<dyn Write + Send>,
+    written: usize,
+}
+
+impl GeoJsonOutput {
+    pub fn new(file: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut writer: Box<dyn Write + Send> = match file {
+            Some(file_path) => Box::new(std::io::BufWriter::new(std::fs::File::create(file_path)?)),
+            None => Box::new(std::io::stdout()),
+        };
+        writer.write_all(br#"{"type":"FeatureCollection","features":["#)?;
+        Ok(Self { writer, written: 0 })
+    }
+}
+
+fn occurrence_feature(occurrence: &Occurrence) -> Result<Value, serde_json::Error> {
+    let terms = serde_json::to_value(occurrence)?;
+    let properties: Map<String, Value> = GEOJSON_PROPERTIES
+        .iter()
+        .filter_map(|term| {
+            terms
+                .get(*term)
+                .filter(|value| !value.is_null() && value.as_str() != Some(""))
+                .map(|value| (term.to_string(), value.clone()))
+        })
+        .collect();
+    let geometry = match (occurrence.decimal_latitude, occurrence.decimal_longitude) {
+        (Some(lat), Some(lng)) => json!({ "type": "Point", "coordinates": [lng, lat] }),
+        _ => Value::Null,
+    };
+    Ok(json!({ "type": "Feature", "geometry": geometry, "properties": properties }))
+}
+
+impl ObservationWriter for GeoJsonOutput {
+    async fn write_observations(
+        &mut self,
+        observations: &[Observation],
+        progress_manager: &ProgressManager,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Taxa are needed for names like vernacularName
+        let taxa_hash = fetch_taxa_for_observations(
+            &collect_taxon_ids(observations),
+            None::<fn(usize, usize)>,
+            None,
+        )
+        .await?;
+        for obs in observations {
+            let feature = occurrence_feature(&Occurrence::from((obs, &taxa_hash)))?;
+            self.writer.write_all(if self.written == 0 { b"\n" } else { b",\n" })?;
+            serde_json::to_writer(&mut self.writer, &feature)?;
+            self.written += 1;
+            progress_manager.observations_bar.inc(1);
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    async fn finalize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.write_all(if self.written == 0 { b"]}\n" } else { b"\n]}\n" })?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_occurrence_feature() {
+        let occurrence = Occurrence {
+            occurrence_id: "https://www.inaturalist.org/observations/1".to_string(),
+            scientific_name: Some("Quercus agrifolia".to_string()),
+            decimal_latitude: Some(37.8),
+            decimal_longitude: Some(-122.5),
+            ..Default::default()
+        };
+
+        let feature = occurrence_feature(&occurrence).unwrap();
+
+        assert_eq!(feature["geometry"]["coordinates"], json!([-122.5, 37.8]));
+        assert_eq!(feature["properties"]["scientificName"], "Quercus agrifolia");
+        assert!(feature["properties"].get("recordedBy").is_none());
+        assert!(feature["properties"].get("decimalLatitude").is_none());
+    }
+
+    #[test]
+    fn test_occurrence_feature_without_coordinates() {
+        let feature = occurrence_feature(&Occurrence::default()).unwrap();
+
+        assert_eq!(feature["geometry"], Value::Null);
+    }
+}
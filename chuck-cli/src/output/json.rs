@@ -0,0 +1,127 @@
+use std::io::Write;
+
+use chuck_core::darwin_core::{collect_taxon_ids, fetch_taxa_for_observations, Occurrence};
+use inaturalist::models::Observation;
+use serde::Serialize;
+
+use super::ObservationWriter;
+use crate::progress::ProgressManager;
+use crate::JsonRecords;
+
+/// Writes observations as a JSON array, or as newline-delimited JSON with
+/// one record per line
+pub struct JsonOutput {
+    writer: Box<dyn Write + Send>,
+    ndjson: bool,
+    records: JsonRecords,
+    written: usize,
+}
+
+impl JsonOutput {
+    pub fn new(
+        file: Option<String>,
+        ndjson: bool,
+        records: JsonRecords,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let writer: Box<dyn Write + Send> = match file {
+            Some(file_path) => Box::new(std::io::BufWriter::new(std::fs::File::create(file_path)?)),
+            None => Box::new(std::io::stdout()),
+        };
+        Ok(Self { writer, ndjson, records, written: 0 })
+    }
+
+    fn write_record<T: Serialize>(&mut self, record: &T) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.ndjson {
+            self.writer.write_all(if self.written == 0 { b"[\n" } else { b",\n" })?;
+        }
+        serde_json::to_writer(&mut self.writer, record)?;
+        if self.ndjson {
+            self.writer.write_all(b"\n")?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+}
+
+impl ObservationWriter for JsonOutput {
+    async fn write_observations(
+        &mut self,
+        observations: &[Observation],
+        progress_manager: &ProgressManager,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.records {
+            JsonRecords::Raw => {
+                for obs in observations {
+                    self.write_record(obs)?;
+                    progress_manager.observations_bar.inc(1);
+                }
+            }
+            JsonRecords::Occurrence => {
+                // Taxa are needed for the higher classification
+                let taxa_hash = fetch_taxa_for_observations(
+                    &collect_taxon_ids(observations),
+                    None::<fn(usize, usize)>,
+                    None,
+                )
+                .await?;
+                for obs in observations {
+                    self.write_record(&Occurrence::from((obs, &taxa_hash)))?;
+                    progress_manager.observations_bar.inc(1);
+                }
+            }
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    async fn finalize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.ndjson {
+            self.writer.write_all(if self.written == 0 { b"[]\n" } else { b"\n]\n" })?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_obs(id: i32) -> Observation {
+        Observation { id: Some(id), ..Default::default() }
+    }
+
+    async fn write(ndjson: bool, observations: &[Observation]) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("observations.json");
+        let mut output =
+            JsonOutput::new(Some(path.to_string_lossy().into_owned()), ndjson, JsonRecords::Raw)
+                .unwrap();
+        let progress_manager = ProgressManager::new(false, false);
+        let (first, rest) = observations.split_at(observations.len().min(1));
+        output.write_observations(first, &progress_manager).await.unwrap();
+        output.write_observations(rest, &progress_manager).await.unwrap();
+        output.finalize().await.unwrap();
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_output_writes_an_array_across_pages() {
+        let json = write(false, &[make_obs(1), make_obs(2)]).await;
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1]["id"], 2);
+        assert_eq!(write(false, &[]).await, "[]\n");
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_output_writes_a_record_per_line() {
+        let ndjson = write(true, &[make_obs(1), make_obs(2)]).await;
+
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["id"], 2);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod csv;
+pub mod geojson;
+pub mod json;
 
 use inaturalist::models::Observation;
 use crate::progress::ProgressManager;
@@ -16,3 +18,5 @@ pub trait ObservationWriter: Send {
 }
 
 pub use csv::CsvOutput;
+pub use geojson::GeoJsonOutput;
+pub use json::JsonOutput;
@@ -1,7 +1,12 @@
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+
 use inaturalist::apis::{configuration::{Configuration, ApiKey}, observations_api, Error};
 use inaturalist::models::ObservationsResponse;
+use serde::{Deserialize, Serialize};
 use tokio::sync::{OnceCell, RwLock};
 
+use crate::api::retry::{send_with_retry, RetryPolicy};
 use crate::auth::{fetch_jwt, TokenStorage};
 
 const USER_AGENT: &str = concat!(
@@ -9,19 +14,133 @@ const USER_AGENT: &str = concat!(
     " (https://github.com/kueda/chuck)"
 );
 
+/// Set to 1 or true to trust the operating system's certificate store
+const USE_NATIVE_CERTS_ENV: &str = "CHUCK_USE_NATIVE_CERTS";
+
+/// Path to a PEM file of extra CA certificates to trust
+const CA_BUNDLE_ENV: &str = "CHUCK_CA_BUNDLE";
+
+/// Extra certificate authorities for HTTP clients to trust, for networks that
+/// intercept TLS and re-sign it with their own certificate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TlsSettings {
+    /// Trust certificates in the operating system's store in addition to the
+    /// built-in roots
+    pub use_native_certs: bool,
+    /// PEM file with one or more CA certificates to trust
+    pub ca_bundle_path: Option<PathBuf>,
+}
+
+impl TlsSettings {
+    /// Applies CHUCK_USE_NATIVE_CERTS and CHUCK_CA_BUNDLE, which take
+    /// precedence over saved settings
+    pub fn with_env_overrides(self) -> Self {
+        self.with_overrides(
+            std::env::var(USE_NATIVE_CERTS_ENV).ok(),
+            std::env::var(CA_BUNDLE_ENV).ok(),
+        )
+    }
+
+    fn with_overrides(mut self, use_native_certs: Option<String>, ca_bundle: Option<String>) -> Self {
+        match use_native_certs.as_deref().map(str::to_lowercase).as_deref() {
+            Some("1" | "true" | "yes") => self.use_native_certs = true,
+            Some("0" | "false" | "no") => self.use_native_certs = false,
+            _ => {}
+        }
+        if let Some(path) = ca_bundle.filter(|path| !path.is_empty()) {
+            self.ca_bundle_path = Some(PathBuf::from(path));
+        }
+        self
+    }
+
+    /// Certificates to add to a client's trusted roots. Certificates that
+    /// can't be loaded are logged and skipped so a bad bundle doesn't break
+    /// requests that would work without it.
+    fn certificates(&self) -> Vec<reqwest::Certificate> {
+        let mut certificates = Vec::new();
+        if self.use_native_certs {
+            let native = rustls_native_certs::load_native_certs();
+            for error in &native.errors {
+                log::warn!("Failed to load a certificate from the OS store: {error}");
+            }
+            certificates.extend(
+                native.certs.iter().filter_map(|cert| reqwest::Certificate::from_der(cert.as_ref()).ok())
+            );
+        }
+        if let Some(path) = &self.ca_bundle_path {
+            match std::fs::read(path).map_err(|e| e.to_string()).and_then(|pem| {
+                reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| e.to_string())
+            }) {
+                Ok(bundle) => certificates.extend(bundle),
+                Err(e) => log::warn!("Failed to load CA bundle {}: {e}", path.display()),
+            }
+        }
+        certificates
+    }
+}
+
+static TLS_SETTINGS: LazyLock<std::sync::RwLock<TlsSettings>> =
+    LazyLock::new(|| std::sync::RwLock::new(TlsSettings::default().with_env_overrides()));
+
+/// Sets the TLS settings and rebuilds the shared client and iNat API config
+/// with them, so requests from now on use them. Environment variables still
+/// take precedence.
+pub fn set_tls_settings(settings: TlsSettings) {
+    let settings = settings.with_env_overrides();
+    match TLS_SETTINGS.write() {
+        Ok(mut guard) => *guard = settings,
+        Err(poisoned) => *poisoned.into_inner() = settings,
+    }
+    let client = Arc::new(build_client());
+    match HTTP_CLIENT.write() {
+        Ok(mut guard) => *guard = client,
+        Err(poisoned) => *poisoned.into_inner() = client,
+    }
+    if let Some(config) = CONFIG.get() {
+        match config.try_write() {
+            Ok(mut config) => config.client = build_client(),
+            Err(_) => log::warn!("iNat API config is in use; it keeps its old TLS settings until a restart"),
+        }
+    }
+}
+
+/// Adds the certificates from the current TLS settings to a client builder.
+/// Use this for every HTTP client, including ones from other crates that
+/// re-export reqwest.
+pub fn with_tls_settings(mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    let settings = match TLS_SETTINGS.read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    for certificate in settings.certificates() {
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder
+}
+
+/// Client builder with the Chuck user agent and TLS settings, for requests
+/// that need options the shared client doesn't have
+pub fn client_builder() -> reqwest::ClientBuilder {
+    with_tls_settings(reqwest::Client::builder().user_agent(USER_AGENT))
+}
+
 fn build_client() -> reqwest::Client {
-    reqwest::Client::builder()
-        .user_agent(USER_AGENT)
+    client_builder()
         .build()
         .expect("failed to build reqwest client")
 }
 
-static HTTP_CLIENT: std::sync::LazyLock<reqwest::Client> =
-    std::sync::LazyLock::new(build_client);
+static HTTP_CLIENT: LazyLock<std::sync::RwLock<Arc<reqwest::Client>>> =
+    LazyLock::new(|| std::sync::RwLock::new(Arc::new(build_client())));
 
-/// Shared HTTP client with the Chuck user agent. Use this for all outbound requests.
-pub(crate) fn http_client() -> &'static reqwest::Client {
-    &HTTP_CLIENT
+/// Shared HTTP client with the Chuck user agent. Use this for all outbound
+/// requests, getting it again for each one so changed TLS settings apply.
+pub fn http_client() -> Arc<reqwest::Client> {
+    match HTTP_CLIENT.read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
 }
 
 // OnceCell ensures the config is initialized exactly once across the entire application,
@@ -108,56 +227,43 @@ pub async fn refresh_jwt_in_config(config: &RwLock<Configuration>) -> Result<(),
     Ok(())
 }
 
-/// Fetch observations with automatic retry on network errors and 401 auth refresh.
+/// Fetch observations with automatic retry on network errors, rate limiting,
+/// and server errors, plus 401 auth refresh.
 ///
-/// Retries up to 3 times with exponential backoff on connection-level errors
-/// (e.g., connection reset after sleep/resume or a transient network blip).
-/// On 401, refreshes the JWT token and retries once.
+/// Retries follow [`RetryPolicy::default`], e.g. after a connection reset on
+/// sleep/resume or a transient network blip. On 401, refreshes the JWT token
+/// and retries once.
 pub async fn fetch_observations_with_retry(
     config: &RwLock<Configuration>,
     params: observations_api::ObservationsGetParams,
 ) -> Result<ObservationsResponse, Error<observations_api::ObservationsGetError>> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
-
-    let mut attempt = 0;
-    loop {
-        attempt += 1;
-
-        let config_read = config.read().await;
-        let result = observations_api::observations_get(&config_read, params.clone()).await;
-        drop(config_read);
-
-        match result {
-            Ok(response) => return Ok(response),
-            Err(Error::ResponseError(ref response)) if response.status.as_u16() == 401 => {
-                eprintln!("Got 401 Unauthorized - attempting to refresh JWT token");
-                match refresh_jwt_in_config(config).await {
-                    Ok(_) => {
-                        eprintln!("Retrying request with refreshed token");
-                        let config_read = config.read().await;
-                        return observations_api::observations_get(&config_read, params).await;
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to refresh JWT token: {e}");
-                        eprintln!("Run `chuck auth` to re-authenticate");
-                        return Err(Error::ResponseError(response.clone()));
-                    }
+    let params = &params;
+    let fetch = || {
+        send_with_retry(RetryPolicy::default(), "Observation fetch", move || async move {
+            let config_read = config.read().await;
+            observations_api::observations_get(&config_read, params.clone()).await
+        })
+    };
+
+    match fetch().await {
+        Ok(response) => Ok(response),
+        Err(Error::ResponseError(response)) if response.status.as_u16() == 401 => {
+            eprintln!("Got 401 Unauthorized - attempting to refresh JWT token");
+            match refresh_jwt_in_config(config).await {
+                Ok(_) => {
+                    eprintln!("Retrying request with refreshed token");
+                    fetch().await.inspect_err(log_observation_fetch_error)
+                }
+                Err(e) => {
+                    eprintln!("Failed to refresh JWT token: {e}");
+                    eprintln!("Run `chuck auth` to re-authenticate");
+                    Err(Error::ResponseError(response))
                 }
             }
-            Err(Error::Reqwest(ref e)) if attempt < MAX_RETRIES => {
-                let delay = RETRY_BASE_DELAY * 2_u32.pow(attempt - 1);
-                log::warn!(
-                    "Observation fetch attempt {attempt}/{MAX_RETRIES} failed ({}), \
-                    retrying in {delay:?}...",
-                    describe_reqwest_error(e)
-                );
-                tokio::time::sleep(delay).await;
-            }
-            Err(ref e) => {
-                log_observation_fetch_error(e);
-                return Err(result.unwrap_err());
-            }
+        }
+        Err(e) => {
+            log_observation_fetch_error(&e);
+            Err(e)
         }
     }
 }
@@ -197,12 +303,49 @@ fn log_observation_fetch_error(e: &Error<observations_api::ObservationsGetError>
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use tokio::io::AsyncWriteExt;
     use tokio::net::TcpListener;
     use tokio::sync::RwLock;
 
+    #[test]
+    fn test_tls_settings_env_overrides() {
+        let saved = TlsSettings {
+            use_native_certs: true,
+            ca_bundle_path: Some(PathBuf::from("/saved.pem")),
+        };
+
+        assert_eq!(saved.clone().with_overrides(None, None), saved);
+        assert_eq!(
+            saved.clone().with_overrides(Some("false".to_string()), Some("/env.pem".to_string())),
+            TlsSettings { use_native_certs: false, ca_bundle_path: Some(PathBuf::from("/env.pem")) }
+        );
+        assert_eq!(
+            TlsSettings::default().with_overrides(Some("1".to_string()), Some(String::new())),
+            TlsSettings { use_native_certs: true, ca_bundle_path: None }
+        );
+    }
+
+    #[test]
+    fn test_tls_settings_skips_unreadable_ca_bundle() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("bundle.pem");
+        std::fs::write(&path, "not a certificate").unwrap();
+        let settings = TlsSettings { use_native_certs: false, ca_bundle_path: Some(path) };
+
+        assert!(settings.certificates().is_empty());
+        assert!(with_tls_settings(reqwest::Client::builder()).build().is_ok());
+    }
+
+    #[test]
+    fn test_set_tls_settings_rebuilds_shared_client() {
+        let before = http_client();
+
+        set_tls_settings(TlsSettings::default());
+
+        assert!(!Arc::ptr_eq(&before, &http_client()));
+    }
+
     #[tokio::test]
     async fn test_fetch_observations_retries_on_connection_error() {
         let call_count = Arc::new(AtomicUsize::new(0));
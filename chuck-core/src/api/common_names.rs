@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::api::client::http_client;
+use crate::api::rate_limiter::get_rate_limiter;
+
+/// Ranks of the taxa fetched when looking up a genus's names in one request
+const SPECIES_RANKS: &str = "species,hybrid,subspecies,variety,form";
+
+/// Most taxa the API returns per request
+const PER_PAGE: &str = "200";
+
+/// Looks up iNat's preferred common name for each scientific name, returning
+/// a map from scientific name to common name. Names with no exact match or no
+/// common name are left out. Requests are rate limited to about one a second,
+/// so names are looked up a genus at a time, and only names the genus's
+/// results don't cover are looked up on their own. `on_progress` is called
+/// with (done, total) after each request. Setting `cancel` stops the lookups
+/// with an error. `base_path` is the API root, e.g.
+/// https://api.inaturalist.org/v1
+pub async fn fetch_common_names(
+    base_path: &str,
+    scientific_names: &[String],
+    locale: Option<&str>,
+    cancel: Option<Arc<AtomicBool>>,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+    let cancelled = || cancel.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed));
+    let mut genera: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for name in scientific_names {
+        let genus = name.split_whitespace().next().unwrap_or(name);
+        genera.entry(genus.to_lowercase()).or_default().push(name);
+    }
+
+    let mut common_names = HashMap::new();
+    let mut done = 0;
+    for (genus, mut names) in genera {
+        if names.len() > 1 {
+            if cancelled() {
+                return Err("Common name lookup cancelled".into());
+            }
+            let taxa = search_taxa(base_path, &genus, Some(SPECIES_RANKS), locale).await?;
+            names.retain(|name| {
+                let Some(taxon) = find_taxon(&taxa, name) else {
+                    return true;
+                };
+                if let Some(common_name) = preferred_common_name(taxon) {
+                    common_names.insert(name.to_string(), common_name.to_string());
+                }
+                done += 1;
+                false
+            });
+            on_progress(done, scientific_names.len());
+        }
+        for name in names {
+            if cancelled() {
+                return Err("Common name lookup cancelled".into());
+            }
+            let taxa = search_taxa(base_path, name, None, locale).await?;
+            if let Some(common_name) = find_taxon(&taxa, name).and_then(preferred_common_name) {
+                common_names.insert(name.clone(), common_name.to_string());
+            }
+            done += 1;
+            on_progress(done, scientific_names.len());
+        }
+    }
+
+    Ok(common_names)
+}
+
+/// Taxa matching `q`, optionally only of some ranks
+async fn search_taxa(
+    base_path: &str,
+    q: &str,
+    ranks: Option<&str>,
+    locale: Option<&str>,
+) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+    get_rate_limiter().await.wait_for_next_request().await;
+    let mut query = vec![("q", q), ("per_page", PER_PAGE)];
+    if let Some(ranks) = ranks {
+        query.push(("rank", ranks));
+    }
+    if let Some(locale) = locale {
+        query.push(("locale", locale));
+    }
+    let mut response: Value = http_client()
+        .get(format!("{base_path}/taxa"))
+        .query(&query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    match response["results"].take() {
+        Value::Array(taxa) => Ok(taxa),
+        _ => Ok(vec![]),
+    }
+}
+
+/// The taxon in `taxa` named exactly `name`, ignoring case
+fn find_taxon<'a>(taxa: &'a [Value], name: &str) -> Option<&'a Value> {
+    taxa.iter()
+        .find(|taxon| taxon["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+}
+
+fn preferred_common_name(taxon: &Value) -> Option<&str> {
+    taxon["preferred_common_name"].as_str().filter(|common_name| !common_name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_fetch_common_names_uses_exact_matches() {
+        let server = MockServer::start();
+        let genus = server.mock(|when, then| {
+            when.method(GET).path("/taxa").query_param("q", "quercus").query_param("rank", SPECIES_RANKS);
+            then.status(200).json_body(serde_json::json!({
+                "results": [
+                    { "id": 1, "name": "Quercus agrifolia oxyadenia", "preferred_common_name": "Oxyadenia oak" },
+                    { "id": 49005, "name": "Quercus agrifolia", "preferred_common_name": "coast live oak" }
+                ]
+            }));
+        });
+        let single = server.mock(|when, then| {
+            when.method(GET).path("/taxa").query_param("q", "Quercus nonexistens");
+            then.status(200).json_body(serde_json::json!({
+                "results": [{ "id": 47851, "name": "Quercus", "preferred_common_name": "oaks" }]
+            }));
+        });
+
+        let names = vec!["Quercus agrifolia".to_string(), "Quercus nonexistens".to_string()];
+        let mut progress = vec![];
+        let common_names = fetch_common_names(
+            &server.base_url(),
+            &names,
+            None,
+            None,
+            |done, total| progress.push((done, total)),
+        ).await.unwrap();
+
+        assert_eq!(common_names.len(), 1);
+        assert_eq!(common_names["Quercus agrifolia"], "coast live oak");
+        assert_eq!(progress, vec![(1, 2), (2, 2)]);
+        // Names the genus lookup found aren't looked up again
+        genus.assert();
+        single.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_common_names_stops_when_cancelled() {
+        let server = MockServer::start();
+        let taxa = server.mock(|when, then| {
+            when.method(GET).path("/taxa");
+            then.status(200).json_body(serde_json::json!({ "results": [] }));
+        });
+
+        let names = vec!["Quercus agrifolia".to_string()];
+        let cancel = Arc::new(AtomicBool::new(true));
+        let result = fetch_common_names(&server.base_url(), &names, None, Some(cancel), |_, _| {}).await;
+
+        assert!(result.is_err());
+        taxa.assert_hits(0);
+    }
+}
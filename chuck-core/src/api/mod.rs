@@ -1,3 +1,7 @@
 pub mod client;
+pub mod common_names;
 pub mod params;
 pub mod rate_limiter;
+pub mod retry;
+pub mod taxon_suggestions;
+pub mod validation;
@@ -1,4 +1,5 @@
 use inaturalist::apis::observations_api;
+use serde::Deserialize;
 
 pub const PER_PAGE: u32 = 200;
 
@@ -494,6 +495,72 @@ pub fn build_params(
     params
 }
 
+/// Observation filters beyond the ones build_params takes. Values are passed
+/// through to the API as is, e.g. quality_grade can be "research,needs_id".
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ObservationFilters {
+    /// Project ID or slug
+    pub project_id: Option<String>,
+    pub quality_grade: Option<String>,
+    /// Controlled term ID of an annotation, e.g. 1 for Life Stage
+    pub term_id: Option<i32>,
+    /// Controlled value ID of an annotation, e.g. 2 for Adult. Only applies
+    /// along with term_id.
+    pub term_value_id: Option<i32>,
+    /// Observation license codes, e.g. "cc-by,cc0"
+    pub license: Option<String>,
+    /// true for observations with obscured or private coordinates, false for
+    /// observations with neither
+    pub obscured: Option<bool>,
+}
+
+/// Parses an annotation filter as "term/value" controlled term and value IDs,
+/// e.g. "1/2" for Life Stage: Adult
+pub fn parse_annotation(value: &str) -> Result<(i32, i32), String> {
+    let invalid = || {
+        format!("annotation \"{value}\" should be a term ID and value ID like 1/2")
+    };
+    let (term, term_value) = value.split_once('/').ok_or_else(invalid)?;
+    let term = term.trim().parse().map_err(|_| invalid())?;
+    let term_value = term_value.trim().parse().map_err(|_| invalid())?;
+    Ok((term, term_value))
+}
+
+/// Sets the API params for the filters that have values
+pub fn apply_filters(
+    params: &mut observations_api::ObservationsGetParams,
+    filters: &ObservationFilters,
+) {
+    let split = |value: &str| -> Vec<String> {
+        value
+            .split(',')
+            .map(|part| part.trim().to_string())
+            .filter(|part| !part.is_empty())
+            .collect()
+    };
+    if let Some(ref project_id) = filters.project_id {
+        params.project_id = Some(split(project_id));
+    }
+    if let Some(ref quality_grade) = filters.quality_grade {
+        params.quality_grade = Some(split(quality_grade).join(","));
+    }
+    if let Some(term_id) = filters.term_id {
+        params.term_id = Some(vec![term_id]);
+        params.term_value_id = filters.term_value_id.map(|value_id| vec![value_id]);
+    }
+    if let Some(ref license) = filters.license {
+        params.license = Some(split(license));
+    }
+    match filters.obscured {
+        Some(true) => {
+            params.obscuration = Some(vec!["obscured".to_string(), "private".to_string()]);
+        }
+        Some(false) => params.obscuration = Some(vec!["none".to_string()]),
+        None => {}
+    }
+}
+
 /// Extract human-readable criteria from ObservationsGetParams
 /// Note: We manually check each field because ObservationsGetParams doesn't implement
 /// reflection by default. We could use serde to serialize to a map and iterate
@@ -526,6 +593,11 @@ pub fn extract_criteria(params: &observations_api::ObservationsGetParams) -> Vec
             criteria.push(format!("place_id: {}", values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")));
         }
     }
+    if let Some(ref values) = params.project_id {
+        if !values.is_empty() {
+            criteria.push(format!("project_id: {}", values.join(", ")));
+        }
+    }
     if let Some(ref value) = params.lat {
         criteria.push(format!("lat: {value}"));
     }
@@ -544,6 +616,28 @@ pub fn extract_criteria(params: &observations_api::ObservationsGetParams) -> Vec
     if let Some(ref value) = params.quality_grade {
         criteria.push(format!("quality_grade: {value}"));
     }
+    if let Some(ref values) = params.term_id {
+        if !values.is_empty() {
+            let terms = values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+            match params.term_value_id.as_ref().filter(|values| !values.is_empty()) {
+                Some(value_ids) => criteria.push(format!(
+                    "annotation: term_id {terms}, term_value_id {}",
+                    value_ids.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+                )),
+                None => criteria.push(format!("annotation: term_id {terms}")),
+            }
+        }
+    }
+    if let Some(ref values) = params.license {
+        if !values.is_empty() {
+            criteria.push(format!("license: {}", values.join(", ")));
+        }
+    }
+    if let Some(ref values) = params.obscuration {
+        if !values.is_empty() {
+            criteria.push(format!("obscuration: {}", values.join(", ")));
+        }
+    }
     if let Some(ref value) = params.photos {
         criteria.push(format!("photos: {value}"));
     }
@@ -797,4 +891,68 @@ mod tests {
             );
         }
     }
+
+    mod apply_filters {
+        use super::*;
+
+        #[test]
+        fn test_sets_filters_that_have_values() {
+            let mut p = build_params(None, None, None, None, None, None, None);
+            apply_filters(&mut p, &ObservationFilters {
+                project_id: Some("city-nature-challenge-2024, 1234".to_string()),
+                quality_grade: Some("research,needs_id".to_string()),
+                term_id: Some(1),
+                term_value_id: Some(2),
+                license: Some("cc-by,cc0".to_string()),
+                obscured: Some(true),
+            });
+            assert_eq!(
+                serialize_params(&p),
+                "project_id=city-nature-challenge-2024,1234&license=cc-by,cc0\
+                 &obscuration=obscured,private&term_id=1&term_value_id=2\
+                 &quality_grade=research,needs_id"
+            );
+        }
+
+        #[test]
+        fn test_default_filters_change_nothing() {
+            let mut p = build_params(Some("47790".to_string()), None, None, None, None, None, None);
+            apply_filters(&mut p, &ObservationFilters::default());
+            assert_eq!(serialize_params(&p), "taxon_id=47790");
+        }
+
+        #[test]
+        fn test_obscured_false_means_no_obscuration() {
+            let mut p = build_params(None, None, None, None, None, None, None);
+            apply_filters(&mut p, &ObservationFilters { obscured: Some(false), ..Default::default() });
+            assert_eq!(p.obscuration, Some(vec!["none".to_string()]));
+        }
+
+        #[test]
+        fn test_parse_annotation() {
+            assert_eq!(parse_annotation("1/2"), Ok((1, 2)));
+            assert_eq!(parse_annotation(" 9 / 10 "), Ok((9, 10)));
+            assert!(parse_annotation("1").is_err());
+            assert!(parse_annotation("Life Stage/Adult").is_err());
+        }
+
+        #[test]
+        fn test_extract_criteria_includes_filters() {
+            let mut p = build_params(None, None, None, None, None, None, None);
+            apply_filters(&mut p, &ObservationFilters {
+                project_id: Some("1234".to_string()),
+                term_id: Some(1),
+                term_value_id: Some(2),
+                license: Some("cc0".to_string()),
+                obscured: Some(false),
+                ..Default::default()
+            });
+            assert_eq!(extract_criteria(&p), vec![
+                "project_id: 1234",
+                "annotation: term_id 1, term_value_id 2",
+                "license: cc0",
+                "obscuration: none",
+            ]);
+        }
+    }
 }
@@ -1,10 +1,12 @@
 use tokio::sync::{OnceCell, Mutex};
-use tokio::time::{interval, Duration, Interval};
+use tokio::time::{interval, sleep_until, Duration, Instant, Interval};
 
 /// A centralized rate limiter for coordinating all iNaturalist API requests
-/// Ensures we never exceed the 1 request per second rate limit
+/// Ensures we never exceed the 1 request per second rate limit, which also
+/// keeps us under iNat's cap of 60 requests per minute
 pub struct RateLimiter {
     interval: Mutex<Interval>,
+    paused_until: Mutex<Option<Instant>>,
 }
 
 impl RateLimiter {
@@ -13,6 +15,7 @@ impl RateLimiter {
         let interval = interval(Duration::from_millis(1100));
         Self {
             interval: Mutex::new(interval),
+            paused_until: Mutex::new(None),
         }
     }
 
@@ -20,8 +23,22 @@ impl RateLimiter {
     /// This method coordinates all API requests across the application
     pub async fn wait_for_next_request(&self) {
         let mut interval_guard = self.interval.lock().await;
+        let paused_until = *self.paused_until.lock().await;
+        if let Some(until) = paused_until {
+            sleep_until(until).await;
+        }
         interval_guard.tick().await;
     }
+
+    /// Hold off all requests for `delay`, e.g. after the API responds with
+    /// HTTP 429. Overlapping pauses keep the later end time.
+    pub async fn back_off(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut paused_until = self.paused_until.lock().await;
+        if paused_until.is_none_or(|current| current < until) {
+            *paused_until = Some(until);
+        }
+    }
 }
 
 // Global rate limiter instance shared across the entire application
@@ -35,7 +52,6 @@ pub async fn get_rate_limiter() -> &'static RateLimiter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::time::Instant;
 
     #[tokio::test]
     async fn test_rate_limiter_enforces_timing() {
@@ -59,6 +75,19 @@ mod tests {
         assert!(first_elapsed < Duration::from_millis(100));
     }
 
+    #[tokio::test]
+    async fn test_back_off_delays_the_next_request() {
+        let rate_limiter = RateLimiter::new();
+        rate_limiter.wait_for_next_request().await;
+
+        let start = Instant::now();
+        rate_limiter.back_off(Duration::from_millis(1500)).await;
+        rate_limiter.back_off(Duration::from_millis(100)).await;
+        rate_limiter.wait_for_next_request().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(1500));
+    }
+
     #[tokio::test]
     async fn test_multiple_callers_coordinate() {
         use std::sync::Arc;
@@ -0,0 +1,199 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use inaturalist::apis::Error;
+use rand::Rng;
+use tokio::time::Duration;
+
+use crate::api::rate_limiter::get_rate_limiter;
+
+/// How many times to try a request and how long to wait between tries
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry. Later retries double it.
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, before jitter
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff for the given retry (1 for the first), plus up to
+    /// 50% random jitter so concurrent callers don't retry in lockstep. Rate
+    /// limited requests wait four times as long since iNat wants callers to
+    /// slow down, not just try again.
+    pub fn delay(&self, retry: u32, rate_limited: bool) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let mut delay = self.base_delay.saturating_mul(2_u32.pow(exponent));
+        if rate_limited {
+            delay = delay.saturating_mul(4);
+        }
+        let delay = delay.min(self.max_delay);
+        let jitter = rand::thread_rng().gen_range(0.0..0.5);
+        delay + delay.mul_f64(jitter)
+    }
+}
+
+/// Whether a failed request might succeed if tried again: connection errors,
+/// timeouts, HTTP 429, and server errors
+pub fn is_retryable<T>(error: &Error<T>) -> bool {
+    match error {
+        Error::Reqwest(_) => true,
+        Error::ResponseError(response) => {
+            response.status.as_u16() == 429 || response.status.is_server_error()
+        }
+        _ => false,
+    }
+}
+
+fn is_rate_limited<T>(error: &Error<T>) -> bool {
+    matches!(error, Error::ResponseError(response) if response.status.as_u16() == 429)
+}
+
+static REQUEST_BUDGET: AtomicUsize = AtomicUsize::new(0);
+static REQUESTS_MADE: AtomicUsize = AtomicUsize::new(0);
+
+/// Caps the total number of API requests, including retries, made through
+/// [`send_with_retry`] from now on. Requests past the budget fail without
+/// being sent. `None` removes the cap.
+pub fn set_request_budget(budget: Option<usize>) {
+    REQUESTS_MADE.store(0, Ordering::SeqCst);
+    REQUEST_BUDGET.store(budget.unwrap_or(0), Ordering::SeqCst);
+}
+
+/// Number of requests counted against the budget since it was last set
+pub fn requests_made() -> usize {
+    REQUESTS_MADE.load(Ordering::SeqCst)
+}
+
+fn take_from_budget() -> Result<(), std::io::Error> {
+    let made = REQUESTS_MADE.fetch_add(1, Ordering::SeqCst);
+    let budget = REQUEST_BUDGET.load(Ordering::SeqCst);
+    if budget > 0 && made >= budget {
+        return Err(std::io::Error::other(format!(
+            "Request budget of {budget} iNaturalist API requests exhausted"
+        )));
+    }
+    Ok(())
+}
+
+/// Sends a request, retrying connection errors, HTTP 429, and 5xx responses
+/// with jittered exponential backoff. A 429 also pauses the shared rate
+/// limiter so every other caller backs off too. `description` names the
+/// request in log messages, e.g. "Taxa fetch".
+///
+/// Callers still wait on the rate limiter before the first attempt; retries
+/// are already spaced further apart than the rate limit.
+pub async fn send_with_retry<T, E, F, Fut>(
+    policy: RetryPolicy,
+    description: &str,
+    mut request: F,
+) -> Result<T, Error<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error<E>>>,
+    E: std::fmt::Debug,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        take_from_budget().map_err(Error::Io)?;
+        match request().await {
+            Ok(response) => return Ok(response),
+            Err(e) if attempt < policy.max_attempts && is_retryable(&e) => {
+                let rate_limited = is_rate_limited(&e);
+                let delay = policy.delay(attempt, rate_limited);
+                log::warn!(
+                    "{description} attempt {attempt}/{} failed ({}), retrying in {delay:?}...",
+                    policy.max_attempts,
+                    describe_error(&e)
+                );
+                if rate_limited {
+                    get_rate_limiter().await.back_off(delay).await;
+                }
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn describe_error<E: std::fmt::Debug>(e: &Error<E>) -> String {
+    match e {
+        Error::Reqwest(e) if e.is_timeout() => format!("request timed out: {e}"),
+        Error::Reqwest(e) if e.is_connect() => format!("connection error: {e}"),
+        Error::ResponseError(response) => format!("HTTP {}", response.status),
+        _ => format!("{e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use inaturalist::apis::ResponseContent;
+
+    fn response_error(status: u16) -> Error<()> {
+        Error::ResponseError(ResponseContent {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            content: String::new(),
+            entity: None,
+        })
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&response_error(429)));
+        assert!(is_retryable(&response_error(503)));
+        assert!(!is_retryable(&response_error(404)));
+        assert!(!is_retryable(&response_error(422)));
+    }
+
+    #[test]
+    fn test_delay_backs_off_with_bounded_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+        };
+
+        for _ in 0..20 {
+            let first = policy.delay(1, false);
+            assert!(first >= Duration::from_secs(1) && first < Duration::from_millis(1500));
+            let third = policy.delay(3, false);
+            assert!(third >= Duration::from_secs(4) && third < Duration::from_secs(6));
+            let capped = policy.delay(3, true);
+            assert!(capped >= Duration::from_secs(10) && capped < Duration::from_secs(15));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_stops_on_client_errors() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+
+        let mut calls = 0;
+        let result: Result<(), Error<()>> = send_with_retry(policy, "Test", || {
+            calls += 1;
+            let result = if calls == 1 { Err(response_error(502)) } else { Err(response_error(404)) };
+            async move { result }
+        })
+        .await;
+
+        assert!(matches!(result, Err(Error::ResponseError(r)) if r.status.as_u16() == 404));
+        assert_eq!(calls, 2);
+    }
+}
@@ -0,0 +1,126 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::api::client::http_client;
+
+/// A candidate taxon for a name someone typed, so they can pick the one they
+/// meant instead of leaving it to the API's name matching
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaxonSuggestion {
+    pub id: i64,
+    pub name: String,
+    pub rank: Option<String>,
+    pub preferred_common_name: Option<String>,
+    pub observations_count: i64,
+}
+
+impl TaxonSuggestion {
+    /// Whether the query is exactly this taxon's scientific or common name,
+    /// ignoring case
+    pub fn matches_exactly(&self, query: &str) -> bool {
+        let query = query.trim();
+        self.name.eq_ignore_ascii_case(query)
+            || self
+                .preferred_common_name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(query))
+    }
+}
+
+impl std::fmt::Display for TaxonSuggestion {
+    /// e.g. "Danaus plexippus (monarch), species, 12345 observations"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(common_name) = &self.preferred_common_name {
+            write!(f, " ({common_name})")?;
+        }
+        if let Some(rank) = &self.rank {
+            write!(f, ", {rank}")?;
+        }
+        write!(f, ", {} observations", self.observations_count)
+    }
+}
+
+/// Fetches taxa matching a partial scientific or common name from iNat's
+/// autocomplete endpoint, best matches first. `base_path` is the API root,
+/// e.g. https://api.inaturalist.org/v1
+pub async fn fetch_taxon_suggestions(
+    base_path: &str,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<TaxonSuggestion>, Box<dyn std::error::Error>> {
+    let per_page = limit.to_string();
+    let response: Value = http_client()
+        .get(format!("{base_path}/taxa/autocomplete"))
+        .query(&[("q", query.trim()), ("per_page", per_page.as_str())])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(parse_taxon_suggestions(&response))
+}
+
+fn parse_taxon_suggestions(response: &Value) -> Vec<TaxonSuggestion> {
+    response["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|taxon| {
+            Some(TaxonSuggestion {
+                id: taxon["id"].as_i64()?,
+                name: taxon["name"].as_str()?.to_string(),
+                rank: taxon["rank"].as_str().map(String::from),
+                preferred_common_name: taxon["preferred_common_name"]
+                    .as_str()
+                    .filter(|name| !name.is_empty())
+                    .map(String::from),
+                observations_count: taxon["observations_count"].as_i64().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_fetch_taxon_suggestions() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/taxa/autocomplete")
+                .query_param("q", "monarch")
+                .query_param("per_page", "5");
+            then.status(200).json_body(serde_json::json!({
+                "results": [
+                    {
+                        "id": 48662,
+                        "name": "Danaus plexippus",
+                        "rank": "species",
+                        "preferred_common_name": "Monarch",
+                        "observations_count": 412000
+                    },
+                    { "id": 1, "name": "Monarcha", "rank": "genus", "observations_count": 900 },
+                    { "name": "No ID" }
+                ]
+            }));
+        });
+
+        let suggestions = fetch_taxon_suggestions(&server.base_url(), " monarch ", 5)
+            .await
+            .unwrap();
+
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].id, 48662);
+        assert!(suggestions[0].matches_exactly("monarch"));
+        assert!(!suggestions[1].matches_exactly("monarch"));
+        assert_eq!(
+            suggestions[0].to_string(),
+            "Danaus plexippus (Monarch), species, 412000 observations"
+        );
+        assert_eq!(suggestions[1].to_string(), "Monarcha, genus, 900 observations");
+    }
+}
@@ -0,0 +1,274 @@
+use inaturalist::apis::observations_api::ObservationsGetParams;
+use serde_json::Value;
+
+use crate::api::client::http_client;
+
+const QUALITY_GRADES: &[&str] = &["research", "needs_id", "casual"];
+
+/// Observation license codes the API accepts
+const LICENSES: &[&str] = &[
+    "cc-by", "cc-by-nc", "cc-by-nd", "cc-by-sa", "cc-by-nc-nd", "cc-by-nc-sa", "cc0",
+];
+
+/// Checks observation search params that can be validated without talking to
+/// the API, returning a human-readable message for each problem found
+pub fn validate_params(params: &ObservationsGetParams) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let d1 = check_date("d1", params.d1.as_deref(), &mut errors);
+    let d2 = check_date("d2", params.d2.as_deref(), &mut errors);
+    if let (Some(d1), Some(d2)) = (d1, d2) {
+        if d1 > d2 {
+            errors.push(format!("d1 ({d1}) is after d2 ({d2}); swap them or widen the range"));
+        }
+    }
+
+    let created_d1 = check_date("created_d1", params.created_d1.as_deref(), &mut errors);
+    let created_d2 = check_date("created_d2", params.created_d2.as_deref(), &mut errors);
+    if let (Some(d1), Some(d2)) = (created_d1, created_d2) {
+        if d1 > d2 {
+            errors.push(format!(
+                "created_d1 ({d1}) is after created_d2 ({d2}); swap them or widen the range"
+            ));
+        }
+    }
+
+    for grade in params.quality_grade.iter().flat_map(|value| value.split(',')) {
+        if !QUALITY_GRADES.contains(&grade) {
+            errors.push(format!(
+                "quality_grade \"{grade}\" is not one of {}",
+                QUALITY_GRADES.join(", ")
+            ));
+        }
+    }
+
+    for license in params.license.iter().flatten() {
+        if !LICENSES.contains(&license.to_lowercase().as_str()) {
+            errors.push(format!(
+                "license \"{license}\" is not one of {}",
+                LICENSES.join(", ")
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Parses a date param as YYYY-MM-DD or an RFC 3339 datetime, recording an
+/// error and returning None if it's neither
+fn check_date(
+    name: &str,
+    value: Option<&str>,
+    errors: &mut Vec<String>,
+) -> Option<chrono::NaiveDate> {
+    let value = value?;
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.date_naive());
+    }
+    errors.push(format!(
+        "{name} \"{value}\" is not a valid date; use YYYY-MM-DD, e.g. 2020-01-31"
+    ));
+    None
+}
+
+/// Checks that taxa and places referenced by the params exist, returning a
+/// message for each one that doesn't. `base_path` is the API root, e.g.
+/// https://api.inaturalist.org/v1
+pub async fn validate_params_remote(
+    base_path: &str,
+    params: &ObservationsGetParams,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut errors = Vec::new();
+
+    if let Some(taxon_ids) = params.taxon_id.as_ref().filter(|ids| !ids.is_empty()) {
+        let url = format!("{base_path}/taxa/{}", taxon_ids.join(","));
+        let found = fetch_result_ids(&url).await?;
+        for id in taxon_ids {
+            if !found.iter().any(|found_id| found_id.to_string() == *id) {
+                errors.push(format!(
+                    "No taxon with ID {id}; look it up at https://www.inaturalist.org/taxa"
+                ));
+            }
+        }
+    }
+
+    for name in params.taxon_name.iter().flatten() {
+        let response: Value = http_client()
+            .get(format!("{base_path}/taxa"))
+            .query(&[("q", name.as_str()), ("per_page", "10")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let results = response["results"].as_array().cloned().unwrap_or_default();
+        let matches_name = |taxon: &Value| {
+            ["name", "preferred_common_name", "matched_term"].iter().any(|key| {
+                taxon[*key].as_str().is_some_and(|n| n.eq_ignore_ascii_case(name))
+            })
+        };
+        if results.iter().any(matches_name) {
+            continue;
+        }
+        let suggestions: Vec<String> = results
+            .iter()
+            .filter_map(|taxon| {
+                let id = taxon["id"].as_i64()?;
+                let name = taxon["name"].as_str()?;
+                Some(format!("{name} ({id})"))
+            })
+            .take(5)
+            .collect();
+        if suggestions.is_empty() {
+            errors.push(format!("No taxon named \"{name}\"; try a scientific name or taxon ID"));
+        } else {
+            errors.push(format!(
+                "No taxon named \"{name}\"; did you mean {}? Pass the taxon ID to be exact",
+                suggestions.join(", ")
+            ));
+        }
+    }
+
+    if let Some(place_ids) = params.place_id.as_ref().filter(|ids| !ids.is_empty()) {
+        let ids: Vec<String> = place_ids.iter().map(|id| id.to_string()).collect();
+        let url = format!("{base_path}/places/{}", ids.join(","));
+        let found = fetch_result_ids(&url).await?;
+        for id in place_ids {
+            if !found.contains(&i64::from(*id)) {
+                errors.push(format!(
+                    "No place with ID {id}; look it up at https://www.inaturalist.org/places"
+                ));
+            }
+        }
+    }
+
+    Ok(errors)
+}
+
+/// Fetches an API endpoint and returns the IDs of its results. A 404 means
+/// none of the requested records exist.
+async fn fetch_result_ids(url: &str) -> Result<Vec<i64>, Box<dyn std::error::Error>> {
+    let response = http_client().get(url).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(vec![]);
+    }
+    let body: Value = response.error_for_status()?.json().await?;
+    Ok(body["results"]
+        .as_array()
+        .map(|results| results.iter().filter_map(|r| r["id"].as_i64()).collect())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::params::build_params;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn test_validate_params_accepts_valid_dates() {
+        let params = build_params(
+            None,
+            None,
+            None,
+            Some("2020-01-01".to_string()),
+            Some("2020-12-31T23:59:59-08:00".to_string()),
+            None,
+            None,
+        );
+        assert!(validate_params(&params).is_empty());
+    }
+
+    #[test]
+    fn test_validate_params_rejects_malformed_dates() {
+        let params = build_params(
+            None,
+            None,
+            None,
+            Some("01/31/2020".to_string()),
+            None,
+            None,
+            Some("2020-13-01".to_string()),
+        );
+        let errors = validate_params(&params);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("d1 \"01/31/2020\""));
+        assert!(errors[1].starts_with("created_d2 \"2020-13-01\""));
+    }
+
+    #[test]
+    fn test_validate_params_rejects_unknown_quality_grades_and_licenses() {
+        let mut params = build_params(None, None, None, None, None, None, None);
+        crate::api::params::apply_filters(&mut params, &crate::api::params::ObservationFilters {
+            quality_grade: Some("research,verifiable".to_string()),
+            license: Some("CC-BY,public-domain".to_string()),
+            ..Default::default()
+        });
+        let errors = validate_params(&params);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("quality_grade \"verifiable\""));
+        assert!(errors[1].starts_with("license \"public-domain\""));
+    }
+
+    #[test]
+    fn test_validate_params_rejects_reversed_range() {
+        let params = build_params(
+            None,
+            None,
+            None,
+            Some("2021-01-01".to_string()),
+            Some("2020-01-01".to_string()),
+            None,
+            None,
+        );
+        let errors = validate_params(&params);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("d1 (2021-01-01) is after d2 (2020-01-01)"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_params_remote_reports_missing_place_and_taxon() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/taxa/47790");
+            then.status(200).json_body(serde_json::json!({
+                "results": [{ "id": 47790, "name": "Fungi" }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/places/999999");
+            then.status(404);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/taxa").query_param("q", "quercus agrifolia");
+            then.status(200).json_body(serde_json::json!({
+                "results": [{ "id": 49005, "name": "Quercus agrifolia" }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/taxa").query_param("q", "Quercus agrifola");
+            then.status(200).json_body(serde_json::json!({
+                "results": [{ "id": 49005, "name": "Quercus agrifolia" }]
+            }));
+        });
+
+        let mut params = build_params(Some("47790".to_string()), Some(999999), None, None, None, None, None);
+        let errors = validate_params_remote(&server.base_url(), &params).await.unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("No place with ID 999999"));
+
+        params.taxon_id = None;
+        params.place_id = None;
+        params.taxon_name = Some(vec!["quercus agrifolia".to_string()]);
+        let errors = validate_params_remote(&server.base_url(), &params).await.unwrap();
+        assert!(errors.is_empty(), "case-insensitive exact match should pass: {errors:?}");
+
+        params.taxon_name = Some(vec!["Quercus agrifola".to_string()]);
+        let errors = validate_params_remote(&server.base_url(), &params).await.unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("did you mean Quercus agrifolia (49005)"));
+    }
+}
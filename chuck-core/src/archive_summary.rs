@@ -0,0 +1,347 @@
+//! A quick summary of a DarwinCore Archive read straight from the zip: what
+//! kind of records it has, how many, which columns are filled in, and when
+//! and where the records are from.
+
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::archive_validator::{meta_xml_name, DataFile};
+
+/// Taxa listed in `top_taxa`
+pub const TOP_TAXA: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSummary {
+    /// Last part of the core rowType, e.g. Occurrence or Event
+    pub core_type: String,
+    pub record_count: usize,
+    pub extensions: Vec<ExtensionSummary>,
+    /// Core columns in file order, then ones meta.xml gives a default value
+    pub columns: Vec<ColumnFill>,
+    /// Earliest and latest eventDate, as YYYY-MM-DD. Dates less precise
+    /// than a day aren't counted.
+    pub date_range: Option<(String, String)>,
+    pub bounding_box: Option<BoundingBox>,
+    /// Most common scientificNames, most common first
+    pub top_taxa: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionSummary {
+    /// Last part of the rowType, e.g. Multimedia
+    pub row_type: String,
+    pub location: String,
+    /// None if meta.xml lists a file the archive doesn't have
+    pub row_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnFill {
+    pub name: String,
+    /// Records with a non-blank value
+    pub filled: usize,
+}
+
+impl ColumnFill {
+    /// Share of `record_count` records with a value, from 0 to 1
+    pub fn fill_rate(&self, record_count: usize) -> f64 {
+        if record_count == 0 {
+            0.0
+        } else {
+            self.filled as f64 / record_count as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+impl BoundingBox {
+    fn extend(bbox: Option<Self>, lat: f64, lng: f64) -> Self {
+        match bbox {
+            None => Self { min_lat: lat, min_lng: lng, max_lat: lat, max_lng: lng },
+            Some(b) => Self {
+                min_lat: b.min_lat.min(lat),
+                min_lng: b.min_lng.min(lng),
+                max_lat: b.max_lat.max(lat),
+                max_lng: b.max_lng.max(lng),
+            },
+        }
+    }
+}
+
+fn short_row_type(node: roxmltree::Node) -> String {
+    let row_type = node.attribute("rowType").unwrap_or_default();
+    row_type.rsplit(['/', '#']).next().unwrap_or(row_type).to_string()
+}
+
+fn csv_reader<R: Read>(data_file: &DataFile, reader: R) -> csv::Reader<R> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(data_file.delimiter)
+        .quoting(data_file.quote.is_some())
+        .quote(data_file.quote.unwrap_or(b'"'))
+        .from_reader(reader)
+}
+
+/// The day an eventDate starts on, if it's at least that precise
+fn event_day(event_date: &str) -> Option<String> {
+    let start = event_date.split('/').next()?.trim();
+    let day = start.get(..10)?;
+    chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+    Some(day.to_string())
+}
+
+/// Summarizes the archive at `zip_path`, reading each data file once
+pub fn summarize_archive(zip_path: &Path) -> Result<ArchiveSummary, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(zip_path)?)?;
+    summarize_zip(&mut archive)
+}
+
+fn summarize_zip<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<ArchiveSummary, Box<dyn std::error::Error>> {
+    let meta_name = meta_xml_name(archive).ok_or("Archive has no meta.xml")?;
+    let prefix = meta_name.trim_end_matches("meta.xml").to_string();
+    let mut meta_xml = String::new();
+    archive.by_name(&meta_name)?.read_to_string(&mut meta_xml)?;
+    let doc = roxmltree::Document::parse(&meta_xml)?;
+    let core_node = doc
+        .descendants()
+        .find(|n| n.has_tag_name("core"))
+        .ok_or("meta.xml has no core")?;
+    let core = DataFile::from_node(core_node, true).ok_or("meta.xml has no core file location")?;
+
+    let mut record_count = 0;
+    let names: Vec<String>;
+    let mut filled: Vec<usize> = vec![];
+    let mut first_day: Option<String> = None;
+    let mut last_day: Option<String> = None;
+    let mut bounding_box = None;
+    let mut taxa: HashMap<String, usize> = HashMap::new();
+    {
+        let mut reader = csv_reader(&core, archive.by_name(&format!("{prefix}{}", core.location))?);
+        let mut header = None;
+        for (i, record) in reader.byte_records().enumerate() {
+            let record = record?;
+            if i < core.header_lines {
+                if i == 0 {
+                    header = Some(record);
+                }
+                continue;
+            }
+            if record.len() > filled.len() {
+                filled.resize(record.len(), 0);
+            }
+            let value = |index: Option<usize>| {
+                index
+                    .and_then(|index| record.get(index))
+                    .map(|value| String::from_utf8_lossy(value).trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            for (index, field) in record.iter().enumerate() {
+                if !field.iter().all(u8::is_ascii_whitespace) {
+                    filled[index] += 1;
+                }
+            }
+
+            let column = |term: &str| {
+                core.field_index(term).or_else(|| {
+                    header.as_ref().and_then(|header: &csv::ByteRecord| {
+                        header.iter().position(|name| name == term.as_bytes())
+                    })
+                })
+            };
+            if let Some(day) = value(column("eventDate")).as_deref().and_then(event_day) {
+                if first_day.as_ref().is_none_or(|first| day < *first) {
+                    first_day = Some(day.clone());
+                }
+                if last_day.as_ref().is_none_or(|last| day > *last) {
+                    last_day = Some(day);
+                }
+            }
+            let lat = value(column("decimalLatitude")).and_then(|v| v.parse::<f64>().ok());
+            let lng = value(column("decimalLongitude")).and_then(|v| v.parse::<f64>().ok());
+            if let (Some(lat), Some(lng)) = (lat, lng)
+                && (-90.0..=90.0).contains(&lat)
+                && (-180.0..=180.0).contains(&lng)
+            {
+                bounding_box = Some(BoundingBox::extend(bounding_box, lat, lng));
+            }
+            if let Some(name) = value(column("scientificName")) {
+                *taxa.entry(name).or_default() += 1;
+            }
+            record_count += 1;
+        }
+
+        names = (0..filled.len())
+            .map(|index| {
+                core.fields
+                    .iter()
+                    .find(|(field_index, _)| *field_index == index)
+                    .map(|(_, term)| term.clone())
+                    .or_else(|| {
+                        header
+                            .as_ref()
+                            .and_then(|header| header.get(index))
+                            .map(|name| String::from_utf8_lossy(name).trim().to_string())
+                    })
+                    .unwrap_or_else(|| format!("column {}", index + 1))
+            })
+            .collect();
+    }
+    let mut columns: Vec<ColumnFill> = names
+        .into_iter()
+        .zip(filled)
+        .map(|(name, filled)| ColumnFill { name, filled })
+        .collect();
+    columns.extend(core.defaults.iter().map(|(name, _)| ColumnFill {
+        name: name.clone(),
+        filled: record_count,
+    }));
+
+    let mut extensions = vec![];
+    for node in doc.descendants().filter(|n| n.has_tag_name("extension")) {
+        let Some(data_file) = DataFile::from_node(node, false) else { continue };
+        let row_count = match archive.by_name(&format!("{prefix}{}", data_file.location)) {
+            Ok(entry) => {
+                let mut reader = csv_reader(&data_file, entry);
+                let rows = reader.byte_records().count();
+                Some(rows.saturating_sub(data_file.header_lines))
+            }
+            Err(_) => None,
+        };
+        extensions.push(ExtensionSummary {
+            row_type: short_row_type(node),
+            location: data_file.location,
+            row_count,
+        });
+    }
+
+    let mut top_taxa: Vec<(String, usize)> = taxa.into_iter().collect();
+    top_taxa.sort_by(|(a_name, a_count), (b_name, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+    });
+    top_taxa.truncate(TOP_TAXA);
+
+    Ok(ArchiveSummary {
+        core_type: short_row_type(core_node),
+        record_count,
+        extensions,
+        columns,
+        date_range: first_day.zip(last_day),
+        bounding_box,
+        top_taxa,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_archive(files: &[(&str, &str)]) -> zip::ZipArchive<std::io::Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        zip::ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    const META_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+    <field index="3" term="http://rs.tdwg.org/dwc/terms/decimalLatitude"/>
+    <field index="4" term="http://rs.tdwg.org/dwc/terms/decimalLongitude"/>
+    <field term="http://rs.tdwg.org/dwc/terms/basisOfRecord" default="HumanObservation"/>
+  </core>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Multimedia" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>multimedia.csv</location></files>
+    <coreid index="0"/>
+  </extension>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Identification" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>identification.csv</location></files>
+    <coreid index="0"/>
+  </extension>
+</archive>"#;
+
+    #[test]
+    fn test_summarize_zip() {
+        let mut archive = zip_archive(&[
+            ("meta.xml", META_XML),
+            (
+                "occurrence.csv",
+                "occurrenceID,scientificName,eventDate,decimalLatitude,decimalLongitude\n\
+                 1,Quercus agrifolia,2021-04-02,37.5,-122.1\n\
+                 2,Quercus agrifolia,2019-06-10T08:00:00Z,36.9,-121.8\n\
+                 3,Pinus ponderosa,2022,,\n\
+                 4,,2020-01-01/2020-01-05,91,-122\n",
+            ),
+            ("multimedia.csv", "occurrenceID,identifier\n1,a.jpg\n1,b.jpg\n"),
+        ]);
+
+        let summary = summarize_zip(&mut archive).unwrap();
+
+        assert_eq!(summary.core_type, "Occurrence");
+        assert_eq!(summary.record_count, 4);
+        assert_eq!(
+            summary.extensions,
+            vec![
+                ExtensionSummary {
+                    row_type: "Multimedia".to_string(),
+                    location: "multimedia.csv".to_string(),
+                    row_count: Some(2),
+                },
+                ExtensionSummary {
+                    row_type: "Identification".to_string(),
+                    location: "identification.csv".to_string(),
+                    row_count: None,
+                },
+            ]
+        );
+        let fills: Vec<(&str, usize)> =
+            summary.columns.iter().map(|c| (c.name.as_str(), c.filled)).collect();
+        assert_eq!(
+            fills,
+            vec![
+                ("occurrenceID", 4),
+                ("scientificName", 3),
+                ("eventDate", 4),
+                ("decimalLatitude", 3),
+                ("decimalLongitude", 3),
+                ("basisOfRecord", 4),
+            ]
+        );
+        assert_eq!(
+            summary.date_range,
+            Some(("2019-06-10".to_string(), "2021-04-02".to_string()))
+        );
+        assert_eq!(
+            summary.bounding_box,
+            Some(BoundingBox { min_lat: 36.9, min_lng: -122.1, max_lat: 37.5, max_lng: -121.8 })
+        );
+        assert_eq!(
+            summary.top_taxa,
+            vec![("Quercus agrifolia".to_string(), 2), ("Pinus ponderosa".to_string(), 1)]
+        );
+    }
+}
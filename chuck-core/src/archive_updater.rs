@@ -6,13 +6,15 @@ use chrono::NaiveDate;
 use crate::chuck_metadata::{ChuckMetadata, parse_pub_date_from_xml};
 use crate::api::params::parse_url_params;
 use crate::darwin_core::{
-    meta::generate_eml,
+    eml::Eml,
     meta::Metadata,
     occurrence::Occurrence,
     multimedia::Multimedia,
     audiovisual::Audiovisual,
     identification::Identification,
     comment::Comment,
+    measurement_or_fact::MeasurementOrFact,
+    verbatim::Verbatim,
 };
 use crate::downloader::{Downloader, DownloadProgress, DownloadStage};
 use crate::merge::{merge_csv_streams, merge_extension_csv_streams};
@@ -55,6 +57,9 @@ fn extensions_from_zip<R: std::io::Read + std::io::Seek>(
     if names.contains(Comment::FILENAME) {
         extensions.push(DwcaExtension::Comments);
     }
+    if names.contains(MeasurementOrFact::FILENAME) {
+        extensions.push(DwcaExtension::MeasurementOrFact);
+    }
     extensions
 }
 
@@ -112,6 +117,23 @@ pub fn read_archive_preview(
     Ok(ArchivePreview { inat_query, pub_date, extensions, has_media })
 }
 
+/// Number of occurrence rows in a ZIP archive, e.g. to tell how many records
+/// an update added.
+pub fn count_occurrences(zip_path: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let entry = archive.by_name(Occurrence::FILENAME)?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(entry);
+    let mut count = 0;
+    for result in rdr.records() {
+        result?;
+        count += 1;
+    }
+    Ok(count)
+}
+
 /// Read a CSV stream into a `HashMap<id_at_col, row>` (one row per id).
 /// Used for occurrence.csv where each observation has exactly one row.
 fn read_updates_map_from_reader<R: std::io::Read>(
@@ -154,39 +176,6 @@ fn read_grouped_updates_from_reader<R: std::io::Read>(
     Ok(map)
 }
 
-/// Read `<para>` lines from the `<abstract>` section of an EML stream.
-/// Returns an empty vec if the stream is unreadable or has no `<abstract>`.
-fn read_abstract_lines_from_reader<R: std::io::Read>(mut reader: R) -> Vec<String> {
-    let mut content = String::new();
-    if reader.read_to_string(&mut content).is_err() {
-        return vec![];
-    }
-    let abs_start = match content.find("<abstract>") {
-        Some(i) => i + "<abstract>".len(),
-        None => return vec![],
-    };
-    let abs_end = match content[abs_start..].find("</abstract>") {
-        Some(i) => abs_start + i,
-        None => return vec![],
-    };
-    let section = &content[abs_start..abs_end];
-    let mut lines = Vec::new();
-    let mut rest = section;
-    while let Some(start) = rest.find("<para>") {
-        rest = &rest[start + "<para>".len()..];
-        if let Some(end) = rest.find("</para>") {
-            let text = rest[..end].trim().to_string();
-            if !text.is_empty() {
-                lines.push(text);
-            }
-            rest = &rest[end + "</para>".len()..];
-        } else {
-            break;
-        }
-    }
-    lines
-}
-
 /// Compute `updated_since` as `pub_date - 1 day`, formatted as `YYYY-MM-DD`.
 pub fn updated_since_from_pub_date(pub_date: &str) -> Result<String, Box<dyn std::error::Error>> {
     let date = NaiveDate::parse_from_str(pub_date, "%Y-%m-%d")?;
@@ -265,6 +254,7 @@ fn merge_archive_into(
         Audiovisual::FILENAME,
         Identification::FILENAME,
         Comment::FILENAME,
+        MeasurementOrFact::FILENAME,
     ]
     .into_iter()
     .collect();
@@ -280,6 +270,7 @@ fn merge_archive_into(
     // occurrence.csv: one row per id → HashMap<id, row>
     // extension CSVs: many rows per coreid → HashMap<coreid, Vec<rows>>
     let mut occ_map: HashMap<String, Vec<String>> = HashMap::new();
+    let mut verbatim_map: HashMap<String, Vec<String>> = HashMap::new();
     let mut ext_maps: HashMap<String, GroupedMap> = HashMap::new();
     let mut media_in_updates: HashSet<String> = HashSet::new();
     {
@@ -290,6 +281,8 @@ fn merge_archive_into(
             let name = entry.name().to_string();
             if name == Occurrence::FILENAME {
                 occ_map = read_updates_map_from_reader(&mut entry, 0)?;
+            } else if name == Verbatim::FILENAME {
+                verbatim_map = read_updates_map_from_reader(&mut entry, 0)?;
             } else if csv_filenames.contains(name.as_str()) {
                 ext_maps.insert(name, read_grouped_updates_from_reader(&mut entry, 0)?);
             } else if name.starts_with("media/") {
@@ -322,19 +315,29 @@ fn merge_archive_into(
             if name == "chuck.json" {
                 // Written fresh below
             } else if name == "eml.xml" {
-                let abstract_lines = read_abstract_lines_from_reader(&mut entry);
-                let new_metadata = Metadata {
-                    abstract_lines,
+                // Keep whatever metadata the archive has, just with a new
+                // pubDate, unless it's too broken to parse
+                let mut content = String::new();
+                let mut eml = match std::io::Read::read_to_string(&mut entry, &mut content) {
+                    Ok(_) => Eml::parse(&content).ok(),
+                    Err(_) => None,
+                }
+                .unwrap_or_else(|| Eml::from_metadata(&Metadata {
+                    abstract_lines: vec![],
                     inat_query: Some(original_inat_query.to_string()),
-                };
+                }));
+                eml.pub_date = Some(chrono::Utc::now().format("%Y-%m-%d").to_string());
                 zip_out.start_file(&name, options)?;
-                zip_out.write_all(generate_eml(&new_metadata).as_bytes())?;
+                zip_out.write_all(eml.to_xml().as_bytes())?;
             } else if name == "meta.xml" {
                 zip_out.start_file(&name, options)?;
                 std::io::copy(&mut entry, &mut zip_out)?;
             } else if name == Occurrence::FILENAME {
                 zip_out.start_file(&name, options)?;
                 merge_csv_streams(&mut entry, &mut zip_out, &occ_map, 0)?;
+            } else if name == Verbatim::FILENAME {
+                zip_out.start_file(&name, options)?;
+                merge_csv_streams(&mut entry, &mut zip_out, &verbatim_map, 0)?;
             } else if csv_filenames.contains(name.as_str()) {
                 let empty_map = HashMap::new();
                 let updates = ext_maps.get(&name).unwrap_or(&empty_map);
@@ -935,6 +938,19 @@ mod tests {
         assert!(!preview.has_media);
     }
 
+    #[test]
+    fn test_count_occurrences_counts_quoted_newlines_once() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let path = tmp.path().to_str().unwrap();
+        build_test_zip(
+            path,
+            "id,occurrenceRemarks\n1,\"two\nlines\"\n2,\n",
+            "taxon_id=47792",
+            "2025-01-15",
+        );
+        assert_eq!(count_occurrences(path).unwrap(), 2);
+    }
+
     #[test]
     fn test_read_archive_preview_detects_media() {
         let tmp = tempfile::NamedTempFile::new().unwrap();
@@ -0,0 +1,552 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// Values of basisOfRecord from the GBIF vocabulary
+/// (https://rs.gbif.org/vocabulary/dwc/basis_of_record)
+const BASIS_OF_RECORD: &[&str] = &[
+    "PreservedSpecimen",
+    "FossilSpecimen",
+    "LivingSpecimen",
+    "MaterialSample",
+    "MaterialCitation",
+    "HumanObservation",
+    "MachineObservation",
+    "Occurrence",
+    "Event",
+    "Taxon",
+];
+
+/// Creative Commons licenses GBIF accepts for occurrence data, as they
+/// appear in URIs and codes once lowercased
+const GBIF_LICENSES: &[&str] = &[
+    "publicdomain/zero/1.0",
+    "licenses/by/4.0",
+    "licenses/by-nc/4.0",
+    "cc0-1.0",
+    "cc-by-4.0",
+    "cc-by-nc-4.0",
+];
+
+/// Issues of one kind reported for each file before the rest are summarized
+const MAX_ISSUES_PER_KIND: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Something GBIF or an IPT would reject
+    Error,
+    /// Something that probably isn't what the publisher meant
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Data file the issue is in, or None for the archive as a whole
+    pub file: Option<String>,
+    /// Line in the data file where the row starts
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: ")?;
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{file} line {line}: ")?,
+            (Some(file), None) => write!(f, "{file}: ")?,
+            _ => {}
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Error).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning).count()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.error_count() == 0
+    }
+}
+
+/// Collects issues, keeping only the first few of each kind per file so a
+/// systematic problem doesn't produce a line for every row
+#[derive(Default)]
+struct Issues {
+    issues: Vec<ValidationIssue>,
+    counts: HashMap<(Option<String>, &'static str), usize>,
+}
+
+impl Issues {
+    fn push(
+        &mut self,
+        kind: &'static str,
+        severity: Severity,
+        file: Option<&str>,
+        line: Option<u64>,
+        message: String,
+    ) {
+        let count = self.counts.entry((file.map(str::to_string), kind)).or_default();
+        *count += 1;
+        if *count <= MAX_ISSUES_PER_KIND {
+            self.issues.push(ValidationIssue {
+                severity,
+                file: file.map(str::to_string),
+                line,
+                message,
+            });
+        }
+    }
+
+    fn into_report(mut self) -> ValidationReport {
+        let mut suppressed: Vec<_> = self.counts
+            .into_iter()
+            .filter(|(_, count)| *count > MAX_ISSUES_PER_KIND)
+            .collect();
+        suppressed.sort();
+        for ((file, kind), count) in suppressed {
+            self.issues.push(ValidationIssue {
+                severity: Severity::Warning,
+                file,
+                line: None,
+                message: format!("{} more {kind} issues not shown", count - MAX_ISSUES_PER_KIND),
+            });
+        }
+        ValidationReport { issues: self.issues }
+    }
+}
+
+/// A core or extension data file as described in meta.xml
+pub(crate) struct DataFile {
+    pub(crate) location: String,
+    is_core: bool,
+    pub(crate) delimiter: u8,
+    pub(crate) quote: Option<u8>,
+    pub(crate) header_lines: usize,
+    encoding: Option<String>,
+    /// Column of the id (core) or coreid (extension) element
+    pub(crate) id_index: Option<usize>,
+    /// (column index, term name)
+    pub(crate) fields: Vec<(usize, String)>,
+    /// (term name, value) for fields with a default
+    pub(crate) defaults: Vec<(String, String)>,
+}
+
+impl DataFile {
+    pub(crate) fn from_node(node: roxmltree::Node, is_core: bool) -> Option<Self> {
+        let location = node
+            .descendants()
+            .find(|n| n.has_tag_name("location"))
+            .and_then(|n| n.text())?
+            .trim()
+            .to_string();
+        let delimiter = match node.attribute("fieldsTerminatedBy") {
+            Some(r"\t") | Some("\t") => b'\t',
+            Some(d) if !d.is_empty() => d.as_bytes()[0],
+            _ => b',',
+        };
+        let quote = match node.attribute("fieldsEnclosedBy") {
+            Some("") => None,
+            Some(q) => q.as_bytes().first().copied(),
+            None => Some(b'"'),
+        };
+        let id_tag = if is_core { "id" } else { "coreid" };
+        let mut fields = Vec::new();
+        let mut defaults = Vec::new();
+        for field in node.descendants().filter(|n| n.has_tag_name("field")) {
+            let Some(term) = field.attribute("term") else { continue };
+            let term_name = term.rsplit(['/', '#']).next().unwrap_or(term).to_string();
+            match field.attribute("index").and_then(|i| i.parse().ok()) {
+                Some(index) => fields.push((index, term_name)),
+                None => {
+                    if let Some(default) = field.attribute("default") {
+                        defaults.push((term_name, default.to_string()));
+                    }
+                }
+            }
+        }
+        Some(Self {
+            location,
+            is_core,
+            delimiter,
+            quote,
+            header_lines: node
+                .attribute("ignoreHeaderLines")
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0),
+            encoding: node.attribute("encoding").map(str::to_string),
+            id_index: node
+                .descendants()
+                .find(|n| n.has_tag_name(id_tag))
+                .and_then(|n| n.attribute("index"))
+                .and_then(|i| i.parse().ok()),
+            fields,
+            defaults,
+        })
+    }
+
+    pub(crate) fn field_index(&self, term_name: &str) -> Option<usize> {
+        self.fields.iter().find(|(_, name)| name == term_name).map(|(index, _)| *index)
+    }
+}
+
+/// Checks a DarwinCore Archive the way an IPT or GBIF would before
+/// publishing it: meta.xml references resolve, core IDs are present and
+/// unique, rows have as many fields as the header, data files are valid
+/// UTF-8, and basisOfRecord and license use their controlled vocabularies.
+pub fn validate_archive(zip_path: &Path) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    Ok(validate_zip(&mut archive))
+}
+
+/// Name of the archive's meta.xml entry. It's usually at the root, but some
+/// tools zip up a directory.
+pub(crate) fn meta_xml_name<R: Read + Seek>(archive: &zip::ZipArchive<R>) -> Option<String> {
+    archive
+        .file_names()
+        .filter(|name| *name == "meta.xml" || name.ends_with("/meta.xml"))
+        .min_by_key(|name| name.len())
+        .map(str::to_string)
+}
+
+fn validate_zip<R: Read + Seek>(archive: &mut zip::ZipArchive<R>) -> ValidationReport {
+    let mut issues = Issues::default();
+
+    let Some(meta_name) = meta_xml_name(archive) else {
+        issues.push("meta.xml", Severity::Error, None, None, "Archive has no meta.xml".to_string());
+        return issues.into_report();
+    };
+    let prefix = meta_name.trim_end_matches("meta.xml").to_string();
+
+    let mut meta_xml = String::new();
+    if let Err(e) = archive
+        .by_name(&meta_name)
+        .map_err(|e| e.to_string())
+        .and_then(|mut f| f.read_to_string(&mut meta_xml).map_err(|e| e.to_string()))
+    {
+        issues.push("meta.xml", Severity::Error, None, None, format!("Couldn't read meta.xml: {e}"));
+        return issues.into_report();
+    }
+    let doc = match roxmltree::Document::parse(&meta_xml) {
+        Ok(doc) => doc,
+        Err(e) => {
+            issues.push("meta.xml", Severity::Error, None, None, format!("meta.xml isn't valid XML: {e}"));
+            return issues.into_report();
+        }
+    };
+
+    let Some(core) = doc.descendants().find(|n| n.has_tag_name("core")).and_then(|n| DataFile::from_node(n, true)) else {
+        issues.push("meta.xml", Severity::Error, None, None, "meta.xml has no core with a file location".to_string());
+        return issues.into_report();
+    };
+    let extensions: Vec<DataFile> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("extension"))
+        .filter_map(|n| DataFile::from_node(n, false))
+        .collect();
+
+    let mut core_ids = HashMap::new();
+    for data_file in std::iter::once(&core).chain(&extensions) {
+        let entry_name = format!("{prefix}{}", data_file.location);
+        match archive.by_name(&entry_name) {
+            Ok(entry) => validate_data_file(data_file, entry, &mut core_ids, &mut issues),
+            Err(_) => issues.push(
+                "meta.xml",
+                Severity::Error,
+                None,
+                None,
+                format!("meta.xml lists {}, but it isn't in the archive", data_file.location),
+            ),
+        }
+    }
+    issues.into_report()
+}
+
+/// Checks the rows of one data file. Core IDs are collected from the core
+/// so extension rows can be checked against them, which relies on the core
+/// being validated first.
+fn validate_data_file(
+    data_file: &DataFile,
+    reader: impl Read,
+    core_ids: &mut HashMap<Vec<u8>, u64>,
+    issues: &mut Issues,
+) {
+    let file = Some(data_file.location.as_str());
+    if let Some(encoding) = &data_file.encoding {
+        if !matches!(encoding.to_ascii_lowercase().as_str(), "utf-8" | "utf8") {
+            issues.push(
+                "encoding",
+                Severity::Warning,
+                file,
+                None,
+                format!("Declared encoding is {encoding}; most tools expect UTF-8"),
+            );
+        }
+    }
+    for (term, value) in &data_file.defaults {
+        check_vocabulary(term, value.as_bytes(), file, None, issues);
+    }
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(data_file.delimiter)
+        .quoting(data_file.quote.is_some())
+        .quote(data_file.quote.unwrap_or(b'"'))
+        .from_reader(reader);
+
+    let mut expected_fields = None;
+    let basis_index = data_file.field_index("basisOfRecord");
+    let license_index = data_file.field_index("license");
+    for (i, record) in csv_reader.byte_records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line());
+                issues.push("csv", Severity::Error, file, line, format!("Couldn't parse row: {e}"));
+                return;
+            }
+        };
+        let line = record.position().map(|p| p.line());
+
+        if i == 0 && record.get(0).is_some_and(|f| f.starts_with(b"\xEF\xBB\xBF")) {
+            issues.push(
+                "encoding",
+                Severity::Warning,
+                file,
+                line,
+                "File starts with a byte order mark, which can end up in the first column name".to_string(),
+            );
+        }
+        if record.iter().any(|field| std::str::from_utf8(field).is_err()) {
+            issues.push("encoding", Severity::Error, file, line, "Row isn't valid UTF-8".to_string());
+        }
+
+        let Some(expected) = expected_fields else {
+            expected_fields = Some(record.len());
+            check_field_indexes(data_file, record.len(), issues);
+            if data_file.header_lines > 0 {
+                continue;
+            }
+            // No header, so the first row is data and sets the count
+            check_row(data_file, &record, line, basis_index, license_index, core_ids, issues);
+            continue;
+        };
+        if i < data_file.header_lines {
+            continue;
+        }
+        if record.len() != expected {
+            issues.push(
+                "field count",
+                Severity::Error,
+                file,
+                line,
+                format!("Row has {} fields but the header has {expected}", record.len()),
+            );
+        }
+        check_row(data_file, &record, line, basis_index, license_index, core_ids, issues);
+    }
+}
+
+/// Reports meta.xml fields that point past the last column
+fn check_field_indexes(data_file: &DataFile, columns: usize, issues: &mut Issues) {
+    let file = Some(data_file.location.as_str());
+    let id = data_file.id_index.map(|index| (index, "the id".to_string()));
+    for (index, name) in data_file.fields.iter().map(|(i, n)| (*i, n.clone())).chain(id) {
+        if index >= columns {
+            issues.push(
+                "meta.xml",
+                Severity::Error,
+                file,
+                None,
+                format!("meta.xml maps {name} to column {index}, but the file only has {columns} columns"),
+            );
+        }
+    }
+}
+
+fn check_row(
+    data_file: &DataFile,
+    record: &csv::ByteRecord,
+    line: Option<u64>,
+    basis_index: Option<usize>,
+    license_index: Option<usize>,
+    core_ids: &mut HashMap<Vec<u8>, u64>,
+    issues: &mut Issues,
+) {
+    let file = Some(data_file.location.as_str());
+    if let Some(id) = data_file.id_index.and_then(|index| record.get(index)) {
+        let id_display = String::from_utf8_lossy(id);
+        if id.iter().all(u8::is_ascii_whitespace) {
+            issues.push("core ID", Severity::Error, file, line, "Row has no ID".to_string());
+        } else if data_file.is_core {
+            if let Some(first_line) = core_ids.insert(id.to_vec(), line.unwrap_or_default()) {
+                issues.push(
+                    "core ID",
+                    Severity::Error,
+                    file,
+                    line,
+                    format!("Duplicate ID {id_display}, first used on line {first_line}"),
+                );
+            }
+        } else if !core_ids.contains_key(id) {
+            issues.push(
+                "core ID",
+                Severity::Error,
+                file,
+                line,
+                format!("Refers to core ID {id_display}, which isn't in the core"),
+            );
+        }
+    }
+    for (term, index) in [("basisOfRecord", basis_index), ("license", license_index)] {
+        if let Some(value) = index.and_then(|index| record.get(index)) {
+            check_vocabulary(term, value, file, line, issues);
+        }
+    }
+}
+
+/// Checks a basisOfRecord or license value against its vocabulary. Blank
+/// values are left alone.
+fn check_vocabulary(term: &str, value: &[u8], file: Option<&str>, line: Option<u64>, issues: &mut Issues) {
+    let value = String::from_utf8_lossy(value);
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    match term {
+        "basisOfRecord" => {
+            if BASIS_OF_RECORD.contains(&value) {
+                return;
+            }
+            let suggestion = BASIS_OF_RECORD
+                .iter()
+                .find(|basis| basis.eq_ignore_ascii_case(&value.replace([' ', '_'], "")));
+            let (severity, message) = match suggestion {
+                Some(basis) => (Severity::Warning, format!("basisOfRecord {value} should be written {basis}")),
+                None => (Severity::Error, format!("basisOfRecord {value} isn't in the GBIF vocabulary")),
+            };
+            issues.push("basisOfRecord", severity, file, line, message);
+        }
+        "license" => {
+            let lower = value.to_lowercase();
+            if !GBIF_LICENSES.iter().any(|license| lower.contains(license)) {
+                issues.push(
+                    "license",
+                    Severity::Warning,
+                    file,
+                    line,
+                    format!("license {value} isn't CC0, CC BY, or CC BY-NC, so GBIF won't accept it"),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_archive(files: &[(&str, &str)]) -> zip::ZipArchive<std::io::Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        zip::ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    const META_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/basisOfRecord"/>
+    <field index="2" term="http://purl.org/dc/terms/license"/>
+  </core>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Multimedia" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>multimedia.csv</location></files>
+    <coreid index="0"/>
+    <field index="1" term="http://purl.org/dc/terms/identifier"/>
+  </extension>
+</archive>"#;
+
+    #[test]
+    fn test_valid_archive_has_no_issues() {
+        let mut archive = zip_archive(&[
+            ("meta.xml", META_XML),
+            ("occurrence.csv", "occurrenceID,basisOfRecord,license\n1,HumanObservation,CC-BY-NC-4.0\n"),
+            ("multimedia.csv", "occurrenceID,identifier\n1,https://example.com/1.jpg\n"),
+        ]);
+
+        let report = validate_zip(&mut archive);
+
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_reports_row_problems_with_lines() {
+        let mut archive = zip_archive(&[
+            ("meta.xml", META_XML),
+            (
+                "occurrence.csv",
+                "occurrenceID,basisOfRecord,license\n\
+                 1,HumanObservation,\n\
+                 1,human observation,\n\
+                 2,Sighting,All rights reserved\n\
+                 3,HumanObservation\n",
+            ),
+            ("multimedia.csv", "occurrenceID,identifier\n4,https://example.com/4.jpg\n"),
+        ]);
+
+        let report = validate_zip(&mut archive);
+        let messages: Vec<String> = report.issues.iter().map(ToString::to_string).collect();
+
+        assert_eq!(messages, vec![
+            "error: occurrence.csv line 3: Duplicate ID 1, first used on line 2",
+            "warning: occurrence.csv line 3: basisOfRecord human observation should be written HumanObservation",
+            "error: occurrence.csv line 4: basisOfRecord Sighting isn't in the GBIF vocabulary",
+            "warning: occurrence.csv line 4: license All rights reserved isn't CC0, CC BY, or CC BY-NC, so GBIF won't accept it",
+            "error: occurrence.csv line 5: Row has 2 fields but the header has 3",
+            "error: multimedia.csv line 2: Refers to core ID 4, which isn't in the core",
+        ]);
+        assert_eq!(report.error_count(), 4);
+    }
+
+    #[test]
+    fn test_reports_missing_data_file() {
+        let mut archive = zip_archive(&[
+            ("meta.xml", META_XML),
+            ("occurrence.csv", "occurrenceID,basisOfRecord,license\n1,HumanObservation,\n"),
+        ]);
+
+        let report = validate_zip(&mut archive);
+
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(
+            report.issues[0].message,
+            "meta.xml lists multimedia.csv, but it isn't in the archive"
+        );
+    }
+}
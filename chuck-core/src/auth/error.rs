@@ -8,6 +8,7 @@ pub enum AuthError {
     IoError(std::io::Error),
     JsonError(serde_json::Error),
     HttpError(reqwest::Error),
+    InvalidProfile(String),
 }
 
 impl fmt::Display for AuthError {
@@ -19,6 +20,10 @@ impl fmt::Display for AuthError {
             AuthError::IoError(e) => write!(f, "I/O error: {e}"),
             AuthError::JsonError(e) => write!(f, "JSON error: {e}"),
             AuthError::HttpError(e) => write!(f, "HTTP error: {e}"),
+            AuthError::InvalidProfile(name) => write!(
+                f,
+                "Invalid profile name '{name}': use only letters, numbers, - and _"
+            ),
         }
     }
 }
@@ -1,9 +1,11 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
 use super::{AuthError, AuthToken};
+use crate::api::client::http_client;
 
 pub async fn fetch_jwt(oauth_token: &AuthToken) -> Result<String, AuthError> {
-    let client = reqwest::Client::new();
-
-    let response = client
+    let response = http_client()
         .get("https://www.inaturalist.org/users/api_token")
         .bearer_auth(&oauth_token.access_token)
         .send()
@@ -29,3 +31,27 @@ pub async fn fetch_jwt(oauth_token: &AuthToken) -> Result<String, AuthError> {
 
     Ok(jwt_string.to_string())
 }
+
+/// The iNat user ID in a JWT's claims, i.e. whose data it grants access to.
+/// The signature isn't verified; the API does that.
+pub fn jwt_user_id(jwt: &str) -> Option<i32> {
+    let payload = jwt.split('.').nth(1)?;
+    let claims: serde_json::Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?).ok()?;
+    claims.get("user_id")?.as_i64()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwt_user_id() {
+        let claims = URL_SAFE_NO_PAD.encode(r#"{"user_id":42,"oauth_application_id":1,"exp":1700000000}"#);
+        let jwt = format!("eyJhbGciOiJIUzUxMiJ9.{claims}.signature");
+
+        assert_eq!(jwt_user_id(&jwt), Some(42));
+        assert_eq!(jwt_user_id("not-a-jwt"), None);
+        assert_eq!(jwt_user_id(&format!("header.{}.sig", URL_SAFE_NO_PAD.encode("{}"))), None);
+    }
+}
@@ -2,22 +2,37 @@ use crate::auth::{AuthError, AuthToken, TokenStorage};
 use keyring::Entry;
 use serde_json;
 
+const ACCOUNT_NAME: &str = "iNaturalist access token";
+
 pub struct KeyringStorage {
     service_name: &'static str,
-    account_name: &'static str,
+    account_name: String,
 }
 
 impl KeyringStorage {
     pub fn new() -> Result<Self, AuthError> {
+        Self::for_profile(None)
+    }
+
+    /// Storage for the token of a named profile. Each profile gets its own
+    /// keyring entry; the default profile keeps the original one.
+    pub fn for_profile(profile: Option<&str>) -> Result<Self, AuthError> {
+        let account_name = match profile.filter(|p| *p != super::DEFAULT_PROFILE) {
+            Some(profile) => {
+                super::profile::validate_profile_name(profile)?;
+                format!("{ACCOUNT_NAME} ({profile})")
+            }
+            None => ACCOUNT_NAME.to_string(),
+        };
         Ok(Self {
             service_name: "Chuck",
-            account_name: "iNaturalist access token",
+            account_name,
         })
     }
 
     pub fn is_available() -> bool {
         // Try to create an entry to test availability
-        Entry::new("Chuck", "iNaturalist access token").is_ok()
+        Entry::new("Chuck", ACCOUNT_NAME).is_ok()
     }
 
     /// Initialize keyring storage for use as application state
@@ -27,7 +42,7 @@ impl KeyringStorage {
     }
 
     fn get_entry(&self) -> Result<Entry, AuthError> {
-        Entry::new(self.service_name, self.account_name)
+        Entry::new(self.service_name, &self.account_name)
             .map_err(|e| {
                 log::error!("Keyring entry creation failed: {e}");
                 AuthError::OAuthFailed(format!("Keyring unavailable: {e}"))
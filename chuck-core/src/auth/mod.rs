@@ -1,6 +1,7 @@
 pub mod error;
 pub mod jwt;
 pub mod oauth;
+pub mod profile;
 pub mod token;
 mod token_storage;
 mod file_storage;
@@ -14,7 +15,8 @@ mod auth_cache;
 
 pub use error::AuthError;
 pub use oauth::{authenticate_user};
-pub use jwt::{fetch_jwt};
+pub use jwt::{fetch_jwt, jwt_user_id};
+pub use profile::{active_profile, set_active_profile, DEFAULT_PROFILE};
 pub use token::{load_auth_token, save_auth_token, clear_auth_token, AuthToken};
 pub use token_storage::TokenStorage;
 pub use file_storage::FileStorage;
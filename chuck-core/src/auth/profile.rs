@@ -0,0 +1,81 @@
+//! Named auth profiles, so someone with more than one iNaturalist account,
+//! e.g. a personal and an institutional one, can keep a token for each and
+//! download as either without signing out of the other
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use super::AuthError;
+
+/// Profile used when none is named. Its token is stored where tokens were
+/// stored before there were profiles.
+pub const DEFAULT_PROFILE: &str = "default";
+
+static ACTIVE_PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Checks that `name` can be used in file names and keyring entries
+pub fn validate_profile_name(name: &str) -> Result<(), AuthError> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AuthError::InvalidProfile(name.to_string()))
+    }
+}
+
+/// Sets the profile whose token StorageFactory loads and saves, including
+/// when a JWT is refreshed mid-download. None goes back to the default.
+pub fn set_active_profile(profile: Option<&str>) -> Result<(), AuthError> {
+    if let Some(profile) = profile {
+        validate_profile_name(profile)?;
+    }
+    let mut active = ACTIVE_PROFILE.write().unwrap_or_else(|e| e.into_inner());
+    *active = profile.filter(|p| *p != DEFAULT_PROFILE).map(str::to_string);
+    Ok(())
+}
+
+/// The profile set with set_active_profile, or None for the default
+pub fn active_profile() -> Option<String> {
+    ACTIVE_PROFILE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Where the token for `profile` is stored when the default profile's is
+/// stored at `path`, e.g. auth-work.json next to auth.json
+pub fn profile_path(path: &Path, profile: Option<&str>) -> PathBuf {
+    let Some(profile) = profile.filter(|p| *p != DEFAULT_PROFILE) else {
+        return path.to_path_buf();
+    };
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("auth");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{stem}-{profile}.{extension}"),
+        None => format!("{stem}-{profile}"),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_profile_name() {
+        assert!(validate_profile_name("work").is_ok());
+        assert!(validate_profile_name("field-station_2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../other").is_err());
+        assert!(validate_profile_name("two words").is_err());
+    }
+
+    #[test]
+    fn test_profile_path() {
+        let path = Path::new("/home/me/.config/chuck/auth.json");
+
+        assert_eq!(profile_path(path, None), path);
+        assert_eq!(profile_path(path, Some(DEFAULT_PROFILE)), path);
+        assert_eq!(
+            profile_path(path, Some("work")),
+            Path::new("/home/me/.config/chuck/auth-work.json")
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::auth::{AuthError, AuthToken, TokenStorage, CustomFileStorage, StorageBackendConfig, StorageBackendType};
+use crate::auth::profile::{active_profile, profile_path};
 #[cfg(feature = "keyring-storage")]
 use crate::auth::KeyringStorage;
 use std::path::PathBuf;
@@ -39,29 +40,33 @@ impl TokenStorage for StorageInstance {
 pub struct StorageFactory;
 
 impl StorageFactory {
-    /// Auto-detect storage without user interaction (for non-interactive contexts)
+    /// Auto-detect storage without user interaction (for non-interactive
+    /// contexts). Stores the token of the active profile.
     pub fn create() -> Result<StorageInstance, AuthError> {
+        let profile = active_profile();
         // Try loading saved config first
         if let Ok(Some(config)) = StorageBackendConfig::load() {
-            return Self::create_from_config(&config);
+            return Self::create_from_config(&config, profile.as_deref());
         }
 
         // No saved config - try keyring auto-detect
-        Self::create_auto_detect()
+        Self::create_auto_detect(profile.as_deref())
     }
 
-    /// Interactive creation for CLI (prompts user if needed)
+    /// Interactive creation for CLI (prompts user if needed). Stores the
+    /// token of the active profile.
     pub fn create_interactive() -> Result<StorageInstance, AuthError> {
+        let profile = active_profile();
         // Try loading saved config first
         if let Ok(Some(config)) = StorageBackendConfig::load() {
-            return Self::create_from_config(&config);
+            return Self::create_from_config(&config, profile.as_deref());
         }
 
         // No saved config - try keyring
         #[cfg(feature = "keyring-storage")]
         {
             if KeyringStorage::is_available() {
-                let storage = KeyringStorage::new()?;
+                let storage = KeyringStorage::for_profile(profile.as_deref())?;
                 Self::save_config(StorageBackendType::Keyring, None)?;
                 println!("Using OS keyring for secure token storage.");
                 return Ok(StorageInstance::Keyring(storage));
@@ -75,18 +80,21 @@ impl StorageFactory {
         println!("This file will contain sensitive information and should be kept secure.\n");
 
         let custom_path = Self::prompt_for_storage_path()?;
-        let storage = CustomFileStorage::new(custom_path.clone())?;
+        let storage = CustomFileStorage::new(profile_path(&custom_path, profile.as_deref()))?;
         Self::save_config(StorageBackendType::File, Some(custom_path))?;
 
         println!("\nToken storage configured successfully!");
         Ok(StorageInstance::File(storage))
     }
 
-    fn create_from_config(config: &StorageBackendConfig) -> Result<StorageInstance, AuthError> {
+    fn create_from_config(
+        config: &StorageBackendConfig,
+        profile: Option<&str>,
+    ) -> Result<StorageInstance, AuthError> {
         match config.backend_type {
             #[cfg(feature = "keyring-storage")]
             StorageBackendType::Keyring => {
-                KeyringStorage::new()
+                KeyringStorage::for_profile(profile)
                     .map(StorageInstance::Keyring)
                     .map_err(|_| AuthError::OAuthFailed(
                         "Configured to use keyring but it's unavailable. Delete ~/.config/chuck/config.json and run 'chuck auth' to reconfigure.".to_string()
@@ -97,7 +105,7 @@ impl StorageFactory {
                     .ok_or_else(|| AuthError::OAuthFailed(
                         "File storage configured but no path specified".to_string()
                     ))?;
-                CustomFileStorage::new(path.clone())
+                CustomFileStorage::new(profile_path(path, profile))
                     .map(StorageInstance::File)
             }
             #[cfg(not(feature = "keyring-storage"))]
@@ -109,10 +117,11 @@ impl StorageFactory {
         }
     }
 
-    fn create_auto_detect() -> Result<StorageInstance, AuthError> {
+    #[cfg_attr(not(feature = "keyring-storage"), allow(unused_variables))]
+    fn create_auto_detect(profile: Option<&str>) -> Result<StorageInstance, AuthError> {
         #[cfg(feature = "keyring-storage")]
         {
-            if let Ok(storage) = KeyringStorage::new() {
+            if let Ok(storage) = KeyringStorage::for_profile(profile) {
                 Self::save_config(StorageBackendType::Keyring, None)?;
                 return Ok(StorageInstance::Keyring(storage));
             }
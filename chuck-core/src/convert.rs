@@ -0,0 +1,321 @@
+//! Converts a DarwinCore Archive to a single CSV or Parquet file of its core
+//! records, optionally with each record's extension rows alongside it, for
+//! loading into R, Python, or a database without opening the app.
+
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::archive_validator::{meta_xml_name, DataFile};
+use crate::dwca_extension::DwcaExtension;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertFormat {
+    Csv,
+    Parquet,
+}
+
+impl ConvertFormat {
+    /// File extension for output in this format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Select expressions, each with a leading comma, that add every extension
+/// table to a query of occurrences as a column of the same name holding a
+/// list of its rows as structs. With `as_json` the list is a JSON array
+/// instead, for formats like CSV that can't hold nested values.
+/// `extension_tables` holds each table's name and the column that refers to
+/// `core_id_column` of occurrences.
+pub fn nested_extension_columns(
+    core_id_column: &str,
+    extension_tables: &[(&str, &str)],
+    as_json: bool,
+) -> String {
+    let quoted_core_id = quote_identifier(core_id_column);
+    extension_tables
+        .iter()
+        .map(|(table_name, ext_core_id_column)| {
+            let quoted_ext_core_id = quote_identifier(ext_core_id_column);
+            let list = if as_json {
+                format!("CAST(to_json(list({table_name})) AS VARCHAR)")
+            } else {
+                format!("list({table_name})")
+            };
+            format!(
+                ", (SELECT {list} FROM {table_name} WHERE {table_name}.{quoted_ext_core_id} = occurrences.{quoted_core_id}) AS {table_name}"
+            )
+        })
+        .collect()
+}
+
+/// read_csv arguments for a data file, each with a leading comma. Files
+/// without a header row get column names from meta.xml.
+fn read_csv_options(data_file: &DataFile, id_tag: &str) -> String {
+    let mut options = format!(
+        ", all_varchar = true, delim = {}, quote = {}",
+        quote_literal(&(data_file.delimiter as char).to_string()),
+        quote_literal(&data_file.quote.map(|q| (q as char).to_string()).unwrap_or_default()),
+    );
+    if data_file.header_lines == 0 {
+        options.push_str(", header = false");
+        let last_index = data_file
+            .fields
+            .iter()
+            .map(|(index, _)| *index)
+            .chain(data_file.id_index)
+            .max();
+        if let Some(last_index) = last_index {
+            let names: Vec<String> = (0..=last_index)
+                .map(|index| {
+                    let name = data_file
+                        .fields
+                        .iter()
+                        .find(|(field_index, _)| *field_index == index)
+                        .map(|(_, term)| term.clone())
+                        .or_else(|| (data_file.id_index == Some(index)).then(|| id_tag.to_string()))
+                        .unwrap_or_else(|| format!("column{index}"));
+                    quote_literal(&name)
+                })
+                .collect();
+            options.push_str(&format!(", names = [{}]", names.join(", ")));
+        }
+    } else {
+        options.push_str(&format!(", header = true, skip = {}", data_file.header_lines - 1));
+    }
+    options
+}
+
+/// Extracts a data file from the archive into `dir` and loads it into a
+/// table, returning the name of the column at `id_index`
+fn load_table<R: Read + Seek>(
+    conn: &duckdb::Connection,
+    archive: &mut zip::ZipArchive<R>,
+    entry_name: &str,
+    dir: &Path,
+    table_name: &str,
+    data_file: &DataFile,
+    id_tag: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let path = dir.join(format!("{table_name}.txt"));
+    std::io::copy(&mut archive.by_name(entry_name)?, &mut std::fs::File::create(&path)?)?;
+    let path = path.to_str().ok_or("Temporary path is not valid UTF-8")?;
+    conn.execute_batch(&format!(
+        "CREATE TABLE {table_name} AS SELECT * FROM read_csv({}{})",
+        quote_literal(path),
+        read_csv_options(data_file, id_tag)
+    ))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT column_name FROM information_schema.columns \
+         WHERE table_name = ? ORDER BY ordinal_position",
+    )?;
+    let columns: Vec<String> = stmt
+        .query_map([table_name], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    for (term, value) in &data_file.defaults {
+        if !columns.contains(term) {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {table_name} ADD COLUMN {} VARCHAR DEFAULT {}",
+                quote_identifier(term),
+                quote_literal(value)
+            ))?;
+        }
+    }
+    Ok(data_file.id_index.and_then(|index| columns.get(index).cloned()))
+}
+
+/// Writes the core records of the archive at `input` to `output` as CSV or
+/// Parquet, with every value as text, just as it is in the archive. With
+/// `flatten_extensions`, rows of supported extensions are added to the
+/// record they belong to as a column per extension. Returns the number of
+/// records written.
+pub fn convert_archive(
+    input: &Path,
+    output: &Path,
+    format: ConvertFormat,
+    flatten_extensions: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(input)?)?;
+    let meta_name = meta_xml_name(&archive).ok_or("Archive has no meta.xml")?;
+    let prefix = meta_name.trim_end_matches("meta.xml").to_string();
+    let mut meta_xml = String::new();
+    archive.by_name(&meta_name)?.read_to_string(&mut meta_xml)?;
+    let doc = roxmltree::Document::parse(&meta_xml)?;
+    let core = doc
+        .descendants()
+        .find(|n| n.has_tag_name("core"))
+        .and_then(|n| DataFile::from_node(n, true))
+        .ok_or("meta.xml has no core file location")?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let conn = duckdb::Connection::open_in_memory()?;
+    let core_id_column = load_table(
+        &conn,
+        &mut archive,
+        &format!("{prefix}{}", core.location),
+        temp_dir.path(),
+        "occurrences",
+        &core,
+        "id",
+    )?;
+
+    let mut extension_tables: Vec<(&str, String)> = vec![];
+    if flatten_extensions {
+        let core_id_column = core_id_column.as_deref().ok_or("meta.xml has no core id column")?;
+        for node in doc.descendants().filter(|n| n.has_tag_name("extension")) {
+            let row_type = node.attribute("rowType").unwrap_or_default();
+            let Some(extension) = DwcaExtension::from_row_type(row_type) else {
+                log::warn!("Skipping unsupported extension {row_type}");
+                continue;
+            };
+            let Some(data_file) = DataFile::from_node(node, false) else { continue };
+            let table_name = extension.table_name();
+            if extension_tables.iter().any(|(name, _)| *name == table_name) {
+                log::warn!("Skipping second {extension} extension in {}", data_file.location);
+                continue;
+            }
+            let entry_name = format!("{prefix}{}", data_file.location);
+            if archive.index_for_name(&entry_name).is_none() {
+                log::warn!("Skipping {extension} extension, {} is missing", data_file.location);
+                continue;
+            }
+            let ext_core_id_column = load_table(
+                &conn,
+                &mut archive,
+                &entry_name,
+                temp_dir.path(),
+                table_name,
+                &data_file,
+                "coreid",
+            )?
+            .ok_or_else(|| format!("meta.xml has no core id column for {}", data_file.location))?;
+            extension_tables.push((table_name, ext_core_id_column));
+        }
+        log::debug!("Nesting extensions under {core_id_column}: {extension_tables:?}");
+    }
+
+    let tables: Vec<(&str, &str)> =
+        extension_tables.iter().map(|(name, column)| (*name, column.as_str())).collect();
+    let extension_columns = match &core_id_column {
+        Some(core_id_column) => {
+            nested_extension_columns(core_id_column, &tables, format == ConvertFormat::Csv)
+        }
+        None => String::new(),
+    };
+    let copy_options = match format {
+        ConvertFormat::Csv => "FORMAT csv, HEADER true",
+        ConvertFormat::Parquet => "FORMAT parquet, COMPRESSION zstd",
+    };
+    let output = output.to_str().ok_or("Output path is not valid UTF-8")?;
+    let count = conn.execute(
+        &format!(
+            "COPY (SELECT *{extension_columns} FROM occurrences ORDER BY rowid) TO {} ({copy_options})",
+            quote_literal(output)
+        ),
+        [],
+    )?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        for (name, contents) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    const META_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="\t" fieldsEnclosedBy="" ignoreHeaderLines="1">
+    <files><location>occurrence.txt</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+    <field term="http://rs.tdwg.org/dwc/terms/basisOfRecord" default="HumanObservation"/>
+  </core>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Multimedia" fieldsTerminatedBy="," ignoreHeaderLines="0">
+    <files><location>multimedia.csv</location></files>
+    <coreid index="0"/>
+    <field index="1" term="http://purl.org/dc/terms/identifier"/>
+  </extension>
+</archive>"#;
+
+    #[test]
+    fn test_convert_archive_to_csv_with_extensions() {
+        let temp = tempfile::tempdir().unwrap();
+        let input = temp.path().join("archive.zip");
+        write_zip(
+            &input,
+            &[
+                ("meta.xml", META_XML),
+                ("occurrence.txt", "occurrenceID\tscientificName\n1\tQuercus \"live oak\"\n2\tPinus\n"),
+                ("multimedia.csv", "1,a.jpg\n1,b.jpg\n"),
+            ],
+        );
+        let output = temp.path().join("archive.csv");
+
+        let count = convert_archive(&input, &output, ConvertFormat::Csv, true).unwrap();
+
+        assert_eq!(count, 2);
+        let mut reader = csv::Reader::from_path(&output).unwrap();
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            vec!["occurrenceID", "scientificName", "basisOfRecord", "multimedia"]
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(&rows[0][1], "Quercus \"live oak\"");
+        assert_eq!(&rows[0][2], "HumanObservation");
+        let media: serde_json::Value = serde_json::from_str(&rows[0][3]).unwrap();
+        assert_eq!(media[1]["identifier"], "b.jpg");
+        assert_eq!(&rows[1][3], "");
+    }
+
+    #[test]
+    fn test_convert_archive_to_parquet_without_extensions() {
+        let temp = tempfile::tempdir().unwrap();
+        let input = temp.path().join("archive.zip");
+        write_zip(
+            &input,
+            &[
+                ("meta.xml", META_XML),
+                ("occurrence.txt", "occurrenceID\tscientificName\n1\tQuercus\n"),
+                ("multimedia.csv", "1,a.jpg\n"),
+            ],
+        );
+        let output = temp.path().join("archive.parquet");
+
+        assert_eq!(convert_archive(&input, &output, ConvertFormat::Parquet, false).unwrap(), 1);
+
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        let columns: Vec<String> = conn
+            .prepare(&format!(
+                "SELECT name FROM parquet_schema('{}') WHERE name <> 'duckdb_schema'",
+                output.display()
+            ))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|name| name.unwrap())
+            .collect();
+        assert_eq!(columns, vec!["occurrenceID", "scientificName", "basisOfRecord"]);
+    }
+}
@@ -8,10 +8,13 @@ use crate::darwin_core::{
     audiovisual::Audiovisual,
     comment::Comment,
     identification::Identification,
+    measurement_or_fact::MeasurementOrFact,
     meta::{self, Metadata},
     multimedia::Multimedia,
     occurrence::Occurrence,
+    verbatim::Verbatim,
 };
+use crate::download_report::ReportEntry;
 
 /// A DarwinCore Archive builder that can stream occurrence records and generate a compliant ZIP archive
 pub struct ArchiveBuilder {
@@ -20,22 +23,32 @@ pub struct ArchiveBuilder {
     /// The final destination path; the ZIP is written to a temp file and renamed here on success.
     output_path: PathBuf,
     occurrence_writer: csv::Writer<File>,
+    verbatim_writer: csv::Writer<File>,
     multimedia_writer: Option<csv::Writer<File>>,
     audiovisual_writer: Option<csv::Writer<File>>,
     identification_writer: Option<csv::Writer<File>>,
     comment_writer: Option<csv::Writer<File>>,
+    measurement_or_fact_writer: Option<csv::Writer<File>>,
     enabled_extensions: Vec<crate::DwcaExtension>,
     record_count: u64,
     multimedia_count: u64,
     audiovisual_count: u64,
     identification_count: u64,
     comment_count: u64,
+    measurement_or_fact_count: u64,
     occurrence_file_path: PathBuf,
+    verbatim_file_path: PathBuf,
     multimedia_file_path: PathBuf,
     audiovisual_file_path: PathBuf,
     identification_file_path: PathBuf,
     comment_file_path: PathBuf,
+    measurement_or_fact_file_path: PathBuf,
     metadata: Metadata,
+    /// Errors and skipped items, written to report.csv if there are any.
+    /// Created with the first entry.
+    report_writer: Option<csv::Writer<File>>,
+    report_count: u64,
+    report_file_path: PathBuf,
 }
 
 impl ArchiveBuilder {
@@ -50,10 +63,13 @@ impl ArchiveBuilder {
         let base_dir = output_path.parent().unwrap_or(Path::new("."));
         let temp_dir = TempDir::new_in(base_dir)?;
         let occurrence_file_path = temp_dir.path().join("occurrence.csv");
+        let verbatim_file_path = temp_dir.path().join(Verbatim::FILENAME);
         let multimedia_file_path = temp_dir.path().join("multimedia.csv");
         let audiovisual_file_path = temp_dir.path().join("audiovisual.csv");
         let identification_file_path = temp_dir.path().join("identification.csv");
         let comment_file_path = temp_dir.path().join("comment.csv");
+        let measurement_or_fact_file_path = temp_dir.path().join(MeasurementOrFact::FILENAME);
+        let report_file_path = temp_dir.path().join(ReportEntry::FILENAME);
 
         // Create media staging directory inside temp dir
         let media_dir_path = temp_dir.path().join("media");
@@ -76,27 +92,41 @@ impl ArchiveBuilder {
         occurrence_writer.write_record(Occurrence::csv_headers())?;
         occurrence_writer.flush()?;
 
+        let mut verbatim_writer = csv::WriterBuilder::new()
+            .has_headers(true)
+            .from_path(&verbatim_file_path)?;
+        verbatim_writer.write_record(Verbatim::csv_headers())?;
+        verbatim_writer.flush()?;
+
         Ok(Self {
             temp_dir,
             zip,
             output_path: output_path.to_path_buf(),
             occurrence_writer,
+            verbatim_writer,
             multimedia_writer: None,
             audiovisual_writer: None,
             identification_writer: None,
             comment_writer: None,
+            measurement_or_fact_writer: None,
             enabled_extensions: dwc_extensions,
             record_count: 0,
             multimedia_count: 0,
             audiovisual_count: 0,
             identification_count: 0,
             comment_count: 0,
+            measurement_or_fact_count: 0,
             occurrence_file_path,
+            verbatim_file_path,
             multimedia_file_path,
             audiovisual_file_path,
             identification_file_path,
             comment_file_path,
+            measurement_or_fact_file_path,
             metadata,
+            report_writer: None,
+            report_count: 0,
+            report_file_path,
         })
     }
 
@@ -142,6 +172,15 @@ impl ArchiveBuilder {
         Ok(())
     }
 
+    /// Add a batch of verbatim occurrence records to the archive
+    pub async fn add_verbatim(&mut self, verbatim: &[Verbatim]) -> Result<(), Box<dyn std::error::Error>> {
+        for record in verbatim {
+            self.verbatim_writer.write_record(record.to_csv_record())?;
+        }
+        self.verbatim_writer.flush()?;
+        Ok(())
+    }
+
     /// Add a batch of DarwinCore multimedia records to the archive
     pub async fn add_multimedia(&mut self, multimedia: &[Multimedia]) -> Result<(), Box<dyn std::error::Error>> {
         if multimedia.is_empty() {
@@ -273,12 +312,70 @@ impl ArchiveBuilder {
         Ok(())
     }
 
+    /// Add a batch of DarwinCore MeasurementOrFact records to the archive
+    pub async fn add_measurements_or_facts(
+        &mut self,
+        measurements_or_facts: &[MeasurementOrFact],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if measurements_or_facts.is_empty() {
+            return Ok(());
+        }
+
+        // Initialize the writer if this is the first batch
+        if self.measurement_or_fact_writer.is_none() {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(true)
+                .from_path(&self.measurement_or_fact_file_path)?;
+            writer.write_record(MeasurementOrFact::csv_headers())?;
+            writer.flush()?;
+            self.measurement_or_fact_writer = Some(writer);
+        }
+
+        if let Some(writer) = &mut self.measurement_or_fact_writer {
+            for measurement_or_fact in measurements_or_facts {
+                writer.write_record(measurement_or_fact.to_csv_record())?;
+                self.measurement_or_fact_count += 1;
+            }
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Record errors or skipped items for the archive's report
+    pub fn add_report_entries(
+        &mut self,
+        entries: impl IntoIterator<Item = ReportEntry>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut entries = entries.into_iter().peekable();
+        if entries.peek().is_none() {
+            return Ok(());
+        }
+        if self.report_writer.is_none() {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_path(&self.report_file_path)?;
+            writer.write_record(ReportEntry::csv_headers())?;
+            self.report_writer = Some(writer);
+        }
+        if let Some(writer) = &mut self.report_writer {
+            for entry in entries {
+                writer.write_record(entry.to_csv_record())?;
+                self.report_count += 1;
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
     /// Finish writing the archive. All media must have been added via `add_media_from_temp`
     /// before calling this; only CSV files and metadata are written here.
     pub async fn build(mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Ensure all CSV data is written
         self.occurrence_writer.flush()?;
         drop(self.occurrence_writer); // Close the file
+        self.verbatim_writer.flush()?;
+        drop(self.verbatim_writer);
 
         // Close multimedia writer if it exists
         if let Some(mut writer) = self.multimedia_writer.take() {
@@ -304,6 +401,19 @@ impl ArchiveBuilder {
             drop(writer);
         }
 
+        // Close measurement or fact writer if it exists
+        if let Some(mut writer) = self.measurement_or_fact_writer.take() {
+            writer.flush()?;
+            drop(writer);
+        }
+
+        // Close report writer if anything was reported
+        let has_report = self.report_writer.is_some();
+        if let Some(mut writer) = self.report_writer.take() {
+            writer.flush()?;
+            drop(writer);
+        }
+
         // Generate meta.xml (includes extensions based on enabled extensions and record counts)
         let meta_xml = meta::generate_meta_xml(&self.enabled_extensions);
         let meta_file_path = self.temp_dir.path().join("meta.xml");
@@ -337,8 +447,11 @@ impl ArchiveBuilder {
 
         // Add occurrence.csv to ZIP
         self.zip.start_file("occurrence.csv", options)?;
-        let occurrence_content = std::fs::read(&self.occurrence_file_path)?;
-        self.zip.write_all(&occurrence_content)?;
+        copy_into_zip(&mut self.zip, &self.occurrence_file_path)?;
+
+        // Add verbatim.csv to ZIP
+        self.zip.start_file(Verbatim::FILENAME, options)?;
+        copy_into_zip(&mut self.zip, &self.verbatim_file_path)?;
 
         // Add extension CSVs to ZIP for all enabled extensions, even if empty
         let ext_specs: &[(crate::DwcaExtension, &str, &std::path::Path, Vec<&str>)] = &[
@@ -366,6 +479,12 @@ impl ArchiveBuilder {
                 &self.comment_file_path,
                 Comment::csv_headers(),
             ),
+            (
+                crate::DwcaExtension::MeasurementOrFact,
+                MeasurementOrFact::FILENAME,
+                &self.measurement_or_fact_file_path,
+                MeasurementOrFact::csv_headers(),
+            ),
         ];
 
         for (ext, zip_name, file_path, headers) in ext_specs {
@@ -381,7 +500,13 @@ impl ArchiveBuilder {
                 wtr.flush()?;
             }
             self.zip.start_file(*zip_name, options)?;
-            self.zip.write_all(&std::fs::read(file_path)?)?;
+            copy_into_zip(&mut self.zip, file_path)?;
+        }
+
+        // Add report.csv if anything was skipped or failed
+        if has_report {
+            self.zip.start_file(ReportEntry::FILENAME, options)?;
+            copy_into_zip(&mut self.zip, &self.report_file_path)?;
         }
 
         // Finish ZIP (writes central directory)
@@ -394,15 +519,24 @@ impl ArchiveBuilder {
 
         log::info!(
             "DarwinCore Archive complete: {} records, {} multimedia, {} audiovisual, \
-            {} identifications, {} comments",
+            {} identifications, {} comments, {} measurements or facts, {} report entries",
             self.record_count, self.multimedia_count, self.audiovisual_count,
-            self.identification_count, self.comment_count,
+            self.identification_count, self.comment_count, self.measurement_or_fact_count,
+            self.report_count,
         );
 
         Ok(())
     }
 }
 
+/// Copies a file into the open ZIP entry a buffer at a time, so large CSVs
+/// don't have to fit in memory
+fn copy_into_zip(zip: &mut ZipWriter<File>, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = std::io::BufReader::new(File::open(path)?);
+    std::io::copy(&mut file, zip)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -520,6 +654,7 @@ mod tests {
             DwcaExtension::Identifications,
             DwcaExtension::SimpleMultimedia,
             DwcaExtension::Audiovisual,
+            DwcaExtension::MeasurementOrFact,
         ])
         .await;
 
@@ -531,6 +666,8 @@ mod tests {
             "multimedia.csv missing from ZIP: {names:?}");
         assert!(names.contains(&"audiovisual.csv".to_string()),
             "audiovisual.csv missing from ZIP: {names:?}");
+        assert!(names.contains(&"measurementorfact.csv".to_string()),
+            "measurementorfact.csv missing from ZIP: {names:?}");
     }
 
     #[tokio::test]
@@ -540,5 +677,62 @@ mod tests {
         assert!(!names.contains(&"identification.csv".to_string()));
         assert!(!names.contains(&"multimedia.csv".to_string()));
         assert!(!names.contains(&"audiovisual.csv".to_string()));
+        assert!(!names.contains(&"measurementorfact.csv".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_report_csv_written_when_entries_present() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let mut builder = ArchiveBuilder::new(vec![], Metadata::default(), tmp.path()).unwrap();
+        builder.add_report_entries(vec![ReportEntry::skip(
+            "photo",
+            "11",
+            "https://www.inaturalist.org/observations/1",
+            "license all-rights-reserved",
+        )]).unwrap();
+        builder.build().await.unwrap();
+
+        let file = std::fs::File::open(tmp.path()).unwrap();
+        let mut archive = ZipArchive::new(file).unwrap();
+        let mut report = archive.by_name(ReportEntry::FILENAME).expect("report.csv missing");
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut report, &mut contents).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], "kind,itemType,itemID,occurrenceID,reason");
+        assert_eq!(
+            lines[1],
+            "skip,photo,11,https://www.inaturalist.org/observations/1,license all-rights-reserved"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_csv_appends_entries_across_batches() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let mut builder = ArchiveBuilder::new(vec![], Metadata::default(), tmp.path()).unwrap();
+        for photo_id in ["11", "12"] {
+            builder.add_report_entries(vec![ReportEntry::skip(
+                "photo",
+                photo_id,
+                "https://www.inaturalist.org/observations/1",
+                "license all-rights-reserved",
+            )]).unwrap();
+        }
+        builder.add_report_entries(vec![]).unwrap();
+        builder.build().await.unwrap();
+
+        let file = std::fs::File::open(tmp.path()).unwrap();
+        let mut archive = ZipArchive::new(file).unwrap();
+        let mut report = archive.by_name(ReportEntry::FILENAME).expect("report.csv missing");
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut report, &mut contents).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].starts_with("skip,photo,12,"));
+    }
+
+    #[tokio::test]
+    async fn test_report_csv_absent_when_no_entries() {
+        let names = zip_file_names(vec![]).await;
+        assert!(!names.contains(&ReportEntry::FILENAME.to_string()));
     }
 }
@@ -1,6 +1,6 @@
 use inaturalist::models::{Observation, ShowTaxon};
 use std::collections::HashMap;
-use super::{Occurrence, Multimedia, Audiovisual, Identification, Comment};
+use super::{Occurrence, Multimedia, Audiovisual, Identification, Comment, Verbatim};
 
 // GBIF-valid life stages
 const GBIF_LIFE_STAGES: &[&str] = &[
@@ -46,6 +46,30 @@ fn extract_reproductive_condition(obs: &Observation) -> Option<String> {
     extract_annotation_value(obs, "Flowers and Fruits", |_| true)
 }
 
+/// Splits the time portion of an ISO 8601 datetime like
+/// `2024-01-15T23:30:00-08:00` into the local time with its offset
+/// (`23:30:00-08:00`) and the offset alone (`-08:00`). A trailing `Z` is
+/// normalized to `+00:00` so the offset is always explicit. Returns
+/// `(None, None)` if there is no time portion.
+fn split_event_time(datetime: &str) -> (Option<String>, Option<String>) {
+    let Some(time_part) = datetime.split('T').nth(1) else {
+        return (None, None);
+    };
+    if let Some(time) = time_part.strip_suffix('Z') {
+        return (Some(format!("{time}+00:00")), Some("+00:00".to_string()));
+    }
+    // Skip the first character so we never mistake a leading sign for an offset
+    let offset_start = time_part
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '+' || *c == '-')
+        .map(|(i, _)| i);
+    match offset_start {
+        Some(i) => (Some(time_part.to_string()), Some(time_part[i..].to_string())),
+        None => (Some(time_part.to_string()), None),
+    }
+}
+
 /// Helper function to extract annotation values with a validation function
 fn extract_annotation_value<F>(
     obs: &Observation,
@@ -88,8 +112,66 @@ where
     }
 }
 
+/// Why the private coordinates of an observation were available to whoever
+/// downloaded it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateAccess {
+    /// The observation is their own
+    Observer,
+    /// The observer lets them see private coordinates, e.g. as a curator of
+    /// a project the observer joined, or as someone the observer trusts
+    Permission,
+}
+
+impl CoordinateAccess {
+    /// How the user with `viewer_id` came to see the private coordinates of
+    /// `obs`, or None if it has none. The API only includes them when the
+    /// request's JWT grants access, so without a viewer it's assumed to have
+    /// been granted by the observer.
+    pub fn of(obs: &Observation, viewer_id: Option<i32>) -> Option<Self> {
+        obs.private_geojson.as_ref()?;
+        let observer_id = obs.user.as_ref().and_then(|user| user.id);
+        if viewer_id.is_some() && viewer_id == observer_id {
+            Some(Self::Observer)
+        } else {
+            Some(Self::Permission)
+        }
+    }
+}
+
+/// Explains why an observation's coordinates were obscured or hidden, and
+/// why they're included anyway if they are
+fn information_withheld(obs: &Observation, access: Option<CoordinateAccess>) -> Option<String> {
+    let reason = match (obs.geoprivacy.as_deref(), obs.taxon_geoprivacy.as_deref()) {
+        (Some("private"), _) => "hidden by the observer",
+        (Some("obscured"), _) => "obscured by the observer",
+        (None, Some("private")) => "hidden due to iNaturalist taxon geoprivacy",
+        (None, Some("obscured")) => "obscured due to iNaturalist taxon geoprivacy",
+        _ => return None,
+    };
+    Some(match access {
+        Some(CoordinateAccess::Observer) => {
+            format!("Coordinates {reason} but included here because the observer downloaded them")
+        }
+        Some(CoordinateAccess::Permission) => {
+            format!("Coordinates {reason} but included here with the observer's permission")
+        }
+        None => format!("Coordinates {reason}"),
+    })
+}
+
 impl From<(&Observation, &HashMap<i32, ShowTaxon>)> for Occurrence {
     fn from((obs, taxa_hash): (&Observation, &HashMap<i32, ShowTaxon>)) -> Self {
+        Self::from((obs, taxa_hash, None))
+    }
+}
+
+// Convert with the iNat user ID of whoever downloaded the observation, which
+// decides how information_withheld explains private coordinates
+impl From<(&Observation, &HashMap<i32, ShowTaxon>, Option<i32>)> for Occurrence {
+    fn from(
+        (obs, taxa_hash, viewer_id): (&Observation, &HashMap<i32, ShowTaxon>, Option<i32>),
+    ) -> Self {
         // Extract coordinates if available
 
         let geojson = obs.private_geojson.as_ref().or(obs.geojson.as_ref());
@@ -229,55 +311,18 @@ impl From<(&Observation, &HashMap<i32, ShowTaxon>)> for Occurrence {
         let sex = extract_sex(obs);
         let reproductive_condition = extract_reproductive_condition(obs);
 
-        let information_withheld = match obs.geoprivacy.as_deref() {
-            Some("private") => {
-                if pvt_coords_available {
-                    Some("Coordinates hidden by the observer but included \
-                        here with the observer's permission".to_string())
-                } else {
-                    Some("Coordinates hidden by the observer".to_string())
-                }
-            },
-            Some("obscured") => {
-                if pvt_coords_available {
-                    Some("Coordinates obscured by the observer but included \
-                        here with the observer's permission".to_string())
-                } else {
-                    Some("Coordinates obscured by the observer".to_string())
-                }
-            }
-            None => {
-                match obs.taxon_geoprivacy.as_deref() {
-                    Some("private") => {
-                        if pvt_coords_available {
-                            Some("Coordinates hidden due to iNaturalist \
-                                taxon geoprivacy but included here with \
-                                the observer's permission".to_string())
-                        } else {
-                            Some("Coordinates hidden due to iNaturalist \
-                                taxon geoprivacy".to_string())
-                        }
-                    },
-                    Some("obscured") => {
-                        if pvt_coords_available {
-                            Some("Coordinates obscured due to iNaturalist \
-                                taxon geoprivacy but included here with the \
-                                observer's permission".to_string())
-                        } else {
-                            Some("Coordinates obscured due to iNaturalist \
-                                taxon geoprivacy".to_string())
-                        }
-                    },
-                    None => None,
-                    _ => None,
-                }
-            },
-            _ => None,
-        };
+        let information_withheld = information_withheld(obs, CoordinateAccess::of(obs, viewer_id));
 
         // Extract license information
         let license = obs.license_code.clone();
 
+        // Keep the UTC offset with the time so it doesn't get lost once the
+        // time is split from the date
+        let (event_time, event_time_zone_offset) = obs.time_observed_at
+            .as_deref()
+            .map(split_event_time)
+            .unwrap_or((None, None));
+
         Occurrence {
             id: None,
             occurrence_id: obs.id.map(|id| format!("https://www.inaturalist.org/observations/{id}")).unwrap_or_default(),
@@ -314,13 +359,8 @@ impl From<(&Observation, &HashMap<i32, ShowTaxon>)> for Occurrence {
             information_withheld,
             modified: obs.updated_at.clone(), // Use the observation's updated timestamp
             captive: obs.captive, // Use the observation's captive flag
-            event_time: obs.time_observed_at.as_ref().and_then(|datetime| {
-                // Extract time portion from ISO 8601 datetime string
-                datetime.split('T').nth(1).map(|time_part| {
-                    // Remove Z suffix if present and return just the time
-                    time_part.trim_end_matches('Z').to_string()
-                })
-            }),
+            event_time,
+            event_time_zone_offset,
             verbatim_event_date: obs.observed_on_string.clone(),
             verbatim_locality: obs.private_place_guess.clone().or(obs.place_guess.clone()),
             continent: None,
@@ -529,6 +569,55 @@ impl From<&Observation> for Occurrence {
     }
 }
 
+/// Fills references with the observation's URL and taxonConceptID with its
+/// taxon's URL, so anyone reading the occurrences can click through to the
+/// live records on iNat
+pub fn add_inat_links(occurrence: &mut Occurrence, obs: &Observation) {
+    occurrence.references = obs.id.map(|id| format!("https://www.inaturalist.org/observations/{id}"));
+    occurrence.taxon_concept_id = obs
+        .taxon
+        .as_ref()
+        .and_then(|taxon| taxon.id)
+        .map(|id| format!("https://www.inaturalist.org/taxa/{id}"));
+}
+
+// Map an iNaturalist observation to its raw API values, without the
+// interpretation in the Occurrence conversion
+impl From<&Observation> for Verbatim {
+    fn from(obs: &Observation) -> Self {
+        let pvt_coords_available = obs.private_geojson.is_some();
+        let coordinates = obs.private_geojson.as_ref()
+            .or(obs.geojson.as_ref())
+            .and_then(|geojson| geojson.coordinates.as_ref())
+            .filter(|coordinates| coordinates.len() >= 2);
+        let acc = if pvt_coords_available {
+            obs.positional_accuracy
+        } else {
+            obs.public_positional_accuracy
+        };
+        let taxon = obs.taxon.as_ref();
+
+        Verbatim {
+            occurrence_id: obs.id.map(|id| format!("https://www.inaturalist.org/observations/{id}")).unwrap_or_default(),
+            event_date: obs.time_observed_at.clone().or(obs.observed_on.clone()),
+            verbatim_event_date: obs.observed_on_string.clone(),
+            decimal_latitude: coordinates.map(|c| c[1].to_string()),
+            decimal_longitude: coordinates.map(|c| c[0].to_string()),
+            coordinate_uncertainty_in_meters: acc.map(|acc| acc.to_string()),
+            verbatim_locality: obs.private_place_guess.clone().or(obs.place_guess.clone()),
+            scientific_name: taxon.and_then(|t| t.name.clone()),
+            taxon_rank: taxon.and_then(|t| t.rank.clone()),
+            taxon_id: taxon.and_then(|t| t.id).map(|id| id.to_string()),
+            vernacular_name: taxon.and_then(|t| t.preferred_common_name.clone()),
+            recorded_by: obs.user.as_ref().and_then(|user| user.login.clone()),
+            occurrence_remarks: obs.description.clone(),
+            captive: obs.captive.map(|captive| captive.to_string()),
+            license: obs.license_code.clone(),
+            modified: obs.updated_at.clone(),
+        }
+    }
+}
+
 // Map iNaturalist photo with context to a DarwinCore multimedia record
 impl From<(&inaturalist::models::Photo, &str, Option<&inaturalist::models::User>, &HashMap<i32, String>)> for Multimedia {
     fn from((photo, occurrence_id, user, photo_mapping): (&inaturalist::models::Photo, &str, Option<&inaturalist::models::User>, &HashMap<i32, String>)) -> Self {
@@ -994,6 +1083,100 @@ mod tests {
         assert_eq!(occurrence.decimal_longitude, Some(PRIVATE_LNG));
     }
 
+    fn obscured_obs_by(observer_id: i32, private: bool) -> Observation {
+        let mut obs = Observation::default();
+        obs.geoprivacy = Some("obscured".to_string());
+        obs.user = Some(Box::new(inaturalist::models::User {
+            id: Some(observer_id),
+            ..Default::default()
+        }));
+        if private {
+            obs.private_geojson = Some(Box::new(inaturalist::models::PointGeoJson {
+                r#type: Some("Point".to_string()),
+                coordinates: Some(vec![PRIVATE_LNG, PRIVATE_LAT]),
+            }));
+        }
+        obs
+    }
+
+    #[test]
+    fn test_information_withheld_when_observer_downloads_own_data() {
+        let obs = obscured_obs_by(42, true);
+
+        let occurrence = Occurrence::from((&obs, &HashMap::new(), Some(42)));
+
+        assert_eq!(occurrence.decimal_latitude, Some(PRIVATE_LAT));
+        assert_eq!(
+            occurrence.information_withheld.as_deref(),
+            Some("Coordinates obscured by the observer but included here because the observer downloaded them")
+        );
+    }
+
+    #[test]
+    fn test_information_withheld_when_observer_grants_access() {
+        // e.g. a curator of a project the observer joined, or someone they trust
+        let obs = obscured_obs_by(42, true);
+
+        let occurrence = Occurrence::from((&obs, &HashMap::new(), Some(7)));
+
+        assert_eq!(occurrence.decimal_latitude, Some(PRIVATE_LAT));
+        assert_eq!(CoordinateAccess::of(&obs, Some(7)), Some(CoordinateAccess::Permission));
+        assert_eq!(
+            occurrence.information_withheld.as_deref(),
+            Some("Coordinates obscured by the observer but included here with the observer's permission")
+        );
+    }
+
+    #[test]
+    fn test_information_withheld_without_access() {
+        let mut obs = obscured_obs_by(42, false);
+        obs.geojson = Some(Box::new(inaturalist::models::PointGeoJson {
+            r#type: Some("Point".to_string()),
+            coordinates: Some(vec![LNG, LAT]),
+        }));
+
+        let occurrence = Occurrence::from((&obs, &HashMap::new(), Some(7)));
+
+        assert_eq!(occurrence.decimal_latitude, Some(LAT));
+        assert_eq!(CoordinateAccess::of(&obs, Some(7)), None);
+        assert_eq!(
+            occurrence.information_withheld.as_deref(),
+            Some("Coordinates obscured by the observer")
+        );
+    }
+
+    #[test]
+    fn test_information_withheld_for_taxon_geoprivacy() {
+        let mut obs = obscured_obs_by(42, true);
+        obs.geoprivacy = None;
+        obs.taxon_geoprivacy = Some("private".to_string());
+
+        let occurrence = Occurrence::from((&obs, &HashMap::new(), Some(42)));
+
+        assert_eq!(
+            occurrence.information_withheld.as_deref(),
+            Some("Coordinates hidden due to iNaturalist taxon geoprivacy but included here because the observer downloaded them")
+        );
+    }
+
+    #[test]
+    fn test_add_inat_links() {
+        let mut obs = Observation::default();
+        obs.id = Some(123);
+        let mut occurrence = Occurrence::from(&obs);
+        assert_eq!(occurrence.references, None);
+
+        add_inat_links(&mut occurrence, &obs);
+        assert_eq!(occurrence.references.as_deref(), Some("https://www.inaturalist.org/observations/123"));
+        assert_eq!(occurrence.taxon_concept_id, None);
+
+        let mut taxon = inaturalist::models::ObservationTaxon::default();
+        taxon.id = Some(47148);
+        obs.taxon = Some(Box::new(taxon));
+        add_inat_links(&mut occurrence, &obs);
+        assert_eq!(occurrence.taxon_concept_id.as_deref(), Some("https://www.inaturalist.org/taxa/47148"));
+    }
+
     #[test]
     fn test_decimal_latitude_from_public_geojson_when_no_private() {
         let mut obs = Observation::default();
@@ -1189,6 +1372,48 @@ mod tests {
         assert_eq!(occurrence.day, Some(2));
     }
 
+    #[test]
+    fn test_event_time_retains_utc_offset() {
+        let mut obs = Observation::default();
+        obs.observed_on = Some("2024-01-15".to_string());
+        obs.time_observed_at = Some("2024-01-15T23:30:00-08:00".to_string());
+
+        let occurrence = Occurrence::from(&obs);
+
+        assert_eq!(occurrence.event_time, Some("23:30:00-08:00".to_string()));
+        assert_eq!(occurrence.event_time_zone_offset, Some("-08:00".to_string()));
+    }
+
+    #[test]
+    fn test_event_time_normalizes_z_to_explicit_offset() {
+        let mut obs = Observation::default();
+        obs.time_observed_at = Some("2024-01-16T07:30:00Z".to_string());
+
+        let occurrence = Occurrence::from(&obs);
+
+        assert_eq!(occurrence.event_time, Some("07:30:00+00:00".to_string()));
+        assert_eq!(occurrence.event_time_zone_offset, Some("+00:00".to_string()));
+    }
+
+    #[test]
+    fn test_event_time_without_offset() {
+        let mut obs = Observation::default();
+        obs.time_observed_at = Some("2024-01-15T12:00:00".to_string());
+
+        let occurrence = Occurrence::from(&obs);
+
+        assert_eq!(occurrence.event_time, Some("12:00:00".to_string()));
+        assert_eq!(occurrence.event_time_zone_offset, None);
+    }
+
+    #[test]
+    fn test_event_time_none_without_time_observed_at() {
+        let occurrence = Occurrence::from(&Observation::default());
+
+        assert_eq!(occurrence.event_time, None);
+        assert_eq!(occurrence.event_time_zone_offset, None);
+    }
+
     #[test]
     fn test_additional_taxonomic_ranks() {
         use inaturalist::models::ObservationTaxon;
@@ -1542,6 +1767,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verbatim_keeps_raw_values() {
+        let mut obs = Observation::default();
+        obs.id = Some(123);
+        obs.observed_on = Some("2024-01-15".to_string());
+        obs.time_observed_at = Some("2024-01-15T23:30:00-08:00".to_string());
+        obs.observed_on_string = Some("Jan 15, 2024 11:30 PM PST".to_string());
+        obs.license_code = Some("cc-by".to_string());
+        obs.captive = Some(false);
+        let mut taxon = ObservationTaxon::default();
+        taxon.id = Some(456);
+        obs.taxon = Some(Box::new(taxon));
+
+        let verbatim = Verbatim::from(&obs);
+
+        assert_eq!(verbatim.occurrence_id, Occurrence::from(&obs).occurrence_id);
+        assert_eq!(verbatim.event_date.as_deref(), Some("2024-01-15T23:30:00-08:00"));
+        assert_eq!(verbatim.verbatim_event_date.as_deref(), Some("Jan 15, 2024 11:30 PM PST"));
+        assert_eq!(verbatim.taxon_id.as_deref(), Some("456"));
+        assert_eq!(verbatim.license.as_deref(), Some("cc-by"));
+        assert_eq!(verbatim.captive.as_deref(), Some("false"));
+        assert_eq!(verbatim.to_csv_record().len(), Verbatim::csv_headers().len());
+    }
+
     #[test]
     fn test_comment_conversion() {
         use inaturalist::models::{Comment as InatComment, User};
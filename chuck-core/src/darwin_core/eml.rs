@@ -0,0 +1,766 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::Range;
+
+use chrono::Utc;
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+
+use crate::darwin_core::meta::Metadata;
+
+const DEFAULT_TITLE: &str = "Chuck DarwinCore Archive";
+const DEFAULT_ABSTRACT: &str = "Observations exported from iNaturalist";
+const DEFAULT_ORGANIZATION: &str = "Chuck";
+
+/// Order of `<dataset>` children in the EML schema. Validators like the IPT
+/// reject documents with elements out of order, so added elements go in
+/// their slot.
+const DATASET_ELEMENT_ORDER: &[&str] = &[
+    "alternateIdentifier",
+    "shortName",
+    "title",
+    "creator",
+    "metadataProvider",
+    "associatedParty",
+    "pubDate",
+    "language",
+    "series",
+    "abstract",
+    "keywordSet",
+    "additionalInfo",
+    "intellectualRights",
+    "licensed",
+    "distribution",
+    "coverage",
+    "purpose",
+    "introduction",
+    "gettingStarted",
+    "acknowledgements",
+    "maintenance",
+    "contact",
+    "publisher",
+    "pubPlace",
+    "methods",
+    "project",
+];
+
+/// `<dataset>` children with fields in Eml, in schema order
+const EDITABLE_ELEMENTS: &[&str] = &[
+    "alternateIdentifier",
+    "title",
+    "creator",
+    "metadataProvider",
+    "pubDate",
+    "language",
+    "abstract",
+    "intellectualRights",
+    "coverage",
+    "contact",
+];
+
+/// A person or organization, e.g. a creator or contact
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmlParty {
+    pub given_name: Option<String>,
+    pub sur_name: Option<String>,
+    pub organization_name: Option<String>,
+    pub email: Option<String>,
+    /// Identifier in `user_id_directory`, e.g. an ORCID iD
+    pub user_id: Option<String>,
+    pub user_id_directory: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeographicCoverage {
+    pub description: String,
+    pub west: f64,
+    pub east: f64,
+    pub north: f64,
+    pub south: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemporalCoverage {
+    /// ISO 8601 date
+    pub begin_date: String,
+    /// ISO 8601 date, or None for a single date
+    pub end_date: Option<String>,
+}
+
+/// The parts of an EML document people usually edit. Parsed documents keep
+/// everything else, and any elements whose fields weren't changed, exactly
+/// as they were.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Eml {
+    /// Other identifiers for the dataset, e.g. a DOI as https://doi.org/...
+    /// Not deserialized so edits from the app can't drop them.
+    #[serde(skip_deserializing)]
+    pub alternate_identifiers: Vec<String>,
+    pub title: String,
+    pub abstract_paragraphs: Vec<String>,
+    pub creators: Vec<EmlParty>,
+    pub metadata_providers: Vec<EmlParty>,
+    pub contacts: Vec<EmlParty>,
+    /// Text of intellectualRights, e.g. "This work is licensed under CC BY 4.0"
+    pub license: Option<String>,
+    pub geographic_coverage: Option<GeographicCoverage>,
+    pub temporal_coverage: Option<TemporalCoverage>,
+    /// ISO 8601 date the archive was published
+    pub pub_date: Option<String>,
+    pub language: Option<String>,
+    #[serde(skip)]
+    source: Option<Box<EmlSource>>,
+}
+
+/// Where the parts of a parsed document are, so to_xml can reuse them
+#[derive(Debug, Clone, PartialEq)]
+struct EmlSource {
+    xml: String,
+    /// Root start tag, e.g. `<eml:eml xmlns:eml="..." packageId="...">`
+    root_start: Range<usize>,
+    root_name: String,
+    /// Root children other than dataset, e.g. additionalMetadata
+    before_dataset: Vec<Range<usize>>,
+    after_dataset: Vec<Range<usize>>,
+    /// (local name, range) of each dataset child
+    dataset_children: Vec<(String, Range<usize>)>,
+    /// Children of coverage besides the geographic and temporal coverage in
+    /// Eml, e.g. taxonomicCoverage
+    other_coverage: Vec<Range<usize>>,
+    /// Fields as parsed, to tell which ones have been edited
+    original: Eml,
+}
+
+impl Eml {
+    /// EML for an archive Chuck creates, with Chuck as the creator
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let chuck = EmlParty {
+            organization_name: Some(DEFAULT_ORGANIZATION.to_string()),
+            ..Default::default()
+        };
+        let abstract_paragraphs = if metadata.abstract_lines.is_empty() {
+            vec![DEFAULT_ABSTRACT.to_string()]
+        } else {
+            metadata.abstract_lines.clone()
+        };
+        Self {
+            title: DEFAULT_TITLE.to_string(),
+            abstract_paragraphs,
+            creators: vec![chuck.clone()],
+            metadata_providers: vec![chuck.clone()],
+            contacts: vec![chuck],
+            pub_date: Some(Utc::now().format("%Y-%m-%d").to_string()),
+            language: Some("en".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Parses an EML document. Elements Eml doesn't have fields for are kept
+    /// for to_xml.
+    pub fn parse(xml: &str) -> Result<Self, roxmltree::Error> {
+        let doc = roxmltree::Document::parse(xml)?;
+        let root = doc.root_element();
+        let mut eml = Eml::default();
+        let mut dataset_children = Vec::new();
+        let mut other_coverage = Vec::new();
+        let mut before_dataset = Vec::new();
+        let mut after_dataset = Vec::new();
+        let mut seen_dataset = false;
+
+        for child in root.children().filter(Node::is_element) {
+            if child.has_tag_name("dataset") && !seen_dataset {
+                seen_dataset = true;
+                for element in child.children().filter(Node::is_element) {
+                    let name = element.tag_name().name();
+                    dataset_children.push((name.to_string(), element.range()));
+                    eml.read_dataset_element(element, &mut other_coverage);
+                }
+            } else if seen_dataset {
+                after_dataset.push(child.range());
+            } else {
+                before_dataset.push(child.range());
+            }
+        }
+
+        // An empty root has nothing to keep
+        let Some(first_child) = root.first_child() else {
+            return Ok(eml);
+        };
+        let root_start = root.range().start..first_child.range().start;
+        let root_name = xml[root_start.clone()]
+            .trim_start_matches('<')
+            .split(|c: char| c.is_whitespace() || c == '>')
+            .next()
+            .unwrap_or("eml:eml")
+            .to_string();
+
+        let original = eml.clone();
+        eml.source = Some(Box::new(EmlSource {
+            xml: xml.to_string(),
+            root_start,
+            root_name,
+            before_dataset,
+            after_dataset,
+            dataset_children,
+            other_coverage,
+            original,
+        }));
+        Ok(eml)
+    }
+
+    /// Replaces the editable fields with those of `edits`, keeping the rest
+    /// of the parsed document and its alternate identifiers
+    pub fn apply(&mut self, edits: Eml) {
+        let source = self.source.take();
+        let alternate_identifiers = std::mem::take(&mut self.alternate_identifiers);
+        *self = Eml { source, alternate_identifiers, ..edits };
+    }
+
+    /// Fills in elements the GBIF EML profile requires but the document is
+    /// missing, so an IPT accepts it. Missing people are copied from the
+    /// creators, contacts, or metadata providers it does have, or else set
+    /// to Chuck, and the rest get from_metadata's defaults. The license is
+    /// left for the publisher to choose.
+    pub fn fill_gbif_required_elements(&mut self) {
+        let defaults = Eml::from_metadata(&Metadata { abstract_lines: vec![], inat_query: None });
+        if self.title.trim().is_empty() {
+            self.title = defaults.title;
+        }
+        if self.abstract_paragraphs.iter().all(|p| p.trim().is_empty()) {
+            self.abstract_paragraphs = defaults.abstract_paragraphs;
+        }
+        let people = [&self.creators, &self.contacts, &self.metadata_providers]
+            .into_iter()
+            .find(|parties| !parties.is_empty())
+            .cloned()
+            .unwrap_or(defaults.creators);
+        for parties in [&mut self.creators, &mut self.contacts, &mut self.metadata_providers] {
+            if parties.is_empty() {
+                parties.clone_from(&people);
+            }
+        }
+        if self.pub_date.is_none() {
+            self.pub_date = defaults.pub_date;
+        }
+        if self.language.is_none() {
+            self.language = defaults.language;
+        }
+    }
+
+    /// Adds an alternate identifier unless the dataset already has it
+    pub fn add_alternate_identifier(&mut self, identifier: &str) {
+        if !self.alternate_identifiers.iter().any(|id| id == identifier) {
+            self.alternate_identifiers.push(identifier.to_string());
+        }
+    }
+
+    fn read_dataset_element(&mut self, element: Node, other_coverage: &mut Vec<Range<usize>>) {
+        match element.tag_name().name() {
+            "alternateIdentifier" => self.alternate_identifiers.push(text(element)),
+            "title" if self.title.is_empty() => self.title = text(element),
+            "creator" => self.creators.push(parse_party(element)),
+            "metadataProvider" => self.metadata_providers.push(parse_party(element)),
+            "contact" => self.contacts.push(parse_party(element)),
+            "pubDate" if self.pub_date.is_none() => self.pub_date = Some(text(element)),
+            "language" if self.language.is_none() => self.language = Some(text(element)),
+            "abstract" if self.abstract_paragraphs.is_empty() => {
+                self.abstract_paragraphs = paragraphs(element);
+            }
+            "intellectualRights" if self.license.is_none() => {
+                self.license = Some(text(element));
+            }
+            "coverage" if self.geographic_coverage.is_none() && self.temporal_coverage.is_none() => {
+                for coverage in element.children().filter(Node::is_element) {
+                    let parsed = match coverage.tag_name().name() {
+                        "geographicCoverage" if self.geographic_coverage.is_none() => {
+                            self.geographic_coverage = parse_geographic_coverage(coverage);
+                            self.geographic_coverage.is_some()
+                        }
+                        "temporalCoverage" if self.temporal_coverage.is_none() => {
+                            self.temporal_coverage = parse_temporal_coverage(coverage);
+                            self.temporal_coverage.is_some()
+                        }
+                        _ => false,
+                    };
+                    if !parsed {
+                        other_coverage.push(coverage.range());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Serializes to EML, reusing the parsed document where possible
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let root_name = match &self.source {
+            Some(source) => {
+                xml.push_str(&source.xml[source.root_start.clone()]);
+                source.root_name.as_str()
+            }
+            None => {
+                let package_id = format!("darwincore-archive-{}", Utc::now().format("%Y%m%d%H%M%S"));
+                write!(
+                    xml,
+                    r#"<eml:eml xmlns:eml="eml://ecoinformatics.org/eml-2.1.1"
+  xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+  xsi:schemaLocation="eml://ecoinformatics.org/eml-2.1.1 http://rs.gbif.org/schema/eml-gbif-profile/1.1/eml.xsd"
+  packageId="{package_id}"
+  system="http://gbif.org"
+  scope="system">"#
+                )
+                .unwrap();
+                "eml:eml"
+            }
+        };
+
+        if let Some(source) = &self.source {
+            for range in &source.before_dataset {
+                write!(xml, "\n  {}", &source.xml[range.clone()]).unwrap();
+            }
+        }
+        xml.push_str("\n  <dataset>");
+        self.write_dataset_children(&mut xml);
+        xml.push_str("\n  </dataset>");
+        if let Some(source) = &self.source {
+            for range in &source.after_dataset {
+                write!(xml, "\n  {}", &source.xml[range.clone()]).unwrap();
+            }
+        }
+        writeln!(xml, "\n</{root_name}>").unwrap();
+        xml
+    }
+
+    /// Writes dataset children in their original order, replacing edited
+    /// ones and adding new ones where the schema expects them
+    fn write_dataset_children(&self, xml: &mut String) {
+        let children = self.source.as_ref().map(|s| s.dataset_children.as_slice()).unwrap_or_default();
+        let original = self.source.as_ref().map(|s| &s.original);
+        let present: HashSet<&str> = children.iter().map(|(name, _)| name.as_str()).collect();
+        let mut missing = EDITABLE_ELEMENTS
+            .iter()
+            .copied()
+            .filter(|name| !present.contains(name) && self.has_element(name))
+            .peekable();
+        let mut written = HashSet::new();
+
+        for (name, range) in children {
+            while let Some(next) = missing.next_if(|m| element_rank(m) < element_rank(name)) {
+                self.write_element(next, xml);
+            }
+            let unchanged = original.is_some_and(|original| self.same_element(name, original));
+            if !EDITABLE_ELEMENTS.contains(&name.as_str()) || unchanged {
+                if let Some(source) = &self.source {
+                    write!(xml, "\n    {}", &source.xml[range.clone()]).unwrap();
+                }
+            } else if written.insert(name.as_str()) && self.has_element(name) {
+                self.write_element(name, xml);
+            }
+        }
+        for name in missing {
+            self.write_element(name, xml);
+        }
+    }
+
+    fn has_element(&self, name: &str) -> bool {
+        match name {
+            "alternateIdentifier" => !self.alternate_identifiers.is_empty(),
+            "title" => !self.title.is_empty(),
+            "creator" => !self.creators.is_empty(),
+            "metadataProvider" => !self.metadata_providers.is_empty(),
+            "contact" => !self.contacts.is_empty(),
+            "pubDate" => self.pub_date.is_some(),
+            "language" => self.language.is_some(),
+            "abstract" => !self.abstract_paragraphs.is_empty(),
+            "intellectualRights" => self.license.is_some(),
+            "coverage" => {
+                self.geographic_coverage.is_some()
+                    || self.temporal_coverage.is_some()
+                    || self.source.as_ref().is_some_and(|s| !s.other_coverage.is_empty())
+            }
+            _ => false,
+        }
+    }
+
+    fn same_element(&self, name: &str, other: &Eml) -> bool {
+        match name {
+            "alternateIdentifier" => self.alternate_identifiers == other.alternate_identifiers,
+            "title" => self.title == other.title,
+            "creator" => self.creators == other.creators,
+            "metadataProvider" => self.metadata_providers == other.metadata_providers,
+            "contact" => self.contacts == other.contacts,
+            "pubDate" => self.pub_date == other.pub_date,
+            "language" => self.language == other.language,
+            "abstract" => self.abstract_paragraphs == other.abstract_paragraphs,
+            "intellectualRights" => self.license == other.license,
+            "coverage" => {
+                self.geographic_coverage == other.geographic_coverage
+                    && self.temporal_coverage == other.temporal_coverage
+            }
+            _ => false,
+        }
+    }
+
+    fn write_element(&self, name: &str, xml: &mut String) {
+        match name {
+            "alternateIdentifier" => {
+                for identifier in &self.alternate_identifiers {
+                    write!(xml, "\n    <alternateIdentifier>{}</alternateIdentifier>", escape(identifier))
+                        .unwrap();
+                }
+            }
+            "title" => write!(xml, "\n    <title>{}</title>", escape(&self.title)).unwrap(),
+            "creator" => write_parties(xml, "creator", &self.creators),
+            "metadataProvider" => write_parties(xml, "metadataProvider", &self.metadata_providers),
+            "contact" => write_parties(xml, "contact", &self.contacts),
+            "pubDate" => {
+                let pub_date = self.pub_date.as_deref().unwrap_or_default();
+                write!(xml, "\n    <pubDate>{}</pubDate>", escape(pub_date)).unwrap();
+            }
+            "language" => {
+                let language = self.language.as_deref().unwrap_or_default();
+                write!(xml, "\n    <language>{}</language>", escape(language)).unwrap();
+            }
+            "abstract" => {
+                xml.push_str("\n    <abstract>");
+                for paragraph in &self.abstract_paragraphs {
+                    write!(xml, "\n      <para>{}</para>", escape(paragraph)).unwrap();
+                }
+                xml.push_str("\n    </abstract>");
+            }
+            "intellectualRights" => {
+                let license = self.license.as_deref().unwrap_or_default();
+                write!(
+                    xml,
+                    "\n    <intellectualRights>\n      <para>{}</para>\n    </intellectualRights>",
+                    escape(license)
+                )
+                .unwrap();
+            }
+            "coverage" => self.write_coverage(xml),
+            _ => {}
+        }
+    }
+
+    fn write_coverage(&self, xml: &mut String) {
+        xml.push_str("\n    <coverage>");
+        if let Some(geo) = &self.geographic_coverage {
+            write!(
+                xml,
+                "\n      <geographicCoverage>\
+                 \n        <geographicDescription>{}</geographicDescription>\
+                 \n        <boundingCoordinates>\
+                 \n          <westBoundingCoordinate>{}</westBoundingCoordinate>\
+                 \n          <eastBoundingCoordinate>{}</eastBoundingCoordinate>\
+                 \n          <northBoundingCoordinate>{}</northBoundingCoordinate>\
+                 \n          <southBoundingCoordinate>{}</southBoundingCoordinate>\
+                 \n        </boundingCoordinates>\
+                 \n      </geographicCoverage>",
+                escape(&geo.description), geo.west, geo.east, geo.north, geo.south,
+            )
+            .unwrap();
+        }
+        if let Some(temporal) = &self.temporal_coverage {
+            xml.push_str("\n      <temporalCoverage>");
+            match &temporal.end_date {
+                Some(end_date) => write!(
+                    xml,
+                    "\n        <rangeOfDates>\
+                     \n          <beginDate><calendarDate>{}</calendarDate></beginDate>\
+                     \n          <endDate><calendarDate>{}</calendarDate></endDate>\
+                     \n        </rangeOfDates>",
+                    escape(&temporal.begin_date), escape(end_date),
+                )
+                .unwrap(),
+                None => write!(
+                    xml,
+                    "\n        <singleDateTime><calendarDate>{}</calendarDate></singleDateTime>",
+                    escape(&temporal.begin_date),
+                )
+                .unwrap(),
+            }
+            xml.push_str("\n      </temporalCoverage>");
+        }
+        if let Some(source) = &self.source {
+            for range in &source.other_coverage {
+                write!(xml, "\n      {}", &source.xml[range.clone()]).unwrap();
+            }
+        }
+        xml.push_str("\n    </coverage>");
+    }
+}
+
+fn element_rank(name: &str) -> usize {
+    DATASET_ELEMENT_ORDER
+        .iter()
+        .position(|n| *n == name)
+        .unwrap_or(DATASET_ELEMENT_ORDER.len())
+}
+
+/// Text content of an element with whitespace collapsed
+fn text(node: Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|n| n.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    node.descendants()
+        .find(|n| n.has_tag_name(name))
+        .map(text)
+        .filter(|t| !t.is_empty())
+}
+
+fn paragraphs(node: Node) -> Vec<String> {
+    let paras: Vec<String> = node
+        .descendants()
+        .filter(|n| n.has_tag_name("para"))
+        .map(text)
+        .filter(|t| !t.is_empty())
+        .collect();
+    if paras.is_empty() {
+        Some(text(node)).filter(|t| !t.is_empty()).into_iter().collect()
+    } else {
+        paras
+    }
+}
+
+fn parse_party(node: Node) -> EmlParty {
+    let user_id = node.descendants().find(|n| n.has_tag_name("userId"));
+    EmlParty {
+        given_name: child_text(node, "givenName"),
+        sur_name: child_text(node, "surName"),
+        organization_name: child_text(node, "organizationName"),
+        email: child_text(node, "electronicMailAddress"),
+        user_id: user_id.map(text).filter(|t| !t.is_empty()),
+        user_id_directory: user_id.and_then(|n| n.attribute("directory")).map(str::to_string),
+    }
+}
+
+fn parse_geographic_coverage(node: Node) -> Option<GeographicCoverage> {
+    let coordinate = |name: &str| child_text(node, name)?.parse::<f64>().ok();
+    Some(GeographicCoverage {
+        description: child_text(node, "geographicDescription").unwrap_or_default(),
+        west: coordinate("westBoundingCoordinate")?,
+        east: coordinate("eastBoundingCoordinate")?,
+        north: coordinate("northBoundingCoordinate")?,
+        south: coordinate("southBoundingCoordinate")?,
+    })
+}
+
+fn parse_temporal_coverage(node: Node) -> Option<TemporalCoverage> {
+    let date = |name: &str| {
+        node.descendants()
+            .find(|n| n.has_tag_name(name))
+            .and_then(|n| child_text(n, "calendarDate"))
+    };
+    if let Some(single) = date("singleDateTime") {
+        return Some(TemporalCoverage { begin_date: single, end_date: None });
+    }
+    Some(TemporalCoverage {
+        begin_date: date("beginDate")?,
+        end_date: Some(date("endDate")?),
+    })
+}
+
+fn write_parties(xml: &mut String, tag: &str, parties: &[EmlParty]) {
+    for party in parties {
+        write!(xml, "\n    <{tag}>").unwrap();
+        if party.given_name.is_some() || party.sur_name.is_some() {
+            xml.push_str("\n      <individualName>");
+            if let Some(given_name) = &party.given_name {
+                write!(xml, "\n        <givenName>{}</givenName>", escape(given_name)).unwrap();
+            }
+            let sur_name = party.sur_name.as_deref().unwrap_or_default();
+            write!(xml, "\n        <surName>{}</surName>", escape(sur_name)).unwrap();
+            xml.push_str("\n      </individualName>");
+        }
+        if let Some(organization_name) = &party.organization_name {
+            write!(xml, "\n      <organizationName>{}</organizationName>", escape(organization_name)).unwrap();
+        }
+        if let Some(email) = &party.email {
+            write!(xml, "\n      <electronicMailAddress>{}</electronicMailAddress>", escape(email)).unwrap();
+        }
+        if let Some(user_id) = &party.user_id {
+            let directory = party.user_id_directory.as_deref().unwrap_or_default();
+            write!(
+                xml,
+                "\n      <userId directory=\"{}\">{}</userId>",
+                escape(directory),
+                escape(user_id)
+            )
+            .unwrap();
+        }
+        write!(xml, "\n    </{tag}>").unwrap();
+    }
+}
+
+/// Escape special XML characters in text content and attribute values
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GBIF_EML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<eml:eml xmlns:eml="eml://ecoinformatics.org/eml-2.1.1" packageId="abc" system="http://gbif.org">
+  <dataset>
+    <alternateIdentifier>abc</alternateIdentifier>
+    <title xml:lang="en">Plants of Tilden Park</title>
+    <creator>
+      <individualName><givenName>Willis</givenName><surName>Jepson</surName></individualName>
+      <userId directory="https://orcid.org/">0000-0002-1825-0097</userId>
+    </creator>
+    <pubDate>2024-05-01</pubDate>
+    <abstract>
+      <para>Vascular plants.</para>
+      <para>Mostly oaks.</para>
+    </abstract>
+    <intellectualRights>
+      <para>Licensed under <ulink url="http://creativecommons.org/licenses/by/4.0/legalcode"><citetitle>CC BY 4.0</citetitle></ulink></para>
+    </intellectualRights>
+    <coverage>
+      <taxonomicCoverage><taxonomicClassification><taxonRankValue>Quercus</taxonRankValue></taxonomicClassification></taxonomicCoverage>
+    </coverage>
+    <contact><organizationName>Jepson Herbarium</organizationName></contact>
+  </dataset>
+  <additionalMetadata><metadata><gbif><hierarchyLevel>dataset</hierarchyLevel></gbif></metadata></additionalMetadata>
+</eml:eml>"#;
+
+    #[test]
+    fn test_parse() {
+        let eml = Eml::parse(GBIF_EML).unwrap();
+
+        assert_eq!(eml.title, "Plants of Tilden Park");
+        assert_eq!(eml.abstract_paragraphs, vec!["Vascular plants.", "Mostly oaks."]);
+        assert_eq!(eml.creators, vec![EmlParty {
+            given_name: Some("Willis".to_string()),
+            sur_name: Some("Jepson".to_string()),
+            user_id: Some("0000-0002-1825-0097".to_string()),
+            user_id_directory: Some("https://orcid.org/".to_string()),
+            ..Default::default()
+        }]);
+        assert_eq!(eml.license.as_deref(), Some("Licensed under CC BY 4.0"));
+        assert_eq!(eml.pub_date.as_deref(), Some("2024-05-01"));
+        assert_eq!(eml.geographic_coverage, None);
+    }
+
+    #[test]
+    fn test_unedited_document_keeps_its_elements() {
+        let xml = Eml::parse(GBIF_EML).unwrap().to_xml();
+
+        assert!(xml.contains(r#"packageId="abc""#));
+        assert!(xml.contains(r#"<title xml:lang="en">Plants of Tilden Park</title>"#));
+        assert!(xml.contains("<ulink url=\"http://creativecommons.org/licenses/by/4.0/legalcode\">"));
+        assert!(xml.contains("<hierarchyLevel>dataset</hierarchyLevel>"));
+        assert!(xml.ends_with("</eml:eml>\n"));
+        roxmltree::Document::parse(&xml).unwrap();
+    }
+
+    #[test]
+    fn test_edits_replace_elements_in_schema_order() {
+        let mut eml = Eml::parse(GBIF_EML).unwrap();
+        let mut edits = eml.clone();
+        edits.title = "Oaks of Tilden & Wildcat".to_string();
+        edits.geographic_coverage = Some(GeographicCoverage {
+            description: "Berkeley Hills".to_string(),
+            west: -122.3,
+            east: -122.2,
+            north: 37.9,
+            south: 37.8,
+        });
+        edits.temporal_coverage = Some(TemporalCoverage {
+            begin_date: "2020-01-01".to_string(),
+            end_date: Some("2024-12-31".to_string()),
+        });
+        edits.language = Some("en".to_string());
+        eml.apply(edits);
+
+        let xml = eml.to_xml();
+        let reparsed = Eml::parse(&xml).unwrap();
+
+        assert_eq!(reparsed.title, "Oaks of Tilden & Wildcat");
+        assert_eq!(reparsed.geographic_coverage, eml.geographic_coverage);
+        assert_eq!(reparsed.temporal_coverage, eml.temporal_coverage);
+        assert!(xml.contains("<taxonRankValue>Quercus</taxonRankValue>"));
+        let position = |needle: &str| xml.find(needle).unwrap();
+        assert!(position("<pubDate>") < position("<language>"));
+        assert!(position("<language>") < position("<abstract>"));
+        assert!(position("<geographicCoverage>") < position("<taxonomicCoverage>"));
+    }
+
+    #[test]
+    fn test_alternate_identifiers_survive_edits() {
+        let mut eml = Eml::parse(GBIF_EML).unwrap();
+        assert_eq!(eml.alternate_identifiers, vec!["abc"]);
+
+        let mut edits = eml.clone();
+        edits.alternate_identifiers.clear();
+        edits.title = "Oaks".to_string();
+        eml.apply(edits);
+        eml.add_alternate_identifier("https://doi.org/10.5281/zenodo.1234");
+        eml.add_alternate_identifier("abc");
+
+        let xml = eml.to_xml();
+        let reparsed = Eml::parse(&xml).unwrap();
+        assert_eq!(
+            reparsed.alternate_identifiers,
+            vec!["abc", "https://doi.org/10.5281/zenodo.1234"]
+        );
+        let position = |needle: &str| xml.find(needle).unwrap();
+        assert!(position("zenodo.1234</alternateIdentifier>") < position("<title>"));
+    }
+
+    #[test]
+    fn test_fill_gbif_required_elements() {
+        let mut eml = Eml::parse(GBIF_EML).unwrap();
+        eml.fill_gbif_required_elements();
+
+        assert_eq!(eml.title, "Plants of Tilden Park");
+        assert_eq!(eml.metadata_providers, eml.creators);
+        assert_eq!(eml.contacts[0].organization_name.as_deref(), Some("Jepson Herbarium"));
+        assert_eq!(eml.language.as_deref(), Some("en"));
+
+        let mut empty = Eml::default();
+        empty.fill_gbif_required_elements();
+        assert_eq!(empty.creators[0].organization_name.as_deref(), Some("Chuck"));
+        assert!(empty.pub_date.is_some());
+        assert_eq!(empty.license, None);
+        let xml = empty.to_xml();
+        let position = |needle: &str| xml.find(needle).unwrap();
+        assert!(position("<metadataProvider>") < position("<pubDate>"));
+        assert!(position("<abstract>") < position("<contact>"));
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let metadata = Metadata {
+            abstract_lines: vec!["Birds & bees".to_string()],
+            inat_query: None,
+        };
+
+        let xml = Eml::from_metadata(&metadata).to_xml();
+
+        assert!(xml.contains("<para>Birds &amp; bees</para>"));
+        assert!(xml.contains("<title>Chuck DarwinCore Archive</title>"));
+        let position = |needle: &str| xml.find(needle).unwrap();
+        assert!(position("<creator>") < position("<metadataProvider>"));
+        assert!(position("<abstract>") < position("<contact>"));
+        roxmltree::Document::parse(&xml).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+/// Groups of terms archives use interchangeably for the same information,
+/// by term URI. The first term in each group is the canonical one. Bare
+/// names stand for headers that aren't terms, like the "id" many archives
+/// give their core ID column.
+const ALIAS_GROUPS: &[&[&str]] = &[
+    &[
+        "http://rs.tdwg.org/dwc/terms/countryCode",
+        "http://rs.tdwg.org/dwc/terms/country",
+    ],
+    &[
+        "http://rs.tdwg.org/dwc/terms/occurrenceID",
+        "http://purl.org/dc/terms/identifier",
+        "id",
+    ],
+    &["http://rs.tdwg.org/dwc/terms/decimalLatitude", "latitude"],
+    &["http://rs.tdwg.org/dwc/terms/decimalLongitude", "longitude"],
+];
+
+/// Short name of a term URI, e.g. countryCode for
+/// http://rs.tdwg.org/dwc/terms/countryCode
+fn short_name(term: &str) -> &str {
+    term.rsplit('/').next().unwrap_or(term)
+}
+
+/// Other names for the same information as `name`, canonical name first,
+/// or nothing if it isn't in an alias group
+pub fn field_aliases(name: &str) -> Vec<&'static str> {
+    ALIAS_GROUPS
+        .iter()
+        .find(|group| group.iter().any(|term| short_name(term).eq_ignore_ascii_case(name)))
+        .map(|group| {
+            group
+                .iter()
+                .map(|term| short_name(term))
+                .filter(|alias| !alias.eq_ignore_ascii_case(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The column in `columns` to use for a field: the one named `name`,
+/// ignoring case, or else the first of its aliases the archive has. None if
+/// the archive has neither.
+pub fn resolve_field<'a>(name: &str, columns: &'a [String]) -> Option<&'a str> {
+    let find = |wanted: &str| {
+        columns
+            .iter()
+            .find(|column| column.eq_ignore_ascii_case(wanted))
+            .map(String::as_str)
+    };
+    find(name).or_else(|| field_aliases(name).into_iter().find_map(find))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_field() {
+        let columns = vec!["id".to_string(), "country".to_string(), "CatalogNumber".to_string()];
+
+        assert_eq!(resolve_field("country", &columns), Some("country"));
+        assert_eq!(resolve_field("countryCode", &columns), Some("country"));
+        assert_eq!(resolve_field("occurrenceID", &columns), Some("id"));
+        assert_eq!(resolve_field("catalogNumber", &columns), Some("CatalogNumber"));
+        assert_eq!(resolve_field("eventDate", &columns), None);
+    }
+
+    #[test]
+    fn test_field_aliases() {
+        assert_eq!(field_aliases("country"), vec!["countryCode"]);
+        assert_eq!(field_aliases("id"), vec!["occurrenceID", "identifier"]);
+        assert!(field_aliases("eventDate").is_empty());
+    }
+}
@@ -0,0 +1,54 @@
+// Darwin Core Measurement or Facts extension
+// https://rs.gbif.org/extension/dwc/measurements_or_facts_2022-02-02.xml
+
+use serde::Serialize;
+
+/// DarwinCore MeasurementOrFact record. Chuck writes one for each iNat
+/// annotation, e.g. measurementType "Alive or Dead" with measurementValue
+/// "Alive", so annotations without a Darwin Core term of their own aren't lost.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MeasurementOrFact {
+    #[serde(rename = "occurrenceID")]
+    pub occurrence_id: String,
+    #[serde(rename = "measurementType")]
+    pub measurement_type: String,
+    #[serde(rename = "measurementValue")]
+    pub measurement_value: String,
+    #[serde(rename = "measurementMethod")]
+    pub measurement_method: Option<String>,
+    #[serde(rename = "measurementRemarks")]
+    pub measurement_remarks: Option<String>,
+}
+
+impl MeasurementOrFact {
+    /// Row type URI for the MeasurementOrFact extension
+    pub const ROW_TYPE: &'static str = "http://rs.tdwg.org/dwc/terms/MeasurementOrFact";
+
+    /// CSV filename for the MeasurementOrFact extension
+    pub const FILENAME: &'static str = "measurementorfact.csv";
+
+    /// Fields written to CSV when exporting, paired with their term URIs
+    pub const WRITE_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("occurrenceID", "http://rs.tdwg.org/dwc/terms/occurrenceID"),
+        ("measurementType", "http://rs.tdwg.org/dwc/terms/measurementType"),
+        ("measurementValue", "http://rs.tdwg.org/dwc/terms/measurementValue"),
+        ("measurementMethod", "http://rs.tdwg.org/dwc/terms/measurementMethod"),
+        ("measurementRemarks", "http://rs.tdwg.org/dwc/terms/measurementRemarks"),
+    ];
+
+    /// Get the CSV header row for MeasurementOrFact records
+    pub fn csv_headers() -> Vec<&'static str> {
+        Self::WRITE_FIELDS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Convert to CSV record for writing
+    pub fn to_csv_record(&self) -> Vec<String> {
+        vec![
+            self.occurrence_id.clone(),
+            self.measurement_type.clone(),
+            self.measurement_value.clone(),
+            self.measurement_method.clone().unwrap_or_default(),
+            self.measurement_remarks.clone().unwrap_or_default(),
+        ]
+    }
+}
@@ -3,11 +3,13 @@ use std::fmt::Write;
 use crate::darwin_core::{
     audiovisual::Audiovisual,
     comment::Comment,
+    eml::Eml,
     identification::Identification,
+    measurement_or_fact::MeasurementOrFact,
     multimedia::Multimedia,
     occurrence::Occurrence,
+    verbatim::Verbatim,
 };
-use chrono::Utc;
 
 /// Metadata for the DarwinCore Archive
 #[derive(Debug, Clone, Default)]
@@ -74,6 +76,12 @@ pub fn generate_meta_xml(enabled_extensions: &[crate::DwcaExtension]) -> String
             Comment::FILENAME,
             Comment::WRITE_FIELDS,
         ),
+        (
+            crate::DwcaExtension::MeasurementOrFact,
+            MeasurementOrFact::ROW_TYPE,
+            MeasurementOrFact::FILENAME,
+            MeasurementOrFact::WRITE_FIELDS,
+        ),
     ];
 
     for (variant, row_type, filename, fields) in &extension_specs {
@@ -94,68 +102,28 @@ pub fn generate_meta_xml(enabled_extensions: &[crate::DwcaExtension]) -> String
         xml.push_str("  </extension>\n");
     }
 
+    // Verbatim values for the core, like the verbatim.txt in GBIF downloads
+    writeln!(
+        xml,
+        r#"  <extension encoding="UTF-8" fieldsTerminatedBy="," linesTerminatedBy="\n" fieldsEnclosedBy="&quot;" ignoreHeaderLines="1" rowType="{}">
+    <files>
+      <location>{}</location>
+    </files>
+    <coreid index="0"/>"#,
+        Verbatim::ROW_TYPE,
+        Verbatim::FILENAME,
+    )
+    .unwrap();
+    write_field_elements(&mut xml, Verbatim::WRITE_FIELDS);
+    xml.push_str("  </extension>\n");
+
     xml.push_str("</archive>\n");
     xml
 }
 
-// TODO: allow user to specify options like org name, contact info, license, etc
 /// Generates an EML (Ecological Metadata Language) file for the archive
 pub fn generate_eml(metadata: &Metadata) -> String {
-    let now = Utc::now().format("%Y-%m-%d").to_string();
-    let package_id =
-        format!("darwincore-archive-{}", Utc::now().format("%Y%m%d%H%M%S"));
-
-    let mut xml = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<eml:eml xmlns:eml="eml://ecoinformatics.org/eml-2.1.1"
-  xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
-  xsi:schemaLocation="eml://ecoinformatics.org/eml-2.1.1 http://rs.gbif.org/schema/eml-gbif-profile/1.1/eml.xsd"
-  packageId="{package_id}"
-  system="http://gbif.org"
-  scope="system">
-  <dataset>
-    <title>Chuck DarwinCore Archive</title>
-    <creator>
-      <organizationName>Chuck</organizationName>
-    </creator>
-    <metadataProvider>
-      <organizationName>Chuck</organizationName>
-    </metadataProvider>
-    <pubDate>{now}</pubDate>
-    <language>en</language>
-    <abstract>
-"#
-    );
-
-    if metadata.abstract_lines.is_empty() {
-        xml.push_str(
-            "      <para>Observations exported from iNaturalist</para>\n",
-        );
-    } else {
-        for line in &metadata.abstract_lines {
-            let escaped = xml_escape(line);
-            writeln!(xml, "      <para>{escaped}</para>").unwrap();
-        }
-    }
-
-    xml.push_str(
-        r#"    </abstract>
-    <contact>
-      <organizationName>Chuck</organizationName>
-    </contact>
-  </dataset>
-</eml:eml>
-"#,
-    );
-
-    xml
-}
-
-/// Escape special XML characters in text content
-fn xml_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
+    Eml::from_metadata(metadata).to_xml()
 }
 
 #[cfg(test)]
@@ -204,4 +172,20 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_meta_xml_declares_verbatim_file() {
+        let meta_xml = generate_meta_xml(&[]);
+        let doc = roxmltree::Document::parse(&meta_xml).unwrap();
+        let verbatim = doc.descendants()
+            .filter(|n| n.has_tag_name("extension"))
+            .find(|n| n.descendants().any(|l| l.has_tag_name("location") && l.text() == Some(Verbatim::FILENAME)))
+            .expect("no verbatim extension");
+
+        assert_eq!(verbatim.attribute("rowType"), Some(Occurrence::ROW_TYPE));
+        assert_eq!(
+            verbatim.children().filter(|n| n.has_tag_name("field")).count(),
+            Verbatim::csv_headers().len()
+        );
+    }
 }
@@ -4,17 +4,29 @@ pub mod multimedia;
 pub mod audiovisual;
 pub mod identification;
 pub mod comment;
+pub mod measurement_or_fact;
+pub mod verbatim;
 pub mod meta;
+pub mod eml;
+pub mod field_aliases;
 pub mod conversions;
 pub mod photos;
 pub mod taxa;
+pub mod taxa_cache;
+pub mod term_types;
 
 pub use archive::ArchiveBuilder;
 pub use occurrence::Occurrence;
+pub use conversions::CoordinateAccess;
 pub use multimedia::Multimedia;
 pub use audiovisual::Audiovisual;
 pub use identification::Identification;
 pub use comment::Comment;
+pub use measurement_or_fact::MeasurementOrFact;
+pub use verbatim::Verbatim;
 pub use meta::Metadata;
+pub use eml::{Eml, EmlParty, GeographicCoverage, TemporalCoverage};
 pub use photos::{PhotoDownloader, SoundDownloader};
 pub use taxa::{collect_taxon_ids, fetch_taxa_for_observations};
+pub use taxa_cache::TaxaCache;
+pub use term_types::{term_type, TermType};
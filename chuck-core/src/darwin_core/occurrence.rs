@@ -2,7 +2,7 @@ use serde::Serialize;
 
 /// Represents a DarwinCore Occurrence record
 /// Based on the DarwinCore Occurrence standard: https://dwc.tdwg.org/terms/#occurrence
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 pub struct Occurrence {
     /// Default core ID if <id> element specified
     #[serde(rename = "id")]
@@ -127,6 +127,11 @@ pub struct Occurrence {
     #[serde(rename = "eventTime")]
     pub event_time: Option<String>,
 
+    /// The UTC offset of eventTime (e.g. -08:00), kept separately so dates
+    /// can be interpreted in local time
+    #[serde(rename = "eventTimeZoneOffset")]
+    pub event_time_zone_offset: Option<String>,
+
     /// The verbatim original representation of the date and time information for an Event
     #[serde(rename = "verbatimEventDate")]
     pub verbatim_event_date: Option<String>,
@@ -938,8 +943,12 @@ impl Occurrence {
         ("modified", "http://purl.org/dc/terms/modified"),
         ("captive", "https://www.inaturalist.org/terms/captive"),
         ("eventTime", "http://rs.tdwg.org/dwc/terms/eventTime"),
+        ("eventTimeZoneOffset", "https://www.inaturalist.org/terms/eventTimeZoneOffset"),
         ("verbatimEventDate", "http://rs.tdwg.org/dwc/terms/verbatimEventDate"),
         ("verbatimLocality", "http://rs.tdwg.org/dwc/terms/verbatimLocality"),
+        ("depth", "http://rs.tdwg.org/dwc/terms/depth"),
+        ("depthAccuracy", "http://rs.tdwg.org/dwc/terms/depthAccuracy"),
+        ("verbatimDepth", "http://rs.tdwg.org/dwc/terms/verbatimDepth"),
     ];
 
     /// All supported DarwinCore occurrence fields for reading
@@ -951,8 +960,9 @@ impl Occurrence {
         "taxonID", "occurrenceRemarks", "establishmentMeans", "georeferencedDate",
         "georeferenceProtocol", "coordinateUncertaintyInMeters", "coordinatePrecision",
         "geodeticDatum", "accessRights", "license", "informationWithheld", "modified",
-        "captive", "eventTime", "verbatimEventDate", "verbatimLocality", "continent",
-        "countryCode", "stateProvince", "county", "municipality", "locality",
+        "captive", "eventTime", "eventTimeZoneOffset", "verbatimEventDate",
+        "verbatimLocality", "continent", "countryCode", "stateProvince", "county",
+        "municipality", "locality",
         "waterBody", "island", "islandGroup", "elevation", "elevationAccuracy",
         "depth", "depthAccuracy", "minimumDistanceAboveSurfaceInMeters",
         "maximumDistanceAboveSurfaceInMeters", "habitat", "georeferenceRemarks",
@@ -1011,6 +1021,36 @@ impl Occurrence {
         Self::WRITE_FIELDS.iter().map(|(name, _)| *name).collect()
     }
 
+    /// Positions in WRITE_FIELDS of the named fields, in the order given, for
+    /// writing only some columns. Errors listing any names that aren't
+    /// written.
+    pub fn field_selection(fields: &[String]) -> Result<Vec<usize>, String> {
+        let mut selection = Vec::with_capacity(fields.len());
+        let mut unknown = Vec::new();
+        for field in fields {
+            match Self::WRITE_FIELDS.iter().position(|(name, _)| name == field) {
+                Some(index) => selection.push(index),
+                None => unknown.push(field.as_str()),
+            }
+        }
+        if unknown.is_empty() {
+            Ok(selection)
+        } else {
+            Err(format!("Unknown occurrence fields: {}", unknown.join(", ")))
+        }
+    }
+
+    /// CSV headers for the fields at `selection`, from field_selection
+    pub fn csv_headers_for(selection: &[usize]) -> Vec<&'static str> {
+        selection.iter().map(|index| Self::WRITE_FIELDS[*index].0).collect()
+    }
+
+    /// CSV record values for the fields at `selection`, from field_selection
+    pub fn to_csv_record_for(&self, selection: &[usize]) -> Vec<String> {
+        let mut record = self.to_csv_record();
+        selection.iter().map(|index| std::mem::take(&mut record[*index])).collect()
+    }
+
     /// Convert to CSV record values
     pub fn to_csv_record(&self) -> Vec<String> {
         vec![
@@ -1046,8 +1086,49 @@ impl Occurrence {
             self.modified.clone().unwrap_or_default(),
             self.captive.map_or(String::new(), |captive| captive.to_string()),
             self.event_time.clone().unwrap_or_default(),
+            self.event_time_zone_offset.clone().unwrap_or_default(),
             self.verbatim_event_date.clone().unwrap_or_default(),
             self.verbatim_locality.clone().unwrap_or_default(),
+            self.depth.map_or(String::new(), |depth| depth.to_string()),
+            self.depth_accuracy.map_or(String::new(), |accuracy| accuracy.to_string()),
+            self.verbatim_depth.clone().unwrap_or_default(),
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_record_for_field_selection() {
+        let occurrence = Occurrence {
+            occurrence_id: "1".to_string(),
+            event_date: Some("2024-05-01".to_string()),
+            scientific_name: Some("Quercus agrifolia".to_string()),
+            ..Default::default()
+        };
+        let fields = ["scientificName", "occurrenceID", "eventDate"].map(String::from);
+
+        let selection = Occurrence::field_selection(&fields).unwrap();
+
+        assert_eq!(
+            Occurrence::csv_headers_for(&selection),
+            vec!["scientificName", "occurrenceID", "eventDate"]
+        );
+        assert_eq!(
+            occurrence.to_csv_record_for(&selection),
+            vec!["Quercus agrifolia", "1", "2024-05-01"]
+        );
+    }
+
+    #[test]
+    fn test_field_selection_rejects_unknown_fields() {
+        let fields = ["occurrenceID", "colour", "sise"].map(String::from);
+
+        assert_eq!(
+            Occurrence::field_selection(&fields),
+            Err("Unknown occurrence fields: colour, sise".to_string())
+        );
+    }
+}
@@ -1,8 +1,12 @@
 use inaturalist::models::{Observation, ShowTaxon};
 use inaturalist::apis::taxa_api;
-use crate::api::{client::get_config, rate_limiter::get_rate_limiter};
+use crate::api::client::get_config;
+use crate::api::rate_limiter::get_rate_limiter;
+use crate::api::retry::{send_with_retry, RetryPolicy};
+use crate::darwin_core::taxa_cache::TaxaCache;
+
+const INAT_API_BASE: &str = "https://api.inaturalist.org/";
 use std::collections::{HashMap, HashSet};
-use tokio::time::{sleep, Duration};
 
 /// Collect all unique taxon IDs from observations and their identifications
 pub fn collect_taxon_ids(observations: &[Observation]) -> Vec<i32> {
@@ -30,7 +34,9 @@ pub fn collect_taxon_ids(observations: &[Observation]) -> Vec<i32> {
     all_taxon_ids.into_iter().collect()
 }
 
-/// Fetch taxa in chunks with retry logic and progress callback
+/// Fetch taxa in chunks with retries and a progress callback. Taxa from the
+/// iNat API are cached on disk, so only ones missing from the cache or older
+/// than its TTL are requested.
 pub async fn fetch_taxa_for_observations<F>(
     taxon_ids: &[i32],
     progress_callback: Option<F>,
@@ -47,15 +53,31 @@ where
     } else {
         get_config().await.read().await.clone()
     };
+    // Only taxa from iNat itself are cached, not ones from test servers
+    let cache = config_instance
+        .base_path
+        .starts_with(INAT_API_BASE)
+        .then(TaxaCache::default_location)
+        .flatten();
     let config = tokio::sync::RwLock::new(config_instance);
 
+    let mut uncached_ids = Vec::new();
+    for &id in taxon_ids {
+        match cache.as_ref().and_then(|cache| cache.get(id)) {
+            Some(taxon) => {
+                taxa_hash.insert(id, taxon);
+            }
+            None => uncached_ids.push(id),
+        }
+    }
+
     let rate_limiter = get_rate_limiter().await;
-    let total_chunks = taxon_ids.len().div_ceil(500);
+    let total_chunks = uncached_ids.len().div_ceil(500);
     let mut chunks_processed = 0;
 
-    for chunk in taxon_ids.chunks(500) {
+    for chunk in uncached_ids.chunks(500) {
         // Rate limiting: coordinate with other API requests
-        if !taxa_hash.is_empty() {
+        if chunks_processed > 0 {
             rate_limiter.wait_for_next_request().await;
         }
 
@@ -77,30 +99,21 @@ where
             order_by: None,
         };
 
-        // Retry with exponential backoff (3 attempts total)
-        let mut attempt = 0;
-        let response = loop {
-            attempt += 1;
+        let (config, params) = (&config, &params);
+        let response = send_with_retry(RetryPolicy::default(), "Taxa fetch", move || async move {
             let config_read = config.read().await;
-            match taxa_api::taxa_get(&config_read, params.clone()).await {
-                Ok(response) => break response,
-                Err(e) => {
-                    if attempt >= 3 {
-                        return Err(format!(
-                            "Failed to fetch taxa after 3 attempts: {e}"
-                        ).into());
-                    }
-                    let backoff_ms = 1000 * (2_u64.pow(attempt - 1));
-                    eprintln!(
-                        "Taxa API request failed (attempt {attempt}), retrying in {backoff_ms}ms: {e}"
-                    );
-                    sleep(Duration::from_millis(backoff_ms)).await;
-                }
-            }
-        };
+            taxa_api::taxa_get(&config_read, params.clone()).await
+        })
+        .await
+        .map_err(|e| format!("Failed to fetch taxa: {e}"))?;
 
         for taxon in response.results {
             if let Some(id) = taxon.id {
+                if let Some(cache) = &cache
+                    && let Err(e) = cache.insert(&taxon)
+                {
+                    log::warn!("Failed to cache taxon {id}: {e}");
+                }
                 taxa_hash.insert(id, taxon);
             }
         }
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use inaturalist::models::ShowTaxon;
+
+/// How long a cached taxon is used before it's fetched again
+pub const DEFAULT_TAXA_CACHE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Taxa fetched from iNat, stored on disk as one JSON file per taxon ID so
+/// repeated downloads and archive updates don't fetch the same ancestors
+/// again. Entries older than the TTL are ignored and overwritten on the next
+/// fetch. Cache errors are never fatal; a bad entry is just a cache miss.
+pub struct TaxaCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl TaxaCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    /// Cache in the user's cache directory, e.g. ~/.cache/chuck/taxa on
+    /// Linux
+    pub fn default_location() -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self::new(dir.join("chuck").join("taxa"), DEFAULT_TAXA_CACHE_TTL))
+    }
+
+    fn path(&self, taxon_id: i32) -> PathBuf {
+        self.dir.join(format!("{taxon_id}.json"))
+    }
+
+    /// Cached taxon if there is one younger than the TTL
+    pub fn get(&self, taxon_id: i32) -> Option<ShowTaxon> {
+        let path = self.path(taxon_id);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if age > self.ttl {
+            return None;
+        }
+        let json = std::fs::read(&path).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    /// Stores a taxon, replacing any older entry. The file is written in
+    /// place atomically so concurrent runs never read half an entry.
+    pub fn insert(&self, taxon: &ShowTaxon) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(taxon_id) = taxon.id else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(&self.dir)?;
+        serde_json::to_writer(&mut file, taxon)?;
+        file.persist(self.path(taxon_id))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taxa_cache_round_trip_and_ttl() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = TaxaCache::new(temp.path().join("taxa"), DEFAULT_TAXA_CACHE_TTL);
+        let taxon = ShowTaxon {
+            id: Some(47148),
+            name: Some("Mollusca".to_string()),
+            ..Default::default()
+        };
+
+        assert!(cache.get(47148).is_none());
+        cache.insert(&taxon).unwrap();
+        assert_eq!(cache.get(47148).and_then(|t| t.name), Some("Mollusca".to_string()));
+
+        let expired = TaxaCache::new(temp.path().join("taxa"), Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(expired.get(47148).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+/// The kind of value a Darwin Core term holds, so anything importing an
+/// archive can type its columns the same way
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TermType {
+    Text,
+    Integer,
+    Double,
+    Boolean,
+    /// ISO 8601 date, datetime, or range
+    Date,
+}
+
+impl TermType {
+    /// DuckDB type for a column holding this term. Dates stay VARCHAR since
+    /// ISO 8601 allows ranges (2025-01-04/2025-02-14), years, and
+    /// year-months, none of which a DATE can hold.
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            Self::Integer => "BIGINT",
+            Self::Double => "DOUBLE",
+            Self::Boolean => "BOOLEAN",
+            Self::Text | Self::Date => "VARCHAR",
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer | Self::Double)
+    }
+}
+
+/// Terms whose values are whole numbers
+const INTEGER_TERMS: &[&str] = &[
+    "year",
+    "month",
+    "day",
+    "startDayOfYear",
+    "endDayOfYear",
+    "individualCount",
+];
+
+/// Terms with decimal values that don't follow the InMeters naming pattern
+const DOUBLE_TERMS: &[&str] = &[
+    "decimalLatitude",
+    "decimalLongitude",
+    "coordinatePrecision",
+    "pointRadiusSpatialFit",
+    // GBIF interpreted elevation and depth, in meters
+    "elevation",
+    "elevationAccuracy",
+    "depth",
+    "depthAccuracy",
+];
+
+/// Flags from GBIF and iNaturalist downloads
+const BOOLEAN_TERMS: &[&str] = &[
+    "captive",
+    "hasCoordinate",
+    "hasGeospatialIssues",
+    "hasTaxonomicIssue",
+    "hasNonTaxonomicIssue",
+    "identificationCurrent",
+    "isInvasive",
+    "isSequenced",
+    "repatriated",
+];
+
+/// Dublin Core terms that hold dates without saying so in their names
+const DATE_TERMS: &[&str] = &["modified", "created"];
+
+/// Type of a term, given either its URI (e.g.
+/// http://rs.tdwg.org/dwc/terms/minimumElevationInMeters) or just its name.
+/// Terms measured in meters are DOUBLE, terms named like eventDate are dates
+/// unless they're verbatim, and anything unrecognized is text.
+pub fn term_type(term: &str) -> TermType {
+    let name = term.rsplit(['/', '#']).next().unwrap_or(term);
+    if INTEGER_TERMS.contains(&name) {
+        TermType::Integer
+    } else if DOUBLE_TERMS.contains(&name) || name.ends_with("InMeters") {
+        TermType::Double
+    } else if BOOLEAN_TERMS.contains(&name) {
+        TermType::Boolean
+    } else if DATE_TERMS.contains(&name)
+        || (name.ends_with("Date") && !name.starts_with("verbatim"))
+    {
+        TermType::Date
+    } else {
+        TermType::Text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_term_type() {
+        assert_eq!(
+            term_type("http://rs.tdwg.org/dwc/terms/minimumDistanceAboveSurfaceInMeters"),
+            TermType::Double
+        );
+        assert_eq!(term_type("decimalLatitude"), TermType::Double);
+        assert_eq!(term_type("http://rs.tdwg.org/dwc/terms/year"), TermType::Integer);
+        assert_eq!(term_type("hasGeospatialIssues"), TermType::Boolean);
+        assert_eq!(term_type("http://rs.tdwg.org/dwc/terms/eventDate"), TermType::Date);
+        assert_eq!(term_type("http://purl.org/dc/terms/modified"), TermType::Date);
+        assert_eq!(term_type("verbatimEventDate"), TermType::Text);
+        assert_eq!(term_type("http://rs.tdwg.org/dwc/terms/scientificName"), TermType::Text);
+    }
+
+    #[test]
+    fn test_sql_type_keeps_dates_as_text() {
+        assert_eq!(TermType::Date.sql_type(), "VARCHAR");
+        assert_eq!(TermType::Integer.sql_type(), "BIGINT");
+    }
+}
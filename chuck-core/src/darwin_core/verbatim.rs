@@ -0,0 +1,102 @@
+// Verbatim occurrence records, like the verbatim.txt in GBIF downloads
+// https://techdocs.gbif.org/en/data-use/download-formats#darwin-core-archive
+
+use serde::Serialize;
+
+use crate::darwin_core::occurrence::Occurrence;
+
+/// Values of an occurrence as the source provided them, before Chuck
+/// normalized them into the occurrence core. For iNaturalist these are the
+/// raw API values, e.g. taxonID is the numeric taxon ID instead of a URI and
+/// license is a code like cc-by.
+#[derive(Debug, Default, Serialize)]
+pub struct Verbatim {
+    #[serde(rename = "occurrenceID")]
+    pub occurrence_id: String,
+    #[serde(rename = "eventDate")]
+    pub event_date: Option<String>,
+    #[serde(rename = "verbatimEventDate")]
+    pub verbatim_event_date: Option<String>,
+    #[serde(rename = "decimalLatitude")]
+    pub decimal_latitude: Option<String>,
+    #[serde(rename = "decimalLongitude")]
+    pub decimal_longitude: Option<String>,
+    #[serde(rename = "coordinateUncertaintyInMeters")]
+    pub coordinate_uncertainty_in_meters: Option<String>,
+    #[serde(rename = "verbatimLocality")]
+    pub verbatim_locality: Option<String>,
+    #[serde(rename = "scientificName")]
+    pub scientific_name: Option<String>,
+    #[serde(rename = "taxonRank")]
+    pub taxon_rank: Option<String>,
+    #[serde(rename = "taxonID")]
+    pub taxon_id: Option<String>,
+    #[serde(rename = "vernacularName")]
+    pub vernacular_name: Option<String>,
+    #[serde(rename = "recordedBy")]
+    pub recorded_by: Option<String>,
+    #[serde(rename = "occurrenceRemarks")]
+    pub occurrence_remarks: Option<String>,
+    #[serde(rename = "captive")]
+    pub captive: Option<String>,
+    #[serde(rename = "license")]
+    pub license: Option<String>,
+    #[serde(rename = "modified")]
+    pub modified: Option<String>,
+}
+
+impl Verbatim {
+    /// Verbatim records describe the same occurrences as the core, so they
+    /// share its row type
+    pub const ROW_TYPE: &'static str = Occurrence::ROW_TYPE;
+
+    /// CSV filename for the verbatim occurrence file
+    pub const FILENAME: &'static str = "verbatim.csv";
+
+    /// Fields written to CSV when exporting, paired with their term URIs
+    pub const WRITE_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("occurrenceID", "http://rs.tdwg.org/dwc/terms/occurrenceID"),
+        ("eventDate", "http://rs.tdwg.org/dwc/terms/eventDate"),
+        ("verbatimEventDate", "http://rs.tdwg.org/dwc/terms/verbatimEventDate"),
+        ("decimalLatitude", "http://rs.tdwg.org/dwc/terms/decimalLatitude"),
+        ("decimalLongitude", "http://rs.tdwg.org/dwc/terms/decimalLongitude"),
+        ("coordinateUncertaintyInMeters", "http://rs.tdwg.org/dwc/terms/coordinateUncertaintyInMeters"),
+        ("verbatimLocality", "http://rs.tdwg.org/dwc/terms/verbatimLocality"),
+        ("scientificName", "http://rs.tdwg.org/dwc/terms/scientificName"),
+        ("taxonRank", "http://rs.tdwg.org/dwc/terms/taxonRank"),
+        ("taxonID", "http://rs.tdwg.org/dwc/terms/taxonID"),
+        ("vernacularName", "http://rs.tdwg.org/dwc/terms/vernacularName"),
+        ("recordedBy", "http://rs.tdwg.org/dwc/terms/recordedBy"),
+        ("occurrenceRemarks", "http://rs.tdwg.org/dwc/terms/occurrenceRemarks"),
+        ("captive", "https://www.inaturalist.org/terms/captive"),
+        ("license", "http://purl.org/dc/terms/license"),
+        ("modified", "http://purl.org/dc/terms/modified"),
+    ];
+
+    /// Get the CSV header row for verbatim records
+    pub fn csv_headers() -> Vec<&'static str> {
+        Self::WRITE_FIELDS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Convert to CSV record for writing
+    pub fn to_csv_record(&self) -> Vec<String> {
+        vec![
+            self.occurrence_id.clone(),
+            self.event_date.clone().unwrap_or_default(),
+            self.verbatim_event_date.clone().unwrap_or_default(),
+            self.decimal_latitude.clone().unwrap_or_default(),
+            self.decimal_longitude.clone().unwrap_or_default(),
+            self.coordinate_uncertainty_in_meters.clone().unwrap_or_default(),
+            self.verbatim_locality.clone().unwrap_or_default(),
+            self.scientific_name.clone().unwrap_or_default(),
+            self.taxon_rank.clone().unwrap_or_default(),
+            self.taxon_id.clone().unwrap_or_default(),
+            self.vernacular_name.clone().unwrap_or_default(),
+            self.recorded_by.clone().unwrap_or_default(),
+            self.occurrence_remarks.clone().unwrap_or_default(),
+            self.captive.clone().unwrap_or_default(),
+            self.license.clone().unwrap_or_default(),
+            self.modified.clone().unwrap_or_default(),
+        ]
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{DataSource, MediaFiles, Page};
+use crate::DwcaExtension;
+use crate::darwin_core::{ArchiveBuilder, Metadata, Occurrence, Verbatim};
+use crate::download_report::ReportEntry;
+use crate::media_license::MediaLicensePolicy;
+
+const ALA_BASE_URL: &str = "https://biocache-ws.ala.org.au/ws";
+const PAGE_SIZE: usize = 500;
+
+/// Time to wait between page requests. ALA doesn't publish a rate limit, so
+/// this matches what's asked of iNat API clients.
+const REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What to download from ALA, in the terms of its biocache search API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlaQuery {
+    /// Main query, e.g. `taxa:"Acacia"` or `*:*` for everything
+    pub q: String,
+    /// Filter queries, e.g. `state:"Victoria"`
+    pub filters: Vec<String>,
+}
+
+impl Default for AlaQuery {
+    fn default() -> Self {
+        Self { q: "*:*".to_string(), filters: Vec::new() }
+    }
+}
+
+impl AlaQuery {
+    /// Query for occurrences of a taxon and everything below it, matched by
+    /// name on ALA's side
+    pub fn for_taxon(name: &str) -> Self {
+        Self { q: format!("taxa:\"{}\"", name.replace('"', "")), filters: Vec::new() }
+    }
+
+    /// Adds a filter for occurrences on or between ISO 8601 dates
+    pub fn with_date_range(mut self, d1: Option<&str>, d2: Option<&str>) -> Self {
+        if d1.is_some() || d2.is_some() {
+            let start = d1.map(|d| format!("{d}T00:00:00Z")).unwrap_or_else(|| "*".to_string());
+            let end = d2.map(|d| format!("{d}T23:59:59Z")).unwrap_or_else(|| "*".to_string());
+            self.filters.push(format!("occurrence_date:[{start} TO {end}]"));
+        }
+        self
+    }
+}
+
+/// An occurrence from the ALA biocache search API. Field types vary between
+/// data resources, so values are read leniently from the JSON.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct AlaRecord(pub Map<String, Value>);
+
+impl AlaRecord {
+    fn text(&self, key: &str) -> Option<String> {
+        super::json_text(&self.0, key)
+    }
+
+    fn number(&self, key: &str) -> Option<f64> {
+        super::json_number(&self.0, key)
+    }
+
+    fn occurrence_id(&self) -> String {
+        self.text("uuid")
+            .map(|uuid| format!("https://biocache.ala.org.au/occurrences/{uuid}"))
+            .unwrap_or_default()
+    }
+
+    /// Date of a field ALA returns as milliseconds since the epoch, or as an
+    /// ISO 8601 string for some resources
+    fn date(&self, key: &str) -> Option<String> {
+        match self.0.get(key)? {
+            Value::Number(millis) => chrono::DateTime::from_timestamp_millis(millis.as_i64()?)
+                .map(|date| date.date_naive().to_string()),
+            Value::String(date) => date.get(..10).map(str::to_string),
+            _ => None,
+        }
+    }
+
+    fn event_date(&self) -> Option<String> {
+        let start = self.date("eventDate")?;
+        match self.date("eventDateEnd") {
+            Some(end) if end != start => Some(format!("{start}/{end}")),
+            _ => Some(start),
+        }
+    }
+}
+
+/// DarwinCore basisOfRecord from ALA's uppercase form, e.g.
+/// HUMAN_OBSERVATION becomes HumanObservation
+fn basis_of_record(value: &str) -> String {
+    value
+        .split('_')
+        .map(|word| {
+            let word = word.to_lowercase();
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+impl From<&AlaRecord> for Occurrence {
+    fn from(record: &AlaRecord) -> Self {
+        let event_date = record.event_date();
+        Occurrence {
+            occurrence_id: record.occurrence_id(),
+            basis_of_record: record
+                .text("basisOfRecord")
+                .map(|value| basis_of_record(&value))
+                .unwrap_or_else(|| "Occurrence".to_string()),
+            recorded_by: record.text("recordedBy").or_else(|| record.text("collectors")).unwrap_or_default(),
+            year: event_date.as_deref().and_then(|d| d.get(..4)?.parse().ok()),
+            month: event_date.as_deref().and_then(|d| d.get(5..7)?.parse().ok()),
+            day: event_date.as_deref().and_then(|d| d.get(8..10)?.parse().ok()),
+            event_date,
+            decimal_latitude: record.number("decimalLatitude"),
+            decimal_longitude: record.number("decimalLongitude"),
+            coordinate_uncertainty_in_meters: record.number("coordinateUncertaintyInMeters"),
+            scientific_name: record.text("scientificName"),
+            taxon_rank: record.text("taxonRank").map(|rank| rank.to_lowercase()),
+            vernacular_name: record.text("vernacularName"),
+            kingdom: record.text("kingdom"),
+            phylum: record.text("phylum"),
+            // Solr reserves "class", so ALA spells it with three s's
+            class: record.text("classs").or_else(|| record.text("class")),
+            order: record.text("order"),
+            family: record.text("family"),
+            genus: record.text("genus"),
+            taxon_id: record.text("taxonConceptID"),
+            license: record.text("license"),
+            country_code: record.text("countryCode"),
+            state_province: record.text("stateProvince"),
+            locality: record.text("locality"),
+            dataset_name: record.text("dataResourceName"),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&AlaRecord> for Verbatim {
+    fn from(record: &AlaRecord) -> Self {
+        let raw = |key: &str| record.text(&format!("raw_{key}")).or_else(|| record.text(key));
+        Verbatim {
+            occurrence_id: record.occurrence_id(),
+            event_date: raw("eventDate"),
+            decimal_latitude: raw("decimalLatitude"),
+            decimal_longitude: raw("decimalLongitude"),
+            coordinate_uncertainty_in_meters: raw("coordinateUncertaintyInMeters"),
+            verbatim_locality: raw("locality"),
+            scientific_name: raw("scientificName"),
+            taxon_rank: raw("taxonRank"),
+            taxon_id: record.text("taxonConceptID"),
+            vernacular_name: raw("vernacularName"),
+            recorded_by: raw("recordedBy").or_else(|| record.text("collectors")),
+            occurrence_remarks: raw("occurrenceRemarks"),
+            license: raw("license"),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    total_records: Option<usize>,
+    #[serde(default)]
+    occurrences: Vec<AlaRecord>,
+}
+
+/// Occurrences from the Atlas of Living Australia's biocache search API,
+/// paged by offset. The occurrence download API would need an email address
+/// and polling for an offline job, so this pages through search results
+/// instead.
+pub struct AlaSource {
+    query: AlaQuery,
+    base_url: String,
+}
+
+impl AlaSource {
+    pub fn new(query: AlaQuery) -> Self {
+        Self::with_base_url(query, ALA_BASE_URL.to_string())
+    }
+
+    /// Source for a different biocache, e.g. a mock server in tests.
+    /// Requests aren't rate limited.
+    pub fn with_base_url(query: AlaQuery, base_url: String) -> Self {
+        Self { query, base_url }
+    }
+
+    pub fn query(&self) -> &AlaQuery {
+        &self.query
+    }
+}
+
+impl DataSource for AlaSource {
+    type Record = AlaRecord;
+    type PageContext = ();
+
+    fn metadata(&self) -> Metadata {
+        let mut abstract_lines = vec![
+            "Occurrences exported from the Atlas of Living Australia using the following criteria:"
+                .to_string(),
+            format!("* Query: {}", self.query.q),
+        ];
+        abstract_lines.extend(self.query.filters.iter().map(|fq| format!("* Filter: {fq}")));
+        Metadata { abstract_lines, inat_query: None }
+    }
+
+    async fn fetch_page(
+        &self,
+        cursor: Option<String>,
+    ) -> Result<Page<AlaRecord>, Box<dyn std::error::Error>> {
+        let start_index: usize = cursor.as_deref().map(str::parse).transpose()?.unwrap_or(0);
+        if start_index > 0 && self.base_url == ALA_BASE_URL {
+            tokio::time::sleep(REQUEST_INTERVAL).await;
+        }
+
+        let mut query: Vec<(&str, String)> = vec![
+            ("q", self.query.q.clone()),
+            ("pageSize", PAGE_SIZE.to_string()),
+            ("startIndex", start_index.to_string()),
+            ("sort", "id".to_string()),
+        ];
+        query.extend(self.query.filters.iter().map(|fq| ("fq", fq.clone())));
+        let response: SearchResponse = crate::api::client::http_client()
+            .get(format!("{}/occurrences/search", self.base_url))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let next_index = start_index + response.occurrences.len();
+        let has_more = !response.occurrences.is_empty()
+            && response.total_records.is_some_and(|total| next_index < total);
+        Ok(Page {
+            total_results: response.total_records,
+            next_cursor: has_more.then(|| next_index.to_string()),
+            records: response.occurrences,
+        })
+    }
+
+    async fn add_records(
+        &self,
+        records: &[AlaRecord],
+        archive: &mut ArchiveBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let occurrences: Vec<Occurrence> = records.iter().map(Occurrence::from).collect();
+        archive.add_occurrences(&occurrences).await?;
+        let verbatim: Vec<Verbatim> = records.iter().map(Verbatim::from).collect();
+        archive.add_verbatim(&verbatim).await?;
+        Ok(())
+    }
+
+    /// ALA records don't carry identifications, comments, or annotations,
+    /// and their images are hosted per data resource, so there are no
+    /// extension rows
+    async fn add_extensions(
+        &self,
+        _records: &[AlaRecord],
+        _context: &(),
+        _media: &MediaFiles,
+        _extensions: &[DwcaExtension],
+        _archive: &mut ArchiveBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn media_count(_records: &[AlaRecord]) -> usize {
+        0
+    }
+
+    fn photo_licenses(_records: &[AlaRecord]) -> BTreeMap<String, usize> {
+        BTreeMap::new()
+    }
+
+    fn apply_media_license_policy(
+        records: &[AlaRecord],
+        _policy: MediaLicensePolicy,
+    ) -> (Vec<AlaRecord>, Vec<ReportEntry>) {
+        (records.to_vec(), Vec::new())
+    }
+
+    fn download_media<F>(
+        _records: Vec<AlaRecord>,
+        _media_dir: PathBuf,
+        _callback: F,
+        _cancellation_token: Option<Arc<AtomicBool>>,
+    ) -> impl Future<Output = Result<MediaFiles, String>> + Send + 'static
+    where
+        F: Fn(usize) + Send + Sync + Clone + 'static,
+    {
+        std::future::ready(Ok(MediaFiles::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn record(value: Value) -> AlaRecord {
+        AlaRecord(value.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn test_occurrence_from_ala_record() {
+        let record = record(serde_json::json!({
+            "uuid": "0a1b2c",
+            "basisOfRecord": "PRESERVED_SPECIMEN",
+            "collectors": ["A. Smith", "B. Jones"],
+            "eventDate": 1_577_836_800_000_i64,
+            "decimalLatitude": -37.8,
+            "decimalLongitude": "144.9",
+            "scientificName": "Acacia dealbata",
+            "taxonRank": "SPECIES",
+            "classs": "Equisetopsida",
+            "stateProvince": "Victoria",
+        }));
+
+        let occurrence = Occurrence::from(&record);
+
+        assert_eq!(occurrence.occurrence_id, "https://biocache.ala.org.au/occurrences/0a1b2c");
+        assert_eq!(occurrence.basis_of_record, "PreservedSpecimen");
+        assert_eq!(occurrence.recorded_by, "A. Smith | B. Jones");
+        assert_eq!(occurrence.event_date.as_deref(), Some("2020-01-01"));
+        assert_eq!(occurrence.year, Some(2020));
+        assert_eq!(occurrence.decimal_longitude, Some(144.9));
+        assert_eq!(occurrence.taxon_rank.as_deref(), Some("species"));
+        assert_eq!(occurrence.class.as_deref(), Some("Equisetopsida"));
+    }
+
+    #[test]
+    fn test_query_with_date_range() {
+        let query = AlaQuery::for_taxon("Acacia").with_date_range(Some("2020-01-01"), None);
+
+        assert_eq!(query.q, "taxa:\"Acacia\"");
+        assert_eq!(query.filters, vec!["occurrence_date:[2020-01-01T00:00:00Z TO *]"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_pages_by_offset() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/occurrences/search")
+                .query_param("q", "taxa:\"Acacia\"")
+                .query_param("startIndex", "500");
+            then.status(200).json_body(serde_json::json!({
+                "totalRecords": 502,
+                "occurrences": [{"uuid": "a"}, {"uuid": "b"}],
+            }));
+        });
+        let source = AlaSource::with_base_url(AlaQuery::for_taxon("Acacia"), server.base_url());
+
+        let page = source.fetch_page(Some("500".to_string())).await.unwrap();
+
+        mock.assert();
+        assert_eq!(page.records.len(), 2);
+        assert_eq!(page.total_results, Some(502));
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use inaturalist::apis::configuration::Configuration;
+use inaturalist::apis::observations_api;
+use inaturalist::models::{Observation, ShowTaxon};
+
+use super::{DataSource, MediaFiles, Page};
+use crate::DwcaExtension;
+use crate::darwin_core::{ArchiveBuilder, Metadata};
+use crate::download_report::ReportEntry;
+use crate::downloader::{
+    convert_to_audiovisual, convert_to_comments, convert_to_identifications,
+    convert_to_measurements_or_facts, convert_to_photo_multimedia, convert_to_sound_multimedia,
+};
+use crate::media_license::{self, MediaLicensePolicy};
+
+/// Observations from the iNaturalist API, paged by ID from newest to oldest
+pub struct INatSource {
+    params: observations_api::ObservationsGetParams,
+    config: Option<Configuration>,
+    jwt: Option<String>,
+    /// iNat user ID the JWT belongs to, for telling the downloader's own
+    /// observations apart when they include private coordinates
+    viewer_id: Option<i32>,
+    /// Whether occurrences link to their observations and taxa on iNat
+    record_links: bool,
+}
+
+impl INatSource {
+    /// Source using the shared API config, or one authenticated with `jwt`
+    pub fn new(params: observations_api::ObservationsGetParams, jwt: Option<String>) -> Self {
+        Self::from_parts(params, None, jwt)
+    }
+
+    /// Source with custom configuration for testing. Requests aren't rate
+    /// limited.
+    pub fn with_config(
+        params: observations_api::ObservationsGetParams,
+        config: Configuration,
+    ) -> Self {
+        Self::from_parts(params, Some(config), None)
+    }
+
+    fn from_parts(
+        params: observations_api::ObservationsGetParams,
+        config: Option<Configuration>,
+        jwt: Option<String>,
+    ) -> Self {
+        let viewer_id = jwt
+            .as_deref()
+            .or(config.as_ref().and_then(|c| c.api_key.as_ref()).map(|key| key.key.as_str()))
+            .and_then(crate::auth::jwt_user_id);
+        Self { params, config, jwt, viewer_id, record_links: false }
+    }
+
+    /// Fill references and taxonConceptID with the URLs of each
+    /// observation and its taxon
+    pub fn with_record_links(mut self, record_links: bool) -> Self {
+        self.record_links = record_links;
+        self
+    }
+
+    pub fn params(&self) -> &observations_api::ObservationsGetParams {
+        &self.params
+    }
+
+    async fn fetch_batch(
+        &self,
+        id_below: Option<String>,
+    ) -> Result<inaturalist::models::ObservationsResponse, Box<dyn std::error::Error>> {
+        use crate::api::client;
+
+        let mut fetch_params = self.params.clone();
+        if let Some(id) = id_below {
+            fetch_params.id_below = Some(id);
+        }
+
+        // Use custom config if provided, otherwise use global config with JWT
+        if let Some(ref config) = self.config {
+            let config_lock = tokio::sync::RwLock::new(config.clone());
+            let response = client::fetch_observations_with_retry(&config_lock, fetch_params).await?;
+            Ok(response)
+        } else if let Some(ref jwt) = self.jwt {
+            let config_instance = client::create_config_with_jwt(Some(jwt.clone()));
+            let config = tokio::sync::RwLock::new(config_instance);
+            let response = client::fetch_observations_with_retry(&config, fetch_params).await?;
+            Ok(response)
+        } else {
+            let config = client::get_config().await;
+            let response = client::fetch_observations_with_retry(config, fetch_params).await?;
+            Ok(response)
+        }
+    }
+}
+
+impl DataSource for INatSource {
+    type Record = Observation;
+    type PageContext = HashMap<i32, ShowTaxon>;
+
+    fn metadata(&self) -> Metadata {
+        let mut abstract_lines = vec![
+            "Observations exported from iNaturalist using the following criteria:".to_string()
+        ];
+        abstract_lines.extend(
+            crate::api::params::extract_criteria(&self.params)
+                .into_iter()
+                .map(|c| format!("* {c}"))
+        );
+        let inat_query = Some(crate::api::params::serialize_params(&self.params));
+        Metadata { abstract_lines, inat_query }
+    }
+
+    async fn fetch_page(
+        &self,
+        cursor: Option<String>,
+    ) -> Result<Page<Observation>, Box<dyn std::error::Error>> {
+        // Rate limit (skip for custom configs, e.g. tests with mock servers)
+        if self.config.is_none() {
+            crate::api::rate_limiter::get_rate_limiter()
+                .await
+                .wait_for_next_request()
+                .await;
+        }
+        let response = self.fetch_batch(cursor).await?;
+        Ok(Page {
+            total_results: response.total_results.map(|total| total as usize),
+            next_cursor: response.results.last().and_then(|o| o.id).map(|id| id.to_string()),
+            records: response.results,
+        })
+    }
+
+    /// Fetches taxa for the observations and writes them as occurrences
+    async fn add_records(
+        &self,
+        records: &[Observation],
+        archive: &mut ArchiveBuilder,
+    ) -> Result<HashMap<i32, ShowTaxon>, Box<dyn std::error::Error>> {
+        use crate::darwin_core::{
+            CoordinateAccess, Occurrence, Verbatim, collect_taxon_ids, fetch_taxa_for_observations,
+        };
+        use crate::darwin_core::conversions::add_inat_links;
+
+        // Fetch taxa for this batch
+        let taxon_ids = collect_taxon_ids(records);
+        let taxa_hash = fetch_taxa_for_observations(
+            &taxon_ids,
+            None::<fn(usize, usize)>,
+            self.config.as_ref(),
+        ).await?;
+
+        // Convert to occurrences
+        let occurrences: Vec<Occurrence> = records
+            .iter()
+            .map(|obs| {
+                let mut occurrence = Occurrence::from((obs, &taxa_hash, self.viewer_id));
+                if self.record_links {
+                    add_inat_links(&mut occurrence, obs);
+                }
+                occurrence
+            })
+            .collect();
+        let private_coordinates = records
+            .iter()
+            .filter_map(|obs| CoordinateAccess::of(obs, self.viewer_id))
+            .count();
+        if private_coordinates > 0 {
+            log::info!("Including private coordinates of {private_coordinates} observations");
+        }
+
+        // Add to archive
+        archive.add_occurrences(&occurrences).await?;
+        let verbatim: Vec<Verbatim> = records.iter().map(Verbatim::from).collect();
+        archive.add_verbatim(&verbatim).await?;
+
+        Ok(taxa_hash)
+    }
+
+    async fn add_extensions(
+        &self,
+        records: &[Observation],
+        taxa_hash: &HashMap<i32, ShowTaxon>,
+        media: &MediaFiles,
+        extensions: &[DwcaExtension],
+        archive: &mut ArchiveBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Multimedia extension
+        if extensions.contains(&DwcaExtension::SimpleMultimedia) {
+            let mut records_out = convert_to_photo_multimedia(records, &media.photos);
+            records_out.extend(convert_to_sound_multimedia(records, &media.sounds));
+            if !records_out.is_empty() {
+                archive.add_multimedia(&records_out).await?;
+            }
+        }
+
+        // Audiovisual extension
+        if extensions.contains(&DwcaExtension::Audiovisual) {
+            let records_out = convert_to_audiovisual(records, &media.photos);
+            if !records_out.is_empty() {
+                archive.add_audiovisual(&records_out).await?;
+            }
+        }
+
+        // Identifications extension
+        if extensions.contains(&DwcaExtension::Identifications) {
+            let records_out = convert_to_identifications(records, taxa_hash);
+            if !records_out.is_empty() {
+                archive.add_identifications(&records_out).await?;
+            }
+        }
+
+        // Comments extension
+        if extensions.contains(&DwcaExtension::Comments) {
+            let records_out = convert_to_comments(records);
+            if !records_out.is_empty() {
+                archive.add_comments(&records_out).await?;
+            }
+        }
+
+        // Annotations as a MeasurementOrFact extension
+        if extensions.contains(&DwcaExtension::MeasurementOrFact) {
+            let records_out = convert_to_measurements_or_facts(records);
+            if !records_out.is_empty() {
+                archive.add_measurements_or_facts(&records_out).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn media_count(records: &[Observation]) -> usize {
+        let photos_count = records
+            .iter()
+            .filter_map(|o| o.photos.as_ref())
+            .flatten()
+            .count();
+        let sounds_count = records
+            .iter()
+            .filter_map(|o| o.sounds.as_ref())
+            .flatten()
+            .filter(|s| s.file_url.is_some() && !s.hidden.unwrap_or(false))
+            .count();
+        photos_count + sounds_count
+    }
+
+    fn photo_licenses(records: &[Observation]) -> BTreeMap<String, usize> {
+        media_license::summarize_photo_licenses(records)
+    }
+
+    fn apply_media_license_policy(
+        records: &[Observation],
+        policy: MediaLicensePolicy,
+    ) -> (Vec<Observation>, Vec<ReportEntry>) {
+        media_license::apply_media_license_policy(records, policy)
+    }
+
+    fn download_media<F>(
+        records: Vec<Observation>,
+        media_dir: PathBuf,
+        callback: F,
+        cancellation_token: Option<Arc<AtomicBool>>,
+    ) -> impl Future<Output = Result<MediaFiles, String>> + Send + 'static
+    where
+        F: Fn(usize) + Send + Sync + Clone + 'static,
+    {
+        use crate::darwin_core::{PhotoDownloader, SoundDownloader};
+
+        async move {
+            let photos = PhotoDownloader::fetch_photos_to_dir(
+                &records,
+                &media_dir,
+                callback.clone(),
+                cancellation_token.clone(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+            let sounds = SoundDownloader::fetch_sounds_to_dir(
+                &records,
+                &media_dir,
+                callback,
+                cancellation_token,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+            Ok(MediaFiles { photos, sounds })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use inaturalist::models::{Photo, Sound};
+
+    #[test]
+    fn test_media_count_skips_hidden_and_missing_sounds() {
+        let observations = vec![Observation {
+            id: Some(123),
+            photos: Some(vec![Photo::default(), Photo::default()]),
+            sounds: Some(vec![
+                Sound {
+                    file_url: Some("https://static.inaturalist.org/sounds/1.mp3".to_string()),
+                    ..Default::default()
+                },
+                Sound {
+                    file_url: Some("https://static.inaturalist.org/sounds/2.mp3".to_string()),
+                    hidden: Some(true),
+                    ..Default::default()
+                },
+                Sound::default(),
+            ]),
+            ..Default::default()
+        }];
+
+        assert_eq!(INatSource::media_count(&observations), 3);
+    }
+
+    #[test]
+    fn test_metadata_describes_query() {
+        let params = observations_api::ObservationsGetParams {
+            taxon_id: Some(vec!["47126".to_string()]),
+            ..crate::api::params::DEFAULT_GET_PARAMS.clone()
+        };
+
+        let metadata = INatSource::new(params, None).metadata();
+
+        assert_eq!(
+            metadata.abstract_lines[0],
+            "Observations exported from iNaturalist using the following criteria:"
+        );
+        assert!(metadata.inat_query.unwrap().contains("taxon_id=47126"));
+    }
+}
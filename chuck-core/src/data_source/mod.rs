@@ -0,0 +1,142 @@
+//! Sources of occurrence data the Downloader can build archives from. A
+//! source knows how to page through records matching a query, turn them into
+//! DarwinCore rows, and download their media; the Downloader takes care of
+//! the archive, progress, and cancellation, so every source gets those for
+//! free.
+
+pub mod ala;
+pub mod inat;
+pub mod obis;
+
+pub use ala::{AlaQuery, AlaSource};
+pub use inat::INatSource;
+pub use obis::{ObisQuery, ObisSource};
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use crate::DwcaExtension;
+use crate::darwin_core::{ArchiveBuilder, Metadata};
+use crate::download_report::ReportEntry;
+use crate::media_license::MediaLicensePolicy;
+use serde_json::{Map, Value};
+
+/// One page of records fetched from a data source
+#[derive(Debug, Clone)]
+pub struct Page<R> {
+    pub records: Vec<R>,
+    /// Records matching the query across all pages, if the source reports it
+    pub total_results: Option<usize>,
+    /// Cursor for fetching the page after this one, or None if this is the
+    /// last page
+    pub next_cursor: Option<String>,
+}
+
+/// Media downloaded for a batch of records, mapping photo and sound IDs to
+/// paths relative to the archive's media directory
+#[derive(Debug, Clone, Default)]
+pub struct MediaFiles {
+    pub photos: HashMap<i32, String>,
+    pub sounds: HashMap<i32, String>,
+}
+
+/// A backend the Downloader can fetch occurrences and media from, e.g. the
+/// iNaturalist API
+pub trait DataSource {
+    /// A record as the source returns it, e.g. an iNat observation
+    type Record: Clone + Send + Sync + 'static;
+
+    /// Lookups fetched while adding a page of records that its extension
+    /// records also need, e.g. the taxa the records refer to
+    type PageContext;
+
+    /// Abstract lines and query describing what the archive contains
+    fn metadata(&self) -> Metadata;
+
+    /// Fetches the page starting at `cursor`, or the first page if None.
+    /// Sources are responsible for their own rate limiting.
+    fn fetch_page(
+        &self,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<Page<Self::Record>, Box<dyn std::error::Error>>>;
+
+    /// Writes occurrence and verbatim rows for `records` to the archive
+    fn add_records(
+        &self,
+        records: &[Self::Record],
+        archive: &mut ArchiveBuilder,
+    ) -> impl Future<Output = Result<Self::PageContext, Box<dyn std::error::Error>>>;
+
+    /// Writes rows for the requested extensions, referring to media by their
+    /// downloaded paths where there are any
+    fn add_extensions(
+        &self,
+        records: &[Self::Record],
+        context: &Self::PageContext,
+        media: &MediaFiles,
+        extensions: &[DwcaExtension],
+        archive: &mut ArchiveBuilder,
+    ) -> impl Future<Output = Result<(), Box<dyn std::error::Error>>>;
+
+    /// Number of photos and sounds attached to `records` that can be
+    /// downloaded
+    fn media_count(records: &[Self::Record]) -> usize;
+
+    /// Number of photos per license, for logging
+    fn photo_licenses(records: &[Self::Record]) -> BTreeMap<String, usize>;
+
+    /// Copies of `records` without media the policy doesn't allow, for
+    /// choosing what to download, and report entries for the media left out
+    fn apply_media_license_policy(
+        records: &[Self::Record],
+        policy: MediaLicensePolicy,
+    ) -> (Vec<Self::Record>, Vec<ReportEntry>);
+
+    /// Downloads the media of `records` into `media_dir`, calling `callback`
+    /// with the number of files finished as they finish. Runs as a
+    /// background task while the next page is fetched.
+    fn download_media<F>(
+        records: Vec<Self::Record>,
+        media_dir: PathBuf,
+        callback: F,
+        cancellation_token: Option<Arc<AtomicBool>>,
+    ) -> impl Future<Output = Result<MediaFiles, String>> + Send + 'static
+    where
+        F: Fn(usize) + Send + Sync + Clone + 'static;
+}
+
+/// Value of `key` in a JSON record as text, with lists joined by " | " like
+/// recordedBy in DarwinCore. Sources that return DarwinCore-ish JSON aren't
+/// consistent about types, so numbers and booleans are accepted too.
+fn json_text(record: &Map<String, Value>, key: &str) -> Option<String> {
+    match record.get(key)? {
+        Value::String(value) => Some(value.trim().to_string()).filter(|v| !v.is_empty()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Array(values) => {
+            let values: Vec<String> = values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::String(value) => Some(value.trim().to_string()),
+                    Value::Null => None,
+                    value => Some(value.to_string()),
+                })
+                .filter(|value| !value.is_empty())
+                .collect();
+            (!values.is_empty()).then(|| values.join(" | "))
+        }
+        _ => None,
+    }
+}
+
+/// Value of `key` in a JSON record as a number, parsing strings
+fn json_number(record: &Map<String, Value>, key: &str) -> Option<f64> {
+    match record.get(key)? {
+        Value::Number(value) => value.as_f64(),
+        Value::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{DataSource, MediaFiles, Page};
+use crate::DwcaExtension;
+use crate::darwin_core::{ArchiveBuilder, MeasurementOrFact, Metadata, Occurrence, Verbatim};
+use crate::download_report::ReportEntry;
+use crate::media_license::MediaLicensePolicy;
+
+const OBIS_BASE_URL: &str = "https://api.obis.org/v3";
+const PAGE_SIZE: usize = 1000;
+
+/// Time to wait between page requests, to go easy on the OBIS API
+const REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What to download from OBIS, in the terms of its occurrence API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObisQuery {
+    /// Scientific name, including everything below it
+    pub scientific_name: Option<String>,
+    /// WoRMS AphiaID of a taxon, including everything below it
+    pub taxon_id: Option<i32>,
+    /// Area as WKT in WGS84, e.g. POLYGON((...))
+    pub geometry: Option<String>,
+    /// Shallowest depth in meters
+    pub start_depth: Option<f64>,
+    /// Deepest depth in meters
+    pub end_depth: Option<f64>,
+    /// Earliest date, e.g. 2020-01-01
+    pub start_date: Option<String>,
+    /// Latest date, e.g. 2020-01-01
+    pub end_date: Option<String>,
+}
+
+impl ObisQuery {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Query parameters for the occurrence API
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(name) = &self.scientific_name {
+            params.push(("scientificname", name.clone()));
+        }
+        if let Some(taxon_id) = self.taxon_id {
+            params.push(("taxonid", taxon_id.to_string()));
+        }
+        if let Some(geometry) = &self.geometry {
+            params.push(("geometry", geometry.clone()));
+        }
+        if let Some(depth) = self.start_depth {
+            params.push(("startdepth", depth.to_string()));
+        }
+        if let Some(depth) = self.end_depth {
+            params.push(("enddepth", depth.to_string()));
+        }
+        if let Some(date) = &self.start_date {
+            params.push(("startdate", date.clone()));
+        }
+        if let Some(date) = &self.end_date {
+            params.push(("enddate", date.clone()));
+        }
+        params
+    }
+
+    /// Human-readable criteria for the archive's EML
+    fn criteria(&self) -> Vec<String> {
+        let mut criteria = Vec::new();
+        if let Some(name) = &self.scientific_name {
+            criteria.push(format!("Taxon: {name}"));
+        }
+        if let Some(taxon_id) = self.taxon_id {
+            criteria.push(format!("AphiaID: {taxon_id}"));
+        }
+        if let Some(geometry) = &self.geometry {
+            criteria.push(format!("Area: {geometry}"));
+        }
+        match (self.start_depth, self.end_depth) {
+            (Some(start), Some(end)) => criteria.push(format!("Depth: {start} to {end} m")),
+            (Some(start), None) => criteria.push(format!("Depth: {start} m or deeper")),
+            (None, Some(end)) => criteria.push(format!("Depth: {end} m or shallower")),
+            (None, None) => {}
+        }
+        if let Some(date) = &self.start_date {
+            criteria.push(format!("Observed on or after: {date}"));
+        }
+        if let Some(date) = &self.end_date {
+            criteria.push(format!("Observed on or before: {date}"));
+        }
+        criteria
+    }
+}
+
+/// An occurrence from the OBIS occurrence API. OBIS returns DarwinCore terms
+/// as datasets published them, so values are read leniently from the JSON.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct ObisRecord(pub Map<String, Value>);
+
+impl ObisRecord {
+    fn text(&self, key: &str) -> Option<String> {
+        super::json_text(&self.0, key)
+    }
+
+    fn number(&self, key: &str) -> Option<f64> {
+        super::json_number(&self.0, key)
+    }
+
+    /// OBIS's ID for the record, used for paging
+    fn obis_id(&self) -> Option<String> {
+        self.text("id")
+    }
+
+    /// occurrenceID from the dataset, or the record's OBIS URL if the dataset
+    /// didn't provide one
+    fn occurrence_id(&self) -> String {
+        self.text("occurrenceID")
+            .or_else(|| self.obis_id().map(|id| format!("https://obis.org/occurrence/{id}")))
+            .unwrap_or_default()
+    }
+
+    /// Depth, its accuracy, and the range it came from. OBIS gives the
+    /// minimum and maximum depth from the dataset, so depth is the middle of
+    /// the range and the accuracy is half its width.
+    fn depth(&self) -> (Option<f64>, Option<f64>, Option<String>) {
+        let min = self.number("minimumDepthInMeters");
+        let max = self.number("maximumDepthInMeters");
+        match (min, max) {
+            (Some(min), Some(max)) if max > min => (
+                Some((min + max) / 2.0),
+                Some((max - min) / 2.0),
+                Some(format!("{min}-{max} m")),
+            ),
+            (Some(depth), _) | (None, Some(depth)) => (Some(depth), None, None),
+            (None, None) => (self.number("depth"), None, None),
+        }
+    }
+}
+
+impl From<&ObisRecord> for Occurrence {
+    fn from(record: &ObisRecord) -> Self {
+        let (depth, depth_accuracy, verbatim_depth) = record.depth();
+        Occurrence {
+            occurrence_id: record.occurrence_id(),
+            basis_of_record: record.text("basisOfRecord").unwrap_or_else(|| "Occurrence".to_string()),
+            recorded_by: record.text("recordedBy").unwrap_or_default(),
+            event_date: record.text("eventDate"),
+            year: record.number("date_year").map(|year| year as i32),
+            decimal_latitude: record.number("decimalLatitude"),
+            decimal_longitude: record.number("decimalLongitude"),
+            coordinate_uncertainty_in_meters: record.number("coordinateUncertaintyInMeters"),
+            scientific_name: record.text("scientificName"),
+            taxon_rank: record.text("taxonRank").map(|rank| rank.to_lowercase()),
+            kingdom: record.text("kingdom"),
+            phylum: record.text("phylum"),
+            class: record.text("class"),
+            order: record.text("order"),
+            family: record.text("family"),
+            genus: record.text("genus"),
+            taxon_id: record.text("scientificNameID"),
+            occurrence_remarks: record.text("occurrenceRemarks"),
+            license: record.text("license"),
+            modified: record.text("modified"),
+            locality: record.text("locality"),
+            water_body: record.text("waterBody"),
+            depth,
+            depth_accuracy,
+            verbatim_depth: record.text("verbatimDepth").or(verbatim_depth),
+            institution_code: record.text("institutionCode"),
+            dataset_name: record.text("datasetName"),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&ObisRecord> for Verbatim {
+    fn from(record: &ObisRecord) -> Self {
+        Verbatim {
+            occurrence_id: record.occurrence_id(),
+            event_date: record.text("eventDate"),
+            verbatim_event_date: record.text("verbatimEventDate"),
+            decimal_latitude: record.text("decimalLatitude"),
+            decimal_longitude: record.text("decimalLongitude"),
+            coordinate_uncertainty_in_meters: record.text("coordinateUncertaintyInMeters"),
+            verbatim_locality: record.text("verbatimLocality").or_else(|| record.text("locality")),
+            scientific_name: record.text("originalScientificName")
+                .or_else(|| record.text("scientificName")),
+            taxon_rank: record.text("taxonRank"),
+            taxon_id: record.text("aphiaID"),
+            vernacular_name: record.text("vernacularName"),
+            recorded_by: record.text("recordedBy"),
+            occurrence_remarks: record.text("occurrenceRemarks"),
+            license: record.text("license"),
+            modified: record.text("modified"),
+            ..Default::default()
+        }
+    }
+}
+
+/// MeasurementOrFact rows OBIS returned with a record, e.g. abundance or
+/// water temperature. Units go in the remarks since the extension doesn't
+/// have a column for them.
+fn measurements_or_facts(record: &ObisRecord) -> Vec<MeasurementOrFact> {
+    let Some(Value::Array(mofs)) = record.0.get("mof") else {
+        return Vec::new();
+    };
+    let occurrence_id = record.occurrence_id();
+    mofs.iter()
+        .filter_map(Value::as_object)
+        .filter_map(|mof| {
+            Some(MeasurementOrFact {
+                occurrence_id: occurrence_id.clone(),
+                measurement_type: super::json_text(mof, "measurementType")?,
+                measurement_value: super::json_text(mof, "measurementValue")?,
+                measurement_method: super::json_text(mof, "measurementMethod"),
+                measurement_remarks: super::json_text(mof, "measurementUnit")
+                    .map(|unit| format!("unit: {unit}")),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct OccurrenceResponse {
+    total: Option<usize>,
+    #[serde(default)]
+    results: Vec<ObisRecord>,
+}
+
+/// Occurrences from the Ocean Biodiversity Information System, paged by
+/// OBIS record ID
+pub struct ObisSource {
+    query: ObisQuery,
+    base_url: String,
+    /// Whether to ask OBIS for each record's MeasurementOrFact rows, which
+    /// only matters for archives with that extension
+    include_measurements: bool,
+}
+
+impl ObisSource {
+    pub fn new(query: ObisQuery, include_measurements: bool) -> Self {
+        Self::with_base_url(query, include_measurements, OBIS_BASE_URL.to_string())
+    }
+
+    /// Source for a different API server, e.g. a mock server in tests.
+    /// Requests aren't rate limited.
+    pub fn with_base_url(query: ObisQuery, include_measurements: bool, base_url: String) -> Self {
+        Self { query, base_url, include_measurements }
+    }
+
+    pub fn query(&self) -> &ObisQuery {
+        &self.query
+    }
+}
+
+impl DataSource for ObisSource {
+    type Record = ObisRecord;
+    type PageContext = ();
+
+    fn metadata(&self) -> Metadata {
+        let mut abstract_lines = vec![
+            "Occurrences exported from the Ocean Biodiversity Information System using the \
+            following criteria:"
+                .to_string(),
+        ];
+        abstract_lines.extend(self.query.criteria().into_iter().map(|c| format!("* {c}")));
+        Metadata { abstract_lines, inat_query: None }
+    }
+
+    async fn fetch_page(
+        &self,
+        cursor: Option<String>,
+    ) -> Result<Page<ObisRecord>, Box<dyn std::error::Error>> {
+        if cursor.is_some() && self.base_url == OBIS_BASE_URL {
+            tokio::time::sleep(REQUEST_INTERVAL).await;
+        }
+
+        let mut params = self.query.params();
+        params.push(("size", PAGE_SIZE.to_string()));
+        if let Some(after) = cursor {
+            params.push(("after", after));
+        }
+        if self.include_measurements {
+            params.push(("mof", "true".to_string()));
+        }
+        let response: OccurrenceResponse = crate::api::client::http_client()
+            .get(format!("{}/occurrence", self.base_url))
+            .query(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let next_cursor = if response.results.len() < PAGE_SIZE {
+            None
+        } else {
+            response.results.last().and_then(ObisRecord::obis_id)
+        };
+        Ok(Page {
+            total_results: response.total,
+            next_cursor,
+            records: response.results,
+        })
+    }
+
+    async fn add_records(
+        &self,
+        records: &[ObisRecord],
+        archive: &mut ArchiveBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let occurrences: Vec<Occurrence> = records.iter().map(Occurrence::from).collect();
+        archive.add_occurrences(&occurrences).await?;
+        let verbatim: Vec<Verbatim> = records.iter().map(Verbatim::from).collect();
+        archive.add_verbatim(&verbatim).await?;
+        Ok(())
+    }
+
+    /// OBIS records can carry MeasurementOrFact rows. They don't have
+    /// identifications, comments, or media Chuck can download.
+    async fn add_extensions(
+        &self,
+        records: &[ObisRecord],
+        _context: &(),
+        _media: &MediaFiles,
+        extensions: &[DwcaExtension],
+        archive: &mut ArchiveBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if extensions.contains(&DwcaExtension::MeasurementOrFact) {
+            let rows: Vec<MeasurementOrFact> = records.iter().flat_map(measurements_or_facts).collect();
+            if !rows.is_empty() {
+                archive.add_measurements_or_facts(&rows).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn media_count(_records: &[ObisRecord]) -> usize {
+        0
+    }
+
+    fn photo_licenses(_records: &[ObisRecord]) -> BTreeMap<String, usize> {
+        BTreeMap::new()
+    }
+
+    fn apply_media_license_policy(
+        records: &[ObisRecord],
+        _policy: MediaLicensePolicy,
+    ) -> (Vec<ObisRecord>, Vec<ReportEntry>) {
+        (records.to_vec(), Vec::new())
+    }
+
+    fn download_media<F>(
+        _records: Vec<ObisRecord>,
+        _media_dir: PathBuf,
+        _callback: F,
+        _cancellation_token: Option<Arc<AtomicBool>>,
+    ) -> impl Future<Output = Result<MediaFiles, String>> + Send + 'static
+    where
+        F: Fn(usize) + Send + Sync + Clone + 'static,
+    {
+        std::future::ready(Ok(MediaFiles::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn record(value: Value) -> ObisRecord {
+        ObisRecord(value.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn test_occurrence_from_obis_record_with_depth_range() {
+        let record = record(serde_json::json!({
+            "id": "00003cf7-f2fc-4c53-98a6-7d846e70f5d1",
+            "basisOfRecord": "HumanObservation",
+            "eventDate": "2011-07-14",
+            "decimalLatitude": 52.1,
+            "decimalLongitude": 3.2,
+            "scientificName": "Abra alba",
+            "scientificNameID": "urn:lsid:marinespecies.org:taxname:141433",
+            "minimumDepthInMeters": 10,
+            "maximumDepthInMeters": "20",
+        }));
+
+        let occurrence = Occurrence::from(&record);
+
+        assert_eq!(
+            occurrence.occurrence_id,
+            "https://obis.org/occurrence/00003cf7-f2fc-4c53-98a6-7d846e70f5d1"
+        );
+        assert_eq!(occurrence.depth, Some(15.0));
+        assert_eq!(occurrence.depth_accuracy, Some(5.0));
+        assert_eq!(occurrence.verbatim_depth.as_deref(), Some("10-20 m"));
+        assert_eq!(
+            occurrence.taxon_id.as_deref(),
+            Some("urn:lsid:marinespecies.org:taxname:141433")
+        );
+    }
+
+    #[test]
+    fn test_measurements_or_facts() {
+        let record = record(serde_json::json!({
+            "occurrenceID": "survey-1",
+            "mof": [
+                {"measurementType": "abundance", "measurementValue": "12"},
+                {"measurementType": "temperature", "measurementValue": 8.5, "measurementUnit": "°C"},
+                {"measurementType": "no value"},
+            ],
+        }));
+
+        let rows = measurements_or_facts(&record);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].occurrence_id, "survey-1");
+        assert_eq!(rows[1].measurement_value, "8.5");
+        assert_eq!(rows[1].measurement_remarks.as_deref(), Some("unit: °C"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_sends_filters() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/occurrence")
+                .query_param("scientificname", "Abra alba")
+                .query_param("startdepth", "10")
+                .query_param("enddepth", "200")
+                .query_param("after", "abc")
+                .query_param("mof", "true");
+            then.status(200).json_body(serde_json::json!({
+                "total": 1001,
+                "results": [{"id": "def", "scientificName": "Abra alba"}],
+            }));
+        });
+        let query = ObisQuery {
+            scientific_name: Some("Abra alba".to_string()),
+            start_depth: Some(10.0),
+            end_depth: Some(200.0),
+            ..Default::default()
+        };
+        let source = ObisSource::with_base_url(query, true, server.base_url());
+
+        let page = source.fetch_page(Some("abc".to_string())).await.unwrap();
+
+        mock.assert();
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.total_results, Some(1001));
+        assert_eq!(page.next_cursor, None);
+    }
+}
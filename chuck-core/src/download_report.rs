@@ -0,0 +1,89 @@
+/// Records that were skipped or failed while generating an archive. Written
+/// to the archive alongside the data so users can see what's missing and why
+/// without digging through logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    Error,
+    Skip,
+}
+
+impl ReportKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportEntry {
+    pub kind: ReportKind,
+    /// What was affected, e.g. "photo" or "sound"
+    pub item_type: String,
+    pub item_id: String,
+    /// occurrenceID of the record the item belongs to
+    pub occurrence_id: String,
+    pub reason: String,
+}
+
+impl ReportEntry {
+    pub const FILENAME: &'static str = "report.csv";
+
+    pub fn skip(
+        item_type: &str,
+        item_id: impl Into<String>,
+        occurrence_id: &str,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind: ReportKind::Skip,
+            item_type: item_type.to_string(),
+            item_id: item_id.into(),
+            occurrence_id: occurrence_id.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn error(
+        item_type: &str,
+        item_id: impl Into<String>,
+        occurrence_id: &str,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind: ReportKind::Error,
+            ..Self::skip(item_type, item_id, occurrence_id, reason)
+        }
+    }
+
+    pub fn csv_headers() -> Vec<&'static str> {
+        vec!["kind", "itemType", "itemID", "occurrenceID", "reason"]
+    }
+
+    pub fn to_csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.as_str().to_string(),
+            self.item_type.clone(),
+            self.item_id.clone(),
+            self.occurrence_id.clone(),
+            self.reason.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_record_matches_headers() {
+        let entry = ReportEntry::error("photo", "11", "https://www.inaturalist.org/observations/1", "timed out");
+
+        let record = entry.to_csv_record();
+
+        assert_eq!(record.len(), ReportEntry::csv_headers().len());
+        assert_eq!(record[0], "error");
+        assert_eq!(record[4], "timed out");
+    }
+}
@@ -1,6 +1,8 @@
 use inaturalist::apis::observations_api;
 use crate::DwcaExtension;
 use crate::darwin_core::Metadata;
+use crate::data_source::{DataSource, INatSource, MediaFiles};
+use crate::media_license::MediaLicensePolicy;
 
 /// Progress information for download operations
 #[derive(Debug, Clone)]
@@ -34,16 +36,24 @@ pub enum DownloadStage {
     Merging { current: usize, total: usize },
 }
 
-/// Centralized downloader for iNaturalist observations to DarwinCore Archive
-pub struct Downloader {
-    params: observations_api::ObservationsGetParams,
+/// Centralized downloader that builds a DarwinCore Archive from a data
+/// source, iNaturalist observations unless another source is given
+pub struct Downloader<S: DataSource = INatSource> {
+    source: S,
     extensions: Vec<DwcaExtension>,
     fetch_media: bool,
     metadata: Metadata,
-    config: Option<inaturalist::apis::configuration::Configuration>,
-    jwt: Option<String>,
+    media_license_policy: MediaLicensePolicy,
 }
 
+/// Media download for a batch running in the background, with the records
+/// and page context its extension rows are built from once it finishes
+type PendingMedia<S> = (
+    tokio::task::JoinHandle<Result<(MediaFiles, usize), String>>,
+    Vec<<S as DataSource>::Record>,
+    <S as DataSource>::PageContext,
+);
+
 impl Downloader {
     pub fn new(
         params: observations_api::ObservationsGetParams,
@@ -51,7 +61,7 @@ impl Downloader {
         fetch_media: bool,
         jwt: Option<String>,
     ) -> Self {
-        Self::from_parts(params, extensions, fetch_media, None, jwt)
+        Self::from_source(INatSource::new(params, jwt), extensions, fetch_media)
     }
 
     /// Create downloader with custom configuration for testing
@@ -61,44 +71,54 @@ impl Downloader {
         fetch_media: bool,
         config: inaturalist::apis::configuration::Configuration,
     ) -> Self {
-        Self::from_parts(params, extensions, fetch_media, Some(config), None)
+        Self::from_source(INatSource::with_config(params, config), extensions, fetch_media)
     }
 
-    /// Internal constructor that builds metadata and creates the Downloader
-    fn from_parts(
-        params: observations_api::ObservationsGetParams,
-        extensions: Vec<DwcaExtension>,
-        fetch_media: bool,
-        config: Option<inaturalist::apis::configuration::Configuration>,
-        jwt: Option<String>,
-    ) -> Self {
-        // Build metadata
-        let mut abstract_lines = vec![
-            "Observations exported from iNaturalist using the following criteria:".to_string()
-        ];
-        abstract_lines.extend(
-            crate::api::params::extract_criteria(&params)
-                .into_iter()
-                .map(|c| format!("* {c}"))
-        );
+    /// Link each occurrence to its observation and taxon on iNat with
+    /// references and taxonConceptID URLs
+    pub fn with_record_links(mut self, record_links: bool) -> Self {
+        self.source = self.source.with_record_links(record_links);
+        if record_links {
+            self.metadata.abstract_lines.push(
+                "* Occurrences link to their iNaturalist observations and taxa".to_string()
+            );
+        }
+        self
+    }
+}
+
+impl<S: DataSource> Downloader<S> {
+    /// Create downloader for any data source
+    pub fn from_source(source: S, extensions: Vec<DwcaExtension>, fetch_media: bool) -> Self {
+        let mut metadata = source.metadata();
         if fetch_media {
-            abstract_lines.push(
+            metadata.abstract_lines.push(
                 "* Photos and sounds downloaded and included in archive".to_string()
             );
         }
-        let inat_query = Some(crate::api::params::serialize_params(&params));
-        let metadata = Metadata { abstract_lines, inat_query };
 
         Self {
-            params,
+            source,
             extensions,
             fetch_media,
             metadata,
-            config,
-            jwt,
+            media_license_policy: MediaLicensePolicy::default(),
         }
     }
 
+    /// Only download media whose license the policy allows. Skipped media are
+    /// listed in the archive's report.csv.
+    pub fn with_media_license_policy(mut self, policy: MediaLicensePolicy) -> Self {
+        self.media_license_policy = policy;
+        if policy != MediaLicensePolicy::All && self.fetch_media {
+            self.metadata.abstract_lines.push(format!(
+                "* Media license policy: {}",
+                policy.as_str()
+            ));
+        }
+        self
+    }
+
     /// Execute the download and build the archive
     pub async fn execute<F>(
         &self,
@@ -127,6 +147,8 @@ impl Downloader {
 
         let mut progress = DownloadProgress::default();
         let mut cumulative_media_seen: usize = 0;
+        let mut photo_licenses: std::collections::BTreeMap<String, usize> =
+            std::collections::BTreeMap::new();
 
         // Track photo/sound IDs already committed to the ZIP so that photos shared
         // across observations (and therefore present in multiple API pages) are not
@@ -135,15 +157,10 @@ impl Downloader {
         let mut added_sound_ids: std::collections::HashSet<i32> = std::collections::HashSet::new();
 
         // Track pending media download from previous batch
-        #[allow(clippy::type_complexity)]
-        let mut pending_media: Option<(
-            tokio::task::JoinHandle<Result<(HashMap<i32, String>, HashMap<i32, String>, usize), String>>,
-            Vec<inaturalist::models::Observation>,
-            HashMap<i32, inaturalist::models::ShowTaxon>,
-        )> = None;
+        let mut pending_media: Option<PendingMedia<S>> = None;
 
         // Pagination loop with true pipeline
-        let mut id_below: Option<i32> = None;
+        let mut cursor: Option<String> = None;
         loop {
             // Check cancellation
             if let Some(token) = &cancellation_token {
@@ -159,7 +176,7 @@ impl Downloader {
             // Fetch next batch. Abort any in-flight media task before propagating errors:
             // dropping a JoinHandle does not abort the task in Tokio, so if we return
             // early the spawned task would keep running and sending stale progress events.
-            let batch = match self.fetch_batch_with_rate_limit(id_below).await {
+            let batch = match self.source.fetch_page(cursor.take()).await {
                 Ok(b) => b,
                 Err(e) => {
                     if let Some((handle, _, _)) = pending_media.take() {
@@ -168,46 +185,18 @@ impl Downloader {
                     return Err(e);
                 }
             };
-            if batch.results.is_empty() {
-                // Before breaking, finish any pending media downloads
-                if let Some((media_handle, observations, taxa_hash)) = pending_media.take() {
-                    // Check cancellation before waiting for media
-                    if let Some(token) = &cancellation_token {
-                        if token.load(Ordering::Relaxed) {
-                            media_handle.abort();
-                            return Err("Download cancelled".into());
-                        }
-                    }
-                    log::debug!("Waiting for final media batch task");
-                    let (photo_mapping, sound_mapping, media_count) = media_handle.await
-                        .map_err(|e| format!("Media download task failed: {e}"))??;
-                    log::debug!(
-                        "Final media batch done: {media_count} downloaded \
-                        ({} photos, {} sounds)",
-                        photo_mapping.len(), sound_mapping.len()
-                    );
-                    progress.media_current += media_count;
-                    self.process_extensions(
-                        &observations, &mut archive, &photo_mapping, &sound_mapping, &taxa_hash
-                    ).await?;
-                    commit_media(
-                        &mut archive, &photo_mapping, &sound_mapping,
-                        &mut added_photo_ids, &mut added_sound_ids,
-                    )?;
-                }
+            if batch.records.is_empty() {
                 break;
             }
 
             // Capture total from first batch
             if progress.observations_total == 0 {
-                progress.observations_total = batch.total_results.unwrap_or(0) as usize;
+                progress.observations_total = batch.total_results.unwrap_or(0);
             }
 
-            // Prepare batch: fetch taxa, convert to occurrences, write to CSV
-            let (taxa_hash, media_count) = match self.prepare_batch(
-                &batch, &mut archive, &mut progress, &progress_callback
-            ).await {
-                Ok(r) => r,
+            // Prepare batch: write occurrences and keep what extensions need
+            let context = match self.source.add_records(&batch.records, &mut archive).await {
+                Ok(context) => context,
                 Err(e) => {
                     if let Some((handle, _, _)) = pending_media.take() {
                         handle.abort();
@@ -215,10 +204,37 @@ impl Downloader {
                     return Err(e);
                 }
             };
+            progress.observations_current += batch.records.len();
+            progress.stage = DownloadStage::Fetching;
+            progress_callback(progress.clone());
+
+            // Decide which media this batch may embed before anything is
+            // downloaded, and leave skipped media out of the estimate
+            let media_records = if self.fetch_media {
+                for (license, count) in S::photo_licenses(&batch.records) {
+                    *photo_licenses.entry(license).or_insert(0) += count;
+                }
+                let (allowed, skipped) = S::apply_media_license_policy(
+                    &batch.records,
+                    self.media_license_policy,
+                );
+                if !skipped.is_empty() {
+                    log::info!("Skipping {} media due to license policy", skipped.len());
+                }
+                cumulative_media_seen += S::media_count(&batch.records).saturating_sub(skipped.len());
+                if let Err(e) = archive.add_report_entries(skipped) {
+                    if let Some((handle, _, _)) = pending_media.take() {
+                        handle.abort();
+                    }
+                    return Err(e);
+                }
+                allowed
+            } else {
+                Vec::new()
+            };
 
             // Update media estimate using running average (never decreasing)
             if self.fetch_media {
-                cumulative_media_seen += media_count;
                 progress.media_total = update_photo_estimate(
                     progress.media_total,
                     cumulative_media_seen,
@@ -228,35 +244,16 @@ impl Downloader {
             }
 
             // If there's a pending media download from the previous batch, wait and process
-            if let Some((media_handle, observations, prev_taxa_hash)) = pending_media.take() {
-                // Check cancellation before waiting for media
-                if let Some(token) = &cancellation_token {
-                    if token.load(Ordering::Relaxed) {
-                        media_handle.abort();
-                        return Err("Download cancelled".into());
-                    }
-                }
-                log::debug!("Waiting for previous media batch task");
-                let (photo_mapping, sound_mapping, media_count) = media_handle.await
-                    .map_err(|e| format!("Media download task failed: {e}"))??;
-                log::debug!(
-                    "Media batch done: {media_count} downloaded \
-                    ({} photos, {} sounds)",
-                    photo_mapping.len(), sound_mapping.len()
-                );
-                progress.media_current += media_count;
-                self.process_extensions(
-                    &observations, &mut archive, &photo_mapping, &sound_mapping, &prev_taxa_hash
+            if let Some(pending) = pending_media.take() {
+                self.finish_media(
+                    pending, &mut archive, &mut progress,
+                    &mut added_photo_ids, &mut added_sound_ids, &cancellation_token,
                 ).await?;
-                commit_media(
-                    &mut archive, &photo_mapping, &sound_mapping,
-                    &mut added_photo_ids, &mut added_sound_ids,
-                )?;
             }
 
             // Start media downloads for current batch in background
             let media_handle = self.start_media_downloads(
-                &batch,
+                media_records,
                 archive.media_dir(),
                 &mut progress,
                 &progress_callback,
@@ -265,16 +262,31 @@ impl Downloader {
 
             // Store handle and data for next iteration (or process immediately if no media)
             if let Some(handle) = media_handle {
-                pending_media = Some((handle, batch.results.clone(), taxa_hash));
+                pending_media = Some((handle, batch.records, context));
             } else {
                 // No media to download, process extensions immediately
-                self.process_extensions(
-                    &batch.results, &mut archive, &HashMap::new(), &HashMap::new(), &taxa_hash
+                self.source.add_extensions(
+                    &batch.records, &context, &MediaFiles::default(), &self.extensions, &mut archive
                 ).await?;
             }
 
             // Update pagination for next iteration
-            id_below = batch.results.last().and_then(|o| o.id);
+            match batch.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+
+        // Finish any pending media downloads from the last batch
+        if let Some(pending) = pending_media.take() {
+            self.finish_media(
+                pending, &mut archive, &mut progress,
+                &mut added_photo_ids, &mut added_sound_ids, &cancellation_token,
+            ).await?;
+        }
+
+        if !photo_licenses.is_empty() {
+            log::info!("Photo licenses: {photo_licenses:?}");
         }
 
         // Build final archive
@@ -291,85 +303,59 @@ impl Downloader {
         Ok(())
     }
 
-    async fn fetch_batch(
-        &self,
-        id_below: Option<i32>,
-    ) -> Result<inaturalist::models::ObservationsResponse, Box<dyn std::error::Error>> {
-        use crate::api::client;
-
-        let mut fetch_params = self.params.clone();
-        if let Some(id) = id_below {
-            fetch_params.id_below = Some(id.to_string());
-        }
-
-        // Use custom config if provided, otherwise use global config with JWT
-        if let Some(ref config) = self.config {
-            let config_lock = tokio::sync::RwLock::new(config.clone());
-            let response = client::fetch_observations_with_retry(&config_lock, fetch_params).await?;
-            Ok(response)
-        } else if let Some(ref jwt) = self.jwt {
-            let config_instance = client::create_config_with_jwt(Some(jwt.clone()));
-            let config = tokio::sync::RwLock::new(config_instance);
-            let response = client::fetch_observations_with_retry(&config, fetch_params).await?;
-            Ok(response)
-        } else {
-            let config = client::get_config().await;
-            let response = client::fetch_observations_with_retry(config, fetch_params).await?;
-            Ok(response)
-        }
-    }
-
-    /// Fetch batch with rate limiting applied
-    async fn fetch_batch_with_rate_limit(
+    /// Wait for a batch's media downloads, then write its extension rows and
+    /// add its media to the archive
+    #[allow(clippy::too_many_arguments)]
+    async fn finish_media(
         &self,
-        id_below: Option<i32>,
-    ) -> Result<inaturalist::models::ObservationsResponse, Box<dyn std::error::Error>> {
-        // Rate limit (skip for custom configs, e.g. tests with mock servers)
-        if self.config.is_none() {
-            crate::api::rate_limiter::get_rate_limiter()
-                .await
-                .wait_for_next_request()
-                .await;
+        (media_handle, records, context): PendingMedia<S>,
+        archive: &mut crate::darwin_core::ArchiveBuilder,
+        progress: &mut DownloadProgress,
+        added_photo_ids: &mut std::collections::HashSet<i32>,
+        added_sound_ids: &mut std::collections::HashSet<i32>,
+        cancellation_token: &Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Check cancellation before waiting for media
+        if let Some(token) = cancellation_token
+            && token.load(std::sync::atomic::Ordering::Relaxed)
+        {
+            media_handle.abort();
+            return Err("Download cancelled".into());
         }
-        self.fetch_batch(id_below).await
+        log::debug!("Waiting for media batch task");
+        let (media, media_count) = media_handle.await
+            .map_err(|e| format!("Media download task failed: {e}"))??;
+        log::debug!(
+            "Media batch done: {media_count} downloaded \
+            ({} photos, {} sounds)",
+            media.photos.len(), media.sounds.len()
+        );
+        progress.media_current += media_count;
+        self.source.add_extensions(&records, &context, &media, &self.extensions, archive).await?;
+        commit_media(archive, &media, added_photo_ids, added_sound_ids)
     }
 
     /// Start media (photo + sound) downloads as a background task.
-    /// Returns a handle that resolves to (photo_mapping, sound_mapping, media_downloaded_count).
-    #[allow(clippy::type_complexity)]
+    /// Returns a handle that resolves to the downloaded media and how many files were downloaded.
     fn start_media_downloads<F>(
         &self,
-        batch: &inaturalist::models::ObservationsResponse,
+        records: Vec<S::Record>,
         media_dir: std::path::PathBuf,
         progress: &mut DownloadProgress,
         callback: &F,
         cancellation_token: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
-    ) -> Option<tokio::task::JoinHandle<Result<(HashMap<i32, String>, HashMap<i32, String>, usize), String>>>
+    ) -> Option<tokio::task::JoinHandle<Result<(MediaFiles, usize), String>>>
     where
         F: Fn(DownloadProgress) + Send + Sync + Clone + 'static,
     {
-        use crate::darwin_core::{PhotoDownloader, SoundDownloader};
         use std::sync::Arc;
 
-        let photos_count = batch.results
-            .iter()
-            .filter_map(|o| o.photos.as_ref())
-            .flatten()
-            .count();
-        let sounds_count = batch.results
-            .iter()
-            .filter_map(|o| o.sounds.as_ref())
-            .flatten()
-            .filter(|s| s.file_url.is_some() && !s.hidden.unwrap_or(false))
-            .count();
-
-        if !self.fetch_media || (photos_count == 0 && sounds_count == 0) {
+        let media_count = S::media_count(&records);
+        if !self.fetch_media || media_count == 0 {
             return None;
         }
 
-        log::info!(
-            "Spawning media batch task: {photos_count} photos + {sounds_count} sounds"
-        );
+        log::info!("Spawning media batch task: {media_count} photos and sounds");
         progress.stage = DownloadStage::DownloadingMedia;
 
         let media_downloaded = Arc::new(std::sync::atomic::AtomicUsize::new(0));
@@ -387,132 +373,15 @@ impl Downloader {
             callback_clone(updated_progress);
         };
 
-        let observations = batch.results.clone();
-
+        let download = S::download_media(records, media_dir, media_callback, cancellation_token);
         let handle = tokio::spawn(async move {
-            let photo_callback = media_callback.clone();
-            let sound_callback = media_callback.clone();
-
-            let photo_mapping = PhotoDownloader::fetch_photos_to_dir(
-                &observations,
-                &media_dir,
-                photo_callback,
-                cancellation_token.clone(),
-            )
-            .await
-            .map_err(|e| e.to_string())?;
-
-            let sound_mapping = SoundDownloader::fetch_sounds_to_dir(
-                &observations,
-                &media_dir,
-                sound_callback,
-                cancellation_token,
-            )
-            .await
-            .map_err(|e| e.to_string())?;
-
+            let media = download.await?;
             let count = media_downloaded.load(std::sync::atomic::Ordering::Relaxed);
-            Ok((photo_mapping, sound_mapping, count))
+            Ok((media, count))
         });
 
         Some(handle)
     }
-
-    /// Prepare a batch by fetching taxa, converting to occurrences, and writing to CSV.
-    /// Returns taxa_hash and media_count (photos + sounds) for use in media downloading.
-    async fn prepare_batch<F>(
-        &self,
-        batch: &inaturalist::models::ObservationsResponse,
-        archive: &mut crate::darwin_core::ArchiveBuilder,
-        progress: &mut DownloadProgress,
-        callback: &F,
-    ) -> Result<(HashMap<i32, ShowTaxon>, usize), Box<dyn std::error::Error>>
-    where
-        F: Fn(DownloadProgress) + Send + Sync + Clone + 'static,
-    {
-        use crate::darwin_core::{Occurrence, collect_taxon_ids, fetch_taxa_for_observations};
-
-        // Fetch taxa for this batch
-        let taxon_ids = collect_taxon_ids(&batch.results);
-        let taxa_hash = fetch_taxa_for_observations(
-            &taxon_ids,
-            None::<fn(usize, usize)>,
-            self.config.as_ref(),
-        ).await?;
-
-        // Convert to occurrences
-        let occurrences: Vec<Occurrence> = batch.results
-            .iter()
-            .map(|obs| Occurrence::from((obs, &taxa_hash)))
-            .collect();
-
-        // Add to archive
-        archive.add_occurrences(&occurrences).await?;
-
-        // Update progress
-        progress.observations_current += batch.results.len();
-        progress.stage = DownloadStage::Fetching;
-        callback(progress.clone());
-
-        // Count photos + sounds for media download estimate
-        let photos_count = batch.results
-            .iter()
-            .filter_map(|o| o.photos.as_ref())
-            .flatten()
-            .count();
-        let sounds_count = batch.results
-            .iter()
-            .filter_map(|o| o.sounds.as_ref())
-            .flatten()
-            .filter(|s| s.file_url.is_some() && !s.hidden.unwrap_or(false))
-            .count();
-
-        Ok((taxa_hash, photos_count + sounds_count))
-    }
-
-    async fn process_extensions(
-        &self,
-        observations: &[Observation],
-        archive: &mut crate::darwin_core::ArchiveBuilder,
-        photo_mapping: &HashMap<i32, String>,
-        sound_mapping: &HashMap<i32, String>,
-        taxa_hash: &HashMap<i32, ShowTaxon>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Multimedia extension
-        if self.extensions.contains(&DwcaExtension::SimpleMultimedia) {
-            let mut records = convert_to_photo_multimedia(observations, photo_mapping);
-            records.extend(convert_to_sound_multimedia(observations, sound_mapping));
-            if !records.is_empty() {
-                archive.add_multimedia(&records).await?;
-            }
-        }
-
-        // Audiovisual extension
-        if self.extensions.contains(&DwcaExtension::Audiovisual) {
-            let records = convert_to_audiovisual(observations, photo_mapping);
-            if !records.is_empty() {
-                archive.add_audiovisual(&records).await?;
-            }
-        }
-
-        // Identifications extension
-        if self.extensions.contains(&DwcaExtension::Identifications) {
-            let records = convert_to_identifications(observations, taxa_hash);
-            if !records.is_empty() {
-                archive.add_identifications(&records).await?;
-            }
-        }
-
-        // Comments extension
-        if self.extensions.contains(&DwcaExtension::Comments) {
-            let records = convert_to_comments(observations);
-            if !records.is_empty() {
-                archive.add_comments(&records).await?;
-            }
-        }
-
-        Ok(())
-    }
 }
 
 /// Add media files from a batch to the archive, skipping any photo or sound IDs already
@@ -520,12 +389,11 @@ impl Downloader {
 /// without writing it to the ZIP.
 fn commit_media(
     archive: &mut crate::darwin_core::ArchiveBuilder,
-    photo_mapping: &HashMap<i32, String>,
-    sound_mapping: &HashMap<i32, String>,
+    media: &MediaFiles,
     added_photo_ids: &mut std::collections::HashSet<i32>,
     added_sound_ids: &mut std::collections::HashSet<i32>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    for (id, rel_path) in photo_mapping {
+    for (id, rel_path) in &media.photos {
         if added_photo_ids.insert(*id) {
             archive.add_media_from_temp(rel_path)?;
         } else {
@@ -533,7 +401,7 @@ fn commit_media(
             let _ = std::fs::remove_file(local);
         }
     }
-    for (id, rel_path) in sound_mapping {
+    for (id, rel_path) in &media.sounds {
         if added_sound_ids.insert(*id) {
             archive.add_media_from_temp(rel_path)?;
         } else {
@@ -562,7 +430,7 @@ pub fn update_photo_estimate(
 
 use std::collections::HashMap;
 use inaturalist::models::{Observation, ShowTaxon};
-use crate::darwin_core::{Multimedia, Audiovisual, Identification, Comment};
+use crate::darwin_core::{Multimedia, Audiovisual, Identification, Comment, MeasurementOrFact};
 
 /// Convert observations to photo multimedia records
 pub fn convert_to_photo_multimedia(
@@ -655,6 +523,40 @@ pub fn convert_to_comments(observations: &[Observation]) -> Vec<Comment> {
         .collect()
 }
 
+/// Convert observation annotations like "Life Stage=Adult" to MeasurementOrFact
+/// records, skipping annotations the community has voted down
+pub fn convert_to_measurements_or_facts(observations: &[Observation]) -> Vec<MeasurementOrFact> {
+    observations
+        .iter()
+        .filter_map(|obs| {
+            let occurrence_id = obs.id.map(|id| id.to_string())?;
+            Some(
+                obs.annotations
+                    .as_ref()?
+                    .iter()
+                    .filter(|a| a.vote_score.unwrap_or(0) >= 0)
+                    .filter_map(|annotation| {
+                        let (attribute, value) = annotation
+                            .concatenated_attr_val
+                            .as_deref()?
+                            .split_once('=')?;
+                        Some(MeasurementOrFact {
+                            occurrence_id: occurrence_id.clone(),
+                            measurement_type: attribute.to_string(),
+                            measurement_value: value.to_string(),
+                            measurement_method: Some("iNaturalist annotation".to_string()),
+                            measurement_remarks: annotation
+                                .vote_score
+                                .map(|score| format!("vote score: {score}")),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -672,7 +574,7 @@ mod tests {
 
         let downloader = Downloader::new(params, extensions, true, None);
 
-        assert!(downloader.params.taxon_id == Some(vec!["47126".to_string()]));
+        assert!(downloader.source.params().taxon_id == Some(vec!["47126".to_string()]));
         assert_eq!(downloader.extensions.len(), 1);
         assert!(downloader.fetch_media);
     }
@@ -960,4 +862,35 @@ mod tests {
         assert_eq!(comments[1].text, Some("no hidden field".to_string()));
     }
 
+    #[test]
+    fn test_convert_to_measurements_or_facts() {
+        use inaturalist::models::{Annotation, Observation};
+
+        let annotation = |concatenated_attr_val: &str, vote_score: i32| Annotation {
+            concatenated_attr_val: Some(concatenated_attr_val.to_string()),
+            vote_score: Some(vote_score),
+            ..Default::default()
+        };
+        let observations = vec![Observation {
+            id: Some(123),
+            annotations: Some(vec![
+                annotation("Life Stage=Adult", 2),
+                annotation("Sex=Female", -1),
+                annotation("Alive or Dead=Alive", 0),
+                annotation("malformed", 1),
+            ]),
+            ..Default::default()
+        }];
+
+        let records = convert_to_measurements_or_facts(&observations);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].occurrence_id, "123");
+        assert_eq!(records[0].measurement_type, "Life Stage");
+        assert_eq!(records[0].measurement_value, "Adult");
+        assert_eq!(records[0].measurement_remarks, Some("vote score: 2".to_string()));
+        assert_eq!(records[1].measurement_type, "Alive or Dead");
+        assert_eq!(records[1].measurement_value, "Alive");
+    }
+
 }
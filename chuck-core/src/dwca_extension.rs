@@ -8,6 +8,8 @@ pub enum DwcaExtension {
     Identifications,
     /// Comments extension
     Comments,
+    /// Measurement or Facts extension, holding iNat annotations
+    MeasurementOrFact,
 }
 
 impl DwcaExtension {
@@ -18,6 +20,7 @@ impl DwcaExtension {
             "http://rs.tdwg.org/ac/terms/Multimedia" => Some(Self::Audiovisual),
             "http://rs.tdwg.org/dwc/terms/Identification" => Some(Self::Identifications),
             "https://schema.org/Comment" => Some(Self::Comments),
+            "http://rs.tdwg.org/dwc/terms/MeasurementOrFact" => Some(Self::MeasurementOrFact),
             _ => None,
         }
     }
@@ -29,6 +32,7 @@ impl DwcaExtension {
             Self::Audiovisual => "audiovisual",
             Self::Identifications => "identifications",
             Self::Comments => "comments",
+            Self::MeasurementOrFact => "measurement_or_facts",
         }
     }
 
@@ -39,6 +43,7 @@ impl DwcaExtension {
             "http://rs.tdwg.org/ac/terms/Multimedia",
             "http://rs.tdwg.org/dwc/terms/Identification",
             "https://schema.org/Comment",
+            "http://rs.tdwg.org/dwc/terms/MeasurementOrFact",
         ]
     }
 }
@@ -50,6 +55,7 @@ impl std::fmt::Display for DwcaExtension {
             Self::Audiovisual => write!(f, "Audiovisual"),
             Self::Identifications => write!(f, "Identifications"),
             Self::Comments => write!(f, "Comments"),
+            Self::MeasurementOrFact => write!(f, "MeasurementOrFact"),
         }
     }
 }
@@ -86,21 +92,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_row_type_measurement_or_fact() {
+        assert_eq!(
+            DwcaExtension::from_row_type("http://rs.tdwg.org/dwc/terms/MeasurementOrFact"),
+            Some(DwcaExtension::MeasurementOrFact)
+        );
+    }
+
     #[test]
     fn test_table_name() {
         assert_eq!(DwcaExtension::SimpleMultimedia.table_name(), "multimedia");
         assert_eq!(DwcaExtension::Audiovisual.table_name(), "audiovisual");
         assert_eq!(DwcaExtension::Identifications.table_name(), "identifications");
         assert_eq!(DwcaExtension::Comments.table_name(), "comments");
+        assert_eq!(DwcaExtension::MeasurementOrFact.table_name(), "measurement_or_facts");
     }
 
     #[test]
     fn test_all_row_types() {
         let row_types = DwcaExtension::all_row_types();
-        assert_eq!(row_types.len(), 4);
+        assert_eq!(row_types.len(), 5);
         assert!(row_types.contains(&"http://rs.gbif.org/terms/1.0/Multimedia"));
         assert!(row_types.contains(&"http://rs.tdwg.org/ac/terms/Multimedia"));
         assert!(row_types.contains(&"http://rs.tdwg.org/dwc/terms/Identification"));
         assert!(row_types.contains(&"https://schema.org/Comment"));
+        assert!(row_types.contains(&"http://rs.tdwg.org/dwc/terms/MeasurementOrFact"));
     }
 }
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthError;
+
+/// Code for errors that don't have one of their own
+pub const UNKNOWN_ERROR_CODE: &str = "unknown";
+
+/// Errors with a stable snake_case code that the app and scripts can match
+/// on instead of parsing messages, which are free to change
+pub trait ErrorCode: std::fmt::Display {
+    fn code(&self) -> &'static str;
+
+    /// What the user can do about the error, when there's more to say than
+    /// the message
+    fn guidance(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// An error as presented to users, e.g. serialized to the frontend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub code: String,
+    pub message: String,
+    pub guidance: Option<String>,
+}
+
+impl ErrorReport {
+    pub fn of(error: &impl ErrorCode) -> Self {
+        Self {
+            code: error.code().to_string(),
+            message: error.to_string(),
+            guidance: error.guidance().map(str::to_string),
+        }
+    }
+
+    /// Report for any error, with the code of the first error in its source
+    /// chain that has one, or UNKNOWN_ERROR_CODE
+    pub fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        let mut current = Some(error);
+        while let Some(e) = current {
+            if let Some(auth_error) = e.downcast_ref::<AuthError>() {
+                return Self { message: error.to_string(), ..Self::of(auth_error) };
+            }
+            current = e.source();
+        }
+        Self {
+            code: UNKNOWN_ERROR_CODE.to_string(),
+            message: error.to_string(),
+            guidance: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(guidance) = &self.guidance {
+            write!(f, "\n{guidance}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorCode for AuthError {
+    fn code(&self) -> &'static str {
+        match self {
+            AuthError::TokenNotFound => "auth_token_not_found",
+            AuthError::TokenExpired => "auth_token_expired",
+            AuthError::OAuthFailed(_) => "auth_failed",
+            AuthError::IoError(_) => "auth_io",
+            AuthError::JsonError(_) => "auth_json",
+            AuthError::HttpError(_) => "auth_http",
+            AuthError::InvalidProfile(_) => "auth_invalid_profile",
+        }
+    }
+
+    fn guidance(&self) -> Option<&'static str> {
+        match self {
+            AuthError::TokenNotFound | AuthError::TokenExpired => {
+                Some("Sign in to iNaturalist again.")
+            }
+            AuthError::HttpError(_) => Some("Check your internet connection and try again."),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_report_from_error() {
+        let error: Box<dyn std::error::Error> = Box::new(AuthError::TokenExpired);
+        let report = ErrorReport::from_error(error.as_ref());
+        assert_eq!(report.code, "auth_token_expired");
+        assert_eq!(report.message, "Authentication token has expired");
+        assert!(report.guidance.is_some());
+
+        let error: Box<dyn std::error::Error> = "Invalid parameters".into();
+        let report = ErrorReport::from_error(error.as_ref());
+        assert_eq!(report.code, UNKNOWN_ERROR_CODE);
+        assert_eq!(report.to_string(), "Invalid parameters");
+    }
+}
@@ -0,0 +1,150 @@
+//! How filter values match the values of a term, shared by the app's
+//! occurrence filters and archive subsets so the same filter selects the
+//! same records in both.
+
+use chrono::NaiveDateTime;
+
+use crate::darwin_core::{term_type, TermType};
+
+/// DuckDB type the values of `term` are compared as when filtering, or None
+/// for text
+pub fn filter_column_type(term: &str) -> Option<&'static str> {
+    match term_type(term) {
+        TermType::Boolean => Some("BOOLEAN"),
+        t if t.is_numeric() => Some("DOUBLE"),
+        _ => None,
+    }
+}
+
+/// How a filter value matches values
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterMatch {
+    /// Numbers from the first up to but not including the second, at the
+    /// precision of the filter value: "3" matches [3, 4), "3.1" [3.1, 3.2)
+    Range(f64, f64),
+    Boolean(bool),
+    /// Values starting with this, e.g. "2020-05" for dates in May 2020
+    Prefix(String),
+    /// Values containing this, ignoring case
+    Contains(String),
+}
+
+impl FilterMatch {
+    /// How `value` matches values of a column of `column_type`, a DuckDB type
+    /// or None for text. None if it can't match that type, e.g. "abc" for a
+    /// number, in which case the filter is ignored.
+    pub fn new(column_type: Option<&str>, value: &str) -> Option<Self> {
+        match column_type {
+            Some("DOUBLE") | Some("BIGINT") => {
+                let lower = value.parse::<f64>().ok()?;
+                let decimal_places = value.split('.').nth(1).map_or(0, str::len);
+                Some(Self::Range(lower, lower + 10_f64.powi(-(decimal_places as i32))))
+            }
+            Some("DATE") => Some(Self::Prefix(value.to_string())),
+            Some("BOOLEAN") => match value.to_lowercase().as_str() {
+                "true" => Some(Self::Boolean(true)),
+                "false" => Some(Self::Boolean(false)),
+                _ => None,
+            },
+            _ => Some(Self::Contains(value.to_string())),
+        }
+    }
+
+    /// Whether a value as written in a data file matches
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Range(lower, upper) => value
+                .trim()
+                .parse::<f64>()
+                .is_ok_and(|number| number >= *lower && number < *upper),
+            Self::Boolean(expected) => parse_bool(value) == Some(*expected),
+            Self::Prefix(prefix) => value.starts_with(prefix.as_str()),
+            Self::Contains(part) => value.to_lowercase().contains(&part.to_lowercase()),
+        }
+    }
+}
+
+/// Boolean values the way DuckDB reads them from text
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "t" | "1" => Some(true),
+        "false" | "f" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// The local date of a UTC eventDate like 2020-01-01T23:30:00Z given an
+/// eventTimeZoneOffset like -08:00, so records near midnight land on the day
+/// they were observed. None for anything else, including plain dates and
+/// datetimes that already carry a local offset, which are used as they are.
+pub fn local_event_date(event_date: &str, time_zone_offset: &str) -> Option<String> {
+    let offset_minutes = parse_offset(time_zone_offset)?;
+    let utc = ["Z", "+00:00", "+0000"]
+        .iter()
+        .find_map(|suffix| event_date.strip_suffix(suffix))?;
+    let timestamp = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(utc, format).ok())?;
+    let local = timestamp + chrono::Duration::minutes(offset_minutes);
+    Some(local.format("%Y-%m-%d").to_string())
+}
+
+/// Minutes east of UTC of an offset like +05:30 or -0800
+fn parse_offset(offset: &str) -> Option<i64> {
+    let sign = match offset.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = offset[1..].replacen(':', "", 1);
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_filters_match_at_their_precision() {
+        let filter = FilterMatch::new(filter_column_type("decimalLatitude"), "37.1").unwrap();
+
+        assert!(filter.matches("37.1"));
+        assert!(filter.matches("37.19"));
+        assert!(!filter.matches("37.2"));
+        assert!(!filter.matches("137.1"));
+        assert_eq!(FilterMatch::new(filter_column_type("individualCount"), "many"), None);
+    }
+
+    #[test]
+    fn test_boolean_filters_match_exactly() {
+        let filter = FilterMatch::new(filter_column_type("captive"), "TRUE").unwrap();
+
+        assert!(filter.matches("true"));
+        assert!(filter.matches("t"));
+        assert!(!filter.matches("false"));
+        assert!(!filter.matches("untrue"));
+    }
+
+    #[test]
+    fn test_text_filters_match_substrings() {
+        let filter = FilterMatch::new(filter_column_type("eventDate"), "2020-05").unwrap();
+
+        assert!(filter.matches("2020-05-01"));
+        assert!(filter.matches("2019-12-01/2020-05-01"));
+        assert!(!filter.matches("2020-06-01"));
+    }
+
+    #[test]
+    fn test_local_event_date() {
+        assert_eq!(local_event_date("2020-01-01T03:30:00Z", "-08:00").as_deref(), Some("2019-12-31"));
+        assert_eq!(local_event_date("2020-01-01T23:30:00.5+00:00", "+0530").as_deref(), Some("2020-01-02"));
+        assert_eq!(local_event_date("2020-01-01T03:30:00-08:00", "-08:00"), None);
+        assert_eq!(local_event_date("2020-01-01", "-08:00"), None);
+        assert_eq!(local_event_date("2020-01-01T03:30:00Z", "PST"), None);
+    }
+}
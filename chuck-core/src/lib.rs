@@ -1,10 +1,24 @@
 pub mod api;
 pub mod archive_updater;
+pub mod archive_summary;
+pub mod archive_validator;
 pub mod auth;
 pub mod chuck_metadata;
+#[cfg(feature = "convert")]
+pub mod convert;
 pub mod darwin_core;
+pub mod data_source;
+pub mod download_report;
 pub mod downloader;
 pub mod dwca_extension;
+pub mod error_code;
+pub mod filter_match;
+pub mod media_license;
 pub mod merge;
+pub mod output_name;
+pub mod publish;
+#[cfg(feature = "sql-console")]
+pub mod sql_console;
+pub mod subset;
 
 pub use dwca_extension::DwcaExtension;
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use inaturalist::models::Observation;
+use serde::{Deserialize, Serialize};
+
+use crate::download_report::ReportEntry;
+
+/// Label used in summaries for media without a license code. iNat leaves the
+/// code blank when the observer has reserved all rights.
+pub const ALL_RIGHTS_RESERVED: &str = "all-rights-reserved";
+
+/// Which media to embed in a generated archive, based on license
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaLicensePolicy {
+    /// Download everything regardless of license
+    #[default]
+    All,
+    /// Skip media whose rights are all reserved
+    ExcludeAllRightsReserved,
+    /// Skip all-rights-reserved media and anything with a non-commercial
+    /// clause, leaving only media that can be reused commercially
+    ExcludeNonCommercial,
+}
+
+impl MediaLicensePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::ExcludeAllRightsReserved => "exclude_all_rights_reserved",
+            Self::ExcludeNonCommercial => "exclude_non_commercial",
+        }
+    }
+
+    /// Whether media with the given iNat license code may be downloaded
+    pub fn allows(&self, license_code: Option<&str>) -> bool {
+        let label = license_label(license_code);
+        match self {
+            Self::All => true,
+            Self::ExcludeAllRightsReserved => label != ALL_RIGHTS_RESERVED,
+            Self::ExcludeNonCommercial => {
+                label != ALL_RIGHTS_RESERVED && !label.split('-').any(|part| part == "nc")
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for MediaLicensePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "exclude_all_rights_reserved" => Ok(Self::ExcludeAllRightsReserved),
+            "exclude_non_commercial" => Ok(Self::ExcludeNonCommercial),
+            _ => Err(format!("Unknown media license policy: {s}")),
+        }
+    }
+}
+
+/// Normalizes an iNat license code for display and comparison, e.g.
+/// `Some("CC-BY-NC")` → `"cc-by-nc"` and `None` → `"all-rights-reserved"`
+pub fn license_label(license_code: Option<&str>) -> String {
+    match license_code.map(str::trim) {
+        Some(code) if !code.is_empty() => code.to_lowercase(),
+        _ => ALL_RIGHTS_RESERVED.to_string(),
+    }
+}
+
+/// Counts photos per license across the given observations
+pub fn summarize_photo_licenses(observations: &[Observation]) -> BTreeMap<String, usize> {
+    let mut summary = BTreeMap::new();
+    for photo in observations.iter().filter_map(|o| o.photos.as_ref()).flatten() {
+        *summary.entry(license_label(photo.license_code.as_deref())).or_insert(0) += 1;
+    }
+    summary
+}
+
+/// Returns copies of the observations with any photos and sounds the policy
+/// doesn't allow removed, along with skip report entries for the removed
+/// media. The copies are only meant for choosing what to download; occurrence
+/// and extension records should still be built from the originals.
+pub fn apply_media_license_policy(
+    observations: &[Observation],
+    policy: MediaLicensePolicy,
+) -> (Vec<Observation>, Vec<ReportEntry>) {
+    if policy == MediaLicensePolicy::All {
+        return (observations.to_vec(), vec![]);
+    }
+
+    let mut skipped = Vec::new();
+    let filtered = observations
+        .iter()
+        .map(|obs| {
+            let mut obs = obs.clone();
+            let occurrence_id = obs.id
+                .map(|id| format!("https://www.inaturalist.org/observations/{id}"))
+                .unwrap_or_default();
+            if let Some(photos) = obs.photos.as_mut() {
+                photos.retain(|photo| {
+                    let allowed = policy.allows(photo.license_code.as_deref());
+                    if !allowed {
+                        skipped.push(ReportEntry::skip(
+                            "photo",
+                            photo.id.map(|id| id.to_string()).unwrap_or_default(),
+                            &occurrence_id,
+                            format!("license {}", license_label(photo.license_code.as_deref())),
+                        ));
+                    }
+                    allowed
+                });
+            }
+            if let Some(sounds) = obs.sounds.as_mut() {
+                sounds.retain(|sound| {
+                    let allowed = policy.allows(sound.license_code.as_deref());
+                    if !allowed {
+                        skipped.push(ReportEntry::skip(
+                            "sound",
+                            sound.id.map(|id| id.to_string()).unwrap_or_default(),
+                            &occurrence_id,
+                            format!("license {}", license_label(sound.license_code.as_deref())),
+                        ));
+                    }
+                    allowed
+                });
+            }
+            obs
+        })
+        .collect();
+
+    (filtered, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use inaturalist::models::{Photo, Sound};
+
+    fn photo(id: i32, license_code: Option<&str>) -> Photo {
+        Photo {
+            id: Some(id),
+            license_code: license_code.map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_license_label_normalizes_codes() {
+        assert_eq!(license_label(Some("CC-BY-NC")), "cc-by-nc");
+        assert_eq!(license_label(Some("")), ALL_RIGHTS_RESERVED);
+        assert_eq!(license_label(None), ALL_RIGHTS_RESERVED);
+    }
+
+    #[test]
+    fn test_policy_allows() {
+        use MediaLicensePolicy::*;
+
+        assert!(All.allows(None));
+        assert!(!ExcludeAllRightsReserved.allows(None));
+        assert!(ExcludeAllRightsReserved.allows(Some("cc-by-nc")));
+        assert!(!ExcludeNonCommercial.allows(Some("cc-by-nc")));
+        assert!(!ExcludeNonCommercial.allows(Some("cc-by-nc-sa")));
+        assert!(ExcludeNonCommercial.allows(Some("cc-by")));
+        assert!(ExcludeNonCommercial.allows(Some("cc0")));
+    }
+
+    #[test]
+    fn test_summarize_photo_licenses() {
+        let observations = vec![
+            Observation {
+                id: Some(1),
+                photos: Some(vec![photo(10, Some("cc-by")), photo(11, None)]),
+                ..Default::default()
+            },
+            Observation {
+                id: Some(2),
+                photos: Some(vec![photo(12, Some("cc-by"))]),
+                ..Default::default()
+            },
+        ];
+
+        let summary = summarize_photo_licenses(&observations);
+
+        assert_eq!(summary.get("cc-by"), Some(&2));
+        assert_eq!(summary.get(ALL_RIGHTS_RESERVED), Some(&1));
+    }
+
+    #[test]
+    fn test_apply_media_license_policy_records_skips() {
+        let observations = vec![Observation {
+            id: Some(1),
+            photos: Some(vec![photo(10, Some("cc-by")), photo(11, None)]),
+            sounds: Some(vec![Sound {
+                id: Some(20),
+                license_code: Some("cc-by-nc".to_string()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }];
+
+        let (filtered, skipped) = apply_media_license_policy(
+            &observations,
+            MediaLicensePolicy::ExcludeNonCommercial,
+        );
+
+        let photos = filtered[0].photos.as_ref().unwrap();
+        assert_eq!(photos.len(), 1);
+        assert_eq!(photos[0].id, Some(10));
+        assert!(filtered[0].sounds.as_ref().unwrap().is_empty());
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].item_id, "11");
+        assert_eq!(skipped[0].reason, "license all-rights-reserved");
+        assert_eq!(skipped[1].item_type, "sound");
+        // Originals are untouched
+        assert_eq!(observations[0].photos.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_apply_media_license_policy_all_keeps_everything() {
+        let observations = vec![Observation {
+            id: Some(1),
+            photos: Some(vec![photo(11, None)]),
+            ..Default::default()
+        }];
+
+        let (filtered, skipped) =
+            apply_media_license_policy(&observations, MediaLicensePolicy::All);
+
+        assert_eq!(filtered[0].photos.as_ref().unwrap().len(), 1);
+        assert!(skipped.is_empty());
+    }
+}
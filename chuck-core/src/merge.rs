@@ -4,7 +4,9 @@ use std::io::{Read, Write};
 /// Stream-based CSV merge: reads from `existing` and writes to `output`,
 /// replacing any row whose value at `id_col_index` appears in `updates`.
 /// Rows in `updates` not found in `existing` are appended at the end.
-/// The header row is always preserved. Both CSVs must share the same schema.
+/// The header row is always preserved. Both CSVs must share the same schema,
+/// except that update rows may have more or fewer trailing columns, which are
+/// dropped or left empty.
 ///
 /// Accepts generic `Read`/`Write` so callers can stream directly between ZIP
 /// entries without materialising files on disk.
@@ -21,6 +23,7 @@ pub fn merge_csv_streams<R: Read, W: Write>(
         .has_headers(false)
         .from_writer(output);
 
+    let width = reader.headers()?.len();
     writer.write_record(reader.headers()?)?;
 
     let mut seen: HashSet<String> = HashSet::new();
@@ -28,7 +31,7 @@ pub fn merge_csv_streams<R: Read, W: Write>(
         let record = result?;
         let id = record.get(id_col_index).unwrap_or("").to_string();
         if let Some(updated_row) = updates.get(&id) {
-            writer.write_record(updated_row)?;
+            writer.write_record(fit_row(updated_row, width))?;
             seen.insert(id);
         } else {
             writer.write_record(&record)?;
@@ -38,7 +41,7 @@ pub fn merge_csv_streams<R: Read, W: Write>(
     // Append rows whose IDs were not in the existing file (new records)
     for (id, row) in updates {
         if !seen.contains(id) {
-            writer.write_record(row)?;
+            writer.write_record(fit_row(row, width))?;
         }
     }
 
@@ -46,6 +49,13 @@ pub fn merge_csv_streams<R: Read, W: Write>(
     Ok(())
 }
 
+/// Pads or truncates a row to `width` columns. Columns are only ever added to
+/// the end of Chuck's CSVs, so an archive written before a column was added
+/// keeps its schema when it's updated.
+fn fit_row(row: &[String], width: usize) -> Vec<&str> {
+    (0..width).map(|i| row.get(i).map_or("", String::as_str)).collect()
+}
+
 /// Group-replace CSV merge for extension CSVs (one-to-many relationships).
 ///
 /// Unlike `merge_csv_streams`, `updates` maps each id to ALL rows for that id.
@@ -265,4 +275,27 @@ mod tests {
         assert_eq!(rows[2], vec!["2", "Robert"]);
         assert_eq!(rows[3], vec!["3", "Carol"]);
     }
+
+    #[test]
+    fn test_fits_update_rows_to_existing_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing.csv");
+        let output = dir.path().join("output.csv");
+
+        write_csv(&existing, &[
+            &["id", "name"],
+            &["1", "Alice"],
+        ]);
+
+        let updates: HashMap<String, Vec<String>> = [
+            ("1".to_string(), vec!["1".to_string(), "Alicia".to_string(), "10".to_string()]),
+            ("2".to_string(), vec!["2".to_string()]),
+        ]
+        .into();
+        merge_csv(&existing, &output, &updates, 0).unwrap();
+
+        let rows = read_csv(&output);
+        assert_eq!(rows[1], vec!["1", "Alicia"]);
+        assert_eq!(rows[2], vec!["2", ""]);
+    }
 }
@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+
+use inaturalist::apis::observations_api::ObservationsGetParams;
+
+/// Used when a template renders to nothing, e.g. `{taxon}` with no taxon
+/// filter
+pub const DEFAULT_NAME: &str = "observations";
+
+/// Renders an output file name (without extension) from a template like
+/// `{taxon}-{place}-{date}`. Supported tokens are `{taxon}`, `{place}`,
+/// `{user}`, `{d1}`, `{d2}`, and `{date}` (today). Tokens for filters that
+/// aren't set render as nothing, and separators left dangling by them are
+/// dropped, so `{taxon}-{place}` with only a place filter renders as the
+/// place ID alone.
+pub fn render_name_template(
+    template: &str,
+    params: &ObservationsGetParams,
+    today: chrono::NaiveDate,
+) -> String {
+    let join = |values: &Option<Vec<String>>| values.as_ref().map(|v| v.join(",")).unwrap_or_default();
+    let taxon = if params.taxon_name.is_some() {
+        join(&params.taxon_name)
+    } else {
+        join(&params.taxon_id)
+    };
+    let place = params.place_id
+        .as_ref()
+        .map(|ids| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(","))
+        .unwrap_or_default();
+    let user = if params.user_login.is_some() {
+        join(&params.user_login)
+    } else {
+        join(&params.user_id)
+    };
+    let tokens = [
+        ("{taxon}", taxon),
+        ("{place}", place),
+        ("{user}", user),
+        ("{d1}", params.d1.clone().unwrap_or_default()),
+        ("{d2}", params.d2.clone().unwrap_or_default()),
+        ("{date}", today.format("%Y-%m-%d").to_string()),
+    ];
+
+    let mut rendered = template.to_string();
+    for (token, value) in &tokens {
+        rendered = rendered.replace(token, &sanitize(value));
+    }
+    let name = collapse_separators(&rendered);
+    if name.is_empty() {
+        DEFAULT_NAME.to_string()
+    } else {
+        name
+    }
+}
+
+/// Makes a token value safe to use in a file name on any platform
+fn sanitize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            c if c.is_whitespace() => '_',
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c => c,
+        })
+        .collect()
+}
+
+/// Drops runs of separators left behind by empty tokens, along with any
+/// leading or trailing ones
+fn collapse_separators(name: &str) -> String {
+    let is_sep = |c: char| c == '-' || c == '_' || c == '.';
+    let mut collapsed = String::with_capacity(name.len());
+    let mut prev_sep = true;
+    for c in name.chars() {
+        if is_sep(c) {
+            if !prev_sep {
+                collapsed.push(c);
+            }
+            prev_sep = true;
+        } else {
+            collapsed.push(c);
+            prev_sep = false;
+        }
+    }
+    collapsed.trim_end_matches(is_sep).to_string()
+}
+
+/// Returns `path` if nothing exists there, otherwise the first free path with
+/// a numbered suffix before the extension, e.g. observations-1.zip
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().into_owned());
+    (1..)
+        .map(|n| {
+            let file_name = match &extension {
+                Some(ext) => format!("{stem}-{n}.{ext}"),
+                None => format!("{stem}-{n}"),
+            };
+            path.with_file_name(file_name)
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("ran out of numbered suffixes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::params::build_params;
+
+    fn today() -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()
+    }
+
+    #[test]
+    fn test_render_name_template_fills_tokens() {
+        let params = build_params(
+            Some("Quercus agrifolia".to_string()),
+            Some(14),
+            Some("kueda".to_string()),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(
+            render_name_template("{taxon}-{place}-{user}-{date}", &params, today()),
+            "Quercus_agrifolia-14-kueda-2025-03-01"
+        );
+    }
+
+    #[test]
+    fn test_render_name_template_drops_empty_tokens() {
+        let params = build_params(None, Some(14), None, None, None, None, None);
+        assert_eq!(render_name_template("{taxon}-{place}-{user}", &params, today()), "14");
+        assert_eq!(render_name_template("{taxon}", &params, today()), DEFAULT_NAME);
+    }
+
+    #[test]
+    fn test_render_name_template_sanitizes_values() {
+        let params = build_params(Some("a/b: c".to_string()), None, None, None, None, None, None);
+        assert_eq!(render_name_template("{taxon}", &params, today()), "a-b-c");
+    }
+
+    #[test]
+    fn test_unique_path_adds_numbered_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("observations.zip");
+        assert_eq!(unique_path(&path), path);
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(unique_path(&path), dir.path().join("observations-1.zip"));
+
+        std::fs::write(dir.path().join("observations-1.zip"), "").unwrap();
+        assert_eq!(unique_path(&path), dir.path().join("observations-2.zip"));
+    }
+}
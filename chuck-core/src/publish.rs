@@ -0,0 +1,366 @@
+//! Publishes Darwin Core Archives to Zenodo so they get a citable DOI
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::api::client::http_client;
+use crate::darwin_core::{Eml, Metadata};
+
+/// Zenodo's REST API
+pub const ZENODO_API_URL: &str = "https://zenodo.org/api";
+
+/// Zenodo's sandbox, for trying out publishing without minting real DOIs.
+/// Needs a token from a sandbox account.
+pub const ZENODO_SANDBOX_API_URL: &str = "https://sandbox.zenodo.org/api";
+
+/// A published Zenodo record
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedArchive {
+    pub deposition_id: i64,
+    /// e.g. 10.5281/zenodo.1234
+    pub doi: String,
+    /// e.g. https://doi.org/10.5281/zenodo.1234
+    pub doi_url: String,
+    /// Web page of the record on Zenodo
+    pub record_url: String,
+}
+
+/// Publishes an archive to Zenodo with metadata from its eml.xml. Zenodo
+/// reserves a DOI first so the uploaded archive can cite it as an
+/// alternateIdentifier in eml.xml, and once the record is published the
+/// archive at `archive_path` is replaced with that copy. `base_path` is the
+/// API root, e.g. ZENODO_API_URL, and `token` a personal access token with
+/// the deposit:write and deposit:actions scopes.
+pub async fn publish_to_zenodo(
+    base_path: &str,
+    token: &str,
+    archive_path: &Path,
+) -> Result<PublishedArchive, Box<dyn std::error::Error>> {
+    let filename = archive_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("archive path has no file name")?;
+    let mut eml = read_eml(archive_path)?;
+
+    let deposition = zenodo_json(
+        http_client()
+            .post(format!("{base_path}/deposit/depositions"))
+            .bearer_auth(token)
+            .json(&json!({ "metadata": deposition_metadata(&eml) }))
+            .send()
+            .await?,
+    )
+    .await?;
+    let deposition_id = deposition["id"].as_i64().ok_or("Zenodo didn't return a deposition ID")?;
+    let doi = deposition["metadata"]["prereserve_doi"]["doi"]
+        .as_str()
+        .ok_or("Zenodo didn't reserve a DOI")?
+        .to_string();
+    let bucket = deposition["links"]["bucket"].as_str().ok_or("Zenodo didn't return an upload URL")?;
+
+    let doi_url = format!("https://doi.org/{doi}");
+    eml.add_alternate_identifier(&doi_url);
+    let updated = write_with_eml(archive_path, &eml.to_xml())?;
+
+    let mut upload_url = url::Url::parse(bucket)?;
+    upload_url
+        .path_segments_mut()
+        .map_err(|_| "Zenodo returned an invalid upload URL")?
+        .push(filename);
+    let length = updated.as_file().metadata()?.len();
+    let file = tokio::fs::File::open(updated.path()).await?;
+    zenodo_json(
+        http_client()
+            .put(upload_url)
+            .bearer_auth(token)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .header(reqwest::header::CONTENT_LENGTH, length)
+            .body(file)
+            .send()
+            .await?,
+    )
+    .await?;
+
+    let published = zenodo_json(
+        http_client()
+            .post(format!("{base_path}/deposit/depositions/{deposition_id}/actions/publish"))
+            .bearer_auth(token)
+            .send()
+            .await?,
+    )
+    .await?;
+    let record_url = published["links"]["record_html"]
+        .as_str()
+        .or_else(|| published["links"]["html"].as_str())
+        .unwrap_or(&doi_url)
+        .to_string();
+
+    updated.persist(archive_path).map_err(|e| e.error)?;
+    Ok(PublishedArchive { deposition_id, doi, doi_url, record_url })
+}
+
+/// Zenodo deposition metadata from EML. Creators fall back to contacts
+/// because Zenodo requires at least one.
+fn deposition_metadata(eml: &Eml) -> Value {
+    let mut creators: Vec<Value> = eml.creators.iter().filter_map(zenodo_creator).collect();
+    if creators.is_empty() {
+        creators = eml.contacts.iter().filter_map(zenodo_creator).collect();
+    }
+    let description = if eml.abstract_paragraphs.is_empty() {
+        escape_html(&eml.title)
+    } else {
+        eml.abstract_paragraphs
+            .iter()
+            .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph)))
+            .collect()
+    };
+    let publication_date = eml
+        .pub_date
+        .as_deref()
+        .filter(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+        .map(String::from)
+        .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+
+    let mut metadata = json!({
+        "upload_type": "dataset",
+        "title": eml.title,
+        "description": description,
+        "creators": creators,
+        "publication_date": publication_date,
+        "access_right": "open",
+        "prereserve_doi": true,
+    });
+    if let Some(license) = eml.license.as_deref().and_then(zenodo_license) {
+        metadata["license"] = json!(license);
+    }
+    metadata
+}
+
+/// A Zenodo creator, named "Family, Given" as Zenodo expects, or by
+/// organization for parties without a person's name
+fn zenodo_creator(party: &crate::darwin_core::EmlParty) -> Option<Value> {
+    let person = match (party.sur_name.as_deref(), party.given_name.as_deref()) {
+        (Some(sur), Some(given)) => Some(format!("{sur}, {given}")),
+        (Some(name), None) | (None, Some(name)) => Some(name.to_string()),
+        (None, None) => None,
+    };
+    let mut creator = match (person, party.organization_name.as_deref()) {
+        (Some(name), Some(organization)) => json!({ "name": name, "affiliation": organization }),
+        (Some(name), None) | (None, Some(name)) => json!({ "name": name }),
+        (None, None) => return None,
+    };
+    let is_orcid = party.user_id_directory.as_deref().is_some_and(|dir| dir.contains("orcid.org"));
+    if let Some(orcid) = party.user_id.as_deref().filter(|_| is_orcid) {
+        creator["orcid"] = json!(orcid.trim_start_matches("https://orcid.org/"));
+    }
+    Some(creator)
+}
+
+/// Zenodo license ID for intellectualRights text naming a Creative Commons
+/// license iNat data can have
+fn zenodo_license(text: &str) -> Option<&'static str> {
+    let text = text.to_lowercase();
+    if text.contains("cc0") || text.contains("publicdomain/zero") {
+        Some("cc-zero")
+    } else if text.contains("by-nc") || text.contains("by nc") {
+        Some("cc-by-nc-4.0")
+    } else if text.contains("cc by") || text.contains("cc-by") || text.contains("licenses/by/") {
+        Some("cc-by-4.0")
+    } else {
+        None
+    }
+}
+
+/// JSON from a Zenodo response, or an error with Zenodo's message if the
+/// request failed
+async fn zenodo_json(response: reqwest::Response) -> Result<Value, Box<dyn std::error::Error>> {
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    if status.is_success() {
+        return Ok(body);
+    }
+    let mut message = body["message"].as_str().unwrap_or("request failed").to_string();
+    for error in body["errors"].as_array().into_iter().flatten() {
+        if let (Some(field), Some(detail)) = (error["field"].as_str(), error["message"].as_str()) {
+            message.push_str(&format!("; {field}: {detail}"));
+        }
+    }
+    Err(format!("Zenodo returned {status}: {message}").into())
+}
+
+/// The archive's EML, or Chuck's defaults if it has none that parses
+fn read_eml(archive_path: &Path) -> Result<Eml, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(archive_path)?)?;
+    let mut content = String::new();
+    let parsed = match archive.by_name("eml.xml") {
+        Ok(mut entry) => match entry.read_to_string(&mut content) {
+            Ok(_) => Eml::parse(&content).ok(),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    };
+    Ok(parsed.unwrap_or_else(|| Eml::from_metadata(&Metadata {
+        abstract_lines: vec![],
+        inat_query: None,
+    })))
+}
+
+/// Copies the archive to a temp file next to it with a new eml.xml. Other
+/// entries are copied without recompressing them.
+fn write_with_eml(
+    archive_path: &Path,
+    eml_xml: &str,
+) -> Result<tempfile::NamedTempFile, Box<dyn std::error::Error>> {
+    use zip::write::{FileOptions, ZipWriter};
+    use zip::CompressionMethod;
+
+    let dir = archive_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(archive_path)?)?;
+    let mut zip_out = ZipWriter::new(tempfile::NamedTempFile::new_in(dir)?);
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        if entry.name() != "eml.xml" {
+            zip_out.raw_copy_file(entry)?;
+        }
+    }
+    let options: FileOptions<()> = FileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    zip_out.start_file("eml.xml", options)?;
+    zip_out.write_all(eml_xml.as_bytes())?;
+    Ok(zip_out.finish()?)
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::darwin_core::EmlParty;
+    use httpmock::prelude::*;
+
+    fn build_archive(path: &Path) {
+        use zip::write::{FileOptions, ZipWriter};
+
+        let mut zip = ZipWriter::new(std::fs::File::create(path).unwrap());
+        let opts: FileOptions<()> = FileOptions::default();
+        zip.start_file("occurrence.csv", opts).unwrap();
+        zip.write_all(b"occurrenceID,scientificName\n1,Quercus agrifolia\n").unwrap();
+        zip.start_file("eml.xml", opts).unwrap();
+        zip.write_all(
+            br#"<eml:eml xmlns:eml="eml://ecoinformatics.org/eml-2.1.1" packageId="abc">
+  <dataset>
+    <title>Oaks of Tilden Park</title>
+    <creator><individualName><givenName>Willis</givenName><surName>Jepson</surName></individualName></creator>
+    <pubDate>2024-05-01</pubDate>
+    <abstract><para>Oaks &amp; more oaks.</para></abstract>
+    <intellectualRights><para>Licensed under CC BY-NC 4.0</para></intellectualRights>
+  </dataset>
+</eml:eml>"#,
+        )
+        .unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_deposition_metadata() {
+        let mut eml = Eml::default();
+        eml.title = "Oaks".to_string();
+        eml.abstract_paragraphs = vec!["Acorns & <galls>".to_string()];
+        eml.creators = vec![EmlParty {
+            given_name: Some("Willis".to_string()),
+            sur_name: Some("Jepson".to_string()),
+            organization_name: Some("Jepson Herbarium".to_string()),
+            user_id: Some("0000-0002-1825-0097".to_string()),
+            user_id_directory: Some("https://orcid.org/".to_string()),
+            ..Default::default()
+        }];
+        eml.license = Some("Public domain (CC0 1.0)".to_string());
+        eml.pub_date = Some("May 2024".to_string());
+
+        let metadata = deposition_metadata(&eml);
+
+        assert_eq!(metadata["description"], "<p>Acorns &amp; &lt;galls&gt;</p>");
+        assert_eq!(metadata["creators"], json!([{
+            "name": "Jepson, Willis",
+            "affiliation": "Jepson Herbarium",
+            "orcid": "0000-0002-1825-0097"
+        }]));
+        assert_eq!(metadata["license"], "cc-zero");
+        assert_eq!(metadata["prereserve_doi"], true);
+        assert_ne!(metadata["publication_date"], "May 2024");
+    }
+
+    #[tokio::test]
+    async fn test_publish_to_zenodo() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("oaks.zip");
+        build_archive(&path);
+        let server = MockServer::start();
+        let create = server.mock(|when, then| {
+            when.method(POST)
+                .path("/deposit/depositions")
+                .header("Authorization", "Bearer secret")
+                .body_contains("\"title\":\"Oaks of Tilden Park\"")
+                .body_contains("\"license\":\"cc-by-nc-4.0\"");
+            then.status(201).json_body(json!({
+                "id": 1234,
+                "links": { "bucket": server.url("/files/bucket-1") },
+                "metadata": { "prereserve_doi": { "doi": "10.5281/zenodo.1234", "recid": 1234 } }
+            }));
+        });
+        let upload = server.mock(|when, then| {
+            when.method(PUT).path("/files/bucket-1/oaks.zip").header("Authorization", "Bearer secret");
+            then.status(201).json_body(json!({ "key": "oaks.zip" }));
+        });
+        let publish = server.mock(|when, then| {
+            when.method(POST).path("/deposit/depositions/1234/actions/publish");
+            then.status(202).json_body(json!({
+                "doi": "10.5281/zenodo.1234",
+                "links": { "record_html": "https://zenodo.org/records/1234" }
+            }));
+        });
+
+        let published = publish_to_zenodo(&server.base_url(), "secret", &path).await.unwrap();
+
+        create.assert();
+        upload.assert();
+        publish.assert();
+        assert_eq!(published.doi, "10.5281/zenodo.1234");
+        assert_eq!(published.record_url, "https://zenodo.org/records/1234");
+        let eml = read_eml(&path).unwrap();
+        assert_eq!(eml.alternate_identifiers, vec!["https://doi.org/10.5281/zenodo.1234"]);
+        assert_eq!(eml.title, "Oaks of Tilden Park");
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert!(archive.by_name("occurrence.csv").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_publish_to_zenodo_reports_errors() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("oaks.zip");
+        build_archive(&path);
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/deposit/depositions");
+            then.status(400).json_body(json!({
+                "message": "Validation error.",
+                "errors": [{ "field": "metadata.creators", "message": "Missing data" }]
+            }));
+        });
+
+        let error = publish_to_zenodo(&server.base_url(), "secret", &path).await.unwrap_err();
+
+        assert!(error.to_string().contains("metadata.creators: Missing data"));
+        assert!(read_eml(&path).unwrap().alternate_identifiers.is_empty());
+    }
+}
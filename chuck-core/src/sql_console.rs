@@ -0,0 +1,217 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Rows returned by execute_sql when no limit is given
+pub const DEFAULT_ROW_LIMIT: usize = 1000;
+
+/// Name and DuckDB type of a result column, e.g. decimalLatitude, DOUBLE
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SqlColumn {
+    pub name: String,
+    pub data_type: String,
+}
+
+/// Result of an ad hoc query. Rows hold values in column order. When the
+/// query matched more rows than the limit, only the first `limit` are
+/// returned and `truncated` is set.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SqlResult {
+    pub columns: Vec<SqlColumn>,
+    pub rows: Vec<Vec<Value>>,
+    pub truncated: bool,
+}
+
+/// Strips comments, surrounding whitespace, and trailing semicolons from a
+/// query and checks that what's left is a single SELECT. Queries may also
+/// start with WITH or, as DuckDB allows, FROM.
+pub fn validate_select(sql: &str) -> Result<String, String> {
+    let mut stripped = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            stripped.push(c);
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                stripped.push(c);
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                stripped.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                stripped.push(' ');
+            }
+            ';' => stripped.push(';'),
+            _ => stripped.push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote".to_string());
+    }
+    let trimmed = stripped.trim().trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+    if trimmed.is_empty() {
+        return Err("Query is empty".to_string());
+    }
+    if has_unquoted_semicolon(trimmed) {
+        return Err("Only one statement can be run at a time".to_string());
+    }
+    let first_word = trimmed
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+    if !matches!(first_word.as_str(), "SELECT" | "WITH" | "FROM") {
+        return Err(format!("Only SELECT queries are allowed, not {first_word}"));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn has_unquoted_semicolon(sql: &str) -> bool {
+    let mut quote: Option<char> = None;
+    for c in sql.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ';') => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Runs a user-supplied SELECT against an archive database and returns up to
+/// `limit` rows as JSON. The query runs as a subquery in a transaction that
+/// is always rolled back, with access to files outside the database turned
+/// off for the connection, so callers should pass a connection opened just
+/// for this, ideally read-only.
+pub fn execute_sql(
+    conn: &duckdb::Connection,
+    sql: &str,
+    limit: usize,
+) -> Result<SqlResult, Box<dyn std::error::Error>> {
+    let query = validate_select(sql)?;
+    conn.execute_batch("SET enable_external_access = false; BEGIN TRANSACTION;")?;
+    let result = run_query(conn, &query, limit);
+    conn.execute_batch("ROLLBACK")?;
+    result
+}
+
+/// Opens a DuckDB database read-only and runs a query with execute_sql
+pub fn execute_sql_in_file(
+    db_path: &std::path::Path,
+    sql: &str,
+    limit: usize,
+) -> Result<SqlResult, Box<dyn std::error::Error>> {
+    let config = duckdb::Config::default().access_mode(duckdb::AccessMode::ReadOnly)?;
+    let conn = duckdb::Connection::open_with_flags(db_path, config)?;
+    execute_sql(&conn, sql, limit)
+}
+
+fn run_query(
+    conn: &duckdb::Connection,
+    query: &str,
+    limit: usize,
+) -> Result<SqlResult, Box<dyn std::error::Error>> {
+    let columns: Vec<SqlColumn> = conn
+        .prepare(&format!("DESCRIBE SELECT * FROM ({query}) AS user_query"))?
+        .query_map([], |row| {
+            Ok(SqlColumn {
+                name: row.get("column_name")?,
+                data_type: row.get("column_type")?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    // Let DuckDB do the JSON conversion so nested and temporal types come
+    // out the same way they would in its own JSON output
+    let mut stmt = conn.prepare(&format!(
+        "SELECT to_json(user_query)::VARCHAR FROM ({query}) AS user_query LIMIT ?"
+    ))?;
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    for json in stmt.query_map([limit + 1], |row| row.get::<_, String>(0))? {
+        if rows.len() == limit {
+            truncated = true;
+            break;
+        }
+        let object: serde_json::Map<String, Value> = serde_json::from_str(&json?)?;
+        rows.push(
+            columns
+                .iter()
+                .map(|column| object.get(&column.name).cloned().unwrap_or(Value::Null))
+                .collect(),
+        );
+    }
+    Ok(SqlResult { columns, rows, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_select() {
+        assert_eq!(
+            validate_select("  -- count them\nSELECT COUNT(*) FROM occurrences;  ").unwrap(),
+            "SELECT COUNT(*) FROM occurrences"
+        );
+        assert!(validate_select("with t AS (SELECT 1) SELECT * FROM t").is_ok());
+        assert!(validate_select("SELECT 'a;b'").is_ok());
+        assert!(validate_select("DELETE FROM occurrences").is_err());
+        assert!(validate_select("SELECT 1; DROP TABLE occurrences").is_err());
+        assert!(validate_select("/* SELECT */ ATTACH 'other.db'").is_err());
+        assert!(validate_select(";").is_err());
+    }
+
+    #[test]
+    fn test_execute_sql() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, decimalLatitude DOUBLE);
+             INSERT INTO occurrences VALUES ('1', 37.5), ('2', NULL), ('3', 38.0);"
+        ).unwrap();
+
+        let result = execute_sql(
+            &conn,
+            "SELECT occurrenceID, decimalLatitude FROM occurrences ORDER BY occurrenceID",
+            2,
+        ).unwrap();
+
+        assert_eq!(
+            result.columns,
+            vec![
+                SqlColumn { name: "occurrenceID".to_string(), data_type: "VARCHAR".to_string() },
+                SqlColumn { name: "decimalLatitude".to_string(), data_type: "DOUBLE".to_string() },
+            ]
+        );
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![serde_json::json!("1"), serde_json::json!(37.5)],
+                vec![serde_json::json!("2"), Value::Null],
+            ]
+        );
+        assert!(result.truncated);
+        assert!(execute_sql(&conn, "UPDATE occurrences SET decimalLatitude = 0", 10).is_err());
+    }
+}
@@ -0,0 +1,465 @@
+//! Writes the records of a DarwinCore Archive that match some filters, and
+//! their extension rows and media files, to a new archive. Filters work the
+//! way they do in the app (see filter_match): numbers match at the precision
+//! of the filter value, booleans exactly, and text when it contains the
+//! filter value, ignoring case. Records with an eventTimeZoneOffset have
+//! their UTC eventDate compared as a local date.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use crate::archive_validator::{meta_xml_name, DataFile};
+use crate::filter_match::{filter_column_type, local_event_date, FilterMatch};
+
+/// Which records to keep
+#[derive(Debug, Clone, Default)]
+pub struct SubsetFilter {
+    /// (term, value) pairs, e.g. ("scientificName", "Quercus"). Records
+    /// must match all of them.
+    pub filters: Vec<(String, String)>,
+    /// Earliest eventDate, e.g. 2020-01-01
+    pub d1: Option<String>,
+    /// Latest eventDate, e.g. 2020-12-31. A shorter date like 2020 includes
+    /// the whole year.
+    pub d2: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubsetSummary {
+    pub core_records: usize,
+    pub extension_rows: usize,
+}
+
+/// Where a filtered term's value comes from in a data file
+enum TermSource {
+    Column(usize),
+    /// meta.xml gives every record the same value
+    Default(String),
+}
+
+impl TermSource {
+    fn find(data_file: &DataFile, header: Option<&csv::ByteRecord>, term: &str) -> Option<Self> {
+        if let Some(index) = data_file.field_index(term) {
+            return Some(Self::Column(index));
+        }
+        if let Some((_, value)) = data_file.defaults.iter().find(|(name, _)| name == term) {
+            return Some(Self::Default(value.clone()));
+        }
+        header
+            .and_then(|header| header.iter().position(|name| name == term.as_bytes()))
+            .map(Self::Column)
+    }
+
+    fn value<'a>(&'a self, record: &'a csv::ByteRecord) -> Cow<'a, str> {
+        match self {
+            Self::Column(index) => String::from_utf8_lossy(record.get(*index).unwrap_or_default()),
+            Self::Default(value) => value.as_str().into(),
+        }
+    }
+}
+
+/// A filter on one term of the core data file
+struct TermFilter {
+    source: TermSource,
+    filter_match: FilterMatch,
+    is_event_date: bool,
+}
+
+/// A SubsetFilter with its terms found in the core data file
+struct CoreMatcher<'a> {
+    filters: Vec<TermFilter>,
+    event_date: Option<TermSource>,
+    time_zone_offset: Option<TermSource>,
+    filter: &'a SubsetFilter,
+}
+
+impl<'a> CoreMatcher<'a> {
+    fn new(
+        filter: &'a SubsetFilter,
+        core: &DataFile,
+        header: Option<&csv::ByteRecord>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let find = |term: &str| {
+            TermSource::find(core, header, term)
+                .ok_or_else(|| format!("Archive has no {term} column to filter by"))
+        };
+        let mut filters = Vec::new();
+        for (term, value) in &filter.filters {
+            let source = find(term)?;
+            // Like the app, values that can't match the term's type are ignored
+            if let Some(filter_match) = FilterMatch::new(filter_column_type(term), value) {
+                filters.push(TermFilter { source, filter_match, is_event_date: term == "eventDate" });
+            }
+        }
+        let event_date = if filter.d1.is_some() || filter.d2.is_some() {
+            Some(find("eventDate")?)
+        } else {
+            None
+        };
+        let time_zone_offset = TermSource::find(core, header, "eventTimeZoneOffset");
+        Ok(Self { filters, event_date, time_zone_offset, filter })
+    }
+
+    fn matches(&self, record: &csv::ByteRecord) -> bool {
+        let values_match = self.filters.iter().all(|term_filter| {
+            let value = if term_filter.is_event_date {
+                self.local_event_date(&term_filter.source, record)
+            } else {
+                term_filter.source.value(record)
+            };
+            term_filter.filter_match.matches(&value)
+        });
+        values_match
+            && self
+                .event_date
+                .as_ref()
+                .is_none_or(|source| date_in_range(&self.local_event_date(source, record), self.filter))
+    }
+
+    /// eventDate as the app filters it, on the local date if there's a
+    /// time zone offset
+    fn local_event_date<'r>(&'r self, source: &'r TermSource, record: &'r csv::ByteRecord) -> Cow<'r, str> {
+        let event_date = source.value(record);
+        self.time_zone_offset
+            .as_ref()
+            .and_then(|offset| local_event_date(&event_date, &offset.value(record)))
+            .map_or(event_date, Cow::Owned)
+    }
+}
+
+/// Whether an eventDate falls between d1 and d2. Intervals like
+/// 2020-01-01/2020-01-05 are compared by their start.
+fn date_in_range(event_date: &str, filter: &SubsetFilter) -> bool {
+    let date = event_date.split('/').next().unwrap_or_default().trim();
+    if date.is_empty() {
+        return false;
+    }
+    let after_d1 = filter.d1.as_deref().is_none_or(|d1| date >= d1);
+    let before_d2 = filter.d2.as_deref().is_none_or(|d2| {
+        let prefix = date.get(..d2.len()).unwrap_or(date);
+        prefix <= d2
+    });
+    after_d1 && before_d2
+}
+
+fn csv_reader<R: Read>(data_file: &DataFile, reader: R) -> csv::Reader<R> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(data_file.delimiter)
+        .quoting(data_file.quote.is_some())
+        .quote(data_file.quote.unwrap_or(b'"'))
+        .from_reader(reader)
+}
+
+fn csv_writer<W: Write>(data_file: &DataFile, writer: W) -> csv::Writer<W> {
+    csv::WriterBuilder::new()
+        .flexible(true)
+        .delimiter(data_file.delimiter)
+        .quote(data_file.quote.unwrap_or(b'"'))
+        .quote_style(if data_file.quote.is_some() {
+            csv::QuoteStyle::Necessary
+        } else {
+            csv::QuoteStyle::Never
+        })
+        .from_writer(writer)
+}
+
+/// Writes the archive at `input` to `output` with only the core records that
+/// match `filter`, the extension rows that refer to them, and the media
+/// files those rows name. Other files, like eml.xml, are copied as they are.
+pub fn write_subset(
+    input: &Path,
+    output: &Path,
+    filter: &SubsetFilter,
+) -> Result<SubsetSummary, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(input)?)?;
+    let output_dir = output.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let tmp_output = tempfile::NamedTempFile::new_in(output_dir)?;
+    let mut zip_out = zip::ZipWriter::new(tmp_output);
+    let summary = write_subset_zip(&mut archive, &mut zip_out, filter)?;
+    zip_out.finish()?.persist(output)?;
+    Ok(summary)
+}
+
+fn write_subset_zip<R: Read + Seek, W: Write + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    zip_out: &mut zip::ZipWriter<W>,
+    filter: &SubsetFilter,
+) -> Result<SubsetSummary, Box<dyn std::error::Error>> {
+    let meta_name = meta_xml_name(archive).ok_or("Archive has no meta.xml")?;
+    let prefix = meta_name.trim_end_matches("meta.xml").to_string();
+    let mut meta_xml = String::new();
+    archive.by_name(&meta_name)?.read_to_string(&mut meta_xml)?;
+    let doc = roxmltree::Document::parse(&meta_xml)?;
+    let core = doc
+        .descendants()
+        .find(|n| n.has_tag_name("core"))
+        .and_then(|n| DataFile::from_node(n, true))
+        .ok_or("meta.xml has no core with a file location")?;
+    let extensions: Vec<DataFile> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("extension"))
+        .filter_map(|n| DataFile::from_node(n, false))
+        .collect();
+    if core.id_index.is_none() && !extensions.is_empty() {
+        return Err("meta.xml doesn't say which column identifies core records, so extension rows can't be matched to them".into());
+    }
+
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let entry_names: HashSet<String> = archive.file_names().map(str::to_string).collect();
+    let mut data_entries = HashSet::new();
+    let mut summary = SubsetSummary::default();
+
+    // Core records, keeping the IDs of the ones that match
+    let mut core_ids: HashSet<Vec<u8>> = HashSet::new();
+    let core_entry = format!("{prefix}{}", core.location);
+    {
+        let mut reader = csv_reader(&core, archive.by_name(&core_entry)?);
+        zip_out.start_file(core_entry.as_str(), options)?;
+        let mut writer = csv_writer(&core, &mut *zip_out);
+        // Terms can be found by header name if there's a header
+        let mut matcher = if core.header_lines == 0 {
+            Some(CoreMatcher::new(filter, &core, None)?)
+        } else {
+            None
+        };
+        for (i, record) in reader.byte_records().enumerate() {
+            let record = record?;
+            if i < core.header_lines {
+                if i == 0 {
+                    matcher = Some(CoreMatcher::new(filter, &core, Some(&record))?);
+                }
+                writer.write_byte_record(&record)?;
+                continue;
+            }
+            if matcher.as_ref().is_some_and(|matcher| matcher.matches(&record)) {
+                if let Some(id) = core.id_index.and_then(|index| record.get(index)) {
+                    core_ids.insert(id.to_vec());
+                }
+                writer.write_byte_record(&record)?;
+                summary.core_records += 1;
+            }
+        }
+        writer.flush()?;
+    }
+    data_entries.insert(core_entry);
+
+    // Extension rows of matching records, noting which media files are
+    // named by rows that were kept and which by rows that weren't
+    let mut kept_media = HashSet::new();
+    let mut dropped_media = HashSet::new();
+    for extension in &extensions {
+        let entry = format!("{prefix}{}", extension.location);
+        if !entry_names.contains(&entry) || !data_entries.insert(entry.clone()) {
+            continue;
+        }
+        let mut reader = csv_reader(extension, archive.by_name(&entry)?);
+        zip_out.start_file(entry.as_str(), options)?;
+        let mut writer = csv_writer(extension, &mut *zip_out);
+        for (i, record) in reader.byte_records().enumerate() {
+            let record = record?;
+            if i < extension.header_lines {
+                writer.write_byte_record(&record)?;
+                continue;
+            }
+            let keep = extension
+                .id_index
+                .and_then(|index| record.get(index))
+                .is_some_and(|core_id| core_ids.contains(core_id));
+            let media = record
+                .iter()
+                .map(|field| format!("{prefix}{}", String::from_utf8_lossy(field)))
+                .filter(|name| entry_names.contains(name));
+            if keep {
+                kept_media.extend(media);
+                writer.write_byte_record(&record)?;
+                summary.extension_rows += 1;
+            } else {
+                dropped_media.extend(media);
+            }
+        }
+        writer.flush()?;
+    }
+
+    // Everything else, except media only unmatched records used
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        let name = entry.name().to_string();
+        if data_entries.contains(&name)
+            || (dropped_media.contains(&name) && !kept_media.contains(&name))
+        {
+            continue;
+        }
+        zip_out.raw_copy_file(entry)?;
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zip_archive(files: &[(&str, &str)]) -> zip::ZipArchive<std::io::Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        zip::ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    fn read_entry<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> String {
+        let mut contents = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    const META_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+  </core>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Multimedia" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>multimedia.csv</location></files>
+    <coreid index="0"/>
+    <field index="1" term="http://purl.org/dc/terms/identifier"/>
+  </extension>
+</archive>"#;
+
+    fn subset(filter: &SubsetFilter) -> (SubsetSummary, zip::ZipArchive<std::io::Cursor<Vec<u8>>>) {
+        let mut archive = zip_archive(&[
+            ("meta.xml", META_XML),
+            ("eml.xml", "<eml/>"),
+            (
+                "occurrence.csv",
+                "occurrenceID,scientificName,eventDate\n\
+                 1,Quercus agrifolia,2021-04-02\n\
+                 2,Pinus ponderosa,2021-05-01\n\
+                 3,\"Quercus lobata, hybrid\",2019-06-10\n",
+            ),
+            ("multimedia.csv", "occurrenceID,identifier\n1,media/1.jpg\n2,media/2.jpg\n"),
+            ("media/1.jpg", "one"),
+            ("media/2.jpg", "two"),
+        ]);
+        let mut zip_out = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let summary = write_subset_zip(&mut archive, &mut zip_out, filter).unwrap();
+        (summary, zip::ZipArchive::new(zip_out.finish().unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_write_subset_keeps_matching_records_and_their_media() {
+        let filter = SubsetFilter {
+            filters: vec![("scientificName".to_string(), "quercus".to_string())],
+            d1: Some("2020-01-01".to_string()),
+            d2: None,
+        };
+
+        let (summary, mut output) = subset(&filter);
+
+        assert_eq!(summary, SubsetSummary { core_records: 1, extension_rows: 1 });
+        assert_eq!(
+            read_entry(&mut output, "occurrence.csv"),
+            "occurrenceID,scientificName,eventDate\n1,Quercus agrifolia,2021-04-02\n"
+        );
+        assert_eq!(read_entry(&mut output, "multimedia.csv"), "occurrenceID,identifier\n1,media/1.jpg\n");
+        assert_eq!(read_entry(&mut output, "eml.xml"), "<eml/>");
+        assert!(output.by_name("media/1.jpg").is_ok());
+        assert!(output.by_name("media/2.jpg").is_err());
+    }
+
+    #[test]
+    fn test_write_subset_quotes_values_that_need_it() {
+        let filter = SubsetFilter { d2: Some("2019".to_string()), ..Default::default() };
+
+        let (summary, mut output) = subset(&filter);
+
+        assert_eq!(summary.core_records, 1);
+        assert!(read_entry(&mut output, "occurrence.csv").ends_with("3,\"Quercus lobata, hybrid\",2019-06-10\n"));
+    }
+
+    #[test]
+    fn test_write_subset_requires_filtered_columns() {
+        let filter = SubsetFilter {
+            filters: vec![("countryCode".to_string(), "US".to_string())],
+            ..Default::default()
+        };
+        let mut archive = zip_archive(&[
+            ("meta.xml", META_XML),
+            ("occurrence.csv", "occurrenceID,scientificName,eventDate\n1,Quercus,2021\n"),
+            ("multimedia.csv", "occurrenceID,identifier\n"),
+        ]);
+        let mut zip_out = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+
+        let error = write_subset_zip(&mut archive, &mut zip_out, &filter).unwrap_err();
+
+        assert_eq!(error.to_string(), "Archive has no countryCode column to filter by");
+    }
+
+    #[test]
+    fn test_write_subset_matches_numbers_and_local_dates_like_the_app() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/individualCount"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+    <field index="3" term="http://rs.tdwg.org/dwc/terms/eventTimeZoneOffset"/>
+  </core>
+</archive>"#;
+        let occurrences = "occurrenceID,individualCount,eventDate,eventTimeZoneOffset\n\
+                           1,3,2020-01-01T03:30:00Z,-08:00\n\
+                           2,3.5,2020-01-01T12:00:00Z,-08:00\n\
+                           3,13,2020-01-01,\n";
+        let core_ids = |filter: &SubsetFilter| {
+            let mut archive = zip_archive(&[("meta.xml", meta_xml), ("occurrence.csv", occurrences)]);
+            let mut zip_out = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            write_subset_zip(&mut archive, &mut zip_out, filter).unwrap();
+            let mut output = zip::ZipArchive::new(zip_out.finish().unwrap()).unwrap();
+            read_entry(&mut output, "occurrence.csv")
+                .lines()
+                .skip(1)
+                .map(|line| line.split(',').next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // 3 matches [3, 4), not 13 the way a substring would
+        let count = SubsetFilter {
+            filters: vec![("individualCount".to_string(), "3".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(core_ids(&count), vec!["1", "2"]);
+        // 03:30 UTC is still the evening before at -08:00
+        let date = SubsetFilter {
+            filters: vec![("eventDate".to_string(), "2019-12-31".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(core_ids(&date), vec!["1"]);
+        let range = SubsetFilter { d2: Some("2019".to_string()), ..Default::default() };
+        assert_eq!(core_ids(&range), vec!["1"]);
+    }
+
+    #[test]
+    fn test_date_in_range() {
+        let filter = SubsetFilter {
+            d1: Some("2020-01-01".to_string()),
+            d2: Some("2020-06".to_string()),
+            ..Default::default()
+        };
+        assert!(date_in_range("2020-06-30T12:00:00Z", &filter));
+        assert!(date_in_range("2020-03-01/2020-08-01", &filter));
+        assert!(!date_in_range("2020-07-01", &filter));
+        assert!(!date_in_range("2019-12-31", &filter));
+        assert!(!date_in_range("", &filter));
+    }
+}
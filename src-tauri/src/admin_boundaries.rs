@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::country_boundaries::{simplified_feature, Area};
+use crate::error::{ChuckError, Result};
+
+/// Natural Earth's public domain 1:10m admin 1 states and provinces,
+/// downloaded with the offline basemaps rather than bundled
+pub const STATES_URL: &str =
+    "https://raw.githubusercontent.com/nvkelso/natural-earth-vector/master/geojson/ne_10m_admin_1_states_provinces.geojson";
+
+/// Natural Earth's 1:10m admin 2 counties, which only cover the US
+pub const COUNTIES_URL: &str =
+    "https://raw.githubusercontent.com/nvkelso/natural-earth-vector/master/geojson/ne_10m_admin_2_counties.geojson";
+
+/// Administrative areas containing a point, as Darwin Core values
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AdminArea {
+    pub country_code: Option<String>,
+    pub state_province: Option<String>,
+    pub county: Option<String>,
+}
+
+impl AdminArea {
+    pub fn is_empty(&self) -> bool {
+        self.country_code.is_none() && self.state_province.is_none() && self.county.is_none()
+    }
+}
+
+struct StateProvince {
+    /// ISO 3166-1 alpha-2 code of the containing country
+    country_code: String,
+    name: String,
+    area: Area,
+}
+
+struct County {
+    name: String,
+    area: Area,
+}
+
+/// Low-resolution first- and second-level administrative boundaries for
+/// filling in missing countryCode, stateProvince, and county values. Counties
+/// are only available for some countries.
+pub struct AdminBoundaries {
+    states: Vec<StateProvince>,
+    counties: Vec<County>,
+}
+
+impl AdminBoundaries {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|source| ChuckError::FileRead { path: path.to_path_buf(), source })?;
+        Self::from_geojson(&json)
+    }
+
+    /// Parses a GeoJSON FeatureCollection of Polygon and MultiPolygon
+    /// features. State and province features have `countryCode` and
+    /// `stateProvince` properties, county features have a `county` property.
+    pub fn from_geojson(json: &str) -> Result<Self> {
+        let collection: Value = serde_json::from_str(json)
+            .map_err(|e| ChuckError::AdminBoundaries(e.to_string()))?;
+        let features = collection["features"]
+            .as_array()
+            .ok_or_else(|| ChuckError::AdminBoundaries("missing features".to_string()))?;
+
+        let mut states = Vec::new();
+        let mut counties = Vec::new();
+        for feature in features {
+            let properties = &feature["properties"];
+            let Some(area) = Area::from_geometry(&feature["geometry"])
+                .map_err(ChuckError::AdminBoundaries)?
+            else {
+                continue;
+            };
+            if let Some(county) = properties["county"].as_str() {
+                counties.push(County { name: county.to_string(), area });
+            } else if let (Some(country_code), Some(name)) = (
+                properties["countryCode"].as_str(),
+                properties["stateProvince"].as_str(),
+            ) {
+                states.push(StateProvince {
+                    country_code: country_code.to_uppercase(),
+                    name: name.to_string(),
+                    area,
+                });
+            }
+        }
+
+        Ok(Self { states, counties })
+    }
+
+    /// Areas containing the point. Any level can be missing, e.g. for points
+    /// at sea or in countries without county boundaries.
+    pub fn admin_area_at(&self, lat: f64, lon: f64) -> AdminArea {
+        let state = self.states.iter().find(|state| state.area.contains(lon, lat));
+        let county = self.counties.iter().find(|county| county.area.contains(lon, lat));
+        AdminArea {
+            country_code: state.map(|state| state.country_code.clone()),
+            state_province: state.map(|state| state.name.clone()),
+            county: county.map(|county| county.name.clone()),
+        }
+    }
+}
+
+/// Strips Natural Earth admin 1 and admin 2 FeatureCollections down to the
+/// names `AdminBoundaries` reads, with rounded coordinates to keep the saved
+/// file small
+pub fn simplify_natural_earth(states: &Value, counties: &Value) -> Value {
+    let features = |collection: &Value| {
+        collection["features"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|feature| !feature["geometry"].is_null())
+    };
+    let state_features = features(states).filter_map(|feature| {
+        let properties = &feature["properties"];
+        let country_code = properties["iso_a2"].as_str().filter(|code| *code != "-1")?;
+        let name = properties["name"].as_str()?;
+        Some(simplified_feature(
+            json!({ "countryCode": country_code, "stateProvince": name }),
+            &feature["geometry"],
+        ))
+    });
+    let county_features = features(counties).filter_map(|feature| {
+        let properties = &feature["properties"];
+        let name = properties["NAME"].as_str().or_else(|| properties["name"].as_str())?;
+        Some(simplified_feature(json!({ "county": name }), &feature["geometry"]))
+    });
+    json!({
+        "type": "FeatureCollection",
+        "features": state_features.chain(county_features).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Two square states side by side, the first with a county in its
+    /// southwest corner
+    pub(crate) const FIXTURE: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": { "countryCode": "aa", "stateProvince": "West" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]]]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": { "countryCode": "AA", "stateProvince": "East" },
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [[[[10, 0], [20, 0], [20, 10], [10, 10], [10, 0]]]]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": { "county": "Corner" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[0, 0], [5, 0], [5, 5], [0, 5], [0, 0]]]
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_admin_area_at() {
+        let boundaries = AdminBoundaries::from_geojson(FIXTURE).unwrap();
+
+        assert_eq!(boundaries.admin_area_at(2.0, 2.0), AdminArea {
+            country_code: Some("AA".to_string()),
+            state_province: Some("West".to_string()),
+            county: Some("Corner".to_string()),
+        });
+        assert_eq!(boundaries.admin_area_at(5.0, 15.0), AdminArea {
+            country_code: Some("AA".to_string()),
+            state_province: Some("East".to_string()),
+            county: None,
+        });
+        assert!(boundaries.admin_area_at(-5.0, -5.0).is_empty());
+    }
+
+    #[test]
+    fn test_simplify_natural_earth() {
+        let square = json!({
+            "type": "Polygon",
+            "coordinates": [[[0.001, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]]]
+        });
+        let states = json!({
+            "type": "FeatureCollection",
+            "features": [
+                { "type": "Feature", "properties": { "iso_a2": "US", "name": "Maine" }, "geometry": square },
+                { "type": "Feature", "properties": { "iso_a2": "-1", "name": "Disputed" }, "geometry": square }
+            ]
+        });
+        let counties = json!({
+            "type": "FeatureCollection",
+            "features": [
+                { "type": "Feature", "properties": { "NAME": "York" }, "geometry": square },
+                { "type": "Feature", "properties": { "NAME": "Nowhere" }, "geometry": null }
+            ]
+        });
+
+        let simplified = simplify_natural_earth(&states, &counties);
+
+        assert_eq!(simplified["features"].as_array().unwrap().len(), 2);
+        let boundaries = AdminBoundaries::from_geojson(&simplified.to_string()).unwrap();
+        assert_eq!(boundaries.admin_area_at(5.0, 5.0), AdminArea {
+            country_code: Some("US".to_string()),
+            state_province: Some("Maine".to_string()),
+            county: Some("York".to_string()),
+        });
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Summary stats computed on import, stored as JSON by name, so the overview
+/// doesn't need a query per stat each time it's shown
+const STATS_TABLE: &str = "stats";
+
+/// Name of the archive summary in the stats table
+const SUMMARY_STAT: &str = "summary";
+
+/// Columns worth knowing the number of distinct values of, when the archive
+/// has them
+const DISTINCT_COUNT_COLUMNS: &[&str] = &[
+    "scientificName",
+    "family",
+    "genus",
+    "recordedBy",
+    "countryCode",
+    "stateProvince",
+    "institutionCode",
+    "collectionCode",
+    "datasetName",
+    "basisOfRecord",
+];
+
+/// How many occurrences have a value in a column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnFill {
+    pub column: String,
+    pub filled: i64,
+    /// Share of occurrences with a value, from 0 to 1
+    pub fill_rate: f64,
+}
+
+/// Extent of the occurrences with coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bounds {
+    pub nelat: f64,
+    pub nelng: f64,
+    pub swlat: f64,
+    pub swlng: f64,
+}
+
+/// Overview of the whole archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStats {
+    pub record_count: i64,
+    /// Every occurrences column, in table order
+    pub fill_rates: Vec<ColumnFill>,
+    /// Distinct values of the DISTINCT_COUNT_COLUMNS the archive has
+    pub distinct_counts: BTreeMap<String, i64>,
+    pub bounds: Option<Bounds>,
+    /// Earliest and latest eventDate, as YYYY-MM-DD
+    pub min_event_date: Option<String>,
+    pub max_event_date: Option<String>,
+}
+
+fn occurrences_columns(conn: &duckdb::Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT column_name FROM information_schema.columns \
+         WHERE table_name = 'occurrences' ORDER BY ordinal_position",
+    )?;
+    let columns = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    Ok(columns)
+}
+
+/// Computes stats for occurrences in one scan, reading them from
+/// `occurrences`, a FROM item like the one from overlay::occurrences_source
+pub fn compute_stats(conn: &duckdb::Connection, occurrences: &str) -> Result<ArchiveStats> {
+    let columns = occurrences_columns(conn)?;
+    let has = |column: &str| columns.iter().any(|c| c == column);
+    let distinct_columns: Vec<&str> =
+        DISTINCT_COUNT_COLUMNS.iter().copied().filter(|column| has(column)).collect();
+
+    let mut selects = vec!["COUNT(*)".to_string()];
+    for column in &columns {
+        let quoted = Database::quote_identifier(column);
+        selects.push(format!(
+            "COUNT(*) FILTER (WHERE {quoted} IS NOT NULL AND trim(CAST({quoted} AS VARCHAR)) != '')"
+        ));
+    }
+    for column in &distinct_columns {
+        let quoted = Database::quote_identifier(column);
+        selects.push(format!("COUNT(DISTINCT NULLIF(trim(CAST({quoted} AS VARCHAR)), ''))"));
+    }
+    let has_coordinates = has("decimalLatitude") && has("decimalLongitude");
+    if has_coordinates {
+        selects.push(
+            "MAX(TRY_CAST(decimalLatitude AS DOUBLE)), MAX(TRY_CAST(decimalLongitude AS DOUBLE)), \
+             MIN(TRY_CAST(decimalLatitude AS DOUBLE)), MIN(TRY_CAST(decimalLongitude AS DOUBLE))"
+                .to_string(),
+        );
+    }
+    let has_event_date = has("eventDate");
+    if has_event_date {
+        // Only the start of intervals like 2020-01-01/2020-02-01, and only
+        // dates starting with a year so junk doesn't sort first
+        let date = "left(CAST(\"eventDate\" AS VARCHAR), 10)";
+        let dated = "regexp_matches(CAST(\"eventDate\" AS VARCHAR), '^\\d{4}')";
+        selects.push(format!(
+            "MIN({date}) FILTER (WHERE {dated}), MAX({date}) FILTER (WHERE {dated})"
+        ));
+    }
+
+    let query = format!("SELECT {} FROM {occurrences}", selects.join(", "));
+    conn.query_row(&query, [], |row| {
+        let record_count: i64 = row.get(0)?;
+        let mut i = 1;
+        let mut fill_rates = Vec::with_capacity(columns.len());
+        for column in &columns {
+            let filled: i64 = row.get(i)?;
+            i += 1;
+            let fill_rate = if record_count > 0 { filled as f64 / record_count as f64 } else { 0.0 };
+            fill_rates.push(ColumnFill { column: column.clone(), filled, fill_rate });
+        }
+        let mut distinct_counts = BTreeMap::new();
+        for column in &distinct_columns {
+            distinct_counts.insert(column.to_string(), row.get(i)?);
+            i += 1;
+        }
+        let mut bounds = None;
+        if has_coordinates {
+            let values: (Option<f64>, Option<f64>, Option<f64>, Option<f64>) =
+                (row.get(i)?, row.get(i + 1)?, row.get(i + 2)?, row.get(i + 3)?);
+            i += 4;
+            if let (Some(nelat), Some(nelng), Some(swlat), Some(swlng)) = values {
+                bounds = Some(Bounds { nelat, nelng, swlat, swlng });
+            }
+        }
+        let (min_event_date, max_event_date) = if has_event_date {
+            (row.get(i)?, row.get(i + 1)?)
+        } else {
+            (None, None)
+        };
+        Ok(ArchiveStats {
+            record_count,
+            fill_rates,
+            distinct_counts,
+            bounds,
+            min_event_date,
+            max_event_date,
+        })
+    })
+    .map_err(Into::into)
+}
+
+/// Computes stats and stores them, replacing any stored before. Needs a
+/// read-write connection.
+pub fn store_stats(conn: &duckdb::Connection, occurrences: &str) -> Result<ArchiveStats> {
+    let stats = compute_stats(conn, occurrences)?;
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {STATS_TABLE} (name VARCHAR PRIMARY KEY, value VARCHAR)"
+    ))?;
+    let json = serde_json::to_string(&stats).unwrap_or_default();
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO {STATS_TABLE} VALUES (?, ?)"),
+        [SUMMARY_STAT, json.as_str()],
+    )?;
+    Ok(stats)
+}
+
+/// Drops stored stats after occurrences change, so they're computed again
+/// instead of being out of date. Needs a read-write connection.
+pub fn clear_stats(conn: &duckdb::Connection) -> Result<()> {
+    conn.execute_batch(&format!("DROP TABLE IF EXISTS {STATS_TABLE}"))?;
+    Ok(())
+}
+
+/// Stats stored on import, if they were, e.g. not for archives imported by
+/// older versions of Chuck
+pub fn read_stats(conn: &duckdb::Connection) -> Result<Option<ArchiveStats>> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [STATS_TABLE],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(None);
+    }
+    let json: Option<String> = conn
+        .query_row(
+            &format!("SELECT value FROM {STATS_TABLE} WHERE name = ?"),
+            [SUMMARY_STAT],
+            |row| row.get(0),
+        )
+        .ok();
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences() -> duckdb::Connection {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                occurrenceID VARCHAR,
+                scientificName VARCHAR,
+                eventDate VARCHAR,
+                decimalLatitude DOUBLE,
+                decimalLongitude DOUBLE
+             );
+             INSERT INTO occurrences VALUES ('1', 'Quercus lobata', '2021-05-01', 37.5, -122.5);
+             INSERT INTO occurrences VALUES ('2', 'Quercus lobata', '2019-03-02/2019-03-04', 38.0, -121.0);
+             INSERT INTO occurrences VALUES ('3', '', 'unknown', NULL, NULL);
+             INSERT INTO occurrences VALUES ('4', 'Sanicula crassicaulis', NULL, 36.0, -123.0);"
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_compute_stats() {
+        let stats = compute_stats(&occurrences(), "occurrences").unwrap();
+
+        assert_eq!(stats.record_count, 4);
+        let scientific_name = stats.fill_rates.iter().find(|f| f.column == "scientificName").unwrap();
+        assert_eq!(scientific_name.filled, 3);
+        assert_eq!(scientific_name.fill_rate, 0.75);
+        assert_eq!(stats.distinct_counts.get("scientificName"), Some(&2));
+        assert_eq!(
+            stats.bounds,
+            Some(Bounds { nelat: 38.0, nelng: -121.0, swlat: 36.0, swlng: -123.0 })
+        );
+        assert_eq!(stats.min_event_date.as_deref(), Some("2019-03-02"));
+        assert_eq!(stats.max_event_date.as_deref(), Some("2021-05-01"));
+    }
+
+    #[test]
+    fn test_store_read_and_clear_stats() {
+        let conn = occurrences();
+        assert_eq!(read_stats(&conn).unwrap(), None);
+
+        let stats = store_stats(&conn, "occurrences").unwrap();
+        assert_eq!(read_stats(&conn).unwrap(), Some(stats));
+
+        clear_stats(&conn).unwrap();
+        assert_eq!(read_stats(&conn).unwrap(), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Watches the zip the current archive was imported from, so when it's
+//! replaced, e.g. by re-downloading an export, Chuck can offer to reimport
+//! it instead of showing stale data
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::dwca::{Archive, ArchiveSource};
+
+/// Event emitted with an ArchiveSourceChanged when the watched zip changes
+pub const SOURCE_CHANGED_EVENT: &str = "archive-source-changed";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSourceChanged {
+    pub archive_id: String,
+    pub path: PathBuf,
+}
+
+/// Watcher for the current archive's zip, with the archive's ID
+#[derive(Default)]
+pub(crate) struct ArchiveWatcher(Mutex<Option<(String, notify::RecommendedWatcher)>>);
+
+/// Starts watching the zip `archive` was imported from, replacing the
+/// watcher for any other archive. Archives imported before sources were
+/// recorded can't be watched.
+pub(crate) fn watch_archive(app: &tauri::AppHandle, archive: &Archive) {
+    let Some(state) = app.try_state::<ArchiveWatcher>() else {
+        return;
+    };
+    let Ok(mut current) = state.0.lock() else {
+        return;
+    };
+    let archive_id = archive.id();
+    if current.as_ref().is_some_and(|(id, _)| *id == archive_id) {
+        return;
+    }
+    *current = None;
+    let Some(source) = archive.source() else {
+        return;
+    };
+    // Downloads usually replace the file rather than writing to it, which
+    // some platforms only report as events on the directory
+    let Some(dir) = source.path.parent().map(Path::to_path_buf) else {
+        return;
+    };
+
+    let app = app.clone();
+    let event_archive_id = archive_id.clone();
+    // The version of the file last reported, so each change is only
+    // reported once however many events it causes
+    let mut reported = source.clone();
+    let handler = move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !event.paths.iter().any(|path| path.file_name() == source.path.file_name()) {
+            return;
+        }
+        let Some(changed) = ArchiveSource::of(&source.path) else {
+            return;
+        };
+        if changed == reported {
+            return;
+        }
+        reported = changed;
+        log::info!("{} changed since it was opened", source.path.display());
+        let payload = ArchiveSourceChanged {
+            archive_id: event_archive_id.clone(),
+            path: source.path.clone(),
+        };
+        if let Err(e) = app.emit(SOURCE_CHANGED_EVENT, payload) {
+            log::warn!("Failed to emit {SOURCE_CHANGED_EVENT}: {e}");
+        }
+    };
+    let watcher = notify::recommended_watcher(handler).and_then(|mut watcher| {
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => *current = Some((archive_id, watcher)),
+        Err(e) => log::warn!("Failed to watch {} for changes: {e}", dir.display()),
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use chuck_core::api::client::with_tls_settings;
+use serde_json::Value;
+use tauri::Runtime;
+
+use super::protocol;
+use crate::{admin_boundaries, country_boundaries};
+
+/// Path to the simplified country boundaries used by the quality report.
+pub fn country_boundaries_path<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<PathBuf, String> {
+    Ok(protocol::basemaps_dir(app)?.join("country_boundaries.geojson"))
+}
+
+/// Path to the simplified state/province and county boundaries used to fill
+/// in missing places.
+pub fn admin_boundaries_path<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<PathBuf, String> {
+    Ok(protocol::basemaps_dir(app)?.join("admin_boundaries.geojson"))
+}
+
+/// Download Natural Earth boundaries and save simplified copies in the
+/// basemaps directory.
+pub async fn download_boundaries<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<(), String> {
+    let dir = protocol::basemaps_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create basemaps dir: {e}"))?;
+
+    let client = with_tls_settings(reqwest::Client::builder())
+        .user_agent(
+            "Chuck/0.2 (https://github.com/kueda/chuck)",
+        )
+        .build()
+        .map_err(|e| format!("HTTP client error: {e}"))?;
+
+    let countries = fetch_geojson(&client, country_boundaries::SOURCE_URL).await?;
+    save_geojson(
+        &country_boundaries_path(app)?,
+        &country_boundaries::simplify_natural_earth(&countries),
+    )?;
+
+    let states = fetch_geojson(&client, admin_boundaries::STATES_URL).await?;
+    let counties = fetch_geojson(&client, admin_boundaries::COUNTIES_URL).await?;
+    save_geojson(
+        &admin_boundaries_path(app)?,
+        &admin_boundaries::simplify_natural_earth(&states, &counties),
+    )
+}
+
+async fn fetch_geojson(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Value, String> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| format!("Boundaries request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("Boundaries download failed: {e}"))
+}
+
+fn save_geojson(path: &std::path::Path, geojson: &Value) -> Result<(), String> {
+    let json = serde_json::to_vec(geojson)
+        .map_err(|e| format!("Failed to serialize boundaries: {e}"))?;
+    std::fs::write(path, json)
+        .map_err(|e| format!("Failed to save boundaries: {e}"))
+}
@@ -3,12 +3,13 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, LazyLock};
 
 use bytes::{Bytes, BytesMut};
+use chuck_core::api::client::with_tls_settings;
 use futures::stream::{self, StreamExt};
 use pmtiles::reqwest::header::{HeaderValue, RANGE};
 use pmtiles::reqwest::{Method, Request, StatusCode};
 use pmtiles::{
-    AsyncBackend, AsyncPmTilesReader, HashMapCache, PmTilesWriter,
-    PmtError, PmtResult, TileCoord,
+    AsyncBackend, AsyncPmTilesReader, Compression, HashMapCache,
+    MmapBackend, PmTilesWriter, PmtError, PmtResult, TileCoord, TileType,
 };
 use serde::Serialize;
 use tauri::Emitter;
@@ -16,6 +17,10 @@ use tokio::sync::{Mutex, OnceCell, RwLock};
 use tokio::time::Instant;
 use url::Url;
 
+use super::boundaries;
+use super::dem;
+use super::gazetteer::{self, Place};
+use super::mbtiles;
 use super::protocol::{
     self, BasemapInfo, Bounds, IndexEntry,
 };
@@ -160,7 +165,7 @@ const PLANET_PMTILES_BASE: &str = "https://build.protomaps.com";
 /// Discover the latest available Protomaps daily build URL.
 /// Tries yesterday through 7 days ago, returns the first that responds 200.
 async fn discover_planet_url() -> Result<String, String> {
-    let client = pmtiles::reqwest::Client::builder()
+    let client = with_tls_settings(pmtiles::reqwest::Client::builder())
         .user_agent("Chuck/0.1")
         .build()
         .map_err(|e| format!("HTTP client error: {e}"))?;
@@ -234,7 +239,7 @@ async fn open_remote_reader() -> Result<
     let planet_url = discover_planet_url().await?;
     log::debug!("got planet_url: {planet_url}");
 
-    let client = pmtiles::reqwest::Client::builder()
+    let client = with_tls_settings(pmtiles::reqwest::Client::builder())
         .user_agent("Chuck/0.1")
         .build()
         .map_err(|e| format!("HTTP client error: {e}"))?;
@@ -384,6 +389,14 @@ struct WriterConfig {
     bounds: Option<Bounds>,
 }
 
+/// The whole Web Mercator world.
+const WORLD_BOUNDS: Bounds = Bounds {
+    min_lon: -180.0,
+    min_lat: -85.0511,
+    max_lon: 180.0,
+    max_lat: 85.0511,
+};
+
 /// Count total tiles across zoom levels 0 through max_zoom.
 fn count_tiles(max_zoom: u8) -> u64 {
     (0..=max_zoom as u32).map(|z| 4u64.pow(z)).sum()
@@ -631,6 +644,326 @@ pub async fn download_regional_basemap(
     Ok(())
 }
 
+/// Fill a `{z}/{x}/{y}` tile URL template for one tile.
+fn fill_tile_url(template: &str, coord: TileCoord) -> String {
+    template
+        .replace("{z}", &coord.z().to_string())
+        .replace("{x}", &coord.x().to_string())
+        .replace("{y}", &coord.y().to_string())
+}
+
+/// Identify a raster tile's image format from its magic bytes.
+fn detect_raster_type(data: &[u8]) -> Option<TileType> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(TileType::Png)
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(TileType::Jpeg)
+    } else if data.len() >= 12
+        && &data[..4] == b"RIFF"
+        && &data[8..12] == b"WEBP"
+    {
+        Some(TileType::Webp)
+    } else {
+        None
+    }
+}
+
+/// Download raster tiles from an XYZ tile server into a local PMTiles
+/// file. The writer is created once the first tile arrives, since the
+/// PMTiles header needs the image format.
+async fn download_xyz_tiles(
+    app: &tauri::AppHandle,
+    url_template: &str,
+    coords: Vec<TileCoord>,
+    tmp_path: &std::path::Path,
+    final_path: &std::path::Path,
+    writer_config: WriterConfig,
+) -> Result<(u64, u64), String> {
+    let client = with_tls_settings(reqwest::Client::builder())
+        .user_agent(
+            "Chuck/0.2 (https://github.com/kueda/chuck)",
+        )
+        .build()
+        .map_err(|e| format!("HTTP client error: {e}"))?;
+
+    let tiles_total = coords.len() as u64;
+    let mut tiles_downloaded: u64 = 0;
+    let mut bytes_downloaded: u64 = 0;
+    let mut stream_writer = None;
+
+    app.emit(
+        "basemap-download-progress",
+        DownloadProgress {
+            tiles_downloaded: 0,
+            tiles_total,
+            bytes_downloaded: 0,
+            phase: "downloading".to_string(),
+        },
+    )
+    .ok();
+
+    // Low concurrency since these are usually public tile servers with
+    // their own rate limits
+    const CONCURRENCY: usize = 8;
+    let cancel = CANCEL_FLAG.clone();
+    let mut tile_stream = stream::iter(coords)
+        .map(|coord| {
+            let client = client.clone();
+            let url = fill_tile_url(url_template, coord);
+            async move {
+                let result = match client.get(&url).send().await {
+                    Ok(resp)
+                        if resp.status() == reqwest::StatusCode::NOT_FOUND =>
+                    {
+                        Ok(None)
+                    }
+                    Ok(resp) => match resp.error_for_status() {
+                        Ok(resp) => resp.bytes().await.map(Some),
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e),
+                };
+                (coord, result)
+            }
+        })
+        .buffer_unordered(CONCURRENCY);
+
+    while let Some((coord, result)) = tile_stream.next().await {
+        if cancel.load(Ordering::SeqCst) {
+            drop(stream_writer);
+            std::fs::remove_file(tmp_path).ok();
+            return Err("Download cancelled".to_string());
+        }
+
+        match result {
+            Ok(Some(tile_data)) if !tile_data.is_empty() => {
+                if stream_writer.is_none() {
+                    let tile_type = detect_raster_type(&tile_data)
+                        .ok_or_else(|| {
+                            "Tile server did not return PNG, JPEG, or \
+                             WebP images"
+                                .to_string()
+                        })?;
+                    let output_file = std::fs::File::create(tmp_path)
+                        .map_err(|e| {
+                            format!("Failed to create output file: {e}")
+                        })?;
+                    let mut builder = PmTilesWriter::new(tile_type)
+                        .tile_compression(Compression::None)
+                        .max_zoom(writer_config.max_zoom);
+                    if let Some(b) = &writer_config.bounds {
+                        builder = builder.bounds(
+                            b.min_lon, b.min_lat, b.max_lon, b.max_lat,
+                        );
+                    }
+                    stream_writer =
+                        Some(builder.create(output_file).map_err(|e| {
+                            format!("Failed to create PMTiles writer: {e}")
+                        })?);
+                }
+                bytes_downloaded += tile_data.len() as u64;
+                if let Some(writer) = stream_writer.as_mut() {
+                    writer.add_raw_tile(coord, &tile_data).map_err(
+                        |e| format!("Failed to write tile: {e}"),
+                    )?;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("Failed to fetch tile {coord:?}: {e}");
+            }
+        }
+
+        tiles_downloaded += 1;
+
+        if tiles_downloaded % 100 == 0 || tiles_downloaded == 1 {
+            app.emit(
+                "basemap-download-progress",
+                DownloadProgress {
+                    tiles_downloaded,
+                    tiles_total,
+                    bytes_downloaded,
+                    phase: "downloading".to_string(),
+                },
+            )
+            .ok();
+        }
+    }
+
+    let Some(stream_writer) = stream_writer else {
+        return Err("Tile server returned no tiles".to_string());
+    };
+
+    app.emit(
+        "basemap-download-progress",
+        DownloadProgress {
+            tiles_downloaded,
+            tiles_total,
+            bytes_downloaded,
+            phase: "finalizing".to_string(),
+        },
+    )
+    .ok();
+
+    stream_writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize PMTiles: {e}"))?;
+
+    std::fs::rename(tmp_path, final_path)
+        .map_err(|e| format!("Failed to move basemap file: {e}"))?;
+
+    Ok((tiles_downloaded, bytes_downloaded))
+}
+
+/// Download a raster basemap (e.g. satellite or terrain imagery) from a
+/// user-supplied XYZ tile URL template like
+/// `https://example.com/tiles/{z}/{x}/{y}.jpg`. Without bounds the whole
+/// world is downloaded, so keep max_zoom low in that case.
+#[tauri::command]
+pub async fn download_raster_basemap(
+    app: tauri::AppHandle,
+    url_template: String,
+    max_zoom: u8,
+    bounds: Option<Bounds>,
+    name: Option<String>,
+) -> Result<(), String> {
+    if max_zoom > 19 {
+        return Err("Max zoom cannot exceed 19".to_string());
+    }
+    if !["{z}", "{x}", "{y}"]
+        .iter()
+        .all(|p| url_template.contains(p))
+    {
+        return Err(
+            "Tile URL must contain {z}, {x}, and {y}".to_string(),
+        );
+    }
+
+    CANCEL_FLAG.store(false, Ordering::SeqCst);
+
+    let dir = protocol::basemaps_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create basemaps dir: {e}"))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let path = dir.join(format!("{id}.pmtiles"));
+    let tmp_path = dir.join(format!("{id}.pmtiles.tmp"));
+
+    let coords = match &bounds {
+        Some(b) => tiles_in_bounds(b, max_zoom),
+        None => tiles_in_bounds(&WORLD_BOUNDS, max_zoom),
+    };
+    let tiles_total = coords.len() as u64;
+
+    app.emit(
+        "basemap-download-progress",
+        DownloadProgress {
+            tiles_downloaded: 0,
+            tiles_total,
+            bytes_downloaded: 0,
+            phase: "connecting".to_string(),
+        },
+    )
+    .ok();
+
+    let display_name = name.unwrap_or_else(|| {
+        Url::parse(&url_template.replace(['{', '}'], ""))
+            .ok()
+            .and_then(|u| u.host_str().map(String::from))
+            .unwrap_or_else(|| "Raster".to_string())
+    });
+
+    let (tiles_downloaded, bytes_downloaded) = download_xyz_tiles(
+        &app,
+        &url_template,
+        coords,
+        &tmp_path,
+        &path,
+        WriterConfig { max_zoom, bounds },
+    )
+    .await?;
+
+    upsert_index_entry(
+        &app,
+        IndexEntry {
+            id,
+            name: display_name,
+            download_date: chrono::Utc::now().to_rfc3339(),
+            source_url: url_template,
+        },
+    )?;
+
+    protocol::reset_reader_cache().await;
+
+    app.emit(
+        "basemap-download-progress",
+        DownloadProgress {
+            tiles_downloaded,
+            tiles_total,
+            bytes_downloaded,
+            phase: "complete".to_string(),
+        },
+    )
+    .ok();
+
+    Ok(())
+}
+
+/// Download Terrarium elevation tiles for filling in missing elevations.
+/// Without bounds the whole world is downloaded, so keep max_zoom low in
+/// that case. Replaces any earlier DEM download.
+#[tauri::command]
+pub async fn download_dem(
+    app: tauri::AppHandle,
+    max_zoom: u8,
+    bounds: Option<Bounds>,
+) -> Result<(), String> {
+    if max_zoom > dem::MAX_DEM_ZOOM {
+        return Err(format!(
+            "Max zoom cannot exceed {}",
+            dem::MAX_DEM_ZOOM
+        ));
+    }
+
+    CANCEL_FLAG.store(false, Ordering::SeqCst);
+
+    let path = dem::dem_path(&app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create DEM dir: {e}"))?;
+    }
+    let tmp_path = path.with_extension("pmtiles.tmp");
+
+    let coords = match &bounds {
+        Some(b) => tiles_in_bounds(b, max_zoom),
+        None => tiles_in_bounds(&WORLD_BOUNDS, max_zoom),
+    };
+    let tiles_total = coords.len() as u64;
+
+    let (tiles_downloaded, bytes_downloaded) = download_xyz_tiles(
+        &app,
+        dem::TERRARIUM_URL,
+        coords,
+        &tmp_path,
+        &path,
+        WriterConfig { max_zoom, bounds },
+    )
+    .await?;
+
+    app.emit(
+        "basemap-download-progress",
+        DownloadProgress {
+            tiles_downloaded,
+            tiles_total,
+            bytes_downloaded,
+            phase: "complete".to_string(),
+        },
+    )
+    .ok();
+
+    Ok(())
+}
+
 /// Count tiles at a single zoom level within bounds.
 fn tiles_at_zoom(bounds: &Bounds, z: u8) -> u64 {
     let n = (1u64 << z) as f64;
@@ -797,6 +1130,95 @@ pub async fn delete_basemap(
     Ok(())
 }
 
+/// Import a .pmtiles or .mbtiles basemap built with another tool into the
+/// basemaps directory. MBTiles files are converted to PMTiles. Returns the
+/// registered basemap.
+#[tauri::command]
+pub async fn import_basemap(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+) -> Result<BasemapInfo, String> {
+    let src = std::path::PathBuf::from(&path);
+    let extension = src
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase);
+
+    let dir = protocol::basemaps_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create basemaps dir: {e}"))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let dest = dir.join(format!("{id}.pmtiles"));
+    let tmp_path = dir.join(format!("{id}.pmtiles.tmp"));
+
+    let source_name = match extension.as_deref() {
+        Some("pmtiles") => {
+            // Make sure it's readable before copying it in
+            let backend = MmapBackend::try_from(src.as_path())
+                .await
+                .map_err(|e| format!("Failed to open {path}: {e}"))?;
+            AsyncPmTilesReader::try_from_source(backend)
+                .await
+                .map_err(|e| format!("Not a valid PMTiles file: {e}"))?;
+            std::fs::copy(&src, &tmp_path)
+                .map_err(|e| format!("Failed to copy basemap: {e}"))?;
+            None
+        }
+        Some("mbtiles") => {
+            let (src, tmp) = (src.clone(), tmp_path.clone());
+            let result = tokio::task::spawn_blocking(move || {
+                mbtiles::convert_mbtiles(&src, &tmp)
+            })
+            .await
+            .map_err(|e| format!("MBTiles conversion failed: {e}"))?;
+            match result {
+                Ok(info) => info.name,
+                Err(e) => {
+                    std::fs::remove_file(&tmp_path).ok();
+                    return Err(e);
+                }
+            }
+        }
+        _ => {
+            return Err(
+                "Basemap must be a .pmtiles or .mbtiles file".to_string(),
+            );
+        }
+    };
+
+    std::fs::rename(&tmp_path, &dest)
+        .map_err(|e| format!("Failed to move basemap file: {e}"))?;
+
+    let display_name = name
+        .or(source_name)
+        .or_else(|| {
+            src.file_stem()
+                .and_then(|s| s.to_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| id.clone());
+
+    upsert_index_entry(
+        &app,
+        IndexEntry {
+            id: id.clone(),
+            name: display_name,
+            download_date: chrono::Utc::now().to_rfc3339(),
+            source_url: src.to_string_lossy().to_string(),
+        },
+    )?;
+
+    protocol::reset_reader_cache().await;
+
+    protocol::list_basemaps(&app)
+        .await?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| "Imported basemap could not be read".to_string())
+}
+
 /// Rate-limit guard for Nominatim (max 1 request per second).
 static NOMINATIM_LAST_REQUEST: LazyLock<Mutex<Instant>> =
     LazyLock::new(|| Mutex::new(Instant::now() - std::time::Duration::from_secs(1)));
@@ -826,12 +1248,9 @@ fn extract_place_name(json: &serde_json::Value) -> Option<String> {
     None
 }
 
-#[tauri::command]
-pub async fn reverse_geocode(
-    lat: f64,
-    lon: f64,
-    zoom: u8,
-) -> Result<String, String> {
+/// Wait until another Nominatim request is allowed, then return a client
+/// for making it.
+async fn nominatim_client() -> Result<reqwest::Client, String> {
     // Enforce 1 req/sec rate limit
     {
         let mut last = NOMINATIM_LAST_REQUEST.lock().await;
@@ -843,12 +1262,21 @@ pub async fn reverse_geocode(
         *last = Instant::now();
     }
 
-    let client = reqwest::Client::builder()
+    with_tls_settings(reqwest::Client::builder())
         .user_agent(
             "Chuck/0.2 (https://github.com/kueda/chuck)",
         )
         .build()
-        .map_err(|e| format!("HTTP client error: {e}"))?;
+        .map_err(|e| format!("HTTP client error: {e}"))
+}
+
+#[tauri::command]
+pub async fn reverse_geocode(
+    lat: f64,
+    lon: f64,
+    zoom: u8,
+) -> Result<String, String> {
+    let client = nominatim_client().await?;
 
     let url = format!(
         "https://nominatim.openstreetmap.org/reverse\
@@ -875,6 +1303,104 @@ pub async fn reverse_geocode(
         .ok_or_else(|| "No place name found".to_string())
 }
 
+/// Download the GeoNames gazetteer so search_places works offline.
+#[tauri::command]
+pub async fn download_gazetteer(
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let db_path = gazetteer::gazetteer_path(&app)?;
+    let dir = protocol::basemaps_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create basemaps dir: {e}"))?;
+
+    let client = with_tls_settings(reqwest::Client::builder())
+        .user_agent(
+            "Chuck/0.2 (https://github.com/kueda/chuck)",
+        )
+        .build()
+        .map_err(|e| format!("HTTP client error: {e}"))?;
+    let bytes = client
+        .get(gazetteer::GEONAMES_URL)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| format!("GeoNames request failed: {e}"))?
+        .bytes()
+        .await
+        .map_err(|e| format!("GeoNames download failed: {e}"))?;
+
+    let zip_path = dir.join("geonames.zip");
+    std::fs::write(&zip_path, &bytes)
+        .map_err(|e| format!("Failed to save GeoNames download: {e}"))?;
+    let result = tokio::task::spawn_blocking({
+        let zip_path = zip_path.clone();
+        move || gazetteer::build_gazetteer(&zip_path, &db_path)
+    })
+    .await
+    .map_err(|e| format!("Failed to build gazetteer: {e}"));
+    std::fs::remove_file(&zip_path).ok();
+    result?
+}
+
+/// Download the country boundaries the quality report checks coordinates
+/// against and the state/province and county boundaries used to fill in
+/// missing places.
+#[tauri::command]
+pub async fn download_boundaries(
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    boundaries::download_boundaries(&app).await
+}
+
+/// Search for places by name to navigate the map to. Uses the offline
+/// gazetteer when it's been downloaded and falls back to Nominatim when it
+/// hasn't or it has no matches.
+#[tauri::command]
+pub async fn search_places(
+    app: tauri::AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<Place>, String> {
+    let limit = limit.unwrap_or(10);
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let db_path = gazetteer::gazetteer_path(&app)?;
+    if db_path.exists() {
+        let offline = duckdb::Connection::open(&db_path)
+            .and_then(|conn| {
+                gazetteer::search_gazetteer(&conn, &query, limit)
+            });
+        match offline {
+            Ok(places) if !places.is_empty() => return Ok(places),
+            Ok(_) => {}
+            Err(e) => log::warn!("Gazetteer search failed: {e}"),
+        }
+    }
+
+    let client = nominatim_client().await?;
+    let limit_param = limit.to_string();
+    let resp = client
+        .get("https://nominatim.openstreetmap.org/search")
+        .query(&[
+            ("format", "json"),
+            ("q", query.as_str()),
+            ("limit", limit_param.as_str()),
+            ("accept-language", "en"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Nominatim request failed: {e}"))?;
+
+    let json: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Invalid JSON from Nominatim: {e}"))?;
+
+    Ok(gazetteer::parse_nominatim_places(&json))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1042,4 +1568,42 @@ mod tests {
             "Small area should have few tiles at zoom 10"
         );
     }
+
+    #[test]
+    fn test_fill_tile_url() {
+        let coord = TileCoord::new(3, 2, 5).unwrap();
+        assert_eq!(
+            fill_tile_url("https://example.com/{z}/{x}/{y}.png", coord),
+            "https://example.com/3/2/5.png"
+        );
+        assert_eq!(
+            fill_tile_url("https://example.com/{z}/{y}/{x}", coord),
+            "https://example.com/3/5/2"
+        );
+    }
+
+    #[test]
+    fn test_detect_raster_type() {
+        assert!(matches!(
+            detect_raster_type(b"\x89PNG\r\n\x1a\n\0\0"),
+            Some(TileType::Png)
+        ));
+        assert!(matches!(
+            detect_raster_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(TileType::Jpeg)
+        ));
+        assert!(matches!(
+            detect_raster_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some(TileType::Webp)
+        ));
+        assert!(detect_raster_type(b"<html>").is_none());
+    }
+
+    #[test]
+    fn test_tiles_in_world_bounds() {
+        assert_eq!(
+            tiles_in_bounds(&WORLD_BOUNDS, 3).len() as u64,
+            count_tiles(3)
+        );
+    }
 }
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use pmtiles::{AsyncPmTilesReader, MmapBackend, TileCoord};
+use tauri::Runtime;
+
+use super::protocol::{self, BasemapReader};
+
+/// Mapzen/AWS terrain tiles in Terrarium encoding, where each pixel's RGB
+/// holds an elevation in meters. Zoom 5 covers the globe at roughly 5 km per
+/// pixel, zoom 10 at about 150 m.
+pub const TERRARIUM_URL: &str =
+    "https://s3.amazonaws.com/elevation-tiles-prod/terrarium/{z}/{x}/{y}.png";
+
+/// Highest zoom offered for DEM downloads
+pub const MAX_DEM_ZOOM: u8 = 12;
+
+/// Path to the downloaded DEM. It lives in its own directory so it isn't
+/// listed or served as a basemap.
+pub fn dem_path<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<PathBuf, String> {
+    Ok(protocol::basemaps_dir(app)?.join("dem").join("terrarium.pmtiles"))
+}
+
+/// Elevation in meters of a Terrarium-encoded pixel
+fn decode_terrarium(r: u8, g: u8, b: u8) -> f64 {
+    r as f64 * 256.0 + g as f64 + b as f64 / 256.0 - 32768.0
+}
+
+/// Fractional Web Mercator tile coordinates of a point at a zoom level
+fn tile_position(lat: f64, lon: f64, zoom: u8) -> (f64, f64) {
+    let n = (1u64 << zoom) as f64;
+    let lat = lat.clamp(-85.0511, 85.0511).to_radians();
+    let x = (lon + 180.0) / 360.0 * n;
+    let y = (1.0 - lat.tan().asinh() / std::f64::consts::PI) / 2.0 * n;
+    (x.clamp(0.0, n - 1e-9), y.clamp(0.0, n - 1e-9))
+}
+
+/// A decoded DEM tile as rows of RGB pixels
+struct DemTile {
+    width: usize,
+    height: usize,
+    rgb: Vec<u8>,
+}
+
+impl DemTile {
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+        decoder.set_transformations(png::Transformations::EXPAND);
+        let mut reader = decoder
+            .read_info()
+            .map_err(|e| format!("Invalid DEM tile: {e}"))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buf)
+            .map_err(|e| format!("Invalid DEM tile: {e}"))?;
+        let channels = match info.color_type {
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            other => {
+                return Err(format!("Unsupported DEM tile color type: {other:?}"));
+            }
+        };
+        let rgb = buf[..info.buffer_size()]
+            .chunks_exact(channels)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            rgb,
+        })
+    }
+
+    /// Elevation at a fractional position within the tile, 0..1 on each axis
+    fn elevation_at(&self, fx: f64, fy: f64) -> f64 {
+        let px = ((fx * self.width as f64) as usize).min(self.width - 1);
+        let py = ((fy * self.height as f64) as usize).min(self.height - 1);
+        let i = (py * self.width + px) * 3;
+        decode_terrarium(self.rgb[i], self.rgb[i + 1], self.rgb[i + 2])
+    }
+}
+
+/// Looks up elevations in the downloaded DEM, keeping decoded tiles around
+/// since nearby occurrences usually share them.
+pub struct Dem {
+    reader: BasemapReader,
+    max_zoom: u8,
+    tiles: HashMap<(u8, u32, u32), Option<DemTile>>,
+}
+
+impl Dem {
+    pub async fn open<R: Runtime>(
+        app: &tauri::AppHandle<R>,
+    ) -> Result<Self, String> {
+        let path = dem_path(app)?;
+        if !path.exists() {
+            return Err("No elevation data downloaded".to_string());
+        }
+        let backend = MmapBackend::try_from(path.as_path())
+            .await
+            .map_err(|e| format!("Failed to open elevation data: {e}"))?;
+        let reader = AsyncPmTilesReader::try_from_source(backend)
+            .await
+            .map_err(|e| format!("Failed to read elevation data: {e}"))?;
+        let max_zoom = reader.get_header().max_zoom;
+        Ok(Self { reader, max_zoom, tiles: HashMap::new() })
+    }
+
+    /// Elevation in meters at a point from the most detailed tile covering
+    /// it, or None if the DEM doesn't cover the point
+    pub async fn elevation_at(&mut self, lat: f64, lon: f64) -> Option<f64> {
+        for zoom in (0..=self.max_zoom).rev() {
+            let (x, y) = tile_position(lat, lon, zoom);
+            let key = (zoom, x as u32, y as u32);
+            let coord = TileCoord::new(key.0, key.1, key.2).ok()?;
+            if !self.tiles.contains_key(&key) {
+                let tile = match self.reader.get_tile_decompressed(coord).await {
+                    Ok(Some(data)) => DemTile::decode(&data)
+                        .inspect_err(|e| log::warn!("{e}"))
+                        .ok(),
+                    Ok(None) => None,
+                    Err(e) => {
+                        log::warn!("Failed to read DEM tile: {e}");
+                        None
+                    }
+                };
+                self.tiles.insert(key, tile);
+            }
+            if let Some(tile) = &self.tiles[&key] {
+                return Some(tile.elevation_at(x.fract(), y.fract()));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_terrarium() {
+        assert_eq!(decode_terrarium(128, 0, 0), 0.0);
+        assert_eq!(decode_terrarium(129, 244, 128), 500.5);
+        assert_eq!(decode_terrarium(127, 156, 0), -100.0);
+    }
+
+    #[test]
+    fn test_tile_position() {
+        assert_eq!(tile_position(0.0, 0.0, 1), (1.0, 1.0));
+        let (x, y) = tile_position(37.87, -122.27, 10);
+        assert_eq!((x as u32, y as u32), (164, 395));
+    }
+
+    #[test]
+    fn test_dem_tile_elevation_at() {
+        // 2x1 PNG: sea level on the left, 500.5 m on the right
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, 2, 1);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[128, 0, 0, 129, 244, 128]).unwrap();
+        }
+
+        let tile = DemTile::decode(&data).unwrap();
+
+        assert_eq!(tile.elevation_at(0.25, 0.5), 0.0);
+        assert_eq!(tile.elevation_at(0.75, 0.5), 500.5);
+    }
+}
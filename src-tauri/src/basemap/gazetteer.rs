@@ -0,0 +1,239 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use duckdb::{Connection, params};
+use serde::Serialize;
+use tauri::Runtime;
+
+use super::protocol::{self, Bounds};
+
+/// GeoNames extract of populated places with at least 5,000 people (~5 MB
+/// zipped). Smaller places are left to the online fallback.
+pub const GEONAMES_URL: &str =
+    "https://download.geonames.org/export/dump/cities5000.zip";
+const GEONAMES_ENTRY: &str = "cities5000.txt";
+
+/// Columns of a GeoNames dump file, in order
+const GEONAMES_COLUMNS: &str = "{
+    'geonameid': 'BIGINT', 'name': 'VARCHAR', 'asciiname': 'VARCHAR',
+    'alternatenames': 'VARCHAR', 'latitude': 'DOUBLE', 'longitude': 'DOUBLE',
+    'feature_class': 'VARCHAR', 'feature_code': 'VARCHAR',
+    'country_code': 'VARCHAR', 'cc2': 'VARCHAR', 'admin1_code': 'VARCHAR',
+    'admin2_code': 'VARCHAR', 'admin3_code': 'VARCHAR', 'admin4_code': 'VARCHAR',
+    'population': 'BIGINT', 'elevation': 'VARCHAR', 'dem': 'VARCHAR',
+    'timezone': 'VARCHAR', 'modification_date': 'VARCHAR'
+}";
+
+/// A place to navigate the map to.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Place {
+    pub name: String,
+    pub bbox: Bounds,
+    /// [longitude, latitude], like a MapLibre LngLat
+    pub center: [f64; 2],
+}
+
+/// Path to the offline gazetteer database.
+pub fn gazetteer_path<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<PathBuf, String> {
+    Ok(protocol::basemaps_dir(app)?.join("gazetteer.duckdb"))
+}
+
+/// GeoNames only has points, so approximate an extent from the population:
+/// roughly 15 km across for a town, growing to about 30 km for a megacity.
+fn place_bbox(lat: f64, lon: f64, population: i64) -> Bounds {
+    let half_deg = (0.02 * (population.max(1) as f64).log10()).clamp(0.025, 0.25);
+    let lon_half = half_deg / lat.to_radians().cos().max(0.1);
+    Bounds {
+        min_lon: lon - lon_half,
+        min_lat: (lat - half_deg).max(-90.0),
+        max_lon: lon + lon_half,
+        max_lat: (lat + half_deg).min(90.0),
+    }
+}
+
+/// Build a gazetteer database from a zipped GeoNames dump, replacing any
+/// existing one.
+pub fn build_gazetteer(zip_path: &Path, db_path: &Path) -> Result<(), String> {
+    let zip_file = std::fs::File::open(zip_path)
+        .map_err(|e| format!("Failed to open GeoNames download: {e}"))?;
+    let mut archive = zip::ZipArchive::new(zip_file)
+        .map_err(|e| format!("Invalid GeoNames download: {e}"))?;
+    let mut entry = archive
+        .by_name(GEONAMES_ENTRY)
+        .map_err(|e| format!("GeoNames download is missing {GEONAMES_ENTRY}: {e}"))?;
+    let tsv_path = db_path.with_extension("txt");
+    let mut tsv = Vec::new();
+    entry
+        .read_to_end(&mut tsv)
+        .map_err(|e| format!("Failed to extract GeoNames data: {e}"))?;
+    std::fs::write(&tsv_path, tsv)
+        .map_err(|e| format!("Failed to extract GeoNames data: {e}"))?;
+
+    let tmp_path = db_path.with_extension("duckdb.tmp");
+    std::fs::remove_file(&tmp_path).ok();
+    let result = Connection::open(&tmp_path)
+        .and_then(|conn| load_geonames(&conn, &tsv_path))
+        .map_err(|e| format!("Failed to build gazetteer: {e}"));
+    std::fs::remove_file(&tsv_path).ok();
+    result?;
+
+    std::fs::rename(&tmp_path, db_path)
+        .map_err(|e| format!("Failed to move gazetteer file: {e}"))
+}
+
+/// Load a GeoNames tab-separated dump into a `places` table.
+fn load_geonames(conn: &Connection, tsv_path: &Path) -> duckdb::Result<()> {
+    let path = tsv_path.to_string_lossy().replace('\'', "''");
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TABLE places AS
+         SELECT name, asciiname, alternatenames, latitude, longitude,
+                country_code, coalesce(population, 0) AS population
+         FROM read_csv('{path}', delim = '\t', header = false, quote = '',
+                       escape = '', columns = {GEONAMES_COLUMNS})"
+    ))
+}
+
+/// Search places by name, best matches first: exact names, then name
+/// prefixes, then alternate names, each ordered by population.
+pub fn search_gazetteer(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> duckdb::Result<Vec<Place>> {
+    let query = query.trim();
+    let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    let mut stmt = conn.prepare(
+        "SELECT name, country_code, latitude, longitude, population
+         FROM places
+         WHERE name ILIKE $1 ESCAPE '\\'
+            OR asciiname ILIKE $1 ESCAPE '\\'
+            OR (',' || alternatenames || ',') ILIKE $2 ESCAPE '\\'
+         ORDER BY lower(name) = lower($3) OR lower(asciiname) = lower($3) DESC,
+                  name ILIKE $1 ESCAPE '\\' OR asciiname ILIKE $1 ESCAPE '\\' DESC,
+                  population DESC
+         LIMIT $4",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            format!("{escaped}%"),
+            format!("%,{escaped}%"),
+            query,
+            limit as i64
+        ],
+        |row| {
+            let name: String = row.get(0)?;
+            let country: Option<String> = row.get(1)?;
+            let lat: f64 = row.get(2)?;
+            let lon: f64 = row.get(3)?;
+            let population: i64 = row.get(4)?;
+            Ok(Place {
+                name: match country {
+                    Some(country) if !country.is_empty() => {
+                        format!("{name}, {country}")
+                    }
+                    _ => name,
+                },
+                bbox: place_bbox(lat, lon, population),
+                center: [lon, lat],
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+/// Convert a Nominatim search response into places. Nominatim gives
+/// coordinates as strings and bounding boxes as [south, north, west, east].
+pub fn parse_nominatim_places(json: &serde_json::Value) -> Vec<Place> {
+    let num = |v: &serde_json::Value| v.as_str().and_then(|s| s.parse::<f64>().ok());
+    json.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|result| {
+            let name = result.get("display_name")?.as_str()?.to_string();
+            let lat = num(result.get("lat")?)?;
+            let lon = num(result.get("lon")?)?;
+            let bbox: Vec<f64> = result
+                .get("boundingbox")?
+                .as_array()?
+                .iter()
+                .map(num)
+                .collect::<Option<_>>()?;
+            let [min_lat, max_lat, min_lon, max_lon] = bbox[..] else {
+                return None;
+            };
+            Some(Place {
+                name,
+                bbox: Bounds { min_lon, min_lat, max_lon, max_lat },
+                center: [lon, lat],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_gazetteer() -> (TempDir, Connection) {
+        let temp = TempDir::new().unwrap();
+        let tsv = temp.path().join("cities.txt");
+        let row = |id: u32, name: &str, alt: &str, lat: f64, lon: f64, cc: &str, pop: u32| {
+            format!(
+                "{id}\t{name}\t{name}\t{alt}\t{lat}\t{lon}\tP\tPPL\t{cc}\t\t\t\t\t\t{pop}\t\t0\tEtc/UTC\t2024-01-01\n"
+            )
+        };
+        let data = [
+            row(1, "Santa Cruz", "", 36.97, -122.03, "US", 64000),
+            row(2, "Santa Cruz de la Sierra", "", -17.79, -63.18, "BO", 1450000),
+            row(3, "Oakland", "", 37.8, -122.27, "US", 430000),
+            row(4, "Wien", "Vienna,Viena", 48.21, 16.37, "AT", 1900000),
+        ]
+        .concat();
+        std::fs::write(&tsv, data).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        load_geonames(&conn, &tsv).unwrap();
+        (temp, conn)
+    }
+
+    #[test]
+    fn test_search_gazetteer_prefers_exact_matches() {
+        let (_temp, conn) = setup_gazetteer();
+        let places = search_gazetteer(&conn, "santa cruz", 10).unwrap();
+        let names: Vec<&str> = places.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Santa Cruz, US", "Santa Cruz de la Sierra, BO"]);
+        assert_eq!(places[0].center, [-122.03, 36.97]);
+        assert!(places[0].bbox.min_lat < 36.97 && places[0].bbox.max_lat > 36.97);
+    }
+
+    #[test]
+    fn test_search_gazetteer_matches_alternate_names() {
+        let (_temp, conn) = setup_gazetteer();
+        let places = search_gazetteer(&conn, "Vienna", 10).unwrap();
+        assert_eq!(places.len(), 1);
+        assert_eq!(places[0].name, "Wien, AT");
+        assert!(search_gazetteer(&conn, "ienna", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_nominatim_places() {
+        let json = serde_json::json!([{
+            "display_name": "Berkeley, Alameda County, California, United States",
+            "lat": "37.8708393",
+            "lon": "-122.272863",
+            "boundingbox": ["37.8357", "37.9066", "-122.3677", "-122.2341"]
+        }, {
+            "display_name": "No bbox",
+            "lat": "1",
+            "lon": "2"
+        }]);
+        let places = parse_nominatim_places(&json);
+        assert_eq!(places.len(), 1);
+        assert_eq!(places[0].center, [-122.272863, 37.8708393]);
+        assert_eq!(places[0].bbox.min_lat, 37.8357);
+        assert_eq!(places[0].bbox.max_lon, -122.2341);
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use pmtiles::{Compression, PmTilesWriter, TileCoord, TileType};
+use rusqlite::{Connection, OpenFlags};
+
+/// Summary of an MBTiles file that was converted to PMTiles.
+#[derive(Debug)]
+pub struct MbtilesInfo {
+    /// `name` from the MBTiles metadata table, if set
+    pub name: Option<String>,
+    pub tile_count: u64,
+}
+
+/// Tile type for an MBTiles `format` metadata value.
+fn tile_type_for_format(format: &str) -> Option<TileType> {
+    match format {
+        "pbf" | "mvt" => Some(TileType::Mvt),
+        "png" => Some(TileType::Png),
+        "jpg" | "jpeg" => Some(TileType::Jpeg),
+        "webp" => Some(TileType::Webp),
+        _ => None,
+    }
+}
+
+/// Parse an MBTiles `bounds` value ("west,south,east,north").
+fn parse_bounds(value: &str) -> Option<[f64; 4]> {
+    let parts: Vec<f64> = value
+        .split(',')
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    parts.try_into().ok()
+}
+
+/// Convert an MBTiles file to PMTiles. Tiles are copied as stored, so
+/// gzipped vector tiles stay gzipped. MBTiles rows use TMS numbering, which
+/// counts rows from the south, so they're flipped to XYZ.
+pub fn convert_mbtiles(src: &Path, dest: &Path) -> Result<MbtilesInfo, String> {
+    let conn = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open MBTiles: {e}"))?;
+
+    let mut metadata = serde_json::Map::new();
+    {
+        let mut stmt = conn
+            .prepare("SELECT name, value FROM metadata")
+            .map_err(|e| format!("Failed to read MBTiles metadata: {e}"))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| format!("Failed to read MBTiles metadata: {e}"))?;
+        for row in rows {
+            let (name, value) =
+                row.map_err(|e| format!("Failed to read MBTiles metadata: {e}"))?;
+            metadata.insert(name, serde_json::Value::String(value));
+        }
+    }
+    let meta_str = |key: &str| metadata.get(key).and_then(|v| v.as_str());
+
+    let format = meta_str("format").unwrap_or("pbf");
+    let tile_type = tile_type_for_format(format)
+        .ok_or_else(|| format!("Unsupported MBTiles format: {format}"))?;
+    let name = meta_str("name").map(String::from);
+    let bounds = meta_str("bounds").and_then(parse_bounds);
+
+    let max_zoom: u8 = conn
+        .query_row("SELECT MAX(zoom_level) FROM tiles", [], |row| {
+            row.get::<_, Option<u8>>(0)
+        })
+        .map_err(|e| format!("Failed to read MBTiles tiles: {e}"))?
+        .ok_or_else(|| "MBTiles file has no tiles".to_string())?;
+
+    // Vector tiles in MBTiles are usually gzipped, but the spec doesn't
+    // require it, so check the first tile
+    let first_tile: Vec<u8> = conn
+        .query_row("SELECT tile_data FROM tiles LIMIT 1", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read MBTiles tiles: {e}"))?;
+    let compression = if first_tile.starts_with(&[0x1f, 0x8b]) {
+        Compression::Gzip
+    } else {
+        Compression::None
+    };
+
+    // The "json" metadata value holds vector_layers etc. as a JSON string;
+    // PMTiles keeps those as top-level metadata keys
+    if let Some(serde_json::Value::String(json)) = metadata.remove("json") {
+        if let Ok(serde_json::Value::Object(extra)) = serde_json::from_str(&json) {
+            metadata.extend(extra);
+        }
+    }
+    let metadata_json = serde_json::Value::Object(metadata).to_string();
+
+    let output_file = std::fs::File::create(dest)
+        .map_err(|e| format!("Failed to create output file: {e}"))?;
+    let mut builder = PmTilesWriter::new(tile_type)
+        .tile_compression(compression)
+        .max_zoom(max_zoom)
+        .metadata(&metadata_json);
+    if let Some([west, south, east, north]) = bounds {
+        builder = builder.bounds(west, south, east, north);
+    }
+    let mut writer = builder
+        .create(output_file)
+        .map_err(|e| format!("Failed to create PMTiles writer: {e}"))?;
+
+    let mut stmt = conn
+        .prepare("SELECT zoom_level, tile_column, tile_row, tile_data FROM tiles")
+        .map_err(|e| format!("Failed to read MBTiles tiles: {e}"))?;
+    let mut rows = stmt
+        .query([])
+        .map_err(|e| format!("Failed to read MBTiles tiles: {e}"))?;
+    let mut tile_count = 0;
+    while let Some(row) = rows
+        .next()
+        .map_err(|e| format!("Failed to read MBTiles tiles: {e}"))?
+    {
+        let read = |e: rusqlite::Error| format!("Failed to read MBTiles tile: {e}");
+        let z: u8 = row.get(0).map_err(read)?;
+        let x: u32 = row.get(1).map_err(read)?;
+        let tms_y: u32 = row.get(2).map_err(read)?;
+        let data: Vec<u8> = row.get(3).map_err(read)?;
+        let coord = 1u32
+            .checked_shl(z.into())
+            .and_then(|n| n.checked_sub(tms_y + 1))
+            .and_then(|y| TileCoord::new(z, x, y).ok())
+            .ok_or_else(|| format!("Invalid tile coord: {z}/{x}/{tms_y}"))?;
+        writer
+            .add_raw_tile(coord, &data)
+            .map_err(|e| format!("Failed to write tile: {e}"))?;
+        tile_count += 1;
+    }
+
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize PMTiles: {e}"))?;
+
+    Ok(MbtilesInfo { name, tile_count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pmtiles::{AsyncPmTilesReader, MmapBackend};
+    use tempfile::TempDir;
+
+    fn write_mbtiles(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE metadata (name TEXT, value TEXT);
+             CREATE TABLE tiles (
+                 zoom_level INTEGER, tile_column INTEGER,
+                 tile_row INTEGER, tile_data BLOB
+             );
+             INSERT INTO metadata VALUES
+                 ('name', 'Field site'),
+                 ('format', 'png'),
+                 ('bounds', '-122.5,37.0,-122.0,37.5');",
+        )
+        .unwrap();
+        let png = b"\x89PNG\r\n\x1a\nfake".to_vec();
+        // TMS row 0 at zoom 1 is the southern half of the world, XYZ y=1
+        conn.execute(
+            "INSERT INTO tiles VALUES (0, 0, 0, ?1), (1, 0, 0, ?1)",
+            [&png],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_convert_mbtiles_flips_tms_rows() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("site.mbtiles");
+        let dest = temp.path().join("site.pmtiles");
+        write_mbtiles(&src);
+
+        let info = convert_mbtiles(&src, &dest).unwrap();
+        assert_eq!(info.name.as_deref(), Some("Field site"));
+        assert_eq!(info.tile_count, 2);
+
+        let backend = MmapBackend::try_from(dest.as_path()).await.unwrap();
+        let reader = AsyncPmTilesReader::try_from_source(backend).await.unwrap();
+        let header = reader.get_header();
+        assert_eq!(header.tile_type, TileType::Png);
+        assert_eq!(header.max_zoom, 1);
+        assert_eq!(header.min_longitude, -122.5);
+        let south = reader.get_tile(TileCoord::new(1, 0, 1).unwrap()).await.unwrap();
+        assert!(south.is_some());
+        let north = reader.get_tile(TileCoord::new(1, 0, 0).unwrap()).await.unwrap();
+        assert!(north.is_none());
+    }
+
+    #[test]
+    fn test_parse_bounds() {
+        assert_eq!(
+            parse_bounds("-122.5, 37.0,-122.0,37.5"),
+            Some([-122.5, 37.0, -122.0, 37.5])
+        );
+        assert_eq!(parse_bounds("-122.5,37.0"), None);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod boundaries;
 pub mod commands;
+pub mod dem;
+mod gazetteer;
+mod mbtiles;
 pub mod protocol;
 
 use tauri::plugin::{Builder, TauriPlugin};
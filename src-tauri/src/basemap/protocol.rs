@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::sync::{Arc, LazyLock};
 
-use pmtiles::{AsyncPmTilesReader, MmapBackend, NoCache};
+use pmtiles::{AsyncPmTilesReader, MmapBackend, NoCache, TileType};
 use serde::{Deserialize, Serialize};
 use tauri::{Manager, Runtime};
 use tokio::sync::RwLock;
@@ -17,11 +17,44 @@ pub struct Bounds {
     pub max_lat: f64,
 }
 
+/// Whether a basemap holds Protomaps vector tiles or raster imagery
+/// (satellite, terrain, etc.). Read from the PMTiles header tile type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BasemapKind {
+    Vector,
+    Raster,
+}
+
+impl From<TileType> for BasemapKind {
+    fn from(tile_type: TileType) -> Self {
+        match tile_type {
+            TileType::Png
+            | TileType::Jpeg
+            | TileType::Webp
+            | TileType::Avif => Self::Raster,
+            _ => Self::Vector,
+        }
+    }
+}
+
+/// Content-Type header for tiles of the given type.
+pub fn tile_content_type(tile_type: TileType) -> &'static str {
+    match tile_type {
+        TileType::Png => "image/png",
+        TileType::Jpeg => "image/jpeg",
+        TileType::Webp => "image/webp",
+        TileType::Avif => "image/avif",
+        _ => "application/vnd.mapbox-vector-tile",
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BasemapInfo {
     pub id: String,
     pub name: String,
+    pub kind: BasemapKind,
     pub max_zoom: u8,
     pub bounds: Option<Bounds>,
     pub download_date: String,
@@ -217,6 +250,7 @@ pub async fn list_basemaps<R: Runtime>(
 
         let header = reader.get_header();
         let max_zoom = header.max_zoom;
+        let kind = BasemapKind::from(header.tile_type);
 
         // Extract bounds from header; treat global defaults as None
         let bounds = if id == "global" {
@@ -265,6 +299,7 @@ pub async fn list_basemaps<R: Runtime>(
         results.push(BasemapInfo {
             id,
             name,
+            kind,
             max_zoom,
             bounds,
             download_date,
@@ -402,11 +437,21 @@ pub fn handle_basemap_request<R: Runtime>(
         }
 
         // Parse z/x/y from URI path: "basemap://localhost/{z}/{x}/{y}"
+        // for the vector basemap, or
+        // "basemap://localhost/raster/{id}/{z}/{x}/{y}" for a raster one
         let uri = request.uri();
         let uri_path = uri.path();
-        let parts: Vec<&str> =
+        let mut parts: Vec<&str> =
             uri_path.trim_matches('/').split('/').collect();
 
+        let raster_id = if parts.len() == 5 && parts[0] == "raster" {
+            let id = parts[1].to_string();
+            parts.drain(..2);
+            Some(id)
+        } else {
+            None
+        };
+
         if parts.len() != 3 {
             respond_error(
                 responder,
@@ -469,12 +514,39 @@ pub fn handle_basemap_request<R: Runtime>(
             }
         };
 
+        if let Some(id) = raster_id {
+            let Some((info, reader)) = readers
+                .iter()
+                .find(|(info, _)| {
+                    info.id == id && info.kind == BasemapKind::Raster
+                })
+            else {
+                respond_error(responder, 404, "Raster basemap not found");
+                return;
+            };
+            if z <= info.max_zoom {
+                match reader.get_tile_decompressed(tile_coord).await {
+                    Ok(Some(data)) => {
+                        respond_tile(responder, reader, &data);
+                        return;
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Raster tile error: {e}"),
+                }
+            }
+            respond_empty(responder);
+            return;
+        }
+
         let tile_geo = tile_bounds(z, x, y);
 
         // Try regional readers first, then global
         let mut regional = Vec::new();
         let mut global = Vec::new();
         for (info, reader) in &readers {
+            if info.kind != BasemapKind::Vector {
+                continue;
+            }
             if info.bounds.is_some() {
                 regional.push((info, reader));
             } else {
@@ -493,7 +565,7 @@ pub fn handle_basemap_request<R: Runtime>(
             }
             match reader.get_tile_decompressed(tile_coord).await {
                 Ok(Some(data)) => {
-                    respond_tile(responder, &data);
+                    respond_tile(responder, reader, &data);
                     return;
                 }
                 Ok(None) => continue,
@@ -511,7 +583,7 @@ pub fn handle_basemap_request<R: Runtime>(
             }
             match reader.get_tile_decompressed(tile_coord).await {
                 Ok(Some(data)) => {
-                    respond_tile(responder, &data);
+                    respond_tile(responder, reader, &data);
                     return;
                 }
                 Ok(None) => continue,
@@ -523,18 +595,13 @@ pub fn handle_basemap_request<R: Runtime>(
         }
 
         // No tile found in any reader
-        responder.respond(
-            tauri::http::Response::builder()
-                .status(204)
-                .header("Access-Control-Allow-Origin", "*")
-                .body(Vec::new())
-                .unwrap(),
-        );
+        respond_empty(responder);
     });
 }
 
 fn respond_tile(
     responder: tauri::UriSchemeResponder,
+    reader: &BasemapReader,
     data: &[u8],
 ) {
     responder.respond(
@@ -542,7 +609,7 @@ fn respond_tile(
             .status(200)
             .header(
                 "Content-Type",
-                "application/vnd.mapbox-vector-tile",
+                tile_content_type(reader.get_header().tile_type),
             )
             .header("Cache-Control", "public, max-age=86400")
             .header("Access-Control-Allow-Origin", "*")
@@ -551,6 +618,16 @@ fn respond_tile(
     );
 }
 
+fn respond_empty(responder: tauri::UriSchemeResponder) {
+    responder.respond(
+        tauri::http::Response::builder()
+            .status(204)
+            .header("Access-Control-Allow-Origin", "*")
+            .body(Vec::new())
+            .unwrap(),
+    );
+}
+
 fn respond_error(
     responder: tauri::UriSchemeResponder,
     status: u16,
@@ -0,0 +1,269 @@
+use chuck_core::darwin_core::{Eml, EmlParty};
+use chuck_core::darwin_core::field_aliases::resolve_field;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::search_params::SearchParams;
+
+/// Per-record citation column, dcterms:bibliographicCitation
+pub const RECORD_CITATION_COLUMN: &str = "bibliographicCitation";
+
+/// Columns that say where records came from, grouped on to list each source
+/// dataset once
+const SOURCE_COLUMNS: [&str; 4] = ["datasetKey", "datasetName", "rightsHolder", "license"];
+
+/// One dataset the records came from, e.g. one of the datasets in a GBIF
+/// download
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationSource {
+    pub dataset_key: Option<String>,
+    pub dataset_name: Option<String>,
+    pub rights_holder: Option<String>,
+    pub license: Option<String>,
+    pub record_count: i64,
+}
+
+/// What users need to cite an archive or a subset of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Citation {
+    pub title: String,
+    /// DOI from eml.xml, as https://doi.org/...
+    pub doi: Option<String>,
+    /// ISO 8601 date
+    pub access_date: String,
+    /// Most records first
+    pub sources: Vec<CitationSource>,
+    /// Citation block ready to paste, with a line per source dataset
+    pub text: String,
+}
+
+/// Today as an ISO 8601 date, for access dates
+pub fn access_date() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// The DOI among the dataset's alternate identifiers, as a doi.org URL.
+/// GBIF downloads and datasets list theirs there.
+pub fn doi(eml: &Eml) -> Option<String> {
+    eml.alternate_identifiers.iter().find_map(|identifier| {
+        let identifier = identifier.trim();
+        let lower = identifier.to_lowercase();
+        let suffix = ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi:"]
+            .iter()
+            .find(|prefix| lower.starts_with(*prefix))
+            .map_or(identifier, |prefix| identifier[prefix.len()..].trim());
+        suffix.starts_with("10.").then(|| format!("https://doi.org/{suffix}"))
+    })
+}
+
+/// Source datasets of the occurrences matching `search_params`, with their
+/// record counts. Archives without any of the source columns have a single
+/// source with nothing but a count.
+pub fn citation_sources(db: &Database, search_params: SearchParams) -> Result<Vec<CitationSource>> {
+    let columns = db.get_available_columns()?;
+    let (_, where_clause, where_interpolations, _) =
+        db.query_parts(search_params, None, db.core_id_column(), db.extension_tables())?;
+    let selects: Vec<String> = SOURCE_COLUMNS
+        .iter()
+        .map(|name| match resolve_field(name, &columns) {
+            Some(column) => {
+                format!("NULLIF(trim(CAST({} AS VARCHAR)), '')", Database::quote_identifier(column))
+            }
+            None => "NULL".to_string(),
+        })
+        .collect();
+    let sql = format!(
+        "SELECT {selects}, COUNT(*) AS count FROM {occurrences}{where_clause} \
+         GROUP BY ALL ORDER BY count DESC, 2, 1",
+        selects = selects.join(", "),
+        occurrences = db.occurrences_source(),
+    );
+    let mut stmt = db.connection().prepare(&sql)?;
+    let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations.iter().map(|p| p.as_ref()).collect();
+    let sources = stmt
+        .query_map(param_refs.as_slice(), |row| {
+            Ok(CitationSource {
+                dataset_key: row.get(0)?,
+                dataset_name: row.get(1)?,
+                rights_holder: row.get(2)?,
+                license: row.get(3)?,
+                record_count: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(sources)
+}
+
+fn party_name(party: &EmlParty) -> Option<String> {
+    let person = [&party.given_name, &party.sur_name]
+        .into_iter()
+        .flatten()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if person.is_empty() {
+        party.organization_name.clone().filter(|name| !name.trim().is_empty())
+    } else {
+        Some(person)
+    }
+}
+
+/// Joins the non-empty parts of a citation into sentences
+fn sentences<'a>(parts: impl IntoIterator<Item = Option<&'a str>>) -> String {
+    parts
+        .into_iter()
+        .flatten()
+        .map(|part| part.trim().trim_end_matches('.'))
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("{part}."))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn dataset_url(dataset_key: &str) -> String {
+    if dataset_key.starts_with("http") {
+        dataset_key.to_string()
+    } else {
+        format!("https://www.gbif.org/dataset/{dataset_key}")
+    }
+}
+
+/// Assembles a citation from the archive's EML and the datasets its records
+/// came from, in the "Creators (year). Title. DOI. Accessed date." form GBIF
+/// asks for
+pub fn build_citation(eml: &Eml, sources: Vec<CitationSource>, access_date: &str) -> Citation {
+    let doi = doi(eml);
+    let creators = eml.creators.iter().filter_map(party_name).collect::<Vec<_>>().join(", ");
+    let year = eml.pub_date.as_deref().and_then(|date| date.get(..4));
+    let authorship = match (creators.is_empty(), year) {
+        (false, Some(year)) => Some(format!("{creators} ({year})")),
+        (false, None) => Some(creators),
+        (true, year) => year.map(|year| format!("({year})")),
+    };
+    let accessed = format!("Accessed {access_date}");
+    let mut lines = vec![sentences([
+        authorship.as_deref(),
+        Some(eml.title.as_str()),
+        doi.as_deref(),
+        Some(accessed.as_str()),
+    ])];
+
+    let cited_sources: Vec<&CitationSource> = sources
+        .iter()
+        .filter(|source| source.dataset_key.is_some() || source.dataset_name.is_some())
+        .collect();
+    if !cited_sources.is_empty() {
+        lines.push(String::new());
+        lines.push("Source datasets:".to_string());
+        for source in cited_sources {
+            let url = source.dataset_key.as_deref().map(dataset_url);
+            let records = format!(
+                "{} record{}",
+                source.record_count,
+                if source.record_count == 1 { "" } else { "s" }
+            );
+            lines.push(sentences([
+                source.rights_holder.as_deref(),
+                source.dataset_name.as_deref(),
+                url.as_deref(),
+                source.license.as_deref(),
+                Some(records.as_str()),
+            ]));
+        }
+    }
+
+    Citation {
+        title: eml.title.clone(),
+        doi,
+        access_date: access_date.to_string(),
+        sources,
+        text: lines.join("\n"),
+    }
+}
+
+/// Citation for a single exported occurrence, from its own dataset and
+/// rights columns, falling back to `archive_title` for the dataset
+pub fn record_citation(row: &Map<String, Value>, archive_title: &str, access_date: &str) -> String {
+    let get = |name: &str| {
+        row.get(name)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    let dataset = get("datasetName").or(Some(archive_title).filter(|title| !title.trim().is_empty()));
+    let url = get("datasetKey").map(dataset_url);
+    let occurrence = get("occurrenceID").map(|id| format!("Occurrence {id}"));
+    let accessed = format!("Accessed {access_date}");
+    sentences([
+        get("rightsHolder"),
+        dataset,
+        url.as_deref(),
+        occurrence.as_deref(),
+        get("license"),
+        Some(accessed.as_str()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_citation() {
+        let mut eml = Eml::default();
+        eml.alternate_identifiers = vec!["doi:10.15468/dl.abc123".to_string()];
+        eml.title = "GBIF Occurrence Download 0001234-240101000000000".to_string();
+        eml.creators = vec![EmlParty {
+            organization_name: Some("GBIF.org".to_string()),
+            ..Default::default()
+        }];
+        eml.pub_date = Some("2024-05-02".to_string());
+        let sources = vec![
+            CitationSource {
+                dataset_key: Some("50c9509d-22c7-4a22-a47d-8c48425ef4a7".to_string()),
+                dataset_name: Some("iNaturalist Research-grade Observations".to_string()),
+                rights_holder: None,
+                license: Some("CC_BY_NC_4_0".to_string()),
+                record_count: 12,
+            },
+            CitationSource { record_count: 1, ..Default::default() },
+        ];
+
+        let citation = build_citation(&eml, sources, "2024-06-01");
+
+        assert_eq!(citation.doi.as_deref(), Some("https://doi.org/10.15468/dl.abc123"));
+        let lines: Vec<&str> = citation.text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "GBIF.org (2024). GBIF Occurrence Download 0001234-240101000000000. \
+             https://doi.org/10.15468/dl.abc123. Accessed 2024-06-01."
+        );
+        assert_eq!(
+            lines[3],
+            "iNaturalist Research-grade Observations. \
+             https://www.gbif.org/dataset/50c9509d-22c7-4a22-a47d-8c48425ef4a7. CC_BY_NC_4_0. 12 records."
+        );
+        // Records without a dataset aren't listed as a source
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_record_citation() {
+        let row: Map<String, Value> = serde_json::from_str(
+            r#"{"occurrenceID": "https://www.inaturalist.org/observations/1",
+                "rightsHolder": "Ken-ichi Ueda", "license": "CC-BY", "datasetName": ""}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            record_citation(&row, "My archive", "2024-06-01"),
+            "Ken-ichi Ueda. My archive. Occurrence https://www.inaturalist.org/observations/1. \
+             CC-BY. Accessed 2024-06-01."
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::backtrace::Backtrace;
 use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
 use serde::{Serialize};
 use tauri::{Emitter, Manager};
 #[cfg(target_os = "linux")]
@@ -8,9 +10,10 @@ use gtk::prelude::{BinExt, Cast, GtkWindowExt, HeaderBarExt};
 #[cfg(target_os = "linux")]
 use gtk::{EventBox, HeaderBar};
 
+use crate::db::ImportProgress;
 use crate::dwca::Archive;
 use crate::error::{ChuckError, Result};
-use crate::photo_cache::PhotoCache;
+use crate::photo_cache::{PhotoCache, ThumbnailSize};
 use crate::search_params::SearchParams;
 use crate::ZipState;
 
@@ -19,13 +22,30 @@ use crate::ZipState;
 pub enum ArchiveOpenProgress {
     Importing,
     Extracting,
+    /// Bytes of archive contents written so far while extracting
+    #[serde(rename_all = "camelCase")]
+    ExtractProgress { extracted_bytes: u64, total_bytes: u64 },
     CreatingDatabase,
+    /// Data imported into the database so far
+    DatabaseProgress(ImportProgress),
     Complete { info: ArchiveInfo },
     Error { message: String },
+    Cancelled,
 }
 
+/// Set by cancel_open_archive to stop the archive open in progress
+static CANCEL_OPEN_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Set by cancel_enrichment to stop common name lookups in progress
+static CANCEL_ENRICHMENT_FLAG: LazyLock<Arc<AtomicBool>> =
+    LazyLock::new(|| Arc::new(AtomicBool::new(false)));
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ArchiveInfo {
+    /// Identifies the archive among open archives, e.g. for archive_id
+    /// arguments
+    pub id: String,
+
     pub name: String,
 
     #[serde(rename = "coreCount")]
@@ -36,12 +56,54 @@ pub struct ArchiveInfo {
 
     #[serde(rename = "availableColumns")]
     pub available_columns: Vec<String>,
+
+    /// Whether records are occurrences, events, or taxa. Event core archives
+    /// with an Occurrence extension have occurrence records.
+    #[serde(rename = "coreType")]
+    pub core_type: crate::dwca::CoreType,
+
+    /// Non-fatal problems from importing the archive, e.g. dropped columns
+    #[serde(rename = "importWarnings")]
+    pub import_warnings: Vec<crate::dwca::ImportWarning>,
+
+    /// Whether write paths like enrichment are disabled
+    #[serde(rename = "readOnly")]
+    pub read_only: bool,
+
+    /// Whether the zip the archive was imported from has changed since, so
+    /// it may need to be reimported
+    #[serde(rename = "sourceChanged")]
+    pub source_changed: bool,
+
+    /// How records were given IDs, if the archive's own were missing or
+    /// duplicated
+    #[serde(rename = "generatedCoreId")]
+    pub generated_core_id: Option<crate::dwca::GeneratedCoreId>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SearchResult {
     pub total: usize,
     pub results: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Pass to the next search to get the rows after these, only included
+    /// when the page was full
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// How the query ran, only included when searching with debug on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionInfo>,
+}
+
+/// Diagnostics for a search, for tracking down slow queries
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionInfo {
+    /// Time spent running the count and select queries
+    pub elapsed_ms: u64,
+    /// Whether DuckDB's plan for the select query uses an index scan
+    pub used_index: bool,
+    /// DuckDB's estimate of the rows read by scans in the select query
+    pub estimated_rows_scanned: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -105,16 +167,24 @@ pub(crate) fn get_archives_dir<R: tauri::Runtime>(app: tauri::AppHandle<R>) -> R
     Ok(base_dir.join("archives"))
 }
 
+/// Imports and opens the archive at `path`. Unless `ignore_disk_space` is
+/// set, archives too big for the free disk space fail with
+/// `ChuckError::InsufficientDiskSpace`, which the user can choose to ignore.
 #[tauri::command]
 pub async fn open_archive(
     app: tauri::AppHandle,
     window: tauri::WebviewWindow,
     path: String,
+    ignore_disk_space: Option<bool>,
 ) -> Result<ArchiveInfo> {
     use std::sync::mpsc;
 
     let base_dir = get_archives_dir(app.clone())?;
     let path_clone = path.clone();
+    let core_id_strategy = super::settings::read_import_settings(&app)?.core_id_strategy;
+
+    // Reset cancellation flag
+    CANCEL_OPEN_FLAG.store(false, Ordering::Relaxed);
 
     // Emit initial importing status
     app.emit("archive-open-progress", ArchiveOpenProgress::Importing)
@@ -132,13 +202,30 @@ pub async fn open_archive(
 
     // Spawn blocking task
     let app_for_thread = app.clone();
+    let app_for_extract = app.clone();
+    let app_for_import = app.clone();
     let result = tauri::async_runtime::spawn_blocking(move || {
-        let archive = Archive::open(
+        let archive = Archive::open_with_extract_progress(
             Path::new(&path_clone),
             &base_dir,
             |stage| {
                 let _ = tx.send(stage.to_string());
             },
+            &|extracted_bytes, total_bytes| {
+                let _ = app_for_extract.emit(
+                    "archive-open-progress",
+                    ArchiveOpenProgress::ExtractProgress { extracted_bytes, total_bytes },
+                );
+            },
+            &|progress| {
+                let _ = app_for_import.emit(
+                    "archive-open-progress",
+                    ArchiveOpenProgress::DatabaseProgress(progress.clone()),
+                );
+            },
+            &core_id_strategy,
+            !ignore_disk_space.unwrap_or(false),
+            &CANCEL_OPEN_FLAG,
         )?;
         // Parse the zip central directory once while still on a blocking thread.
         // Returns None on failure; get_photo will re-attempt lazily if needed.
@@ -162,10 +249,11 @@ pub async fn open_archive(
     match result.await {
         Ok(Ok((archive, zip_archive))) => {
             let info = archive.info()?;
+            crate::archive_watcher::watch_archive(&app, &archive);
 
             if let Some(zip) = zip_archive {
                 if let Ok(mut guard) = app.state::<ZipState>().0.lock() {
-                    *guard = Some(zip);
+                    *guard = Some((archive.storage_dir.clone(), zip));
                 }
             }
 
@@ -184,6 +272,12 @@ pub async fn open_archive(
 
             Ok(info)
         }
+        Ok(Err(ChuckError::Cancelled)) => {
+            log::info!(path = path.as_str(); "Cancelled opening {path}");
+            app.emit("archive-open-progress", ArchiveOpenProgress::Cancelled)
+                .map_err(|err| ChuckError::Tauri(err.to_string()))?;
+            Err(ChuckError::Cancelled)
+        }
         Ok(Err(e)) => {
             log::debug!("Failed to open archive: {e}");
 
@@ -215,6 +309,14 @@ pub async fn open_archive(
     }
 }
 
+/// Stops the archive open in progress. Extraction stops right away; if the
+/// database is already being created, its result is discarded when done.
+#[tauri::command]
+pub fn cancel_open_archive() -> Result<()> {
+    CANCEL_OPEN_FLAG.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
 /// Returns and clears the file path passed via CLI args (file association on
 /// Windows/Linux). Returns None if no file was passed or it was already consumed.
 #[tauri::command]
@@ -247,15 +349,18 @@ fn set_archive_window_title(window: &tauri::WebviewWindow, info: &ArchiveInfo) {
 }
 
 #[tauri::command]
-pub fn current_archive(app: tauri::AppHandle) -> Result<ArchiveInfo> {
-    let info = Archive::current(&get_archives_dir(app.clone())?).map_err(|e| {
-        log::error!(
-            "Failed to get current archive: {}, backtrace: {}",
-            e,
-            Backtrace::capture()
-        );
-        e
-    })?.info()?;
+pub fn current_archive(app: tauri::AppHandle, archive_id: Option<String>) -> Result<ArchiveInfo> {
+    let archive =
+        Archive::find(&get_archives_dir(app.clone())?, archive_id.as_deref()).map_err(|e| {
+            log::error!(
+                "Failed to get current archive: {}, backtrace: {}",
+                e,
+                Backtrace::capture()
+            );
+            e
+        })?;
+    let info = archive.info()?;
+    crate::archive_watcher::watch_archive(&app, &archive);
     // Set window title in a spawned task to avoid interfering with the command response.
     // Using WebviewWindow as a command parameter breaks the return value in Tauri 2.
     let app_clone = app;
@@ -268,15 +373,68 @@ pub fn current_archive(app: tauri::AppHandle) -> Result<ArchiveInfo> {
     Ok(info)
 }
 
+/// Lists the archives kept from previous opens, most recently opened first
+#[tauri::command]
+pub fn list_open_archives(app: tauri::AppHandle) -> Result<Vec<ArchiveInfo>> {
+    Archive::list(&get_archives_dir(app)?)?
+        .iter()
+        .map(Archive::info)
+        .collect()
+}
+
+/// Removes an open archive and its extracted files
+#[tauri::command]
+pub fn close_archive(app: tauri::AppHandle, archive_id: String) -> Result<()> {
+    let base_dir = get_archives_dir(app.clone())?;
+    let archive = Archive::by_id(&base_dir, &archive_id)?;
+    // The cached zip belongs to the current archive. Release it in case
+    // that's the one being removed, since open handles prevent deletion on
+    // Windows. get_photo rebuilds it lazily.
+    if let Ok(mut guard) = app.state::<ZipState>().0.lock() {
+        *guard = None;
+    }
+    archive.close().map_err(|e| {
+        log::error!("caught close_archive error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Imports an archive again from the zip it was opened from, e.g. after a
+/// new export replaced it, and removes the old import. Changes made in
+/// Chuck, like edits and flags, don't carry over.
+#[tauri::command]
+pub async fn reimport_archive(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    archive_id: String,
+) -> Result<ArchiveInfo> {
+    let base_dir = get_archives_dir(app.clone())?;
+    let source = Archive::by_id(&base_dir, &archive_id)?
+        .source()
+        .ok_or_else(|| ChuckError::UnknownArchiveSource(archive_id.clone()))?;
+    let path = source.path.to_str().ok_or(ChuckError::PathEncoding)?.to_string();
+    let info = open_archive(app, window, path, None).await?;
+    // Opening may already have removed it to make room
+    if let Ok(old) = Archive::by_id(&base_dir, &archive_id) {
+        if let Err(e) = old.close() {
+            log::warn!("Failed to remove the previous import of {archive_id}: {e}");
+        }
+    }
+    Ok(info)
+}
+
 #[tauri::command]
 pub fn search(
     app: tauri::AppHandle,
     limit: usize,
     offset: usize,
+    cursor: Option<String>,
     search_params: SearchParams,
     fields: Option<Vec<String>>,
+    debug: Option<bool>,
+    archive_id: Option<String>,
 ) -> Result<SearchResult> {
-    let archive = Archive::current(&get_archives_dir(app)?).map_err(|e| {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
         log::error!(
             "caught error opening current: {}, backtrace: {}",
             e,
@@ -284,9 +442,57 @@ pub fn search(
         );
         e
     })?;
-    archive.search(limit, offset, search_params, fields).map_err(|e| {
+    let result = archive.search_with_execution_info(
+        limit,
+        offset,
+        cursor.as_deref(),
+        search_params,
+        fields,
+        debug.unwrap_or(false),
+    ).map_err(|e| {
         log::error!("caught search error: {}, backtrace: {}", e, Backtrace::capture());
         e
+    })?;
+    if let Some(execution) = &result.execution {
+        log::debug!("search execution: {execution:?}");
+    }
+    Ok(result)
+}
+
+/// Summary stats for the archive overview: record count, fill rate of each
+/// column, distinct counts of key columns, extent, and date range. Computed
+/// on import, so it's quick.
+#[tauri::command]
+pub fn get_archive_stats(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<crate::archive_stats::ArchiveStats> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.stats().map_err(|e| {
+        log::error!("caught get_archive_stats error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Materializes the occurrences matching the search's filters so later
+/// searches, aggregations, tiles, and exports with the same filters don't
+/// have to run them again. Returns the number of matching occurrences.
+#[tauri::command]
+pub fn materialize_filter(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<usize> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.materialize_filter(search_params).map_err(|e| {
+        log::error!("caught materialize_filter error: {}, backtrace: {}", e, Backtrace::capture());
+        e
     })
 }
 
@@ -296,8 +502,9 @@ pub fn get_autocomplete_suggestions(
     column_name: String,
     search_term: String,
     limit: Option<usize>,
+    archive_id: Option<String>,
 ) -> Result<Vec<String>> {
-    let archive = Archive::current(&get_archives_dir(app)?).map_err(|e| {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
         log::error!(
             "caught error opening current: {}, backtrace: {}",
             e,
@@ -311,15 +518,96 @@ pub fn get_autocomplete_suggestions(
     })
 }
 
+/// Lists the most common values of a text column among occurrences matching
+/// the search, with counts and the number of distinct values, for faceted
+/// browsing. With `search_term`, only values containing it are listed.
+#[tauri::command]
+pub fn get_column_values(
+    app: tauri::AppHandle,
+    column_name: String,
+    search_params: SearchParams,
+    search_term: Option<String>,
+    limit: Option<usize>,
+    archive_id: Option<String>,
+) -> Result<crate::db::ColumnValues> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive
+        .get_column_values(&column_name, &search_params, search_term.as_deref(), limit.unwrap_or(50))
+        .map_err(|e| {
+            log::error!("caught get_column_values error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+/// Lists the most common values of each of `column_names` among occurrences
+/// matching the search in one query, for live facet counts in the sidebar
+#[tauri::command]
+pub fn get_facets(
+    app: tauri::AppHandle,
+    column_names: Vec<String>,
+    search_params: SearchParams,
+    limit: Option<usize>,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::db::Facet>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive
+        .get_facets(&column_names, &search_params, limit.unwrap_or(10))
+        .map_err(|e| {
+            log::error!("caught get_facets error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+/// Runs a SELECT query typed by the user against the database of the archive
+/// with `archive_id`, or the current archive. Anything other than a single
+/// SELECT is rejected, and at most `limit` rows (1000 by default) are
+/// returned.
+#[tauri::command]
+pub fn execute_sql(
+    app: tauri::AppHandle,
+    sql: String,
+    limit: Option<usize>,
+    archive_id: Option<String>,
+) -> Result<chuck_core::sql_console::SqlResult> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.execute_sql(
+        &sql,
+        limit.unwrap_or(chuck_core::sql_console::DEFAULT_ROW_LIMIT),
+    )
+}
+
 #[tauri::command]
 pub fn get_occurrence(
     app: tauri::AppHandle,
     occurrence_id: String,
+    archive_id: Option<String>,
 ) -> Result<serde_json::Map<String, serde_json::Value>> {
-    let archive = Archive::current(&get_archives_dir(app)?)?;
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
     archive.get_occurrence(&occurrence_id)
 }
 
+/// Occurrences within `tolerance` pixels (5 by default) of a clicked point
+/// on the map, nearest first
+#[tauri::command]
+pub fn get_occurrences_at_point(
+    app: tauri::AppHandle,
+    lat: f64,
+    lng: f64,
+    zoom: f64,
+    tolerance: Option<f64>,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::tile_server::TilePoint>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.occurrences_at_point(lat, lng, zoom, tolerance.unwrap_or(5.0), search_params)
+}
+
 /// Opens the archive zip and parses its central directory, returning a ZipArchive
 /// ready for repeated photo lookups. Returns None and logs a warning on failure.
 fn build_zip_archive(storage_dir: &Path) -> Option<zip::ZipArchive<std::fs::File>> {
@@ -348,8 +636,21 @@ pub fn get_photo(
     app: tauri::AppHandle,
     zip_state: tauri::State<'_, ZipState>,
     photo_path: String,
+    archive_id: Option<String>,
 ) -> Result<String> {
-    let archive = Archive::current(&get_archives_dir(app)?)?;
+    cache_archive_file(app, &zip_state, &photo_path, archive_id.as_deref())
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Extracts a file in the archive zip to the photo cache, unless it's
+/// already there, and returns its path in the cache
+fn cache_archive_file<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    zip_state: &ZipState,
+    photo_path: &str,
+    archive_id: Option<&str>,
+) -> Result<PathBuf> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id)?;
 
     let cache_dir = archive.storage_dir.join("photo_cache");
     std::fs::create_dir_all(&cache_dir).map_err(|e| ChuckError::DirectoryCreate {
@@ -358,13 +659,13 @@ pub fn get_photo(
     })?;
     let photo_cache = PhotoCache::new(&cache_dir);
 
-    if let Some(cached_path) = photo_cache.get_cached_photo(&photo_path)? {
+    if let Some(cached_path) = photo_cache.get_cached_photo(photo_path)? {
         photo_cache.touch_file(&cached_path)?;
-        return Ok(cached_path.to_string_lossy().to_string());
+        return Ok(cached_path);
     }
 
     let normalized_path = photo_path.replace('\\', "/");
-    let cached_file_path = photo_cache.get_cache_path(&photo_path);
+    let cached_file_path = photo_cache.get_cache_path(photo_path);
 
     if let Some(p) = cached_file_path.parent() {
         if !p.exists() {
@@ -376,15 +677,20 @@ pub fn get_photo(
     }
 
     // Use the shared ZipArchive so the central directory is only parsed once.
-    // Initialise lazily here if open_archive hasn't run yet (e.g. after restart).
+    // Initialise lazily here if open_archive hasn't run yet (e.g. after
+    // restart) or the cached zip belongs to a different archive.
     {
         let mut guard = zip_state
             .0
             .lock()
             .map_err(|_| ChuckError::Tauri("ZipState mutex poisoned".to_string()))?;
 
-        if guard.is_none() {
-            *guard = build_zip_archive(&archive.storage_dir);
+        if guard
+            .as_ref()
+            .is_none_or(|(dir, _)| *dir != archive.storage_dir)
+        {
+            *guard = build_zip_archive(&archive.storage_dir)
+                .map(|zip| (archive.storage_dir.clone(), zip));
             if guard.is_none() {
                 return Err(ChuckError::Tauri(
                     "Failed to open archive zip for photo extraction".to_string(),
@@ -393,7 +699,7 @@ pub fn get_photo(
             log::debug!("ZipState initialised lazily in get_photo");
         }
 
-        let zip = guard.as_mut().unwrap();
+        let (_, zip) = guard.as_mut().unwrap();
         let zip_file = zip
             .by_name(&normalized_path)
             .map_err(ChuckError::ArchiveExtraction)?;
@@ -411,10 +717,138 @@ pub fn get_photo(
         })?;
     } // release the mutex before eviction
 
-    const MAX_CACHE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
-    photo_cache.evict_lru(MAX_CACHE_SIZE)?;
+    photo_cache.evict_lru(crate::photo_cache::max_cache_size())?;
+
+    Ok(cached_file_path)
+}
 
-    Ok(cached_file_path.to_string_lossy().to_string())
+/// Downloads a file to the photo cache of the current archive, unless it's
+/// already there, and returns its path in the cache
+async fn cache_remote_file<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    url: &str,
+    archive_id: Option<&str>,
+) -> Result<PathBuf> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id)?;
+    let cache_dir = archive.storage_dir.join("photo_cache");
+    drop(archive);
+    std::fs::create_dir_all(&cache_dir).map_err(|e| ChuckError::DirectoryCreate {
+        path: cache_dir.clone(),
+        source: e,
+    })?;
+    let photo_cache = PhotoCache::new(&cache_dir);
+    let cached_file_path = photo_cache.get_remote_cache_path(url);
+    if cached_file_path.exists() {
+        photo_cache.touch_file(&cached_file_path)?;
+        return Ok(cached_file_path);
+    }
+
+    let download_error = |source| ChuckError::MediaDownload { url: url.to_string(), source };
+    let bytes = chuck_core::api::client::http_client()
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(download_error)?
+        .bytes()
+        .await
+        .map_err(download_error)?;
+    // Written under another name first so an interrupted download is never
+    // served as a complete file
+    let partial_path = cached_file_path.with_extension("part");
+    std::fs::write(&partial_path, &bytes).map_err(|source| ChuckError::FileWrite {
+        path: partial_path.clone(),
+        source,
+    })?;
+    std::fs::rename(&partial_path, &cached_file_path).map_err(|source| ChuckError::FileWrite {
+        path: cached_file_path.clone(),
+        source,
+    })?;
+    photo_cache.evict_lru(crate::photo_cache::max_cache_size())?;
+    Ok(cached_file_path)
+}
+
+/// Caches a photo or sound from the archive, or from a remote URL for
+/// archives that link to their media, returning its path in the cache
+pub(crate) async fn cache_media<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    media_path: &str,
+    archive_id: Option<&str>,
+) -> Result<PathBuf> {
+    if media_path.starts_with("http://") || media_path.starts_with("https://") {
+        cache_remote_file(app, media_path, archive_id).await
+    } else {
+        let zip_state = app.state::<ZipState>();
+        cache_archive_file(app.clone(), &zip_state, media_path, archive_id)
+    }
+}
+
+/// A cached photo or sound and the MIME type to serve it as
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaFile {
+    pub path: String,
+    pub mime_type: String,
+}
+
+/// Like get_photo, but for any media, including sounds and media at remote
+/// URLs. Players that need to seek should load it through the media://
+/// protocol rather than the returned path.
+#[tauri::command]
+pub async fn get_media(
+    app: tauri::AppHandle,
+    media_path: String,
+    archive_id: Option<String>,
+) -> Result<MediaFile> {
+    let path = cache_media(app, &media_path, archive_id.as_deref()).await?;
+    Ok(MediaFile {
+        path: path.to_string_lossy().to_string(),
+        mime_type: crate::media_server::mime_type(&media_path).to_string(),
+    })
+}
+
+/// Returns the path to a thumbnail of a photo in the archive that fits
+/// `size`, generating and caching it the first time it's requested
+#[tauri::command]
+pub async fn get_photo_thumbnail(
+    app: tauri::AppHandle,
+    zip_state: tauri::State<'_, ZipState>,
+    photo_path: String,
+    size: ThumbnailSize,
+    archive_id: Option<String>,
+) -> Result<String> {
+    let archive = Archive::find(&get_archives_dir(app.clone())?, archive_id.as_deref())?;
+    let photo_cache = PhotoCache::new(&archive.storage_dir.join("photo_cache"));
+    drop(archive);
+    let thumbnail_path = photo_cache.get_thumbnail_path(&photo_path, size);
+    if thumbnail_path.exists() {
+        photo_cache.touch_file(&thumbnail_path)?;
+        return Ok(thumbnail_path.to_string_lossy().to_string());
+    }
+
+    let photo_file = get_photo(app, zip_state, photo_path, archive_id)?;
+    photo_cache.create_thumbnail(Path::new(&photo_file), &thumbnail_path, size)?;
+    Ok(thumbnail_path.to_string_lossy().to_string())
+}
+
+/// Reads EXIF metadata from a photo in the archive, checking it against the
+/// eventDate and coordinates of the occurrence with `occurrence_id` if given
+#[tauri::command]
+pub async fn get_photo_metadata(
+    app: tauri::AppHandle,
+    zip_state: tauri::State<'_, ZipState>,
+    photo_path: String,
+    occurrence_id: Option<String>,
+    archive_id: Option<String>,
+) -> Result<crate::exif::PhotoMetadata> {
+    let photo_file = get_photo(app.clone(), zip_state, photo_path, archive_id.clone())?;
+    let mut metadata = crate::exif::read_photo_metadata(Path::new(&photo_file))?;
+    if let Some(occurrence_id) = occurrence_id {
+        let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+        let occurrence = archive.get_occurrence(&occurrence_id)?;
+        metadata.mismatches = crate::exif::check_against_occurrence(&metadata, &occurrence);
+    }
+    Ok(metadata)
 }
 
 #[tauri::command]
@@ -423,24 +857,606 @@ pub fn aggregate_by_field(
     field_name: String,
     search_params: SearchParams,
     limit: usize,
+    split_values: Option<bool>,
+    metrics: Option<Vec<crate::db::AggregationMetric>>,
+    order_by: Option<crate::db::AggregationMetric>,
+    archive_id: Option<String>,
 ) -> Result<Vec<crate::db::AggregationResult>> {
-    let archive = Archive::current(&get_archives_dir(app)?).map_err(|e| {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
         log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
         e
     })?;
-    archive.aggregate_by_field(&field_name, &search_params, Some(limit)).map_err(|e| {
+    let result = if split_values.unwrap_or(false) {
+        archive.aggregate_by_field_values(&field_name, &search_params, Some(limit))
+    } else {
+        archive.aggregate_by_field(
+            &field_name,
+            &search_params,
+            Some(limit),
+            &metrics.unwrap_or_default(),
+            order_by,
+        )
+    };
+    result.map_err(|e| {
         log::error!("caught aggregate_by_field error: {}, backtrace: {}", e, Backtrace::capture());
         e
     })
 }
 
 #[tauri::command]
-pub fn get_archive_metadata(app: tauri::AppHandle) -> Result<ArchiveMetadata> {
+pub fn get_group_examples(
+    app: tauri::AppHandle,
+    field_name: String,
+    value: Option<String>,
+    search_params: SearchParams,
+    limit: usize,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::db::GroupExample>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.group_examples(&field_name, value.as_deref(), &search_params, limit).map_err(|e| {
+        log::error!("caught get_group_examples error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+#[tauri::command]
+pub fn aggregate_by_two_fields(
+    app: tauri::AppHandle,
+    primary_field: String,
+    secondary_field: String,
+    search_params: SearchParams,
+    limit_per_group: Option<usize>,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::db::CrosstabResult>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive
+        .aggregate_by_two_fields(&primary_field, &secondary_field, &search_params, limit_per_group)
+        .map_err(|e| {
+            log::error!("caught aggregate_by_two_fields error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+#[tauri::command]
+pub fn column_stats(
+    app: tauri::AppHandle,
+    column_name: String,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<crate::db::ColumnStats> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.column_stats(&column_name, &search_params).map_err(|e| {
+        log::error!("caught column_stats error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+#[tauri::command]
+pub fn run_quality_report(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<crate::quality::QualityReport> {
+    // Boundaries are an optional download and missing ones only skip the
+    // country check, so don't fail the report
+    let boundaries = crate::basemap::boundaries::country_boundaries_path(&app)
+        .map_err(ChuckError::Tauri)
+        .and_then(|path| crate::country_boundaries::CountryBoundaries::load(&path))
+        .inspect_err(|e| log::warn!("Country boundaries unavailable: {e}"))
+        .ok();
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.quality_report(boundaries.as_ref()).map_err(|e| {
+        log::error!("caught run_quality_report error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichmentProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[tauri::command]
+pub fn get_enrichments(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::enrichment::Enrichment>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.enrichments()
+}
+
+/// Fills in missing vernacularName values by scientificName from a local
+/// file or iNat. Emits enrichment-progress while looking names up on iNat,
+/// which cancel_enrichment stops.
+#[tauri::command]
+pub async fn enrich_vernacular_names(
+    app: tauri::AppHandle,
+    source: crate::enrichment::VernacularNameSource,
+    archive_id: Option<String>,
+) -> Result<crate::enrichment::Enrichment> {
+    use crate::enrichment::{fill_vernacular_names, fill_vernacular_names_from_file, VernacularNameSource};
+
+    let archives_dir = get_archives_dir(app.clone())?;
+    let archive = Archive::find(&archives_dir, archive_id.as_deref())?;
+    let core_id_column = archive.core_id_column.clone();
+    let result = match source {
+        VernacularNameSource::File { path } => archive.with_writable_db(|conn| {
+            fill_vernacular_names_from_file(conn, &core_id_column, Path::new(&path))
+        }),
+        VernacularNameSource::Inaturalist { locale } => {
+            let names = archive.scientific_names_missing_vernacular()?;
+            // Don't hold the database open while waiting on the network
+            drop(archive);
+            CANCEL_ENRICHMENT_FLAG.store(false, Ordering::Relaxed);
+            let common_names = chuck_core::api::common_names::fetch_common_names(
+                "https://api.inaturalist.org/v1",
+                &names,
+                locale.as_deref(),
+                Some(Arc::clone(&CANCEL_ENRICHMENT_FLAG)),
+                |done, total| {
+                    let _ = app.emit("enrichment-progress", EnrichmentProgress { done, total });
+                },
+            )
+                .await
+                .map_err(|e| {
+                    if CANCEL_ENRICHMENT_FLAG.load(Ordering::Relaxed) {
+                        ChuckError::Cancelled
+                    } else {
+                        ChuckError::Tauri(format!("Failed to fetch common names: {e}"))
+                    }
+                })?;
+            Archive::find(&archives_dir, archive_id.as_deref())?.with_writable_db(|conn| {
+                fill_vernacular_names(conn, &core_id_column, &common_names, "iNaturalist")
+            })
+        }
+    };
+    result.map_err(|e| {
+        log::error!("caught enrich_vernacular_names error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Stops common name lookups started by enrich_vernacular_names. Names
+/// already looked up are discarded.
+#[tauri::command]
+pub fn cancel_enrichment() -> Result<()> {
+    CANCEL_ENRICHMENT_FLAG.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Fills in missing countryCode, stateProvince, and county values of
+/// filtered occurrences from the downloaded admin boundaries. With an
+/// export_path, also writes the enriched occurrences to a CSV file.
+#[tauri::command]
+pub fn enrich_admin_areas(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    export_path: Option<String>,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::enrichment::Enrichment>> {
+    let path = crate::basemap::boundaries::admin_boundaries_path(&app).map_err(ChuckError::Tauri)?;
+    if !path.exists() {
+        return Err(ChuckError::BoundariesNotDownloaded);
+    }
+    let boundaries = crate::admin_boundaries::AdminBoundaries::load(&path)?;
+    let archives_dir = get_archives_dir(app)?;
+    let archive = Archive::find(&archives_dir, archive_id.as_deref())?;
+    let enrichments = archive
+        .enrich_admin_areas(&boundaries, search_params.clone())
+        .map_err(|e| {
+            log::error!("caught enrich_admin_areas error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })?;
+    if let Some(path) = export_path {
+        crate::commands::export::export_enriched_csv(
+            archives_dir,
+            archive_id.as_deref(),
+            search_params,
+            path,
+        )?;
+    }
+    Ok(enrichments)
+}
+
+/// Fills in missing elevation values of filtered occurrences from the
+/// downloaded DEM. Emits enrichment-progress while looking up elevations.
+#[tauri::command]
+pub async fn enrich_elevation(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<crate::enrichment::Enrichment> {
+    let archives_dir = get_archives_dir(app.clone())?;
+    let candidates = Archive::find(&archives_dir, archive_id.as_deref())?
+        .occurrences_missing_elevation(search_params)?;
+
+    let mut dem = crate::basemap::dem::Dem::open(&app).await.map_err(ChuckError::Tauri)?;
+    let total = candidates.len();
+    let mut elevations = Vec::with_capacity(total);
+    for (done, (id, lat, lon)) in candidates.into_iter().enumerate() {
+        if let Some(elevation) = dem.elevation_at(lat, lon).await {
+            elevations.push((id, elevation));
+        }
+        if done % 1000 == 0 {
+            let _ = app.emit("enrichment-progress", EnrichmentProgress { done, total });
+        }
+    }
+
+    let archive = Archive::find(&archives_dir, archive_id.as_deref())?;
+    let core_id_column = archive.core_id_column.clone();
+    archive
+        .with_writable_db(|conn| {
+            crate::enrichment::fill_elevations(conn, &core_id_column, &elevations, "Terrarium DEM")
+        })
+        .map_err(|e| {
+            log::error!("caught enrich_elevation error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+#[tauri::command]
+pub fn split_multi_value_fields(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<Vec<String>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.split_multi_value_fields().map_err(|e| {
+        log::error!("caught split_multi_value_fields error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Loads a taxonomy (e.g. Taxon.tsv from the GBIF backbone or taxa.csv from
+/// the iNaturalist taxonomy archive) for higher taxon filters, returning the
+/// number of taxa loaded
+#[tauri::command]
+pub fn load_taxonomy(
+    app: tauri::AppHandle,
+    path: String,
+    archive_id: Option<String>,
+) -> Result<usize> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.load_taxonomy(Path::new(&path)).map_err(|e| {
+        log::error!("caught load_taxonomy error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Maps placeholder values like -9999 in numeric fields to NULL, returning
+/// the number of values mapped
+#[tauri::command]
+pub fn map_sentinel_values(app: tauri::AppHandle, archive_id: Option<String>) -> Result<usize> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.map_sentinel_values().map_err(|e| {
+        log::error!("caught map_sentinel_values error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Restores placeholder values mapped to NULL by map_sentinel_values
+#[tauri::command]
+pub fn restore_sentinel_values(app: tauri::AppHandle, archive_id: Option<String>) -> Result<usize> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.restore_sentinel_values().map_err(|e| {
+        log::error!("caught restore_sentinel_values error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Sets one field of an occurrence in the current archive, e.g. to fix a
+/// typo. An empty or null value clears the field.
+#[tauri::command]
+pub fn update_occurrence_field(
+    app: tauri::AppHandle,
+    core_id: String,
+    column: String,
+    value: Option<String>,
+    archive_id: Option<String>,
+) -> Result<crate::edits::Edit> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive
+        .update_occurrence_field(&core_id, &column, value.as_deref())
+        .map_err(|e| {
+            log::error!("caught update_occurrence_field error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+/// Lists edits to the current archive, optionally only those to one
+/// occurrence
+#[tauri::command]
+pub fn list_edits(
+    app: tauri::AppHandle,
+    core_id: Option<String>,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::edits::Edit>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.edits(core_id.as_deref())
+}
+
+/// Flags an occurrence in the current archive for review, e.g. with
+/// "suspect coordinates", and an optional note
+#[tauri::command]
+pub fn flag_occurrence(
+    app: tauri::AppHandle,
+    core_id: String,
+    flag: String,
+    note: Option<String>,
+    archive_id: Option<String>,
+) -> Result<crate::flags::Flag> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive
+        .flag_occurrence(&core_id, &flag, note.as_deref())
+        .map_err(|e| {
+            log::error!("caught flag_occurrence error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+/// Removes a flag from an occurrence in the current archive, returning
+/// whether it had the flag
+#[tauri::command]
+pub fn unflag_occurrence(
+    app: tauri::AppHandle,
+    core_id: String,
+    flag: String,
+    archive_id: Option<String>,
+) -> Result<bool> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.unflag_occurrence(&core_id, &flag)
+}
+
+/// Lists flags in the current archive, optionally only those on one
+/// occurrence
+#[tauri::command]
+pub fn list_flags(
+    app: tauri::AppHandle,
+    core_id: Option<String>,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::flags::Flag>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.flags(core_id.as_deref())
+}
+
+/// Excludes the occurrences matching search_params from exports of the
+/// current archive without deleting them, returning the number newly
+/// excluded
+#[tauri::command]
+pub fn exclude_occurrences(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    reason: Option<String>,
+    archive_id: Option<String>,
+) -> Result<usize> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive
+        .exclude_occurrences(search_params, reason.as_deref())
+        .map_err(|e| {
+            log::error!("caught exclude_occurrences error: {}, backtrace: {}", e, Backtrace::capture());
+            e
+        })
+}
+
+/// Restores excluded occurrences in the current archive by core ID, or all
+/// of them without any, returning the number restored
+#[tauri::command]
+pub fn restore_exclusions(
+    app: tauri::AppHandle,
+    core_ids: Option<Vec<String>>,
+    archive_id: Option<String>,
+) -> Result<usize> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.restore_exclusions(core_ids.as_deref())
+}
+
+/// Lists occurrences excluded from exports of the current archive
+#[tauri::command]
+pub fn list_exclusions(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::exclusions::Exclusion>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.exclusions()
+}
+
+/// Allows enrichments and other changes to the current archive's database
+#[tauri::command]
+pub fn unlock_archive(app: tauri::AppHandle, archive_id: Option<String>) -> Result<ArchiveInfo> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.set_read_only(false)?;
+    archive.info()
+}
+
+/// Disables changes to the current archive's database again
+#[tauri::command]
+pub fn lock_archive(app: tauri::AppHandle, archive_id: Option<String>) -> Result<ArchiveInfo> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.set_read_only(true)?;
+    archive.info()
+}
+
+#[tauri::command]
+pub fn aggregate_by_time(
+    app: tauri::AppHandle,
+    bucket: crate::db::TimeBucket,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::db::TimeAggregationResult>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.aggregate_by_time(bucket, &search_params).map_err(|e| {
+        log::error!("caught aggregate_by_time error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Counts occurrences matching the search per day, month, or year between
+/// the archive's earliest and latest eventDate, for charting activity
+#[tauri::command]
+pub fn get_time_series(
+    app: tauri::AppHandle,
+    interval: crate::db::TimeInterval,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::db::TimeSeriesPoint>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.time_series(interval, &search_params).map_err(|e| {
+        log::error!("caught get_time_series error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Species accumulation curve for occurrences matching the search, for
+/// judging how complete an inventory is
+#[tauri::command]
+pub fn get_species_accumulation(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::db::AccumulationPoint>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.species_accumulation(&search_params).map_err(|e| {
+        log::error!("caught get_species_accumulation error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+/// Summarizes occurrences matching the search on a grid of `cell_size`
+/// degrees, e.g. 0.1 or 0.01, as a GeoJSON FeatureCollection of cell
+/// polygons with recordCount and speciesCount properties, for showing
+/// where sampling effort has and hasn't gone
+#[tauri::command]
+pub fn get_grid_summary(
+    app: tauri::AppHandle,
+    cell_size: f64,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<serde_json::Value> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    let cells = archive.grid_summary(cell_size, &search_params).map_err(|e| {
+        log::error!("caught get_grid_summary error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    Ok(crate::db::grid_geojson(&cells))
+}
+
+/// Record count, species count, date range, extent, and monthly activity of
+/// the occurrences matching the search that `recorded_by` collected, for a
+/// collector profile
+#[tauri::command]
+pub fn get_collector_summary(
+    app: tauri::AppHandle,
+    recorded_by: String,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<crate::db::CollectorSummary> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref()).map_err(|e| {
+        log::error!("caught error opening current: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })?;
+    archive.collector_summary(&recorded_by, &search_params).map_err(|e| {
+        log::error!("caught get_collector_summary error: {}, backtrace: {}", e, Backtrace::capture());
+        e
+    })
+}
+
+#[tauri::command]
+pub fn get_import_warnings(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::dwca::ImportWarning>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    Ok(archive.import_warnings())
+}
+
+/// Lists rows of the current archive's data files that couldn't be read,
+/// e.g. because of an unescaped quote, and were skipped while importing
+#[tauri::command]
+pub fn get_import_issues(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::import_issues::ImportIssue>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.import_issues()
+}
+
+#[tauri::command]
+pub fn get_archive_metadata(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<ArchiveMetadata> {
     let base_dir = get_archives_dir(app)?;
-    let archive = Archive::current(&base_dir)?;
+    let archive = Archive::find(&base_dir, archive_id.as_deref())?;
     get_metadata_from_storage(&archive.storage_dir)
 }
 
+#[tauri::command]
+pub fn get_eml(
+    app: tauri::AppHandle,
+    archive_id: Option<String>,
+) -> Result<chuck_core::darwin_core::Eml> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.eml()
+}
+
+/// Citation block for the current archive, or for the occurrences matching
+/// search_params when exporting a subset
+#[tauri::command]
+pub fn get_citation(
+    app: tauri::AppHandle,
+    search_params: Option<SearchParams>,
+    archive_id: Option<String>,
+) -> Result<crate::citation::Citation> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.citation(search_params.unwrap_or_default())
+}
+
+/// License codes of the filtered media, so users can see what they may
+/// reuse before exporting photos
+#[tauri::command]
+pub fn get_media_license_summary(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    archive_id: Option<String>,
+) -> Result<Vec<crate::media_licenses::LicenseCount>> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.media_license_summary(search_params)
+}
+
+#[tauri::command]
+pub fn update_eml(
+    app: tauri::AppHandle,
+    eml: chuck_core::darwin_core::Eml,
+    archive_id: Option<String>,
+) -> Result<chuck_core::darwin_core::Eml> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    archive.update_eml(eml)
+}
+
 #[tauri::command]
 pub fn save_text_file(path: String, content: String) -> Result<()> {
     let p = std::path::PathBuf::from(&path);
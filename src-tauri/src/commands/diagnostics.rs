@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use super::archive::get_archives_dir;
+use super::storage::disk_usage;
+use crate::dwca::{Archive, CoreType};
+use crate::error::Result;
+use crate::logging::{self, LogEntry};
+
+/// What's known about one open archive, for bug reports
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveDiagnostics {
+    pub id: String,
+    pub name: String,
+    pub core_type: CoreType,
+    /// None if the database couldn't be queried
+    pub core_count: Option<usize>,
+    pub database_bytes: u64,
+    /// Tables for the archive's extensions, e.g. multimedia
+    pub extension_tables: Vec<String>,
+    pub import_warnings: usize,
+    pub read_only: bool,
+}
+
+/// Details users can attach to bug reports
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub tauri_version: String,
+    pub os: String,
+    pub os_family: String,
+    pub arch: String,
+    /// Where the JSON log is, if logging started
+    pub log_path: Option<PathBuf>,
+    /// Open archives, most recently opened first
+    pub archives: Vec<ArchiveDiagnostics>,
+    /// Warnings and errors logged since the app started, oldest first
+    pub recent_errors: Vec<LogEntry>,
+}
+
+fn archive_diagnostics(archive: &Archive) -> ArchiveDiagnostics {
+    ArchiveDiagnostics {
+        id: archive.id(),
+        name: archive.name.clone(),
+        core_type: archive.core_type,
+        core_count: archive
+            .core_count()
+            .inspect_err(|e| log::warn!("Failed to count records in {}: {e}", archive.name))
+            .ok(),
+        database_bytes: disk_usage(archive.db_path()),
+        extension_tables: archive
+            .extension_tables()
+            .iter()
+            .map(|(_, table)| table.clone())
+            .collect(),
+        import_warnings: archive.import_warnings().len(),
+        read_only: archive.is_read_only(),
+    }
+}
+
+/// Reports the app version, platform, open archives, and recent errors, for
+/// attaching to bug reports
+#[tauri::command]
+pub fn get_diagnostics(app: tauri::AppHandle) -> Result<Diagnostics> {
+    let archives = Archive::list(&get_archives_dir(app.clone())?)?
+        .iter()
+        .map(archive_diagnostics)
+        .collect();
+    Ok(Diagnostics {
+        app_version: app.package_info().version.to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        os: std::env::consts::OS.to_string(),
+        os_family: std::env::consts::FAMILY.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        log_path: logging::log_path().map(PathBuf::from),
+        archives,
+        recent_errors: logging::recent_errors(),
+    })
+}
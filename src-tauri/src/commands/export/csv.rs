@@ -3,45 +3,96 @@ use std::path::PathBuf;
 
 use serde_json::Value;
 
+use crate::citation::{access_date, record_citation, RECORD_CITATION_COLUMN};
 use crate::commands::archive::get_archives_dir;
+use crate::db::is_filtered;
 use crate::dwca::Archive;
+use crate::enrichment::DERIVED_FIELDS_COLUMN;
 use crate::error::{ChuckError, Result};
 use crate::search_params::SearchParams;
 
-use super::csv_escape;
+use super::{csv_escape, normalize_person_id_fields};
 
 /// Exports filtered occurrences as a CSV file, streaming rows directly to
 /// disk via BufWriter to avoid materialising the full result set in memory.
+/// Subsets get a bibliographicCitation for each record that doesn't have
+/// one, since they're no longer covered by the archive's own citation.
+/// Archives with enrichments get a chuck_derived_fields column naming the
+/// fields of each row that were filled in rather than coming from the
+/// archive.
 pub(super) fn export_csv(
     app: tauri::AppHandle,
     search_params: SearchParams,
     path: String,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    export_csv_inner(get_archives_dir(app)?, search_params, path)
+    export_csv_inner(
+        get_archives_dir(app)?,
+        archive_id.as_deref(),
+        search_params,
+        path,
+    )
 }
 
 pub(super) fn export_csv_inner(
     archives_dir: PathBuf,
+    archive_id: Option<&str>,
     search_params: SearchParams,
     path: String,
 ) -> Result<()> {
-    let archive = Archive::current(&archives_dir)?;
+    let archive = Archive::find(&archives_dir, archive_id)?;
     let dest = PathBuf::from(&path);
     let file = std::fs::File::create(&dest).map_err(|e| ChuckError::FileOpen {
         path: dest.clone(),
         source: e,
     })?;
     let mut writer = BufWriter::new(file);
-    let mut header_written = false;
+    let mut output_columns: Option<Vec<String>> = None;
+    // Archive title and access date for record citations, if exporting a
+    // subset
+    let citing = is_filtered(&search_params)
+        .then(|| (archive.eml().map(|eml| eml.title).unwrap_or_default(), access_date()));
+    let derived_fields = archive.derived_fields()?;
+    let core_id_column = archive.core_id_column.clone();
 
-    archive.for_each_occurrence(search_params, |columns, row| {
-        if !header_written {
-            let header = columns.iter().map(|c| csv_escape(c)).collect::<Vec<_>>().join(",");
+    archive.for_each_occurrence(search_params, |columns, mut row| {
+        normalize_person_id_fields(&mut row);
+        if let Some((title, date)) = &citing {
+            let cited = row
+                .get(RECORD_CITATION_COLUMN)
+                .and_then(Value::as_str)
+                .is_some_and(|citation| !citation.trim().is_empty());
+            if !cited {
+                let citation = record_citation(&row, title, date);
+                row.insert(RECORD_CITATION_COLUMN.to_string(), Value::String(citation));
+            }
+        }
+        if !derived_fields.is_empty() {
+            let fields = match row.get(&core_id_column) {
+                Some(Value::String(core_id)) => derived_fields.get(core_id),
+                Some(core_id) => derived_fields.get(&core_id.to_string()),
+                None => None,
+            };
+            row.insert(
+                DERIVED_FIELDS_COLUMN.to_string(),
+                fields.map_or(Value::Null, |fields| Value::String(fields.clone())),
+            );
+        }
+        if output_columns.is_none() {
+            let mut header_columns = columns.to_vec();
+            if citing.is_some() && !header_columns.iter().any(|c| c == RECORD_CITATION_COLUMN) {
+                header_columns.push(RECORD_CITATION_COLUMN.to_string());
+            }
+            if !derived_fields.is_empty() {
+                header_columns.push(DERIVED_FIELDS_COLUMN.to_string());
+            }
+            let header = header_columns.iter().map(|c| csv_escape(c)).collect::<Vec<_>>().join(",");
             writer.write_all(header.as_bytes())
                 .and_then(|_| writer.write_all(b"\n"))
                 .map_err(|e| ChuckError::FileWrite { path: dest.clone(), source: e })?;
-            header_written = true;
+            output_columns = Some(header_columns);
         }
+        let columns = output_columns.as_deref().unwrap_or_default();
         let fields: Vec<String> = columns
             .iter()
             .map(|col| match row.get(col) {
@@ -107,6 +158,7 @@ mod tests {
 
         export_csv_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -129,6 +181,7 @@ mod tests {
 
         export_csv_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -154,6 +207,7 @@ mod tests {
 
         export_csv_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -165,4 +219,95 @@ mod tests {
         assert!(lines[1].contains(",present,has_value"), "row 1: {}", lines[1]);
         assert!(lines[2].ends_with(",only_a,"), "row 2 b should be empty: {}", lines[2]);
     }
+
+    #[test]
+    fn test_export_csv_cites_records_in_subsets() {
+        let csv = "occurrenceID,scientificName\nabc-1,Homo sapiens\nabc-2,Canis lupus\n";
+        let fixture = setup_archive(csv);
+        let search_params = SearchParams {
+            filters: std::collections::HashMap::from([(
+                "scientificName".to_string(),
+                "Homo sapiens".to_string(),
+            )]),
+            ..SearchParams::default()
+        };
+
+        export_csv_inner(
+            fixture.archives_dir.clone(),
+            None,
+            search_params,
+            fixture.output.to_string_lossy().to_string(),
+        )
+        .unwrap();
+
+        let result = std::fs::read_to_string(&fixture.output).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "occurrenceID,scientificName,bibliographicCitation");
+        assert!(
+            lines[1].starts_with("abc-1,Homo sapiens,Occurrence abc-1. Accessed "),
+            "row 1: {}",
+            lines[1]
+        );
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_export_csv_normalizes_person_ids() {
+        let csv = "occurrenceID,recordedByID\n\
+                   occ-1,0000-0002-1825-0097|wd:Q42\n\
+                   occ-2,not an id\n";
+        let fixture = setup_archive(csv);
+
+        export_csv_inner(
+            fixture.archives_dir.clone(),
+            None,
+            SearchParams::default(),
+            fixture.output.to_string_lossy().to_string(),
+        )
+        .unwrap();
+
+        let result = std::fs::read_to_string(&fixture.output).unwrap();
+        assert!(
+            result.contains(
+                "occ-1,https://orcid.org/0000-0002-1825-0097 | https://www.wikidata.org/wiki/Q42"
+            ),
+            "ids not normalized: {result}"
+        );
+        assert!(result.contains("occ-2,not an id"), "invalid id changed: {result}");
+    }
+
+    #[test]
+    fn test_export_csv_marks_derived_fields() {
+        let csv = "occurrenceID,decimalLatitude,decimalLongitude\nocc-1,2.0,2.0\nocc-2,3.0,3.0\n";
+        let fixture = setup_archive(csv);
+        let conn = duckdb::Connection::open(
+            fixture.archives_dir.join("test.zip-abc123").join("test.db"),
+        )
+        .unwrap();
+        let area = crate::admin_boundaries::AdminArea {
+            country_code: Some("AA".to_string()),
+            state_province: None,
+            county: None,
+        };
+        crate::enrichment::fill_admin_areas(&conn, "occurrenceID", &[("occ-1".to_string(), area)], "test")
+            .unwrap();
+        drop(conn);
+
+        export_csv_inner(
+            fixture.archives_dir.clone(),
+            None,
+            SearchParams::default(),
+            fixture.output.to_string_lossy().to_string(),
+        )
+        .unwrap();
+
+        let result = std::fs::read_to_string(&fixture.output).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines[0].ends_with(",chuck_derived_fields"), "header: {}", lines[0]);
+        let occ_1 = lines.iter().find(|line| line.starts_with("occ-1,")).unwrap();
+        assert!(occ_1.contains(",AA"), "derived value missing: {occ_1}");
+        assert!(occ_1.ends_with(",countryCode"), "derived field not marked: {occ_1}");
+        let occ_2 = lines.iter().find(|line| line.starts_with("occ-2,")).unwrap();
+        assert!(occ_2.ends_with(','), "row without derived values: {occ_2}");
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 
@@ -8,8 +8,13 @@ use roxmltree;
 
 use crate::dwca::{parse_delimiter, parse_meta_xml, Archive};
 use crate::error::{ChuckError, Result};
+use crate::media_licenses::{license_code, MEDIA_CREATOR_COLUMNS, MEDIA_LICENSE_COLUMNS};
+use crate::person_ids::{normalize_person_ids, PERSON_ID_FIELDS};
 use crate::search_params::SearchParams;
 
+/// Credits for exported media, written alongside them
+const MEDIA_ATTRIBUTION_FILENAME: &str = "media_attribution.csv";
+
 // ─── EML transform state ────────────────────────────────────────────────────
 
 #[derive(Default)]
@@ -221,7 +226,7 @@ fn filter_csv(
 }
 
 /// Simple delimited-row parser handling double-quoted fields and escaped quotes.
-fn parse_csv_row(line: &str, delimiter: char) -> Vec<String> {
+pub(super) fn parse_csv_row(line: &str, delimiter: char) -> Vec<String> {
     let mut fields = Vec::new();
     let mut current = String::new();
     let mut in_quotes = false;
@@ -288,6 +293,120 @@ fn extract_nth_field(line: &str, delimiter: char, index: usize) -> Option<String
     }
 }
 
+/// Joins values into a delimited row, quoting any that contain the
+/// delimiter, a quote, or a line break
+fn format_csv_row(fields: &[String], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+/// Puts edited values into a (filtered) CSV/TSV, matching rows by
+/// `id_column` and edited columns by header. Edits to columns the file
+/// doesn't have, like ones added by enrichment, are left out.
+fn apply_edits(
+    csv_bytes: Vec<u8>,
+    delimiter: char,
+    id_column: &str,
+    edits: &HashMap<String, HashMap<String, Option<String>>>,
+) -> Vec<u8> {
+    if edits.is_empty() {
+        return csv_bytes;
+    }
+    let Ok(content) = std::str::from_utf8(&csv_bytes) else {
+        return csv_bytes;
+    };
+    let mut lines = content.lines();
+    let Some(header_line) = lines.next() else {
+        return csv_bytes;
+    };
+    let headers = parse_csv_row(header_line, delimiter);
+    let Some(id_idx) = headers.iter().position(|h| h == id_column) else {
+        return csv_bytes;
+    };
+
+    let mut output = Vec::with_capacity(csv_bytes.len());
+    output.extend_from_slice(header_line.as_bytes());
+    output.push(b'\n');
+    for line in lines {
+        let row_edits = extract_nth_field(line, delimiter, id_idx)
+            .and_then(|id| edits.get(&id));
+        let Some(row_edits) = row_edits else {
+            output.extend_from_slice(line.as_bytes());
+            output.push(b'\n');
+            continue;
+        };
+        let mut fields = parse_csv_row(line, delimiter);
+        for (column, value) in row_edits {
+            let field = headers
+                .iter()
+                .position(|h| h == column)
+                .and_then(|idx| fields.get_mut(idx));
+            if let Some(field) = field {
+                *field = value.clone().unwrap_or_default();
+            }
+        }
+        output.extend_from_slice(format_csv_row(&fields, delimiter).as_bytes());
+        output.push(b'\n');
+    }
+    output
+}
+
+/// Canonicalizes ORCID iDs and Wikidata URIs in the person identifier
+/// columns of a (filtered) CSV/TSV, as CSV and KML exports do. Identifiers
+/// that can't be normalized are written unchanged.
+fn normalize_person_id_columns(csv_bytes: Vec<u8>, delimiter: char) -> Vec<u8> {
+    let Ok(content) = std::str::from_utf8(&csv_bytes) else {
+        return csv_bytes;
+    };
+    let mut lines = content.lines();
+    let Some(header_line) = lines.next() else {
+        return csv_bytes;
+    };
+    let headers = parse_csv_row(header_line, delimiter);
+    let id_indices: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| PERSON_ID_FIELDS.contains(&h.as_str()))
+        .map(|(idx, _)| idx)
+        .collect();
+    if id_indices.is_empty() {
+        return csv_bytes;
+    }
+
+    let mut output = Vec::with_capacity(csv_bytes.len());
+    output.extend_from_slice(header_line.as_bytes());
+    output.push(b'\n');
+    for line in lines {
+        let mut fields = parse_csv_row(line, delimiter);
+        let mut changed = false;
+        for idx in &id_indices {
+            if let Some(field) = fields.get_mut(*idx) {
+                let normalized = normalize_person_ids(field).value;
+                if normalized != *field {
+                    *field = normalized;
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            output.extend_from_slice(format_csv_row(&fields, delimiter).as_bytes());
+        } else {
+            output.extend_from_slice(line.as_bytes());
+        }
+        output.push(b'\n');
+    }
+    output
+}
+
 /// Collects relative photo paths from a (filtered) multimedia CSV/TSV.
 /// Values starting with `http://` or `https://` are skipped.
 fn collect_photo_paths(csv_bytes: &[u8], delimiter: char) -> Vec<String> {
@@ -329,6 +448,74 @@ fn collect_photo_paths(csv_bytes: &[u8], delimiter: char) -> Vec<String> {
     paths
 }
 
+/// Index of the first of `candidates` among `headers`
+fn find_column(headers: &[String], candidates: &[&str]) -> Option<usize> {
+    candidates
+        .iter()
+        .find_map(|candidate| headers.iter().position(|h| h == candidate))
+}
+
+/// Drops rows of a (filtered) multimedia CSV/TSV whose license code, see
+/// `license_code`, isn't in `allowed`. Rows of a file without a license
+/// column have no license.
+fn filter_media_by_license(csv_bytes: Vec<u8>, delimiter: char, allowed: &HashSet<String>) -> Vec<u8> {
+    let Ok(content) = std::str::from_utf8(&csv_bytes) else {
+        return csv_bytes;
+    };
+    let mut lines = content.lines();
+    let Some(header_line) = lines.next() else {
+        return csv_bytes;
+    };
+    let headers = parse_csv_row(header_line.trim_start_matches('\u{FEFF}'), delimiter);
+    let license_idx = find_column(&headers, MEDIA_LICENSE_COLUMNS);
+
+    let mut output = Vec::with_capacity(csv_bytes.len());
+    output.extend_from_slice(header_line.as_bytes());
+    output.push(b'\n');
+    for line in lines {
+        if line.is_empty() {
+            continue;
+        }
+        let license = license_idx
+            .and_then(|idx| extract_nth_field(line, delimiter, idx))
+            .unwrap_or_default();
+        if allowed.contains(&license_code(&license)) {
+            output.extend_from_slice(line.as_bytes());
+            output.push(b'\n');
+        }
+    }
+    output
+}
+
+/// Rows for the attribution manifest from a (filtered) multimedia CSV/TSV:
+/// the media's identifier, creator, license, and page
+fn media_attributions(csv_bytes: &[u8], delimiter: char) -> Vec<Vec<String>> {
+    let Ok(content) = std::str::from_utf8(csv_bytes) else {
+        return Vec::new();
+    };
+    let mut lines = content.lines();
+    let Some(header_line) = lines.next() else {
+        return Vec::new();
+    };
+    let headers = parse_csv_row(header_line.trim_start_matches('\u{FEFF}'), delimiter);
+    let indexes = [
+        find_column(&headers, &["identifier", "accessURI"]),
+        find_column(&headers, MEDIA_CREATOR_COLUMNS),
+        find_column(&headers, MEDIA_LICENSE_COLUMNS),
+        find_column(&headers, &["references", "attributionLinkURL"]),
+    ];
+    lines
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields = parse_csv_row(line, delimiter);
+            indexes
+                .iter()
+                .map(|idx| idx.and_then(|idx| fields.get(idx)).cloned().unwrap_or_default())
+                .collect()
+        })
+        .collect()
+}
+
 /// Modifies an EML XML document to reflect applied filters.
 /// Appends a `<para>` to `<abstract>`, updates `<boundingCoordinates>` if bbox
 /// present, and adds `<taxonomicClassification>` if taxonomic filters present.
@@ -336,12 +523,9 @@ fn modify_eml(eml: &str, params: &SearchParams, count: usize) -> String {
     let filter_desc = build_filter_description(params, count);
 
     if eml.trim().is_empty() {
-        let escaped = quick_xml::escape::escape(&filter_desc);
-        return format!(
-            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
-             <eml:eml xmlns:eml=\"https://eml.ecoinformatics.org/eml-2.2.0\">\
-             <dataset><abstract><para>{escaped}</para></abstract></dataset></eml:eml>"
-        );
+        let mut eml = chuck_core::darwin_core::Eml::default();
+        eml.abstract_paragraphs = vec![filter_desc];
+        return eml.to_xml();
     }
 
     let has_bbox = params.nelat.is_some()
@@ -642,20 +826,48 @@ fn filter_csv_by_index(
     Ok(output)
 }
 
+/// Writes filtered occurrences and their extension rows to a DarwinCore
+/// Archive. Data files are copied from the original with hand edits applied,
+/// so values derived by enrichments are left out. With `ipt`, data files, meta.xml, and eml.xml are rewritten the
+/// way an IPT expects (see ipt.rs) and the package is validated. With
+/// `allowed_licenses`, media rows and files with other license codes are
+/// left out. Media that are exported are credited in a manifest. With
+/// `omit_excluded`, occurrences excluded with exclude_occurrences are left
+/// out too.
 pub(super) fn export_dwca_inner(
     archives_dir: PathBuf,
+    archive_id: Option<&str>,
     search_params: SearchParams,
     path: String,
+    ipt: bool,
+    allowed_licenses: Option<&HashSet<String>>,
+    omit_excluded: bool,
 ) -> Result<()> {
-    let archive = Archive::current(&archives_dir)?;
+    let archive = Archive::find(&archives_dir, archive_id)?;
 
     // Get IDs of all matching occurrences
-    let matching_ids = archive.query_matching_ids(search_params.clone())?;
+    let mut matching_ids = archive.query_matching_ids(search_params.clone())?;
+    if omit_excluded {
+        let excluded = archive.excluded_ids()?;
+        matching_ids.retain(|id| !excluded.contains(id));
+    }
 
     // Parse meta.xml for source file paths and delimiter
     let meta = parse_meta_xml(&archive.storage_dir)?;
-    let core_files = meta.core_files;
-    let core_delimiter = meta.core_delimiter;
+    // In an Event core archive the matching occurrences are in the Occurrence
+    // extension, which is filtered with the other extensions below, and the
+    // event files are kept whole
+    let (core_files, occurrence_ext_location, core_file_delimiter) = match meta.events {
+        Some(events) => (
+            events.files,
+            meta.core_files.into_iter().next(),
+            events.format.delimiter(),
+        ),
+        None => (meta.core_files, None, meta.core_format.delimiter()),
+    };
+    let core_delimiter = meta.core_format.delimiter();
+    // Hand edits replace the archive's values in the occurrences written out
+    let edits = crate::edits::edited_values(archive.edits(None)?);
 
     // Parse ALL extension entries (including types not loaded into DuckDB)
     let all_exts = parse_all_extensions_for_export(&archive.storage_dir)?;
@@ -667,14 +879,22 @@ pub(super) fn export_dwca_inner(
     } else {
         String::new()
     };
-    let modified_eml = modify_eml(&raw_eml, &search_params, matching_ids.len());
+    let mut modified_eml = modify_eml(&raw_eml, &search_params, matching_ids.len());
+    if ipt {
+        modified_eml = super::ipt::ipt_eml(&modified_eml);
+    }
 
     // Read meta.xml verbatim
     let meta_path = archive.storage_dir.join("meta.xml");
-    let meta_xml = std::fs::read(&meta_path).map_err(|e| ChuckError::FileRead {
+    let mut meta_xml = std::fs::read(&meta_path).map_err(|e| ChuckError::FileRead {
         path: meta_path.clone(),
         source: e,
     })?;
+    if ipt {
+        meta_xml = super::ipt::ipt_meta_xml(&String::from_utf8_lossy(&meta_xml))
+            .map_err(|e| ChuckError::XmlParse { path: meta_path.clone(), source: e })?
+            .into_bytes();
+    }
 
     // Filter core CSV(s) and extension CSVs; collect embedded photo paths
     let deflated_opts = zip::write::FileOptions::<()>::default()
@@ -697,7 +917,7 @@ pub(super) fn export_dwca_inner(
         source: e,
     })?;
 
-    // meta.xml (verbatim)
+    // meta.xml (verbatim unless packaging for an IPT)
     zip.start_file("meta.xml", deflated_opts)
         .map_err(ChuckError::ArchiveExtraction)?;
     zip.write_all(&meta_xml).map_err(|e| ChuckError::FileWrite {
@@ -720,11 +940,28 @@ pub(super) fn export_dwca_inner(
         // Replace backslashes (Windows) with forward slashes for ZIP compatibility
         let rel = rel.replace('\\', "/");
         let filtered =
-            if core_path.exists() {
-                filter_csv(core_path, core_delimiter, &archive.core_id_column, &matching_ids)?
-            } else {
+            if !core_path.exists() {
                 Vec::new()
+            } else if occurrence_ext_location.is_some() {
+                std::fs::read(core_path).map_err(|e| ChuckError::FileRead {
+                    path: core_path.clone(),
+                    source: e,
+                })?
+            } else {
+                let filtered =
+                    filter_csv(core_path, core_delimiter, &archive.core_id_column, &matching_ids)?;
+                let edited =
+                    apply_edits(filtered, core_delimiter, &archive.core_id_column, &edits);
+                normalize_person_id_columns(edited, core_delimiter)
             };
+        let (rel, filtered) = if ipt {
+            (
+                super::ipt::ipt_location(&rel),
+                super::ipt::ipt_data_file(&filtered, core_file_delimiter),
+            )
+        } else {
+            (rel, filtered)
+        };
         zip.start_file(&rel, deflated_opts)
             .map_err(ChuckError::ArchiveExtraction)?;
         zip.write_all(&filtered).map_err(|e| ChuckError::FileWrite {
@@ -735,6 +972,7 @@ pub(super) fn export_dwca_inner(
 
     // All extension CSVs (every rowType) + collect photo paths from multimedia
     let mut photo_paths: Vec<String> = Vec::new();
+    let mut attributions: Vec<Vec<String>> = Vec::new();
     for ext in &all_exts {
         let rel = ext
             .location
@@ -748,7 +986,17 @@ pub(super) fn export_dwca_inner(
                     .to_string()
             });
         let rel = rel.replace('\\', "/");
-        let filtered = if ext.location.exists() {
+        let filtered = if occurrence_ext_location.as_ref() == Some(&ext.location) {
+            if ext.location.exists() {
+                let filtered =
+                    filter_csv(&ext.location, ext.delimiter, &archive.core_id_column, &matching_ids)?;
+                let edited =
+                    apply_edits(filtered, ext.delimiter, &archive.core_id_column, &edits);
+                normalize_person_id_columns(edited, ext.delimiter)
+            } else {
+                Vec::new()
+            }
+        } else if ext.location.exists() {
             // Prefer filtering by column name (handles old-format archives with
             // a separate blank coreid column). Fall back to index only when the
             // column name isn't present in the header — any other error (I/O,
@@ -767,15 +1015,26 @@ pub(super) fn export_dwca_inner(
             Vec::new()
         };
 
-        // Collect photo paths from multimedia/audiovisual extensions
+        // Collect photo paths and credits from multimedia/audiovisual
+        // extensions, leaving out media with licenses that weren't allowed
         use chuck_core::DwcaExtension;
-        if chuck_core::DwcaExtension::from_row_type(&ext.row_type)
+        let is_media = chuck_core::DwcaExtension::from_row_type(&ext.row_type)
             .map(|e| matches!(e, DwcaExtension::SimpleMultimedia | DwcaExtension::Audiovisual))
-            .unwrap_or(false)
-        {
+            .unwrap_or(false);
+        let filtered = match allowed_licenses {
+            Some(allowed) if is_media => filter_media_by_license(filtered, ext.delimiter, allowed),
+            _ => filtered,
+        };
+        if is_media {
             let mut photos = collect_photo_paths(&filtered, ext.delimiter);
             photo_paths.append(&mut photos);
+            attributions.append(&mut media_attributions(&filtered, ext.delimiter));
         }
+        let (rel, filtered) = if ipt {
+            (super::ipt::ipt_location(&rel), super::ipt::ipt_data_file(&filtered, ext.delimiter))
+        } else {
+            (rel, filtered)
+        };
 
         zip.start_file(&rel, deflated_opts)
             .map_err(ChuckError::ArchiveExtraction)?;
@@ -785,6 +1044,22 @@ pub(super) fn export_dwca_inner(
         })?;
     }
 
+    if !attributions.is_empty() {
+        let header = ["identifier", "creator", "license", "references"].map(String::from);
+        let mut manifest = format_csv_row(&header, ',');
+        manifest.push('\n');
+        for row in &attributions {
+            manifest.push_str(&format_csv_row(row, ','));
+            manifest.push('\n');
+        }
+        zip.start_file(MEDIA_ATTRIBUTION_FILENAME, deflated_opts)
+            .map_err(ChuckError::ArchiveExtraction)?;
+        zip.write_all(manifest.as_bytes()).map_err(|e| ChuckError::FileWrite {
+            path: dest.clone(),
+            source: e,
+        })?;
+    }
+
     // Embedded photos from archive.zip
     let archive_zip_path = archive.storage_dir.join("archive.zip");
     if archive_zip_path.exists() && !photo_paths.is_empty() {
@@ -813,6 +1088,9 @@ pub(super) fn export_dwca_inner(
     }
 
     zip.finish().map_err(ChuckError::ArchiveExtraction)?;
+    if ipt {
+        super::ipt::validate_package(&dest)?;
+    }
     Ok(())
 }
 
@@ -918,6 +1196,34 @@ mod tests {
         assert!(!paths.iter().any(|p| p.starts_with("http")));
     }
 
+    // ── media licenses ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_filter_media_by_license_and_attributions() {
+        let csv = b"occurrenceID,identifier,creator,license\n\
+              1,media/1.jpg,\"Ueda, Ken-ichi\",http://creativecommons.org/licenses/by-nc/4.0/\n\
+              2,media/2.jpg,Someone,All rights reserved\n\
+              3,media/3.jpg,,\n"
+            .to_vec();
+        let allowed: HashSet<String> = ["CC-BY-NC".to_string(), "none".to_string()].into();
+
+        let filtered = filter_media_by_license(csv, ',', &allowed);
+        let paths = collect_photo_paths(&filtered, ',');
+        assert_eq!(paths, vec!["media/1.jpg".to_string(), "media/3.jpg".to_string()]);
+
+        let attributions = media_attributions(&filtered, ',');
+        assert_eq!(
+            attributions[0],
+            vec![
+                "media/1.jpg".to_string(),
+                "Ueda, Ken-ichi".to_string(),
+                "http://creativecommons.org/licenses/by-nc/4.0/".to_string(),
+                String::new(),
+            ]
+        );
+        assert_eq!(attributions.len(), 2);
+    }
+
     // ── modify_eml ────────────────────────────────────────────────────────────
 
     #[test]
@@ -1078,12 +1384,28 @@ mod tests {
         fn run(&self, search_params: SearchParams) {
             export_dwca_inner(
                 self.base_dir.clone(),
+                None,
                 search_params,
                 self.output_path.to_string_lossy().to_string(),
+                false,
+                None,
+                false,
             )
             .unwrap();
         }
 
+        fn run_for_ipt(&self, search_params: SearchParams) -> Result<()> {
+            export_dwca_inner(
+                self.base_dir.clone(),
+                None,
+                search_params,
+                self.output_path.to_string_lossy().to_string(),
+                true,
+                None,
+                false,
+            )
+        }
+
         fn zip_entry_names(&self) -> Vec<String> {
             let file = std::fs::File::open(&self.output_path).unwrap();
             let mut zip = zip::ZipArchive::new(file).unwrap();
@@ -1117,6 +1439,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_export_dwca_omits_excluded_occurrences() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+  </core>
+</archive>"#;
+        let occurrence_csv =
+            b"occurrenceID,scientificName\nobs1,Quercus agrifolia\nobs2,Pinus ponderosa\n";
+        let fixture = ExportDwcaFixture::new(meta_xml, occurrence_csv);
+        let db_path = fixture.base_dir.join("test_archive.zip-abc123").join("test_archive.db");
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        let params: Vec<Box<dyn duckdb::ToSql>> = vec![Box::new("obs2".to_string())];
+        crate::exclusions::exclude_occurrences(&conn, "occurrenceID", " WHERE occurrenceID = ?", &params, None)
+            .unwrap();
+        drop(conn);
+
+        export_dwca_inner(
+            fixture.base_dir.clone(),
+            None,
+            SearchParams::default(),
+            fixture.output_path.to_string_lossy().to_string(),
+            false,
+            None,
+            true,
+        )
+        .unwrap();
+
+        let file = std::fs::File::open(&fixture.output_path).unwrap();
+        let mut zip = zip::ZipArchive::new(file).unwrap();
+        let mut occ = zip.by_name("occurrence.csv").unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut occ, &mut content).unwrap();
+        assert!(content.contains("obs1"), "should keep included rows: {content}");
+        assert!(!content.contains("obs2"), "should omit excluded rows: {content}");
+    }
+
     #[test]
     fn test_export_dwca_handles_tab_separated_files() {
         use crate::db::Database;
@@ -1154,8 +1517,12 @@ mod tests {
         let output_path = base_dir.join("output.zip");
         export_dwca_inner(
             base_dir.clone(),
+            None,
             SearchParams::default(),
             output_path.to_string_lossy().to_string(),
+            false,
+            None,
+            false,
         )
         .unwrap();
 
@@ -1227,8 +1594,12 @@ mod tests {
         let output_path = base_dir.join("output.zip");
         export_dwca_inner(
             base_dir.clone(),
+            None,
             SearchParams::default(),
             output_path.to_string_lossy().to_string(),
+            false,
+            None,
+            false,
         )
         .unwrap();
 
@@ -1273,6 +1644,132 @@ mod tests {
         assert!(!content.contains("Pinus ponderosa"), "should exclude non-matching row");
     }
 
+    #[test]
+    fn test_export_dwca_includes_edits() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/recordedBy"/>
+  </core>
+</archive>"#;
+        let occurrence_csv = b"occurrenceID,recordedBy\nobs1,Jepsen\nobs2,Hall\n";
+        let fixture = ExportDwcaFixture::new(meta_xml, occurrence_csv);
+        let archive = Archive::current(&fixture.base_dir).unwrap();
+        archive.set_read_only(false).unwrap();
+        archive
+            .update_occurrence_field("obs1", "recordedBy", Some("Jepson, W. L."))
+            .unwrap();
+
+        fixture.run(SearchParams::default());
+
+        let file = std::fs::File::open(&fixture.output_path).unwrap();
+        let mut zip = zip::ZipArchive::new(file).unwrap();
+        let mut occ = zip.by_name("occurrence.csv").unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut occ, &mut content).unwrap();
+
+        assert_eq!(content, "occurrenceID,recordedBy\nobs1,\"Jepson, W. L.\"\nobs2,Hall\n");
+    }
+
+    #[test]
+    fn test_export_dwca_normalizes_person_ids() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/recordedBy"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/recordedByID"/>
+  </core>
+</archive>"#;
+        let occurrence_csv = b"occurrenceID,recordedBy,recordedByID\n\
+obs1,Jepson,0000-0002-1825-0097|wd:Q42\n\
+obs2,Hall,W. L. Jepson\n";
+        let fixture = ExportDwcaFixture::new(meta_xml, occurrence_csv);
+
+        fixture.run(SearchParams::default());
+
+        let file = std::fs::File::open(&fixture.output_path).unwrap();
+        let mut zip = zip::ZipArchive::new(file).unwrap();
+        let mut occ = zip.by_name("occurrence.csv").unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut occ, &mut content).unwrap();
+
+        assert_eq!(
+            content,
+            "occurrenceID,recordedBy,recordedByID\n\
+             obs1,Jepson,https://orcid.org/0000-0002-1825-0097 | https://www.wikidata.org/wiki/Q42\n\
+             obs2,Hall,W. L. Jepson\n"
+        );
+    }
+
+    #[test]
+    fn test_export_dwca_for_ipt() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="1" term="scientificName"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+  </core>
+</archive>"#;
+        let occurrence_csv =
+            b"occurrenceID,scientificName\r\nobs1,\"Quercus\tagrifolia\"\r\nobs2,Pinus ponderosa\r\n";
+        let fixture = ExportDwcaFixture::new(meta_xml, occurrence_csv);
+
+        fixture.run_for_ipt(SearchParams::default()).unwrap();
+
+        let names = fixture.zip_entry_names();
+        assert!(names.contains(&"occurrence.txt".to_string()), "got: {names:?}");
+        assert!(!names.contains(&"occurrence.csv".to_string()));
+        let file = std::fs::File::open(&fixture.output_path).unwrap();
+        let mut zip = zip::ZipArchive::new(file).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut zip.by_name(name).unwrap(), &mut content).unwrap();
+            content
+        };
+        assert_eq!(
+            read("occurrence.txt"),
+            "occurrenceID\tscientificName\nobs1\tQuercus agrifolia\nobs2\tPinus ponderosa\n"
+        );
+        let meta = read("meta.xml");
+        assert!(meta.contains(r#"fieldsTerminatedBy="\t""#));
+        assert!(
+            meta.find("terms/occurrenceID").unwrap()
+                < meta.find("http://rs.tdwg.org/dwc/terms/scientificName").unwrap()
+        );
+        let eml = chuck_core::darwin_core::Eml::parse(&read("eml.xml")).unwrap();
+        assert!(!eml.creators.is_empty());
+        assert!(!eml.metadata_providers.is_empty());
+        assert!(!eml.contacts.is_empty());
+    }
+
+    #[test]
+    fn test_export_dwca_for_ipt_rejects_invalid_packages() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/basisOfRecord"/>
+  </core>
+</archive>"#;
+        let occurrence_csv = b"occurrenceID,basisOfRecord\nobs1,Photograph\n";
+        let fixture = ExportDwcaFixture::new(meta_xml, occurrence_csv);
+
+        let error = fixture.run_for_ipt(SearchParams::default()).unwrap_err();
+
+        assert!(error.to_string().contains("basisOfRecord Photograph"), "got: {error}");
+        assert!(!fixture.output_path.exists());
+    }
+
     #[test]
     fn test_export_dwca_identification_csv_contains_data_rows() {
         // Reproduces bug: identification.csv in export was empty (header only).
@@ -1337,8 +1834,12 @@ mod tests {
         let output_path = base_dir.join("output.zip");
         export_dwca_inner(
             base_dir.clone(),
+            None,
             SearchParams::default(),
             output_path.to_string_lossy().to_string(),
+            false,
+            None,
+            false,
         ).unwrap();
 
         let file = std::fs::File::open(&output_path).unwrap();
@@ -1422,8 +1923,12 @@ mod tests {
         let output_path = base_dir.join("output.zip");
         export_dwca_inner(
             base_dir.clone(),
+            None,
             params,
             output_path.to_string_lossy().to_string(),
+            false,
+            None,
+            false,
         ).unwrap();
 
         let file = std::fs::File::open(&output_path).unwrap();
@@ -1511,8 +2016,12 @@ mod tests {
         let output_path = base_dir.join("output.zip");
         export_dwca_inner(
             base_dir.clone(),
+            None,
             SearchParams::default(),
             output_path.to_string_lossy().to_string(),
+            false,
+            None,
+            false,
         ).unwrap();
 
         let file = std::fs::File::open(&output_path).unwrap();
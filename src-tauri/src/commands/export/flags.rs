@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use crate::commands::archive::get_archives_dir;
+use crate::dwca::Archive;
+use crate::error::Result;
+
+/// Exports every flag in the current archive, with notes, to a CSV that can
+/// be sent to the data provider
+pub(super) fn export_flags_csv(
+    app: tauri::AppHandle,
+    path: String,
+    archive_id: Option<String>,
+) -> Result<()> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    let count = archive.export_flags_csv(&PathBuf::from(&path))?;
+    log::info!(
+        format = "flags_csv", count = count, path = path.as_str();
+        "Exported {count} flags to {path}"
+    );
+    Ok(())
+}
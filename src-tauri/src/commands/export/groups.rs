@@ -30,9 +30,10 @@ pub(super) fn export_groups_csv(
     search_params: SearchParams,
     field_name: String,
     path: String,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    let archive = Archive::current(&get_archives_dir(app)?)?;
-    let rows = archive.aggregate_by_field(&field_name, &search_params, None)?;
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    let rows = archive.aggregate_by_field(&field_name, &search_params, None, &[], None)?;
     let csv = build_groups_csv(&field_name, &rows);
     let dest = PathBuf::from(&path);
     std::fs::write(&dest, csv).map_err(|source| ChuckError::FileWrite {
@@ -46,7 +47,7 @@ mod tests {
     use super::*;
 
     fn make_agg(value: Option<&str>, count: i64) -> AggregationResult {
-        AggregationResult { value: value.map(|s| s.to_string()), count, photo_url: None }
+        AggregationResult { value: value.map(|s| s.to_string()), count, ..Default::default() }
     }
 
     #[test]
@@ -0,0 +1,266 @@
+//! Packaging exported archives the way an IPT writes them: tab-delimited,
+//! unquoted UTF-8 .txt files, meta.xml with fields in column order and full
+//! term URIs, and eml.xml with what the GBIF EML profile requires
+
+use std::fmt::Write;
+use std::path::Path;
+
+use chuck_core::archive_validator::{validate_archive, Severity};
+use chuck_core::darwin_core::Eml;
+
+use super::dwca::parse_csv_row;
+use crate::error::{ChuckError, Result};
+
+const DWC_TERMS: &str = "http://rs.tdwg.org/dwc/terms/";
+const DC_TERMS: &str = "http://purl.org/dc/terms/";
+
+/// Dublin Core terms used in occurrence data, which are written without a
+/// namespace in some archives just like Darwin Core terms
+const DC_TERM_NAMES: &[&str] = &[
+    "accessRights",
+    "available",
+    "bibliographicCitation",
+    "contributor",
+    "created",
+    "creator",
+    "description",
+    "format",
+    "identifier",
+    "language",
+    "license",
+    "modified",
+    "publisher",
+    "references",
+    "rights",
+    "rightsHolder",
+    "source",
+    "title",
+    "type",
+];
+
+/// Issues listed in the error when a package fails validation
+const MAX_REPORTED_ISSUES: usize = 5;
+
+/// Where a data file goes in the package, e.g. occurrence.txt for
+/// occurrence.csv, since its contents become tab-delimited
+pub(super) fn ipt_location(location: &str) -> String {
+    let lower = location.to_lowercase();
+    if lower.ends_with(".csv") || lower.ends_with(".tsv") {
+        format!("{}.txt", &location[..location.len() - 4])
+    } else {
+        location.to_string()
+    }
+}
+
+/// Rewrites a delimited data file as tab-delimited UTF-8 without quotes or a
+/// byte order mark. Tabs and line breaks in values, which an unquoted file
+/// can't hold, become spaces, and CRLF line endings become LF.
+pub(super) fn ipt_data_file(bytes: &[u8], delimiter: char) -> Vec<u8> {
+    let content = String::from_utf8_lossy(bytes);
+    let content = content.strip_prefix('\u{FEFF}').unwrap_or(&content);
+    let mut output = Vec::with_capacity(bytes.len());
+    for line in content.lines().filter(|line| !line.is_empty()) {
+        let fields: Vec<String> = parse_csv_row(line, delimiter)
+            .into_iter()
+            .map(|field| field.replace(['\t', '\r', '\n'], " "))
+            .collect();
+        output.extend_from_slice(fields.join("\t").as_bytes());
+        output.push(b'\n');
+    }
+    output
+}
+
+/// Full URI for a meta.xml term, which some archives write as a bare or
+/// prefixed name like scientificName or dcterms:license
+fn term_uri(term: &str) -> String {
+    if term.contains("://") {
+        return term.to_string();
+    }
+    let (prefix, name) = term.split_once(':').unwrap_or(("", term));
+    let namespace = match prefix {
+        "dwc" => DWC_TERMS,
+        "dc" | "dcterms" => DC_TERMS,
+        "ac" => "http://rs.tdwg.org/ac/terms/",
+        "gbif" => "http://rs.gbif.org/terms/1.0/",
+        _ if DC_TERM_NAMES.contains(&name) => DC_TERMS,
+        _ => DWC_TERMS,
+    };
+    format!("{namespace}{name}")
+}
+
+/// meta.xml for the package: data files renamed by ipt_location and
+/// declared as tab-delimited UTF-8, fields in column order followed by
+/// constant ones, and every term a full URI. Files without a location are
+/// left out since the export doesn't write them.
+pub(super) fn ipt_meta_xml(meta_xml: &str) -> std::result::Result<String, roxmltree::Error> {
+    let doc = roxmltree::Document::parse(meta_xml)?;
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <archive xmlns=\"http://rs.tdwg.org/dwc/text/\" metadata=\"eml.xml\">",
+    );
+    let files = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("core") || n.has_tag_name("extension"));
+    for node in files {
+        let locations: Vec<&str> = node
+            .descendants()
+            .filter(|n| n.has_tag_name("location"))
+            .filter_map(|n| n.text())
+            .map(str::trim)
+            .collect();
+        if locations.is_empty() {
+            continue;
+        }
+        let tag = node.tag_name().name();
+        let id_tag = if tag == "core" { "id" } else { "coreid" };
+        write!(
+            xml,
+            "\n  <{tag} encoding=\"UTF-8\" fieldsTerminatedBy=\"\\t\" linesTerminatedBy=\"\\n\" \
+             fieldsEnclosedBy=\"\" ignoreHeaderLines=\"{}\" rowType=\"{}\">\n    <files>",
+            escape(node.attribute("ignoreHeaderLines").unwrap_or("0")),
+            escape(node.attribute("rowType").unwrap_or_default()),
+        )
+        .unwrap();
+        for location in locations {
+            write!(xml, "\n      <location>{}</location>", escape(&ipt_location(location))).unwrap();
+        }
+        xml.push_str("\n    </files>");
+        if let Some(index) = node
+            .descendants()
+            .find(|n| n.has_tag_name(id_tag))
+            .and_then(|n| n.attribute("index"))
+        {
+            write!(xml, "\n    <{id_tag} index=\"{}\"/>", escape(index)).unwrap();
+        }
+
+        let mut fields: Vec<_> = node
+            .descendants()
+            .filter(|n| n.has_tag_name("field"))
+            .filter_map(|field| {
+                let term = field.attribute("term")?;
+                let index = field.attribute("index").and_then(|i| i.parse::<usize>().ok());
+                Some((index, term, field))
+            })
+            .collect();
+        // Constant fields have no index and go last
+        fields.sort_by_key(|(index, _, _)| index.unwrap_or(usize::MAX));
+        for (index, term, field) in fields {
+            xml.push_str("\n    <field");
+            if let Some(index) = index {
+                write!(xml, " index=\"{index}\"").unwrap();
+            }
+            write!(xml, " term=\"{}\"", escape(&term_uri(term))).unwrap();
+            for attribute in ["default", "vocabulary", "delimitedBy"] {
+                if let Some(value) = field.attribute(attribute) {
+                    write!(xml, " {attribute}=\"{}\"", escape(value)).unwrap();
+                }
+            }
+            xml.push_str("/>");
+        }
+        write!(xml, "\n  </{tag}>").unwrap();
+    }
+    xml.push_str("\n</archive>\n");
+    Ok(xml)
+}
+
+/// eml.xml with any elements the GBIF EML profile requires filled in.
+/// Documents that can't be parsed are replaced with Chuck's defaults.
+pub(super) fn ipt_eml(eml_xml: &str) -> String {
+    let mut eml = Eml::parse(eml_xml).unwrap_or_default();
+    eml.fill_gbif_required_elements();
+    if eml.license.is_none() {
+        log::warn!("eml.xml has no license, which an IPT will ask for before publishing");
+    }
+    eml.to_xml()
+}
+
+/// Checks a finished package the way an IPT would, logging every issue.
+/// Packages it wouldn't accept are deleted and the first few errors
+/// returned.
+pub(super) fn validate_package(path: &Path) -> Result<()> {
+    let report = validate_archive(path).map_err(|e| ChuckError::IptPackage(e.to_string()))?;
+    for issue in &report.issues {
+        match issue.severity {
+            Severity::Error => log::error!("{issue}"),
+            Severity::Warning => log::warn!("{issue}"),
+        }
+    }
+    if report.is_valid() {
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(path);
+    let mut errors: Vec<String> = report
+        .issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .take(MAX_REPORTED_ISSUES)
+        .map(ToString::to_string)
+        .collect();
+    if report.error_count() > MAX_REPORTED_ISSUES {
+        errors.push(format!("{} more", report.error_count() - MAX_REPORTED_ISSUES));
+    }
+    Err(ChuckError::IptPackage(errors.join("; ")))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipt_data_file() {
+        let csv = "\u{FEFF}occurrenceID,remarks\r\n1,\"Under an oak, in\tshade\"\r\n2,\"Said \"\"hi\"\"\"\r\n";
+
+        let output = ipt_data_file(csv.as_bytes(), ',');
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "occurrenceID\tremarks\n1\tUnder an oak, in shade\n2\tSaid \"hi\"\n"
+        );
+    }
+
+    #[test]
+    fn test_ipt_meta_xml() {
+        let meta_xml = r#"<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="," ignoreHeaderLines="1">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field term="http://rs.tdwg.org/dwc/terms/basisOfRecord" default="HumanObservation"/>
+    <field index="2" term="dcterms:license"/>
+    <field index="1" term="scientificName"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+  </core>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Multimedia">
+    <coreid index="0"/>
+  </extension>
+</archive>"#;
+
+        let xml = ipt_meta_xml(meta_xml).unwrap();
+
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let core = doc.descendants().find(|n| n.has_tag_name("core")).unwrap();
+        assert_eq!(core.attribute("fieldsTerminatedBy"), Some(r"\t"));
+        assert_eq!(core.attribute("fieldsEnclosedBy"), Some(""));
+        assert_eq!(core.attribute("encoding"), Some("UTF-8"));
+        let location = doc.descendants().find(|n| n.has_tag_name("location")).unwrap();
+        assert_eq!(location.text(), Some("occurrence.txt"));
+        let terms: Vec<_> = core
+            .children()
+            .filter(|n| n.has_tag_name("field"))
+            .map(|n| n.attribute("term").unwrap())
+            .collect();
+        assert_eq!(terms, vec![
+            "http://rs.tdwg.org/dwc/terms/occurrenceID",
+            "http://rs.tdwg.org/dwc/terms/scientificName",
+            "http://purl.org/dc/terms/license",
+            "http://rs.tdwg.org/dwc/terms/basisOfRecord",
+        ]);
+        assert!(!doc.descendants().any(|n| n.has_tag_name("extension")));
+    }
+}
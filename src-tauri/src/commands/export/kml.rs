@@ -67,16 +67,23 @@ pub(super) fn export_kml(
     app: tauri::AppHandle,
     search_params: SearchParams,
     path: String,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    export_kml_inner(get_archives_dir(app)?, search_params, path)
+    export_kml_inner(
+        get_archives_dir(app)?,
+        archive_id.as_deref(),
+        search_params,
+        path,
+    )
 }
 
 pub(super) fn export_kml_inner(
     archives_dir: PathBuf,
+    archive_id: Option<&str>,
     search_params: SearchParams,
     path: String,
 ) -> Result<()> {
-    let archive = Archive::current(&archives_dir)?;
+    let archive = Archive::find(&archives_dir, archive_id)?;
     let core_id_column = archive.core_id_column.clone();
     let dest = PathBuf::from(&path);
     let file = std::fs::File::create(&dest).map_err(|e| ChuckError::FileOpen {
@@ -93,7 +100,8 @@ pub(super) fn export_kml_inner(
         )
         .map_err(|e| ChuckError::FileWrite { path: dest.clone(), source: e })?;
 
-    archive.for_each_occurrence(search_params, |_columns, row| {
+    archive.for_each_occurrence(search_params, |_columns, mut row| {
+        super::normalize_person_id_fields(&mut row);
         if let Some(placemark) = format_placemark(&row, &core_id_column) {
             writer
                 .write_all(placemark.as_bytes())
@@ -158,6 +166,7 @@ mod tests {
 
         export_kml_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -179,6 +188,7 @@ mod tests {
 
         export_kml_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -195,6 +205,7 @@ mod tests {
 
         export_kml_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -212,6 +223,7 @@ mod tests {
 
         export_kml_inner(
             fixture.archives_dir.clone(),
+            None,
             SearchParams::default(),
             fixture.output.to_string_lossy().to_string(),
         )
@@ -1,10 +1,20 @@
 mod csv;
 mod dwca;
+mod flags;
 mod groups;
+mod ipt;
 mod kml;
+mod parquet;
+mod pmtiles;
+mod sqlite;
+
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
 
 use crate::commands::archive::get_archives_dir;
 use crate::error::Result;
+use crate::person_ids::{normalize_person_ids, PERSON_ID_FIELDS};
 use crate::search_params::SearchParams;
 
 /// Escapes a CSV field value per RFC 4180
@@ -16,13 +26,36 @@ fn csv_escape(s: &str) -> String {
     }
 }
 
+/// Canonicalizes ORCID iDs and Wikidata URIs in exported rows. The archive
+/// itself is left as it is, and identifiers that can't be normalized are
+/// written unchanged.
+fn normalize_person_id_fields(row: &mut Map<String, Value>) {
+    for field in PERSON_ID_FIELDS {
+        if let Some(Value::String(value)) = row.get_mut(*field) {
+            *value = normalize_person_ids(value).value;
+        }
+    }
+}
+
+/// Writes filtered occurrences as they are in the database, including
+/// enriched values marked in chuck_derived_fields, to a CSV file
+pub(crate) fn export_enriched_csv(
+    archives_dir: std::path::PathBuf,
+    archive_id: Option<&str>,
+    search_params: SearchParams,
+    path: String,
+) -> Result<()> {
+    csv::export_csv_inner(archives_dir, archive_id, search_params, path)
+}
+
 #[tauri::command]
 pub fn export_csv(
     app: tauri::AppHandle,
     search_params: SearchParams,
     path: String,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    csv::export_csv(app, search_params, path)
+    csv::export_csv(app, search_params, path, archive_id)
 }
 
 #[tauri::command]
@@ -30,8 +63,56 @@ pub fn export_kml(
     app: tauri::AppHandle,
     search_params: SearchParams,
     path: String,
+    archive_id: Option<String>,
+) -> Result<()> {
+    kml::export_kml(app, search_params, path, archive_id)
+}
+
+/// Exports filtered occurrences as a PMTiles vector tileset rendered from zoom
+/// 0 through max_zoom
+#[tauri::command]
+pub fn export_pmtiles(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    path: String,
+    max_zoom: Option<u8>,
+    archive_id: Option<String>,
+) -> Result<()> {
+    pmtiles::export_pmtiles(app, search_params, path, max_zoom, archive_id)
+}
+
+/// Exports filtered occurrences as an Apache Parquet file, with extension
+/// rows as nested columns unless include_extensions is false
+#[tauri::command]
+pub fn export_parquet(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    path: String,
+    include_extensions: Option<bool>,
+    archive_id: Option<String>,
+) -> Result<()> {
+    parquet::export_parquet(app, search_params, path, include_extensions, archive_id)
+}
+
+/// Exports all occurrences and extension rows, ignoring any filters, as a
+/// SQLite database
+#[tauri::command]
+pub fn export_sqlite(
+    app: tauri::AppHandle,
+    path: String,
+    archive_id: Option<String>,
+) -> Result<()> {
+    sqlite::export_sqlite(app, path, archive_id)
+}
+
+/// Exports flags on occurrences, ignoring any filters, as a CSV
+#[tauri::command]
+pub fn export_flags_csv(
+    app: tauri::AppHandle,
+    path: String,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    kml::export_kml(app, search_params, path)
+    flags::export_flags_csv(app, path, archive_id)
 }
 
 #[tauri::command]
@@ -40,15 +121,35 @@ pub fn export_groups_csv(
     search_params: SearchParams,
     field_name: String,
     path: String,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    groups::export_groups_csv(app, search_params, field_name, path)
+    groups::export_groups_csv(app, search_params, field_name, path, archive_id)
 }
 
+/// Exports filtered occurrences as a DarwinCore Archive. With ipt, the
+/// archive is packaged and validated for uploading to an IPT. With
+/// allowed_licenses, only media with those license codes (see
+/// get_media_license_summary) are exported. With omit_excluded, occurrences
+/// excluded with exclude_occurrences are left out.
 #[tauri::command]
 pub fn export_dwca(
     app: tauri::AppHandle,
     search_params: SearchParams,
     path: String,
+    ipt: Option<bool>,
+    allowed_licenses: Option<Vec<String>>,
+    omit_excluded: Option<bool>,
+    archive_id: Option<String>,
 ) -> Result<()> {
-    dwca::export_dwca_inner(get_archives_dir(app)?, search_params, path)
+    let allowed_licenses: Option<HashSet<String>> =
+        allowed_licenses.map(|licenses| licenses.into_iter().collect());
+    dwca::export_dwca_inner(
+        get_archives_dir(app)?,
+        archive_id.as_deref(),
+        search_params,
+        path,
+        ipt.unwrap_or(false),
+        allowed_licenses.as_ref(),
+        omit_excluded.unwrap_or(false),
+    )
 }
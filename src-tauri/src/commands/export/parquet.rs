@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use crate::commands::archive::get_archives_dir;
+use crate::dwca::Archive;
+use crate::error::Result;
+use crate::search_params::SearchParams;
+
+/// Exports filtered occurrences as an Apache Parquet file for analysis in R
+/// or Python. Extension rows are included as nested columns unless
+/// `include_extensions` is false.
+pub(super) fn export_parquet(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    path: String,
+    include_extensions: Option<bool>,
+    archive_id: Option<String>,
+) -> Result<()> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    let count = archive.export_parquet(
+        search_params,
+        &PathBuf::from(&path),
+        include_extensions.unwrap_or(true),
+    )?;
+    log::info!(
+        format = "parquet", count = count, path = path.as_str();
+        "Exported {count} occurrences to {path}"
+    );
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+use pmtiles::{Compression, PmTilesWriter, TileCoord, TileType};
+
+use crate::commands::archive::get_archives_dir;
+use crate::dwca::Archive;
+use crate::error::{ChuckError, Result};
+use crate::search_params::SearchParams;
+use crate::tile_server::coords::{lat_lng_to_tile, sample_grid_size};
+use crate::tile_server::{generate_tile, ObscuredMode, TilePoint};
+
+/// Highest zoom to render when none is given. Points are never sampled past
+/// zoom 8, so higher zooms only add tiles.
+pub(super) const DEFAULT_MAX_ZOOM: u8 = 12;
+
+/// Highest zoom we'll render, matching what MapLibre overzooms from nicely
+const MAX_ZOOM_LIMIT: u8 = 16;
+
+/// Exports filtered occurrences as a standalone PMTiles vector tileset with
+/// the same "occurrences" layer the map uses
+pub(super) fn export_pmtiles(
+    app: tauri::AppHandle,
+    search_params: SearchParams,
+    path: String,
+    max_zoom: Option<u8>,
+    archive_id: Option<String>,
+) -> Result<()> {
+    export_pmtiles_inner(
+        get_archives_dir(app)?,
+        archive_id.as_deref(),
+        search_params,
+        path,
+        max_zoom,
+    )
+}
+
+pub(super) fn export_pmtiles_inner(
+    archives_dir: PathBuf,
+    archive_id: Option<&str>,
+    search_params: SearchParams,
+    path: String,
+    max_zoom: Option<u8>,
+) -> Result<()> {
+    let max_zoom = max_zoom.unwrap_or(DEFAULT_MAX_ZOOM).min(MAX_ZOOM_LIMIT);
+    let archive = Archive::find(&archives_dir, archive_id)?;
+
+    // query_tile doesn't sample at MAX_ZOOM_LIMIT, so this gets every point
+    // and each zoom gets sampled below
+    let points = archive.query_tile(
+        -180.0,
+        -90.0,
+        180.0,
+        90.0,
+        MAX_ZOOM_LIMIT,
+        search_params,
+        None,
+        ObscuredMode::Ignore,
+    )?;
+
+    let dest = PathBuf::from(&path);
+    let file = std::fs::File::create(&dest).map_err(|e| ChuckError::FileOpen {
+        path: dest.clone(),
+        source: e,
+    })?;
+    let mut builder = PmTilesWriter::new(TileType::Mvt)
+        .tile_compression(Compression::None)
+        .max_zoom(max_zoom)
+        .metadata(&tileset_metadata(&archive.info()?.name, max_zoom));
+    if let Some((min_lon, min_lat, max_lon, max_lat)) = points_bounds(&points) {
+        builder = builder.bounds(min_lon, min_lat, max_lon, max_lat);
+    }
+    let mut writer = builder
+        .create(file)
+        .map_err(|e| ChuckError::PmTiles(e.to_string()))?;
+
+    for zoom in 0..=max_zoom {
+        for ((x, y), tile_points) in tiles_for_zoom(&points, zoom) {
+            let coord = TileCoord::new(zoom, x, y)
+                .map_err(|e| ChuckError::PmTiles(e.to_string()))?;
+            writer
+                .add_raw_tile(coord, &generate_tile(zoom, x, y, tile_points, None, false))
+                .map_err(|e| ChuckError::PmTiles(e.to_string()))?;
+        }
+    }
+
+    writer
+        .finalize()
+        .map_err(|e| ChuckError::PmTiles(e.to_string()))
+}
+
+/// Groups points into the tiles containing them at a zoom level, keeping one
+/// point per sample grid cell like the map does. Empty tiles are left out.
+fn tiles_for_zoom(points: &[TilePoint], zoom: u8) -> BTreeMap<(u32, u32), Vec<TilePoint>> {
+    let grid_size = sample_grid_size(zoom);
+    let mut sampled_cells = HashSet::new();
+    let mut tiles: BTreeMap<(u32, u32), Vec<TilePoint>> = BTreeMap::new();
+    for point in points {
+        let (lat, lng) = (point.latitude, point.longitude);
+        if let Some(grid) = grid_size {
+            let cell = ((lat / grid).floor() as i64, (lng / grid).floor() as i64);
+            if !sampled_cells.insert(cell) {
+                continue;
+            }
+        }
+        tiles.entry(lat_lng_to_tile(lat, lng, zoom)).or_default().push(point.clone());
+    }
+    tiles
+}
+
+/// (min_lon, min_lat, max_lon, max_lat) of the points, if there are any
+fn points_bounds(points: &[TilePoint]) -> Option<(f64, f64, f64, f64)> {
+    points.iter().fold(None, |bounds, point| {
+        let (lat, lng) = (point.latitude, point.longitude);
+        let (min_lon, min_lat, max_lon, max_lat) = bounds.unwrap_or((lng, lat, lng, lat));
+        Some((min_lon.min(lng), min_lat.min(lat), max_lon.max(lng), max_lat.max(lat)))
+    })
+}
+
+/// TileJSON-style metadata describing the occurrences layer so MapLibre and
+/// other clients know what's in the tiles
+fn tileset_metadata(name: &str, max_zoom: u8) -> String {
+    serde_json::json!({
+        "name": name,
+        "format": "pbf",
+        "vector_layers": [{
+            "id": "occurrences",
+            "fields": { "core_id": "String", "scientificName": "String", "eventDate": "String" },
+            "minzoom": 0,
+            "maxzoom": max_zoom,
+        }],
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use pmtiles::{AsyncPmTilesReader, MmapBackend};
+
+    fn setup_archive(csv_content: &str) -> (tempfile::TempDir, PathBuf) {
+        let temp = tempfile::tempdir().unwrap();
+        let archives_dir = temp.path().to_path_buf();
+        let storage_dir = archives_dir.join("test.zip-abc123");
+        std::fs::create_dir_all(&storage_dir).unwrap();
+
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy=",">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+  </core>
+</archive>"#;
+        std::fs::write(storage_dir.join("meta.xml"), meta_xml).unwrap();
+        std::fs::write(storage_dir.join("occurrence.csv"), csv_content).unwrap();
+
+        let db = Database::create_from_core_files(
+            &[storage_dir.join("occurrence.csv")],
+            &[],
+            &storage_dir.join("test.db"),
+            "occurrenceID",
+        )
+        .unwrap();
+        drop(db);
+
+        (temp, archives_dir)
+    }
+
+    #[test]
+    fn test_tiles_for_zoom_samples_low_zooms() {
+        let point = |core_id: &str, latitude: f64, longitude: f64| TilePoint {
+            core_id: core_id.to_string(),
+            latitude,
+            longitude,
+            scientific_name: None,
+            event_date: None,
+            attribute: None,
+            obscured: false,
+            cell: None,
+            uncertainty: None,
+        };
+        let points = vec![
+            point("1", 37.51, -122.01),
+            point("2", 37.52, -122.02),
+            point("3", -33.9, 18.4),
+        ];
+
+        let world = tiles_for_zoom(&points, 0);
+        assert_eq!(world.len(), 1);
+        assert_eq!(world[&(0, 0)].len(), 2);
+
+        let detailed = tiles_for_zoom(&points, 10);
+        assert_eq!(detailed.len(), 2);
+        assert_eq!(detailed.values().map(Vec::len).sum::<usize>(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_export_pmtiles_writes_occurrence_tiles() {
+        let csv = "occurrenceID,decimalLatitude,decimalLongitude,scientificName\n\
+                   obs1,37.5,-122.0,Quercus agrifolia\n\
+                   obs2,,,No coords\n";
+        let (_temp, archives_dir) = setup_archive(csv);
+        let output = archives_dir.join("out.pmtiles");
+
+        export_pmtiles_inner(
+            archives_dir.clone(),
+            None,
+            SearchParams::default(),
+            output.to_string_lossy().to_string(),
+            Some(4),
+        )
+        .unwrap();
+
+        let backend = MmapBackend::try_from(output.as_path()).await.unwrap();
+        let reader = AsyncPmTilesReader::try_from_source(backend).await.unwrap();
+        let header = reader.get_header();
+        assert_eq!(header.max_zoom, 4);
+        let (x, y) = lat_lng_to_tile(37.5, -122.0, 4);
+        let tile = reader.get_tile(TileCoord::new(4, x, y).unwrap()).await.unwrap();
+        assert!(tile.is_some_and(|data| !data.is_empty()));
+        let empty = reader.get_tile(TileCoord::new(4, 0, 0).unwrap()).await.unwrap();
+        assert!(empty.is_none());
+    }
+}
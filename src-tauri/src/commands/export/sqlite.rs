@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use crate::commands::archive::get_archives_dir;
+use crate::dwca::Archive;
+use crate::error::Result;
+
+/// Exports the current archive's occurrences and extension tables as a
+/// standalone SQLite database that can be opened in tools like DB Browser
+pub(super) fn export_sqlite(
+    app: tauri::AppHandle,
+    path: String,
+    archive_id: Option<String>,
+) -> Result<()> {
+    let archive = Archive::find(&get_archives_dir(app)?, archive_id.as_deref())?;
+    let count = archive.export_sqlite(&PathBuf::from(&path))?;
+    log::info!(
+        format = "sqlite", count = count, path = path.as_str();
+        "Exported {count} rows to {path}"
+    );
+    Ok(())
+}
@@ -134,8 +134,7 @@ async fn fetch_username_from_api(jwt: &str) -> Result<String, String> {
 
     // Fetch user info from public API
     let url = format!("https://api.inaturalist.org/v1/users/{}", claims.user_id);
-    let client = reqwest::Client::new();
-    let response = client.get(&url)
+    let response = chuck_core::api::client::http_client().get(&url)
         .send()
         .await
         .map_err(|e| format!("Failed to fetch user info: {e}"))?;
@@ -1,7 +1,8 @@
 use chuck_core::api::{client, params};
+use chuck_core::api::taxon_suggestions::{fetch_taxon_suggestions, TaxonSuggestion};
 use chuck_core::auth::{fetch_jwt, AuthCache};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, LazyLock};
 
@@ -14,6 +15,8 @@ pub struct CountParams {
     d2: Option<String>,
     created_d1: Option<String>,
     created_d2: Option<String>,
+    #[serde(flatten)]
+    filters: params::ObservationFilters,
     url_params: Option<String>,
 }
 
@@ -35,7 +38,7 @@ fn build_api_params_from_count(
     if let Some(ref url_params) = p.url_params {
         params::parse_url_params(extract_query(url_params))
     } else {
-        params::build_params(
+        let mut api_params = params::build_params(
             p.taxon_id.map(|id| id.to_string()),
             p.place_id,
             p.user.clone(),
@@ -43,7 +46,9 @@ fn build_api_params_from_count(
             p.d2.clone(),
             p.created_d1.clone(),
             p.created_d2.clone(),
-        )
+        );
+        params::apply_filters(&mut api_params, &p.filters);
+        api_params
     }
 }
 
@@ -54,7 +59,7 @@ fn build_api_params_from_generate(
     if let Some(ref url_params) = p.url_params {
         params::parse_url_params(extract_query(url_params))
     } else {
-        params::build_params(
+        let mut api_params = params::build_params(
             p.taxon_id.map(|id| id.to_string()),
             p.place_id,
             p.user.clone(),
@@ -62,7 +67,9 @@ fn build_api_params_from_generate(
             p.d2.clone(),
             p.created_d1.clone(),
             p.created_d2.clone(),
-        )
+        );
+        params::apply_filters(&mut api_params, &p.filters);
+        api_params
     }
 }
 
@@ -94,6 +101,9 @@ pub struct MediaEstimate {
     photo_count: usize,
     sound_count: usize,
     sample_size: usize,
+    /// Photos per license in the sample, so users can pick a license policy
+    /// before downloading
+    photo_licenses: std::collections::BTreeMap<String, usize>,
 }
 
 #[tauri::command]
@@ -127,14 +137,17 @@ pub async fn estimate_media_count(params: CountParams) -> Result<MediaEstimate,
                 .flatten()
                 .filter(|s| s.file_url.is_some() && !s.hidden.unwrap_or(false))
                 .count();
+            let photo_licenses =
+                chuck_core::media_license::summarize_photo_licenses(&response.results);
             log::info!(
                 "Media estimate sample: {photo_count} photos + {sound_count} sounds \
-                / {sample_size} obs"
+                / {sample_size} obs, photo licenses: {photo_licenses:?}"
             );
             Ok(MediaEstimate {
                 photo_count,
                 sound_count,
                 sample_size,
+                photo_licenses,
             })
         }
         Err(e) => {
@@ -164,7 +177,13 @@ pub struct GenerateParams {
     d2: Option<String>,
     created_d1: Option<String>,
     created_d2: Option<String>,
+    #[serde(flatten)]
+    filters: params::ObservationFilters,
     fetch_media: bool,
+    #[serde(default)]
+    media_license_policy: chuck_core::media_license::MediaLicensePolicy,
+    #[serde(default)]
+    record_links: bool,
     extensions: Vec<String>,
     url_params: Option<String>,
 }
@@ -191,6 +210,9 @@ pub async fn generate_inat_archive(
             "Audiovisual" => extensions.push(chuck_core::DwcaExtension::Audiovisual),
             "Identifications" => extensions.push(chuck_core::DwcaExtension::Identifications),
             "Comments" => extensions.push(chuck_core::DwcaExtension::Comments),
+            "MeasurementOrFact" => {
+                extensions.push(chuck_core::DwcaExtension::MeasurementOrFact)
+            }
             _ => {
                 log::warn!("Unknown extension: {ext}");
             }
@@ -201,6 +223,7 @@ pub async fn generate_inat_archive(
     let api_params = build_api_params_from_generate(&params);
 
     log::info!(
+        output = params.output_path.as_str(), fetch_media = params.fetch_media;
         "generate_inat_archive: output={}, fetch_media={}, extensions={:?}",
         params.output_path, params.fetch_media, params.extensions
     );
@@ -217,7 +240,9 @@ pub async fn generate_inat_archive(
     };
 
     // Create downloader with JWT for authenticated requests
-    let downloader = Downloader::new(api_params, extensions, params.fetch_media, jwt);
+    let downloader = Downloader::new(api_params, extensions, params.fetch_media, jwt)
+        .with_media_license_policy(params.media_license_policy)
+        .with_record_links(params.record_links);
 
     // Create progress callback
     let app_clone = app.clone();
@@ -274,12 +299,53 @@ pub async fn generate_inat_archive(
     Ok(())
 }
 
+/// Suggests a path in the downloads folder for a new archive, named from the
+/// template (or the default name) with a numbered suffix if a file by that
+/// name already exists
+#[tauri::command]
+pub fn suggest_archive_path(
+    app: AppHandle,
+    params: CountParams,
+    name_template: Option<String>,
+) -> Result<String, String> {
+    use chuck_core::output_name::{render_name_template, unique_path, DEFAULT_NAME};
+
+    let api_params = build_api_params_from_count(&params);
+    let name = match name_template.as_deref().map(str::trim) {
+        Some(template) if !template.is_empty() => {
+            render_name_template(template, &api_params, chrono::Local::now().date_naive())
+        }
+        _ => DEFAULT_NAME.to_string(),
+    };
+    let file_name = format!("{name}.zip");
+    let Ok(dir) = app.path().download_dir() else {
+        return Ok(file_name);
+    };
+    Ok(unique_path(&dir.join(file_name)).to_string_lossy().into_owned())
+}
+
 #[tauri::command]
 pub fn cancel_inat_archive() -> Result<(), String> {
     CANCEL_FLAG.as_ref().store(true, Ordering::Relaxed);
     Ok(())
 }
 
+/// Candidate taxa for a partial name so the download window can offer a
+/// picker instead of guessing which taxon was meant
+#[tauri::command]
+pub async fn get_taxon_suggestions(
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<TaxonSuggestion>, String> {
+    let base_path = client::get_config().await.read().await.base_path.clone();
+    fetch_taxon_suggestions(&base_path, &query, limit.unwrap_or(10))
+        .await
+        .map_err(|e| {
+            log::error!("Failed to get taxon suggestions: {e}");
+            format!("Failed to get taxon suggestions: {e}")
+        })
+}
+
 #[tauri::command]
 pub async fn parse_inat_url(url: String) -> Result<ParsedInatUrl, String> {
     let api_params = params::parse_url_params(extract_query(&url));
@@ -1,4 +1,9 @@
 pub mod archive;
+pub mod diagnostics;
 pub mod export;
 pub mod inat_auth;
 pub mod inat_download;
+pub mod scheduler;
+pub mod schema;
+pub mod settings;
+pub mod storage;
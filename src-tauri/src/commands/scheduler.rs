@@ -0,0 +1,79 @@
+use chrono::Utc;
+use chuck_core::archive_updater::read_archive_preview;
+
+use crate::error::{ChuckError, Result};
+use crate::scheduler::{self, Schedule, ScheduleInterval, ScheduleRun};
+
+#[tauri::command]
+pub fn list_schedules(app: tauri::AppHandle) -> Result<Vec<Schedule>> {
+    scheduler::load_schedules(&app)
+}
+
+/// Schedules updates of the Chuck archive at `archive_path`, starting one
+/// interval from now
+#[tauri::command]
+pub fn add_schedule(
+    app: tauri::AppHandle,
+    archive_path: String,
+    interval: ScheduleInterval,
+) -> Result<Schedule> {
+    let preview = read_archive_preview(&archive_path)
+        .map_err(|e| ChuckError::Schedule(format!("can't read {archive_path}: {e}")))?;
+    if preview.inat_query.is_none() {
+        return Err(ChuckError::Schedule(format!(
+            "{archive_path} wasn't downloaded from iNaturalist by Chuck, so it can't be updated"
+        )));
+    }
+    let schedule = Schedule::new(archive_path, interval, Utc::now());
+    scheduler::update_schedules(&app, |schedules| {
+        schedules.push(schedule.clone());
+        Ok(())
+    })?;
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub fn remove_schedule(app: tauri::AppHandle, id: String) -> Result<()> {
+    scheduler::update_schedules(&app, |schedules| {
+        schedules.retain(|schedule| schedule.id != id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn set_schedule_enabled(app: tauri::AppHandle, id: String, enabled: bool) -> Result<Schedule> {
+    scheduler::update_schedules(&app, |schedules| {
+        let schedule = schedules
+            .iter_mut()
+            .find(|schedule| schedule.id == id)
+            .ok_or_else(|| ChuckError::Schedule(format!("no schedule with ID {id}")))?;
+        schedule.enabled = enabled;
+        Ok(schedule.clone())
+    })
+}
+
+/// Runs of the schedule with `schedule_id`, or of all schedules, most recent
+/// first
+#[tauri::command]
+pub fn get_schedule_history(
+    app: tauri::AppHandle,
+    schedule_id: Option<String>,
+) -> Result<Vec<ScheduleRun>> {
+    let mut history = scheduler::load_history(&app)?;
+    if let Some(schedule_id) = schedule_id {
+        history.retain(|run| run.schedule_id == schedule_id);
+    }
+    history.reverse();
+    Ok(history)
+}
+
+/// Runs a schedule now instead of waiting until it's due. Waits for any
+/// scheduled update already running to finish first.
+#[tauri::command]
+pub async fn run_schedule_now(app: tauri::AppHandle, id: String) -> Result<ScheduleRun> {
+    let schedule = scheduler::load_schedules(&app)?
+        .into_iter()
+        .find(|schedule| schedule.id == id)
+        .ok_or_else(|| ChuckError::Schedule(format!("no schedule with ID {id}")))?;
+    scheduler::run_schedule(&app, &schedule).await
+}
@@ -0,0 +1,283 @@
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Version of the schema format, bumped when its shape changes
+const SCHEMA_VERSION: u32 = 1;
+
+/// Sources of every module with commands, parsed to build the schema so it
+/// can't drift from the actual signatures
+const COMMAND_SOURCES: &[&str] = &[
+    include_str!("archive.rs"),
+    include_str!("diagnostics.rs"),
+    include_str!("export/mod.rs"),
+    include_str!("inat_auth.rs"),
+    include_str!("inat_download.rs"),
+    include_str!("scheduler.rs"),
+    include_str!("schema.rs"),
+    include_str!("settings.rs"),
+    include_str!("storage.rs"),
+    include_str!("../basemap/commands.rs"),
+];
+
+/// Lines after the attribute to search for the end of a signature
+const MAX_SIGNATURE_LINES: usize = 40;
+
+/// Parameter types Tauri injects rather than taking from the caller
+const INJECTED_TYPES: &[&str] = &["AppHandle", "WebviewWindow", "Window", "State<"];
+
+/// Describes the commands the frontend can invoke, so other frontends and
+/// test harnesses can call the backend without reading the Rust source
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandSchema {
+    pub version: u32,
+    pub commands: Vec<CommandDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandDescription {
+    /// Name to pass to invoke
+    pub name: String,
+    /// The command's doc comment
+    pub description: Option<String>,
+    pub params: Vec<CommandParam>,
+    /// Rust return type. Err values are returned as ErrorReports, i.e.
+    /// {code, message, guidance}.
+    pub result: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandParam {
+    /// Argument name as sent from JS, i.e. camelCase
+    pub name: String,
+    /// Rust type, e.g. SearchParams or Option<String>
+    pub rust_type: String,
+    /// Whether the argument can be left out (Option types)
+    pub optional: bool,
+}
+
+/// Schema of all commands, sorted by name
+pub fn command_schema() -> CommandSchema {
+    let mut commands: Vec<CommandDescription> = COMMAND_SOURCES
+        .iter()
+        .flat_map(|source| parse_commands(source))
+        .collect();
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    CommandSchema { version: SCHEMA_VERSION, commands }
+}
+
+/// Returns the names, parameters, and result types of all commands
+#[tauri::command]
+pub fn get_command_schema() -> Result<CommandSchema> {
+    Ok(command_schema())
+}
+
+/// Finds functions marked with the tauri::command attribute in Rust source
+fn parse_commands(source: &str) -> Vec<CommandDescription> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut commands = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        // Tests may define example commands
+        if line.trim() == "#[cfg(test)]" {
+            break;
+        }
+        if line.trim() != "#[tauri::command]" {
+            continue;
+        }
+
+        let doc: Vec<&str> = lines[..i]
+            .iter()
+            .rev()
+            .map(|line| line.trim())
+            .take_while(|line| line.starts_with("///"))
+            .map(|line| line.trim_start_matches("///").trim())
+            .collect();
+        let description = (!doc.is_empty())
+            .then(|| doc.into_iter().rev().collect::<Vec<_>>().join(" "));
+
+        let signature: String = lines[i + 1..]
+            .iter()
+            .take(MAX_SIGNATURE_LINES)
+            .map(|line| line.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(command) = parse_signature(&signature, description) {
+            commands.push(command);
+        }
+    }
+    commands
+}
+
+/// Parses "pub async fn name(params) -> Result { ..." into a description
+fn parse_signature(signature: &str, description: Option<String>) -> Option<CommandDescription> {
+    let after_fn = &signature[signature.find("fn ")? + 3..];
+    let name_end = after_fn.find(['(', '<'])?;
+    let name = after_fn[..name_end].trim().to_string();
+
+    let params_start = after_fn.find('(')? + 1;
+    let params_len = matching_close(&after_fn[params_start..], '(', ')')?;
+    let params_source = &after_fn[params_start..params_start + params_len];
+    let rest = &after_fn[params_start + params_len + 1..];
+    let body_start = rest.find('{').unwrap_or(rest.len());
+    let result = rest[..body_start]
+        .trim()
+        .strip_prefix("->")
+        .map(|result| result.trim().to_string())
+        .unwrap_or_else(|| "()".to_string());
+
+    let params = split_top_level(params_source)
+        .into_iter()
+        .filter_map(|param| {
+            let (param_name, rust_type) = param.split_once(':')?;
+            let rust_type = rust_type.trim().to_string();
+            if INJECTED_TYPES.iter().any(|injected| {
+                rust_type.trim_start_matches("tauri::").starts_with(injected)
+            }) {
+                return None;
+            }
+            Some(CommandParam {
+                name: to_camel_case(param_name.trim().trim_start_matches("mut ")),
+                optional: rust_type.starts_with("Option<"),
+                rust_type,
+            })
+        })
+        .collect();
+
+    Some(CommandDescription { name, description, params, result })
+}
+
+/// Length of `text` up to the bracket closing one that was just opened
+fn matching_close(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Splits parameters on commas that aren't inside generics or tuples
+fn split_top_level(params: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    for c in params.chars() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+        .into_iter()
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Tauri renames snake_case command arguments to camelCase
+fn to_camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper_next = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper_next = !camel.is_empty();
+        } else if upper_next {
+            camel.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        let source = r#"
+/// Does a thing
+/// to an archive
+#[tauri::command]
+pub async fn do_thing(
+    app: tauri::AppHandle,
+    cache: State<'_, AuthCache>,
+    search_params: SearchParams,
+    field_names: Option<Vec<String>>,
+    pair: HashMap<String, (i64, i64)>,
+) -> Result<Vec<String>> {
+    todo!()
+}
+
+#[tauri::command]
+pub fn cancel() {}
+"#;
+
+        let commands = parse_commands(source);
+
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].name, "do_thing");
+        assert_eq!(commands[0].description.as_deref(), Some("Does a thing to an archive"));
+        assert_eq!(commands[0].result, "Result<Vec<String>>");
+        assert_eq!(commands[0].params, vec![
+            CommandParam {
+                name: "searchParams".to_string(),
+                rust_type: "SearchParams".to_string(),
+                optional: false,
+            },
+            CommandParam {
+                name: "fieldNames".to_string(),
+                rust_type: "Option<Vec<String>>".to_string(),
+                optional: true,
+            },
+            CommandParam {
+                name: "pair".to_string(),
+                rust_type: "HashMap<String, (i64, i64)>".to_string(),
+                optional: false,
+            },
+        ]);
+        assert_eq!(commands[1].name, "cancel");
+        assert!(commands[1].params.is_empty());
+        assert_eq!(commands[1].result, "()");
+    }
+
+    #[test]
+    fn test_command_schema_covers_registered_commands() {
+        let handlers = include_str!("../lib.rs");
+        let start = handlers.find("generate_handler![").unwrap();
+        let end = start + handlers[start..].find("])").unwrap();
+        let registered: Vec<&str> = handlers[start..end]
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.trim().trim_end_matches(',').rsplit("::").next())
+            .filter(|name| !name.is_empty())
+            .collect();
+        let schema = command_schema();
+
+        for name in &registered {
+            assert!(
+                schema.commands.iter().any(|command| command.name == *name),
+                "{name} is missing from the command schema"
+            );
+        }
+        assert_eq!(schema.commands.len(), registered.len());
+    }
+}
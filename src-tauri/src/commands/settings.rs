@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use chuck_core::api::client::{set_tls_settings, TlsSettings};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::dwca::CoreIdStrategy;
+use crate::error::{ChuckError, Result};
+use crate::photo_cache;
+
+const NETWORK_SETTINGS_FILENAME: &str = "network_settings.json";
+const STORAGE_SETTINGS_FILENAME: &str = "storage_settings.json";
+const IMPORT_SETTINGS_FILENAME: &str = "import_settings.json";
+
+/// Settings for what Chuck keeps on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StorageSettings {
+    /// Most bytes of photos to cache for each archive before evicting the
+    /// least recently viewed
+    pub photo_cache_max_bytes: u64,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self { photo_cache_max_bytes: photo_cache::DEFAULT_MAX_CACHE_SIZE }
+    }
+}
+
+/// Settings for importing archives
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImportSettings {
+    /// How to give records IDs when an archive's own are missing or
+    /// duplicated
+    pub core_id_strategy: CoreIdStrategy,
+}
+
+pub(crate) fn settings_path<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    filename: &str,
+) -> Result<PathBuf> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    Ok(config_dir.join(filename))
+}
+
+/// Reads saved settings, falling back to the defaults when there are none
+/// or they can't be parsed
+pub(crate) fn read_settings<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let Ok(json) = std::fs::read_to_string(path) else {
+        return T::default();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        log::warn!("Ignoring invalid settings in {}: {e}", path.display());
+        T::default()
+    })
+}
+
+pub(crate) fn write_settings<T: Serialize>(path: &Path, settings: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|source| ChuckError::DirectoryCreate {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    std::fs::write(path, json)
+        .map_err(|source| ChuckError::FileWrite { path: path.to_path_buf(), source })
+}
+
+/// Applies saved network settings to HTTP clients. Call this at startup,
+/// before anything makes a request.
+pub(crate) fn apply_network_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<()> {
+    let path = settings_path(app, NETWORK_SETTINGS_FILENAME)?;
+    set_tls_settings(read_settings(&path));
+    Ok(())
+}
+
+/// Applies saved storage settings, e.g. the photo cache size. Call this at
+/// startup.
+pub(crate) fn apply_storage_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<()> {
+    let settings: StorageSettings = read_settings(&settings_path(app, STORAGE_SETTINGS_FILENAME)?);
+    photo_cache::set_max_cache_size(settings.photo_cache_max_bytes);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_network_settings(app: tauri::AppHandle) -> Result<TlsSettings> {
+    Ok(read_settings(&settings_path(&app, NETWORK_SETTINGS_FILENAME)?))
+}
+
+/// Saves network settings and rebuilds the shared HTTP client with them, so
+/// they apply to requests from now on without a restart.
+#[tauri::command]
+pub fn set_network_settings(app: tauri::AppHandle, settings: TlsSettings) -> Result<TlsSettings> {
+    write_settings(&settings_path(&app, NETWORK_SETTINGS_FILENAME)?, &settings)?;
+    set_tls_settings(settings.clone());
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn get_storage_settings(app: tauri::AppHandle) -> Result<StorageSettings> {
+    Ok(read_settings(&settings_path(&app, STORAGE_SETTINGS_FILENAME)?))
+}
+
+/// Saves storage settings. A smaller photo cache takes effect the next time
+/// a photo is cached.
+#[tauri::command]
+pub fn set_storage_settings(app: tauri::AppHandle, settings: StorageSettings) -> Result<StorageSettings> {
+    write_settings(&settings_path(&app, STORAGE_SETTINGS_FILENAME)?, &settings)?;
+    photo_cache::set_max_cache_size(settings.photo_cache_max_bytes);
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn get_import_settings(app: tauri::AppHandle) -> Result<ImportSettings> {
+    read_import_settings(&app)
+}
+
+pub(crate) fn read_import_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<ImportSettings> {
+    Ok(read_settings(&settings_path(app, IMPORT_SETTINGS_FILENAME)?))
+}
+
+/// Saves import settings, which apply to archives opened from now on
+#[tauri::command]
+pub fn set_import_settings(app: tauri::AppHandle, settings: ImportSettings) -> Result<ImportSettings> {
+    write_settings(&settings_path(&app, IMPORT_SETTINGS_FILENAME)?, &settings)?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_settings_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("config").join(NETWORK_SETTINGS_FILENAME);
+        assert_eq!(read_settings::<TlsSettings>(&path), TlsSettings::default());
+
+        let settings = TlsSettings {
+            use_native_certs: true,
+            ca_bundle_path: Some(PathBuf::from("/etc/ssl/corporate.pem")),
+        };
+        write_settings(&path, &settings).unwrap();
+
+        assert_eq!(read_settings::<TlsSettings>(&path), settings);
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(read_settings::<TlsSettings>(&path), TlsSettings::default());
+    }
+
+    #[test]
+    fn test_storage_settings_defaults_missing_fields() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join(STORAGE_SETTINGS_FILENAME);
+        std::fs::write(&path, "{}").unwrap();
+
+        let settings: StorageSettings = read_settings(&path);
+
+        assert_eq!(settings.photo_cache_max_bytes, photo_cache::DEFAULT_MAX_CACHE_SIZE);
+    }
+
+    #[test]
+    fn test_import_settings_core_id_strategy_json() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join(IMPORT_SETTINGS_FILENAME);
+        std::fs::write(
+            &path,
+            r#"{"coreIdStrategy": {"strategy": "hash", "columns": ["catalogNumber"]}}"#,
+        )
+        .unwrap();
+
+        let settings: ImportSettings = read_settings(&path);
+
+        assert_eq!(
+            settings.core_id_strategy,
+            CoreIdStrategy::Hash { columns: vec!["catalogNumber".to_string()] }
+        );
+    }
+}
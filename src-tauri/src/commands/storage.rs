@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::archive::get_archives_dir;
+use crate::dwca::Archive;
+use crate::error::{ChuckError, Result};
+use crate::photo_cache::PhotoCache;
+
+/// Disk space one open archive takes up
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageUsage {
+    /// Identifies the archive among open archives
+    pub id: String,
+    pub name: String,
+    /// Everything in the archive's storage directory. This includes the link
+    /// to the original zip, which only takes up space of its own once the
+    /// original is deleted.
+    pub total_bytes: u64,
+    pub database_bytes: u64,
+    pub photo_cache_bytes: u64,
+    /// Data files left over from extracting the archive, which can be
+    /// deleted without losing anything
+    pub extracted_bytes: u64,
+}
+
+/// Disk space Chuck is using for archives and basemaps
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    /// Open archives, most recently opened first
+    pub archives: Vec<ArchiveStorageUsage>,
+    pub photo_cache_bytes: u64,
+    pub basemaps_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Size of a file, or of everything under a directory. Anything that can't
+/// be read counts as empty.
+pub(crate) fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| disk_usage(&entry.path())).sum())
+        .unwrap_or_default()
+}
+
+fn archive_storage_usage(archive: &Archive) -> ArchiveStorageUsage {
+    ArchiveStorageUsage {
+        id: archive.id(),
+        name: archive.name.clone(),
+        total_bytes: disk_usage(&archive.storage_dir),
+        database_bytes: disk_usage(archive.db_path()),
+        photo_cache_bytes: disk_usage(&archive.storage_dir.join("photo_cache")),
+        extracted_bytes: archive.extracted_data_files().iter().map(|path| disk_usage(path)).sum(),
+    }
+}
+
+fn storage_usage(archives_dir: &Path, basemaps_dir: &Path) -> Result<StorageUsage> {
+    let archives: Vec<ArchiveStorageUsage> = Archive::list(archives_dir)?
+        .iter()
+        .map(archive_storage_usage)
+        .collect();
+    let basemaps_bytes = disk_usage(basemaps_dir);
+    Ok(StorageUsage {
+        photo_cache_bytes: archives.iter().map(|a| a.photo_cache_bytes).sum(),
+        total_bytes: archives.iter().map(|a| a.total_bytes).sum::<u64>() + basemaps_bytes,
+        archives,
+        basemaps_bytes,
+    })
+}
+
+fn basemaps_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf> {
+    crate::basemap::protocol::basemaps_dir(app).map_err(ChuckError::Tauri)
+}
+
+/// Reports how much disk space open archives, their photo caches, and
+/// downloaded basemaps take up
+#[tauri::command]
+pub fn get_storage_usage(app: tauri::AppHandle) -> Result<StorageUsage> {
+    storage_usage(&get_archives_dir(app.clone())?, &basemaps_dir(&app)?)
+}
+
+/// Frees disk space by deleting cached photos and/or data files left over
+/// from extracting archives, for one archive or all of them. Databases are
+/// kept, so archives stay open. Returns storage usage afterwards.
+#[tauri::command]
+pub fn clear_caches(
+    app: tauri::AppHandle,
+    photo_cache: bool,
+    extracted_data: bool,
+    archive_id: Option<String>,
+) -> Result<StorageUsage> {
+    let archives_dir = get_archives_dir(app.clone())?;
+    let archives = match archive_id {
+        Some(archive_id) => vec![Archive::by_id(&archives_dir, &archive_id)?],
+        None => Archive::list(&archives_dir)?,
+    };
+    let mut freed = 0;
+    for archive in &archives {
+        if photo_cache {
+            freed += PhotoCache::new(&archive.storage_dir.join("photo_cache")).clear()?;
+        }
+        if extracted_data {
+            freed += archive.remove_extracted_data()?;
+        }
+    }
+    log::info!(bytes = freed; "Cleared {freed} bytes of cached data");
+    storage_usage(&archives_dir, &basemaps_dir(&app)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_usage() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "12345").unwrap();
+        std::fs::create_dir(temp.path().join("photo_cache")).unwrap();
+        std::fs::write(temp.path().join("photo_cache").join("b.jpg"), "123").unwrap();
+
+        assert_eq!(disk_usage(temp.path()), 8);
+        assert_eq!(disk_usage(&temp.path().join("photo_cache")), 3);
+        assert_eq!(disk_usage(&temp.path().join("missing")), 0);
+    }
+}
@@ -0,0 +1,320 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::error::{ChuckError, Result};
+
+/// Natural Earth's public domain 1:110m admin 0 countries, downloaded with
+/// the offline basemaps rather than bundled
+pub const SOURCE_URL: &str =
+    "https://raw.githubusercontent.com/nvkelso/natural-earth-vector/master/geojson/ne_110m_admin_0_countries.geojson";
+
+/// Decimal places kept when simplifying downloaded boundaries
+const PRECISION: i32 = 2;
+
+/// A ring of (longitude, latitude) points
+type Ring = Vec<(f64, f64)>;
+
+struct Polygon {
+    exterior: Ring,
+    holes: Vec<Ring>,
+}
+
+impl Polygon {
+    fn contains(&self, lon: f64, lat: f64) -> bool {
+        ring_contains(&self.exterior, lon, lat)
+            && !self.holes.iter().any(|hole| ring_contains(hole, lon, lat))
+    }
+}
+
+/// One or more polygons making up a region, like a country or a state
+pub(crate) struct Area {
+    polygons: Vec<Polygon>,
+    /// (min_lon, min_lat, max_lon, max_lat) to skip most polygons cheaply
+    bbox: (f64, f64, f64, f64),
+}
+
+impl Area {
+    /// Parses a GeoJSON Polygon or MultiPolygon geometry. Other geometry types
+    /// give None.
+    pub(crate) fn from_geometry(geometry: &Value) -> std::result::Result<Option<Self>, String> {
+        let polygons = match geometry["type"].as_str() {
+            Some("Polygon") => vec![parse_polygon(&geometry["coordinates"])?],
+            Some("MultiPolygon") => geometry["coordinates"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(parse_polygon)
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+            _ => return Ok(None),
+        };
+        let bbox = polygons
+            .iter()
+            .flat_map(|polygon| polygon.exterior.iter())
+            .fold(
+                (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+                |(min_lon, min_lat, max_lon, max_lat), &(lon, lat)| {
+                    (min_lon.min(lon), min_lat.min(lat), max_lon.max(lon), max_lat.max(lat))
+                },
+            );
+        Ok(Some(Self { polygons, bbox }))
+    }
+
+    pub(crate) fn contains(&self, lon: f64, lat: f64) -> bool {
+        let (min_lon, min_lat, max_lon, max_lat) = self.bbox;
+        lon >= min_lon && lon <= max_lon && lat >= min_lat && lat <= max_lat
+            && self.polygons.iter().any(|polygon| polygon.contains(lon, lat))
+    }
+}
+
+struct Country {
+    /// ISO 3166-1 alpha-2 code, e.g. "US"
+    code: String,
+    area: Area,
+}
+
+/// Low-resolution country polygons for checking coordinates against
+/// countryCode. At this resolution points near borders and coastlines can
+/// land in the wrong country or in none, so callers should only treat a point
+/// that falls inside a *different* country as a mismatch.
+pub struct CountryBoundaries {
+    countries: Vec<Country>,
+}
+
+impl CountryBoundaries {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|source| ChuckError::FileRead { path: path.to_path_buf(), source })?;
+        Self::from_geojson(&json)
+    }
+
+    /// Parses a GeoJSON FeatureCollection of Polygon and MultiPolygon features
+    /// with the country code in a `code` property
+    pub fn from_geojson(json: &str) -> Result<Self> {
+        let collection: Value = serde_json::from_str(json)
+            .map_err(|e| ChuckError::CountryBoundaries(e.to_string()))?;
+        let features = collection["features"]
+            .as_array()
+            .ok_or_else(|| ChuckError::CountryBoundaries("missing features".to_string()))?;
+
+        let mut countries = Vec::with_capacity(features.len());
+        for feature in features {
+            let Some(code) = feature["properties"]["code"].as_str() else {
+                continue;
+            };
+            let Some(area) = Area::from_geometry(&feature["geometry"])
+                .map_err(ChuckError::CountryBoundaries)?
+            else {
+                continue;
+            };
+            countries.push(Country { code: code.to_uppercase(), area });
+        }
+
+        Ok(Self { countries })
+    }
+
+    /// Code of the country containing the point, if any
+    pub fn country_at(&self, lat: f64, lon: f64) -> Option<&str> {
+        self.countries
+            .iter()
+            .find(|country| country.area.contains(lon, lat))
+            .map(|country| country.code.as_str())
+    }
+
+    /// Whether the dataset has a boundary for the country code. Small
+    /// countries and territories are missing at low resolution.
+    pub fn has_country(&self, code: &str) -> bool {
+        self.countries.iter().any(|country| country.code.eq_ignore_ascii_case(code))
+    }
+}
+
+/// Strips a Natural Earth admin 0 FeatureCollection down to the ISO 3166-1
+/// alpha-2 codes `CountryBoundaries` reads, with rounded coordinates to keep
+/// the saved file small
+pub fn simplify_natural_earth(source: &Value) -> Value {
+    let features: Vec<Value> = source["features"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|feature| {
+            let code = natural_earth_code(&feature["properties"])?;
+            Some(simplified_feature(json!({ "code": code }), &feature["geometry"]))
+        })
+        .collect();
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+/// Natural Earth uses -99 for a few countries (e.g. France, Norway) whose
+/// ISO_A2 is ambiguous, with the usual code in ISO_A2_EH
+fn natural_earth_code(properties: &Value) -> Option<&str> {
+    ["ISO_A2", "ISO_A2_EH"]
+        .iter()
+        .filter_map(|key| properties[key].as_str())
+        .find(|code| *code != "-99")
+}
+
+/// Feature with the given properties and the geometry's coordinates rounded
+pub(crate) fn simplified_feature(properties: Value, geometry: &Value) -> Value {
+    json!({
+        "type": "Feature",
+        "properties": properties,
+        "geometry": {
+            "type": geometry["type"],
+            "coordinates": round_coordinates(&geometry["coordinates"]),
+        },
+    })
+}
+
+/// Rounds nested GeoJSON coordinates, dropping the runs of identical points
+/// rounding leaves in rings
+fn round_coordinates(coordinates: &Value) -> Value {
+    let Some(items) = coordinates.as_array() else {
+        return coordinates.clone();
+    };
+    if items.first().is_some_and(Value::is_number) {
+        let factor = 10f64.powi(PRECISION);
+        return items
+            .iter()
+            .map(|n| n.as_f64().map_or(Value::Null, |n| json!((n * factor).round() / factor)))
+            .collect();
+    }
+    let mut rounded: Vec<Value> = Vec::with_capacity(items.len());
+    for item in items.iter().map(round_coordinates) {
+        if item[0].is_number() && rounded.last() == Some(&item) {
+            continue;
+        }
+        rounded.push(item);
+    }
+    Value::Array(rounded)
+}
+
+fn parse_polygon(coordinates: &Value) -> std::result::Result<Polygon, String> {
+    let mut rings = coordinates
+        .as_array()
+        .ok_or_else(|| "polygon without rings".to_string())?
+        .iter()
+        .map(|ring| {
+            ring.as_array()
+                .into_iter()
+                .flatten()
+                .map(|point| match (point[0].as_f64(), point[1].as_f64()) {
+                    (Some(lon), Some(lat)) => Ok((lon, lat)),
+                    _ => Err(format!("invalid point {point}")),
+                })
+                .collect::<std::result::Result<Ring, _>>()
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if rings.is_empty() {
+        return Err("polygon without rings".to_string());
+    }
+    let exterior = rings.remove(0);
+    Ok(Polygon { exterior, holes: rings })
+}
+
+/// Even-odd ray casting test
+fn ring_contains(ring: &[(f64, f64)], lon: f64, lat: f64) -> bool {
+    let mut inside = false;
+    let mut j = ring.len().wrapping_sub(1);
+    for i in 0..ring.len() {
+        let (xi, yi) = ring[i];
+        let (xj, yj) = ring[j];
+        if (yi > lat) != (yj > lat) && lon < (xj - xi) * (lat - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Two adjacent square "countries" split at longitude 10, the first with a
+    /// hole in the middle
+    pub(crate) const FIXTURE: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": { "code": "AA" },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [
+                        [[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]],
+                        [[4, 4], [6, 4], [6, 6], [4, 6], [4, 4]]
+                    ]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": { "code": "BB" },
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [
+                        [[[10, 0], [20, 0], [20, 10], [10, 10], [10, 0]]],
+                        [[[30, 30], [31, 30], [31, 31], [30, 31], [30, 30]]]
+                    ]
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_country_at() {
+        let boundaries = CountryBoundaries::from_geojson(FIXTURE).unwrap();
+
+        assert_eq!(boundaries.country_at(2.0, 2.0), Some("AA"));
+        assert_eq!(boundaries.country_at(5.0, 15.0), Some("BB"));
+        assert_eq!(boundaries.country_at(30.5, 30.5), Some("BB"));
+        // In the hole
+        assert_eq!(boundaries.country_at(5.0, 5.0), None);
+        assert_eq!(boundaries.country_at(-5.0, -5.0), None);
+    }
+
+    #[test]
+    fn test_has_country() {
+        let boundaries = CountryBoundaries::from_geojson(FIXTURE).unwrap();
+
+        assert!(boundaries.has_country("aa"));
+        assert!(!boundaries.has_country("CC"));
+    }
+
+    #[test]
+    fn test_simplify_natural_earth() {
+        let source = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "properties": { "ISO_A2": "-99", "ISO_A2_EH": "FR", "NAME": "France" },
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[
+                            [0.001, 0.004], [10.0, 0.0], [10.0, 0.001],
+                            [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]
+                        ]]
+                    }
+                },
+                {
+                    "type": "Feature",
+                    "properties": { "ISO_A2": "-99", "ISO_A2_EH": "-99" },
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[[20, 0], [30, 0], [30, 10], [20, 0]]]
+                    }
+                }
+            ]
+        });
+
+        let simplified = simplify_natural_earth(&source);
+
+        assert_eq!(simplified["features"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            simplified["features"][0]["geometry"]["coordinates"],
+            json!([[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]]])
+        );
+        let boundaries = CountryBoundaries::from_geojson(&simplified.to_string()).unwrap();
+        assert_eq!(boundaries.country_at(5.0, 5.0), Some("FR"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Splitting large data files into chunks that can be imported one at a
+//! time, so creating a database from a multi-GB archive can report progress
+//! as it goes
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::dwca::CsvFormat;
+use crate::error::{ChuckError, Result};
+
+/// Files larger than this are imported in chunks of about this size
+pub(super) const CHUNK_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Calls `import` with each chunk of the data file at `path`, the bytes of
+/// the file read so far, and the number of lines of records in earlier
+/// chunks, for turning line numbers in the chunk into line numbers in the
+/// file. Chunks end at a record boundary and start with the file's header
+/// lines, so each can be read with the same options as the whole file. Files
+/// no larger than `chunk_bytes`, and files whose format can't be split
+/// safely, are passed whole.
+pub(super) fn for_each_chunk<F>(
+    path: &Path,
+    format: &CsvFormat,
+    chunk_bytes: u64,
+    mut import: F,
+) -> Result<()>
+where
+    F: FnMut(&Path, u64, u64) -> Result<()>,
+{
+    let read_error = |source| ChuckError::FileRead { path: path.to_path_buf(), source };
+    let total_bytes = std::fs::metadata(path).map_err(read_error)?.len();
+    let Some(header_lines) = splittable_header_lines(format) else {
+        return import(path, total_bytes, 0);
+    };
+    if total_bytes <= chunk_bytes {
+        return import(path, total_bytes, 0);
+    }
+
+    let mut reader = BufReader::new(File::open(path).map_err(read_error)?);
+    let quote = quote_byte(format);
+    let mut header = Vec::new();
+    for _ in 0..header_lines {
+        read_record(&mut reader, quote, &mut header).map_err(read_error)?;
+    }
+    let chunk_path = chunk_path(path);
+    let result = import_chunks(&mut reader, quote, &header, &chunk_path, chunk_bytes, |chunk, bytes, lines| {
+        import(chunk, header.len() as u64 + bytes, lines)
+    });
+    if let Err(e) = std::fs::remove_file(&chunk_path) {
+        log::warn!("Failed to remove {}: {e}", chunk_path.display());
+    }
+    result
+}
+
+/// Writes records from `reader` to `chunk_path` in chunks of about
+/// `chunk_bytes`, each starting with `header`, calling `import` after each
+/// with the bytes of records read so far and the lines of records in earlier
+/// chunks
+fn import_chunks<F>(
+    reader: &mut impl BufRead,
+    quote: Option<u8>,
+    header: &[u8],
+    chunk_path: &Path,
+    chunk_bytes: u64,
+    mut import: F,
+) -> Result<()>
+where
+    F: FnMut(&Path, u64, u64) -> Result<()>,
+{
+    let write_error = |source| ChuckError::FileWrite { path: chunk_path.to_path_buf(), source };
+    let mut bytes_read = 0;
+    let mut lines_before = 0;
+    let mut record = Vec::new();
+    loop {
+        let mut writer = BufWriter::new(File::create(chunk_path).map_err(write_error)?);
+        writer.write_all(header).map_err(write_error)?;
+        let mut chunk_len = 0;
+        let mut chunk_lines = 0;
+        while chunk_len < chunk_bytes {
+            record.clear();
+            let len = read_record(reader, quote, &mut record).map_err(|source| {
+                ChuckError::FileRead { path: chunk_path.to_path_buf(), source }
+            })?;
+            if len == 0 {
+                break;
+            }
+            writer.write_all(&record).map_err(write_error)?;
+            chunk_len += len as u64;
+            chunk_lines += record.iter().filter(|b| **b == b'\n').count() as u64;
+        }
+        writer.flush().map_err(write_error)?;
+        drop(writer);
+        if chunk_len == 0 {
+            return Ok(());
+        }
+        bytes_read += chunk_len;
+        import(chunk_path, bytes_read, lines_before)?;
+        lines_before += chunk_lines;
+    }
+}
+
+/// Appends the next record to `record`, reading more than one line if a
+/// quoted value has line breaks. Returns the number of bytes read, which is
+/// 0 at the end of the file.
+fn read_record(reader: &mut impl BufRead, quote: Option<u8>, record: &mut Vec<u8>) -> std::io::Result<usize> {
+    let mut len = 0;
+    let mut quoted = false;
+    loop {
+        let start = record.len();
+        let line_len = reader.read_until(b'\n', record)?;
+        len += line_len;
+        if let Some(quote) = quote {
+            // Escaped quotes come in pairs and leave the state as it was
+            let quotes = record[start..].iter().filter(|b| **b == quote).count();
+            quoted ^= quotes % 2 == 1;
+        }
+        if line_len == 0 || !quoted {
+            return Ok(len);
+        }
+    }
+}
+
+/// Header lines to repeat at the start of each chunk, or None if the file
+/// shouldn't be split. DuckDB sniffs the delimiter and header of files whose
+/// meta.xml doesn't declare them, and might sniff chunks differently, and
+/// files can only be split on \n if it's a single byte ending each line.
+fn splittable_header_lines(format: &CsvFormat) -> Option<usize> {
+    if format.delimiter.is_none()
+        || format.line_terminator == Some(r"\r")
+        || format.encoding == Some("utf-16")
+    {
+        return None;
+    }
+    format.header_lines
+}
+
+/// The byte values are quoted with, if any. DuckDB quotes with " unless told
+/// otherwise.
+fn quote_byte(format: &CsvFormat) -> Option<u8> {
+    match format.quote.as_deref() {
+        None => Some(b'"'),
+        Some(quote) => quote.bytes().next(),
+    }
+}
+
+fn chunk_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".chunk");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(content: &str, format: &CsvFormat, chunk_bytes: u64) -> Vec<(String, u64, u64)> {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("occurrence.csv");
+        std::fs::write(&path, content).unwrap();
+        let mut chunks = Vec::new();
+        for_each_chunk(&path, format, chunk_bytes, |chunk, bytes_read, lines_before| {
+            chunks.push((std::fs::read_to_string(chunk).unwrap(), bytes_read, lines_before));
+            Ok(())
+        })
+        .unwrap();
+        assert!(!chunk_path(&path).exists());
+        chunks
+    }
+
+    fn csv_format() -> CsvFormat {
+        CsvFormat {
+            delimiter: Some(','),
+            header_lines: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_chunks_repeat_header_and_keep_quoted_line_breaks() {
+        let content = "id,remarks\n1,\"two\nlines\"\n2,short\n3,\"said \"\"hi\"\"\"\n";
+
+        let chunks = chunks(content, &csv_format(), 8);
+
+        assert_eq!(chunks, vec![
+            ("id,remarks\n1,\"two\nlines\"\n".to_string(), 25, 0),
+            ("id,remarks\n2,short\n".to_string(), 33, 2),
+            ("id,remarks\n3,\"said \"\"hi\"\"\"\n".to_string(), content.len() as u64, 3),
+        ]);
+    }
+
+    #[test]
+    fn test_small_and_sniffed_files_are_not_split() {
+        let content = "id,name\n1,a\n2,b\n";
+
+        assert_eq!(chunks(content, &csv_format(), 1024), vec![(content.to_string(), 16, 0)]);
+        assert_eq!(chunks(content, &CsvFormat::default(), 4), vec![(content.to_string(), 16, 0)]);
+    }
+}
@@ -0,0 +1,103 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChuckError, Result};
+
+/// Where a keyset-paginated search left off: the sort it was fetched with
+/// and that sort's values for the last row of the page. Handed to the
+/// frontend as opaque URL-safe base64 JSON.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SearchCursor {
+    /// (column, direction) pairs in order of precedence, ending with the
+    /// core ID so every row has a distinct position
+    pub sort: Vec<(String, String)>,
+    /// Each sort column's value for the last row as text, None for NULL
+    pub values: Vec<Option<String>>,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let json = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|e| ChuckError::SearchCursor(e.to_string()))?;
+        let cursor: Self = serde_json::from_slice(&json)
+            .map_err(|e| ChuckError::SearchCursor(e.to_string()))?;
+        if cursor.sort.len() != cursor.values.len() {
+            return Err(ChuckError::SearchCursor("sort and values don't match".to_string()));
+        }
+        Ok(cursor)
+    }
+
+    /// SQL condition matching rows that come after the cursor when sorted by
+    /// `expressions` (one per sort column, in order) with NULLS LAST, and the
+    /// values to bind to it. A row is after the cursor if it ties on the
+    /// first n columns and comes after it on the next one.
+    pub fn condition(&self, expressions: &[String]) -> (String, Vec<String>) {
+        let mut disjuncts = Vec::new();
+        let mut params = Vec::new();
+        let mut ties: Vec<String> = Vec::new();
+        let mut tie_params: Vec<String> = Vec::new();
+        for ((expression, (_, direction)), value) in
+            expressions.iter().zip(&self.sort).zip(&self.values)
+        {
+            let Some(value) = value else {
+                // Nothing sorts after NULL, so only ties on this column can
+                // lead to later rows
+                ties.push(format!("{expression} IS NULL"));
+                continue;
+            };
+            let operator = if direction.eq_ignore_ascii_case("DESC") { "<" } else { ">" };
+            let mut conjuncts = ties.clone();
+            conjuncts.push(format!("({expression} {operator} ? OR {expression} IS NULL)"));
+            disjuncts.push(format!("({})", conjuncts.join(" AND ")));
+            params.extend(tie_params.iter().cloned());
+            params.push(value.clone());
+            ties.push(format!("{expression} = ?"));
+            tie_params.push(value.clone());
+        }
+        if disjuncts.is_empty() {
+            return ("FALSE".to_string(), params);
+        }
+        (format!("({})", disjuncts.join(" OR ")), params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(sort: &[(&str, &str)], values: &[Option<&str>]) -> SearchCursor {
+        SearchCursor {
+            sort: sort.iter().map(|(c, d)| (c.to_string(), d.to_string())).collect(),
+            values: values.iter().map(|v| v.map(str::to_string)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_search_cursor_round_trip() {
+        let original = cursor(&[("family", "ASC"), ("id", "ASC")], &[None, Some("42")]);
+        assert_eq!(SearchCursor::decode(&original.encode()).unwrap(), original);
+        assert!(SearchCursor::decode("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_search_cursor_condition() {
+        let expressions = vec!["\"family\"".to_string(), "\"id\"".to_string()];
+        let (condition, params) = cursor(&[("family", "DESC"), ("id", "ASC")], &[Some("Fagaceae"), Some("7")])
+            .condition(&expressions);
+        assert_eq!(
+            condition,
+            "(((\"family\" < ? OR \"family\" IS NULL)) OR (\"family\" = ? AND (\"id\" > ? OR \"id\" IS NULL)))"
+        );
+        assert_eq!(params, vec!["Fagaceae", "Fagaceae", "7"]);
+
+        let (condition, params) = cursor(&[("family", "ASC"), ("id", "ASC")], &[None, Some("7")])
+            .condition(&expressions);
+        assert_eq!(condition, "((\"family\" IS NULL AND (\"id\" > ? OR \"id\" IS NULL)))");
+        assert_eq!(params, vec!["7"]);
+    }
+}
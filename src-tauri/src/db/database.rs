@@ -1,57 +1,591 @@
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use duckdb::{params, Row};
-use chuck_core::darwin_core::Occurrence;
+use chuck_core::darwin_core::field_aliases::resolve_field;
+use chuck_core::darwin_core::{term_type, Occurrence, TermType};
+use chuck_core::filter_match::FilterMatch;
 
+use super::csv_chunks::{for_each_chunk, CHUNK_BYTES};
+use super::cursor::SearchCursor;
+use super::filter_cache::{is_filtered, FilterCache};
 use crate::error::{ChuckError, Result};
-use crate::dwca::ExtensionInfo;
+use crate::import_issues::{self, STORE_REJECTS_OPTIONS};
+use crate::dwca::{
+    core_id::{self, CoreIdStrategy, GeneratedCoreId, GENERATED_ID_COLUMN},
+    CsvFormat, EventCoreInfo, ExtensionInfo, FieldDefault, ImportWarning, ImportWarningKind,
+};
 use crate::search_params::SearchParams;
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "aggregation", rename_all = "camelCase")]
 pub struct AggregationResult {
     pub value: Option<String>,
     pub count: i64,
     pub photo_url: Option<String>,
+    /// Whether photo_url can be loaded without the network, i.e. it's a
+    /// path in the archive or a remote photo that's already cached
+    #[serde(default)]
+    pub photo_is_local: bool,
+    /// Metrics, when requested. See AggregationMetric.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species_count: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub earliest_event_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_event_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with_photos_count: Option<i64>,
+}
+
+/// Figure computed for each group of an aggregation in addition to its
+/// record count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AggregationMetric {
+    /// Distinct non-blank scientificName values
+    SpeciesCount,
+    /// Earliest eventDate, as the date part of the ISO 8601 value
+    EarliestEventDate,
+    LatestEventDate,
+    /// Records with at least one media extension row
+    WithPhotosCount,
+}
+
+impl AggregationMetric {
+    /// Column alias in aggregation queries
+    fn alias(self) -> &'static str {
+        match self {
+            Self::SpeciesCount => "species_count",
+            Self::EarliestEventDate => "earliest_event_date",
+            Self::LatestEventDate => "latest_event_date",
+            Self::WithPhotosCount => "with_photos_count",
+        }
+    }
+
+    const ALL: [Self; 4] = [
+        Self::SpeciesCount,
+        Self::EarliestEventDate,
+        Self::LatestEventDate,
+        Self::WithPhotosCount,
+    ];
+}
+
+/// A value of a column and the number of occurrences that have it
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// The most common values of a column, for browsing by facet
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnValues {
+    /// Most common first
+    pub values: Vec<ValueCount>,
+    /// Distinct values in all, including ones past the limit
+    pub distinct_count: i64,
+}
+
+/// The most common values of one column of a faceted search
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Facet {
+    pub column: String,
+    /// Most common first
+    pub values: Vec<ValueCount>,
+    /// Distinct values in all, including ones past the limit
+    pub distinct_count: i64,
+}
+
+/// An example occurrence from one group of an aggregation
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupExample {
+    pub record: serde_json::Map<String, serde_json::Value>,
+    pub photo_url: Option<String>,
+}
+
+/// Summary statistics for a numeric column. Values are computed over those
+/// that parse as numbers; the rest are counted in `non_numeric_count`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnStats {
+    pub column: String,
+    /// Number of occurrences matching the filters
+    pub count: i64,
+    pub null_count: i64,
+    pub non_numeric_count: i64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    pub p5: Option<f64>,
+    pub p25: Option<f64>,
+    pub p75: Option<f64>,
+    pub p95: Option<f64>,
+}
+
+/// One cell of a two-field crosstab
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrosstabResult {
+    pub primary: Option<String>,
+    pub secondary: Option<String>,
+    pub count: i64,
+}
+
+/// How far creating a database has got, reported after each chunk of each
+/// data file is imported
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    /// Name of the data file being imported, e.g. occurrence.txt
+    pub file: String,
+    /// Table the file is imported into
+    pub table: String,
+    /// Bytes of the file imported so far
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    /// Rows in the table so far
+    pub rows_inserted: usize,
+    /// Data files fully imported, counting core, event, and extension files
+    pub files_done: usize,
+    pub total_files: usize,
+}
+
+/// Counts data files imported while creating a database and passes on
+/// progress within each one
+struct ProgressReporter<'a> {
+    on_progress: &'a dyn Fn(&ImportProgress),
+    files_done: usize,
+    total_files: usize,
+}
+
+/// Calendar unit used to bucket occurrences by eventDate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeBucket {
+    /// Month of the year, 1-12, regardless of year
+    Month,
+    /// ISO week of the year, 1-53, regardless of year
+    Week,
+    Year,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeAggregationResult {
+    pub bucket: i32,
+    pub count: i64,
+}
+
+/// Length of the periods in a time series. Unlike TimeBucket, periods are
+/// on the calendar, so March 2024 and March 2025 are different months.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeInterval {
+    Day,
+    Month,
+    Year,
+}
+
+impl TimeInterval {
+    fn sql_interval(self) -> &'static str {
+        match self {
+            Self::Day => "INTERVAL 1 DAY",
+            Self::Month => "INTERVAL 1 MONTH",
+            Self::Year => "INTERVAL 1 YEAR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSeriesPoint {
+    /// First day of the period, e.g. 2024-03-01 for March 2024
+    pub date: String,
+    pub count: i64,
+}
+
+/// Range of grid cell sizes, in degrees, grid_summary allows. Smaller cells
+/// make too many polygons to draw.
+pub const MIN_GRID_CELL_SIZE: f64 = 0.001;
+pub const MAX_GRID_CELL_SIZE: f64 = 90.0;
+
+/// Occurrences in one cell of a lat/lng grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridCell {
+    /// South-west corner of the cell
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub size: f64,
+    pub record_count: i64,
+    /// Distinct scientificNames in the cell
+    pub species_count: i64,
+}
+
+impl GridCell {
+    /// The cell as a GeoJSON Polygon feature with its counts as properties
+    pub fn to_feature(&self) -> serde_json::Value {
+        // Keeps corners like 0.30000000000000004 tidy
+        let round = |degrees: f64| (degrees * 1e9).round() / 1e9;
+        let (south, west) = (round(self.min_lat), round(self.min_lng));
+        let (north, east) = (round(self.min_lat + self.size), round(self.min_lng + self.size));
+        serde_json::json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[
+                    [west, south],
+                    [east, south],
+                    [east, north],
+                    [west, north],
+                    [west, south],
+                ]],
+            },
+            "properties": {
+                "recordCount": self.record_count,
+                "speciesCount": self.species_count,
+            },
+        })
+    }
+}
+
+/// A GeoJSON FeatureCollection of grid cells
+pub fn grid_geojson(cells: &[GridCell]) -> serde_json::Value {
+    serde_json::json!({
+        "type": "FeatureCollection",
+        "features": cells.iter().map(GridCell::to_feature).collect::<Vec<_>>(),
+    })
+}
+
+/// A day on a species accumulation curve
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccumulationPoint {
+    pub date: String,
+    /// Names recorded for the first time that day
+    pub new_species: i64,
+    /// Names recorded that day or before
+    pub total_species: i64,
+}
+
+/// What one collector recorded, for a collector profile
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorSummary {
+    pub recorded_by: String,
+    pub record_count: i64,
+    /// Distinct scientificNames, or None without a scientificName column
+    pub species_count: Option<i64>,
+    /// ISO 8601 date part of the earliest and latest eventDate, e.g. 1902,
+    /// 1902-05, or 1902-05-14
+    pub min_event_date: Option<String>,
+    pub max_event_date: Option<String>,
+    /// Extent of the collector's records with coordinates
+    pub bounds: Option<crate::archive_stats::Bounds>,
+    /// Records per calendar month, from time_series
+    pub monthly: Vec<TimeSeriesPoint>,
+}
+
+// Most DwC attributes are strings, but a few are typed when read so queries
+// can treat them as numbers and booleans. Types come from the term mapping
+// in chuck-core, but only booleans and coordinates are typed on read, since
+// a single value like "1985?" would fail the whole import. Other numeric
+// terms stay VARCHAR and get cast in queries (see typed_column).
+//
+// Resist the temptation to override the types of columns that might be
+// used as the core_id, e.g. gbifID, which *is* always an integer. The
+// core_id varies per archive, and sometimes it's stringlike, so we always
+// need to treat it as a varchar
+fn type_override(column: &str) -> Option<&'static str> {
+    match term_type(column) {
+        TermType::Boolean => Some("BOOLEAN"),
+        TermType::Double if matches!(column, "decimalLatitude" | "decimalLongitude") => {
+            Some("DOUBLE")
+        }
+        _ => None,
+    }
+}
+
+/// Bytes in a DuckDB memory size like "12.4 GiB" or "953.6 MiB"
+fn parse_memory_size(value: &str) -> Option<u64> {
+    let (number, unit) = value.trim().split_once(' ')?;
+    let number: f64 = number.parse().ok()?;
+    let unit_bytes: u64 = match unit {
+        "bytes" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        _ => return None,
+    };
+    Some((number * unit_bytes as f64) as u64)
 }
 
-// Most DwC attributes are strings, but a few should have different types to
-// enable better queries
-const TYPE_OVERRIDES: [(&str, &str); 11] = [
-    ("decimalLatitude", "DOUBLE"),
-    ("decimalLongitude", "DOUBLE"),
-    // DarwinCore allows ISO 8601-1:2019 dates *and* datetimes in this field
-    // (https://dwc.tdwg.org/terms/#dwc:eventDate), and that standard
-    // supports ranges (e.g. 2025-01-04/2025-02-14), imprecise years
-    // (e.g. 2025) and year-months (e.g. 2025-04), none of which duckdb
-    // handles. Unless there's a very compelling need to use a DATE here,
-    // best left as  VARCHAR
-    //
-    // ("eventDate", "DATE"),
-
-    // Resist the temptation to override the types of columns that might be
-    // used as the core_id, e.g. gbifID, which *is* always an integer. The
-    // core_id varies per archive, and sometimes it's stringlike, so we
-    // always need to treat it as a varchar
-
-    // Boolean fields
-    ("captive", "BOOLEAN"),
-    ("hasCoordinate", "BOOLEAN"),
-    ("hasGeospatialIssues", "BOOLEAN"),
-    ("hasTaxonomicIssue", "BOOLEAN"),
-    ("hasNonTaxonomicIssue", "BOOLEAN"),
-    ("identificationCurrent", "BOOLEAN"),
-    ("isInvasive", "BOOLEAN"),
-    ("isSequenced", "BOOLEAN"),
-    ("repatriated", "BOOLEAN"),
+/// Types to pass to read_csv for the columns of a file that have one, by
+/// header, looked up by the term each column holds
+fn type_overrides<'a>(headers: &'a [String], terms: &[&str]) -> HashMap<&'a str, &'static str> {
+    headers
+        .iter()
+        .zip(terms)
+        .filter_map(|(header, term)| type_override(term).map(|typ| (header.as_str(), typ)))
+        .collect()
+}
+
+/// read_csv types argument for a map of column names to types, in DuckDB's
+/// struct format, e.g. types = {'col1': 'TYPE1', 'col2': 'TYPE2'}, or
+/// nothing without any types
+fn read_csv_types(type_map: &HashMap<&str, &'static str>) -> String {
+    if type_map.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = type_map
+        .iter()
+        .map(|(col, typ)| format!("'{col}': '{typ}'"))
+        .collect();
+    format!(", types = {{{}}}", pairs.join(", "))
+}
+
+/// A typed column that was read as text and then parsed, see
+/// Database::parse_relaxed_columns
+struct ParsedColumn {
+    column: String,
+    column_type: &'static str,
+    /// Column the original text was copied to, if any
+    verbatim_column: Option<String>,
+    /// Values that couldn't be parsed and became NULL
+    unparsed: usize,
+}
+
+/// Terms that can appear in Taxon and Event cores but aren't occurrence
+/// fields, so their headers are recognized in checklists and sampling event
+/// archives
+const NON_OCCURRENCE_CORE_FIELD_NAMES: &[&str] = &[
+    "scientificNameAuthorship",
+    "country",
+    "verbatimLatitude",
+    "verbatimLongitude",
+    "verbatimCoordinates",
+    "minimumElevationInMeters",
+    "maximumElevationInMeters",
+    "minimumDepthInMeters",
+    "maximumDepthInMeters",
 ];
 
+/// Suffixes filter keys can have after the field name
+const FILTER_KEY_SUFFIXES: &[&str] = &["_min", "_max", "_include_blank", INCLUDES_FILTER_SUFFIX];
+
+/// Whether a media URL points off the machine rather than into the archive
+pub(crate) fn is_remote_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// SQL condition matching media URL expressions that are photos in the
+/// archive rather than remote URLs
+fn local_photo_condition(url: &str) -> String {
+    format!(
+        "{url} NOT ILIKE 'http://%' AND {url} NOT ILIKE 'https://%' \
+         AND regexp_matches({url}, '\\.(jpe?g|png|gif|webp)$', 'i')"
+    )
+}
+
+/// Prefix of the extra columns search selects to build its next cursor from.
+/// They're dropped from the results.
+const CURSOR_FIELD_PREFIX: &str = "__cursor_";
+
+/// Renames filter and sort fields `columns` doesn't have to a synonymous
+/// column it does. See Database::with_field_aliases.
+fn alias_fields(mut search_params: SearchParams, columns: &[String]) -> SearchParams {
+    let resolve = |name: &str| resolve_field(name, columns).unwrap_or(name).to_string();
+    search_params.filters = search_params
+        .filters
+        .into_iter()
+        .map(|(key, value)| {
+            let key = FILTER_KEY_SUFFIXES
+                .iter()
+                .find_map(|suffix| {
+                    key.strip_suffix(suffix).map(|field| format!("{}{suffix}", resolve(field)))
+                })
+                .unwrap_or_else(|| resolve(&key));
+            (key, value)
+        })
+        .collect();
+    search_params.sort_by = search_params
+        .sort_by
+        .as_deref()
+        .map(|sort_by| sort_by.split(',').map(|column| resolve(column.trim())).collect::<Vec<_>>().join(","));
+    search_params
+}
+
+/// Records which occurrences columns were renamed on import, as (table_name,
+/// original_name, canonical_name), so the original headers aren't lost
+const COLUMN_RENAMES_TABLE: &str = "column_renames";
+
+/// Lowercases a header and drops separators and any namespace prefix, so
+/// "dwc:Scientific_Name" and "scientificName" compare equal
+fn normalized_column_name(name: &str) -> String {
+    name.rsplit(':')
+        .next()
+        .unwrap_or(name)
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' ' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Records the term of occurrences columns that couldn't be named after it,
+/// as (column_name, term), so queries can still type them by their term
+const COLUMN_TERMS_TABLE: &str = "column_terms";
+
+/// Terms core CSV columns hold: the known term the header matches
+/// case-insensitively and ignoring separators, or else the term meta.xml
+/// declares at the column's index, or else the header as it is. Headers
+/// that already look like terms win over meta.xml because old Chuck
+/// archives declare fields one index off from their CSVs.
+fn core_column_terms<'a>(headers: &'a [String], fields: &'a [(usize, String)]) -> Vec<&'a str> {
+    let known_terms: Vec<&str> = fields
+        .iter()
+        .map(|(_, term)| term.as_str())
+        .chain(Occurrence::FIELD_NAMES.iter().copied())
+        .chain(NON_OCCURRENCE_CORE_FIELD_NAMES.iter().copied())
+        .collect();
+    headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            let normalized = normalized_column_name(header);
+            known_terms
+                .iter()
+                .find(|term| normalized_column_name(term) == normalized)
+                .copied()
+                .or_else(|| declared_term(fields, index))
+                .unwrap_or(header)
+        })
+        .collect()
+}
+
+/// Terms extension CSV columns hold: the term meta.xml declares at the
+/// column's index, or else the header as it is
+fn declared_terms<'a>(headers: &'a [String], fields: &'a [(usize, String)]) -> Vec<&'a str> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| declared_term(fields, index).unwrap_or(header))
+        .collect()
+}
+
+/// Term meta.xml declares for the column at `index`
+fn declared_term(fields: &[(usize, String)], index: usize) -> Option<&str> {
+    fields
+        .iter()
+        .find(|(field_index, _)| *field_index == index)
+        .map(|(_, term)| term.as_str())
+}
+
+/// Names for core CSV columns: the term each holds (see core_column_terms),
+/// unless that's already used by another column, in which case the header
+/// as it is
+fn canonical_column_names(headers: &[String], fields: &[(usize, String)]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(headers.len());
+    for (header, term) in headers.iter().zip(core_column_terms(headers, fields)) {
+        let available = term == header.as_str()
+            || (!names.iter().any(|n| n.as_str() == term)
+                && !headers.iter().any(|h| h.as_str() == term));
+        names.push(if available { term } else { header }.to_string());
+    }
+    names
+}
+
+/// Term an occurrences column holds, which is its name unless it's in
+/// `column_terms`
+fn column_term<'a>(column_terms: &'a HashMap<String, String>, column: &'a str) -> &'a str {
+    column_terms.get(column).map_or(column, String::as_str)
+}
+
+// Filter key suffix for matching one value of a multi-value field, e.g.
+// recordedBy_includes
+const INCLUDES_FILTER_SUFFIX: &str = "_includes";
+
+// Columns that might hold a media URL in multimedia and audiovisual
+// tables, best first. Some archives use snake_case headers or only have a
+// link to the media's page.
+const PHOTO_URL_COLUMNS: &[&str] = &["accessURI", "access_uri", "identifier", "references"];
+
+// Alias for the photo URL selected alongside group example records
+const GROUP_EXAMPLE_PHOTO_COLUMN: &str = "chuck_group_example_photo_url";
+
+// Share of the machine's memory DuckDB may use while importing an archive,
+// leaving the rest for the OS and other apps. DuckDB's own default is 80%,
+// which is also how we work out how much memory there is.
+const IMPORT_MEMORY_FRACTION: f64 = 0.5;
+const DUCKDB_DEFAULT_MEMORY_FRACTION: f64 = 0.8;
+
+// Directory next to the database where DuckDB spills data that doesn't fit
+// in memory during an import
+const IMPORT_TEMP_DIR: &str = "duckdb_tmp";
+
+// Companion column holding the UTC offset of eventTime, e.g. -08:00
+const TIME_ZONE_OFFSET_COLUMN: &str = "eventTimeZoneOffset";
+
+/// SQL expression for the local calendar date of eventDate. Datetimes
+/// recorded in UTC (trailing Z or +00:00) are shifted by the offset in
+/// eventTimeZoneOffset so records near midnight land on the day they were
+/// observed. Anything else, including plain dates and datetimes that already
+/// carry a local offset, is left as is. Subsets use
+/// chuck_core::filter_match::local_event_date to do the same.
+const LOCAL_EVENT_DATE_SQL: &str = r#"COALESCE(
+    CASE WHEN regexp_matches("eventTimeZoneOffset", '^[+-]\d{2}:?\d{2}$')
+        AND regexp_matches("eventDate", '^\d{4}-\d{2}-\d{2}T.*(Z|\+00:?00)$')
+    THEN strftime(
+        TRY_CAST(regexp_replace("eventDate", '(Z|\+00:?00)$', '') AS TIMESTAMP)
+            + to_minutes(
+                (CASE WHEN starts_with("eventTimeZoneOffset", '-') THEN -1 ELSE 1 END)
+                * (CAST(substr("eventTimeZoneOffset", 2, 2) AS BIGINT) * 60
+                    + CAST(right("eventTimeZoneOffset", 2) AS BIGINT))
+            ),
+        '%Y-%m-%d'
+    ) END,
+    "eventDate"
+)"#;
+
 /// Represents a DuckDB database for Darwin Core Archive data
 pub struct Database {
     conn: duckdb::Connection,
     core_id_column: String,
     /// Extension table metadata: (extension, core_id_column)
     extension_tables: Vec<(chuck_core::DwcaExtension, String)>,
+    /// Whether occurrences has an eventTimeZoneOffset column to interpret
+    /// eventDate in local time
+    has_time_zone_offsets: bool,
+    /// Whether multi-value fields have been split into the multi_values table
+    has_multi_values: bool,
+    /// Whether a taxonomy has been loaded for higher taxon filters
+    has_taxonomy: bool,
+    /// Whether any occurrences have been flagged for review
+    has_flags: bool,
+    /// Non-fatal problems from creating the database. Only populated by
+    /// create_from_core_files.
+    import_warnings: Vec<ImportWarning>,
+    /// How core IDs were generated, if the archive's own couldn't be used.
+    /// Only populated by create_from_core_files.
+    generated_core_id: Option<GeneratedCoreId>,
+    /// Where materialized filters are kept, if anywhere. See
+    /// materialize_filter.
+    filter_cache: Option<FilterCache>,
+    /// FROM item queries read occurrences from, with hand edits and other
+    /// overlays applied. See overlay::occurrences_source.
+    occurrences_source: String,
+    /// Terms of occurrences columns that aren't named after them. See
+    /// column_term.
+    column_terms: HashMap<String, String>,
 }
 
 impl Database {
@@ -62,12 +596,79 @@ impl Database {
         extensions: &[ExtensionInfo],
         db_path: &Path,
         core_id_column: &str,
+    ) -> Result<Self> {
+        Self::create_from_core_files_with_defaults(
+            core_files,
+            &CsvFormat::default(),
+            &[],
+            &[],
+            None,
+            extensions,
+            db_path,
+            core_id_column,
+        )
+    }
+
+    /// Creates a new database from core files and extension files, applying
+    /// default values declared for core fields in meta.xml. Core files are
+    /// read with the delimiters, header lines, and encoding in `core_format`
+    /// rather than whatever DuckDB sniffs. Core columns are
+    /// renamed to the terms meta.xml declares for them in `core_fields`, and
+    /// other headers that differ from a known term only in case or
+    /// separators, like ScientificName or decimal_latitude, are renamed to
+    /// that term. With `events`, the core files hold occurrences from an
+    /// Event core archive's Occurrence extension, and each occurrence gets
+    /// its event's fields.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_from_core_files_with_defaults(
+        core_files: &[PathBuf],
+        core_format: &CsvFormat,
+        core_fields: &[(usize, String)],
+        core_defaults: &[FieldDefault],
+        events: Option<&EventCoreInfo>,
+        extensions: &[ExtensionInfo],
+        db_path: &Path,
+        core_id_column: &str,
+    ) -> Result<Self> {
+        Self::create_from_core_files_with_progress(
+            core_files,
+            core_format,
+            core_fields,
+            core_defaults,
+            events,
+            extensions,
+            db_path,
+            core_id_column,
+            &CoreIdStrategy::default(),
+            &|_| {},
+        )
+    }
+
+    /// Like `create_from_core_files_with_defaults`, also calling
+    /// `on_progress` as data files are imported. Large files are imported in
+    /// chunks so progress is reported within them too. If the values of
+    /// `core_id_column` can't identify records, because the column is
+    /// missing or has blanks or duplicates, IDs are generated with
+    /// `core_id_strategy` and used instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_from_core_files_with_progress(
+        core_files: &[PathBuf],
+        core_format: &CsvFormat,
+        core_fields: &[(usize, String)],
+        core_defaults: &[FieldDefault],
+        events: Option<&EventCoreInfo>,
+        extensions: &[ExtensionInfo],
+        db_path: &Path,
+        core_id_column: &str,
+        core_id_strategy: &CoreIdStrategy,
+        on_progress: &dyn Fn(&ImportProgress),
     ) -> Result<Self> {
         if core_files.is_empty() {
             return Err(ChuckError::NoCoreFiles);
         }
 
         let conn = duckdb::Connection::open(db_path)?;
+        Self::limit_import_resources(&conn, db_path)?;
 
         // Try to create table from first file
         let first_file = core_files[0]
@@ -78,72 +679,133 @@ impl Database {
         // read_csv below, we need to know what columns are present in the
         // file, or read_csv will error out when we tell it to use types for
         // columns that don't exist
+        let csv_options = core_format.read_csv_options();
         let mut stmt = conn.prepare(&format!(
-            "SELECT unnest(Columns).name FROM sniff_csv('{first_file}')"
+            "SELECT unnest(Columns).name FROM sniff_csv('{first_file}'{csv_options})"
         ))?;
         let column_names: Vec<String> = stmt.query_map([], |row| {
             row.get(0)
         })?.collect::<std::result::Result<Vec<_>, _>>()?;
-        // Check if core_id_column is in TYPE_OVERRIDES - this is a developer error
-        // because the core ID must always be VARCHAR to handle all ID formats
-        if TYPE_OVERRIDES.iter().any(|(col, _)| col == &core_id_column) {
+        // Check if core_id_column has a type override - this is a developer
+        // error because the core ID must always be VARCHAR to handle all ID
+        // formats
+        if type_override(core_id_column).is_some() {
             return Err(ChuckError::CoreIdTypeOverride(core_id_column.to_string()));
         }
 
-        let type_map: HashMap<&str, &str> = TYPE_OVERRIDES
-            .iter()
-            .filter(|(col, _)| column_names.contains(&col.to_string()))
-            .copied()
-            .collect();
-        // Convert to DuckDB's JSON format: {'col1': 'TYPE1', 'col2': 'TYPE2'}
-        // Only include types parameter if we have overrides
-        let types_param = if type_map.is_empty() {
-            String::new()
-        } else {
-            let pairs: Vec<String> = type_map
-                .iter()
-                .map(|(col, typ)| format!("'{col}': '{typ}'"))
-                .collect();
-            format!(", types = {{{}}}", pairs.join(", "))
+        // Types are looked up by the term each column holds but passed to
+        // read_csv under the header's name
+        let header_terms = core_column_terms(&column_names, core_fields);
+        let canonical_names = canonical_column_names(&column_names, core_fields);
+        let mut type_map = type_overrides(&column_names, &header_terms);
+
+        let mut progress = ProgressReporter {
+            on_progress,
+            files_done: 0,
+            total_files: core_files.len()
+                + events.map_or(0, |events| events.files.len())
+                + extensions.iter().filter(|ext| ext.location.exists()).count(),
         };
 
-        // Try to create table with specific types for known columns if they
-        // exist. nullstr will treat empty columns as NULL when converting to
-        // boolean
-        let sql = format!(
-            "CREATE TABLE occurrences AS SELECT * FROM read_csv('{first_file}', all_varchar = true, nullstr = ''{types_param})"
-        );
-        let create_result = conn.execute(&sql, []);
-
-        // If table already exists, insert from first file instead
-        match create_result {
-            Ok(_) => {
-                // Insert remaining files with the same type overrides
-                for core_file in &core_files[1..] {
-                    let csv_path = core_file
-                        .to_str()
-                        .ok_or(ChuckError::PathEncoding)?;
-
-                    conn.execute(
-                        &format!(
-                            "INSERT INTO occurrences SELECT * FROM read_csv('{csv_path}', all_varchar = true, nullstr = ''{types_param})"
-                        ),
-                        [],
+        // If we've previously created this db file there's nothing to import
+        let newly_created = !Self::table_exists(&conn, "occurrences")?;
+        // Headers of typed columns that had to be read as text
+        let mut relaxed_headers: Vec<String> = Vec::new();
+        if newly_created {
+            let files_done = progress.files_done;
+            loop {
+                // Create the table with specific types for known columns if
+                // they exist. nullstr will treat empty columns as NULL when
+                // converting to boolean
+                let read_options = format!(
+                    ", all_varchar = true, nullstr = ''{}{csv_options}",
+                    read_csv_types(&type_map)
+                );
+                for (i, core_file) in core_files.iter().enumerate() {
+                    Self::import_data_file(
+                        &conn,
+                        "occurrences",
+                        i == 0,
+                        core_file,
+                        core_format,
+                        &read_options,
+                        &mut progress,
                     )?;
                 }
-            },
-            Err(e) => {
-                let error_msg = e.to_string();
-                if error_msg.contains("already exists") || error_msg.contains("Table with name") {
-                    // We've previously created this db file, nothing to do
-                } else {
-                    return Err(e.into());
+                // Rows with a value that can't be cast to its column's type
+                // get skipped, so read those columns as text and start over
+                let uncastable: Vec<String> = import_issues::cast_error_columns(&conn, "occurrences")?
+                    .into_iter()
+                    .filter(|header| type_map.contains_key(header.as_str()))
+                    .collect();
+                if uncastable.is_empty() {
+                    break;
                 }
+                log::warn!("Importing again with {uncastable:?} read as text");
+                import_issues::clear_import_issues(&conn, "occurrences")?;
+                progress.files_done = files_done;
+                for header in &uncastable {
+                    type_map.remove(header.as_str());
+                }
+                relaxed_headers.extend(uncastable);
             }
         }
 
+        let renamed_columns = if newly_created {
+            Self::store_column_terms(&conn, &canonical_names, &header_terms)?;
+            Self::rename_core_columns(&conn, &column_names, &canonical_names)?
+        } else {
+            vec![]
+        };
+        let column_terms = Self::load_column_terms(&conn)?;
+        let relaxed_columns: Vec<&str> = column_names
+            .iter()
+            .zip(&canonical_names)
+            .filter(|(header, _)| relaxed_headers.contains(header))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        let parsed_columns = Self::parse_relaxed_columns(&conn, &relaxed_columns, &column_terms)?;
+
+        // Apply defaults before dropping empty columns so field indexes still
+        // line up and columns that are empty apart from a default are kept
+        Self::apply_field_defaults(&conn, "occurrences", core_defaults)?;
+
+        if let (true, Some(events)) = (newly_created, events) {
+            Self::join_event_core(&conn, events, &mut progress)?;
+        }
+
         // Drop columns that are entirely null or empty strings
-        Self::drop_empty_columns(&conn, core_id_column)?;
+        let mut import_warnings: Vec<ImportWarning> = renamed_columns
+            .into_iter()
+            .map(|(header, name)| ImportWarning::new(
+                ImportWarningKind::RenamedColumn,
+                &header,
+                format!("Column {header} was renamed to {name} to match the Darwin Core term"),
+            ))
+            .collect();
+        import_warnings.extend(parsed_columns.into_iter().map(|parsed| {
+            let kept = match &parsed.verbatim_column {
+                Some(verbatim) => format!("The original values are in {verbatim}."),
+                None => "The original values weren't kept.".to_string(),
+            };
+            ImportWarning::new(
+                ImportWarningKind::RelaxedColumnType,
+                &parsed.column,
+                format!(
+                    "{} values of {} couldn't be read as {} and were left blank. {kept}",
+                    parsed.unparsed, parsed.column, parsed.column_type,
+                ),
+            )
+        }));
+        import_warnings.extend(
+            Self::drop_empty_columns(&conn, core_id_column, &column_terms)?
+                .into_iter()
+                .map(|column| ImportWarning::new(
+                    ImportWarningKind::DroppedEmptyColumn,
+                    &column,
+                    format!("Column {column} has no values and was not imported"),
+                )),
+        );
 
         // Create indices on coordinate columns for fast spatial queries
         // (Do this after dropping columns in case lat/lng were dropped)
@@ -155,8 +817,58 @@ impl Database {
             conn.execute("CREATE INDEX IF NOT EXISTS idx_lng ON occurrences(decimalLongitude)", [])?;
         }
 
+        for sentinel in crate::sentinels::detect_sentinels(&conn, &updated_columns)? {
+            import_warnings.push(ImportWarning::new(
+                ImportWarningKind::SentinelValues,
+                &sentinel.column,
+                format!(
+                    "Column {} has {} values of {}, which usually means the value is unknown",
+                    sentinel.column, sentinel.count, sentinel.value,
+                ),
+            ));
+        }
+
         // Create extension tables
-        let extension_tables = Self::create_extension_tables(&conn, extensions)?;
+        let extension_tables =
+            Self::create_extension_tables(&conn, extensions, &mut import_warnings, &mut progress)?;
+
+        let generated_core_id = match core_id::unusable_reason(&conn, core_id_column)? {
+            Some(reason) if newly_created => {
+                let strategy = core_id::generate_core_ids(&conn, core_id_strategy)?;
+                for (extension, ext_core_id_column) in &extension_tables {
+                    let table_name = extension.table_name();
+                    if !core_id::link_extension(&conn, table_name, ext_core_id_column, core_id_column)? {
+                        log::warn!("Can't match {table_name} rows to occurrences without {core_id_column}");
+                    }
+                }
+                import_warnings.push(ImportWarning::new(
+                    ImportWarningKind::GeneratedCoreId,
+                    core_id_column,
+                    format!("Records were given generated IDs because {reason}"),
+                ));
+                Some(GeneratedCoreId {
+                    strategy,
+                    original_column: core_id_column.to_string(),
+                    reason,
+                })
+            }
+            _ => None,
+        };
+        let (core_id_column, extension_tables) =
+            Self::linked_core_ids(&conn, core_id_column, extension_tables)?;
+
+        for (file, count) in import_issues::count_by_file(&conn)? {
+            import_warnings.push(ImportWarning::new(
+                ImportWarningKind::SkippedRows,
+                &file,
+                format!("{count} rows of {file} couldn't be read and were skipped"),
+            ));
+        }
+
+        let has_time_zone_offsets = updated_columns.contains(&TIME_ZONE_OFFSET_COLUMN.to_string());
+
+        let occurrences_source = crate::overlay::occurrences_source(&conn, &core_id_column)?;
+        crate::archive_stats::store_stats(&conn, &occurrences_source)?;
 
         // Force a WAL checkpoint so all data is written to the main .db file.
         // Without this, the WAL file persists and a subsequent read-only open
@@ -164,11 +876,191 @@ impl Database {
         // replay, because replay requires write access to the .db file.
         conn.execute("CHECKPOINT", [])?;
 
-        Ok(Self { conn, core_id_column: core_id_column.to_string(), extension_tables })
+        Ok(Self {
+            conn,
+            core_id_column,
+            extension_tables,
+            has_time_zone_offsets,
+            has_multi_values: false,
+            has_taxonomy: false,
+            has_flags: false,
+            import_warnings,
+            generated_core_id,
+            filter_cache: None,
+            occurrences_source,
+            column_terms,
+        })
+    }
+
+    /// The columns occurrences and each extension table are joined on: the
+    /// generated ID column where core IDs were generated, otherwise the
+    /// columns from meta.xml
+    fn linked_core_ids(
+        conn: &duckdb::Connection,
+        core_id_column: &str,
+        extension_tables: Vec<(chuck_core::DwcaExtension, String)>,
+    ) -> Result<(String, Vec<(chuck_core::DwcaExtension, String)>)> {
+        let generated = GENERATED_ID_COLUMN.to_string();
+        if !Self::get_column_names(conn, "occurrences")?.contains(&generated) {
+            return Ok((core_id_column.to_string(), extension_tables));
+        }
+        let mut linked = Vec::with_capacity(extension_tables.len());
+        for (extension, ext_core_id_column) in extension_tables {
+            let table_name = extension.table_name();
+            if Self::table_exists(conn, table_name)?
+                && Self::get_column_names(conn, table_name)?.contains(&generated)
+            {
+                linked.push((extension, generated.clone()));
+            } else {
+                linked.push((extension, ext_core_id_column));
+            }
+        }
+        Ok((generated, linked))
+    }
+
+    /// Keeps a big import from taking over the machine by capping DuckDB's
+    /// memory below its default and having it spill to a directory next to
+    /// the database, in the archive's storage directory
+    fn limit_import_resources(conn: &duckdb::Connection, db_path: &Path) -> Result<()> {
+        let default_limit: String =
+            conn.query_row("SELECT current_setting('memory_limit')", [], |row| row.get(0))?;
+        match parse_memory_size(&default_limit) {
+            Some(bytes) => {
+                let limit = bytes as f64 / DUCKDB_DEFAULT_MEMORY_FRACTION * IMPORT_MEMORY_FRACTION;
+                conn.execute_batch(&format!("SET memory_limit = '{}MiB'", limit as u64 >> 20))?;
+            }
+            None => log::warn!("Couldn't parse DuckDB memory limit {default_limit}, leaving it as is"),
+        }
+        if let Some(dir) = db_path.parent() {
+            let temp_dir = dir.join(IMPORT_TEMP_DIR);
+            let temp_dir = temp_dir.to_str().ok_or(ChuckError::PathEncoding)?.replace('\'', "''");
+            conn.execute_batch(&format!("SET temp_directory = '{temp_dir}'"))?;
+        }
+        Ok(())
+    }
+
+    /// Converts occurrences columns that were read as text because some of
+    /// their values couldn't be cast back to the types queries expect of
+    /// them, copying the text to a verbatim column first, e.g.
+    /// decimalLatitude to verbatimDecimalLatitude, unless the archive
+    /// already has one. Values that can't be cast become NULL.
+    fn parse_relaxed_columns(
+        conn: &duckdb::Connection,
+        columns: &[&str],
+        column_terms: &HashMap<String, String>,
+    ) -> Result<Vec<ParsedColumn>> {
+        let existing_columns = Self::get_column_names(conn, "occurrences")?;
+        let mut parsed = Vec::new();
+        for &column in columns {
+            let Some(column_type) = type_override(column_term(column_terms, column)) else {
+                continue;
+            };
+            let quoted_column = Self::quote_identifier(column);
+            let mut chars = column.chars();
+            let verbatim_column = chars
+                .next()
+                .map(|first| format!("verbatim{}{}", first.to_uppercase(), chars.as_str()))
+                .filter(|verbatim| !existing_columns.contains(verbatim));
+            if let Some(verbatim) = &verbatim_column {
+                let quoted_verbatim = Self::quote_identifier(verbatim);
+                conn.execute_batch(&format!(
+                    "ALTER TABLE occurrences ADD COLUMN {quoted_verbatim} VARCHAR; \
+                     UPDATE occurrences SET {quoted_verbatim} = {quoted_column};"
+                ))?;
+            }
+            let unparsed: usize = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM occurrences \
+                     WHERE {quoted_column} IS NOT NULL AND TRY_CAST({quoted_column} AS {column_type}) IS NULL"
+                ),
+                [],
+                |row| row.get(0),
+            )?;
+            conn.execute(
+                &format!(
+                    "ALTER TABLE occurrences ALTER COLUMN {quoted_column} \
+                     SET DATA TYPE {column_type} USING TRY_CAST({quoted_column} AS {column_type})"
+                ),
+                [],
+            )?;
+            log::warn!("Left {unparsed} values of {column} that aren't {column_type} blank");
+            parsed.push(ParsedColumn {
+                column: column.to_string(),
+                column_type,
+                verbatim_column,
+                unparsed,
+            });
+        }
+        Ok(parsed)
+    }
+
+    /// Whether the database has a table by this name
+    fn table_exists(conn: &duckdb::Connection, table_name: &str) -> Result<bool> {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+            [table_name],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Imports a data file into a table a chunk at a time, reporting
+    /// progress after each. With `create`, the table is created, or
+    /// replaced, from the first chunk. `read_options` are appended to the
+    /// read_csv arguments. Rows DuckDB can't read are skipped and recorded
+    /// in the import issues table.
+    fn import_data_file(
+        conn: &duckdb::Connection,
+        table_name: &str,
+        create: bool,
+        path: &Path,
+        format: &CsvFormat,
+        read_options: &str,
+        progress: &mut ProgressReporter,
+    ) -> Result<()> {
+        let file = path
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let total_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+        let mut create = create;
+        for_each_chunk(path, format, CHUNK_BYTES, |chunk, bytes_read, lines_before| {
+            let chunk = chunk.to_str().ok_or(ChuckError::PathEncoding)?;
+            let source = format!("read_csv('{chunk}'{read_options}{STORE_REJECTS_OPTIONS})");
+            import_issues::clear_rejects(conn)?;
+            let sql = if create {
+                format!("CREATE OR REPLACE TABLE {table_name} AS SELECT * FROM {source}")
+            } else {
+                format!("INSERT INTO {table_name} SELECT * FROM {source}")
+            };
+            conn.execute(&sql, [])?;
+            create = false;
+            let rejected = import_issues::record_rejects(conn, &file, table_name, lines_before)?;
+            if rejected > 0 {
+                log::warn!("Skipped {rejected} rows of {file} that couldn't be read");
+            }
+            let rows_inserted: usize = conn.query_row(
+                &format!("SELECT COUNT(*) FROM {table_name}"),
+                [],
+                |row| row.get(0),
+            )?;
+            (progress.on_progress)(&ImportProgress {
+                file: file.clone(),
+                table: table_name.to_string(),
+                bytes_read,
+                total_bytes,
+                rows_inserted,
+                files_done: progress.files_done,
+                total_files: progress.total_files,
+            });
+            Ok(())
+        })?;
+        progress.files_done += 1;
+        Ok(())
     }
 
     /// Helper to get column names for a table
-    fn get_column_names(conn: &duckdb::Connection, table_name: &str) -> Result<Vec<String>> {
+    pub(crate) fn get_column_names(conn: &duckdb::Connection, table_name: &str) -> Result<Vec<String>> {
         let mut stmt = conn.prepare(&format!(
             "SELECT column_name FROM information_schema.columns WHERE table_name = '{table_name}' ORDER BY column_name"
         ))?;
@@ -178,10 +1070,89 @@ impl Database {
         Ok(columns)
     }
 
-    /// Drops columns from the occurrences table that contain only NULL or empty strings
-    fn drop_empty_columns(conn: &duckdb::Connection, core_id_column: &str) -> Result<()> {
+    /// Renames occurrences columns from their CSV headers to the names from
+    /// canonical_column_names and records each rename in the column_renames
+    /// table. Returns the (header, new name) pairs.
+    fn rename_core_columns(
+        conn: &duckdb::Connection,
+        headers: &[String],
+        canonical_names: &[String],
+    ) -> Result<Vec<(String, String)>> {
+        let renames: Vec<(String, String)> = headers
+            .iter()
+            .zip(canonical_names)
+            .filter(|(header, name)| header != name)
+            .map(|(header, name)| (header.clone(), name.clone()))
+            .collect();
+        if renames.is_empty() {
+            return Ok(renames);
+        }
+        conn.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {COLUMN_RENAMES_TABLE} \
+                 (table_name VARCHAR, original_name VARCHAR, canonical_name VARCHAR)"
+            ),
+            [],
+        )?;
+        for (header, name) in &renames {
+            log::info!("Renaming occurrences.\"{header}\" -> \"{name}\"");
+            conn.execute(
+                &format!(
+                    "ALTER TABLE occurrences RENAME COLUMN {} TO {}",
+                    Self::quote_identifier(&header.replace('"', "\"\"")),
+                    Self::quote_identifier(name)
+                ),
+                [],
+            )?;
+            conn.execute(
+                &format!("INSERT INTO {COLUMN_RENAMES_TABLE} VALUES ('occurrences', ?, ?)"),
+                params![header, name],
+            )?;
+        }
+        Ok(renames)
+    }
+
+    /// Records the terms of core columns whose names aren't their terms, so
+    /// they can still be typed by term after import
+    fn store_column_terms(conn: &duckdb::Connection, names: &[String], terms: &[&str]) -> Result<()> {
+        conn.execute(
+            &format!("CREATE TABLE IF NOT EXISTS {COLUMN_TERMS_TABLE} (column_name VARCHAR, term VARCHAR)"),
+            [],
+        )?;
+        for (name, term) in names.iter().zip(terms) {
+            if name != term {
+                conn.execute(
+                    &format!("INSERT INTO {COLUMN_TERMS_TABLE} VALUES (?, ?)"),
+                    params![name, term],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Terms of occurrences columns recorded by store_column_terms. Empty
+    /// for databases created before they were recorded.
+    fn load_column_terms(conn: &duckdb::Connection) -> Result<HashMap<String, String>> {
+        if !Self::table_exists(conn, COLUMN_TERMS_TABLE)? {
+            return Ok(HashMap::new());
+        }
+        let mut stmt = conn.prepare(&format!("SELECT column_name, term FROM {COLUMN_TERMS_TABLE}"))?;
+        let column_terms = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<HashMap<_, _>, _>>()?;
+        Ok(column_terms)
+    }
+
+    /// Drops columns from the occurrences table that contain only NULL or
+    /// empty strings, returning the names of the dropped columns
+    fn drop_empty_columns(
+        conn: &duckdb::Connection,
+        core_id_column: &str,
+        column_terms: &HashMap<String, String>,
+    ) -> Result<Vec<String>> {
         // Get all column names
         let column_names = Self::get_column_names(conn, "occurrences")?;
+        let mut dropped = Vec::new();
 
         // Check each column to see if it's entirely empty
         for column_name in &column_names {
@@ -191,14 +1162,10 @@ impl Database {
             }
 
             // Get the column type to determine how to check for emptiness
-            let type_override = TYPE_OVERRIDES.iter()
-                .find(|(col, _)| col == &column_name.as_str())
-                .map(|(_, typ)| *typ);
-
             // Quote column name to handle reserved keywords like "order"
             let quoted_column = format!("\"{column_name}\"");
 
-            let query = match type_override {
+            let query = match type_override(column_term(column_terms, column_name)) {
                 Some("DOUBLE") | Some("BIGINT") => {
                     // For numeric types, just check for NULL
                     format!("SELECT COUNT(*) FROM occurrences WHERE {quoted_column} IS NOT NULL")
@@ -225,16 +1192,19 @@ impl Database {
             if count == 0 {
                 log::info!("Dropping empty column: {column_name}");
                 conn.execute(&format!("ALTER TABLE occurrences DROP COLUMN {quoted_column}"), [])?;
+                dropped.push(column_name.clone());
             }
         }
 
-        Ok(())
+        Ok(dropped)
     }
 
     /// Creates tables for DarwinCore Archive extensions
     fn create_extension_tables(
         conn: &duckdb::Connection,
         extensions: &[ExtensionInfo],
+        warnings: &mut Vec<ImportWarning>,
+        progress: &mut ProgressReporter,
     ) -> Result<Vec<(chuck_core::DwcaExtension, String)>> {
         let mut created_tables = Vec::new();
 
@@ -245,6 +1215,18 @@ impl Database {
                     "Extension file does not exist: {}. Skipping.",
                     ext.location.display()
                 );
+                let file_name = ext.location
+                    .file_name()
+                    .map(|f| f.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                warnings.push(ImportWarning::new(
+                    ImportWarningKind::SkippedExtensionFile,
+                    &file_name,
+                    format!(
+                        "Extension file {file_name} listed in meta.xml is missing, so {} data is unavailable",
+                        ext.extension.table_name()
+                    ),
+                ));
                 continue;
             }
 
@@ -254,85 +1236,64 @@ impl Database {
                 .ok_or(ChuckError::PathEncoding)?;
 
             // Sniff the CSV to get column names
+            let csv_options = ext.format.read_csv_options();
             let mut stmt = conn.prepare(&format!(
-                "SELECT unnest(Columns).name FROM sniff_csv('{csv_path}')"
+                "SELECT unnest(Columns).name FROM sniff_csv('{csv_path}'{csv_options})"
             ))?;
             let column_names: Vec<String> = stmt
                 .query_map([], |row| row.get(0))?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
 
-            // Check if extension's core_id_column is in TYPE_OVERRIDES
-            if TYPE_OVERRIDES.iter().any(|(col, _)| col == &ext.core_id_column.as_str()) {
+            // Check if extension's core_id_column has a type override
+            if type_override(&ext.core_id_column).is_some() {
                 return Err(ChuckError::CoreIdTypeOverride(ext.core_id_column.clone()));
             }
 
-            // Apply type overrides for known numeric/date columns
-            let type_map: HashMap<&str, &str> = TYPE_OVERRIDES
-                .iter()
-                .filter(|(col, _)| column_names.contains(&col.to_string()))
-                .copied()
-                .collect();
-
-            let types_param = if type_map.is_empty() {
-                String::new()
-            } else {
-                let pairs: Vec<String> = type_map
-                    .iter()
-                    .map(|(col, typ)| format!("'{col}': '{typ}'"))
-                    .collect();
-                format!(", types = {{{}}}", pairs.join(", "))
-            };
+            // Apply type overrides for known numeric/boolean columns, by the
+            // terms meta.xml declares for them since they're renamed after
+            let types_param = read_csv_types(&type_overrides(
+                &column_names,
+                &declared_terms(&column_names, &ext.fields),
+            ));
 
-            // Try to create the table
             let table_name = ext.extension.table_name();
-            let sql = format!(
-                "CREATE TABLE {table_name} AS SELECT * FROM read_csv('{csv_path}', all_varchar = true, nullstr = ''{types_param})"
-            );
-
-            let create_result = conn.execute(&sql, []);
-
-            match create_result {
-                Ok(_) => {
-                    // Rename columns to canonical term names from meta.xml
-                    Self::rename_extension_columns(conn, table_name, &ext.fields)?;
-                    log::info!(
-                        "Created extension table: {} (joins on {})",
-                        table_name, ext.core_id_column
-                    );
-                    created_tables.push((ext.extension, ext.core_id_column.clone()));
-                }
-                Err(e) => {
-                    let error_msg = e.to_string();
-                    if error_msg.contains("already exists")
-                        || error_msg.contains("Table with name")
-                    {
-                        log::info!("Extension table already exists: {table_name}");
-                        created_tables.push((ext.extension, ext.core_id_column.clone()));
-                    } else {
-                        log::error!(
-                            "Failed to create extension table {table_name}: {e}"
-                        );
-                        return Err(e.into());
-                    }
-                }
+            if Self::table_exists(conn, table_name)? {
+                log::info!("Extension table already exists: {table_name}");
+                created_tables.push((ext.extension, ext.core_id_column.clone()));
+                continue;
             }
+
+            let read_options = format!(", all_varchar = true, nullstr = ''{types_param}{csv_options}");
+            Self::import_data_file(conn, table_name, true, &ext.location, &ext.format, &read_options, progress)
+                .inspect_err(|e| log::error!("Failed to create extension table {table_name}: {e}"))?;
+            // Rename columns to canonical term names from meta.xml
+            Self::rename_extension_columns(conn, table_name, &ext.fields)?;
+            Self::rename_extension_core_id_column(conn, table_name, ext)?;
+            Self::apply_field_defaults(conn, table_name, &ext.defaults)?;
+            log::info!(
+                "Created extension table: {} (joins on {})",
+                table_name, ext.core_id_column
+            );
+            created_tables.push((ext.extension, ext.core_id_column.clone()));
         }
 
         Ok(created_tables)
     }
 
-    /// Renames extension table columns from CSV headers to canonical term names
-    /// based on field declarations from meta.xml
-    fn rename_extension_columns(
+    /// Names the column at an extension's `<coreid>` index after the
+    /// extension's join column. meta.xml doesn't always declare a `<field>`
+    /// at that index, in which case the CSV header can be anything (e.g. "id")
+    /// and wouldn't match the join column. Tables that already have a column
+    /// by that name are left alone, since old Chuck archives have a blank
+    /// coreid column alongside a populated occurrenceID.
+    fn rename_extension_core_id_column(
         conn: &duckdb::Connection,
         table_name: &str,
-        fields: &[(usize, String)],
+        ext: &ExtensionInfo,
     ) -> Result<()> {
-        if fields.is_empty() {
+        let Some(core_id_index) = ext.core_id_index else {
             return Ok(());
-        }
-
-        // Get actual column names by position using PRAGMA table_info
+        };
         let mut stmt = conn.prepare(
             &format!("PRAGMA table_info('{table_name}')")
         )?;
@@ -341,7 +1302,183 @@ impl Database {
                 Ok((row.get::<_, usize>(0)?, row.get::<_, String>(1)?))
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
-
+        if columns.iter().any(|(_, name)| *name == ext.core_id_column) {
+            return Ok(());
+        }
+        let Some((_, current_name)) = columns.iter().find(|(idx, _)| *idx == core_id_index) else {
+            log::warn!(
+                "{table_name} has no column at coreid index {core_id_index}, so it can't be joined on {}",
+                ext.core_id_column
+            );
+            return Ok(());
+        };
+        log::info!(
+            "Renaming {table_name}.\"{current_name}\" -> \"{}\" to join on it",
+            ext.core_id_column
+        );
+        conn.execute(
+            &format!(
+                "ALTER TABLE {table_name} RENAME COLUMN {} TO {}",
+                Self::quote_identifier(current_name),
+                Self::quote_identifier(&ext.core_id_column),
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// SQL type for a column, from its type override or VARCHAR
+    fn column_type(column: &str) -> &'static str {
+        type_override(column).unwrap_or("VARCHAR")
+    }
+
+    /// SQL expression and type to filter and sort a column by, from the
+    /// term it holds. Columns with a type override are already typed, other
+    /// numeric terms are cast, and anything else is compared as text (None).
+    fn typed_column(column: &str, column_terms: &HashMap<String, String>) -> (String, Option<&'static str>) {
+        let quoted = Self::quote_identifier(column);
+        let term = column_term(column_terms, column);
+        if let Some(typ) = type_override(term) {
+            (quoted, Some(typ))
+        } else if term_type(term).is_numeric() {
+            (format!("TRY_CAST({quoted} AS DOUBLE)"), Some("DOUBLE"))
+        } else {
+            (quoted, None)
+        }
+    }
+
+    /// Loads Event core files into an events table and copies each event's
+    /// fields onto its occurrences. Occurrence values win over event values,
+    /// so e.g. an occurrence's own eventDate is kept.
+    fn join_event_core(
+        conn: &duckdb::Connection,
+        events: &EventCoreInfo,
+        progress: &mut ProgressReporter,
+    ) -> Result<()> {
+        let read_options = format!(", all_varchar = true, nullstr = ''{}", events.format.read_csv_options());
+        for (i, event_file) in events.files.iter().enumerate() {
+            Self::import_data_file(conn, "events", i == 0, event_file, &events.format, &read_options, progress)?;
+        }
+
+        let occurrence_columns = Self::get_column_names(conn, "occurrences")?;
+        if !occurrence_columns.contains(&events.occurrence_event_id_column) {
+            return Err(ChuckError::CsvColumnNotFound(events.occurrence_event_id_column.clone()));
+        }
+        let event_columns = Self::get_column_names(conn, "events")?;
+        if !event_columns.contains(&events.id_column) {
+            return Err(ChuckError::CsvColumnNotFound(events.id_column.clone()));
+        }
+
+        let mut assignments = Vec::new();
+        for column in &event_columns {
+            if *column == events.occurrence_event_id_column {
+                continue;
+            }
+            let quoted_column = Self::quote_identifier(column);
+            let column_type = Self::column_type(column);
+            let event_value = format!("TRY_CAST(events.{quoted_column} AS {column_type})");
+            if occurrence_columns.contains(column) {
+                assignments.push(format!(
+                    "{quoted_column} = COALESCE(occurrences.{quoted_column}, {event_value})"
+                ));
+            } else {
+                conn.execute(
+                    &format!("ALTER TABLE occurrences ADD COLUMN {quoted_column} {column_type}"),
+                    [],
+                )?;
+                assignments.push(format!("{quoted_column} = {event_value}"));
+            }
+        }
+        if assignments.is_empty() {
+            return Ok(());
+        }
+
+        conn.execute(
+            &format!(
+                "UPDATE occurrences SET {} FROM events WHERE occurrences.{} = events.{}",
+                assignments.join(", "),
+                Self::quote_identifier(&events.occurrence_event_id_column),
+                Self::quote_identifier(&events.id_column),
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Applies meta.xml field defaults to a table. Defaults for indexed fields
+    /// fill in empty values in the column at that index, and defaults without
+    /// an index become constant columns named after the term. Values are cast
+    /// to the term's type override, if any.
+    fn apply_field_defaults(
+        conn: &duckdb::Connection,
+        table_name: &str,
+        defaults: &[FieldDefault],
+    ) -> Result<()> {
+        if defaults.is_empty() {
+            return Ok(());
+        }
+
+        let mut stmt = conn.prepare(
+            &format!("PRAGMA table_info('{table_name}')")
+        )?;
+        let columns: Vec<(usize, String)> = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, usize>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for default in defaults {
+            let column_name = default.index
+                .and_then(|index| columns.iter().find(|(idx, _)| *idx == index))
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| default.term_name.clone());
+            let column_type = Self::column_type(&default.term_name);
+            let quoted_column = Self::quote_identifier(&column_name);
+
+            if !columns.iter().any(|(_, name)| *name == column_name) {
+                log::info!("Adding {table_name}.{quoted_column} with default \"{}\"", default.value);
+                conn.execute(
+                    &format!("ALTER TABLE {table_name} ADD COLUMN {quoted_column} {column_type}"),
+                    [],
+                )?;
+            }
+            let empty_condition = if column_type == "VARCHAR" {
+                format!("{quoted_column} IS NULL OR {quoted_column} = ''")
+            } else {
+                format!("{quoted_column} IS NULL")
+            };
+            conn.execute(
+                &format!(
+                    "UPDATE {table_name} SET {quoted_column} = TRY_CAST(? AS {column_type}) WHERE {empty_condition}"
+                ),
+                [&default.value],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Renames extension table columns from CSV headers to canonical term names
+    /// based on field declarations from meta.xml
+    fn rename_extension_columns(
+        conn: &duckdb::Connection,
+        table_name: &str,
+        fields: &[(usize, String)],
+    ) -> Result<()> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+
+        // Get actual column names by position using PRAGMA table_info
+        let mut stmt = conn.prepare(
+            &format!("PRAGMA table_info('{table_name}')")
+        )?;
+        let columns: Vec<(usize, String)> = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, usize>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
         for (field_index, term_name) in fields {
             if let Some((_, current_name)) = columns
                 .iter()
@@ -394,21 +1531,63 @@ impl Database {
             .iter()
             .map(|ext| (ext.extension, ext.core_id_column.clone()))
             .collect();
+        let (core_id_column, extension_tables) =
+            Self::linked_core_ids(&conn, &core_id_column, extension_tables)?;
+
+        let has_time_zone_offsets = Self::get_column_names(&conn, "occurrences")?
+            .contains(&TIME_ZONE_OFFSET_COLUMN.to_string());
+        let has_multi_values = crate::multi_value::has_multi_values(&conn)?;
+        let has_taxonomy = crate::taxonomy::has_taxonomy(&conn)?;
+        let has_flags = crate::flags::has_flags(&conn)?;
+        let occurrences_source = crate::overlay::occurrences_source(&conn, &core_id_column)?;
+        let column_terms = Self::load_column_terms(&conn)?;
+
+        Ok(Self {
+            conn,
+            core_id_column,
+            extension_tables,
+            has_time_zone_offsets,
+            has_multi_values,
+            has_taxonomy,
+            has_flags,
+            import_warnings: vec![],
+            generated_core_id: None,
+            filter_cache: None,
+            occurrences_source,
+            column_terms,
+        })
+    }
+
+    /// Returns non-fatal problems from creating the database
+    pub fn import_warnings(&self) -> &[ImportWarning] {
+        &self.import_warnings
+    }
+
+    /// Returns how core IDs were generated while creating the database, if
+    /// they were
+    pub fn generated_core_id(&self) -> Option<&GeneratedCoreId> {
+        self.generated_core_id.as_ref()
+    }
 
-        Ok(Self { conn, core_id_column, extension_tables })
+    /// Column occurrences are identified by, which is the generated ID
+    /// column if core IDs were generated
+    pub fn core_id_column(&self) -> &str {
+        &self.core_id_column
     }
 
     /// Counts the number of observations in the database
     pub fn count_records(&self) -> Result<usize> {
+        let occurrences = &self.occurrences_source;
         let count: usize = self.conn.query_row(
-            "SELECT COUNT(*) FROM occurrences",
+            &format!("SELECT COUNT(*) FROM {occurrences}"),
             [],
             |row| row.get(0),
         )?;
         Ok(count)
     }
 
-    /// Returns a list of all column names in the occurrences table
+    /// Returns a list of all column names in the occurrences table, plus
+    /// columns only enrichments have filled
     pub fn get_available_columns(&self) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(
             "SELECT column_name FROM information_schema.columns \
@@ -416,10 +1595,17 @@ impl Database {
              ORDER BY column_name"
         )?;
 
-        let columns: Vec<String> = stmt.query_map([], |row| {
+        let mut columns: Vec<String> = stmt.query_map([], |row| {
             row.get(0)
         })?.collect::<std::result::Result<Vec<_>, _>>()?;
 
+        let derived_columns = crate::enrichment::derived_columns(&self.conn)?;
+        if derived_columns.iter().any(|c| !columns.contains(c)) {
+            columns.extend(derived_columns);
+            columns.sort();
+            columns.dedup();
+        }
+
         Ok(columns)
     }
 
@@ -428,21 +1614,177 @@ impl Database {
         &self.conn
     }
 
+    /// FROM item for reading occurrences with overlays like hand edits
+    /// applied, named occurrences so queries can refer to its columns that way
+    pub fn occurrences_source(&self) -> &str {
+        &self.occurrences_source
+    }
+
     /// Returns the extension table metadata
     pub fn extension_tables(&self) -> &[(chuck_core::DwcaExtension, String)] {
         &self.extension_tables
     }
 
+    /// Whether eventDate filters should account for eventTimeZoneOffset
+    pub fn has_time_zone_offsets(&self) -> bool {
+        self.has_time_zone_offsets
+    }
+
+    /// Whether multi-value filters can use the multi_values table
+    pub fn has_multi_values(&self) -> bool {
+        self.has_multi_values
+    }
+
+    /// Whether higher taxon filters can use the taxon_ancestors table
+    pub fn has_taxonomy(&self) -> bool {
+        self.has_taxonomy
+    }
+
+    /// Whether flagged filters can use the flags table
+    pub fn has_flags(&self) -> bool {
+        self.has_flags
+    }
+
+    /// Moves filters and sorting on fields the archive doesn't have to a
+    /// synonymous column it does, e.g. countryCode to country, so they
+    /// don't silently match nothing. Names also get the column's casing.
+    pub fn with_field_aliases(&self, search_params: SearchParams) -> SearchParams {
+        match self.get_available_columns() {
+            Ok(columns) => alias_fields(search_params, &columns),
+            Err(_) => search_params,
+        }
+    }
+
+    /// Whether a column can be filtered, sorted, or aggregated by: any
+    /// column the occurrences table has
+    fn is_searchable_field(&self, name: &str) -> Result<bool> {
+        Ok(self.get_available_columns()?.iter().any(|c| c == name))
+    }
+
+    /// Keeps materialized filters in `dir`, see materialize_filter
+    pub fn with_filter_cache(mut self, dir: PathBuf, db_path: &Path) -> Self {
+        self.filter_cache = Some(FilterCache::new(dir, db_path));
+        self
+    }
+
+    /// Writes the core IDs matching the filters in `search_params` to the
+    /// filter cache, replacing any earlier filter's, so later queries with
+    /// the same filters read them instead of running the WHERE clause
+    /// again. Does nothing without a filter cache or if the filters are
+    /// already materialized. Returns the number of matching occurrences.
+    pub fn materialize_filter(&self, search_params: SearchParams) -> Result<usize> {
+        let occurrences = &self.occurrences_source;
+        let Some(filter_cache) = &self.filter_cache else {
+            return Ok(0);
+        };
+        let columns = self.get_available_columns()?;
+        let search_params = alias_fields(search_params, &columns);
+        if !is_filtered(&search_params) {
+            return Ok(self.conn.query_row(&format!("SELECT COUNT(*) FROM {occurrences}"), [], |row| row.get(0))?);
+        }
+        if let Some(path) = filter_cache.get(&search_params) {
+            let path = path.to_str().ok_or(ChuckError::PathEncoding)?.replace('\'', "''");
+            return Ok(self.conn.query_row(
+                &format!("SELECT COUNT(*) FROM read_parquet('{path}')"),
+                [],
+                |row| row.get(0),
+            )?);
+        }
+
+        let (temp_path, path) = filter_cache.prepare(&search_params)?;
+        let (_, where_clause, where_interpolations, _) = Self::sql_parts(
+            search_params,
+            None,
+            &self.core_id_column,
+            &[],
+            self.has_time_zone_offsets,
+            self.has_multi_values,
+            self.has_taxonomy,
+            self.has_flags,
+            &columns,
+            &self.column_terms,
+        );
+        let quoted_core_id = Self::quote_identifier(&self.core_id_column);
+        let temp = temp_path.to_str().ok_or(ChuckError::PathEncoding)?.replace('\'', "''");
+        let copy_query = format!(
+            "COPY (SELECT {quoted_core_id} AS id FROM {occurrences}{where_clause}) \
+             TO '{temp}' (FORMAT parquet)"
+        );
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let count = self.conn.execute(&copy_query, param_refs.as_slice())?;
+        FilterCache::commit(&temp_path, &path)?;
+        Ok(count)
+    }
+
+    /// sql_parts for this database's columns, with filters on synonymous
+    /// fields resolved first. If the filters have been materialized, the
+    /// WHERE clause looks up the matching core IDs instead.
+    pub fn query_parts(
+        &self,
+        search_params: SearchParams,
+        fields: Option<Vec<String>>,
+        core_id_column: &str,
+        extension_tables: &[(chuck_core::DwcaExtension, String)],
+    ) -> Result<(String, String, Vec<Box<dyn duckdb::ToSql>>, String)> {
+        let columns = self.get_available_columns()?;
+        let search_params = alias_fields(search_params, &columns);
+        let materialized = self
+            .filter_cache
+            .as_ref()
+            .and_then(|filter_cache| filter_cache.get(&search_params))
+            .and_then(|path| path.to_str().map(|path| path.replace('\'', "''")));
+        if let Some(path) = materialized {
+            let unfiltered = SearchParams {
+                sort_by: search_params.sort_by,
+                sort_direction: search_params.sort_direction,
+                ..SearchParams::default()
+            };
+            let (select_fields, _, _, order_clause) = Self::sql_parts(
+                unfiltered,
+                fields,
+                core_id_column,
+                extension_tables,
+                self.has_time_zone_offsets,
+                self.has_multi_values,
+                self.has_taxonomy,
+                self.has_flags,
+                &columns,
+                &self.column_terms,
+            );
+            let where_clause = format!(
+                " WHERE {} IN (SELECT id FROM read_parquet('{path}'))",
+                Self::quote_identifier(&self.core_id_column)
+            );
+            return Ok((select_fields, where_clause, Vec::new(), order_clause));
+        }
+        Ok(Self::sql_parts(
+            search_params,
+            fields,
+            core_id_column,
+            extension_tables,
+            self.has_time_zone_offsets,
+            self.has_multi_values,
+            self.has_taxonomy,
+            self.has_flags,
+            &columns,
+            &self.column_terms,
+        ))
+    }
+
     /// Returns the set of core IDs matching the given search params (for export filtering)
     pub(crate) fn query_matching_ids(
         &self,
         search_params: SearchParams,
     ) -> crate::error::Result<std::collections::HashSet<String>> {
+        let occurrences = &self.occurrences_source;
         let (_, where_clause, where_interpolations, _) =
-            Self::sql_parts(search_params, None, &self.core_id_column, &[]);
+            self.query_parts(search_params, None, &self.core_id_column, &[])?;
 
         let quoted = Self::quote_identifier(&self.core_id_column);
-        let query = format!("SELECT {quoted} FROM occurrences{where_clause}");
+        let query = format!("SELECT {quoted} FROM {occurrences}{where_clause}");
 
         let mut stmt = self.conn.prepare(&query)?;
         let param_refs: Vec<&dyn duckdb::ToSql> =
@@ -523,22 +1865,31 @@ impl Database {
 
     /// Quotes an identifier for use in SQL queries to handle reserved keywords
     /// like "order", "class", "type", etc.
-    fn quote_identifier(identifier: &str) -> String {
-        format!("\"{identifier}\"")
+    pub(crate) fn quote_identifier(identifier: &str) -> String {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn sql_parts(
         search_params: SearchParams,
         fields: Option<Vec<String>>,
         core_id_column: &str,
         // extension_tables: &Vec<(chuck_core::DwcaExtension, String)>,
         extension_tables: &[(chuck_core::DwcaExtension, String)],
+        has_time_zone_offsets: bool,
+        has_multi_values: bool,
+        has_taxonomy: bool,
+        has_flags: bool,
+        searchable_columns: &[String],
+        column_terms: &HashMap<String, String>,
     ) -> (String, String, Vec<Box<dyn duckdb::ToSql>>, String) {
+        let is_searchable_field = |name: &str| searchable_columns.iter().any(|c| c == name);
+
         // Validate and filter requested fields against allowlist
         let core_select_fields = if let Some(ref requested) = fields {
             let validated: Vec<&str> = requested
                 .iter()
-                .filter(|f| Occurrence::FIELD_NAMES.contains(&f.as_str()))
+                .filter(|f| is_searchable_field(f.as_str()))
                 .map(|s| s.as_str())
                 .collect();
 
@@ -583,59 +1934,48 @@ impl Database {
 
         let range_suffixes = ["_min", "_max", "_include_blank"];
         for (column_name, filter_value) in &search_params.filters {
-            // Skip range-filter keys; handled in a second pass
-            if range_suffixes.iter().any(|s| column_name.ends_with(s)) {
+            // Skip range-filter and multi-value keys; handled in later passes
+            if range_suffixes.iter().any(|s| column_name.ends_with(s))
+                || column_name.ends_with(INCLUDES_FILTER_SUFFIX)
+            {
                 continue;
             }
             // Validate column name against allowlist
-            if Occurrence::FIELD_NAMES.contains(&column_name.as_str()) {
-                // Check if this column has a type override
-                let type_override = TYPE_OVERRIDES.iter()
-                    .find(|(col, _)| col == &column_name.as_str())
-                    .map(|(_, typ)| *typ);
-
-                match type_override {
-                    Some("DOUBLE") | Some("BIGINT") => {
+            if is_searchable_field(column_name.as_str()) {
+                // Check if this column should be compared as a type. Subsets
+                // match the same way, see chuck_core::filter_match.
+                let (typed, column_type) = Self::typed_column(column_name, column_terms);
+                let quoted = Self::quote_identifier(column_name);
+
+                match FilterMatch::new(column_type, filter_value) {
+                    Some(FilterMatch::Range(lower_bound, upper_bound)) => {
                         // For numeric types, use range matching (e.g., "3" matches 3.0 to 3.9999...)
-                        if let Ok(lower_bound) = filter_value.parse::<f64>() {
-                            // Calculate upper bound by incrementing at the precision level
-                            // "3" -> [3.0, 4.0), "3.1" -> [3.1, 3.2), "3.14" -> [3.14, 3.15)
-                            let decimal_places = filter_value
-                                .split('.')
-                                .nth(1)
-                                .map(|s| s.len())
-                                .unwrap_or(0);
-                            let increment = 10_f64.powi(-(decimal_places as i32));
-                            let upper_bound = lower_bound + increment;
-
-                            let quoted = Self::quote_identifier(column_name);
-                            where_clauses.push(format!("{quoted} >= ? AND {quoted} < ?"));
-                            where_interpolations.push(Box::new(lower_bound));
-                            where_interpolations.push(Box::new(upper_bound));
-                        }
-                        // If parse fails, skip this filter
+                        where_clauses.push(format!("{typed} >= ? AND {typed} < ?"));
+                        where_interpolations.push(Box::new(lower_bound));
+                        where_interpolations.push(Box::new(upper_bound));
                     }
-                    Some("DATE") => {
+                    Some(FilterMatch::Prefix(prefix)) => {
                         // For date types, cast to string and use prefix matching
-                        let quoted = Self::quote_identifier(column_name);
                         where_clauses.push(format!("CAST({quoted} AS VARCHAR) LIKE ?"));
-                        where_interpolations.push(Box::new(format!("{filter_value}%")));
+                        where_interpolations.push(Box::new(format!("{prefix}%")));
                     }
-                    Some("BOOLEAN") => {
+                    Some(FilterMatch::Boolean(value)) => {
                         // For boolean types, compare directly to TRUE or FALSE
-                        let quoted = Self::quote_identifier(column_name);
-                        match filter_value.to_lowercase().as_str() {
-                            "true" => where_clauses.push(format!("{quoted} = TRUE")),
-                            "false" => where_clauses.push(format!("{quoted} = FALSE")),
-                            _ => {} // Skip invalid boolean filter values
-                        }
+                        where_clauses.push(format!("{typed} = {}", if value { "TRUE" } else { "FALSE" }));
+                    }
+                    Some(FilterMatch::Contains(part)) if column_name == "eventDate" && has_time_zone_offsets => {
+                        // Match against the local date so UTC datetimes
+                        // don't fall on the wrong side of midnight
+                        where_clauses.push(format!("{LOCAL_EVENT_DATE_SQL} ILIKE ?"));
+                        where_interpolations.push(Box::new(format!("%{part}%")));
                     }
-                    _ => {
+                    Some(FilterMatch::Contains(part)) => {
                         // For VARCHAR (default), use ILIKE with substring matching
-                        let quoted = Self::quote_identifier(column_name);
                         where_clauses.push(format!("{quoted} ILIKE ?"));
-                        where_interpolations.push(Box::new(format!("%{filter_value}%")));
+                        where_interpolations.push(Box::new(format!("%{part}%")));
                     }
+                    // Skip values that can't match the type, e.g. "abc" for a number
+                    None => {}
                 }
             }
         }
@@ -646,7 +1986,7 @@ impl Database {
         for key in search_params.filters.keys() {
             for suffix in &range_suffixes {
                 if let Some(base) = key.strip_suffix(suffix) {
-                    if Occurrence::FIELD_NAMES.contains(&base) {
+                    if is_searchable_field(base) {
                         range_columns.insert(base.to_string());
                     }
                 }
@@ -699,6 +2039,67 @@ impl Database {
             }
         }
 
+        // Third pass: "recordedBy_includes" style filters match one of the
+        // individual values of a multi-value field, case-insensitively
+        for (key, filter_value) in &search_params.filters {
+            let Some(base_col) = key.strip_suffix(INCLUDES_FILTER_SUFFIX) else {
+                continue;
+            };
+            if !crate::multi_value::is_multi_value_field(base_col) {
+                continue;
+            }
+            if has_multi_values {
+                where_clauses.push(format!(
+                    "{} IN (SELECT core_id FROM {} WHERE field = ? AND lower(value) = lower(?))",
+                    Self::quote_identifier(core_id_column),
+                    crate::multi_value::MULTI_VALUES_TABLE,
+                ));
+                where_interpolations.push(Box::new(base_col.to_string()));
+            } else {
+                let split = crate::multi_value::split_values_sql(
+                    &format!("lower(trim({}))", Self::quote_identifier(base_col))
+                );
+                where_clauses.push(format!("list_contains({split}, lower(?))"));
+            }
+            where_interpolations.push(Box::new(filter_value.trim().to_string()));
+        }
+
+        // Fourth pass: "higherTaxon" matches records identified as the taxon
+        // or any of its descendants. Without a loaded taxonomy only the taxon
+        // itself can match.
+        if let Some(taxon) = search_params.filters.get(crate::taxonomy::HIGHER_TAXON_FILTER) {
+            if has_taxonomy {
+                where_clauses.push(format!(
+                    "lower(scientificName) IN (SELECT name FROM {} WHERE ancestor = lower(?))",
+                    crate::taxonomy::TAXON_ANCESTORS_TABLE,
+                ));
+            } else {
+                where_clauses.push("lower(scientificName) = lower(?)".to_string());
+            }
+            where_interpolations.push(Box::new(taxon.trim().to_string()));
+        }
+
+        // Fifth pass: "flagged" matches occurrences flagged with a label, any
+        // flag for "true", or no flag for "false"
+        if let Some(flag) = search_params.filters.get(crate::flags::FLAGGED_FILTER) {
+            let flagged_ids = format!(
+                "{} IN (SELECT core_id FROM {}",
+                Self::quote_identifier(core_id_column),
+                crate::flags::FLAGS_TABLE,
+            );
+            match (flag.trim().to_lowercase().as_str(), has_flags) {
+                ("false", false) => {}
+                ("false", true) => where_clauses.push(format!("NOT {flagged_ids})")),
+                ("true", true) => where_clauses.push(format!("{flagged_ids})")),
+                (_, true) => {
+                    where_clauses.push(format!("{flagged_ids} WHERE lower(flag) = lower(?))"));
+                    where_interpolations.push(Box::new(flag.trim().to_string()));
+                }
+                // Nothing has been flagged yet
+                (_, false) => where_clauses.push("FALSE".to_string()),
+            }
+        }
+
         // Handle bounding box parameters (all four must be present)
         if let (Some(nelat), Some(nelng), Some(swlat), Some(swlng)) =
             (&search_params.nelat, &search_params.nelng, &search_params.swlat, &search_params.swlng) {
@@ -724,29 +2125,46 @@ impl Database {
         };
 
         // Build ORDER clause
-        let order_clause = if let Some(sort_by) = search_params.sort_by {
-            if Occurrence::FIELD_NAMES.contains(&sort_by.as_str()) {
-                let direction = search_params.sort_direction
-                    .as_ref()
-                    .and_then(|d| {
-                        let upper = d.to_uppercase();
-                        if upper == "ASC" || upper == "DESC" {
-                            Some(upper)
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "ASC".to_string());
-                format!(" ORDER BY {} {}", Self::quote_identifier(&sort_by), direction)
-            } else {
-                String::new()
-            }
-        } else {
+        let order_terms: Vec<String> = Self::order_columns(&search_params, searchable_columns)
+            .into_iter()
+            .map(|(column, direction)| {
+                // Numeric terms stored as text need the cast to sort as
+                // numbers, e.g. 9 before 10
+                let (typed, _) = Self::typed_column(&column, column_terms);
+                format!("{typed} {direction}")
+            })
+            .collect();
+        let order_clause = if order_terms.is_empty() {
             String::new()
+        } else {
+            format!(" ORDER BY {}", order_terms.join(", "))
         };
         (select_fields, where_clause, where_interpolations, order_clause)
     }
 
+    /// Columns to sort by as validated (column, direction) pairs, skipping
+    /// unknown and repeated columns. Directions other than DESC are ASC.
+    fn order_columns(
+        search_params: &SearchParams,
+        searchable_columns: &[String],
+    ) -> Vec<(String, &'static str)> {
+        let mut order_columns: Vec<(String, &'static str)> = Vec::new();
+        for (column, direction) in search_params.sort_columns() {
+            if !searchable_columns.contains(&column)
+                || order_columns.iter().any(|(sorted, _)| *sorted == column)
+            {
+                continue;
+            }
+            let direction = if direction.is_some_and(|d| d.eq_ignore_ascii_case("DESC")) {
+                "DESC"
+            } else {
+                "ASC"
+            };
+            order_columns.push((column, direction));
+        }
+        order_columns
+    }
+
     /// Searches for occurrences, returning up to the specified limit starting at offset
     pub fn search(
         &self,
@@ -755,20 +2173,82 @@ impl Database {
         search_params: SearchParams,
         fields: Option<Vec<String>>,
     ) -> Result<crate::commands::archive::SearchResult> {
+        self.search_with_execution_info(limit, offset, None, search_params, fields, false)
+    }
+
+    /// Like search, but with `debug` also reports timing and DuckDB's plan
+    /// for the select query in the result's execution field.
+    ///
+    /// Given the next_cursor of a previous page, returns the rows after that
+    /// page instead of skipping `offset` rows. OFFSET makes DuckDB read and
+    /// discard every skipped row, so deep pages of big archives get slow;
+    /// the cursor's keyset of sort values and core ID doesn't. Pages only
+    /// come with a next_cursor when they're sorted, since the core ID
+    /// tiebreaker cursors need means sorting the whole filtered set; unsorted
+    /// pages keep the archive's own order.
+    pub fn search_with_execution_info(
+        &self,
+        limit: usize,
+        offset: usize,
+        cursor: Option<&str>,
+        search_params: SearchParams,
+        fields: Option<Vec<String>>,
+        debug: bool,
+    ) -> Result<crate::commands::archive::SearchResult> {
+        let occurrences = &self.occurrences_source;
+        let started = std::time::Instant::now();
+
+        let columns = self.get_available_columns()?;
+        let mut keyset = Self::order_columns(&alias_fields(search_params.clone(), &columns), &columns);
+        let keyed = cursor.is_some() || !keyset.is_empty();
+
         let (
             select_fields,
             where_clause,
             mut where_interpolations,
             order_clause
-        ) = Self::sql_parts(
+        ) = self.query_parts(
             search_params,
             fields,
             &self.core_id_column,
-            self.extension_tables.as_ref()
-        );
+            self.extension_tables.as_ref(),
+        )?;
+
+        // Sorted pages sort by the requested columns, then the core ID so
+        // every row has a distinct position to resume from
+        if keyed && !keyset.iter().any(|(column, _)| *column == self.core_id_column) {
+            keyset.push((self.core_id_column.clone(), "ASC"));
+        }
+        let sort: Vec<(String, String)> = keyset
+            .iter()
+            .map(|(column, direction)| (column.clone(), direction.to_string()))
+            .collect();
+        let expressions: Vec<String> = keyset
+            .iter()
+            .map(|(column, _)| Self::typed_column(column, &self.column_terms).0)
+            .collect();
+        let (order_clause, select_fields) = if keyed {
+            let order_clause = format!(
+                " ORDER BY {}",
+                expressions
+                    .iter()
+                    .zip(&keyset)
+                    .map(|(expression, (_, direction))| format!("{expression} {direction} NULLS LAST"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let cursor_fields: Vec<String> = expressions
+                .iter()
+                .enumerate()
+                .map(|(i, expression)| format!("CAST({expression} AS VARCHAR) AS \"{CURSOR_FIELD_PREFIX}{i}\""))
+                .collect();
+            (order_clause, format!("{select_fields}, {}", cursor_fields.join(", ")))
+        } else {
+            (order_clause, select_fields)
+        };
 
         // Execute COUNT query
-        let count_query = format!("SELECT COUNT(*) FROM occurrences{where_clause}");
+        let count_query = format!("SELECT COUNT(*) FROM {occurrences}{where_clause}");
         let count_param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations.iter()
             .map(|p| p.as_ref()).collect();
         let total: usize = self.conn.query_row(
@@ -776,12 +2256,30 @@ impl Database {
             count_param_refs.as_slice(), |row| row.get(0)
         )?;
 
-        // Build SELECT query
-        let select_query = format!(
-            "SELECT {select_fields} FROM occurrences{where_clause}{order_clause} LIMIT ? OFFSET ?"
-        );
-        where_interpolations.push(Box::new(limit));
-        where_interpolations.push(Box::new(offset));
+        // Build SELECT query, resuming after the cursor if there is one
+        let select_query = if let Some(cursor) = cursor {
+            let cursor = SearchCursor::decode(cursor)?;
+            if cursor.sort != sort {
+                return Err(ChuckError::SearchCursor(
+                    "sort changed since the cursor was made".to_string()
+                ));
+            }
+            let (condition, values) = cursor.condition(&expressions);
+            for value in values {
+                where_interpolations.push(Box::new(value));
+            }
+            where_interpolations.push(Box::new(limit));
+            let where_clause = if where_clause.is_empty() {
+                format!(" WHERE {condition}")
+            } else {
+                format!("{where_clause} AND {condition}")
+            };
+            format!("SELECT {select_fields} FROM {occurrences}{where_clause}{order_clause} LIMIT ?")
+        } else {
+            where_interpolations.push(Box::new(limit));
+            where_interpolations.push(Box::new(offset));
+            format!("SELECT {select_fields} FROM {occurrences}{where_clause}{order_clause} LIMIT ? OFFSET ?")
+        };
 
         let mut stmt = self.conn.prepare(&select_query)?;
 
@@ -791,11 +2289,16 @@ impl Database {
         let rows = stmt.query_map(select_param_refs.as_slice(), |row| {
             // Dynamically map columns to JSON
             let mut map = serde_json::Map::new();
+            let mut keyset_values = Vec::new();
             let column_count = row.as_ref().column_count();
 
             for i in 0..column_count {
                 let name = row.as_ref().column_name(i)
                     .map_err(|_e| duckdb::Error::InvalidColumnIndex(i))?;
+                if name.starts_with(CURSOR_FIELD_PREFIX) {
+                    keyset_values.push(row.get::<_, Option<String>>(i)?);
+                    continue;
+                }
                 let value = Self::get_column_as_json(row, i);
 
                 // For extension columns, parse JSON string into array
@@ -821,20 +2324,88 @@ impl Database {
                 }
             }
 
-            Ok(map)
+            Ok((map, keyset_values))
         })?;
 
         let mut results = Vec::new();
+        let mut last_values = None;
         for row in rows {
-            results.push(row?);
+            let (map, keyset_values) = row?;
+            results.push(map);
+            last_values = Some(keyset_values);
         }
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        // A full page may have more rows after it
+        let next_cursor = last_values
+            .filter(|_| keyed && limit > 0 && results.len() == limit)
+            .map(|values| SearchCursor { sort, values }.encode());
+
+        let execution = if debug {
+            let (used_index, estimated_rows_scanned) =
+                self.explain_scans(&select_query, &select_param_refs)?;
+            Some(crate::commands::archive::ExecutionInfo {
+                elapsed_ms,
+                used_index,
+                estimated_rows_scanned,
+            })
+        } else {
+            None
+        };
 
         Ok(crate::commands::archive::SearchResult {
             total,
             results,
+            next_cursor,
+            execution,
         })
     }
 
+    /// Asks DuckDB for the plan of a query and returns whether it scans an
+    /// index and the summed estimated cardinality of its scans
+    fn explain_scans(
+        &self,
+        query: &str,
+        params: &[&dyn duckdb::ToSql],
+    ) -> Result<(bool, Option<u64>)> {
+        let plans: Vec<String> = self.conn
+            .prepare(&format!("EXPLAIN (FORMAT json) {query}"))?
+            .query_map(params, |row| row.get::<_, String>(1))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        fn visit(node: &serde_json::Value, used_index: &mut bool, rows: &mut Option<u64>) {
+            let name = node["name"].as_str().unwrap_or_default();
+            let scan_type = node["extra_info"]["Type"].as_str().unwrap_or_default();
+            if name.contains("INDEX_SCAN") || scan_type.contains("Index Scan") {
+                *used_index = true;
+            }
+            if name.contains("SCAN") {
+                let estimate = node["extra_info"]["Estimated Cardinality"]
+                    .as_str()
+                    .map(|s| s.trim_start_matches('~'))
+                    .and_then(|s| s.parse::<u64>().ok());
+                if let Some(estimate) = estimate {
+                    *rows = Some(rows.unwrap_or(0) + estimate);
+                }
+            }
+            for child in node["children"].as_array().into_iter().flatten() {
+                visit(child, used_index, rows);
+            }
+        }
+
+        let mut used_index = false;
+        let mut rows = None;
+        for plan in plans {
+            let Ok(plan) = serde_json::from_str::<serde_json::Value>(&plan) else {
+                continue;
+            };
+            for node in plan.as_array().into_iter().flatten() {
+                visit(node, &mut used_index, &mut rows);
+            }
+        }
+        Ok((used_index, rows))
+    }
+
     /// Calls `f` once per occurrence matching `search_params`, in query order.
     /// Column names are extracted from the executed statement before the first
     /// call so the caller can write headers without a separate query.
@@ -847,11 +2418,12 @@ impl Database {
     where
         F: FnMut(&[String], serde_json::Map<String, serde_json::Value>) -> Result<()>,
     {
+        let occurrences = &self.occurrences_source;
         let (select_fields, where_clause, where_interpolations, order_clause) =
-            Self::sql_parts(search_params, None, &self.core_id_column, &[]);
+            self.query_parts(search_params, None, &self.core_id_column, &[])?;
 
         let select_query = format!(
-            "SELECT {select_fields} FROM occurrences{where_clause}{order_clause}"
+            "SELECT {select_fields} FROM {occurrences}{where_clause}{order_clause}"
         );
 
         let mut stmt = self.conn.prepare(&select_query)?;
@@ -880,6 +2452,59 @@ impl Database {
         Ok(())
     }
 
+    /// Writes occurrences matching `search_params` to a Parquet file with
+    /// DuckDB's COPY, keeping the column types they have in the database.
+    /// With `include_extensions`, each extension table is added as a column
+    /// holding a list of structs, one per extension row. Returns the number of
+    /// occurrences written.
+    pub(crate) fn export_parquet(
+        &self,
+        search_params: SearchParams,
+        path: &std::path::Path,
+        include_extensions: bool,
+    ) -> Result<usize> {
+        let occurrences = &self.occurrences_source;
+        let (select_fields, where_clause, where_interpolations, order_clause) =
+            self.query_parts(search_params, None, &self.core_id_column, &[])?;
+
+        let mut select_fields = select_fields;
+        if include_extensions {
+            let tables: Vec<(&str, &str)> = self
+                .extension_tables
+                .iter()
+                .map(|(extension, ext_core_id_col)| (extension.table_name(), ext_core_id_col.as_str()))
+                .collect();
+            select_fields.push_str(&chuck_core::convert::nested_extension_columns(
+                &self.core_id_column,
+                &tables,
+                false,
+            ));
+        }
+
+        let path = path.to_str().ok_or(ChuckError::PathEncoding)?.replace('\'', "''");
+        let copy_query = format!(
+            "COPY (SELECT {select_fields} FROM {occurrences}{where_clause}{order_clause}) \
+             TO '{path}' (FORMAT parquet, COMPRESSION zstd)"
+        );
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        Ok(self.conn.execute(&copy_query, param_refs.as_slice())?)
+    }
+
+    /// Copies occurrences, with hand edits applied, and every extension table
+    /// into a standalone SQLite database at `path`, with the core ID columns
+    /// indexed, for people who can't use DuckDB. Returns the number of rows
+    /// copied.
+    pub(crate) fn export_sqlite(&self, path: &Path) -> Result<usize> {
+        let mut tables = vec![("occurrences".to_string(), self.core_id_column.clone())];
+        tables.extend(self.extension_tables.iter().map(|(extension, core_id_column)| {
+            (extension.table_name().to_string(), core_id_column.clone())
+        }));
+        super::sqlite_export::export_sqlite(&self.conn, &tables, &self.occurrences_source, path)
+    }
+
     /// Get autocomplete suggestions for a column
     pub fn get_autocomplete_suggestions(
         &self,
@@ -887,15 +2512,14 @@ impl Database {
         search_term: &str,
         limit: usize,
     ) -> Result<Vec<String>> {
+        let occurrences = &self.occurrences_source;
         // Validate column name against allowlist
-        if !Occurrence::FIELD_NAMES.contains(&column_name) {
-            return Err(crate::error::ChuckError::Database(
-                duckdb::Error::InvalidColumnName(column_name.to_string())
-            ));
+        if !self.is_searchable_field(column_name)? {
+            return Err(crate::error::ChuckError::MissingColumn(column_name.to_string()));
         }
 
         // Check if column has a non-VARCHAR type override
-        if let Some((_, column_type)) = TYPE_OVERRIDES.iter().find(|(col, _)| col == &column_name) {
+        if let Some(column_type) = type_override(column_term(&self.column_terms, column_name)) {
             return Err(crate::error::ChuckError::AutocompleteNotAvailable {
                 column: column_name.to_string(),
                 column_type: column_type.to_string(),
@@ -904,7 +2528,7 @@ impl Database {
 
         let quoted = Self::quote_identifier(column_name);
         let query = format!(
-            "SELECT DISTINCT {quoted} FROM occurrences WHERE {quoted} IS NOT NULL AND {quoted} ILIKE ? ORDER BY {quoted} LIMIT ?"
+            "SELECT DISTINCT {quoted} FROM {occurrences} WHERE {quoted} IS NOT NULL AND {quoted} ILIKE ? ORDER BY {quoted} LIMIT ?"
         );
 
         let mut stmt = self.conn.prepare(&query)?;
@@ -921,6 +2545,158 @@ impl Database {
         Ok(suggestions)
     }
 
+    /// Errors unless values of `column_name` can be listed, i.e. it's a
+    /// searchable text column
+    fn check_value_column(&self, column_name: &str) -> Result<()> {
+        if !self.is_searchable_field(column_name)? {
+            return Err(crate::error::ChuckError::MissingColumn(column_name.to_string()));
+        }
+        if let Some(column_type) = type_override(column_term(&self.column_terms, column_name)) {
+            return Err(crate::error::ChuckError::AutocompleteNotAvailable {
+                column: column_name.to_string(),
+                column_type: column_type.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the `limit` most common non-blank values of a text column
+    /// among occurrences matching the search, with their counts. With
+    /// `search_term`, only values containing it are included. Like
+    /// autocomplete, this isn't available for typed columns.
+    pub fn get_column_values(
+        &self,
+        column_name: &str,
+        search_params: &SearchParams,
+        search_term: Option<&str>,
+        limit: usize,
+        core_id_column: &str,
+    ) -> Result<ColumnValues> {
+        let occurrences = &self.occurrences_source;
+        self.check_value_column(column_name)?;
+        if !self.get_available_columns()?.iter().any(|c| c == column_name) {
+            return Ok(ColumnValues { values: vec![], distinct_count: 0 });
+        }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let quoted = Self::quote_identifier(column_name);
+        // The window count runs after grouping and before the limit, so it
+        // counts every distinct value
+        let query = format!(
+            "SELECT value, COUNT(*) AS count, COUNT(*) OVER () AS distinct_count \
+             FROM (SELECT CAST({quoted} AS VARCHAR) AS value FROM {occurrences}{where_clause}) \
+             WHERE value IS NOT NULL AND value != '' AND value ILIKE ? \
+             GROUP BY value ORDER BY count DESC, value LIMIT ?"
+        );
+
+        let search_pattern = format!("%{}%", search_term.unwrap_or_default());
+        let limit = limit as i64;
+        let mut param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        param_refs.push(&search_pattern);
+        param_refs.push(&limit);
+
+        let mut stmt = self.conn.prepare(&query)?;
+        let mut distinct_count = 0;
+        let values = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                distinct_count = row.get(2)?;
+                Ok(ValueCount {
+                    value: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(ColumnValues { values, distinct_count })
+    }
+
+    /// Like `get_column_values` for each of `column_names`, in one query.
+    /// Facets are returned in the order of `column_names`; ones for columns
+    /// the archive doesn't have are empty.
+    pub fn get_facets(
+        &self,
+        column_names: &[String],
+        search_params: &SearchParams,
+        limit: usize,
+        core_id_column: &str,
+    ) -> Result<Vec<Facet>> {
+        let occurrences = &self.occurrences_source;
+        for column_name in column_names {
+            self.check_value_column(column_name)?;
+        }
+        let available_columns = self.get_available_columns()?;
+        let mut facets: Vec<Facet> = column_names
+            .iter()
+            .map(|column| Facet { column: column.clone(), values: vec![], distinct_count: 0 })
+            .collect();
+        let present: Vec<&String> = column_names
+            .iter()
+            .filter(|column| available_columns.contains(column))
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if present.is_empty() {
+            return Ok(facets);
+        }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let selected: Vec<String> = present.iter().map(|c| Self::quote_identifier(c)).collect();
+        // Each facet ranks its own values, so one LIMIT can't be used
+        let facet_queries: Vec<String> = present
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let quoted = Self::quote_identifier(column);
+                format!(
+                    "SELECT {i} AS facet, value, COUNT(*) AS count, \
+                            COUNT(*) OVER () AS distinct_count, \
+                            row_number() OVER (ORDER BY COUNT(*) DESC, value) AS rank \
+                     FROM (SELECT CAST({quoted} AS VARCHAR) AS value FROM filtered) \
+                     WHERE value IS NOT NULL AND value != '' \
+                     GROUP BY value"
+                )
+            })
+            .collect();
+        let query = format!(
+            "WITH filtered AS (SELECT {} FROM {occurrences}{where_clause}) \
+             SELECT facet, value, count, distinct_count \
+             FROM ({}) \
+             WHERE rank <= ? \
+             ORDER BY facet, rank",
+            selected.join(", "),
+            facet_queries.join(" UNION ALL "),
+        );
+
+        let limit = limit as i64;
+        let mut param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        param_refs.push(&limit);
+
+        let mut stmt = self.conn.prepare(&query)?;
+        let mut rows = stmt.query(param_refs.as_slice())?;
+        while let Some(row) = rows.next()? {
+            let index: usize = row.get(0)?;
+            let column = present[index];
+            let value = ValueCount { value: row.get(1)?, count: row.get(2)? };
+            let distinct_count: i64 = row.get(3)?;
+            for facet in facets.iter_mut().filter(|facet| &facet.column == column) {
+                facet.values.push(value.clone());
+                facet.distinct_count = distinct_count;
+            }
+        }
+
+        Ok(facets)
+    }
+
     pub fn aggregate_by_field(
         &self,
         field_name: &str,
@@ -928,109 +2704,124 @@ impl Database {
         limit: Option<usize>,
         core_id_column: &str,
     ) -> Result<Vec<AggregationResult>> {
+        self.aggregate_by_field_with_metrics(field_name, search_params, limit, core_id_column, &[], None)
+    }
+
+    /// Aggregates occurrences by `field_name`, computing `metrics` for each
+    /// group in the same grouped query. Groups are ordered by `order_by`,
+    /// largest or latest first (earliest first for EarliestEventDate), then
+    /// by count, or just by count without it. `order_by` is computed even
+    /// if it isn't in `metrics`.
+    pub fn aggregate_by_field_with_metrics(
+        &self,
+        field_name: &str,
+        search_params: &SearchParams,
+        limit: Option<usize>,
+        core_id_column: &str,
+        metrics: &[AggregationMetric],
+        order_by: Option<AggregationMetric>,
+    ) -> Result<Vec<AggregationResult>> {
+        let occurrences = &self.occurrences_source;
         // Validate field name against allowlist to prevent SQL injection
-        if !Occurrence::FIELD_NAMES.contains(&field_name) {
-            return Err(crate::error::ChuckError::Database(
-                duckdb::Error::InvalidColumnName(field_name.to_string())
-            ));
+        if !self.is_searchable_field(field_name)? {
+            return Err(crate::error::ChuckError::MissingColumn(field_name.to_string()));
         }
 
-        let (_, where_clause, where_interpolations, _) =
-            Self::sql_parts(
-                search_params.clone(),
-                None,
-                core_id_column,
-                &self.extension_tables
-            );
-
         // Build subquery for aggregation with MIN(core_id_column)
         let quoted_field = Self::quote_identifier(field_name);
         let quoted_core_id = Self::quote_identifier(core_id_column);
-        let mut subquery = format!(
-            "SELECT {quoted_field} as value, COUNT(*) as count, MIN({quoted_core_id}) as min_core_id FROM occurrences"
-        );
-
-        if !where_clause.is_empty() {
-            subquery.push_str(&where_clause);
-        }
-
-        subquery.push_str(&format!(" GROUP BY {quoted_field}"));
+        let photo_sources = self.photo_sources()?;
+        let metric_columns = AggregationMetric::ALL
+            .iter()
+            .map(|metric| {
+                let requested = metrics.contains(metric) || order_by == Some(*metric);
+                let sql = if requested {
+                    self.metric_sql(*metric, &quoted_core_id, &photo_sources)?
+                } else {
+                    "NULL".to_string()
+                };
+                Ok(format!(", {sql} as {}", metric.alias()))
+            })
+            .collect::<Result<String>>()?;
+        let (subquery, where_clause, where_interpolations) = self.grouped_query(
+            &quoted_field,
+            &format!(", MIN({quoted_core_id}) as min_core_id{metric_columns}"),
+            search_params,
+            core_id_column,
+        )?;
 
-        // Build JOIN clauses based on available extension tables
+        // Join a photo from each media extension that has a usable URL
+        // column. Photos in the archive itself load without the network, so
+        // any group member's is preferred over the first occurrence's photo.
         let mut joins = String::new();
-        let mut photo_select = "NULL".to_string();
-
-        let has_multimedia = self.extension_tables.iter()
-            .any(|(ext, _)| *ext == chuck_core::DwcaExtension::SimpleMultimedia);
-        let has_audiovisual = self.extension_tables.iter()
-            .any(|(ext, _)| *ext == chuck_core::DwcaExtension::Audiovisual);
-
-        if has_multimedia && has_audiovisual {
-            let (_, multimedia_coreid) = self.extension_tables
-                .iter()
-                .find(|(ext, _)| *ext == chuck_core::DwcaExtension::SimpleMultimedia)
-                .unwrap();
-            let (_, audiovisual_coreid) = self.extension_tables
-                .iter()
-                .find(|(ext, _)| *ext == chuck_core::DwcaExtension::Audiovisual)
-                .unwrap();
-            let quoted_mm_coreid = Self::quote_identifier(multimedia_coreid);
-            let quoted_av_coreid = Self::quote_identifier(audiovisual_coreid);
-            joins.push_str(&format!(
-                " LEFT JOIN {} m ON m.{} = agg.min_core_id LEFT JOIN {} a ON a.{} = agg.min_core_id",
-                chuck_core::DwcaExtension::SimpleMultimedia.table_name(),
-                quoted_mm_coreid,
-                chuck_core::DwcaExtension::Audiovisual.table_name(),
-                quoted_av_coreid
-            ));
-            photo_select = "COALESCE(m.identifier, a.\"accessURI\")".to_string();
-        } else if has_multimedia {
-            let (_, multimedia_coreid) = self.extension_tables
-                .iter()
-                .find(|(ext, _)| *ext == chuck_core::DwcaExtension::SimpleMultimedia)
-                .unwrap();
-            let quoted_mm_coreid = Self::quote_identifier(multimedia_coreid);
+        let mut local_photo_columns = Vec::new();
+        let mut photo_columns = Vec::new();
+        for (i, (table_name, ext_core_id, url_column)) in photo_sources.iter().enumerate() {
+            let alias = format!("media{i}");
+            let quoted_ext_core_id = Self::quote_identifier(ext_core_id);
+            let quoted_url = Self::quote_identifier(url_column);
             joins.push_str(&format!(
-                " LEFT JOIN {} m ON m.{} = agg.min_core_id",
-                chuck_core::DwcaExtension::SimpleMultimedia.table_name(),
-                quoted_mm_coreid
+                " LEFT JOIN {table_name} {alias} ON {alias}.{quoted_ext_core_id} = agg.min_core_id"
             ));
-            photo_select = "m.identifier".to_string();
-        } else if has_audiovisual {
-            let (_, audiovisual_coreid) = self.extension_tables
-                .iter()
-                .find(|(ext, _)| *ext == chuck_core::DwcaExtension::Audiovisual)
-                .unwrap();
-            let quoted_av_coreid = Self::quote_identifier(audiovisual_coreid);
+            photo_columns.push(format!("{alias}.{quoted_url}"));
+
+            let local_alias = format!("local{i}");
             joins.push_str(&format!(
-                " LEFT JOIN {} a ON a.{} = agg.min_core_id",
-                chuck_core::DwcaExtension::Audiovisual.table_name(),
-                quoted_av_coreid
+                " LEFT JOIN (SELECT occ.value, MIN(m.{quoted_url}) AS url FROM {table_name} m \
+                 JOIN (SELECT {quoted_core_id} AS core_id, {quoted_field} AS value FROM {occurrences}{where_clause}) occ \
+                 ON m.{quoted_ext_core_id} = occ.core_id \
+                 WHERE {} GROUP BY occ.value) {local_alias} \
+                 ON {local_alias}.value IS NOT DISTINCT FROM agg.value",
+                local_photo_condition(&format!("m.{quoted_url}"))
             ));
-            photo_select = "a.\"accessURI\"".to_string();
+            local_photo_columns.push(format!("{local_alias}.url"));
         }
+        local_photo_columns.append(&mut photo_columns);
+        let photo_select = match local_photo_columns.len() {
+            0 => "NULL".to_string(),
+            1 => local_photo_columns.remove(0),
+            _ => format!("COALESCE({})", local_photo_columns.join(", ")),
+        };
 
         // Build final query
         let limit_clause = limit
             .map(|n| format!(" LIMIT {n}"))
             .unwrap_or_default();
+        let order_clause = match order_by {
+            Some(AggregationMetric::EarliestEventDate) => "agg.earliest_event_date ASC NULLS LAST, count DESC".to_string(),
+            Some(metric) => format!("agg.{} DESC NULLS LAST, count DESC", metric.alias()),
+            None => "count DESC".to_string(),
+        };
+        let metric_select = AggregationMetric::ALL
+            .iter()
+            .map(|metric| format!(", agg.{}", metric.alias()))
+            .collect::<String>();
         let sql = format!(
-            "SELECT DISTINCT ON (agg.value) agg.value, agg.count, {photo_select} as photo_url FROM ({subquery}) agg{joins} ORDER BY count DESC{limit_clause}"
+            "SELECT DISTINCT ON (agg.value) agg.value, agg.count, {photo_select} as photo_url{metric_select} \
+             FROM ({subquery}) agg{joins} ORDER BY {order_clause}{limit_clause}"
         );
         // log::debug!("sql: {sql}");
 
         let mut stmt = self.conn.prepare(&sql)?;
 
-        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
-            .iter()
+        // The filters appear once in the aggregate and once per local photo
+        // subquery
+        let param_refs: Vec<&dyn duckdb::ToSql> = std::iter::repeat_n(&where_interpolations, 1 + photo_sources.len())
+            .flatten()
             .map(|p| p.as_ref())
             .collect();
 
         let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            let photo_url: Option<String> = row.get(2)?;
             Ok(AggregationResult {
                 value: row.get(0)?,
                 count: row.get(1)?,
-                photo_url: row.get(2)?,
+                photo_is_local: photo_url.as_deref().is_some_and(|url| !is_remote_url(url)),
+                photo_url,
+                species_count: row.get(3)?,
+                earliest_event_date: row.get(4)?,
+                latest_event_date: row.get(5)?,
+                with_photos_count: row.get(6)?,
             })
         })?;
 
@@ -1042,288 +2833,2013 @@ impl Database {
         Ok(results)
     }
 
-    /// Retrieves a single occurrence by ID with all columns and extension data
-    pub fn get_occurrence(
+    /// Query of occurrences matching the search grouped by the SQL
+    /// expression `group`, selecting it as value, the count of each group as
+    /// count, and `extra_columns`, e.g. aggregates. Also returns the WHERE
+    /// clause and its interpolations, for queries that filter again.
+    fn grouped_query(
         &self,
+        group: &str,
+        extra_columns: &str,
+        search_params: &SearchParams,
         core_id_column: &str,
-        occurrence_id: &str,
-    ) -> Result<serde_json::Map<String, serde_json::Value>> {
-        // Build extension subqueries (same pattern as search method)
-        let quoted_core_id = Self::quote_identifier(core_id_column);
-        let extension_subqueries: Vec<String> = self.extension_tables
-            .iter()
-            .map(|(extension, ext_core_id_col)| {
-                let table_name = extension.table_name();
-                let quoted_ext_core_id = Self::quote_identifier(ext_core_id_col);
-                format!(
-                    "(SELECT COALESCE(to_json(list({table_name})), '[]') FROM {table_name} WHERE {table_name}.{quoted_ext_core_id} = occurrences.{quoted_core_id}) as {table_name}"
-                )
-            })
-            .collect();
+    ) -> Result<(String, String, Vec<Box<dyn duckdb::ToSql>>)> {
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+        let sql = format!(
+            "SELECT {group} as value, COUNT(*) as count{extra_columns} \
+             FROM {}{where_clause} GROUP BY {group}",
+            self.occurrences_source
+        );
+        Ok((sql, where_clause, where_interpolations))
+    }
 
-        let select_fields = if extension_subqueries.is_empty() {
-            "occurrences.*".to_string()
+    /// SQL expression for the ISO 8601 date part of eventDate, i.e. YYYY,
+    /// YYYY-MM, or YYYY-MM-DD, in local time if the archive has offsets, and
+    /// NULL for values that don't start with a year. None without an
+    /// eventDate column.
+    fn event_date_part_sql(&self, columns: &[String]) -> Option<String> {
+        if !columns.iter().any(|c| c == "eventDate") {
+            return None;
+        }
+        let event_date = if self.has_time_zone_offsets {
+            LOCAL_EVENT_DATE_SQL
         } else {
-            format!("occurrences.*, {}", extension_subqueries.join(", "))
+            "\"eventDate\""
         };
+        Some(format!("NULLIF(regexp_extract({event_date}, '^\\d{{4}}(-\\d{{2}}(-\\d{{2}})?)?'), '')"))
+    }
 
-        // Build query with WHERE clause on core_id_column
-        let query = format!(
-            "SELECT {select_fields} FROM occurrences WHERE {quoted_core_id} = ?"
-        );
-
-        let mut stmt = self.conn.prepare(&query)?;
-
-        let result = stmt.query_row([occurrence_id], |row| {
-            let mut map = serde_json::Map::new();
-            let column_count = row.as_ref().column_count();
-
-            for i in 0..column_count {
-                let name = row.as_ref().column_name(i)
-                    .map_err(|_| duckdb::Error::InvalidColumnIndex(i))?;
-                let value = Self::get_column_as_json(row, i);
-
-                // Parse extension JSON strings
-                let is_extension = self.extension_tables.iter()
-                    .any(|(ext, _)| ext.table_name() == name);
-                if is_extension {
-                    if let serde_json::Value::String(json_str) = &value {
-                        match serde_json::from_str::<serde_json::Value>(json_str) {
-                            Ok(parsed) => {
-                                map.insert(name.to_string(), parsed);
-                            }
-                            Err(_) => {
-                                map.insert(name.to_string(), serde_json::json!([]));
-                            }
-                        }
-                    } else {
-                        map.insert(name.to_string(), serde_json::json!([]));
-                    }
+    /// Aggregate SQL for a metric over the rows of one group, or NULL if the
+    /// archive lacks the columns it needs
+    fn metric_sql(
+        &self,
+        metric: AggregationMetric,
+        quoted_core_id: &str,
+        photo_sources: &[(&'static str, String, String)],
+    ) -> Result<String> {
+        let columns = self.get_available_columns()?;
+        let date_part = self.event_date_part_sql(&columns);
+        Ok(match metric {
+            AggregationMetric::SpeciesCount => match resolve_field("scientificName", &columns) {
+                Some(column) => format!(
+                    "COUNT(DISTINCT NULLIF(trim({}), ''))",
+                    Self::quote_identifier(column)
+                ),
+                None => "NULL".to_string(),
+            },
+            AggregationMetric::EarliestEventDate => {
+                date_part.map_or("NULL".to_string(), |date_part| format!("MIN({date_part})"))
+            }
+            AggregationMetric::LatestEventDate => {
+                date_part.map_or("NULL".to_string(), |date_part| format!("MAX({date_part})"))
+            }
+            AggregationMetric::WithPhotosCount => {
+                if photo_sources.is_empty() {
+                    "0".to_string()
                 } else {
-                    map.insert(name.to_string(), value);
+                    let conditions = photo_sources
+                        .iter()
+                        .map(|(table_name, ext_core_id, _)| {
+                            format!(
+                                "{quoted_core_id} IN (SELECT {} FROM {table_name})",
+                                Self::quote_identifier(ext_core_id)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" OR ");
+                    format!("COUNT(*) FILTER (WHERE {conditions})")
                 }
             }
-
-            Ok(map)
-        })?;
-
-        Ok(result)
+        })
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
 
-    struct TestFixture {
-        temp_dir: PathBuf,
-        csv_paths: Vec<PathBuf>,
-        db_path: PathBuf,
+    /// Media extension tables that can supply a photo URL, as (table name,
+    /// core ID column, URL column). Each table's columns are checked since
+    /// archives don't agree on which term holds the URL, and tables without
+    /// any of PHOTO_URL_COLUMNS are left out.
+    fn photo_sources(&self) -> Result<Vec<(&'static str, String, String)>> {
+        let mut sources = Vec::new();
+        for extension in [
+            chuck_core::DwcaExtension::SimpleMultimedia,
+            chuck_core::DwcaExtension::Audiovisual,
+        ] {
+            let Some((_, ext_core_id)) = self.extension_tables
+                .iter()
+                .find(|(ext, _)| *ext == extension)
+            else {
+                continue;
+            };
+            let table_name = extension.table_name();
+            let columns = Self::get_column_names(&self.conn, table_name)?;
+            if let Some(url_column) = PHOTO_URL_COLUMNS
+                .iter()
+                .find(|candidate| columns.iter().any(|c| c == *candidate))
+            {
+                sources.push((table_name, ext_core_id.clone(), url_column.to_string()));
+            }
+        }
+        Ok(sources)
     }
 
-    impl TestFixture {
-        fn new(test_name: &str, csv_data: Vec<&[u8]>) -> Self {
-            let temp_dir = std::env::temp_dir()
-                .join(format!("chuck_test_db_{test_name}"));
+    /// Aggregates occurrences by the individual values of a multi-value field
+    /// like recordedBy, so "A | B" counts toward both A and B. Uses the
+    /// multi_values table if the archive has been split and splits values on
+    /// the fly otherwise. Results have no photos.
+    pub fn aggregate_by_field_values(
+        &self,
+        field_name: &str,
+        search_params: &SearchParams,
+        limit: Option<usize>,
+        core_id_column: &str,
+    ) -> Result<Vec<AggregationResult>> {
+        let occurrences = &self.occurrences_source;
+        // The allowlist also makes field_name safe to use as a literal
+        if !crate::multi_value::is_multi_value_field(field_name) {
+            return Err(crate::error::ChuckError::Database(
+                duckdb::Error::InvalidColumnName(field_name.to_string())
+            ));
+        }
 
-            // Clean up from any previous test runs
-            std::fs::remove_dir_all(&temp_dir).ok();
-            std::fs::create_dir_all(&temp_dir).unwrap();
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
 
-            // Create CSV files
-            let mut csv_paths = Vec::new();
-            for (i, data) in csv_data.iter().enumerate() {
-                let csv_path = temp_dir.join(format!("test{i}.csv"));
-                let mut file = std::fs::File::create(&csv_path).unwrap();
-                file.write_all(data).unwrap();
-                csv_paths.push(csv_path);
-            }
+        let quoted_core_id = Self::quote_identifier(core_id_column);
+        let values = if self.has_multi_values {
+            format!(
+                "SELECT core_id, value FROM {} WHERE field = '{field_name}' \
+                 AND core_id IN (SELECT {quoted_core_id} FROM {occurrences}{where_clause})",
+                crate::multi_value::MULTI_VALUES_TABLE,
+            )
+        } else {
+            let split = crate::multi_value::split_values_sql(&Self::quote_identifier(field_name));
+            format!(
+                "SELECT core_id, trim(value) AS value FROM \
+                 (SELECT {quoted_core_id} AS core_id, unnest({split}) AS value FROM {occurrences}{where_clause}) \
+                 WHERE trim(value) != ''"
+            )
+        };
+        let limit_clause = limit
+            .map(|n| format!(" LIMIT {n}"))
+            .unwrap_or_default();
+        let sql = format!(
+            "SELECT value, COUNT(DISTINCT core_id) AS count FROM ({values}) \
+             GROUP BY value ORDER BY count DESC, value{limit_clause}"
+        );
 
-            let db_path = temp_dir.join("test.db");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
 
-            Self {
-                temp_dir,
-                csv_paths,
-                db_path,
-            }
+        let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            Ok(AggregationResult {
+                value: row.get(0)?,
+                count: row.get(1)?,
+                ..Default::default()
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
         }
+
+        Ok(results)
     }
 
-    impl Drop for TestFixture {
-        fn drop(&mut self) {
-            std::fs::remove_dir_all(&self.temp_dir).ok();
+    /// Returns up to `limit` example occurrences whose `field_name` equals
+    /// `value` (or is NULL when `value` is None) under the given filters, for
+    /// drilling into a group from aggregate_by_field. Examples with a photo
+    /// come first. Photos come from the same columns as in
+    /// aggregate_by_field.
+    pub fn group_examples(
+        &self,
+        field_name: &str,
+        value: Option<&str>,
+        search_params: &SearchParams,
+        limit: usize,
+        core_id_column: &str,
+    ) -> Result<Vec<GroupExample>> {
+        let occurrences = &self.occurrences_source;
+        // Validate field name against allowlist to prevent SQL injection
+        if !self.is_searchable_field(field_name)? {
+            return Err(crate::error::ChuckError::MissingColumn(field_name.to_string()));
+        }
+
+        let (_, where_clause, mut where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        // Compare as text so values of typed columns match the strings
+        // returned by aggregate_by_field
+        let quoted_field = Self::quote_identifier(field_name);
+        let group_condition = match value {
+            Some(value) => {
+                where_interpolations.push(Box::new(value.to_string()));
+                format!("CAST({quoted_field} AS VARCHAR) = ?")
+            }
+            None => format!("{quoted_field} IS NULL"),
+        };
+        let where_clause = if where_clause.is_empty() {
+            format!(" WHERE {group_condition}")
+        } else {
+            format!("{where_clause} AND {group_condition}")
+        };
+
+        let quoted_core_id = Self::quote_identifier(core_id_column);
+        let mut photo_subqueries = Vec::new();
+        for (table_name, ext_core_id, url_column) in self.photo_sources()? {
+            let quoted_url = Self::quote_identifier(&url_column);
+            let quoted_ext_core_id = Self::quote_identifier(&ext_core_id);
+            photo_subqueries.push(format!(
+                "(SELECT {quoted_url} FROM {table_name} WHERE {table_name}.{quoted_ext_core_id} = occurrences.{quoted_core_id} AND {quoted_url} IS NOT NULL LIMIT 1)"
+            ));
+        }
+        let photo_select = match photo_subqueries.len() {
+            0 => "NULL".to_string(),
+            1 => photo_subqueries.remove(0),
+            _ => format!("COALESCE({})", photo_subqueries.join(", ")),
+        };
+
+        let sql = format!(
+            "SELECT occurrences.*, {photo_select} AS {GROUP_EXAMPLE_PHOTO_COLUMN} FROM {occurrences}{where_clause} \
+             ORDER BY {GROUP_EXAMPLE_PHOTO_COLUMN} IS NULL, {quoted_core_id} LIMIT ?"
+        );
+        where_interpolations.push(Box::new(limit));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+
+        let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            let mut record = serde_json::Map::new();
+            let mut photo_url = None;
+            for i in 0..row.as_ref().column_count() {
+                let name = row.as_ref().column_name(i)
+                    .map_err(|_e| duckdb::Error::InvalidColumnIndex(i))?;
+                if name == GROUP_EXAMPLE_PHOTO_COLUMN {
+                    photo_url = row.get(i)?;
+                } else {
+                    record.insert(name.to_string(), Self::get_column_as_json(row, i));
+                }
+            }
+            Ok(GroupExample { record, photo_url })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+
+        Ok(results)
+    }
+
+    /// Aggregates occurrences by two fields (GROUP BY primary, secondary),
+    /// keeping at most `limit_per_group` of the most frequent secondary values
+    /// for each primary value. Results are ordered by primary value, then by
+    /// count descending.
+    pub fn aggregate_by_two_fields(
+        &self,
+        primary_field: &str,
+        secondary_field: &str,
+        search_params: &SearchParams,
+        limit_per_group: Option<usize>,
+        core_id_column: &str,
+    ) -> Result<Vec<CrosstabResult>> {
+        let occurrences = &self.occurrences_source;
+        // Validate field names against allowlist to prevent SQL injection
+        for field_name in [primary_field, secondary_field] {
+            if !self.is_searchable_field(field_name)? {
+                return Err(crate::error::ChuckError::MissingColumn(field_name.to_string()));
+            }
+        }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let quoted_primary = Self::quote_identifier(primary_field);
+        let quoted_secondary = Self::quote_identifier(secondary_field);
+        let qualify_clause = limit_per_group
+            .map(|n| format!(
+                " QUALIFY ROW_NUMBER() OVER (PARTITION BY {quoted_primary} ORDER BY COUNT(*) DESC, {quoted_secondary}) <= {n}"
+            ))
+            .unwrap_or_default();
+        let sql = format!(
+            "SELECT CAST({quoted_primary} AS VARCHAR), CAST({quoted_secondary} AS VARCHAR), COUNT(*) AS count \
+             FROM {occurrences}{where_clause} \
+             GROUP BY {quoted_primary}, {quoted_secondary}{qualify_clause} \
+             ORDER BY {quoted_primary} NULLS LAST, count DESC, {quoted_secondary} NULLS LAST"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            Ok(CrosstabResult {
+                primary: row.get(0)?,
+                secondary: row.get(1)?,
+                count: row.get(2)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+
+        Ok(results)
+    }
+
+    /// Computes summary statistics for a numeric column over occurrences
+    /// matching the search
+    pub fn column_stats(
+        &self,
+        column_name: &str,
+        search_params: &SearchParams,
+        core_id_column: &str,
+    ) -> Result<ColumnStats> {
+        let occurrences = &self.occurrences_source;
+        // Validate column name against allowlist to prevent SQL injection.
+        // This also makes sure it wasn't dropped as empty on import.
+        if !self.is_searchable_field(column_name)? {
+            return Err(crate::error::ChuckError::MissingColumn(column_name.to_string()));
+        }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let quoted_column = Self::quote_identifier(column_name);
+        let sql = format!(
+            "SELECT \
+                COUNT(*), \
+                COUNT(*) FILTER (WHERE raw IS NULL OR trim(raw) = ''), \
+                COUNT(*) FILTER (WHERE raw IS NOT NULL AND trim(raw) != '' AND v IS NULL), \
+                MIN(v), MAX(v), AVG(v), MEDIAN(v), \
+                quantile_cont(v, 0.05), quantile_cont(v, 0.25), \
+                quantile_cont(v, 0.75), quantile_cont(v, 0.95) \
+             FROM (\
+                SELECT CAST({quoted_column} AS VARCHAR) AS raw, TRY_CAST({quoted_column} AS DOUBLE) AS v \
+                FROM {occurrences}{where_clause}\
+             )"
+        );
+
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let stats = self.conn.query_row(&sql, param_refs.as_slice(), |row| {
+            Ok(ColumnStats {
+                column: column_name.to_string(),
+                count: row.get(0)?,
+                null_count: row.get(1)?,
+                non_numeric_count: row.get(2)?,
+                min: row.get(3)?,
+                max: row.get(4)?,
+                mean: row.get(5)?,
+                median: row.get(6)?,
+                p5: row.get(7)?,
+                p25: row.get(8)?,
+                p75: row.get(9)?,
+                p95: row.get(10)?,
+            })
+        })?;
+
+        Ok(stats)
+    }
+
+    /// Counts occurrences matching the search per month, week, or year of
+    /// eventDate, ordered by bucket. Records whose eventDate is too imprecise
+    /// for the bucket (e.g. just a year when bucketing by month) are left out.
+    pub fn aggregate_by_time(
+        &self,
+        bucket: TimeBucket,
+        search_params: &SearchParams,
+        core_id_column: &str,
+    ) -> Result<Vec<TimeAggregationResult>> {
+        if !self.get_available_columns()?.iter().any(|c| c == "eventDate") {
+            return Ok(vec![]);
+        }
+
+        let event_date = if self.has_time_zone_offsets {
+            LOCAL_EVENT_DATE_SQL
+        } else {
+            "\"eventDate\""
+        };
+        let bucket_sql = match bucket {
+            TimeBucket::Month => format!(
+                "TRY_CAST(regexp_extract({event_date}, '^\\d{{4}}-(\\d{{2}})', 1) AS INTEGER)"
+            ),
+            TimeBucket::Week => format!(
+                "weekofyear(TRY_CAST(regexp_extract({event_date}, '^\\d{{4}}-\\d{{2}}-\\d{{2}}') AS DATE))"
+            ),
+            TimeBucket::Year => format!(
+                "TRY_CAST(regexp_extract({event_date}, '^(\\d{{4}})', 1) AS INTEGER)"
+            ),
+        };
+        let (grouped, _, where_interpolations) =
+            self.grouped_query(&bucket_sql, "", search_params, core_id_column)?;
+        let sql = format!(
+            "SELECT value, count FROM ({grouped}) WHERE value IS NOT NULL ORDER BY value"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            Ok(TimeAggregationResult {
+                bucket: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+
+        Ok(results)
+    }
+
+    /// Counts occurrences matching the search in each day, month, or year
+    /// from the earliest eventDate in the archive to the latest, including
+    /// periods with none. The range doesn't depend on the search, so charts
+    /// of different searches line up. Records whose eventDate is too
+    /// imprecise for the interval are left out.
+    pub fn time_series(
+        &self,
+        interval: TimeInterval,
+        search_params: &SearchParams,
+        core_id_column: &str,
+    ) -> Result<Vec<TimeSeriesPoint>> {
+        let occurrences = &self.occurrences_source;
+        if !self.get_available_columns()?.iter().any(|c| c == "eventDate") {
+            return Ok(vec![]);
         }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let event_date = if self.has_time_zone_offsets {
+            LOCAL_EVENT_DATE_SQL
+        } else {
+            "\"eventDate\""
+        };
+        let period_sql = match interval {
+            TimeInterval::Day => format!(
+                "TRY_CAST(regexp_extract({event_date}, '^\\d{{4}}-\\d{{2}}-\\d{{2}}') AS DATE)"
+            ),
+            TimeInterval::Month => format!(
+                "TRY_CAST(regexp_extract({event_date}, '^\\d{{4}}-\\d{{2}}') || '-01' AS DATE)"
+            ),
+            TimeInterval::Year => format!(
+                "TRY_CAST(regexp_extract({event_date}, '^\\d{{4}}') || '-01-01' AS DATE)"
+            ),
+        };
+        let step = interval.sql_interval();
+        // Periods come from generate_series so ones without records get a
+        // zero count
+        let sql = format!(
+            "WITH counts AS ( \
+                 SELECT period, COUNT(*) AS count \
+                 FROM (SELECT {period_sql} AS period FROM {occurrences}{where_clause}) \
+                 WHERE period IS NOT NULL GROUP BY period \
+             ), \
+             periods AS ( \
+                 SELECT CAST(unnest(generate_series( \
+                     CAST(MIN(period) AS TIMESTAMP), CAST(MAX(period) AS TIMESTAMP), {step} \
+                 )) AS DATE) AS period \
+                 FROM (SELECT {period_sql} AS period FROM {occurrences}) \
+             ) \
+             SELECT strftime(periods.period, '%Y-%m-%d'), COALESCE(counts.count, 0) \
+             FROM periods LEFT JOIN counts ON counts.period = periods.period \
+             ORDER BY periods.period"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let points = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok(TimeSeriesPoint {
+                    date: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(points)
+    }
+
+    /// Species accumulation curve for occurrences matching the search: for
+    /// each day a scientificName was first recorded, how many were first
+    /// recorded that day and how many had been recorded by then. Records
+    /// without a name or a full date are left out.
+    pub fn species_accumulation(
+        &self,
+        search_params: &SearchParams,
+        core_id_column: &str,
+    ) -> Result<Vec<AccumulationPoint>> {
+        let occurrences = &self.occurrences_source;
+        let available_columns = self.get_available_columns()?;
+        if !["eventDate", "scientificName"]
+            .iter()
+            .all(|column| available_columns.iter().any(|c| c == column))
+        {
+            return Ok(vec![]);
+        }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let event_date = if self.has_time_zone_offsets {
+            LOCAL_EVENT_DATE_SQL
+        } else {
+            "\"eventDate\""
+        };
+        let sql = format!(
+            "WITH first_records AS ( \
+                 SELECT name, MIN(day) AS day \
+                 FROM ( \
+                     SELECT \"scientificName\" AS name, \
+                            TRY_CAST(regexp_extract({event_date}, '^\\d{{4}}-\\d{{2}}-\\d{{2}}') AS DATE) AS day \
+                     FROM {occurrences}{where_clause} \
+                 ) \
+                 WHERE day IS NOT NULL AND name IS NOT NULL AND name != '' \
+                 GROUP BY name \
+             ) \
+             SELECT strftime(day, '%Y-%m-%d'), \
+                    COUNT(*), \
+                    CAST(SUM(COUNT(*)) OVER (ORDER BY day) AS BIGINT) \
+             FROM first_records GROUP BY day ORDER BY day"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let points = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok(AccumulationPoint {
+                    date: row.get(0)?,
+                    new_species: row.get(1)?,
+                    total_species: row.get(2)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(points)
+    }
+
+    /// Summarizes the occurrences matching the search that `recorded_by` is
+    /// one of the recordedBy values of, matched like the recordedBy_includes
+    /// filter, which the collector replaces
+    pub fn collector_summary(
+        &self,
+        recorded_by: &str,
+        search_params: &SearchParams,
+        core_id_column: &str,
+    ) -> Result<CollectorSummary> {
+        let occurrences = &self.occurrences_source;
+        let mut search_params = search_params.clone();
+        search_params
+            .filters
+            .insert(format!("recordedBy{INCLUDES_FILTER_SUFFIX}"), recorded_by.to_string());
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let columns = self.get_available_columns()?;
+        let species_count = match resolve_field("scientificName", &columns) {
+            Some(column) => format!("COUNT(DISTINCT NULLIF(trim({}), ''))", Self::quote_identifier(column)),
+            None => "NULL".to_string(),
+        };
+        let (min_date, max_date) = match self.event_date_part_sql(&columns) {
+            Some(date_part) => (format!("MIN({date_part})"), format!("MAX({date_part})")),
+            None => ("NULL".to_string(), "NULL".to_string()),
+        };
+        let coordinate = |name: &str| {
+            resolve_field(name, &columns).map(|column| {
+                format!("TRY_CAST({} AS DOUBLE)", Self::quote_identifier(column))
+            })
+        };
+        let bounds = match (coordinate("decimalLatitude"), coordinate("decimalLongitude")) {
+            (Some(lat), Some(lng)) => {
+                let valid = format!("FILTER (WHERE {lat} BETWEEN -90 AND 90 AND {lng} BETWEEN -180 AND 180)");
+                format!("MAX({lat}) {valid}, MAX({lng}) {valid}, MIN({lat}) {valid}, MIN({lng}) {valid}")
+            }
+            _ => "NULL, NULL, NULL, NULL".to_string(),
+        };
+        let sql = format!(
+            "SELECT COUNT(*), {species_count}, {min_date}, {max_date}, {bounds} FROM {occurrences}{where_clause}"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let mut summary = stmt.query_row(param_refs.as_slice(), |row| {
+            let extent = (
+                row.get::<_, Option<f64>>(4)?,
+                row.get::<_, Option<f64>>(5)?,
+                row.get::<_, Option<f64>>(6)?,
+                row.get::<_, Option<f64>>(7)?,
+            );
+            let bounds = match extent {
+                (Some(nelat), Some(nelng), Some(swlat), Some(swlng)) => {
+                    Some(crate::archive_stats::Bounds { nelat, nelng, swlat, swlng })
+                }
+                _ => None,
+            };
+            Ok(CollectorSummary {
+                recorded_by: recorded_by.trim().to_string(),
+                record_count: row.get(0)?,
+                species_count: row.get(1)?,
+                min_event_date: row.get(2)?,
+                max_event_date: row.get(3)?,
+                bounds,
+                monthly: vec![],
+            })
+        })?;
+        if summary.record_count > 0 {
+            summary.monthly = self.time_series(TimeInterval::Month, &search_params, core_id_column)?;
+        }
+        Ok(summary)
+    }
+
+    /// Counts occurrences matching the search, and the species among them,
+    /// in each cell of a grid of `cell_size` degrees. Only cells with
+    /// records are returned, ordered from south-west to north-east.
+    pub fn grid_summary(
+        &self,
+        cell_size: f64,
+        search_params: &SearchParams,
+        core_id_column: &str,
+    ) -> Result<Vec<GridCell>> {
+        let occurrences = &self.occurrences_source;
+        if !cell_size.is_finite() || !(MIN_GRID_CELL_SIZE..=MAX_GRID_CELL_SIZE).contains(&cell_size) {
+            return Err(ChuckError::GridCellSize(cell_size));
+        }
+        let available_columns = self.get_available_columns()?;
+        if !["decimalLatitude", "decimalLongitude"]
+            .iter()
+            .all(|column| available_columns.iter().any(|c| c == column))
+        {
+            return Ok(vec![]);
+        }
+
+        let (_, where_clause, where_interpolations, _) =
+            self.query_parts(search_params.clone(), None, core_id_column, &self.extension_tables)?;
+
+        let species_sql = if available_columns.iter().any(|c| c == "scientificName") {
+            "COUNT(DISTINCT NULLIF(\"scientificName\", ''))"
+        } else {
+            "0"
+        };
+        let sql = format!(
+            "SELECT row, col, COUNT(*), {species_sql} \
+             FROM ( \
+                 SELECT *, \
+                        CAST(FLOOR(\"decimalLatitude\" / {cell_size}) AS BIGINT) AS row, \
+                        CAST(FLOOR(\"decimalLongitude\" / {cell_size}) AS BIGINT) AS col \
+                 FROM {occurrences}{where_clause} \
+             ) \
+             WHERE \"decimalLatitude\" BETWEEN -90 AND 90 \
+               AND \"decimalLongitude\" BETWEEN -180 AND 180 \
+             GROUP BY row, col ORDER BY row, col"
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let cells = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                let (row_index, col_index): (i64, i64) = (row.get(0)?, row.get(1)?);
+                Ok(GridCell {
+                    min_lat: row_index as f64 * cell_size,
+                    min_lng: col_index as f64 * cell_size,
+                    size: cell_size,
+                    record_count: row.get(2)?,
+                    species_count: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(cells)
+    }
+
+    /// Retrieves a single occurrence by ID with all columns and extension data
+    pub fn get_occurrence(
+        &self,
+        core_id_column: &str,
+        occurrence_id: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let occurrences = &self.occurrences_source;
+        // Build extension subqueries (same pattern as search method)
+        let quoted_core_id = Self::quote_identifier(core_id_column);
+        let extension_subqueries: Vec<String> = self.extension_tables
+            .iter()
+            .map(|(extension, ext_core_id_col)| {
+                let table_name = extension.table_name();
+                let quoted_ext_core_id = Self::quote_identifier(ext_core_id_col);
+                format!(
+                    "(SELECT COALESCE(to_json(list({table_name})), '[]') FROM {table_name} WHERE {table_name}.{quoted_ext_core_id} = occurrences.{quoted_core_id}) as {table_name}"
+                )
+            })
+            .collect();
+
+        let select_fields = if extension_subqueries.is_empty() {
+            "occurrences.*".to_string()
+        } else {
+            format!("occurrences.*, {}", extension_subqueries.join(", "))
+        };
+
+        // Build query with WHERE clause on core_id_column
+        let query = format!(
+            "SELECT {select_fields} FROM {occurrences} WHERE {quoted_core_id} = ?"
+        );
+
+        let mut stmt = self.conn.prepare(&query)?;
+
+        let result = stmt.query_row([occurrence_id], |row| {
+            let mut map = serde_json::Map::new();
+            let column_count = row.as_ref().column_count();
+
+            for i in 0..column_count {
+                let name = row.as_ref().column_name(i)
+                    .map_err(|_| duckdb::Error::InvalidColumnIndex(i))?;
+                let value = Self::get_column_as_json(row, i);
+
+                // Parse extension JSON strings
+                let is_extension = self.extension_tables.iter()
+                    .any(|(ext, _)| ext.table_name() == name);
+                if is_extension {
+                    if let serde_json::Value::String(json_str) = &value {
+                        match serde_json::from_str::<serde_json::Value>(json_str) {
+                            Ok(parsed) => {
+                                map.insert(name.to_string(), parsed);
+                            }
+                            Err(_) => {
+                                map.insert(name.to_string(), serde_json::json!([]));
+                            }
+                        }
+                    } else {
+                        map.insert(name.to_string(), serde_json::json!([]));
+                    }
+                } else {
+                    map.insert(name.to_string(), value);
+                }
+            }
+
+            Ok(map)
+        })?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    struct TestFixture {
+        temp_dir: PathBuf,
+        csv_paths: Vec<PathBuf>,
+        db_path: PathBuf,
+    }
+
+    impl TestFixture {
+        fn new(test_name: &str, csv_data: Vec<&[u8]>) -> Self {
+            let temp_dir = std::env::temp_dir()
+                .join(format!("chuck_test_db_{test_name}"));
+
+            // Clean up from any previous test runs
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+
+            // Create CSV files
+            let mut csv_paths = Vec::new();
+            for (i, data) in csv_data.iter().enumerate() {
+                let csv_path = temp_dir.join(format!("test{i}.csv"));
+                let mut file = std::fs::File::create(&csv_path).unwrap();
+                file.write_all(data).unwrap();
+                csv_paths.push(csv_path);
+            }
+
+            let db_path = temp_dir.join("test.db");
+
+            Self {
+                temp_dir,
+                csv_paths,
+                db_path,
+            }
+        }
+    }
+
+    /// Columns for calling sql_parts without a database
+    fn known_columns() -> Vec<String> {
+        Occurrence::FIELD_NAMES.iter().map(|name| name.to_string()).collect()
+    }
+
+    impl Drop for TestFixture {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.temp_dir).ok();
+        }
+    }
+
+    #[test]
+    fn test_create_with_existing_table() {
+        let fixture = TestFixture::new(
+            "existing_table",
+            vec![b"id,name\n1,Alice\n2,Bob\n"]
+        );
+
+        // First call should succeed
+        let result1 = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "id"
+        );
+        assert!(result1.is_ok());
+        let db1 = result1.unwrap();
+        assert_eq!(db1.count_records().unwrap(), 2);
+
+        // Drop first connection before opening second - on Windows, files are locked while open
+        drop(db1);
+
+        // Second call should recognize existing table and not alter it
+        let result2 = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "id"
+        );
+
+        assert!(result2.is_ok());
+        let db2 = result2.unwrap();
+        assert_eq!(db2.count_records().unwrap(), 2);
+
+        // Drop database before fixture cleanup - on Windows, files are locked while open
+        drop(db2);
+    }
+
+    #[test]
+    fn test_create_with_multiple_core_files() {
+        let fixture = TestFixture::new(
+            "multiple_cores",
+            vec![
+                b"id,name\n
+                  1,Collins\n
+                  2,Gardiner\n",
+                b"3,Lizzy\n
+                  4,Jane\n"
+            ]
+        );
+
+        let result = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "id"
+        );
+        assert!(result.is_ok());
+        let db = result.unwrap();
+        assert_eq!(db.count_records().unwrap(), 4);
+
+        // Cleanup happens automatically via Drop
+    }
+
+    #[test]
+    fn test_parse_memory_size() {
+        assert_eq!(parse_memory_size("1.5 GiB"), Some(3 << 29));
+        assert_eq!(parse_memory_size("512 bytes"), Some(512));
+        assert_eq!(parse_memory_size("lots"), None);
+    }
+
+    #[test]
+    fn test_create_limits_import_memory() {
+        let fixture = TestFixture::new("import_memory", vec![b"id,name\n1,Collins\n"]);
+
+        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "id")
+            .unwrap();
+
+        let default_conn = duckdb::Connection::open_in_memory().unwrap();
+        let memory_limit = |conn: &duckdb::Connection| {
+            let limit: String = conn
+                .query_row("SELECT current_setting('memory_limit')", [], |row| row.get(0))
+                .unwrap();
+            parse_memory_size(&limit).unwrap()
+        };
+        assert!(memory_limit(db.connection()) < memory_limit(&default_conn));
+    }
+
+    #[test]
+    fn test_create_reports_import_progress() {
+        let fixture = TestFixture::new(
+            "import_progress",
+            vec![b"id,name\n1,Collins\n2,Gardiner\n", b"id,name\n3,Lizzy\n"],
+        );
+        let progress = std::cell::RefCell::new(Vec::new());
+
+        Database::create_from_core_files_with_progress(
+            &fixture.csv_paths,
+            &CsvFormat::default(),
+            &[],
+            &[],
+            None,
+            &[],
+            &fixture.db_path,
+            "id",
+            &CoreIdStrategy::default(),
+            &|p| progress.borrow_mut().push(p.clone()),
+        ).unwrap();
+
+        let progress = progress.into_inner();
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[0].file, "test0.csv");
+        assert_eq!(progress[0].bytes_read, progress[0].total_bytes);
+        assert_eq!(progress[0].rows_inserted, 2);
+        assert_eq!(progress[0].files_done, 0);
+        assert_eq!(progress[1].file, "test1.csv");
+        assert_eq!(progress[1].rows_inserted, 3);
+        assert_eq!((progress[1].files_done, progress[1].total_files), (1, 2));
+    }
+
+    // DwC-A accepts a variety of date formats that won't be captured in a
+    // duckdb DATE column, so we just need to use a VARCHAR
+    #[test]
+    fn test_create_with_multiple_date_formats() {
+        let fixture = TestFixture::new(
+            "multiple_date_formats",
+            vec![
+                // For some reason, when you have one line with partial dates
+                // and another with mixed formats like this, the
+                // auto-detected date_format ends up as %m/%d/%Y... but not
+                // if you only have the one line with mixed formats. duckdb
+                // will choke on the partials as well
+                b"id,eventDate,verbatimEventDate\n
+                  1,,\n
+                  2,1988-10-25,10/25/1988\n
+                  3,1967-07,1967-07-\n"
+            ]
+        );
+        let result = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "id"
+        );
+        let db = result.unwrap();
+        assert_eq!(db.count_records().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_search_returns_occurrence_records() {
+        // Create a CSV with Darwin Core occurrence fields
+        let csv_data = br#"occurrenceID,basisOfRecord,recordedBy,eventDate,decimalLatitude,decimalLongitude,scientificName,taxonRank,taxonomicStatus,vernacularName,kingdom,phylum,class,order,family,genus,specificEpithet,infraspecificEpithet,taxonID,occurrenceRemarks,establishmentMeans,georeferencedDate,georeferenceProtocol,coordinateUncertaintyInMeters,coordinatePrecision,geodeticDatum,accessRights,license,informationWithheld,modified,captive,eventTime,verbatimEventDate,verbatimLocality
+123456,HumanObservation,John Doe,2024-01-15,34.0522,-118.2437,Quercus agrifolia,species,accepted,Coast Live Oak,Plantae,Tracheophyta,Magnoliopsida,Fagales,Fagaceae,Quercus,agrifolia,,12345,Observed in park,native,2024-01-15,GPS,10.0,0.0001,WGS84,public,CC-BY,,,false,14:30:00,January 15 2024,Golden Gate Park
+789012,HumanObservation,Jane Smith,2024-01-16,37.7749,-122.4194,Pinus radiata,species,accepted,Monterey Pine,Plantae,Tracheophyta,Pinopsida,Pinales,Pinaceae,Pinus,radiata,,67890,Tall specimen,native,2024-01-16,GPS,5.0,0.0001,WGS84,public,CC-BY-NC,,,false,09:15:00,January 16 2024,Presidio
+345678,HumanObservation,Bob Jones,2024-01-17,36.7783,-119.4179,Sequoiadendron giganteum,species,accepted,Giant Sequoia,Plantae,Tracheophyta,Pinopsida,Pinales,Cupressaceae,Sequoiadendron,giganteum,,11111,Ancient tree,native,2024-01-17,GPS,20.0,0.0001,WGS84,public,CC0,,,false,11:00:00,January 17 2024,Sequoia National Park
+"#;
+
+        let fixture = TestFixture::new(
+            "search_occurrences",
+            vec![csv_data]
+        );
+
+        let db = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "occurrenceID"
+        ).unwrap();
+
+        // Test searching for all records
+        let search_result = db.search(10, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(search_result.total, 3);
+        assert_eq!(search_result.results.len(), 3);
+
+        // Verify first occurrence fields
+        let first = &search_result.results[0];
+        // occurrenceID is parsed as a number since it's all digits
+        assert_eq!(first.get("occurrenceID").and_then(|v| v.as_i64()), Some(123456));
+        assert_eq!(first.get("basisOfRecord").and_then(|v| v.as_str()), Some("HumanObservation"));
+        assert_eq!(first.get("recordedBy").and_then(|v| v.as_str()), Some("John Doe"));
+        assert_eq!(first.get("eventDate").and_then(|v| v.as_str()), Some("2024-01-15"));
+        assert_eq!(first.get("decimalLatitude").and_then(|v| v.as_f64()), Some(34.0522));
+        assert_eq!(first.get("decimalLongitude").and_then(|v| v.as_f64()), Some(-118.2437));
+        assert_eq!(first.get("scientificName").and_then(|v| v.as_str()), Some("Quercus agrifolia"));
+        assert_eq!(first.get("taxonRank").and_then(|v| v.as_str()), Some("species"));
+        assert_eq!(first.get("kingdom").and_then(|v| v.as_str()), Some("Plantae"));
+        assert_eq!(first.get("family").and_then(|v| v.as_str()), Some("Fagaceae"));
+
+        // Test limit parameter
+        let limited = db.search(2, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(limited.total, 3);
+        assert_eq!(limited.results.len(), 2);
+
+        // Test offset parameter
+        let offset_result = db.search(2, 1, SearchParams::default(), None).unwrap();
+        assert_eq!(offset_result.total, 3);
+        assert_eq!(offset_result.results.len(), 2);
+        assert_eq!(offset_result.results[0].get("occurrenceID").and_then(|v| v.as_i64()), Some(789012));
+
+        // Test limit larger than available records
+        let all = db.search(100, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(all.total, 3);
+        assert_eq!(all.results.len(), 3);
+    }
+
+    #[test]
+    fn test_search_by_scientific_name() {
+        // Create test data with various scientific names
+        let csv_data = br#"occurrenceID,basisOfRecord,recordedBy,eventDate,decimalLatitude,decimalLongitude,scientificName,taxonRank,taxonomicStatus,vernacularName,kingdom,phylum,class,order,family,genus,specificEpithet,infraspecificEpithet,taxonID,occurrenceRemarks,establishmentMeans,georeferencedDate,georeferenceProtocol,coordinateUncertaintyInMeters,coordinatePrecision,geodeticDatum,accessRights,license,informationWithheld,modified,captive,eventTime,verbatimEventDate,verbatimLocality
+1,obs,John,2024-01-01,0,0,Foobar,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
+2,obs,Jane,2024-01-01,0,0,foo,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
+3,obs,Bob,2024-01-01,0,0,Foo,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
+4,obs,Alice,2024-01-01,0,0,Barfoo,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
+5,obs,Charlie,2024-01-01,0,0,Bar,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
+"#;
+
+        let fixture = TestFixture::new("search_scientific_name", vec![csv_data]);
+
+        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
+
+        // Search for "foo" (case-insensitive partial match)
+        let mut filters = HashMap::new();
+        filters.insert("scientificName".to_string(), "foo".to_string());
+        let search_result = db.search(10, 0, SearchParams {
+            filters,
+            sort_by: Some("occurrenceID".to_string()),
+            sort_direction: None,
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        }, None).unwrap();
+
+        // Should return 4 results: "Foobar", "foo", "Foo", "Barfoo"
+        assert_eq!(search_result.total, 4, "Expected total count of 4");
+        assert_eq!(search_result.results.len(), 4, "Expected 4 results containing 'foo'");
+
+        // Verify the names contain "foo" (case-insensitive)
+        for result in &search_result.results {
+            let name = result.get("scientificName")
+                .and_then(|v| v.as_str())
+                .expect("scientificName should exist")
+                .to_lowercase();
+            assert!(
+                name.contains("foo"),
+                "Expected '{}' to contain 'foo'",
+                result.get("scientificName").and_then(|v| v.as_str()).unwrap()
+            );
+        }
+
+        // Should NOT return "Bar"
+        for result in &search_result.results {
+            let name = result.get("scientificName").and_then(|v| v.as_str()).unwrap();
+            assert_ne!(
+                name,
+                "Bar",
+                "Should not return 'Bar'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_with_field_selection() {
+        // Create test data
+        let csv_data = br#"occurrenceID,basisOfRecord,recordedBy,eventDate,decimalLatitude,decimalLongitude,scientificName
+1,obs,John,2024-01-01,0,0,Test species
+"#;
+
+        let fixture = TestFixture::new("search_field_selection", vec![csv_data]);
+        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
+
+        // Search with specific fields
+        let search_result = db.search(10, 0, SearchParams::default(), Some(vec![
+            "occurrenceID".to_string(),
+            "scientificName".to_string(),
+        ])).unwrap();
+
+        assert_eq!(search_result.total, 1);
+        assert_eq!(search_result.results.len(), 1);
+
+        let first = &search_result.results[0];
+        // Should have the requested fields
+        assert!(first.contains_key("occurrenceID"));
+        assert!(first.contains_key("scientificName"));
+        // Should only have the requested fields (2 fields)
+        assert_eq!(first.len(), 2);
+    }
+
+    #[test]
+    fn test_canonical_column_names() {
+        let headers: Vec<String> = ["id", "Scientific_Name", "dwc:decimalLatitude", "scientificName", "notes"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let fields = vec![(0, "occurrenceID".to_string()), (2, "eventDate".to_string())];
+
+        assert_eq!(
+            core_column_terms(&headers, &fields),
+            vec!["occurrenceID", "scientificName", "decimalLatitude", "scientificName", "notes"]
+        );
+        assert_eq!(
+            canonical_column_names(&headers, &fields),
+            // Scientific_Name stays since a later column already has the name
+            vec!["occurrenceID", "Scientific_Name", "decimalLatitude", "scientificName", "notes"]
+        );
+    }
+
+    #[test]
+    fn test_create_types_columns_by_meta_xml_term() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("occurrence.csv");
+        std::fs::write(
+            &csv_path,
+            "id,elev,lat,decimalLatitude\n1,100.5,37.5,37.5\n2,1000,38.25,38.25\n",
+        ).unwrap();
+        // lat keeps its header since another column is already named after
+        // its term
+        let core_fields = vec![
+            (0, "occurrenceID".to_string()),
+            (1, "minimumElevationInMeters".to_string()),
+            (2, "decimalLatitude".to_string()),
+        ];
+
+        let db = Database::create_from_core_files_with_defaults(
+            &[csv_path],
+            &CsvFormat::default(),
+            &core_fields,
+            &[],
+            None,
+            &[],
+            &temp_dir.path().join("test.db"),
+            "occurrenceID",
+        ).unwrap();
+
+        let result = db.search(10, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(result.results[0]["lat"], serde_json::json!(37.5));
+        for (column, value) in [("minimumElevationInMeters", "100"), ("lat", "37")] {
+            let mut filters = HashMap::new();
+            filters.insert(column.to_string(), value.to_string());
+            let result = db.search(10, 0, SearchParams { filters, ..Default::default() }, None).unwrap();
+            assert_eq!(result.total, 1, "{column} should match {value} as a number");
+            assert_eq!(result.results[0]["occurrenceID"], "1");
+        }
+    }
+
+    #[test]
+    fn test_create_normalizes_core_headers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("occurrence.csv");
+        std::fs::write(
+            &csv_path,
+            "OccurrenceID,scientific_name,DecimalLatitude\n1,Quercus agrifolia,37.5\n",
+        ).unwrap();
+
+        let db = Database::create_from_core_files(
+            &[csv_path],
+            &[],
+            &temp_dir.path().join("test.db"),
+            "occurrenceID",
+        ).unwrap();
+
+        let mut filters = HashMap::new();
+        filters.insert("scientificName".to_string(), "quercus".to_string());
+        let result = db.search(10, 0, SearchParams { filters, ..Default::default() }, None).unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.results[0]["decimalLatitude"], serde_json::json!(37.5));
+        let renames: usize = db.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {COLUMN_RENAMES_TABLE}"), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(renames, 3);
+        assert!(db.import_warnings().iter().any(|w| {
+            w.kind == ImportWarningKind::RenamedColumn && w.subject == "scientific_name"
+        }));
+    }
+
+    #[test]
+    fn test_create_reads_core_with_meta_xml_format() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("occurrence.txt");
+        // Latin-1, tab-delimited, with a stray quote that isn't quoting anything
+        let mut contents = b"occurrenceID\tscientificName\tlocality\r\n".to_vec();
+        contents.extend_from_slice(b"1\tQuercus agrifolia\t5\" from Pe\xf1a Creek\r\n");
+        std::fs::write(&csv_path, contents).unwrap();
+        let format = CsvFormat {
+            delimiter: Some('\t'),
+            quote: Some(String::new()),
+            line_terminator: Some(r"\r\n"),
+            header_lines: Some(1),
+            column_names: vec![],
+            encoding: Some("latin-1"),
+        };
+
+        let db = Database::create_from_core_files_with_defaults(
+            &[csv_path],
+            &format,
+            &[],
+            &[],
+            None,
+            &[],
+            &temp_dir.path().join("test.db"),
+            "occurrenceID",
+        ).unwrap();
+
+        let result = db.search(10, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.results[0]["scientificName"], "Quercus agrifolia");
+        assert_eq!(result.results[0]["locality"], "5\" from Pe\u{f1}a Creek");
+    }
+
+    #[test]
+    fn test_create_names_headerless_core_columns() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("occurrence.txt");
+        std::fs::write(&csv_path, "1,Quercus agrifolia,37.5\n2,Quercus lobata,38\n").unwrap();
+        let format = CsvFormat {
+            header_lines: Some(0),
+            column_names: vec![
+                "occurrenceID".to_string(),
+                "scientificName".to_string(),
+                "decimalLatitude".to_string(),
+            ],
+            ..Default::default()
+        };
+        let defaults = vec![FieldDefault {
+            index: None,
+            term_name: "basisOfRecord".to_string(),
+            value: "PreservedSpecimen".to_string(),
+        }];
+
+        let db = Database::create_from_core_files_with_defaults(
+            &[csv_path],
+            &format,
+            &[],
+            &defaults,
+            None,
+            &[],
+            &temp_dir.path().join("test.db"),
+            "occurrenceID",
+        ).unwrap();
+
+        // Neither row was taken as a header
+        let result = db.search(10, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(result.total, 2);
+        let mut filters = HashMap::new();
+        filters.insert("scientificName".to_string(), "lobata".to_string());
+        let result = db.search(10, 0, SearchParams { filters, ..Default::default() }, None).unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.results[0]["occurrenceID"], "2");
+        assert_eq!(result.results[0]["decimalLatitude"], serde_json::json!(38.0));
+        assert_eq!(result.results[0]["basisOfRecord"], "PreservedSpecimen");
+    }
+
+    #[test]
+    fn test_create_with_extensions() {
+        // Create occurrence CSV
+        let occurrence_csv = br#"occurrenceID,scientificName
+1,Species A
+2,Species B
+"#;
+
+        // Create multimedia extension CSV
+        let multimedia_csv = br#"occurrenceID,type,identifier
+1,StillImage,http://example.com/img1.jpg
+1,StillImage,http://example.com/img2.jpg
+2,StillImage,http://example.com/img3.jpg
+"#;
+
+        // Set up fixture
+        let temp_dir = std::env::temp_dir()
+            .join("chuck_test_db_extensions");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let occurrence_path = temp_dir.join("occurrence.csv");
+        let multimedia_path = temp_dir.join("multimedia.csv");
+        let db_path = temp_dir.join("test.db");
+
+        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
+        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
+
+        // Create extension info
+        let extensions = vec![ExtensionInfo {
+            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
+            location: multimedia_path.clone(),
+            extension: chuck_core::DwcaExtension::SimpleMultimedia,
+            core_id_column: "occurrenceID".to_string(),
+            fields: vec![],
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
+        }];
+
+        // Create database with extensions
+        let db = Database::create_from_core_files(
+            &[occurrence_path],
+            &extensions,
+            &db_path,
+            "occurrenceID"
+        ).unwrap();
+
+        // Verify occurrences table was created
+        assert_eq!(db.count_records().unwrap(), 2);
+
+        // Verify extension table is tracked
+        assert_eq!(db.extension_tables.len(), 1);
+        assert_eq!(db.extension_tables[0].0, chuck_core::DwcaExtension::SimpleMultimedia);
+        assert_eq!(db.extension_tables[0].1, "occurrenceID");
+
+        // Search and verify extensions are included
+        let search_result = db.search(10, 0, SearchParams::default(), None).unwrap();
+        assert_eq!(search_result.results.len(), 2);
+
+        // Check first occurrence has multimedia array
+        let first = &search_result.results[0];
+        assert!(first.contains_key("multimedia"));
+
+        let multimedia = first.get("multimedia").unwrap();
+        assert!(multimedia.is_array());
+
+        let multimedia_array = multimedia.as_array().unwrap();
+        assert_eq!(multimedia_array.len(), 2); // Two images for occurrence 1
+
+        // Check multimedia items have expected fields
+        let first_image = &multimedia_array[0];
+
+        // occurrenceID is stored as VARCHAR in extension tables, so it comes back as string
+        assert_eq!(
+            first_image.get("occurrenceID").and_then(|v| v.as_str()),
+            Some("1")
+        );
+        assert_eq!(
+            first_image.get("type").and_then(|v| v.as_str()),
+            Some("StillImage")
+        );
+        assert_eq!(
+            first_image.get("identifier").and_then(|v| v.as_str()),
+            Some("http://example.com/img1.jpg")
+        );
+
+        // Check second occurrence has multimedia array
+        let second = &search_result.results[1];
+        let multimedia_second = second.get("multimedia").unwrap().as_array().unwrap();
+        assert_eq!(multimedia_second.len(), 1); // One image for occurrence 2
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_export_parquet_with_extensions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let occurrence_path = temp_dir.path().join("occurrence.csv");
+        let multimedia_path = temp_dir.path().join("multimedia.csv");
+        std::fs::write(
+            &occurrence_path,
+            "occurrenceID,scientificName,decimalLatitude\n1,Species A,37.5\n2,Species B,38\n",
+        ).unwrap();
+        std::fs::write(
+            &multimedia_path,
+            "occurrenceID,identifier\n1,http://example.com/img1.jpg\n1,http://example.com/img2.jpg\n",
+        ).unwrap();
+        let extensions = vec![ExtensionInfo {
+            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
+            location: multimedia_path,
+            extension: chuck_core::DwcaExtension::SimpleMultimedia,
+            core_id_column: "occurrenceID".to_string(),
+            fields: vec![],
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
+        }];
+        let db = Database::create_from_core_files(
+            &[occurrence_path],
+            &extensions,
+            &temp_dir.path().join("test.db"),
+            "occurrenceID",
+        ).unwrap();
+        let mut filters = HashMap::new();
+        filters.insert("scientificName".to_string(), "Species A".to_string());
+        let parquet_path = temp_dir.path().join("out.parquet");
+
+        let written = db.export_parquet(
+            SearchParams { filters, ..Default::default() },
+            &parquet_path,
+            true,
+        ).unwrap();
+
+        assert_eq!(written, 1);
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        let (latitude_type, images): (String, i64) = conn.query_row(
+            &format!(
+                "SELECT typeof(\"decimalLatitude\"), len(multimedia) FROM read_parquet('{}')",
+                parquet_path.display()
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(latitude_type, "DOUBLE");
+        assert_eq!(images, 2);
+    }
+
+    #[test]
+    fn test_open_database_detects_extensions() {
+        // Create occurrence CSV
+        let occurrence_csv = br#"occurrenceID,scientificName
+1,Species A
+"#;
+
+        // Create extension CSV
+        let multimedia_csv = br#"occurrenceID,identifier
+1,http://example.com/img1.jpg
+"#;
+
+        let temp_dir = std::env::temp_dir()
+            .join("chuck_test_db_open_extensions");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let occurrence_path = temp_dir.join("occurrence.csv");
+        let multimedia_path = temp_dir.join("multimedia.csv");
+        let db_path = temp_dir.join("test.db");
+
+        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
+        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
+
+        let extensions = vec![ExtensionInfo {
+            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
+            location: multimedia_path,
+            extension: chuck_core::DwcaExtension::SimpleMultimedia,
+            core_id_column: "occurrenceID".to_string(),
+            fields: vec![],
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
+        }];
+
+        // Create database
+        let db = Database::create_from_core_files(
+            &[occurrence_path],
+            &extensions,
+            &db_path,
+            "occurrenceID"
+        ).unwrap();
+
+        // Drop first connection before reopening - on Windows, files are locked while open
+        drop(db);
+
+        // Reopen the database with extension info
+        let reopened_db = Database::open(&db_path, "occurrenceID".to_string(), &extensions).unwrap();
+
+        // Verify it has the extension table info
+        assert_eq!(reopened_db.extension_tables.len(), 1);
+        assert_eq!(reopened_db.extension_tables[0].0, chuck_core::DwcaExtension::SimpleMultimedia);
+        assert_eq!(reopened_db.extension_tables[0].1, "occurrenceID");
+
+        // Drop database before cleanup - on Windows, files are locked while open
+        drop(reopened_db);
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_sql_parts_only_allows_known_sort_by() {
+        let params = crate::search_params::SearchParams {
+            filters: HashMap::new(),
+            sort_by: Some("foo".to_string()),
+            sort_direction: None,
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        };
+        let (_, _, _, order_clause) = Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
+        assert_eq!(order_clause, "");
+    }
+
+    #[test]
+    fn test_types_come_from_term_mapping() {
+        assert_eq!(type_override("decimalLatitude"), Some("DOUBLE"));
+        assert_eq!(type_override("isSequenced"), Some("BOOLEAN"));
+        // Typed in queries but not on read
+        assert_eq!(type_override("minimumElevationInMeters"), None);
+        assert_eq!(
+            Database::typed_column("minimumElevationInMeters", &HashMap::new()),
+            ("TRY_CAST(\"minimumElevationInMeters\" AS DOUBLE)".to_string(), Some("DOUBLE"))
+        );
+        assert_eq!(Database::typed_column("eventDate", &HashMap::new()), ("\"eventDate\"".to_string(), None));
+    }
+
+    #[test]
+    fn test_sql_parts_includes_bbox_params_in_where_clause() {
+        // Create params with bbox fields populated
+        let params = crate::search_params::SearchParams {
+            filters: HashMap::new(),
+            sort_by: None,
+            sort_direction: None,
+            nelat: Some("40.0".to_string()),
+            nelng: Some("-120.0".to_string()),
+            swlat: Some("35.0".to_string()),
+            swlng: Some("-125.0".to_string()),
+        };
+
+        let (
+            _select_fields,
+            where_clause,
+            where_interpolations,
+            _order_clause
+        ) = Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
+
+        // Bbox params should generate WHERE clause conditions
+        assert!(where_clause.contains("decimalLatitude"), "Should filter by decimalLatitude");
+        assert!(where_clause.contains("decimalLongitude"), "Should filter by decimalLongitude");
+        assert_eq!(where_interpolations.len(), 4, "Should have 4 interpolations for bbox (nelat, swlat, nelng, swlng)");
+    }
+
+    #[test]
+    fn test_sql_parts_bbox_params_work_with_filters() {
+        // Create params with both filters and bbox
+        let mut filters = HashMap::new();
+        filters.insert("scientificName".to_string(), "test".to_string());
+
+        let params = crate::search_params::SearchParams {
+            filters,
+            sort_by: None,
+            sort_direction: None,
+            nelat: Some("40.0".to_string()),
+            nelng: Some("-120.0".to_string()),
+            swlat: Some("35.0".to_string()),
+            swlng: Some("-125.0".to_string()),
+        };
+
+        let (
+            _select_fields,
+            where_clause,
+            where_interpolations,
+            _order_clause
+        ) = Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
+
+        // Should have both scientificName filter AND bbox conditions
+        assert!(where_clause.contains("scientificName"), "Should have scientificName filter");
+        assert!(where_clause.contains("decimalLatitude"), "Should have decimalLatitude bbox conditions");
+        assert!(where_clause.contains("decimalLongitude"), "Should have decimalLongitude bbox conditions");
+        // Bbox params should not appear by name (they're converted to lat/lng checks)
+        assert!(!where_clause.contains("nelat"), "Should not have nelat column name in WHERE clause");
+        assert!(!where_clause.contains("nelng"), "Should not have nelng column name in WHERE clause");
+        assert!(!where_clause.contains("swlat"), "Should not have swlat column name in WHERE clause");
+        assert!(!where_clause.contains("swlng"), "Should not have swlng column name in WHERE clause");
+        // 1 for scientificName + 4 for bbox
+        assert_eq!(where_interpolations.len(), 5, "Should have 5 interpolations (1 for scientificName + 4 for bbox)");
+    }
+
+    #[test]
+    fn test_get_occurrence_with_extensions() {
+        // Create occurrence and multimedia test data
+        let occurrence_csv = br#"occurrenceID,scientificName,decimalLatitude,decimalLongitude
+1,Species A,34.05,-118.24
+2,Species B,37.77,-122.41
+"#;
+
+        let multimedia_csv = br#"occurrenceID,type,identifier
+1,StillImage,http://example.com/img1.jpg
+1,StillImage,http://example.com/img2.jpg
+"#;
+
+        let temp_dir = std::env::temp_dir().join("chuck_test_get_occurrence");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let occurrence_path = temp_dir.join("occurrence.csv");
+        let multimedia_path = temp_dir.join("multimedia.csv");
+        let db_path = temp_dir.join("test.db");
+
+        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
+        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
+
+        let extensions = vec![ExtensionInfo {
+            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
+            location: multimedia_path,
+            extension: chuck_core::DwcaExtension::SimpleMultimedia,
+            core_id_column: "occurrenceID".to_string(),
+            fields: vec![],
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
+        }];
+
+        let db = Database::create_from_core_files(
+            &[occurrence_path],
+            &extensions,
+            &db_path,
+            "occurrenceID"
+        ).unwrap();
+
+        // Get occurrence by ID
+        let occurrence = db.get_occurrence("occurrenceID", "1").unwrap();
+
+        // Verify core fields
+        assert_eq!(
+            occurrence.get("scientificName").and_then(|v| v.as_str()),
+            Some("Species A")
+        );
+        assert_eq!(
+            occurrence.get("decimalLatitude").and_then(|v| v.as_f64()),
+            Some(34.05)
+        );
+
+        // Verify multimedia extension
+        let multimedia = occurrence.get("multimedia").unwrap();
+        assert!(multimedia.is_array());
+        let multimedia_array = multimedia.as_array().unwrap();
+        assert_eq!(multimedia_array.len(), 2);
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_get_available_columns() {
+        // Create a temporary directory for test database
+        let temp_dir = std::env::temp_dir().join("chuck_test_available_columns");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+
+        // Create test CSV file
+        let csv_path = temp_dir.join("test.csv");
+        std::fs::write(
+            &csv_path,
+            "id,scientificName,decimalLatitude,eventDate\n\
+             1,Species A,10.5,2023-01-01\n\
+             2,Species B,20.3,2023-01-02\n"
+        ).unwrap();
+
+        // Create database from CSV
+        let db = Database::create_from_core_files(
+            &[csv_path],
+            &[],
+            &db_path,
+            "id"
+        ).unwrap();
+
+        // Test getting available columns
+        let columns = db.get_available_columns().unwrap();
+
+        assert_eq!(columns.len(), 4);
+        assert!(columns.contains(&"id".to_string()));
+        assert!(columns.contains(&"scientificName".to_string()));
+        assert!(columns.contains(&"decimalLatitude".to_string()));
+        assert!(columns.contains(&"eventDate".to_string()));
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_search_with_order() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_search_order");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+
+        let csv_path = temp_dir.join("test.csv");
+        std::fs::write(
+            &csv_path,
+            "id,scientificName\n\
+             3,Zebra\n\
+             1,Apple\n\
+             2,Banana\n"
+        ).unwrap();
+
+        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id").unwrap();
+
+        // Test ASC order
+        let params_asc = SearchParams {
+            filters: HashMap::new(),
+            sort_by: Some("scientificName".to_string()),
+            sort_direction: Some("ASC".to_string()),
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        };
+        let result_asc = db.search(10, 0, params_asc, Some(vec!["scientificName".to_string()])).unwrap();
+        let first_name = result_asc.results[0].get("scientificName").unwrap().as_str().unwrap();
+        assert_eq!(first_name, "Apple");
+
+        // Test DESC order
+        let params_desc = SearchParams {
+            filters: HashMap::new(),
+            sort_by: Some("scientificName".to_string()),
+            sort_direction: Some("DESC".to_string()),
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        };
+        let result_desc = db.search(10, 0, params_desc, Some(vec!["scientificName".to_string()])).unwrap();
+        let first_name_desc = result_desc.results[0].get("scientificName").unwrap().as_str().unwrap();
+        assert_eq!(first_name_desc, "Zebra");
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_search_with_multiple_sort_columns() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_multiple_sort_columns");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+
+        let csv_path = temp_dir.join("test.csv");
+        std::fs::write(
+            &csv_path,
+            "id,family,scientificName,eventDate\n\
+             1,Fagaceae,Quercus lobata,2024-01-01\n\
+             2,Apiaceae,Sanicula crassicaulis,2024-03-01\n\
+             3,Fagaceae,Quercus agrifolia,2024-01-01\n\
+             4,Fagaceae,Quercus agrifolia,2024-02-01\n"
+        ).unwrap();
+
+        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id").unwrap();
+
+        let mut params = SearchParams::default();
+        params.set_sort_columns(&[("family", "ASC"), ("scientificName", "ASC"), ("eventDate", "DESC")]);
+        let fields = vec!["scientificName".to_string(), "eventDate".to_string()];
+        let result = db.search(10, 0, params, Some(fields)).unwrap();
+        let rows: Vec<(&str, &str)> = result.results.iter()
+            .map(|r| (
+                r.get("scientificName").unwrap().as_str().unwrap(),
+                r.get("eventDate").unwrap().as_str().unwrap(),
+            ))
+            .collect();
+        assert_eq!(rows, vec![
+            ("Sanicula crassicaulis", "2024-03-01"),
+            ("Quercus agrifolia", "2024-02-01"),
+            ("Quercus agrifolia", "2024-01-01"),
+            ("Quercus lobata", "2024-01-01"),
+        ]);
+
+        // Unknown columns are dropped rather than rejecting the whole sort
+        let mut params = SearchParams::default();
+        params.set_sort_columns(&[("foo", "ASC"), ("family", "DESC"), ("family", "ASC")]);
+        let (_, _, _, order_clause) = db.query_parts(params, None, "id", &[]).unwrap();
+        assert_eq!(order_clause, " ORDER BY \"family\" DESC");
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_create_with_existing_table() {
-        let fixture = TestFixture::new(
-            "existing_table",
-            vec![b"id,name\n1,Alice\n2,Bob\n"]
-        );
+    fn test_search_pages_with_cursor() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_search_cursor");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
 
-        // First call should succeed
-        let result1 = Database::create_from_core_files(
-            &fixture.csv_paths,
-            &[],
-            &fixture.db_path,
-            "id"
-        );
-        assert!(result1.is_ok());
-        let db1 = result1.unwrap();
-        assert_eq!(db1.count_records().unwrap(), 2);
+        let csv_path = temp_dir.join("test.csv");
+        std::fs::write(
+            &csv_path,
+            "id,family,scientificName\n\
+             1,Fagaceae,Quercus lobata\n\
+             2,,Unknown\n\
+             3,Apiaceae,Sanicula crassicaulis\n\
+             4,Fagaceae,Quercus agrifolia\n\
+             5,,Unknown\n\
+             6,Fagaceae,Quercus kelloggii\n"
+        ).unwrap();
 
-        // Drop first connection before opening second - on Windows, files are locked while open
-        drop(db1);
+        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id").unwrap();
+        let mut params = SearchParams::default();
+        params.set_sort_columns(&[("family", "DESC")]);
+        let fields = Some(vec!["id".to_string()]);
+        let ids = |result: &crate::commands::archive::SearchResult| -> Vec<String> {
+            result.results.iter().map(|r| r["id"].to_string()).collect()
+        };
 
-        // Second call should recognize existing table and not alter it
-        let result2 = Database::create_from_core_files(
-            &fixture.csv_paths,
-            &[],
-            &fixture.db_path,
-            "id"
+        let by_offset = db.search(10, 0, params.clone(), fields.clone()).unwrap();
+        assert!(by_offset.next_cursor.is_none());
+
+        let mut by_cursor = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = db.search_with_execution_info(
+                4, 0, cursor.as_deref(), params.clone(), fields.clone(), false
+            ).unwrap();
+            assert_eq!(page.total, 6);
+            by_cursor.extend(ids(&page));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(by_cursor, ids(&by_offset));
+        assert!(by_offset.results.iter().all(|r| r.keys().all(|k| !k.starts_with(CURSOR_FIELD_PREFIX))));
+
+        // Unsorted pages keep file order and don't sort for a cursor
+        let unsorted = db.search(2, 0, SearchParams::default(), fields.clone()).unwrap();
+        assert_eq!(ids(&unsorted), vec!["\"1\"", "\"2\""]);
+        assert!(unsorted.next_cursor.is_none());
+
+        // A cursor only works for the sort it was made with
+        let first = db.search(2, 0, params.clone(), fields.clone()).unwrap();
+        let result = db.search_with_execution_info(
+            2, 0, first.next_cursor.as_deref(), SearchParams::default(), fields, false
         );
+        assert!(matches!(result, Err(ChuckError::SearchCursor(_))));
 
-        assert!(result2.is_ok());
-        let db2 = result2.unwrap();
-        assert_eq!(db2.count_records().unwrap(), 2);
-
-        // Drop database before fixture cleanup - on Windows, files are locked while open
-        drop(db2);
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_create_with_multiple_core_files() {
-        let fixture = TestFixture::new(
-            "multiple_cores",
-            vec![
-                b"id,name\n
-                  1,Collins\n
-                  2,Gardiner\n",
-                b"3,Lizzy\n
-                  4,Jane\n"
-            ]
-        );
+    fn test_materialized_filter_is_reused() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_materialized_filter");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
 
-        let result = Database::create_from_core_files(
-            &fixture.csv_paths,
-            &[],
-            &fixture.db_path,
-            "id"
-        );
-        assert!(result.is_ok());
-        let db = result.unwrap();
-        assert_eq!(db.count_records().unwrap(), 4);
+        let csv_path = temp_dir.join("test.csv");
+        std::fs::write(
+            &csv_path,
+            "id,genus,scientificName\n\
+             1,Quercus,Quercus lobata\n\
+             2,Sanicula,Sanicula crassicaulis\n\
+             3,Quercus,Quercus agrifolia\n"
+        ).unwrap();
 
-        // Cleanup happens automatically via Drop
-    }
+        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id")
+            .unwrap()
+            .with_filter_cache(temp_dir.join("filter_cache"), &db_path);
+        let params = SearchParams {
+            filters: HashMap::from([("genus".to_string(), "Quercus".to_string())]),
+            ..SearchParams::default()
+        };
 
-    // DwC-A accepts a variety of date formats that won't be captured in a
-    // duckdb DATE column, so we just need to use a VARCHAR
-    #[test]
-    fn test_create_with_multiple_date_formats() {
-        let fixture = TestFixture::new(
-            "multiple_date_formats",
-            vec![
-                // For some reason, when you have one line with partial dates
-                // and another with mixed formats like this, the
-                // auto-detected date_format ends up as %m/%d/%Y... but not
-                // if you only have the one line with mixed formats. duckdb
-                // will choke on the partials as well
-                b"id,eventDate,verbatimEventDate\n
-                  1,,\n
-                  2,1988-10-25,10/25/1988\n
-                  3,1967-07,1967-07-\n"
-            ]
-        );
-        let result = Database::create_from_core_files(
-            &fixture.csv_paths,
-            &[],
-            &fixture.db_path,
-            "id"
-        );
-        let db = result.unwrap();
-        assert_eq!(db.count_records().unwrap(), 3);
+        let (_, where_clause, _, _) = db.query_parts(params.clone(), None, "id", &[]).unwrap();
+        assert!(!where_clause.contains("read_parquet"));
+
+        assert_eq!(db.materialize_filter(params.clone()).unwrap(), 2);
+        let (_, where_clause, interpolations, _) = db.query_parts(params.clone(), None, "id", &[]).unwrap();
+        assert!(where_clause.contains("read_parquet"));
+        assert!(interpolations.is_empty());
+
+        let result = db.search(10, 0, params.clone(), Some(vec!["scientificName".to_string()])).unwrap();
+        assert_eq!(result.total, 2);
+        assert!(result.results.iter().all(|r| r["scientificName"].as_str().unwrap().starts_with("Quercus")));
+
+        // Materializing again reuses the file
+        assert_eq!(db.materialize_filter(params).unwrap(), 2);
+
+        // Other filters still run their WHERE clause
+        let other = SearchParams {
+            filters: HashMap::from([("genus".to_string(), "Sanicula".to_string())]),
+            ..SearchParams::default()
+        };
+        let (_, where_clause, _, _) = db.query_parts(other, None, "id", &[]).unwrap();
+        assert!(!where_clause.contains("read_parquet"));
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_search_returns_occurrence_records() {
-        // Create a CSV with Darwin Core occurrence fields
-        let csv_data = br#"occurrenceID,basisOfRecord,recordedBy,eventDate,decimalLatitude,decimalLongitude,scientificName,taxonRank,taxonomicStatus,vernacularName,kingdom,phylum,class,order,family,genus,specificEpithet,infraspecificEpithet,taxonID,occurrenceRemarks,establishmentMeans,georeferencedDate,georeferenceProtocol,coordinateUncertaintyInMeters,coordinatePrecision,geodeticDatum,accessRights,license,informationWithheld,modified,captive,eventTime,verbatimEventDate,verbatimLocality
-123456,HumanObservation,John Doe,2024-01-15,34.0522,-118.2437,Quercus agrifolia,species,accepted,Coast Live Oak,Plantae,Tracheophyta,Magnoliopsida,Fagales,Fagaceae,Quercus,agrifolia,,12345,Observed in park,native,2024-01-15,GPS,10.0,0.0001,WGS84,public,CC-BY,,,false,14:30:00,January 15 2024,Golden Gate Park
-789012,HumanObservation,Jane Smith,2024-01-16,37.7749,-122.4194,Pinus radiata,species,accepted,Monterey Pine,Plantae,Tracheophyta,Pinopsida,Pinales,Pinaceae,Pinus,radiata,,67890,Tall specimen,native,2024-01-16,GPS,5.0,0.0001,WGS84,public,CC-BY-NC,,,false,09:15:00,January 16 2024,Presidio
-345678,HumanObservation,Bob Jones,2024-01-17,36.7783,-119.4179,Sequoiadendron giganteum,species,accepted,Giant Sequoia,Plantae,Tracheophyta,Pinopsida,Pinales,Cupressaceae,Sequoiadendron,giganteum,,11111,Ancient tree,native,2024-01-17,GPS,20.0,0.0001,WGS84,public,CC0,,,false,11:00:00,January 17 2024,Sequoia National Park
-"#;
-
-        let fixture = TestFixture::new(
-            "search_occurrences",
-            vec![csv_data]
-        );
+    fn test_search_with_numeric_order() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_numeric_order");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
 
-        let db = Database::create_from_core_files(
-            &fixture.csv_paths,
-            &[],
-            &fixture.db_path,
-            "occurrenceID"
+        let csv_path = temp_dir.join("test.csv");
+        std::fs::write(
+            &csv_path,
+            "occurrenceID,scientificName,decimalLatitude\n\
+             1,Species A,10.5\n\
+             2,Species B,-5.3\n\
+             3,Species C,2.1\n\
+             4,Species D,-15.7\n"
         ).unwrap();
 
-        // Test searching for all records
-        let search_result = db.search(10, 0, SearchParams::default(), None).unwrap();
-        assert_eq!(search_result.total, 3);
-        assert_eq!(search_result.results.len(), 3);
+        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id").unwrap();
 
-        // Verify first occurrence fields
-        let first = &search_result.results[0];
-        // occurrenceID is parsed as a number since it's all digits
-        assert_eq!(first.get("occurrenceID").and_then(|v| v.as_i64()), Some(123456));
-        assert_eq!(first.get("basisOfRecord").and_then(|v| v.as_str()), Some("HumanObservation"));
-        assert_eq!(first.get("recordedBy").and_then(|v| v.as_str()), Some("John Doe"));
-        assert_eq!(first.get("eventDate").and_then(|v| v.as_str()), Some("2024-01-15"));
-        assert_eq!(first.get("decimalLatitude").and_then(|v| v.as_f64()), Some(34.0522));
-        assert_eq!(first.get("decimalLongitude").and_then(|v| v.as_f64()), Some(-118.2437));
-        assert_eq!(first.get("scientificName").and_then(|v| v.as_str()), Some("Quercus agrifolia"));
-        assert_eq!(first.get("taxonRank").and_then(|v| v.as_str()), Some("species"));
-        assert_eq!(first.get("kingdom").and_then(|v| v.as_str()), Some("Plantae"));
-        assert_eq!(first.get("family").and_then(|v| v.as_str()), Some("Fagaceae"));
+        // Test ASC order - should be numeric ordering
+        let params_asc = SearchParams {
+            filters: HashMap::new(),
+            sort_by: Some("decimalLatitude".to_string()),
+            sort_direction: Some("ASC".to_string()),
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        };
+        let result_asc = db.search(10, 0, params_asc, Some(vec!["occurrenceID".to_string(), "decimalLatitude".to_string()])).unwrap();
 
-        // Test limit parameter
-        let limited = db.search(2, 0, SearchParams::default(), None).unwrap();
-        assert_eq!(limited.total, 3);
-        assert_eq!(limited.results.len(), 2);
+        // If sorted numerically: -15.7, -5.3, 2.1, 10.5 (ids: 4, 2, 3, 1)
+        // If sorted alphabetically: -15.7, -5.3, 10.5, 2.1 (ids: 4, 2, 1, 3)
+        // Values come back as numbers, so use as_i64()
+        let first_id = result_asc.results[0].get("occurrenceID").unwrap().as_i64().unwrap();
+        let second_id = result_asc.results[1].get("occurrenceID").unwrap().as_i64().unwrap();
+        let third_id = result_asc.results[2].get("occurrenceID").unwrap().as_i64().unwrap();
+        let fourth_id = result_asc.results[3].get("occurrenceID").unwrap().as_i64().unwrap();
 
-        // Test offset parameter
-        let offset_result = db.search(2, 1, SearchParams::default(), None).unwrap();
-        assert_eq!(offset_result.total, 3);
-        assert_eq!(offset_result.results.len(), 2);
-        assert_eq!(offset_result.results[0].get("occurrenceID").and_then(|v| v.as_i64()), Some(789012));
+        assert_eq!(first_id, 4, "Expected -15.7 first (numeric sort)");
+        assert_eq!(second_id, 2, "Expected -5.3 second (numeric sort)");
+        assert_eq!(third_id, 3, "Expected 2.1 third (numeric sort)");
+        assert_eq!(fourth_id, 1, "Expected 10.5 fourth (numeric sort)");
 
-        // Test limit larger than available records
-        let all = db.search(100, 0, SearchParams::default(), None).unwrap();
-        assert_eq!(all.total, 3);
-        assert_eq!(all.results.len(), 3);
+        // Test DESC order
+        let params_desc = SearchParams {
+            filters: HashMap::new(),
+            sort_by: Some("decimalLatitude".to_string()),
+            sort_direction: Some("DESC".to_string()),
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        };
+        let result_desc = db.search(10, 0, params_desc, Some(vec!["occurrenceID".to_string(), "decimalLatitude".to_string()])).unwrap();
+        let first_id_desc = result_desc.results[0].get("occurrenceID").unwrap().as_i64().unwrap();
+        assert_eq!(first_id_desc, 1, "Expected 10.5 first in DESC order");
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_search_by_scientific_name() {
-        // Create test data with various scientific names
-        let csv_data = br#"occurrenceID,basisOfRecord,recordedBy,eventDate,decimalLatitude,decimalLongitude,scientificName,taxonRank,taxonomicStatus,vernacularName,kingdom,phylum,class,order,family,genus,specificEpithet,infraspecificEpithet,taxonID,occurrenceRemarks,establishmentMeans,georeferencedDate,georeferenceProtocol,coordinateUncertaintyInMeters,coordinatePrecision,geodeticDatum,accessRights,license,informationWithheld,modified,captive,eventTime,verbatimEventDate,verbatimLocality
-1,obs,John,2024-01-01,0,0,Foobar,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
-2,obs,Jane,2024-01-01,0,0,foo,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
-3,obs,Bob,2024-01-01,0,0,Foo,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
-4,obs,Alice,2024-01-01,0,0,Barfoo,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
-5,obs,Charlie,2024-01-01,0,0,Bar,species,accepted,,,,,,,,,,,,,,,,,,,,,,,,,
+    fn test_search_with_decimal_latitude_range_filter() {
+        // Create test data with various decimalLatitude values
+        let csv_data = br#"occurrenceID,scientificName,decimalLatitude,decimalLongitude
+1,Species A,3.0,0.0
+2,Species B,3.1,0.0
+3,Species C,3.14,0.0
+4,Species D,3.141,0.0
+5,Species E,30.0,0.0
+6,Species F,301.3,0.0
+7,Species G,2.9,0.0
 "#;
 
-        let fixture = TestFixture::new("search_scientific_name", vec![csv_data]);
+        let fixture = TestFixture::new("search_decimal_latitude_range", vec![csv_data]);
+        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
+
+        // Test 1: Search for "3" should match 3.0-3.9999 (ids: 1, 2, 3, 4)
+        let mut filters = HashMap::new();
+        filters.insert("decimalLatitude".to_string(), "3".to_string());
+        let search_result = db.search(10, 0, SearchParams {
+            filters: filters.clone(),
+            sort_by: Some("occurrenceID".to_string()),
+            sort_direction: None,
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        }, None).unwrap();
+
+        assert_eq!(search_result.total, 4, "Search for '3' should match 3.0, 3.1, 3.14, 3.141");
+        let ids: Vec<i64> = search_result.results.iter()
+            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3, 4], "Should match records with lat 3.x");
+
+        // Test 2: Search for "3.1" should match 3.1-3.19999 (ids: 2, 3, 4)
+        let mut filters = HashMap::new();
+        filters.insert("decimalLatitude".to_string(), "3.1".to_string());
+        let search_result = db.search(10, 0, SearchParams {
+            filters: filters.clone(),
+            sort_by: Some("occurrenceID".to_string()),
+            sort_direction: None,
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        }, None).unwrap();
 
-        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
+        assert_eq!(search_result.total, 3, "Search for '3.1' should match 3.1, 3.14, 3.141");
+        let ids: Vec<i64> = search_result.results.iter()
+            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
+            .collect();
+        assert_eq!(ids, vec![2, 3, 4], "Should match records with lat 3.1x");
 
-        // Search for "foo" (case-insensitive partial match)
+        // Test 3: Search for "3.14" should match 3.14-3.149999 (ids: 3, 4)
         let mut filters = HashMap::new();
-        filters.insert("scientificName".to_string(), "foo".to_string());
+        filters.insert("decimalLatitude".to_string(), "3.14".to_string());
         let search_result = db.search(10, 0, SearchParams {
-            filters,
+            filters: filters.clone(),
             sort_by: Some("occurrenceID".to_string()),
             sort_direction: None,
             nelat: None,
@@ -1332,665 +4848,1130 @@ mod tests {
             swlng: None,
         }, None).unwrap();
 
-        // Should return 4 results: "Foobar", "foo", "Foo", "Barfoo"
-        assert_eq!(search_result.total, 4, "Expected total count of 4");
-        assert_eq!(search_result.results.len(), 4, "Expected 4 results containing 'foo'");
+        assert_eq!(search_result.total, 2, "Search for '3.14' should match 3.14, 3.141");
+        let ids: Vec<i64> = search_result.results.iter()
+            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
+            .collect();
+        assert_eq!(ids, vec![3, 4], "Should match records with lat 3.14x");
 
-        // Verify the names contain "foo" (case-insensitive)
-        for result in &search_result.results {
-            let name = result.get("scientificName")
-                .and_then(|v| v.as_str())
-                .expect("scientificName should exist")
-                .to_lowercase();
-            assert!(
-                name.contains("foo"),
-                "Expected '{}' to contain 'foo'",
-                result.get("scientificName").and_then(|v| v.as_str()).unwrap()
-            );
-        }
+        // Test 4: Search for "30" should NOT match "3.x" (should only match 30.x)
+        let mut filters = HashMap::new();
+        filters.insert("decimalLatitude".to_string(), "30".to_string());
+        let search_result = db.search(10, 0, SearchParams {
+            filters: filters.clone(),
+            sort_by: Some("occurrenceID".to_string()),
+            sort_direction: None,
+            nelat: None,
+            nelng: None,
+            swlat: None,
+            swlng: None,
+        }, None).unwrap();
 
-        // Should NOT return "Bar"
-        for result in &search_result.results {
-            let name = result.get("scientificName").and_then(|v| v.as_str()).unwrap();
-            assert_ne!(
-                name,
-                "Bar",
-                "Should not return 'Bar'"
-            );
-        }
+        assert_eq!(search_result.total, 1, "Search for '30' should only match 30.0");
+        let ids: Vec<i64> = search_result.results.iter()
+            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
+            .collect();
+        assert_eq!(ids, vec![5], "Should match only record with lat 30.0");
     }
 
     #[test]
-    fn test_search_with_field_selection() {
+    fn test_get_autocomplete_suggestions_rejects_non_varchar_columns() {
         // Create test data
-        let csv_data = br#"occurrenceID,basisOfRecord,recordedBy,eventDate,decimalLatitude,decimalLongitude,scientificName
-1,obs,John,2024-01-01,0,0,Test species
+        let csv_data = br#"occurrenceID,scientificName,decimalLatitude,decimalLongitude
+1,Species A,3.0,0.0
 "#;
 
-        let fixture = TestFixture::new("search_field_selection", vec![csv_data]);
+        let fixture = TestFixture::new("autocomplete_type_check", vec![csv_data]);
         let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
 
-        // Search with specific fields
-        let search_result = db.search(10, 0, SearchParams::default(), Some(vec![
-            "occurrenceID".to_string(),
-            "scientificName".to_string(),
-        ])).unwrap();
+        // Test that VARCHAR column works
+        let result = db.get_autocomplete_suggestions("scientificName", "Spec", 10);
+        assert!(result.is_ok(), "scientificName (VARCHAR) should work for autocomplete");
 
-        assert_eq!(search_result.total, 1);
-        assert_eq!(search_result.results.len(), 1);
+        // Test that DOUBLE column is rejected with informative error
+        let result = db.get_autocomplete_suggestions("decimalLatitude", "3", 10);
+        assert!(result.is_err(), "decimalLatitude (DOUBLE) should be rejected");
 
-        let first = &search_result.results[0];
-        // Should have the requested fields
-        assert!(first.contains_key("occurrenceID"));
-        assert!(first.contains_key("scientificName"));
-        // Should only have the requested fields (2 fields)
-        assert_eq!(first.len(), 2);
+        let err = result.unwrap_err();
+        let err_msg = err.to_string();
+        assert!(
+            err_msg.contains("decimalLatitude") && err_msg.contains("not available for autocomplete"),
+            "Error should mention column name and that it's not available for autocomplete. Got: {err_msg}"
+        );
+        assert!(
+            err_msg.contains("DOUBLE"),
+            "Error should mention the column type. Got: {err_msg}"
+        );
     }
 
     #[test]
-    fn test_create_with_extensions() {
-        // Create occurrence CSV
-        let occurrence_csv = br#"occurrenceID,scientificName
-1,Species A
-2,Species B
-"#;
-
-        // Create multimedia extension CSV
-        let multimedia_csv = br#"occurrenceID,type,identifier
-1,StillImage,http://example.com/img1.jpg
-1,StillImage,http://example.com/img2.jpg
-2,StillImage,http://example.com/img3.jpg
-"#;
-
-        // Set up fixture
-        let temp_dir = std::env::temp_dir()
-            .join("chuck_test_db_extensions");
+    fn test_aggregate_by_field() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_aggregate");
         std::fs::remove_dir_all(&temp_dir).ok();
         std::fs::create_dir_all(&temp_dir).unwrap();
-
-        let occurrence_path = temp_dir.join("occurrence.csv");
-        let multimedia_path = temp_dir.join("multimedia.csv");
         let db_path = temp_dir.join("test.db");
 
-        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
-        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
+        // Insert test data with varied values
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, basisOfRecord VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', 'HumanObservation');
+             INSERT INTO occurrences VALUES ('002', 'Species B', 'HumanObservation');
+             INSERT INTO occurrences VALUES ('003', 'Species C', 'PreservedSpecimen');
+             INSERT INTO occurrences VALUES ('004', 'Species D', NULL);"
+        ).unwrap();
+        drop(conn);
 
-        // Create extension info
-        let extensions = vec![ExtensionInfo {
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+
+        let params = SearchParams::default();
+        let result = db.aggregate_by_field("basisOfRecord", &params, Some(1000), "occurrenceID").unwrap();
+
+        assert_eq!(result.len(), 3);
+        // First result should be HumanObservation with count 2 (highest count)
+        assert_eq!(result[0].value, Some("HumanObservation".to_string()));
+        assert_eq!(result[0].count, 2);
+
+        // The next two results both have count 1, so their order is non-deterministic
+        // Just verify they exist in the results
+        let remaining_values: Vec<_> = result[1..].iter().map(|r| r.value.clone()).collect();
+        assert!(remaining_values.contains(&Some("PreservedSpecimen".to_string())));
+        assert!(remaining_values.contains(&None));
+        assert_eq!(result[1].count, 1);
+        assert_eq!(result[2].count, 1);
+
+        // Cleanup
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    fn multimedia_extension_info() -> ExtensionInfo {
+        ExtensionInfo {
             row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
-            location: multimedia_path.clone(),
+            location: std::path::PathBuf::new(),
             extension: chuck_core::DwcaExtension::SimpleMultimedia,
             core_id_column: "occurrenceID".to_string(),
             fields: vec![],
-            delimiter: ',',
-        }];
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
+        }
+    }
 
-        // Create database with extensions
-        let db = Database::create_from_core_files(
-            &[occurrence_path],
-            &extensions,
+    #[test]
+    fn test_group_examples() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_group_examples");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, basisOfRecord VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', 'HumanObservation');
+             INSERT INTO occurrences VALUES ('002', 'Species B', 'HumanObservation');
+             INSERT INTO occurrences VALUES ('003', 'Species C', 'HumanObservation');
+             INSERT INTO occurrences VALUES ('004', 'Species D', NULL);
+             CREATE TABLE multimedia (occurrenceID VARCHAR, identifier VARCHAR);
+             INSERT INTO multimedia VALUES ('002', 'http://example.com/2.jpg');"
+        ).unwrap();
+        drop(conn);
+
+        let db = Database::open(
             &db_path,
-            "occurrenceID"
+            "occurrenceID".to_string(),
+            &[multimedia_extension_info()],
+        ).unwrap();
+        let params = SearchParams::default();
+
+        let examples = db.group_examples(
+            "basisOfRecord",
+            Some("HumanObservation"),
+            &params,
+            2,
+            "occurrenceID",
+        ).unwrap();
+        assert_eq!(examples.len(), 2);
+        // The example with a photo comes first
+        assert_eq!(examples[0].record["occurrenceID"], "002");
+        assert_eq!(examples[0].photo_url.as_deref(), Some("http://example.com/2.jpg"));
+        assert_eq!(examples[1].record["occurrenceID"], "001");
+        assert_eq!(examples[1].photo_url, None);
+
+        let examples = db.group_examples("basisOfRecord", None, &params, 10, "occurrenceID").unwrap();
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].record["scientificName"], "Species D");
+
+        // Filters still apply within the group
+        let mut filters = HashMap::new();
+        filters.insert("scientificName".to_string(), "Species C".to_string());
+        let filtered = SearchParams { filters, ..SearchParams::default() };
+        let examples = db.group_examples(
+            "basisOfRecord",
+            Some("HumanObservation"),
+            &filtered,
+            10,
+            "occurrenceID",
+        ).unwrap();
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].record["occurrenceID"], "003");
+
+        assert!(db.group_examples("malicious_field", None, &params, 10, "occurrenceID").is_err());
+    }
+
+    #[test]
+    fn test_group_examples_without_media_url_column() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_group_examples_no_media_url");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, basisOfRecord VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'HumanObservation');
+             CREATE TABLE multimedia (occurrenceID VARCHAR, title VARCHAR);
+             INSERT INTO multimedia VALUES ('001', 'A photo with no URL');"
+        ).unwrap();
+        drop(conn);
+
+        let db = Database::open(
+            &db_path,
+            "occurrenceID".to_string(),
+            &[multimedia_extension_info()],
+        ).unwrap();
+
+        let examples = db.group_examples(
+            "basisOfRecord",
+            Some("HumanObservation"),
+            &SearchParams::default(),
+            10,
+            "occurrenceID",
+        ).unwrap();
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].photo_url, None);
+    }
+
+    #[test]
+    fn test_aggregate_by_field_photo_fallbacks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        // Audiovisual with a snake_case URL column and multimedia without any
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, basisOfRecord VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'HumanObservation');
+             INSERT INTO occurrences VALUES ('002', 'PreservedSpecimen');
+             CREATE TABLE audiovisual (occurrenceID VARCHAR, access_uri VARCHAR);
+             INSERT INTO audiovisual VALUES ('001', 'http://example.com/1.jpg');
+             CREATE TABLE multimedia (occurrenceID VARCHAR, title VARCHAR);
+             INSERT INTO multimedia VALUES ('002', 'A photo with no URL');"
+        ).unwrap();
+        drop(conn);
+        let audiovisual = ExtensionInfo {
+            row_type: "http://rs.tdwg.org/ac/terms/Multimedia".to_string(),
+            extension: chuck_core::DwcaExtension::Audiovisual,
+            ..multimedia_extension_info()
+        };
+
+        let db = Database::open(
+            &db_path,
+            "occurrenceID".to_string(),
+            &[multimedia_extension_info(), audiovisual],
+        ).unwrap();
+        let mut result = db.aggregate_by_field(
+            "basisOfRecord",
+            &SearchParams::default(),
+            None,
+            "occurrenceID",
+        ).unwrap();
+        result.sort_by(|a, b| a.value.cmp(&b.value));
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].photo_url, Some("http://example.com/1.jpg".to_string()));
+        assert_eq!(result[1].photo_url, None);
+    }
+
+    #[test]
+    fn test_aggregate_by_field_prefers_local_photos() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, basisOfRecord VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'PreservedSpecimen');
+             INSERT INTO occurrences VALUES ('002', 'PreservedSpecimen');
+             INSERT INTO occurrences VALUES ('003', 'HumanObservation');
+             CREATE TABLE multimedia (occurrenceID VARCHAR, identifier VARCHAR);
+             INSERT INTO multimedia VALUES ('001', 'http://example.com/1.jpg');
+             INSERT INTO multimedia VALUES ('002', 'media/2.JPG');
+             INSERT INTO multimedia VALUES ('003', 'http://example.com/3.jpg');
+             INSERT INTO multimedia VALUES ('003', 'media/3.mp3');"
+        ).unwrap();
+        drop(conn);
+
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[multimedia_extension_info()])
+            .unwrap();
+        let mut result = db.aggregate_by_field(
+            "basisOfRecord",
+            &SearchParams::default(),
+            None,
+            "occurrenceID",
+        ).unwrap();
+        result.sort_by(|a, b| a.value.cmp(&b.value));
+
+        assert_eq!(result[0].photo_url.as_deref(), Some("http://example.com/3.jpg"));
+        assert!(!result[0].photo_is_local);
+        assert_eq!(result[1].photo_url.as_deref(), Some("media/2.JPG"));
+        assert!(result[1].photo_is_local);
+
+        // Filters apply to the local photo's occurrences too
+        let filtered = SearchParams {
+            filters: HashMap::from([("occurrenceID".to_string(), "001".to_string())]),
+            ..SearchParams::default()
+        };
+        let result = db.aggregate_by_field("basisOfRecord", &filtered, None, "occurrenceID").unwrap();
+        assert_eq!(result[0].photo_url.as_deref(), Some("http://example.com/1.jpg"));
+    }
+
+    #[test]
+    fn test_aggregate_by_field_with_metrics() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR, scientificName VARCHAR, eventDate VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'A', 'Quercus agrifolia', '2020-05-01T10:00:00Z');
+             INSERT INTO occurrences VALUES ('002', 'A', 'Quercus agrifolia', '2019-03');
+             INSERT INTO occurrences VALUES ('003', 'A', 'Quercus agrifolia', 'spring');
+             INSERT INTO occurrences VALUES ('004', 'B', 'Quercus lobata', '2021-01-02');
+             INSERT INTO occurrences VALUES ('005', 'B', 'Pinus sabiniana', NULL);
+             CREATE TABLE multimedia (occurrenceID VARCHAR, identifier VARCHAR);
+             INSERT INTO multimedia VALUES ('001', 'media/1.jpg');
+             INSERT INTO multimedia VALUES ('001', 'media/1b.jpg');
+             INSERT INTO multimedia VALUES ('004', 'media/4.jpg');"
+        ).unwrap();
+        drop(conn);
+
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[multimedia_extension_info()])
+            .unwrap();
+        let result = db.aggregate_by_field_with_metrics(
+            "recordedBy",
+            &SearchParams::default(),
+            None,
+            "occurrenceID",
+            &[
+                AggregationMetric::EarliestEventDate,
+                AggregationMetric::LatestEventDate,
+                AggregationMetric::WithPhotosCount,
+            ],
+            Some(AggregationMetric::SpeciesCount),
         ).unwrap();
 
-        // Verify occurrences table was created
-        assert_eq!(db.count_records().unwrap(), 2);
+        // B has fewer records but more species
+        assert_eq!(result[0].value.as_deref(), Some("B"));
+        assert_eq!(result[0].species_count, Some(2));
+        assert_eq!(result[0].with_photos_count, Some(1));
+        assert_eq!(result[1].value.as_deref(), Some("A"));
+        assert_eq!(result[1].count, 3);
+        assert_eq!(result[1].species_count, Some(1));
+        assert_eq!(result[1].earliest_event_date.as_deref(), Some("2019-03"));
+        assert_eq!(result[1].latest_event_date.as_deref(), Some("2020-05-01"));
+        assert_eq!(result[1].with_photos_count, Some(1));
+
+        // Metrics that weren't asked for are left out
+        let result = db.aggregate_by_field("recordedBy", &SearchParams::default(), None, "occurrenceID")
+            .unwrap();
+        assert_eq!(result[0].value.as_deref(), Some("A"));
+        assert_eq!(result[0].species_count, None);
+        assert_eq!(result[0].earliest_event_date, None);
+    }
 
-        // Verify extension table is tracked
-        assert_eq!(db.extension_tables.len(), 1);
-        assert_eq!(db.extension_tables[0].0, chuck_core::DwcaExtension::SimpleMultimedia);
-        assert_eq!(db.extension_tables[0].1, "occurrenceID");
+    #[test]
+    fn test_multi_value_filters_and_aggregation() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_multi_values");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
 
-        // Search and verify extensions are included
-        let search_result = db.search(10, 0, SearchParams::default(), None).unwrap();
-        assert_eq!(search_result.results.len(), 2);
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'W. L. Jepson | H. M. Hall');
+             INSERT INTO occurrences VALUES ('002', 'W. L. Jepson');
+             INSERT INTO occurrences VALUES ('003', 'W. L. Jepsonia; A. Eastwood');"
+        ).unwrap();
+        drop(conn);
 
-        // Check first occurrence has multimedia array
-        let first = &search_result.results[0];
-        assert!(first.contains_key("multimedia"));
+        let check = |db: &Database| {
+            let mut filters = HashMap::new();
+            filters.insert("recordedBy_includes".to_string(), "w. l. jepson".to_string());
+            let params = SearchParams { filters, ..SearchParams::default() };
+            let ids = db.query_matching_ids(params).unwrap();
+            assert_eq!(ids, ["001", "002"].iter().map(|s| s.to_string()).collect());
 
-        let multimedia = first.get("multimedia").unwrap();
-        assert!(multimedia.is_array());
+            let result = db.aggregate_by_field_values(
+                "recordedBy",
+                &SearchParams::default(),
+                None,
+                "occurrenceID",
+            ).unwrap();
+            let counts: Vec<(Option<String>, i64)> = result
+                .into_iter()
+                .map(|r| (r.value, r.count))
+                .collect();
+            assert_eq!(counts, vec![
+                (Some("W. L. Jepson".to_string()), 2),
+                (Some("A. Eastwood".to_string()), 1),
+                (Some("H. M. Hall".to_string()), 1),
+                (Some("W. L. Jepsonia".to_string()), 1),
+            ]);
+        };
 
-        let multimedia_array = multimedia.as_array().unwrap();
-        assert_eq!(multimedia_array.len(), 2); // Two images for occurrence 1
+        // Splitting on the fly
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert!(!db.has_multi_values());
+        check(&db);
+        drop(db);
 
-        // Check multimedia items have expected fields
-        let first_image = &multimedia_array[0];
+        // Using the multi_values table
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        crate::multi_value::split_multi_value_fields(&conn, "occurrenceID").unwrap();
+        drop(conn);
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert!(db.has_multi_values());
+        check(&db);
+
+        assert!(db.aggregate_by_field_values(
+            "scientificName",
+            &SearchParams::default(),
+            None,
+            "occurrenceID",
+        ).is_err());
+    }
 
-        // occurrenceID is stored as VARCHAR in extension tables, so it comes back as string
-        assert_eq!(
-            first_image.get("occurrenceID").and_then(|v| v.as_str()),
-            Some("1")
-        );
-        assert_eq!(
-            first_image.get("type").and_then(|v| v.as_str()),
-            Some("StillImage")
-        );
-        assert_eq!(
-            first_image.get("identifier").and_then(|v| v.as_str()),
-            Some("http://example.com/img1.jpg")
-        );
+    #[test]
+    fn test_higher_taxon_filter() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_higher_taxon");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+        let taxa_path = temp_dir.join("taxa.csv");
+        std::fs::write(
+            &taxa_path,
+            "taxonID,parentNameUsageID,scientificName,taxonRank\n\
+             1,,Lepidoptera,order\n\
+             2,1,Nymphalidae,family\n\
+             3,2,Danaus plexippus,species\n\
+             4,,Araneae,order\n",
+        ).unwrap();
 
-        // Check second occurrence has multimedia array
-        let second = &search_result.results[1];
-        let multimedia_second = second.get("multimedia").unwrap().as_array().unwrap();
-        assert_eq!(multimedia_second.len(), 1); // One image for occurrence 2
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Danaus plexippus');
+             INSERT INTO occurrences VALUES ('002', 'Lepidoptera');
+             INSERT INTO occurrences VALUES ('003', 'Araneae');"
+        ).unwrap();
+        drop(conn);
 
-        // Cleanup
-        std::fs::remove_dir_all(&temp_dir).ok();
+        let ids_for = |db: &Database, taxon: &str| {
+            let mut filters = HashMap::new();
+            filters.insert("higherTaxon".to_string(), taxon.to_string());
+            let params = SearchParams { filters, ..SearchParams::default() };
+            let mut ids: Vec<String> = db.query_matching_ids(params).unwrap().into_iter().collect();
+            ids.sort();
+            ids
+        };
+
+        // Without a taxonomy only the taxon itself matches
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert!(!db.has_taxonomy());
+        assert_eq!(ids_for(&db, "lepidoptera"), vec!["002"]);
+        drop(db);
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        crate::taxonomy::load_taxonomy(&conn, &taxa_path).unwrap();
+        drop(conn);
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert!(db.has_taxonomy());
+        assert_eq!(ids_for(&db, "Lepidoptera"), vec!["001", "002"]);
+        assert_eq!(ids_for(&db, "Nymphalidae"), vec!["001"]);
+        assert_eq!(ids_for(&db, "Araneae"), vec!["003"]);
     }
 
     #[test]
-    fn test_open_database_detects_extensions() {
-        // Create occurrence CSV
-        let occurrence_csv = br#"occurrenceID,scientificName
-1,Species A
-"#;
+    fn test_flagged_filter() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Danaus plexippus');
+             INSERT INTO occurrences VALUES ('002', 'Danaus gilippus');
+             INSERT INTO occurrences VALUES ('003', 'Araneae');"
+        ).unwrap();
+        drop(conn);
 
-        // Create extension CSV
-        let multimedia_csv = br#"occurrenceID,identifier
-1,http://example.com/img1.jpg
-"#;
+        let ids_for = |db: &Database, flag: &str| {
+            let mut filters = HashMap::new();
+            filters.insert("flagged".to_string(), flag.to_string());
+            let params = SearchParams { filters, ..SearchParams::default() };
+            let mut ids: Vec<String> = db.query_matching_ids(params).unwrap().into_iter().collect();
+            ids.sort();
+            ids
+        };
 
-        let temp_dir = std::env::temp_dir()
-            .join("chuck_test_db_open_extensions");
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert!(!db.has_flags());
+        assert!(ids_for(&db, "true").is_empty());
+        assert_eq!(ids_for(&db, "false").len(), 3);
+        drop(db);
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        crate::flags::flag_occurrence(&conn, "occurrenceID", "001", "needs ID review", None).unwrap();
+        crate::flags::flag_occurrence(&conn, "occurrenceID", "002", "suspect coordinates", None).unwrap();
+        drop(conn);
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert!(db.has_flags());
+        assert_eq!(ids_for(&db, "Needs ID review"), vec!["001"]);
+        assert_eq!(ids_for(&db, "true"), vec!["001", "002"]);
+        assert_eq!(ids_for(&db, "false"), vec!["003"]);
+    }
+
+    #[test]
+    fn test_create_from_core_files_joins_event_core() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_event_core");
         std::fs::remove_dir_all(&temp_dir).ok();
         std::fs::create_dir_all(&temp_dir).unwrap();
-
+        let event_path = temp_dir.join("event.csv");
         let occurrence_path = temp_dir.join("occurrence.csv");
-        let multimedia_path = temp_dir.join("multimedia.csv");
         let db_path = temp_dir.join("test.db");
+        std::fs::write(
+            &event_path,
+            "eventID,eventDate,decimalLatitude,decimalLongitude,samplingProtocol\n\
+             e1,2024-05-01,37.5,-122.1,quadrat\n\
+             e2,2024-05-02,38.0,-121.0,transect\n",
+        ).unwrap();
+        std::fs::write(
+            &occurrence_path,
+            "eventID,occurrenceID,scientificName,eventDate\n\
+             e1,o1,Quercus agrifolia,\n\
+             e1,o2,Pinus ponderosa,2024-04-30\n\
+             e2,o3,Quercus agrifolia,\n",
+        ).unwrap();
+        let events = EventCoreInfo {
+            files: vec![event_path],
+            id_column: "eventID".to_string(),
+            occurrence_event_id_column: "eventID".to_string(),
+            format: CsvFormat::default(),
+        };
 
-        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
-        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
-
-        let extensions = vec![ExtensionInfo {
-            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
-            location: multimedia_path,
-            extension: chuck_core::DwcaExtension::SimpleMultimedia,
-            core_id_column: "occurrenceID".to_string(),
-            fields: vec![],
-            delimiter: ',',
-        }];
-
-        // Create database
-        let db = Database::create_from_core_files(
+        let db = Database::create_from_core_files_with_defaults(
             &[occurrence_path],
-            &extensions,
+            &CsvFormat::default(),
+            &[],
+            &[],
+            Some(&events),
+            &[],
             &db_path,
-            "occurrenceID"
+            "occurrenceID",
         ).unwrap();
 
-        // Drop first connection before reopening - on Windows, files are locked while open
-        drop(db);
-
-        // Reopen the database with extension info
-        let reopened_db = Database::open(&db_path, "occurrenceID".to_string(), &extensions).unwrap();
-
-        // Verify it has the extension table info
-        assert_eq!(reopened_db.extension_tables.len(), 1);
-        assert_eq!(reopened_db.extension_tables[0].0, chuck_core::DwcaExtension::SimpleMultimedia);
-        assert_eq!(reopened_db.extension_tables[0].1, "occurrenceID");
-
-        // Drop database before cleanup - on Windows, files are locked while open
-        drop(reopened_db);
+        let rows: Vec<(String, String, f64, String)> = db.conn
+            .prepare(
+                "SELECT occurrenceID, eventDate, decimalLatitude, samplingProtocol \
+                 FROM occurrences ORDER BY occurrenceID"
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(rows, vec![
+            ("o1".to_string(), "2024-05-01".to_string(), 37.5, "quadrat".to_string()),
+            // The occurrence's own eventDate wins over its event's
+            ("o2".to_string(), "2024-04-30".to_string(), 37.5, "quadrat".to_string()),
+            ("o3".to_string(), "2024-05-02".to_string(), 38.0, "transect".to_string()),
+        ]);
 
-        // Cleanup
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_sql_parts_only_allows_known_sort_by() {
-        let params = crate::search_params::SearchParams {
-            filters: HashMap::new(),
-            sort_by: Some("foo".to_string()),
-            sort_direction: None,
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        };
-        let (_, _, _, order_clause) = Database::sql_parts(params, None, "", &vec![]);
-        assert_eq!(order_clause, "");
+    fn test_search_with_execution_info() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_search_execution_info");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
+        {
+            let conn = duckdb::Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+                 INSERT INTO occurrences VALUES ('1', 'Quercus agrifolia');
+                 INSERT INTO occurrences VALUES ('2', 'Pinus ponderosa');"
+            ).unwrap();
+        }
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+
+        let result = db.search(10, 0, SearchParams::default(), None).unwrap();
+        assert!(result.execution.is_none());
+
+        let result = db.search_with_execution_info(10, 0, None, SearchParams::default(), None, true)
+            .unwrap();
+        assert_eq!(result.total, 2);
+        let execution = result.execution.expect("debug search should include execution info");
+        assert!(!execution.used_index);
+        assert!(execution.estimated_rows_scanned.is_some());
+
+        std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_sql_parts_includes_bbox_params_in_where_clause() {
-        // Create params with bbox fields populated
-        let params = crate::search_params::SearchParams {
-            filters: HashMap::new(),
-            sort_by: None,
-            sort_direction: None,
-            nelat: Some("40.0".to_string()),
-            nelng: Some("-120.0".to_string()),
-            swlat: Some("35.0".to_string()),
-            swlng: Some("-125.0".to_string()),
+    fn test_create_from_core_files_skips_malformed_rows() {
+        let temp = tempfile::tempdir().unwrap();
+        let csv_path = temp.path().join("occurrence.csv");
+        let db_path = temp.path().join("test.db");
+        std::fs::write(
+            &csv_path,
+            "occurrenceID,scientificName\n1,Quercus agrifolia\n2,Pinus,ponderosa\n3,Acer\n",
+        ).unwrap();
+        let format = CsvFormat {
+            delimiter: Some(','),
+            header_lines: Some(1),
+            ..Default::default()
         };
 
-        let (
-            _select_fields,
-            where_clause,
-            where_interpolations,
-            _order_clause
-        ) = Database::sql_parts(params, None, "", &vec![]);
+        let db = Database::create_from_core_files_with_defaults(
+            &[csv_path],
+            &format,
+            &[],
+            &[],
+            None,
+            &[],
+            &db_path,
+            "occurrenceID",
+        ).unwrap();
 
-        // Bbox params should generate WHERE clause conditions
-        assert!(where_clause.contains("decimalLatitude"), "Should filter by decimalLatitude");
-        assert!(where_clause.contains("decimalLongitude"), "Should filter by decimalLongitude");
-        assert_eq!(where_interpolations.len(), 4, "Should have 4 interpolations for bbox (nelat, swlat, nelng, swlng)");
+        assert_eq!(db.count_records().unwrap(), 2);
+        let issues = crate::import_issues::list_import_issues(&db.conn).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].table_name, "occurrences");
+        assert_eq!(issues[0].line, Some(3));
+        assert!(db.import_warnings().iter().any(|warning| {
+            warning.kind == ImportWarningKind::SkippedRows && warning.subject == "occurrence.csv"
+        }));
     }
 
     #[test]
-    fn test_sql_parts_bbox_params_work_with_filters() {
-        // Create params with both filters and bbox
-        let mut filters = HashMap::new();
-        filters.insert("scientificName".to_string(), "test".to_string());
+    fn test_create_from_core_files_generates_ids_for_duplicates() {
+        let temp = tempfile::tempdir().unwrap();
+        let csv_path = temp.path().join("occurrence.csv");
+        let db_path = temp.path().join("test.db");
+        std::fs::write(
+            &csv_path,
+            "occurrenceID,scientificName\n1,Quercus agrifolia\n1,Pinus ponderosa\n2,Acer\n",
+        ).unwrap();
 
-        let params = crate::search_params::SearchParams {
-            filters,
-            sort_by: None,
-            sort_direction: None,
-            nelat: Some("40.0".to_string()),
-            nelng: Some("-120.0".to_string()),
-            swlat: Some("35.0".to_string()),
-            swlng: Some("-125.0".to_string()),
-        };
+        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "occurrenceID").unwrap();
 
-        let (
-            _select_fields,
-            where_clause,
-            where_interpolations,
-            _order_clause
-        ) = Database::sql_parts(params, None, "", &vec![]);
+        assert_eq!(db.core_id_column(), GENERATED_ID_COLUMN);
+        let generated = db.generated_core_id().unwrap();
+        assert_eq!(generated.strategy, CoreIdStrategy::RowNumber);
+        assert_eq!(generated.original_column, "occurrenceID");
+        assert!(db.import_warnings().iter().any(|warning| {
+            warning.kind == ImportWarningKind::GeneratedCoreId && warning.subject == "occurrenceID"
+        }));
+        drop(db);
 
-        // Should have both scientificName filter AND bbox conditions
-        assert!(where_clause.contains("scientificName"), "Should have scientificName filter");
-        assert!(where_clause.contains("decimalLatitude"), "Should have decimalLatitude bbox conditions");
-        assert!(where_clause.contains("decimalLongitude"), "Should have decimalLongitude bbox conditions");
-        // Bbox params should not appear by name (they're converted to lat/lng checks)
-        assert!(!where_clause.contains("nelat"), "Should not have nelat column name in WHERE clause");
-        assert!(!where_clause.contains("nelng"), "Should not have nelng column name in WHERE clause");
-        assert!(!where_clause.contains("swlat"), "Should not have swlat column name in WHERE clause");
-        assert!(!where_clause.contains("swlng"), "Should not have swlng column name in WHERE clause");
-        // 1 for scientificName + 4 for bbox
-        assert_eq!(where_interpolations.len(), 5, "Should have 5 interpolations (1 for scientificName + 4 for bbox)");
+        let db = Database::open(&db_path, "occurrenceID".to_string(), &[]).unwrap();
+        assert_eq!(db.core_id_column(), GENERATED_ID_COLUMN);
     }
 
     #[test]
-    fn test_get_occurrence_with_extensions() {
-        // Create occurrence and multimedia test data
-        let occurrence_csv = br#"occurrenceID,scientificName,decimalLatitude,decimalLongitude
-1,Species A,34.05,-118.24
-2,Species B,37.77,-122.41
-"#;
-
-        let multimedia_csv = br#"occurrenceID,type,identifier
-1,StillImage,http://example.com/img1.jpg
-1,StillImage,http://example.com/img2.jpg
-"#;
-
-        let temp_dir = std::env::temp_dir().join("chuck_test_get_occurrence");
+    fn test_create_from_core_files_applies_field_defaults() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_field_defaults");
         std::fs::remove_dir_all(&temp_dir).ok();
         std::fs::create_dir_all(&temp_dir).unwrap();
-
-        let occurrence_path = temp_dir.join("occurrence.csv");
-        let multimedia_path = temp_dir.join("multimedia.csv");
+        let csv_path = temp_dir.join("occurrence.csv");
         let db_path = temp_dir.join("test.db");
+        std::fs::write(
+            &csv_path,
+            "occurrenceID,basisOfRecord,countryCode\n1,PreservedSpecimen,\n2,,\n",
+        ).unwrap();
 
-        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
-        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
+        let defaults = vec![
+            FieldDefault {
+                index: Some(1),
+                term_name: "basisOfRecord".to_string(),
+                value: "HumanObservation".to_string(),
+            },
+            FieldDefault {
+                index: Some(2),
+                term_name: "countryCode".to_string(),
+                value: "US".to_string(),
+            },
+            FieldDefault {
+                index: None,
+                term_name: "institutionCode".to_string(),
+                value: "CAS".to_string(),
+            },
+            FieldDefault {
+                index: None,
+                term_name: "captive".to_string(),
+                value: "false".to_string(),
+            },
+        ];
+        let db = Database::create_from_core_files_with_defaults(
+            &[csv_path],
+            &CsvFormat::default(),
+            &[],
+            &defaults,
+            None,
+            &[],
+            &db_path,
+            "occurrenceID",
+        ).unwrap();
+
+        let rows: Vec<(String, String, String, String, bool)> = db.conn
+            .prepare(
+                "SELECT occurrenceID, basisOfRecord, countryCode, institutionCode, captive \
+                 FROM occurrences ORDER BY occurrenceID"
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(rows, vec![
+            ("1".to_string(), "PreservedSpecimen".to_string(), "US".to_string(), "CAS".to_string(), false),
+            ("2".to_string(), "HumanObservation".to_string(), "US".to_string(), "CAS".to_string(), false),
+        ]);
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
 
-        let extensions = vec![ExtensionInfo {
-            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
-            location: multimedia_path,
-            extension: chuck_core::DwcaExtension::SimpleMultimedia,
-            core_id_column: "occurrenceID".to_string(),
-            fields: vec![],
-            delimiter: ',',
-        }];
+    #[test]
+    fn test_aggregate_by_two_fields() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_aggregate_two_fields");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db_path = temp_dir.join("test.db");
 
-        let db = Database::create_from_core_files(
-            &[occurrence_path],
-            &extensions,
-            &db_path,
-            "occurrenceID"
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, year VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', '2024');
+             INSERT INTO occurrences VALUES ('002', 'Species A', '2024');
+             INSERT INTO occurrences VALUES ('003', 'Species B', '2024');
+             INSERT INTO occurrences VALUES ('004', 'Species C', '2024');
+             INSERT INTO occurrences VALUES ('005', 'Species C', '2024');
+             INSERT INTO occurrences VALUES ('006', 'Species C', '2024');
+             INSERT INTO occurrences VALUES ('007', 'Species B', '2025');"
         ).unwrap();
+        drop(conn);
 
-        // Get occurrence by ID
-        let occurrence = db.get_occurrence("occurrenceID", "1").unwrap();
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let params = SearchParams::default();
 
-        // Verify core fields
-        assert_eq!(
-            occurrence.get("scientificName").and_then(|v| v.as_str()),
-            Some("Species A")
-        );
-        assert_eq!(
-            occurrence.get("decimalLatitude").and_then(|v| v.as_f64()),
-            Some(34.05)
-        );
+        let result = db
+            .aggregate_by_two_fields("year", "scientificName", &params, Some(2), "occurrenceID")
+            .unwrap();
+        let cells: Vec<_> = result
+            .iter()
+            .map(|r| (r.primary.as_deref().unwrap(), r.secondary.as_deref().unwrap(), r.count))
+            .collect();
+        assert_eq!(cells, vec![
+            ("2024", "Species C", 3),
+            ("2024", "Species A", 2),
+            ("2025", "Species B", 1),
+        ]);
 
-        // Verify multimedia extension
-        let multimedia = occurrence.get("multimedia").unwrap();
-        assert!(multimedia.is_array());
-        let multimedia_array = multimedia.as_array().unwrap();
-        assert_eq!(multimedia_array.len(), 2);
+        let result = db
+            .aggregate_by_two_fields("year", "scientificName", &params, None, "occurrenceID")
+            .unwrap();
+        assert_eq!(result.len(), 4);
+
+        let result = db.aggregate_by_two_fields("year", "bogus", &params, None, "occurrenceID");
+        assert!(result.is_err(), "Invalid field name should be rejected");
 
-        // Cleanup
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_get_available_columns() {
-        // Create a temporary directory for test database
-        let temp_dir = std::env::temp_dir().join("chuck_test_available_columns");
+    fn test_column_stats() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_column_stats");
         std::fs::remove_dir_all(&temp_dir).ok();
         std::fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.db");
 
-        // Create test CSV file
-        let csv_path = temp_dir.join("test.csv");
-        std::fs::write(
-            &csv_path,
-            "id,scientificName,decimalLatitude,eventDate\n\
-             1,Species A,10.5,2023-01-01\n\
-             2,Species B,20.3,2023-01-02\n"
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, elevation VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', '10');
+             INSERT INTO occurrences VALUES ('002', 'Species A', '20');
+             INSERT INTO occurrences VALUES ('003', 'Species A', '30');
+             INSERT INTO occurrences VALUES ('004', 'Species A', '40');
+             INSERT INTO occurrences VALUES ('005', 'Species A', 'about 50');
+             INSERT INTO occurrences VALUES ('006', 'Species A', NULL);
+             INSERT INTO occurrences VALUES ('007', 'Species B', '1000');"
         ).unwrap();
+        drop(conn);
 
-        // Create database from CSV
-        let db = Database::create_from_core_files(
-            &[csv_path],
-            &[],
-            &db_path,
-            "id"
-        ).unwrap();
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let mut params = SearchParams::default();
+        params.filters.insert("scientificName".to_string(), "Species A".to_string());
 
-        // Test getting available columns
-        let columns = db.get_available_columns().unwrap();
+        let stats = db.column_stats("elevation", &params, "occurrenceID").unwrap();
 
-        assert_eq!(columns.len(), 4);
-        assert!(columns.contains(&"id".to_string()));
-        assert!(columns.contains(&"scientificName".to_string()));
-        assert!(columns.contains(&"decimalLatitude".to_string()));
-        assert!(columns.contains(&"eventDate".to_string()));
+        assert_eq!(stats.count, 6);
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(stats.non_numeric_count, 1);
+        assert_eq!(stats.min, Some(10.0));
+        assert_eq!(stats.max, Some(40.0));
+        assert_eq!(stats.mean, Some(25.0));
+        assert_eq!(stats.median, Some(25.0));
+        assert_eq!(stats.p25, Some(17.5));
+
+        assert!(db.column_stats("bogus", &params, "occurrenceID").is_err());
+        assert!(db.column_stats("depth", &params, "occurrenceID").is_err(), "missing column should be rejected");
 
-        // Cleanup
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_search_with_order() {
-        let temp_dir = std::env::temp_dir().join("chuck_test_search_order");
+    fn test_aggregate_by_time() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_aggregate_by_time");
         std::fs::remove_dir_all(&temp_dir).ok();
         std::fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.db");
 
-        let csv_path = temp_dir.join("test.csv");
-        std::fs::write(
-            &csv_path,
-            "id,scientificName\n\
-             3,Zebra\n\
-             1,Apple\n\
-             2,Banana\n"
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, eventDate VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', '2024-04-02');
+             INSERT INTO occurrences VALUES ('002', 'Species A', '2025-04-20T10:00:00-07:00');
+             INSERT INTO occurrences VALUES ('003', 'Species B', '2025-06-15');
+             INSERT INTO occurrences VALUES ('004', 'Species A', '2025');
+             INSERT INTO occurrences VALUES ('005', 'Species A', NULL);"
         ).unwrap();
+        drop(conn);
 
-        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id").unwrap();
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let params = SearchParams::default();
 
-        // Test ASC order
-        let params_asc = SearchParams {
-            filters: HashMap::new(),
-            sort_by: Some("scientificName".to_string()),
-            sort_direction: Some("ASC".to_string()),
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        };
-        let result_asc = db.search(10, 0, params_asc, Some(vec!["scientificName".to_string()])).unwrap();
-        let first_name = result_asc.results[0].get("scientificName").unwrap().as_str().unwrap();
-        assert_eq!(first_name, "Apple");
+        let by_month = db.aggregate_by_time(TimeBucket::Month, &params, "occurrenceID").unwrap();
+        let by_month: Vec<_> = by_month.iter().map(|r| (r.bucket, r.count)).collect();
+        assert_eq!(by_month, vec![(4, 2), (6, 1)]);
 
-        // Test DESC order
-        let params_desc = SearchParams {
-            filters: HashMap::new(),
-            sort_by: Some("scientificName".to_string()),
-            sort_direction: Some("DESC".to_string()),
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        };
-        let result_desc = db.search(10, 0, params_desc, Some(vec!["scientificName".to_string()])).unwrap();
-        let first_name_desc = result_desc.results[0].get("scientificName").unwrap().as_str().unwrap();
-        assert_eq!(first_name_desc, "Zebra");
+        let by_year = db.aggregate_by_time(TimeBucket::Year, &params, "occurrenceID").unwrap();
+        let by_year: Vec<_> = by_year.iter().map(|r| (r.bucket, r.count)).collect();
+        assert_eq!(by_year, vec![(2024, 1), (2025, 3)]);
+
+        let by_week = db.aggregate_by_time(TimeBucket::Week, &params, "occurrenceID").unwrap();
+        let by_week: Vec<_> = by_week.iter().map(|r| (r.bucket, r.count)).collect();
+        assert_eq!(by_week, vec![(14, 1), (16, 1), (24, 1)]);
+
+        // Filters apply before bucketing
+        let mut params = SearchParams::default();
+        params.filters.insert("scientificName".to_string(), "Species A".to_string());
+        let by_month = db.aggregate_by_time(TimeBucket::Month, &params, "occurrenceID").unwrap();
+        let by_month: Vec<_> = by_month.iter().map(|r| (r.bucket, r.count)).collect();
+        assert_eq!(by_month, vec![(4, 2)]);
 
-        // Cleanup
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
     #[test]
-    fn test_search_with_numeric_order() {
-        let temp_dir = std::env::temp_dir().join("chuck_test_numeric_order");
+    fn test_aggregate_by_time_without_event_date() {
+        let temp_dir = std::env::temp_dir().join("chuck_test_aggregate_by_time_no_date");
         std::fs::remove_dir_all(&temp_dir).ok();
         std::fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.db");
 
-        let csv_path = temp_dir.join("test.csv");
-        std::fs::write(
-            &csv_path,
-            "occurrenceID,scientificName,decimalLatitude\n\
-             1,Species A,10.5\n\
-             2,Species B,-5.3\n\
-             3,Species C,2.1\n\
-             4,Species D,-15.7\n"
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A');"
         ).unwrap();
+        drop(conn);
 
-        let db = Database::create_from_core_files(&[csv_path], &[], &db_path, "id").unwrap();
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let result = db
+            .aggregate_by_time(TimeBucket::Month, &SearchParams::default(), "occurrenceID")
+            .unwrap();
+        assert!(result.is_empty());
 
-        // Test ASC order - should be numeric ordering
-        let params_asc = SearchParams {
-            filters: HashMap::new(),
-            sort_by: Some("decimalLatitude".to_string()),
-            sort_direction: Some("ASC".to_string()),
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        };
-        let result_asc = db.search(10, 0, params_asc, Some(vec!["occurrenceID".to_string(), "decimalLatitude".to_string()])).unwrap();
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
 
-        // If sorted numerically: -15.7, -5.3, 2.1, 10.5 (ids: 4, 2, 3, 1)
-        // If sorted alphabetically: -15.7, -5.3, 10.5, 2.1 (ids: 4, 2, 1, 3)
-        // Values come back as numbers, so use as_i64()
-        let first_id = result_asc.results[0].get("occurrenceID").unwrap().as_i64().unwrap();
-        let second_id = result_asc.results[1].get("occurrenceID").unwrap().as_i64().unwrap();
-        let third_id = result_asc.results[2].get("occurrenceID").unwrap().as_i64().unwrap();
-        let fourth_id = result_asc.results[3].get("occurrenceID").unwrap().as_i64().unwrap();
+    #[test]
+    fn test_time_series_fills_gaps() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
 
-        assert_eq!(first_id, 4, "Expected -15.7 first (numeric sort)");
-        assert_eq!(second_id, 2, "Expected -5.3 second (numeric sort)");
-        assert_eq!(third_id, 3, "Expected 2.1 third (numeric sort)");
-        assert_eq!(fourth_id, 1, "Expected 10.5 fourth (numeric sort)");
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, eventDate VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', '2024-11-02');
+             INSERT INTO occurrences VALUES ('002', 'Species A', '2024-11-20T10:00:00-07:00');
+             INSERT INTO occurrences VALUES ('003', 'Species B', '2025-02-15');
+             INSERT INTO occurrences VALUES ('004', 'Species A', '2025');
+             INSERT INTO occurrences VALUES ('005', 'Species A', NULL);"
+        ).unwrap();
+        drop(conn);
 
-        // Test DESC order
-        let params_desc = SearchParams {
-            filters: HashMap::new(),
-            sort_by: Some("decimalLatitude".to_string()),
-            sort_direction: Some("DESC".to_string()),
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let points = |interval, params: &SearchParams| -> Vec<(String, i64)> {
+            db.time_series(interval, params, "occurrenceID")
+                .unwrap()
+                .into_iter()
+                .map(|point| (point.date, point.count))
+                .collect()
         };
-        let result_desc = db.search(10, 0, params_desc, Some(vec!["occurrenceID".to_string(), "decimalLatitude".to_string()])).unwrap();
-        let first_id_desc = result_desc.results[0].get("occurrenceID").unwrap().as_i64().unwrap();
-        assert_eq!(first_id_desc, 1, "Expected 10.5 first in DESC order");
+        let params = SearchParams::default();
 
-        // Cleanup
-        std::fs::remove_dir_all(&temp_dir).ok();
+        assert_eq!(
+            points(TimeInterval::Month, &params),
+            vec![
+                ("2024-11-01".to_string(), 2),
+                ("2024-12-01".to_string(), 0),
+                ("2025-01-01".to_string(), 0),
+                ("2025-02-01".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            points(TimeInterval::Year, &params),
+            vec![("2024-01-01".to_string(), 2), ("2025-01-01".to_string(), 2)]
+        );
+        assert_eq!(points(TimeInterval::Day, &params).len(), 106);
+
+        // Filters apply to counts but not the range
+        let mut params = SearchParams::default();
+        params.filters.insert("scientificName".to_string(), "Species B".to_string());
+        let by_month = points(TimeInterval::Month, &params);
+        assert_eq!(by_month.len(), 4);
+        assert_eq!(by_month[0], ("2024-11-01".to_string(), 0));
+        assert_eq!(by_month[3], ("2025-02-01".to_string(), 1));
     }
 
     #[test]
-    fn test_search_with_decimal_latitude_range_filter() {
-        // Create test data with various decimalLatitude values
-        let csv_data = br#"occurrenceID,scientificName,decimalLatitude,decimalLongitude
-1,Species A,3.0,0.0
-2,Species B,3.1,0.0
-3,Species C,3.14,0.0
-4,Species D,3.141,0.0
-5,Species E,30.0,0.0
-6,Species F,301.3,0.0
-7,Species G,2.9,0.0
-"#;
+    fn test_species_accumulation() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
 
-        let fixture = TestFixture::new("search_decimal_latitude_range", vec![csv_data]);
-        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, eventDate VARCHAR, country VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Species A', '2024-04-02', 'US');
+             INSERT INTO occurrences VALUES ('002', 'Species B', '2024-04-02', 'US');
+             INSERT INTO occurrences VALUES ('003', 'Species A', '2024-04-05', 'MX');
+             INSERT INTO occurrences VALUES ('004', 'Species C', '2024-04-09T08:00:00', 'MX');
+             INSERT INTO occurrences VALUES ('005', 'Species D', '2024', 'US');
+             INSERT INTO occurrences VALUES ('006', NULL, '2024-04-10', 'US');"
+        ).unwrap();
+        drop(conn);
 
-        // Test 1: Search for "3" should match 3.0-3.9999 (ids: 1, 2, 3, 4)
-        let mut filters = HashMap::new();
-        filters.insert("decimalLatitude".to_string(), "3".to_string());
-        let search_result = db.search(10, 0, SearchParams {
-            filters: filters.clone(),
-            sort_by: Some("occurrenceID".to_string()),
-            sort_direction: None,
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        }, None).unwrap();
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let points = |params: &SearchParams| -> Vec<(String, i64, i64)> {
+            db.species_accumulation(params, "occurrenceID")
+                .unwrap()
+                .into_iter()
+                .map(|point| (point.date, point.new_species, point.total_species))
+                .collect()
+        };
 
-        assert_eq!(search_result.total, 4, "Search for '3' should match 3.0, 3.1, 3.14, 3.141");
-        let ids: Vec<i64> = search_result.results.iter()
-            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
-            .collect();
-        assert_eq!(ids, vec![1, 2, 3, 4], "Should match records with lat 3.x");
+        assert_eq!(
+            points(&SearchParams::default()),
+            vec![
+                ("2024-04-02".to_string(), 2, 2),
+                ("2024-04-09".to_string(), 1, 3),
+            ]
+        );
 
-        // Test 2: Search for "3.1" should match 3.1-3.19999 (ids: 2, 3, 4)
-        let mut filters = HashMap::new();
-        filters.insert("decimalLatitude".to_string(), "3.1".to_string());
-        let search_result = db.search(10, 0, SearchParams {
-            filters: filters.clone(),
-            sort_by: Some("occurrenceID".to_string()),
-            sort_direction: None,
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        }, None).unwrap();
+        // Species first seen elsewhere count as new under the filter
+        let mut params = SearchParams::default();
+        params.filters.insert("country".to_string(), "MX".to_string());
+        assert_eq!(
+            points(&params),
+            vec![
+                ("2024-04-05".to_string(), 1, 1),
+                ("2024-04-09".to_string(), 1, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collector_summary() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
+
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR, scientificName VARCHAR, \
+                 eventDate VARCHAR, decimalLatitude VARCHAR, decimalLongitude VARCHAR, country VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'W. L. Jepson | H. M. Hall', 'Species A', '1902-05-14', '37.5', '-122.0', 'US');
+             INSERT INTO occurrences VALUES ('002', 'W. L. Jepson', 'Species B', '1902-07', '38.0', '-121.5', 'US');
+             INSERT INTO occurrences VALUES ('003', 'W. L. Jepson', 'Species A', NULL, NULL, NULL, 'MX');
+             INSERT INTO occurrences VALUES ('004', 'A. Eastwood', 'Species C', '1910-01-01', '36.0', '-120.0', 'US');"
+        ).unwrap();
+        drop(conn);
+
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let summary = db.collector_summary("w. l. jepson", &SearchParams::default(), "occurrenceID").unwrap();
+        assert_eq!(summary.record_count, 3);
+        assert_eq!(summary.species_count, Some(2));
+        assert_eq!(summary.min_event_date.as_deref(), Some("1902-05-14"));
+        assert_eq!(summary.max_event_date.as_deref(), Some("1902-07"));
+        assert_eq!(
+            summary.bounds,
+            Some(crate::archive_stats::Bounds { nelat: 38.0, nelng: -121.5, swlat: 37.5, swlng: -122.0 })
+        );
+        let may = summary.monthly.iter().find(|point| point.date == "1902-05-01").unwrap();
+        assert_eq!(may.count, 1);
+        assert_eq!(summary.monthly.iter().map(|point| point.count).sum::<i64>(), 2);
+
+        // Other filters still apply
+        let mut params = SearchParams::default();
+        params.filters.insert("country".to_string(), "MX".to_string());
+        let summary = db.collector_summary("W. L. Jepson", &params, "occurrenceID").unwrap();
+        assert_eq!(summary.record_count, 1);
+        assert_eq!(summary.bounds, None);
+        assert!(summary.monthly.iter().all(|point| point.count == 0));
+    }
 
-        assert_eq!(search_result.total, 3, "Search for '3.1' should match 3.1, 3.14, 3.141");
-        let ids: Vec<i64> = search_result.results.iter()
-            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
-            .collect();
-        assert_eq!(ids, vec![2, 3, 4], "Should match records with lat 3.1x");
+    #[test]
+    fn test_grid_summary() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
 
-        // Test 3: Search for "3.14" should match 3.14-3.149999 (ids: 3, 4)
-        let mut filters = HashMap::new();
-        filters.insert("decimalLatitude".to_string(), "3.14".to_string());
-        let search_result = db.search(10, 0, SearchParams {
-            filters: filters.clone(),
-            sort_by: Some("occurrenceID".to_string()),
-            sort_direction: None,
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        }, None).unwrap();
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, decimalLatitude DOUBLE, decimalLongitude DOUBLE);
+             INSERT INTO occurrences VALUES ('001', 'Species A', 37.71, -122.46);
+             INSERT INTO occurrences VALUES ('002', 'Species A', 37.72, -122.45);
+             INSERT INTO occurrences VALUES ('003', 'Species B', 37.79, -122.41);
+             INSERT INTO occurrences VALUES ('004', 'Species B', 37.91, -122.41);
+             INSERT INTO occurrences VALUES ('005', 'Species C', NULL, NULL);"
+        ).unwrap();
+        drop(conn);
 
-        assert_eq!(search_result.total, 2, "Search for '3.14' should match 3.14, 3.141");
-        let ids: Vec<i64> = search_result.results.iter()
-            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
-            .collect();
-        assert_eq!(ids, vec![3, 4], "Should match records with lat 3.14x");
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let params = SearchParams::default();
 
-        // Test 4: Search for "30" should NOT match "3.x" (should only match 30.x)
-        let mut filters = HashMap::new();
-        filters.insert("decimalLatitude".to_string(), "30".to_string());
-        let search_result = db.search(10, 0, SearchParams {
-            filters: filters.clone(),
-            sort_by: Some("occurrenceID".to_string()),
-            sort_direction: None,
-            nelat: None,
-            nelng: None,
-            swlat: None,
-            swlng: None,
-        }, None).unwrap();
+        let cells = db.grid_summary(0.1, &params, "occurrenceID").unwrap();
+        let counts: Vec<_> = cells.iter().map(|c| (c.record_count, c.species_count)).collect();
+        assert_eq!(counts, vec![(3, 2), (1, 1)]);
 
-        assert_eq!(search_result.total, 1, "Search for '30' should only match 30.0");
-        let ids: Vec<i64> = search_result.results.iter()
-            .map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()).unwrap())
-            .collect();
-        assert_eq!(ids, vec![5], "Should match only record with lat 30.0");
+        let cells = db.grid_summary(0.01, &params, "occurrenceID").unwrap();
+        assert_eq!(cells.len(), 4);
+
+        let geojson = grid_geojson(&db.grid_summary(1.0, &params, "occurrenceID").unwrap());
+        assert_eq!(
+            geojson["features"][0]["geometry"]["coordinates"][0],
+            serde_json::json!([[-123.0, 37.0], [-122.0, 37.0], [-122.0, 38.0], [-123.0, 38.0], [-123.0, 37.0]])
+        );
+        assert_eq!(geojson["features"][0]["properties"]["recordCount"], 4);
+
+        assert!(matches!(
+            db.grid_summary(0.0, &params, "occurrenceID"),
+            Err(ChuckError::GridCellSize(_))
+        ));
     }
 
     #[test]
-    fn test_get_autocomplete_suggestions_rejects_non_varchar_columns() {
-        // Create test data
-        let csv_data = br#"occurrenceID,scientificName,decimalLatitude,decimalLongitude
-1,Species A,3.0,0.0
-"#;
+    fn test_get_column_values() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
 
-        let fixture = TestFixture::new("autocomplete_type_check", vec![csv_data]);
-        let db = Database::create_from_core_files(&fixture.csv_paths, &[], &fixture.db_path, "occurrenceID").unwrap();
+        let conn = duckdb::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR, country VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Ana', 'US');
+             INSERT INTO occurrences VALUES ('002', 'Ana', 'US');
+             INSERT INTO occurrences VALUES ('003', 'Ben', 'US');
+             INSERT INTO occurrences VALUES ('004', 'Carla', 'MX');
+             INSERT INTO occurrences VALUES ('005', 'Carla', 'MX');
+             INSERT INTO occurrences VALUES ('006', 'Carla', 'MX');
+             INSERT INTO occurrences VALUES ('007', '', 'US');
+             INSERT INTO occurrences VALUES ('008', NULL, 'US');"
+        ).unwrap();
+        drop(conn);
 
-        // Test that VARCHAR column works
-        let result = db.get_autocomplete_suggestions("scientificName", "Spec", 10);
-        assert!(result.is_ok(), "scientificName (VARCHAR) should work for autocomplete");
+        let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let counts = |result: ColumnValues| -> (Vec<(String, i64)>, i64) {
+            let values = result.values.into_iter().map(|v| (v.value, v.count)).collect();
+            (values, result.distinct_count)
+        };
+        let params = SearchParams::default();
 
-        // Test that DOUBLE column is rejected with informative error
-        let result = db.get_autocomplete_suggestions("decimalLatitude", "3", 10);
-        assert!(result.is_err(), "decimalLatitude (DOUBLE) should be rejected");
+        let result = db.get_column_values("recordedBy", &params, None, 2, "occurrenceID").unwrap();
+        assert_eq!(
+            counts(result),
+            (vec![("Carla".to_string(), 3), ("Ana".to_string(), 2)], 3)
+        );
 
-        let err = result.unwrap_err();
-        let err_msg = err.to_string();
-        assert!(
-            err_msg.contains("decimalLatitude") && err_msg.contains("not available for autocomplete"),
-            "Error should mention column name and that it's not available for autocomplete. Got: {err_msg}"
+        // Matches anywhere in the value, not just the start
+        let result = db.get_column_values("recordedBy", &params, Some("n"), 10, "occurrenceID").unwrap();
+        assert_eq!(
+            counts(result),
+            (vec![("Ana".to_string(), 2), ("Ben".to_string(), 1)], 2)
         );
-        assert!(
-            err_msg.contains("DOUBLE"),
-            "Error should mention the column type. Got: {err_msg}"
+
+        let mut params = SearchParams::default();
+        params.filters.insert("country".to_string(), "US".to_string());
+        let result = db.get_column_values("recordedBy", &params, None, 10, "occurrenceID").unwrap();
+        assert_eq!(
+            counts(result),
+            (vec![("Ana".to_string(), 2), ("Ben".to_string(), 1)], 2)
         );
+
+        assert!(db.get_column_values("decimalLatitude", &params, None, 10, "occurrenceID").is_err());
     }
 
     #[test]
-    fn test_aggregate_by_field() {
-        let temp_dir = std::env::temp_dir().join("chuck_test_aggregate");
-        std::fs::remove_dir_all(&temp_dir).ok();
-        std::fs::create_dir_all(&temp_dir).unwrap();
-        let db_path = temp_dir.join("test.db");
+    fn test_get_facets() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join("test.db");
 
-        // Insert test data with varied values
         let conn = duckdb::Connection::open(&db_path).unwrap();
         conn.execute_batch(
-            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR, basisOfRecord VARCHAR);
-             INSERT INTO occurrences VALUES ('001', 'Species A', 'HumanObservation');
-             INSERT INTO occurrences VALUES ('002', 'Species B', 'HumanObservation');
-             INSERT INTO occurrences VALUES ('003', 'Species C', 'PreservedSpecimen');
-             INSERT INTO occurrences VALUES ('004', 'Species D', NULL);"
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR, country VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'Ana', 'US');
+             INSERT INTO occurrences VALUES ('002', 'Ana', 'US');
+             INSERT INTO occurrences VALUES ('003', 'Ben', 'US');
+             INSERT INTO occurrences VALUES ('004', 'Carla', 'MX');"
         ).unwrap();
         drop(conn);
 
         let db = Database::open(&db_path, "".to_string(), &[]).unwrap();
+        let columns = vec!["country".to_string(), "recordedBy".to_string(), "stateProvince".to_string()];
 
-        let params = SearchParams::default();
-        let result = db.aggregate_by_field("basisOfRecord", &params, Some(1000), "occurrenceID").unwrap();
-
-        assert_eq!(result.len(), 3);
-        // First result should be HumanObservation with count 2 (highest count)
-        assert_eq!(result[0].value, Some("HumanObservation".to_string()));
-        assert_eq!(result[0].count, 2);
+        let facets = db.get_facets(&columns, &SearchParams::default(), 2, "occurrenceID").unwrap();
 
-        // The next two results both have count 1, so their order is non-deterministic
-        // Just verify they exist in the results
-        let remaining_values: Vec<_> = result[1..].iter().map(|r| r.value.clone()).collect();
-        assert!(remaining_values.contains(&Some("PreservedSpecimen".to_string())));
-        assert!(remaining_values.contains(&None));
-        assert_eq!(result[1].count, 1);
-        assert_eq!(result[2].count, 1);
+        let summary: Vec<(&str, Vec<(&str, i64)>, i64)> = facets
+            .iter()
+            .map(|facet| (
+                facet.column.as_str(),
+                facet.values.iter().map(|v| (v.value.as_str(), v.count)).collect(),
+                facet.distinct_count,
+            ))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("country", vec![("US", 3), ("MX", 1)], 2),
+                ("recordedBy", vec![("Ana", 2), ("Ben", 1)], 3),
+                ("stateProvince", vec![], 0),
+            ]
+        );
 
-        // Cleanup
-        std::fs::remove_dir_all(&temp_dir).ok();
+        let mut params = SearchParams::default();
+        params.filters.insert("country".to_string(), "MX".to_string());
+        let facets = db.get_facets(&columns, &params, 2, "occurrenceID").unwrap();
+        assert_eq!(facets[1].values, vec![ValueCount { value: "Carla".to_string(), count: 1 }]);
     }
 
     #[test]
@@ -2032,6 +6013,61 @@ mod tests {
         std::fs::remove_dir_all(&temp_dir).ok();
     }
 
+    #[test]
+    fn test_search_filters_and_sorts_by_custom_columns() {
+        let csv_data = b"occurrenceID,scientificName,herbariumDrawer
+1,Quercus agrifolia,B12
+2,Pinus radiata,A07
+";
+        let fixture = TestFixture::new("custom_columns", vec![csv_data]);
+        let db = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "occurrenceID"
+        ).unwrap();
+
+        let result = db.search(10, 0, SearchParams {
+            filters: HashMap::from([("herbariumDrawer".to_string(), "A07".to_string())]),
+            ..Default::default()
+        }, None).unwrap();
+        assert_eq!(result.total, 1);
+
+        let sorted = db.search(10, 0, SearchParams {
+            sort_by: Some("herbariumDrawer".to_string()),
+            ..Default::default()
+        }, None).unwrap();
+        assert_eq!(sorted.results[0].get("herbariumDrawer").and_then(|v| v.as_str()), Some("A07"));
+
+        // Any column name can be used, so quotes in them get escaped
+        assert_eq!(Database::quote_identifier("odd \"name\""), "\"odd \"\"name\"\"\"");
+    }
+
+    #[test]
+    fn test_search_filters_match_aliased_columns() {
+        let csv_data = b"occurrenceID,scientificName,country,CatalogNumber
+1,Quercus agrifolia,US,A-1
+2,Pinus radiata,NZ,B-2
+";
+        let fixture = TestFixture::new("aliased_columns", vec![csv_data]);
+        let db = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "occurrenceID"
+        ).unwrap();
+        let params = |key: &str, value: &str| SearchParams {
+            filters: HashMap::from([(key.to_string(), value.to_string())]),
+            ..Default::default()
+        };
+
+        let result = db.search(10, 0, params("countryCode", "NZ"), None).unwrap();
+        assert_eq!(result.total, 1);
+
+        let aliased = db.with_field_aliases(params("countryCode_includes", "NZ"));
+        assert!(aliased.filters.contains_key("country_includes"));
+    }
+
     #[test]
     fn test_search_filter_by_reserved_keyword_order() {
         // Test that filtering, sorting, and aggregating by SQL reserved keyword
@@ -2117,6 +6153,56 @@ mod tests {
         assert_eq!(search_result.total, 2, "Should find 2 Pinopsida records");
     }
 
+    #[test]
+    fn test_create_from_core_files_relaxes_uncastable_columns() {
+        let temp = tempfile::tempdir().unwrap();
+        let csv_path = temp.path().join("occurrence.csv");
+        let db_path = temp.path().join("test.db");
+        std::fs::write(
+            &csv_path,
+            "occurrenceID,decimalLatitude,decimalLongitude\n1,37.5,-122.25\n2,37.5N,-122.0\n3,,-121.0\n",
+        ).unwrap();
+
+        let format = CsvFormat {
+            delimiter: Some(','),
+            header_lines: Some(1),
+            ..Default::default()
+        };
+
+        let db = Database::create_from_core_files_with_defaults(
+            &[csv_path],
+            &format,
+            &[],
+            &[],
+            None,
+            &[],
+            &db_path,
+            "occurrenceID",
+        ).unwrap();
+
+        // No rows are skipped for the bad latitude
+        assert_eq!(db.count_records().unwrap(), 3);
+        assert!(crate::import_issues::list_import_issues(&db.conn).unwrap().is_empty());
+        let rows: Vec<(Option<f64>, Option<String>, f64)> = db.conn
+            .prepare("SELECT decimalLatitude, verbatimDecimalLatitude, decimalLongitude FROM occurrences ORDER BY occurrenceID")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows, vec![
+            (Some(37.5), Some("37.5".to_string()), -122.25),
+            (None, Some("37.5N".to_string()), -122.0),
+            (None, None, -121.0),
+        ]);
+        let warning = db.import_warnings()
+            .iter()
+            .find(|warning| warning.kind == ImportWarningKind::RelaxedColumnType)
+            .unwrap();
+        assert_eq!(warning.subject, "decimalLatitude");
+        assert!(warning.message.starts_with("1 values of decimalLatitude"));
+    }
+
     #[test]
     fn test_create_from_core_files_checkpoints_wal() {
         // After create_from_core_files returns, the WAL should already be
@@ -2148,6 +6234,60 @@ mod tests {
         drop(db);
     }
 
+    #[test]
+    fn test_create_extension_tables_joins_on_coreid_index() {
+        let occurrence_csv = b"gbifID,scientificName\n1,Species A\n2,Species B\n";
+        // meta.xml declares <coreid index="2"/> with no <field> there, and the
+        // header is neither "coreid" nor gbifID
+        let multimedia_csv =
+            b"type,identifier,id\n\
+              StillImage,http://example.com/img1.jpg,1\n\
+              StillImage,http://example.com/img2.jpg,2\n\
+              StillImage,http://example.com/img3.jpg,2\n";
+
+        let temp_dir = std::env::temp_dir().join("chuck_test_db_coreid_index");
+        std::fs::remove_dir_all(&temp_dir).ok();
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let occurrence_path = temp_dir.join("occurrence.csv");
+        let multimedia_path = temp_dir.join("multimedia.csv");
+        let db_path = temp_dir.join("test.db");
+        std::fs::write(&occurrence_path, occurrence_csv).unwrap();
+        std::fs::write(&multimedia_path, multimedia_csv).unwrap();
+
+        let extensions = vec![ExtensionInfo {
+            row_type: "http://rs.gbif.org/terms/1.0/Multimedia".to_string(),
+            location: multimedia_path,
+            extension: chuck_core::DwcaExtension::SimpleMultimedia,
+            core_id_column: "coreid".to_string(),
+            fields: vec![
+                (0, "type".to_string()),
+                (1, "identifier".to_string()),
+            ],
+            format: CsvFormat::default(),
+            core_id_index: Some(2),
+            defaults: vec![],
+        }];
+
+        let db = Database::create_from_core_files(
+            &[occurrence_path],
+            &extensions,
+            &db_path,
+            "gbifID",
+        ).unwrap();
+
+        let search_result = db.search(10, 0, SearchParams {
+            sort_by: Some("gbifID".to_string()),
+            ..Default::default()
+        }, None).unwrap();
+        let multimedia_counts: Vec<usize> = search_result.results
+            .iter()
+            .map(|r| r["multimedia"].as_array().unwrap().len())
+            .collect();
+        assert_eq!(multimedia_counts, vec![1, 2]);
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
     #[test]
     fn test_rename_extension_columns_skips_duplicate_name() {
         // Old Chuck-generated archives have a "coreid" CSV column that
@@ -2184,7 +6324,9 @@ mod tests {
                 (1, "type".to_string()),
                 (2, "identifier".to_string()),
             ],
-            delimiter: ',',
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
         }];
 
         let result = Database::create_from_core_files(
@@ -2228,7 +6370,7 @@ mod tests {
         };
 
         let (_, where_clause, where_interpolations, _) =
-            Database::sql_parts(params, None, "", &vec![]);
+            Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
 
         assert!(
             where_clause.contains("coordinateUncertaintyInMeters"),
@@ -2268,7 +6410,7 @@ mod tests {
         };
 
         let (_, where_clause, where_interpolations, _) =
-            Database::sql_parts(params, None, "", &vec![]);
+            Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
 
         assert!(where_clause.contains(">="), "Should have >= for min");
         assert!(
@@ -2301,7 +6443,7 @@ mod tests {
         };
 
         let (_, where_clause, _, _) =
-            Database::sql_parts(params, None, "", &vec![]);
+            Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
 
         assert!(
             where_clause.contains("IS NULL"),
@@ -2329,7 +6471,7 @@ mod tests {
         };
 
         let (_, where_clause, where_interpolations, _) =
-            Database::sql_parts(params, None, "", &vec![]);
+            Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
 
         assert_eq!(where_clause, "", "Should produce no WHERE clause");
         assert_eq!(where_interpolations.len(), 0);
@@ -2354,7 +6496,7 @@ mod tests {
         };
 
         let (_, where_clause, _, _) =
-            Database::sql_parts(params, None, "", &vec![]);
+            Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
 
         assert!(
             !where_clause.contains("ILIKE"),
@@ -2541,4 +6683,95 @@ mod tests {
             "Result should have captive=false"
         );
     }
+
+    #[test]
+    fn test_search_numeric_terms_filter_and_sort_as_numbers() {
+        let csv_data = b"occurrenceID,scientificName,year,individualCount\n\
+            a,Species A,2020,9\n\
+            b,Species B,1985?,10\n\
+            c,Species C,2020,100\n\
+            d,Species D,202,\n";
+
+        let fixture = TestFixture::new("numeric_terms", vec![csv_data]);
+        let db = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "occurrenceID",
+        ).unwrap();
+
+        // Exact match rather than the substring match text columns get, and
+        // the dirty value doesn't stop the import
+        let mut filters = HashMap::new();
+        filters.insert("year".to_string(), "202".to_string());
+        let result = db.search(10, 0, SearchParams {
+            filters,
+            ..Default::default()
+        }, None).unwrap();
+        assert_eq!(result.total, 1);
+
+        let result = db.search(10, 0, SearchParams {
+            sort_by: Some("individualCount".to_string()),
+            sort_direction: Some("DESC".to_string()),
+            ..Default::default()
+        }, Some(vec!["occurrenceID".to_string()])).unwrap();
+        let ids: Vec<&str> = result.results
+            .iter()
+            .map(|row| row.get("occurrenceID").unwrap().as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["c", "b", "a", "d"]);
+    }
+
+    #[test]
+    fn test_search_filter_by_event_date_uses_time_zone_offset() {
+        // Occurrence 1 was observed at 23:30 local time on the 15th, but its
+        // eventDate is recorded in UTC, which is already the 16th
+        let csv_data = b"occurrenceID,eventDate,eventTimeZoneOffset\n\
+            1,2024-01-16T07:30:00Z,-08:00\n\
+            2,2024-01-16,-08:00\n\
+            3,2024-01-15T23:30:00-08:00,-08:00\n";
+
+        let fixture = TestFixture::new("event_date_time_zone_offset", vec![csv_data]);
+        let db = Database::create_from_core_files(
+            &fixture.csv_paths,
+            &[],
+            &fixture.db_path,
+            "occurrenceID",
+        ).unwrap();
+        assert!(db.has_time_zone_offsets());
+
+        let mut filters = HashMap::new();
+        filters.insert("eventDate".to_string(), "2024-01-15".to_string());
+        let result = db.search(10, 0, SearchParams {
+            filters,
+            ..Default::default()
+        }, None).unwrap();
+        let mut ids: Vec<i64> = result.results.iter()
+            .filter_map(|r| r.get("occurrenceID").and_then(|v| v.as_i64()))
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 3], "UTC datetime should match its local date");
+
+        let mut filters = HashMap::new();
+        filters.insert("eventDate".to_string(), "2024-01-16".to_string());
+        let result = db.search(10, 0, SearchParams {
+            filters,
+            ..Default::default()
+        }, None).unwrap();
+        assert_eq!(result.total, 1, "Only the plain date should match the 16th");
+    }
+
+    #[test]
+    fn test_sql_parts_event_date_filter_without_time_zone_offsets() {
+        let mut filters = HashMap::new();
+        filters.insert("eventDate".to_string(), "2024-01-15".to_string());
+        let params = SearchParams { filters, ..Default::default() };
+
+        let (_, where_clause, _, _) = Database::sql_parts(params, None, "", &[], false, false, false, false, &known_columns(), &HashMap::new());
+
+        assert!(
+            !where_clause.contains("eventTimeZoneOffset"),
+            "Should not reference a column the archive doesn't have: {where_clause}"
+        );
+    }
 }
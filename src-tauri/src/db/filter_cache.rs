@@ -0,0 +1,111 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::error::{ChuckError, Result};
+use crate::search_params::SearchParams;
+
+/// Core IDs matching a filter, written to Parquet so searches, aggregations,
+/// tiles, and exports with the same filter can look them up instead of
+/// running its WHERE clause again. Only the most recently materialized
+/// filter is kept. Keys include when the database was last written, so
+/// changes to the data invalidate them.
+#[derive(Debug, Clone)]
+pub(crate) struct FilterCache {
+    dir: PathBuf,
+    db_version: u128,
+}
+
+impl FilterCache {
+    pub fn new(dir: PathBuf, db_path: &Path) -> Self {
+        let db_version = std::fs::metadata(db_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|age| age.as_nanos())
+            .unwrap_or_default();
+        Self { dir, db_version }
+    }
+
+    /// Key for the parts of `search_params` that choose rows: filters and
+    /// the bounding box, but not sorting
+    fn key(&self, search_params: &SearchParams) -> String {
+        let mut filters: Vec<_> = search_params.filters.iter().collect();
+        filters.sort();
+        let mut hasher = DefaultHasher::new();
+        filters.hash(&mut hasher);
+        (&search_params.nelat, &search_params.nelng, &search_params.swlat, &search_params.swlng)
+            .hash(&mut hasher);
+        self.db_version.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.parquet"))
+    }
+
+    /// Parquet file of the core IDs matching `search_params`, if they've
+    /// been materialized
+    pub fn get(&self, search_params: &SearchParams) -> Option<PathBuf> {
+        if !is_filtered(search_params) {
+            return None;
+        }
+        Some(self.path(&self.key(search_params))).filter(|path| path.exists())
+    }
+
+    /// Where to write the core IDs matching `search_params`, after removing
+    /// any other filter's. Write to the returned temporary path and `commit`
+    /// it so a half-written file is never read.
+    pub fn prepare(&self, search_params: &SearchParams) -> Result<(PathBuf, PathBuf)> {
+        std::fs::create_dir_all(&self.dir).map_err(|source| ChuckError::DirectoryCreate {
+            path: self.dir.clone(),
+            source,
+        })?;
+        if let Ok(entries) = std::fs::read_dir(&self.dir) {
+            for entry in entries.flatten() {
+                std::fs::remove_file(entry.path()).ok();
+            }
+        }
+        let path = self.path(&self.key(search_params));
+        Ok((path.with_extension("parquet.tmp"), path))
+    }
+
+    pub fn commit(temp_path: &Path, path: &Path) -> Result<()> {
+        std::fs::rename(temp_path, path).map_err(|source| ChuckError::FileWrite {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Whether `search_params` exclude any rows. Unfiltered searches aren't
+/// worth materializing.
+pub(crate) fn is_filtered(search_params: &SearchParams) -> bool {
+    !search_params.filters.is_empty()
+        || [&search_params.nelat, &search_params.nelng, &search_params.swlat, &search_params.swlng]
+            .iter()
+            .any(|bound| bound.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_filter_cache_key_ignores_sorting() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = FilterCache::new(temp_dir.path().join("filter_cache"), temp_dir.path());
+        let filtered = SearchParams {
+            filters: HashMap::from([("genus".to_string(), "Quercus".to_string())]),
+            ..SearchParams::default()
+        };
+        let sorted = SearchParams {
+            sort_by: Some("eventDate".to_string()),
+            ..filtered.clone()
+        };
+
+        assert_eq!(cache.key(&filtered), cache.key(&sorted));
+        assert_ne!(cache.key(&filtered), cache.key(&SearchParams::default()));
+    }
+}
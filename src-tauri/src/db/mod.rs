@@ -1,3 +1,8 @@
+mod csv_chunks;
+mod cursor;
+mod filter_cache;
 mod database;
+mod sqlite_export;
 
-pub use database::{Database, AggregationResult};
+pub(crate) use filter_cache::is_filtered;
+pub use database::{Database, AggregationMetric, AggregationResult, ColumnStats, CrosstabResult, GroupExample, ImportProgress, TimeAggregationResult, TimeBucket, TimeInterval, TimeSeriesPoint, AccumulationPoint, CollectorSummary, GridCell, grid_geojson, ColumnValues, ValueCount, Facet};
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use crate::error::{ChuckError, Result};
+
+/// SQLite column type for a DuckDB type, so numbers and booleans stay
+/// numeric instead of becoming text
+fn sqlite_type(duckdb_type: &str) -> &'static str {
+    match duckdb_type {
+        "BOOLEAN" | "TINYINT" | "SMALLINT" | "INTEGER" | "BIGINT" | "UTINYINT"
+        | "USMALLINT" | "UINTEGER" => "INTEGER",
+        "FLOAT" | "DOUBLE" | "HUGEINT" | "UBIGINT" => "REAL",
+        t if t.starts_with("DECIMAL") => "REAL",
+        _ => "TEXT",
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Copies DuckDB tables into a new SQLite database at `path`, replacing any
+/// file already there. Each table is given as (table name, indexed column),
+/// and the indexed column gets an index so occurrences and extension rows
+/// can be joined quickly. Occurrences are read from `occurrences_source` so
+/// hand edits are copied too. Returns the number of rows copied.
+pub(super) fn export_sqlite(
+    conn: &duckdb::Connection,
+    tables: &[(String, String)],
+    occurrences_source: &str,
+    path: &Path,
+) -> Result<usize> {
+    if path.exists() {
+        std::fs::remove_file(path).map_err(|source| ChuckError::FileWrite {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    }
+    let mut sqlite = rusqlite::Connection::open(path)?;
+    let mut total = 0;
+    for (table, index_column) in tables {
+        let columns: Vec<(String, String)> = conn
+            .prepare(
+                "SELECT column_name, data_type FROM information_schema.columns
+                 WHERE table_name = ? ORDER BY ordinal_position",
+            )?
+            .query_map([table], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let types: Vec<&str> = columns.iter().map(|(_, t)| sqlite_type(t)).collect();
+
+        let column_defs = columns
+            .iter()
+            .zip(&types)
+            .map(|((name, _), typ)| format!("{} {typ}", quote(name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let tx = sqlite.transaction()?;
+        tx.execute(&format!("CREATE TABLE {} ({column_defs})", quote(table)), [])?;
+
+        // Cast in DuckDB so every value comes back as one of the few types
+        // SQLite stores
+        let select_columns = columns
+            .iter()
+            .zip(&types)
+            .map(|((name, _), typ)| match *typ {
+                "INTEGER" => format!("CAST({} AS BIGINT)", quote(name)),
+                "REAL" => format!("CAST({} AS DOUBLE)", quote(name)),
+                _ => format!("CAST({} AS VARCHAR)", quote(name)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        {
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO {} VALUES ({placeholders})",
+                quote(table)
+            ))?;
+            let source = if table == "occurrences" {
+                occurrences_source.to_string()
+            } else {
+                quote(table)
+            };
+            let mut select = conn.prepare(&format!("SELECT {select_columns} FROM {source}"))?;
+            let mut rows = select.query([])?;
+            while let Some(row) = rows.next()? {
+                let mut values = Vec::with_capacity(types.len());
+                for (i, typ) in types.iter().enumerate() {
+                    let value = match *typ {
+                        "INTEGER" => row
+                            .get::<_, Option<i64>>(i)?
+                            .map_or(rusqlite::types::Value::Null, rusqlite::types::Value::Integer),
+                        "REAL" => row
+                            .get::<_, Option<f64>>(i)?
+                            .map_or(rusqlite::types::Value::Null, rusqlite::types::Value::Real),
+                        _ => row
+                            .get::<_, Option<String>>(i)?
+                            .map_or(rusqlite::types::Value::Null, rusqlite::types::Value::Text),
+                    };
+                    values.push(value);
+                }
+                insert.execute(rusqlite::params_from_iter(values))?;
+                total += 1;
+            }
+        }
+        if columns.iter().any(|(name, _)| name == index_column) {
+            tx.execute(
+                &format!(
+                    "CREATE INDEX {} ON {} ({})",
+                    quote(&format!("idx_{table}_{index_column}")),
+                    quote(table),
+                    quote(index_column)
+                ),
+                [],
+            )?;
+        }
+        tx.commit()?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_sqlite_copies_tables_with_types_and_indexes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("archive.sqlite");
+        std::fs::write(&path, "not a database").unwrap();
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, decimalLatitude DOUBLE, captive BOOLEAN);
+             INSERT INTO occurrences VALUES ('1', 37.5, true), ('2', NULL, false);
+             CREATE TABLE multimedia (occurrenceID VARCHAR, identifier VARCHAR);
+             INSERT INTO multimedia VALUES ('1', 'http://example.com/1.jpg');"
+        ).unwrap();
+        let tables = vec![
+            ("occurrences".to_string(), "occurrenceID".to_string()),
+            ("multimedia".to_string(), "occurrenceID".to_string()),
+        ];
+
+        assert_eq!(export_sqlite(&conn, &tables, "occurrences", &path).unwrap(), 3);
+
+        let sqlite = rusqlite::Connection::open(&path).unwrap();
+        let (latitude, captive): (Option<f64>, i64) = sqlite
+            .query_row(
+                "SELECT decimalLatitude, captive FROM occurrences WHERE occurrenceID = '1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(latitude, Some(37.5));
+        assert_eq!(captive, 1);
+        let identifier: String = sqlite
+            .query_row("SELECT identifier FROM multimedia", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(identifier, "http://example.com/1.jpg");
+        let indexes: i64 = sqlite
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 2);
+    }
+}
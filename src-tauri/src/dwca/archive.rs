@@ -1,21 +1,110 @@
 use rayon::prelude::*;
 use roxmltree::Node;
 use std::collections::HashSet;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use chuck_core::darwin_core::Eml;
+
 use crate::search_params::SearchParams;
-use crate::db::Database;
+use crate::db::{Database, ImportProgress};
+use crate::tile_server::coords::obscuration_cell;
+use crate::tile_server::{ObscuredMode, TilePoint};
+use crate::dwca::{
+    load_generated_core_id, load_import_warnings, load_source, save_generated_core_id,
+    save_import_warnings, save_source, ArchiveSource, CoreIdStrategy, GeneratedCoreId, ImportWarning,
+    ImportWarningKind,
+};
 use crate::error::{ChuckError, Result};
 
-/// Parsed contents of a DarwinCore Archive meta.xml file
+/// Marker file in the storage directory present when the archive has been
+/// unlocked for changes
+const UNLOCKED_FILENAME: &str = ".unlocked";
+
+const EML_FILENAME: &str = "eml.xml";
+
+/// Number of imported archives kept in the base directory. Opening another
+/// removes the least recently opened ones beyond this.
+pub const MAX_OPEN_ARCHIVES: usize = 5;
+
+/// Parsed contents of a DarwinCore Archive meta.xml file. For an Event core
+/// with an Occurrence extension, the occurrences are treated as the core and
+/// the events are joined onto them, so `core_files` and `core_id_column`
+/// describe the occurrence extension and `events` the original core.
 pub(crate) struct MetaXmlInfo {
+    pub core_type: CoreType,
     pub core_files: Vec<PathBuf>,
     pub core_id_column: String,
-    pub core_delimiter: char,
+    /// How the core data files are delimited and encoded
+    pub core_format: CsvFormat,
+    /// Core `<field>` declarations: (column index, term name)
+    pub core_fields: Vec<(usize, String)>,
+    /// Core `<field>` declarations with a default value
+    pub core_defaults: Vec<FieldDefault>,
     pub extensions: Vec<ExtensionInfo>,
+    /// Event core to join onto occurrences from an Occurrence extension
+    pub events: Option<EventCoreInfo>,
+    /// Non-fatal problems with meta.xml, e.g. unsupported extensions
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl MetaXmlInfo {
+    /// The class of the records that get imported, which is Occurrence for
+    /// an Event core with an Occurrence extension
+    pub fn record_type(&self) -> CoreType {
+        if self.events.is_some() {
+            CoreType::Occurrence
+        } else {
+            self.core_type
+        }
+    }
+}
+
+/// The class of record in an archive's core, from the core rowType
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoreType {
+    #[default]
+    Occurrence,
+    /// Sampling events, e.g. from a survey dataset
+    Event,
+    /// Taxa, e.g. from a checklist
+    Taxon,
+}
+
+impl CoreType {
+    pub fn from_row_type(row_type: &str) -> Option<Self> {
+        match term_name(row_type)? {
+            "Occurrence" => Some(Self::Occurrence),
+            "Event" => Some(Self::Event),
+            "Taxon" => Some(Self::Taxon),
+            _ => None,
+        }
+    }
+
+    /// ID column to assume when meta.xml doesn't identify one
+    fn default_id_column(&self) -> &'static str {
+        match self {
+            Self::Occurrence => "occurrenceID",
+            Self::Event => "eventID",
+            Self::Taxon => "taxonID",
+        }
+    }
+}
+
+/// Event core data files for an archive whose occurrences are in an
+/// Occurrence extension
+#[derive(Debug, Clone)]
+pub struct EventCoreInfo {
+    pub files: Vec<PathBuf>,
+    /// ID column of the event core, e.g. eventID
+    pub id_column: String,
+    /// Column of the Occurrence extension that references events
+    pub occurrence_event_id_column: String,
+    /// How the event core data files are delimited and encoded
+    pub format: CsvFormat,
 }
 
 /// Information about an extension in a DarwinCore Archive
@@ -34,8 +123,60 @@ pub struct ExtensionInfo {
     /// Field declarations from meta.xml: (column index, term name)
     /// Used to rename CSV columns to canonical term names during import
     pub fields: Vec<(usize, String)>,
-    /// Field delimiter parsed from fieldsTerminatedBy attribute (default: ',')
-    pub delimiter: char,
+    /// How the extension file is delimited and encoded
+    pub format: CsvFormat,
+    /// Position of the `<coreid>` column, which is renamed to
+    /// `core_id_column` on import if its header doesn't already match
+    pub core_id_index: Option<usize>,
+    /// Field declarations with a default value
+    pub defaults: Vec<FieldDefault>,
+}
+
+/// A meta.xml `<field>` with a `default` attribute. With an index, the
+/// default fills in rows where that column is empty; without one, the field
+/// is a constant that applies to every row and has no column in the data file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDefault {
+    pub index: Option<usize>,
+    /// Term name, e.g. "basisOfRecord"
+    pub term_name: String,
+    pub value: String,
+}
+
+/// Extracts the term name from a term URI, e.g.
+/// http://rs.tdwg.org/dwc/terms/basisOfRecord -> basisOfRecord, or
+/// http://www.w3.org/2003/01/geo/wgs84_pos#lat -> lat
+fn term_name(term: &str) -> Option<&str> {
+    term.rsplit(['/', '#']).next()
+}
+
+/// Parses `<field>` declarations with an index as (index, term name)
+fn parse_fields(node: Node) -> Vec<(usize, String)> {
+    node.descendants()
+        .filter(|n| n.has_tag_name("field"))
+        .filter_map(|field_node| {
+            let index = field_node.attribute("index")?
+                .parse::<usize>().ok()?;
+            let term_name = term_name(field_node.attribute("term")?)?;
+            Some((index, term_name.to_string()))
+        })
+        .collect()
+}
+
+/// Parses `<field>` declarations that have a default value
+fn parse_field_defaults(node: Node) -> Vec<FieldDefault> {
+    node.descendants()
+        .filter(|n| n.has_tag_name("field"))
+        .filter_map(|field_node| {
+            let value = field_node.attribute("default")?;
+            let term_name = term_name(field_node.attribute("term")?)?;
+            Some(FieldDefault {
+                index: field_node.attribute("index").and_then(|i| i.parse().ok()),
+                term_name: term_name.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect()
 }
 
 /// Parses the `fieldsTerminatedBy` XML attribute value into a delimiter char.
@@ -50,16 +191,171 @@ pub(crate) fn parse_delimiter(attr: Option<&str>) -> char {
     }
 }
 
+/// How a core or extension data file is laid out, from the attributes on its
+/// meta.xml element. Only attributes meta.xml actually declares are set, so
+/// DuckDB still sniffs anything left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvFormat {
+    /// From fieldsTerminatedBy
+    pub delimiter: Option<char>,
+    /// From fieldsEnclosedBy. Empty means values aren't quoted at all, as in
+    /// GBIF's tab-delimited downloads, where stray quotes are part of values.
+    pub quote: Option<String>,
+    /// From linesTerminatedBy, as DuckDB spells it, e.g. `\r\n`
+    pub line_terminator: Option<&'static str>,
+    /// From ignoreHeaderLines. The last ignored line is taken as the header.
+    pub header_lines: Option<usize>,
+    /// Column names for a file without a header row, from the terms of its
+    /// `<field>` declarations by index. Columns without one are named the
+    /// way the `<id>` or `<coreid>` at that index would be, or columnN.
+    pub column_names: Vec<String>,
+    /// From encoding, as DuckDB names it. None for UTF-8 or an encoding
+    /// DuckDB can't read, see `is_supported_encoding`.
+    pub encoding: Option<&'static str>,
+}
+
+impl CsvFormat {
+    fn from_node(node: Node) -> Self {
+        let header_lines = node
+            .attribute("ignoreHeaderLines")
+            .and_then(|attr| attr.trim().parse().ok());
+        Self {
+            delimiter: node
+                .attribute("fieldsTerminatedBy")
+                .map(|attr| parse_delimiter(Some(attr))),
+            // DuckDB only quotes with a single character
+            quote: node
+                .attribute("fieldsEnclosedBy")
+                .filter(|attr| attr.chars().count() <= 1)
+                .map(String::from),
+            line_terminator: node.attribute("linesTerminatedBy").and_then(|attr| {
+                match attr {
+                    r"\n" | "\n" => Some(r"\n"),
+                    r"\r\n" | "\r\n" => Some(r"\r\n"),
+                    r"\r" | "\r" => Some(r"\r"),
+                    _ => None,
+                }
+            }),
+            header_lines,
+            column_names: if header_lines == Some(0) {
+                headerless_column_names(node)
+            } else {
+                vec![]
+            },
+            encoding: node.attribute("encoding").and_then(duckdb_encoding),
+        }
+    }
+
+    /// Field delimiter, defaulting to a comma like the Darwin Core text guide
+    pub fn delimiter(&self) -> char {
+        self.delimiter.unwrap_or(',')
+    }
+
+    /// Options to append to a DuckDB read_csv or sniff_csv call, each with a
+    /// leading comma
+    pub(crate) fn read_csv_options(&self) -> String {
+        let quote_sql = |value: &str| value.replace('\'', "''");
+        let mut options = String::new();
+        if let Some(delimiter) = self.delimiter {
+            options.push_str(&format!(", delim = '{}'", quote_sql(&delimiter.to_string())));
+        }
+        if let Some(quote) = &self.quote {
+            options.push_str(&format!(", quote = '{}'", quote_sql(quote)));
+        }
+        if let Some(line_terminator) = self.line_terminator {
+            options.push_str(&format!(", new_line = '{line_terminator}'"));
+        }
+        match self.header_lines {
+            Some(0) => options.push_str(", header = false"),
+            Some(n) => options.push_str(&format!(", header = true, skip = {}", n - 1)),
+            None => {}
+        }
+        if !self.column_names.is_empty() {
+            let names: Vec<String> = self.column_names
+                .iter()
+                .map(|name| format!("'{}'", quote_sql(name)))
+                .collect();
+            options.push_str(&format!(", names = [{}]", names.join(", ")));
+        }
+        if let Some(encoding) = self.encoding {
+            options.push_str(&format!(", encoding = '{encoding}'"));
+        }
+        options
+    }
+}
+
+/// Names for the columns of a data file without a header row, up to the
+/// last one meta.xml declares
+fn headerless_column_names(node: Node) -> Vec<String> {
+    let fields = parse_fields(node);
+    let id_index = ["id", "coreid"].into_iter().find_map(|tag| {
+        node.children()
+            .find(|n| n.has_tag_name(tag))
+            .and_then(|n| n.attribute("index"))
+            .and_then(|index| index.parse::<usize>().ok())
+            .map(|index| (index, tag))
+    });
+    let Some(last_index) = fields
+        .iter()
+        .map(|(index, _)| *index)
+        .chain(id_index.map(|(index, _)| index))
+        .max()
+    else {
+        return vec![];
+    };
+    (0..=last_index)
+        .map(|index| {
+            fields
+                .iter()
+                .find(|(field_index, _)| *field_index == index)
+                .map(|(_, term)| term.clone())
+                .or_else(|| {
+                    id_index
+                        .filter(|(id_index, _)| *id_index == index)
+                        .map(|(_, tag)| tag.to_string())
+                })
+                .unwrap_or_else(|| format!("column{index}"))
+        })
+        .collect()
+}
+
+/// DuckDB's name for a meta.xml encoding it can read other than UTF-8
+fn duckdb_encoding(encoding: &str) -> Option<&'static str> {
+    match encoding.trim().to_ascii_lowercase().as_str() {
+        "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" => Some("latin-1"),
+        "utf-16" | "utf16" => Some("utf-16"),
+        _ => None,
+    }
+}
+
+/// Whether DuckDB can read data files in a meta.xml encoding
+fn is_supported_encoding(encoding: &str) -> bool {
+    matches!(encoding.trim().to_ascii_lowercase().as_str(), "utf-8" | "utf8")
+        || duckdb_encoding(encoding).is_some()
+}
 
 #[derive(Debug)]
 struct ZipFileInfo {
     index: usize,
     path: PathBuf,
     is_dir: bool,
+    /// Uncompressed size in bytes
+    size: u64,
     #[cfg(unix)]
     unix_mode: Option<u32>,
 }
 
+/// SQL expressions for the values of tile points beyond their coordinates,
+/// NULL (or false) for columns the archive doesn't have
+struct TilePointColumns {
+    event_date: String,
+    /// Value of the column the map is styled by
+    attribute: String,
+    /// Whether informationWithheld says the coordinates were obscured
+    obscured: String,
+    uncertainty: String,
+}
+
 /// Represents a Darwin Core Archive
 pub struct Archive {
     /// Directory where archive contents are stored
@@ -69,6 +365,12 @@ pub struct Archive {
 
     pub core_id_column: String,
 
+    /// Class of the records in the archive's database
+    pub core_type: CoreType,
+
+    /// Path to the DuckDB database file
+    db_path: PathBuf,
+
     /// Internal database for querying archive data
     db: Database,
 }
@@ -76,22 +378,108 @@ pub struct Archive {
 impl Archive {
     /// Opens and extracts a Darwin Core Archive with progress callback
     pub fn open<F>(
+        archive_path: &Path,
+        base_dir: &Path,
+        progress_callback: F,
+    ) -> Result<Self>
+    where
+        F: FnMut(&str),
+    {
+        Self::open_with_extract_progress(
+            archive_path,
+            base_dir,
+            progress_callback,
+            &|_, _| {},
+            &|_| {},
+            &CoreIdStrategy::default(),
+            true,
+            &AtomicBool::new(false),
+        )
+    }
+
+    /// Like `open`, also calling `on_extract_progress` with (extracted bytes,
+    /// total bytes) during the extracting stage and `on_import_progress` as
+    /// data files are imported while creating the database.
+    /// `on_extract_progress` is called from extraction worker threads.
+    /// Records are given IDs generated with `core_id_strategy` if the
+    /// archive's own are missing or duplicated.
+    /// With `check_disk_space`, archives that look too big for the free
+    /// space under `base_dir` fail with `ChuckError::InsufficientDiskSpace`
+    /// before anything is extracted. Setting `cancel` stops extraction and
+    /// returns `ChuckError::Cancelled` at the next check, after removing the
+    /// partially-created storage directory.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_with_extract_progress<F>(
         archive_path: &Path,
         base_dir: &Path,
         mut progress_callback: F,
+        on_extract_progress: &(dyn Fn(u64, u64) + Sync),
+        on_import_progress: &dyn Fn(&ImportProgress),
+        core_id_strategy: &CoreIdStrategy,
+        check_disk_space: bool,
+        cancel: &AtomicBool,
     ) -> Result<Self>
     where
         F: FnMut(&str),
     {
         // Validate that the zip contains meta.xml before any destructive operations
         validate_is_dwca(archive_path)?;
+        if check_disk_space {
+            super::preflight::check_disk_space(archive_path, base_dir)?;
+        }
+        // Read before extracting so a file replaced mid-import shows up as
+        // changed
+        let source = ArchiveSource::of(archive_path);
 
         // Create storage directory based on archive hash
         progress_callback("importing");
         let storage_dir = create_storage_dir(archive_path, base_dir)?;
 
-        // Remove all other archive directories in the base directory
-        remove_other_archives(base_dir, &storage_dir)?;
+        let result = Self::import_into(
+            archive_path,
+            base_dir,
+            storage_dir.clone(),
+            progress_callback,
+            on_extract_progress,
+            on_import_progress,
+            core_id_strategy,
+            cancel,
+        );
+        if matches!(result, Err(ChuckError::Cancelled)) {
+            if let Err(e) = std::fs::remove_dir_all(&storage_dir) {
+                log::warn!("Failed to remove cancelled archive {}: {e}", storage_dir.display());
+            }
+        }
+        if let (Ok(archive), Some(source)) = (&result, source) {
+            save_source(&archive.storage_dir, &source)?;
+        }
+        result
+    }
+
+    fn import_into<F>(
+        archive_path: &Path,
+        base_dir: &Path,
+        storage_dir: PathBuf,
+        mut progress_callback: F,
+        on_extract_progress: &(dyn Fn(u64, u64) + Sync),
+        on_import_progress: &dyn Fn(&ImportProgress),
+        core_id_strategy: &CoreIdStrategy,
+        cancel: &AtomicBool,
+    ) -> Result<Self>
+    where
+        F: FnMut(&str),
+    {
+        let check_cancelled = || {
+            if cancel.load(Ordering::Relaxed) {
+                Err(ChuckError::Cancelled)
+            } else {
+                Ok(())
+            }
+        };
+        check_cancelled()?;
+
+        // Make room for this archive by removing the oldest ones
+        remove_old_archives(base_dir, MAX_OPEN_ARCHIVES)?;
 
         // Create a hard link to the original archive for lazy photo extraction
         // This is instant and doesn't copy data, but keeps the file accessible
@@ -103,7 +491,8 @@ impl Archive {
         })?;
 
         progress_callback("extracting");
-        extract_archive(archive_path, &storage_dir)?;
+        extract_archive(archive_path, &storage_dir, on_extract_progress, cancel)?;
+        check_cancelled()?;
 
         let meta = parse_meta_xml(&storage_dir)?;
         log::debug!("extensions: {:?}", meta.extensions);
@@ -115,17 +504,45 @@ impl Archive {
             .and_then(|s| s.to_str())
             .unwrap_or("archive");
         let db_path = storage_dir.join(format!("{db_name}.db"));
-        let db = Database::create_from_core_files(
+        let db = Database::create_from_core_files_with_progress(
             &meta.core_files,
+            &meta.core_format,
+            &meta.core_fields,
+            &meta.core_defaults,
+            meta.events.as_ref(),
             &meta.extensions,
             &db_path,
             &meta.core_id_column,
+            core_id_strategy,
+            on_import_progress,
         )?;
+        // Database creation can't be interrupted, but cancelling while it
+        // runs still discards the result
+        if cancel.load(Ordering::Relaxed) {
+            drop(db);
+            return Err(ChuckError::Cancelled);
+        }
 
         // Remove CSV/TXT data files now that they've been imported into the database
         remove_data_files(&meta.core_files, &meta.extensions);
+        if let Some(events) = &meta.events {
+            remove_data_files(&events.files, &[]);
+        }
 
-        let core_id_column = meta.core_id_column;
+        let core_type = meta.record_type();
+        let import_warnings: Vec<ImportWarning> = meta.warnings
+            .into_iter()
+            .chain(db.import_warnings().iter().cloned())
+            .collect();
+        for warning in &import_warnings {
+            log::warn!("Import warning: {}", warning.message);
+        }
+        save_import_warnings(&storage_dir, &import_warnings)?;
+        if let Some(generated) = db.generated_core_id() {
+            save_generated_core_id(&storage_dir, generated)?;
+        }
+
+        let core_id_column = db.core_id_column().to_string();
 
         Ok(Self {
             // archive_path: archive_path.to_path_buf(),
@@ -136,28 +553,87 @@ impl Archive {
                 .unwrap_or("unknown")
                 .to_string(),
             core_id_column,
+            core_type,
+            db_path,
             db,
         })
     }
 
-    /// Returns an Archive representing the currently-open archive
-    /// (i.e. the archive that is already unzipped and has a DuckDB database)
+    /// Returns an Archive representing the currently-open archive, i.e. the
+    /// most recently opened archive that is already unzipped and has a
+    /// DuckDB database
     pub fn current(base_dir: &Path) -> Result<Self> {
-        if !base_dir.exists() {
-            return Err(ChuckError::NoArchiveFound(base_dir.to_path_buf()));
+        let storage_dir = archive_dirs(base_dir)?
+            .into_iter()
+            .find(|dir| find_db_path(dir).is_some())
+            .ok_or_else(|| ChuckError::NoArchiveFound(base_dir.to_path_buf()))?;
+        Self::load(storage_dir)
+    }
+
+    /// Returns the open archive with the given ID (see `id`)
+    pub fn by_id(base_dir: &Path, archive_id: &str) -> Result<Self> {
+        // IDs are directory names, so don't let them reach outside base_dir
+        if archive_id.is_empty()
+            || archive_id.contains(['/', '\\'])
+            || archive_id == "."
+            || archive_id == ".."
+        {
+            return Err(ChuckError::InvalidFileName(PathBuf::from(archive_id)));
+        }
+        let storage_dir = base_dir.join(archive_id);
+        if !storage_dir.is_dir() {
+            return Err(ChuckError::NoArchiveFound(storage_dir));
         }
+        Self::load(storage_dir)
+    }
 
-        // Find the first directory in base_dir
-        let storage_dir = std::fs::read_dir(base_dir)
-            .map_err(|e| ChuckError::FileRead {
-                path: base_dir.to_path_buf(),
-                source: e,
-            })?
-            .filter_map(|e| e.ok())
-            .map(|e| e.path())
-            .find(|p| p.is_dir())
-            .ok_or_else(|| ChuckError::NoArchiveFound(base_dir.to_path_buf()))?;
+    /// Returns the archive with the given ID, or the current archive if
+    /// there's no ID
+    pub fn find(base_dir: &Path, archive_id: Option<&str>) -> Result<Self> {
+        match archive_id {
+            Some(archive_id) => Self::by_id(base_dir, archive_id),
+            None => Self::current(base_dir),
+        }
+    }
+
+    /// Returns all open archives, most recently opened first. Archives that
+    /// fail to load, e.g. from an interrupted import, are skipped.
+    pub fn list(base_dir: &Path) -> Result<Vec<Self>> {
+        Ok(archive_dirs(base_dir)?
+            .into_iter()
+            .filter(|dir| find_db_path(dir).is_some())
+            .filter_map(|dir| match Self::load(dir.clone()) {
+                Ok(archive) => Some(archive),
+                Err(e) => {
+                    log::warn!("Skipping archive in {}: {e}", dir.display());
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Removes an open archive and everything extracted from it
+    pub fn close(self) -> Result<()> {
+        let Self { storage_dir, db, .. } = self;
+        // Release the database file before deleting it
+        drop(db);
+        std::fs::remove_dir_all(&storage_dir).map_err(|source| ChuckError::FileWrite {
+            path: storage_dir,
+            source,
+        })
+    }
 
+    /// Identifies the archive among open archives. This is the name of its
+    /// storage directory.
+    pub fn id(&self) -> String {
+        self.storage_dir
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn load(storage_dir: PathBuf) -> Result<Self> {
         // Extract name from the directory name (format: "filename-hash")
         let dir_name = storage_dir
             .file_name()
@@ -170,31 +646,63 @@ impl Archive {
             .unwrap_or("unknown")
             .to_string();
 
-        // Find the first .db file in the storage directory
-        let db_path = std::fs::read_dir(&storage_dir)
-            .map_err(|e| ChuckError::FileRead {
-                path: storage_dir.clone(),
-                source: e,
-            })?
-            .filter_map(|e| e.ok())
-            .map(|e| e.path())
-            .find(|p| p.extension().and_then(|s| s.to_str()) == Some("db"))
+        let db_path = find_db_path(&storage_dir)
             .ok_or_else(|| ChuckError::NoArchiveFound(storage_dir.clone()))?;
 
         // Parse meta.xml to get extension information
         let meta = parse_meta_xml(&storage_dir)?;
 
-        let db = Database::open(&db_path, meta.core_id_column.clone(), &meta.extensions)?;
-        let core_id_column = meta.core_id_column;
+        let db = Database::open(&db_path, meta.core_id_column.clone(), &meta.extensions)?
+            .with_filter_cache(storage_dir.join("filter_cache"), &db_path);
+        let core_type = meta.record_type();
+        let core_id_column = db.core_id_column().to_string();
 
         Ok(Self {
             storage_dir,
             name,
             core_id_column,
+            core_type,
+            db_path,
             db,
         })
     }
 
+    /// Path to the archive's DuckDB database
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    /// Data files extracted from the archive that are still on disk. They're
+    /// removed once they've been imported, but archives imported by older
+    /// versions of Chuck, or where removing them failed, can still have them.
+    pub fn extracted_data_files(&self) -> Vec<PathBuf> {
+        let meta = match parse_meta_xml(&self.storage_dir) {
+            Ok(meta) => meta,
+            Err(e) => {
+                log::warn!("Failed to parse meta.xml in {}: {e}", self.storage_dir.display());
+                return vec![];
+            }
+        };
+        meta.core_files
+            .into_iter()
+            .chain(meta.extensions.into_iter().map(|ext| ext.location))
+            .chain(meta.events.into_iter().flat_map(|events| events.files))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// Deletes data files left over from extracting the archive, keeping the
+    /// database. Returns the number of bytes freed.
+    pub fn remove_extracted_data(&self) -> Result<u64> {
+        let mut freed = 0;
+        for path in self.extracted_data_files() {
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or_default();
+            std::fs::remove_file(&path).map_err(|source| ChuckError::FileWrite { path, source })?;
+            freed += size;
+        }
+        Ok(freed)
+    }
+
     /// Returns the number of core records in the archive
     pub fn core_count(&self) -> Result<usize> {
         self.db.count_records()
@@ -205,13 +713,107 @@ impl Archive {
         let available_columns = self.db.get_available_columns()?;
 
         Ok(crate::commands::archive::ArchiveInfo {
+            id: self.id(),
             name: self.name.clone(),
             core_count: self.core_count()?,
             core_id_column: self.core_id_column.clone(),
+            core_type: self.core_type,
             available_columns,
+            import_warnings: self.import_warnings(),
+            read_only: self.is_read_only(),
+            source_changed: self.source_changed(),
+            generated_core_id: self.generated_core_id(),
         })
     }
 
+    /// Whether changes to the database are disabled. Archives are read-only
+    /// until explicitly unlocked so browsing can never modify the source
+    /// data.
+    pub fn is_read_only(&self) -> bool {
+        !self.storage_dir.join(UNLOCKED_FILENAME).exists()
+    }
+
+    /// Locks or unlocks the archive. The setting lasts until the archive is
+    /// replaced by another import.
+    pub fn set_read_only(&self, read_only: bool) -> Result<()> {
+        let path = self.storage_dir.join(UNLOCKED_FILENAME);
+        if read_only {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(ChuckError::FileWrite { path, source: e })
+                }
+                _ => Ok(()),
+            }
+        } else {
+            std::fs::write(&path, "").map_err(|source| ChuckError::FileWrite { path, source })
+        }
+    }
+
+    /// The zip the archive was imported from, as it was then. None for
+    /// archives imported before sources were recorded.
+    pub fn source(&self) -> Option<ArchiveSource> {
+        load_source(&self.storage_dir)
+    }
+
+    /// Whether the zip the archive was imported from has been replaced
+    /// since, so the data here may be stale
+    pub fn source_changed(&self) -> bool {
+        self.source().is_some_and(|source| source.has_changed())
+    }
+
+    /// Returns non-fatal problems recorded when the archive was imported
+    pub fn import_warnings(&self) -> Vec<ImportWarning> {
+        load_import_warnings(&self.storage_dir)
+    }
+
+    /// How records were given IDs at import, if the archive's own couldn't
+    /// be used
+    pub fn generated_core_id(&self) -> Option<GeneratedCoreId> {
+        load_generated_core_id(&self.storage_dir)
+    }
+
+    /// Returns the archive's EML metadata, or empty metadata if it has no
+    /// eml.xml
+    pub fn eml(&self) -> Result<Eml> {
+        let path = self.storage_dir.join(EML_FILENAME);
+        let xml = match std::fs::read_to_string(&path) {
+            Ok(xml) => xml,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Eml::default()),
+            Err(source) => return Err(ChuckError::FileRead { path, source }),
+        };
+        Eml::parse(&xml).map_err(|source| ChuckError::XmlParse { path, source })
+    }
+
+    /// Citation for the occurrences matching `search_params`, from eml.xml
+    /// and the datasets the records came from, accessed today
+    pub fn citation(&self, search_params: SearchParams) -> Result<crate::citation::Citation> {
+        let sources = crate::citation::citation_sources(&self.db, search_params)?;
+        Ok(crate::citation::build_citation(&self.eml()?, sources, &crate::citation::access_date()))
+    }
+
+    /// License codes of the media of occurrences matching `search_params`,
+    /// most common first
+    pub fn media_license_summary(
+        &self,
+        search_params: SearchParams,
+    ) -> Result<Vec<crate::media_licenses::LicenseCount>> {
+        crate::media_licenses::media_license_summary(&self.db, search_params)
+    }
+
+    /// Replaces the editable EML fields and rewrites eml.xml, keeping any
+    /// elements the fields don't cover. Exports include the new eml.xml. Fails
+    /// if the archive is read-only.
+    pub fn update_eml(&self, edits: Eml) -> Result<Eml> {
+        if self.is_read_only() {
+            return Err(ChuckError::ReadOnly);
+        }
+        let mut eml = self.eml()?;
+        eml.apply(edits);
+        let path = self.storage_dir.join(EML_FILENAME);
+        std::fs::write(&path, eml.to_xml()).map_err(|source| ChuckError::FileWrite { path, source })?;
+        Ok(eml)
+    }
+
     /// Searches for occurrences in the archive
     pub fn search(
         &self,
@@ -219,19 +821,42 @@ impl Archive {
         offset: usize,
         search_params: SearchParams,
         fields: Option<Vec<String>>,
+    ) -> Result<crate::commands::archive::SearchResult> {
+        self.search_with_execution_info(limit, offset, None, search_params, fields, false)
+    }
+
+    /// Searches for occurrences in the archive, optionally including
+    /// diagnostics about how the query ran. See
+    /// `Database::search_with_execution_info` for cursors.
+    pub fn search_with_execution_info(
+        &self,
+        limit: usize,
+        offset: usize,
+        cursor: Option<&str>,
+        search_params: SearchParams,
+        fields: Option<Vec<String>>,
+        debug: bool,
     ) -> Result<crate::commands::archive::SearchResult> {
         let params = SearchParams {
             sort_by: search_params.sort_by.clone().or(Some(self.core_id_column.clone())),
             ..search_params
         };
-        self.db.search(
+        self.db.search_with_execution_info(
             limit,
             offset,
+            cursor,
             params,
-            fields
+            fields,
+            debug,
         )
     }
 
+    /// Materializes the rows matching `search_params` for later queries
+    /// with the same filters. See `Database::materialize_filter`.
+    pub fn materialize_filter(&self, search_params: SearchParams) -> Result<usize> {
+        self.db.materialize_filter(search_params)
+    }
+
     /// Calls `f` once per occurrence matching `search_params`.
     /// See `Database::for_each_occurrence` for details.
     pub fn for_each_occurrence<F>(
@@ -245,6 +870,33 @@ impl Archive {
         self.db.for_each_occurrence(search_params, f)
     }
 
+    /// Writes filtered occurrences to a Parquet file, optionally with
+    /// extension rows as nested columns
+    pub fn export_parquet(
+        &self,
+        search_params: SearchParams,
+        path: &Path,
+        include_extensions: bool,
+    ) -> Result<usize> {
+        self.db.export_parquet(search_params, path, include_extensions)
+    }
+
+    /// Copies the archive's occurrences and extensions into a SQLite database
+    pub fn export_sqlite(&self, path: &Path) -> Result<usize> {
+        self.db.export_sqlite(path)
+    }
+
+    /// Runs an ad hoc SELECT against the archive database, returning at most
+    /// `limit` rows
+    pub fn execute_sql(
+        &self,
+        sql: &str,
+        limit: usize,
+    ) -> Result<chuck_core::sql_console::SqlResult> {
+        chuck_core::sql_console::execute_sql(self.db.connection(), sql, limit)
+            .map_err(|e| ChuckError::Sql(e.to_string()))
+    }
+
     /// Get autocomplete suggestions for a given column
     pub fn get_autocomplete_suggestions(
         &self,
@@ -255,6 +907,27 @@ impl Archive {
         self.db.get_autocomplete_suggestions(column_name, search_term, limit)
     }
 
+    /// Most common values of a column under the search, with counts
+    pub fn get_column_values(
+        &self,
+        column_name: &str,
+        search_params: &SearchParams,
+        search_term: Option<&str>,
+        limit: usize,
+    ) -> Result<crate::db::ColumnValues> {
+        self.db.get_column_values(column_name, search_params, search_term, limit, &self.core_id_column)
+    }
+
+    /// Most common values of several columns under the search, with counts
+    pub fn get_facets(
+        &self,
+        column_names: &[String],
+        search_params: &SearchParams,
+        limit: usize,
+    ) -> Result<Vec<crate::db::Facet>> {
+        self.db.get_facets(column_names, search_params, limit, &self.core_id_column)
+    }
+
     /// Returns the extension table metadata (extension type + core ID column)
     pub fn extension_tables(&self) -> &[(chuck_core::DwcaExtension, String)] {
         self.db.extension_tables()
@@ -274,8 +947,355 @@ impl Archive {
         field_name: &str,
         search_params: &SearchParams,
         limit: Option<usize>,
+        metrics: &[crate::db::AggregationMetric],
+        order_by: Option<crate::db::AggregationMetric>,
     ) -> Result<Vec<crate::db::AggregationResult>> {
-        self.db.aggregate_by_field(field_name, search_params, limit, &self.core_id_column)
+        let mut results = self.db.aggregate_by_field_with_metrics(
+            field_name,
+            search_params,
+            limit,
+            &self.core_id_column,
+            metrics,
+            order_by,
+        )?;
+        // Remote photos that were already downloaded load from the cache
+        let photo_cache = crate::photo_cache::PhotoCache::new(&self.storage_dir.join("photo_cache"));
+        for result in &mut results {
+            if let Some(url) = &result.photo_url
+                && !result.photo_is_local
+            {
+                result.photo_is_local = photo_cache.get_remote_cache_path(url).exists();
+            }
+        }
+        Ok(results)
+    }
+
+    /// Aggregates occurrences by the individual values of a multi-value field
+    pub fn aggregate_by_field_values(
+        &self,
+        field_name: &str,
+        search_params: &SearchParams,
+        limit: Option<usize>,
+    ) -> Result<Vec<crate::db::AggregationResult>> {
+        self.db.aggregate_by_field_values(field_name, search_params, limit, &self.core_id_column)
+    }
+
+    /// Example occurrences from one group of aggregate_by_field
+    pub fn group_examples(
+        &self,
+        field_name: &str,
+        value: Option<&str>,
+        search_params: &SearchParams,
+        limit: usize,
+    ) -> Result<Vec<crate::db::GroupExample>> {
+        self.db.group_examples(field_name, value, search_params, limit, &self.core_id_column)
+    }
+
+    /// Aggregates occurrences by two fields for a crosstab
+    pub fn aggregate_by_two_fields(
+        &self,
+        primary_field: &str,
+        secondary_field: &str,
+        search_params: &SearchParams,
+        limit_per_group: Option<usize>,
+    ) -> Result<Vec<crate::db::CrosstabResult>> {
+        self.db.aggregate_by_two_fields(
+            primary_field,
+            secondary_field,
+            search_params,
+            limit_per_group,
+            &self.core_id_column,
+        )
+    }
+
+    /// Summary statistics for a numeric column under the given filters
+    pub fn column_stats(
+        &self,
+        column_name: &str,
+        search_params: &SearchParams,
+    ) -> Result<crate::db::ColumnStats> {
+        self.db.column_stats(column_name, search_params, &self.core_id_column)
+    }
+
+    /// Aggregates occurrences by month, week, or year of eventDate
+    pub fn aggregate_by_time(
+        &self,
+        bucket: crate::db::TimeBucket,
+        search_params: &SearchParams,
+    ) -> Result<Vec<crate::db::TimeAggregationResult>> {
+        self.db.aggregate_by_time(bucket, search_params, &self.core_id_column)
+    }
+
+    /// Counts occurrences per day, month, or year of eventDate, with zeros
+    /// for periods that have none
+    pub fn time_series(
+        &self,
+        interval: crate::db::TimeInterval,
+        search_params: &SearchParams,
+    ) -> Result<Vec<crate::db::TimeSeriesPoint>> {
+        self.db.time_series(interval, search_params, &self.core_id_column)
+    }
+
+    /// Cumulative count of distinct scientific names by the date each was
+    /// first recorded
+    pub fn species_accumulation(
+        &self,
+        search_params: &SearchParams,
+    ) -> Result<Vec<crate::db::AccumulationPoint>> {
+        self.db.species_accumulation(search_params, &self.core_id_column)
+    }
+
+    /// Record and species counts, date range, extent, and monthly activity
+    /// of one collector
+    pub fn collector_summary(
+        &self,
+        recorded_by: &str,
+        search_params: &SearchParams,
+    ) -> Result<crate::db::CollectorSummary> {
+        self.db.collector_summary(recorded_by, search_params, &self.core_id_column)
+    }
+
+    /// Record and species counts per cell of a lat/lng grid
+    pub fn grid_summary(
+        &self,
+        cell_size: f64,
+        search_params: &SearchParams,
+    ) -> Result<Vec<crate::db::GridCell>> {
+        self.db.grid_summary(cell_size, search_params, &self.core_id_column)
+    }
+
+    /// Returns enrichments that have filled in columns of the archive
+    pub fn enrichments(&self) -> Result<Vec<crate::enrichment::Enrichment>> {
+        crate::enrichment::list_enrichments(self.db.connection())
+    }
+
+    /// Fields of each occurrence filled by enrichments, by core ID
+    pub fn derived_fields(&self) -> Result<std::collections::HashMap<String, String>> {
+        crate::enrichment::derived_fields(self.db.connection(), &self.core_id_column)
+    }
+
+    /// Returns scientific names of occurrences that lack a vernacularName
+    pub fn scientific_names_missing_vernacular(&self) -> Result<Vec<String>> {
+        crate::enrichment::scientific_names_missing_vernacular(self.db.connection(), &self.core_id_column)
+    }
+
+    /// Runs `f` with a read-write connection to the archive's database, e.g.
+    /// to apply an enrichment. Consumes the archive because its read-only
+    /// connection has to be closed first. Fails if the archive is read-only.
+    pub fn with_writable_db<T>(
+        self,
+        f: impl FnOnce(&duckdb::Connection) -> Result<T>,
+    ) -> Result<T> {
+        if self.is_read_only() {
+            return Err(ChuckError::ReadOnly);
+        }
+        let Self { db_path, db, .. } = self;
+        drop(db);
+        let conn = duckdb::Connection::open(&db_path)?;
+        f(&conn)
+    }
+
+    /// Splits multi-value fields like recordedBy into the multi_values table
+    /// so filters and aggregations can use individual values. Returns the
+    /// names of the fields that were split.
+    pub fn split_multi_value_fields(self) -> Result<Vec<String>> {
+        let core_id_column = self.core_id_column.clone();
+        self.with_writable_db(|conn| {
+            crate::multi_value::split_multi_value_fields(conn, &core_id_column)
+        })
+    }
+
+    /// Loads a Darwin Core taxon file so higherTaxon filters match records
+    /// identified at any rank below the filtered taxon. Returns the number of
+    /// taxa loaded.
+    pub fn load_taxonomy(self, taxon_file: &Path) -> Result<usize> {
+        self.with_writable_db(|conn| crate::taxonomy::load_taxonomy(conn, taxon_file))
+    }
+
+    /// Reads placeholder values like -9999 in numeric fields as NULL so
+    /// stats, maps, and exports treat them as missing. Returns the number of
+    /// values mapped.
+    pub fn map_sentinel_values(self) -> Result<usize> {
+        let core_id_column = self.core_id_column.clone();
+        let available_columns = self.db.get_available_columns()?;
+        self.with_writable_db(|conn| {
+            let mapped = crate::sentinels::map_sentinels_to_null(conn, &available_columns)?;
+            let occurrences = crate::overlay::occurrences_source(conn, &core_id_column)?;
+            crate::archive_stats::store_stats(conn, &occurrences)?;
+            Ok(mapped)
+        })
+    }
+
+    /// Sets one field of an occurrence by core ID. The change is logged as an
+    /// edit on top of the archive's own value, so it can be listed with
+    /// `edits` and carried into exported archives
+    pub fn update_occurrence_field(
+        self,
+        core_id: &str,
+        column: &str,
+        value: Option<&str>,
+    ) -> Result<crate::edits::Edit> {
+        let core_id_column = self.core_id_column.clone();
+        self.with_writable_db(|conn| {
+            let edit =
+                crate::edits::update_occurrence_field(conn, &core_id_column, core_id, column, value)?;
+            // Recomputing stats for every edit would be slow, so they're
+            // computed when next asked for instead
+            crate::archive_stats::clear_stats(conn)?;
+            Ok(edit)
+        })
+    }
+
+    /// Summary stats for the overview: stored ones if there are any,
+    /// otherwise computed now
+    pub fn stats(&self) -> Result<crate::archive_stats::ArchiveStats> {
+        let conn = self.db.connection();
+        match crate::archive_stats::read_stats(conn)? {
+            Some(stats) => Ok(stats),
+            None => crate::archive_stats::compute_stats(conn, self.db.occurrences_source()),
+        }
+    }
+
+    /// Returns edits made with update_occurrence_field, oldest first,
+    /// optionally only those to one occurrence
+    pub fn edits(&self, core_id: Option<&str>) -> Result<Vec<crate::edits::Edit>> {
+        crate::edits::list_edits(self.db.connection(), core_id)
+    }
+
+    /// Flags an occurrence by core ID for review, replacing the note of a
+    /// flag it already has
+    pub fn flag_occurrence(
+        self,
+        core_id: &str,
+        flag: &str,
+        note: Option<&str>,
+    ) -> Result<crate::flags::Flag> {
+        let core_id_column = self.core_id_column.clone();
+        self.with_writable_db(|conn| {
+            crate::flags::flag_occurrence(conn, &core_id_column, core_id, flag, note)
+        })
+    }
+
+    /// Removes a flag from an occurrence. Returns whether it had the flag.
+    pub fn unflag_occurrence(self, core_id: &str, flag: &str) -> Result<bool> {
+        self.with_writable_db(|conn| crate::flags::unflag_occurrence(conn, core_id, flag))
+    }
+
+    /// Excludes the occurrences matching `search_params` from exports
+    /// without deleting them. Returns the number newly excluded.
+    pub fn exclude_occurrences(self, search_params: SearchParams, reason: Option<&str>) -> Result<usize> {
+        let (_, where_clause, params, _) = self.db.query_parts(
+            search_params,
+            None,
+            &self.core_id_column,
+            self.db.extension_tables(),
+        )?;
+        let core_id_column = self.core_id_column.clone();
+        self.with_writable_db(|conn| {
+            crate::exclusions::exclude_occurrences(conn, &core_id_column, &where_clause, &params, reason)
+        })
+    }
+
+    /// Restores excluded occurrences by core ID, or all of them without any.
+    /// Returns the number restored.
+    pub fn restore_exclusions(self, core_ids: Option<&[String]>) -> Result<usize> {
+        self.with_writable_db(|conn| crate::exclusions::restore_exclusions(conn, core_ids))
+    }
+
+    /// Returns occurrences excluded from exports
+    pub fn exclusions(&self) -> Result<Vec<crate::exclusions::Exclusion>> {
+        crate::exclusions::list_exclusions(self.db.connection())
+    }
+
+    /// Core IDs of occurrences excluded from exports
+    pub fn excluded_ids(&self) -> Result<HashSet<String>> {
+        crate::exclusions::excluded_ids(self.db.connection())
+    }
+
+    /// Returns rows that couldn't be read from the data files and were
+    /// skipped while importing
+    pub fn import_issues(&self) -> Result<Vec<crate::import_issues::ImportIssue>> {
+        crate::import_issues::list_import_issues(self.db.connection())
+    }
+
+    /// Returns flags on occurrences, optionally only those on one occurrence
+    pub fn flags(&self, core_id: Option<&str>) -> Result<Vec<crate::flags::Flag>> {
+        crate::flags::list_flags(self.db.connection(), core_id)
+    }
+
+    /// Writes all flags to a CSV to share with data providers. Returns the
+    /// number of flags written.
+    pub fn export_flags_csv(&self, path: &Path) -> Result<usize> {
+        crate::flags::export_flags_csv(self.db.connection(), &self.core_id_column, path)
+    }
+
+    /// Undoes map_sentinel_values. Returns the number of values restored.
+    pub fn restore_sentinel_values(self) -> Result<usize> {
+        let core_id_column = self.core_id_column.clone();
+        self.with_writable_db(|conn| {
+            let restored = crate::sentinels::restore_sentinel_values(conn)?;
+            let occurrences = crate::overlay::occurrences_source(conn, &core_id_column)?;
+            crate::archive_stats::store_stats(conn, &occurrences)?;
+            Ok(restored)
+        })
+    }
+
+    /// Core IDs and coordinates of occurrences matching `search_params`
+    /// that have coordinates but no elevation
+    pub fn occurrences_missing_elevation(
+        &self,
+        search_params: SearchParams,
+    ) -> Result<Vec<(String, f64, f64)>> {
+        let matching_ids = self.query_matching_ids(search_params)?;
+        Ok(crate::enrichment::occurrences_missing_elevation(
+            self.db.connection(),
+            &self.core_id_column,
+        )?
+            .into_iter()
+            .filter(|(id, _, _)| matching_ids.contains(id))
+            .collect())
+    }
+
+    /// Fills missing countryCode, stateProvince, and county values of
+    /// occurrences matching `search_params` from their coordinates. Returns
+    /// an enrichment for each column that received values.
+    pub fn enrich_admin_areas(
+        self,
+        boundaries: &crate::admin_boundaries::AdminBoundaries,
+        search_params: SearchParams,
+    ) -> Result<Vec<crate::enrichment::Enrichment>> {
+        let matching_ids = self.query_matching_ids(search_params)?;
+        let areas: Vec<_> = crate::enrichment::occurrences_missing_admin_areas(
+            self.db.connection(),
+            &self.core_id_column,
+        )?
+            .into_iter()
+            .filter(|(id, _, _)| matching_ids.contains(id))
+            .map(|(id, lat, lon)| (id, boundaries.admin_area_at(lat, lon)))
+            .filter(|(_, area)| !area.is_empty())
+            .collect();
+        let core_id_column = self.core_id_column.clone();
+        self.with_writable_db(|conn| {
+            crate::enrichment::fill_admin_areas(
+                conn,
+                &core_id_column,
+                &areas,
+                "Natural Earth admin boundaries",
+            )
+        })
+    }
+
+    /// Runs data quality checks over all occurrences in the archive
+    pub fn quality_report(
+        &self,
+        boundaries: Option<&crate::country_boundaries::CountryBoundaries>,
+    ) -> Result<crate::quality::QualityReport> {
+        crate::quality::run_quality_report(
+            self.db.connection(),
+            &self.core_id_column,
+            &self.db.get_available_columns()?,
+            boundaries,
+        )
     }
 
     /// Retrieves a single occurrence by its core ID with all fields and extensions
@@ -286,12 +1306,58 @@ impl Archive {
         self.db.get_occurrence(&self.core_id_column, occurrence_id)
     }
 
-    /// Query occurrences within a bounding box for tile generation
-    /// Returns (core_id, latitude, longitude, scientificName) tuples
+    /// SQL expressions for the values of tile points beyond their
+    /// coordinates. Fails if `attribute` isn't a column, since it gets
+    /// interpolated into the query.
+    fn tile_point_columns(&self, attribute: Option<&str>) -> Result<TilePointColumns> {
+        let available_columns = self.db.get_available_columns()?;
+        let has_column = |column: &str| available_columns.iter().any(|c| c == column);
+        let as_text = |column: &str| format!("CAST({} AS VARCHAR)", Database::quote_identifier(column));
+
+        let event_date = if has_column("eventDate") {
+            as_text("eventDate")
+        } else {
+            String::from("NULL")
+        };
+        let attribute = match attribute {
+            Some(column) if has_column(column) => as_text(column),
+            Some(column) => {
+                return Err(ChuckError::MissingColumn(column.to_string()));
+            }
+            None => String::from("NULL"),
+        };
+        // Archives Chuck builds from coordinates the observer shared say the
+        // precise ones are "included here", so those aren't obscured
+        let obscured = if has_column("informationWithheld") {
+            let withheld = as_text("informationWithheld");
+            format!(
+                "COALESCE(({withheld} ILIKE '%obscured%' OR {withheld} ILIKE '%uncertainty increased%')
+                    AND {withheld} NOT ILIKE '%included here%', false)"
+            )
+        } else {
+            String::from("false")
+        };
+        let uncertainty = if has_column("coordinateUncertaintyInMeters") {
+            format!(
+                "TRY_CAST({} AS DOUBLE)",
+                Database::quote_identifier("coordinateUncertaintyInMeters")
+            )
+        } else {
+            String::from("NULL")
+        };
+        Ok(TilePointColumns { event_date, attribute, obscured, uncertainty })
+    }
+
+    /// Query occurrences within a bounding box for tile generation, with the
+    /// value of the `attribute` column if one is given so the map can style
+    /// points by it
     ///
     /// Uses grid-based sampling at low zoom levels to reduce data volume while
     /// preserving spatial extent (showing where observations exist across the tile)
-    #[allow(clippy::type_complexity)]
+    ///
+    /// `obscured` decides whether points with obscured coordinates are
+    /// marked, and whether they come with the cell their true location is in
+    #[allow(clippy::too_many_arguments)]
     pub fn query_tile(
         &self,
         west: f64,
@@ -300,40 +1366,48 @@ impl Archive {
         north: f64,
         zoom: u8,
         search_params: SearchParams,
-    ) -> Result<Vec<(String, f64, f64, Option<String>)>> {
+        attribute: Option<&str>,
+        obscured: ObscuredMode,
+    ) -> Result<Vec<TilePoint>> {
         let conn = self.db.connection();
+        let occurrences = self.db.occurrences_source();
+        let TilePointColumns {
+            event_date: event_date_select,
+            attribute: attribute_select,
+            obscured: obscured_select,
+            uncertainty: uncertainty_select,
+        } = self.tile_point_columns(attribute)?;
+        let obscured_select = match obscured {
+            ObscuredMode::Ignore => String::from("false"),
+            ObscuredMode::Mark | ObscuredMode::Cell => obscured_select,
+        };
+        let draw_cells = obscured == ObscuredMode::Cell;
 
         let (
             _,
             where_clause,
             mut where_interpolations,
             _
-        ) = Database::sql_parts(
+        ) = self.db.query_parts(
             search_params,
             None,
             self.core_id_column.as_ref(),
-            &[]
-        );
-
-        // Determine grid cell size based on zoom level
-        // At low zoom, use coarse grid to reduce points while preserving spatial extent
-        // At high zoom, return all points (no sampling)
-        let grid_size = match zoom {
-            0..=2 => Some(1.0),    // ~111km cells - very coarse sampling
-            3..=5 => Some(0.1),    // ~11km cells - moderate sampling
-            6..=8 => Some(0.01),   // ~1km cells - fine sampling
-            _ => None              // No sampling at zoom 9+
-        };
+            &[],
+        )?;
 
-        let query = if let Some(grid) = grid_size {
+        let query = if let Some(grid) = crate::tile_server::coords::sample_grid_size(zoom) {
             // Grid-based sampling: pick one point per grid cell
             format!(
                 "SELECT
                     ANY_VALUE({}) as core_id,
                     ANY_VALUE(decimalLatitude) as decimalLatitude,
                     ANY_VALUE(decimalLongitude) as decimalLongitude,
-                    ANY_VALUE(scientificName) as scientificName
-                 FROM occurrences
+                    ANY_VALUE(scientificName) as scientificName,
+                    ANY_VALUE({event_date_select}) as eventDate,
+                    ANY_VALUE({attribute_select}) as attribute,
+                    ANY_VALUE({obscured_select}) as obscured,
+                    ANY_VALUE({uncertainty_select}) as coordinateUncertaintyInMeters
+                 FROM {occurrences}
                  {}
                      decimalLatitude BETWEEN ? AND ?
                      AND decimalLongitude BETWEEN ? AND ?
@@ -354,8 +1428,11 @@ impl Archive {
         } else {
             // No sampling at high zoom - return all points
             format!(
-                "SELECT {}, decimalLatitude, decimalLongitude, scientificName
-                 FROM occurrences
+                "SELECT {}, decimalLatitude, decimalLongitude, scientificName,
+                    {event_date_select} as eventDate, {attribute_select} as attribute,
+                    {obscured_select} as obscured,
+                    {uncertainty_select} as coordinateUncertaintyInMeters
+                 FROM {occurrences}
                  {}
                      decimalLatitude BETWEEN ? AND ?
                      AND decimalLongitude BETWEEN ? AND ?
@@ -383,12 +1460,101 @@ impl Archive {
         let rows = stmt
             // .query_map([south, north, west, east], |row| {
             .query_map(select_param_refs.as_slice(), |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, f64>(1)?,
-                    row.get::<_, f64>(2)?,
-                    row.get::<_, Option<String>>(3)?,
-                ))
+                let latitude = row.get(1)?;
+                let longitude = row.get(2)?;
+                let obscured: bool = row.get(6)?;
+                Ok(TilePoint {
+                    core_id: row.get(0)?,
+                    latitude,
+                    longitude,
+                    scientific_name: row.get(3)?,
+                    event_date: row.get(4)?,
+                    attribute: row.get(5)?,
+                    obscured,
+                    cell: (obscured && draw_cells).then(|| obscuration_cell(latitude, longitude)),
+                    uncertainty: row.get(7)?,
+                })
+            })
+            .map_err(ChuckError::Database)?;
+
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(ChuckError::Database)
+    }
+
+    /// Occurrences within `tolerance` screen pixels of a point on the map at
+    /// a zoom level, nearest first. Tiles only have one point per grid cell
+    /// at low zooms, so this finds the occurrences a clicked point stands
+    /// for.
+    pub fn occurrences_at_point(
+        &self,
+        lat: f64,
+        lng: f64,
+        zoom: f64,
+        tolerance: f64,
+        search_params: SearchParams,
+    ) -> Result<Vec<TilePoint>> {
+        const LIMIT: usize = 50;
+        let conn = self.db.connection();
+        let occurrences = self.db.occurrences_source();
+        let TilePointColumns {
+            event_date: event_date_select,
+            obscured: obscured_select,
+            uncertainty: uncertainty_select,
+            ..
+        } = self.tile_point_columns(None)?;
+        let bbox = crate::tile_server::coords::pixel_bbox(lat, lng, zoom, tolerance);
+
+        let (_, where_clause, mut where_interpolations, _) = self.db.query_parts(
+            search_params,
+            None,
+            self.core_id_column.as_ref(),
+            &[],
+        )?;
+        // Longitude degrees shrink away from the equator, so scale them to
+        // compare distances
+        let query = format!(
+            "SELECT {}, decimalLatitude, decimalLongitude, scientificName, {event_date_select},
+                 {obscured_select}, {uncertainty_select}
+             FROM {occurrences}
+             {}
+                 decimalLatitude BETWEEN ? AND ?
+                 AND decimalLongitude BETWEEN ? AND ?
+             ORDER BY POW(decimalLatitude - ?, 2)
+                 + POW((decimalLongitude - ?) * COS(RADIANS(?)), 2)
+             LIMIT {LIMIT}",
+            Database::quote_identifier(&self.core_id_column),
+            if where_clause.is_empty() {
+                String::from("WHERE")
+            } else {
+                format!("{where_clause} AND")
+            },
+        );
+
+        let mut stmt = conn.prepare(&query).map_err(ChuckError::Database)?;
+        where_interpolations.push(Box::new(bbox.south));
+        where_interpolations.push(Box::new(bbox.north));
+        where_interpolations.push(Box::new(bbox.west));
+        where_interpolations.push(Box::new(bbox.east));
+        where_interpolations.push(Box::new(lat));
+        where_interpolations.push(Box::new(lng));
+        where_interpolations.push(Box::new(lat));
+        let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations
+            .iter()
+            .map(|p| p.as_ref())
+            .collect();
+        let rows = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok(TilePoint {
+                    core_id: row.get(0)?,
+                    latitude: row.get(1)?,
+                    longitude: row.get(2)?,
+                    scientific_name: row.get(3)?,
+                    event_date: row.get(4)?,
+                    attribute: None,
+                    obscured: row.get(5)?,
+                    cell: None,
+                    uncertainty: row.get(6)?,
+                })
             })
             .map_err(ChuckError::Database)?;
 
@@ -426,9 +1592,8 @@ impl Archive {
             &cached_file_path,
         )?;
 
-        // Evict LRU photos if cache is too large (2GB default)
-        const MAX_CACHE_SIZE: u64 = 2 * 1024 * 1024 * 1024; // 2GB
-        photo_cache.evict_lru(MAX_CACHE_SIZE)?;
+        // Evict LRU photos if cache is too large
+        photo_cache.evict_lru(crate::photo_cache::max_cache_size())?;
 
         Ok(cached_file_path.to_string_lossy().to_string())
     }
@@ -490,9 +1655,10 @@ fn remove_data_files(core_files: &[PathBuf], extensions: &[ExtensionInfo]) {
     }
 }
 
-fn remove_other_archives(base_dir: &Path, current_storage_dir: &Path) -> Result<()> {
+/// Storage directories in the base directory, most recently created first
+fn archive_dirs(base_dir: &Path) -> Result<Vec<PathBuf>> {
     if !base_dir.exists() {
-        return Ok(());
+        return Ok(vec![]);
     }
 
     let entries = std::fs::read_dir(base_dir).map_err(|e| ChuckError::FileRead {
@@ -500,94 +1666,173 @@ fn remove_other_archives(base_dir: &Path, current_storage_dir: &Path) -> Result<
         source: e,
     })?;
 
+    let mut dirs = Vec::new();
     for entry in entries {
         let entry = entry.map_err(|e| ChuckError::FileRead {
             path: base_dir.to_path_buf(),
             source: e,
         })?;
-
         let path = entry.path();
-        if path.is_dir() && path != current_storage_dir {
-            std::fs::remove_dir_all(&path).map_err(|e| ChuckError::DirectoryCreate {
-                path: path.clone(),
-                source: e,
-            })?;
+        if path.is_dir() {
+            dirs.push(path);
         }
     }
+    dirs.sort_by_key(|dir| std::cmp::Reverse(storage_dir_timestamp(dir)));
+    Ok(dirs)
+}
+
+/// The creation timestamp encoded in a storage directory name by
+/// create_storage_dir, or 0 if there isn't one
+fn storage_dir_timestamp(storage_dir: &Path) -> u128 {
+    storage_dir
+        .file_name()
+        .and_then(|s| s.to_str())
+        .and_then(|name| name.rsplit_once('-'))
+        .and_then(|(_, timestamp)| u128::from_str_radix(timestamp, 16).ok())
+        .unwrap_or(0)
+}
+
+/// The DuckDB database in a storage directory, if the import got that far
+fn find_db_path(storage_dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(storage_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| p.extension().and_then(|s| s.to_str()) == Some("db"))
+}
+
+/// Removes all but the `keep` most recently created archive directories
+fn remove_old_archives(base_dir: &Path, keep: usize) -> Result<()> {
+    for path in archive_dirs(base_dir)?.into_iter().skip(keep) {
+        std::fs::remove_dir_all(&path).map_err(|e| ChuckError::DirectoryCreate {
+            path: path.clone(),
+            source: e,
+        })?;
+    }
 
     Ok(())
 }
 
-fn extract_archive(archive_path: &Path, target_dir: &Path) -> Result<()> {
+/// Entries are copied in chunks of this size, reporting progress as they go
+const EXTRACT_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Minimum number of newly extracted bytes between progress reports
+const EXTRACT_PROGRESS_INTERVAL_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Extracts the entries meta.xml needs. Entries are split into one batch per
+/// worker thread, balanced by size, and each worker opens the zip once for its
+/// whole batch rather than once per entry, which matters for archives with
+/// thousands of media files. `on_progress` is called with (extracted bytes,
+/// total bytes) as data is written, including partway through large entries.
+fn extract_archive(
+    archive_path: &Path,
+    target_dir: &Path,
+    on_progress: &(dyn Fn(u64, u64) + Sync),
+    cancel: &AtomicBool,
+) -> Result<()> {
     let files_to_extract = get_files_to_extract(archive_path, target_dir)?;
-    let archive_path = Arc::new(archive_path.to_path_buf());
-    let errors: Arc<Mutex<Vec<ChuckError>>> = Arc::new(Mutex::new(Vec::new()));
-
-    // Extract files in parallel
-    files_to_extract.par_iter().for_each(|file_info| {
-        let result = (|| -> Result<()> {
-            if file_info.is_dir {
-                std::fs::create_dir_all(&file_info.path).map_err(|e| ChuckError::DirectoryCreate {
-                    path: file_info.path.clone(),
-                    source: e,
-                })?;
-            } else {
-                // Create parent directories if needed
-                if let Some(p) = file_info.path.parent() {
-                    std::fs::create_dir_all(p).map_err(|e| ChuckError::DirectoryCreate {
-                        path: p.to_path_buf(),
-                        source: e,
-                    })?;
-                }
 
-                // Open a new archive instance for this thread
-                let file = std::fs::File::open(&*archive_path).map_err(|e| ChuckError::FileOpen {
-                    path: archive_path.as_ref().clone(),
-                    source: e,
-                })?;
+    // Create directories up front so workers only deal with files
+    let (dirs, files): (Vec<ZipFileInfo>, Vec<ZipFileInfo>) = files_to_extract
+        .into_iter()
+        .partition(|file_info| file_info.is_dir);
+    for dir in &dirs {
+        std::fs::create_dir_all(&dir.path).map_err(|e| ChuckError::DirectoryCreate {
+            path: dir.path.clone(),
+            source: e,
+        })?;
+    }
 
-                let mut archive = zip::ZipArchive::new(file).map_err(ChuckError::ArchiveExtraction)?;
-                let zip_file = archive.by_index(file_info.index).map_err(ChuckError::ArchiveExtraction)?;
+    let total_bytes: u64 = files.iter().map(|file_info| file_info.size).sum();
+    let extracted_bytes = AtomicU64::new(0);
+    let last_reported = AtomicU64::new(0);
+    let add_progress = |bytes: u64| {
+        let extracted = extracted_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        let last = last_reported.load(Ordering::Relaxed);
+        if extracted.saturating_sub(last) >= EXTRACT_PROGRESS_INTERVAL_BYTES
+            && last_reported
+                .compare_exchange(last, extracted, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            on_progress(extracted, total_bytes);
+        }
+    };
 
-                let outfile = std::fs::File::create(&file_info.path).map_err(|e| ChuckError::FileOpen {
-                    path: file_info.path.clone(),
-                    source: e,
-                })?;
+    let batches = partition_by_size(files, rayon::current_num_threads());
+    batches.par_iter().try_for_each(|batch| -> Result<()> {
+        let file = std::fs::File::open(archive_path).map_err(|e| ChuckError::FileOpen {
+            path: archive_path.to_path_buf(),
+            source: e,
+        })?;
+        let mut archive = zip::ZipArchive::new(file).map_err(ChuckError::ArchiveExtraction)?;
 
-                // Use buffered I/O with 64KB buffers for better performance
-                let mut reader = BufReader::with_capacity(64 * 1024, zip_file);
-                let mut writer = BufWriter::with_capacity(64 * 1024, outfile);
-                std::io::copy(&mut reader, &mut writer).map_err(|e| ChuckError::FileRead {
-                    path: file_info.path.clone(),
+        for file_info in batch {
+            // Create parent directories if needed
+            if let Some(p) = file_info.path.parent() {
+                std::fs::create_dir_all(p).map_err(|e| ChuckError::DirectoryCreate {
+                    path: p.to_path_buf(),
                     source: e,
                 })?;
+            }
 
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    if let Some(mode) = file_info.unix_mode {
-                        std::fs::set_permissions(&file_info.path, std::fs::Permissions::from_mode(mode))
-                            .ok();
-                    }
+            let mut zip_file = archive.by_index(file_info.index).map_err(ChuckError::ArchiveExtraction)?;
+            let outfile = std::fs::File::create(&file_info.path).map_err(|e| ChuckError::FileOpen {
+                path: file_info.path.clone(),
+                source: e,
+            })?;
+            let mut writer = BufWriter::with_capacity(EXTRACT_BUFFER_BYTES, outfile);
+            let mut buffer = vec![0; EXTRACT_BUFFER_BYTES];
+            let copy_error = |e| ChuckError::FileRead {
+                path: file_info.path.clone(),
+                source: e,
+            };
+            loop {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(ChuckError::Cancelled);
+                }
+                let read = zip_file.read(&mut buffer).map_err(copy_error)?;
+                if read == 0 {
+                    break;
+                }
+                writer.write_all(&buffer[..read]).map_err(copy_error)?;
+                add_progress(read as u64);
+            }
+            writer.flush().map_err(copy_error)?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Some(mode) = file_info.unix_mode {
+                    std::fs::set_permissions(&file_info.path, std::fs::Permissions::from_mode(mode))
+                        .ok();
                 }
             }
-            Ok(())
-        })();
-
-        if let Err(e) = result {
-            errors.lock().unwrap().push(e);
         }
-    });
-
-    // Check for errors
-    let errors = Arc::try_unwrap(errors).unwrap().into_inner().unwrap();
-    if let Some(err) = errors.into_iter().next() {
-        return Err(err);
-    }
+        Ok(())
+    })?;
 
+    on_progress(extracted_bytes.load(Ordering::Relaxed), total_bytes);
     Ok(())
 }
 
+/// Splits entries into at most `workers` batches with roughly equal total
+/// sizes, assigning the largest entries first so a single huge entry gets a
+/// batch to itself
+fn partition_by_size(mut files: Vec<ZipFileInfo>, workers: usize) -> Vec<Vec<ZipFileInfo>> {
+    let batch_count = workers.max(1).min(files.len());
+    let mut batches: Vec<(u64, Vec<ZipFileInfo>)> = (0..batch_count).map(|_| (0, Vec::new())).collect();
+    files.sort_by_key(|file_info| std::cmp::Reverse(file_info.size));
+    for file_info in files {
+        let (batch_size, batch) = batches
+            .iter_mut()
+            .min_by_key(|(batch_size, _)| *batch_size)
+            .expect("at least one batch when there are files");
+        *batch_size += file_info.size;
+        batch.push(file_info);
+    }
+    batches.into_iter().map(|(_, batch)| batch).collect()
+}
+
 /// Extracts a single file from the archive to a target path
 /// Returns the size of the extracted file in bytes
 fn extract_single_file(
@@ -703,6 +1948,7 @@ fn get_files_to_extract(archive_path: &Path, target_dir: &Path) -> Result<Vec<Zi
                 index: i,
                 path: outpath,
                 is_dir: file.is_dir(),
+                size: file.size(),
                 #[cfg(unix)]
                 unix_mode: file.unix_mode(),
             })
@@ -719,9 +1965,15 @@ fn get_needed_files_from_meta(storage_dir: &Path) -> Result<std::collections::Ha
         path: meta_path.clone(),
         source: e,
     })?;
-
-    let doc = roxmltree::Document::parse(&contents).map_err(|e| ChuckError::XmlParse {
-        path: meta_path,
+    needed_files_in_meta(&contents, &meta_path)
+}
+
+/// Paths of the data files Chuck imports from an archive with this
+/// meta.xml: the core files and those of supported extensions. `meta_path`
+/// is only used in errors.
+pub(super) fn needed_files_in_meta(contents: &str, meta_path: &Path) -> Result<HashSet<String>> {
+    let doc = roxmltree::Document::parse(contents).map_err(|e| ChuckError::XmlParse {
+        path: meta_path.to_path_buf(),
         source: e,
     })?;
 
@@ -832,14 +2084,91 @@ pub(crate) fn parse_meta_xml(storage_dir: &Path) -> Result<MetaXmlInfo> {
         return Err(ChuckError::NoCoreFiles);
     }
 
+    let mut warnings = Vec::new();
+
+    let core_row_type = core_node.attribute("rowType");
+    let core_type = match core_row_type.map(CoreType::from_row_type) {
+        Some(Some(core_type)) => core_type,
+        Some(None) => {
+            let row_type = core_row_type.unwrap_or_default();
+            warnings.push(ImportWarning::new(
+                ImportWarningKind::UnsupportedCoreType,
+                row_type,
+                format!("Core rowType {row_type} isn't supported, so records were treated as occurrences"),
+            ));
+            CoreType::Occurrence
+        }
+        None => CoreType::Occurrence,
+    };
+
     let core_id_column = parse_core_id_column(core_node, "id");
 
     let core_id_column = core_id_column.unwrap_or_else(|| {
-        log::warn!("Could not determine core ID column from meta.xml, defaulting to 'occurrenceID'");
-        "occurrenceID".to_string()
+        let default_id_column = core_type.default_id_column();
+        log::warn!("Could not determine core ID column from meta.xml, defaulting to '{default_id_column}'");
+        warnings.push(ImportWarning::new(
+            ImportWarningKind::MissingCoreId,
+            default_id_column,
+            format!("meta.xml doesn't say which column identifies records, so {default_id_column} was assumed"),
+        ));
+        default_id_column.to_string()
     });
 
-    let core_delimiter = parse_delimiter(core_node.attribute("fieldsTerminatedBy"));
+    let core_format = CsvFormat::from_node(core_node);
+
+    // Note data files in encodings DuckDB can't read, which get read as UTF-8
+    let encoding_nodes = std::iter::once(core_node)
+        .chain(doc.descendants().filter(|n| n.has_tag_name("extension")));
+    for node in encoding_nodes {
+        let Some(encoding) = node.attribute("encoding") else { continue };
+        if is_supported_encoding(encoding) {
+            continue;
+        }
+        let location = node
+            .descendants()
+            .find(|n| n.has_tag_name("location"))
+            .and_then(|n| n.text())
+            .unwrap_or_default();
+        warnings.push(ImportWarning::new(
+            ImportWarningKind::EncodingFallback,
+            location,
+            format!("{location} declares {encoding} encoding but was read as UTF-8; some characters may be garbled"),
+        ));
+    }
+
+    // Occurrences in an Event core archive get joined to their events below
+    let occurrence_ext_node = (core_type == CoreType::Event)
+        .then(|| {
+            doc.descendants()
+                .filter(|n| n.has_tag_name("extension"))
+                .find(|n| {
+                    n.attribute("rowType").and_then(CoreType::from_row_type)
+                        == Some(CoreType::Occurrence)
+                })
+        })
+        .flatten();
+
+    // Note extensions we don't support so it's clear why their data is missing
+    for ext_node in doc.descendants().filter(|n| n.has_tag_name("extension")) {
+        let Some(row_type) = ext_node.attribute("rowType") else { continue };
+        if Some(ext_node) == occurrence_ext_node {
+            continue;
+        }
+        // Verbatim copies of the core, like verbatim.txt in GBIF downloads,
+        // don't hold anything the core is missing
+        if core_type == CoreType::Occurrence
+            && CoreType::from_row_type(row_type) == Some(CoreType::Occurrence)
+        {
+            continue;
+        }
+        if chuck_core::DwcaExtension::from_row_type(row_type).is_none() {
+            warnings.push(ImportWarning::new(
+                ImportWarningKind::UnsupportedExtension,
+                row_type,
+                format!("Extension {row_type} isn't supported and was not imported"),
+            ));
+        }
+    }
 
     // Parse extensions (only supported types)
     let extensions: Vec<ExtensionInfo> = doc
@@ -863,22 +2192,8 @@ pub(crate) fn parse_meta_xml(storage_dir: &Path) -> Result<MetaXmlInfo> {
             let ext_core_id_column = parse_core_id_column(ext_node, "coreid")
                 .ok_or_else(|| ChuckError::NoExtensionCoreId(row_type.to_string()));
 
-            let delimiter = parse_delimiter(ext_node.attribute("fieldsTerminatedBy"));
-
             // Extract field declarations: (index, term_name) for each <field>
-            let fields: Vec<(usize, String)> = ext_node
-                .descendants()
-                .filter(|n| n.has_tag_name("field"))
-                .filter_map(|field_node| {
-                    let index = field_node.attribute("index")?
-                        .parse::<usize>().ok()?;
-                    let term = field_node.attribute("term")?;
-                    let term_name = term.rsplit('/')
-                        .next()
-                        .or_else(|| term.rsplit('#').next())?;
-                    Some((index, term_name.to_string()))
-                })
-                .collect();
+            let fields = parse_fields(ext_node);
 
             Some(ExtensionInfo {
                 row_type: row_type.to_string(),
@@ -886,12 +2201,67 @@ pub(crate) fn parse_meta_xml(storage_dir: &Path) -> Result<MetaXmlInfo> {
                 extension,
                 core_id_column: ext_core_id_column.unwrap(),
                 fields,
-                delimiter,
+                format: CsvFormat::from_node(ext_node),
+                core_id_index: ext_node
+                    .descendants()
+                    .find(|n| n.has_tag_name("coreid"))
+                    .and_then(|n| n.attribute("index"))
+                    .and_then(|index| index.parse().ok()),
+                defaults: parse_field_defaults(ext_node),
             })
         })
         .collect();
 
-    Ok(MetaXmlInfo { core_files, core_id_column, core_delimiter, extensions })
+    let core_defaults = parse_field_defaults(core_node);
+
+    let occurrence_ext_location = occurrence_ext_node.and_then(|node| {
+        node.descendants()
+            .find(|n| n.has_tag_name("location"))
+            .and_then(|n| n.text())
+    });
+    if let (Some(ext_node), Some(location)) = (occurrence_ext_node, occurrence_ext_location) {
+        let occurrence_event_id_column = parse_core_id_column(ext_node, "coreid")
+            .unwrap_or_else(|| core_id_column.clone());
+        let has_occurrence_id = ext_node
+            .descendants()
+            .filter(|n| n.has_tag_name("field"))
+            .filter_map(|n| n.attribute("term"))
+            .any(|term| term_name(term) == Some("occurrenceID"));
+        // Without occurrenceIDs, occurrences can only be told apart by event
+        let occurrence_id_column = if has_occurrence_id {
+            "occurrenceID".to_string()
+        } else {
+            occurrence_event_id_column.clone()
+        };
+        return Ok(MetaXmlInfo {
+            core_type,
+            core_files: vec![storage_dir.join(location)],
+            core_id_column: occurrence_id_column,
+            core_format: CsvFormat::from_node(ext_node),
+            core_fields: parse_fields(ext_node),
+            core_defaults: parse_field_defaults(ext_node),
+            extensions,
+            events: Some(EventCoreInfo {
+                files: core_files,
+                id_column: core_id_column,
+                occurrence_event_id_column,
+                format: core_format,
+            }),
+            warnings,
+        });
+    }
+
+    Ok(MetaXmlInfo {
+        core_type,
+        core_files,
+        core_id_column,
+        core_format,
+        core_fields: parse_fields(core_node),
+        core_defaults,
+        extensions,
+        events: None,
+        warnings,
+    })
 }
 
 #[cfg(test)]
@@ -899,6 +2269,13 @@ mod tests {
     use super::*;
     use std::io::Write;
 
+    #[test]
+    fn test_term_name() {
+        assert_eq!(term_name("http://rs.tdwg.org/dwc/terms/basisOfRecord"), Some("basisOfRecord"));
+        assert_eq!(term_name("http://www.w3.org/2003/01/geo/wgs84_pos#lat"), Some("lat"));
+        assert_eq!(term_name("basisOfRecord"), Some("basisOfRecord"));
+    }
+
     struct UnzippedArchiveFixture {
         _temp: tempfile::TempDir,
         storage_dir: PathBuf,
@@ -1188,6 +2565,194 @@ mod tests {
 
         // Verify Occurrence extension was not included
         assert!(!meta.extensions.iter().any(|ext| ext.row_type == "http://rs.tdwg.org/dwc/terms/Occurrence"));
+
+        // ...but was noted as a warning
+        assert_eq!(meta.warnings.len(), 1);
+        assert_eq!(meta.warnings[0].kind, ImportWarningKind::UnsupportedExtension);
+        assert_eq!(meta.warnings[0].subject, "http://rs.tdwg.org/dwc/terms/Occurrence");
+    }
+
+    #[test]
+    fn test_parse_meta_xml_warns_about_unsupported_encoding() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive>
+  <core encoding="Shift_JIS" rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files>
+      <location>occurrence.txt</location>
+    </files>
+    <id index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+  </core>
+</archive>"#;
+        let fixture = UnzippedArchiveFixture::new(meta_xml);
+
+        let meta = parse_meta_xml(fixture.dir()).unwrap();
+
+        assert_eq!(meta.warnings.len(), 1);
+        assert_eq!(meta.warnings[0].kind, ImportWarningKind::EncodingFallback);
+        assert_eq!(meta.warnings[0].subject, "occurrence.txt");
+    }
+
+    #[test]
+    fn test_parse_meta_xml_reads_csv_format() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive>
+  <core encoding="ISO-8859-1" fieldsTerminatedBy="\t" linesTerminatedBy="\r\n" fieldsEnclosedBy="" ignoreHeaderLines="1" rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files>
+      <location>occurrence.txt</location>
+    </files>
+    <id index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+  </core>
+  <extension fieldsEnclosedBy='"' ignoreHeaderLines="0" rowType="http://rs.gbif.org/terms/1.0/Multimedia">
+    <files>
+      <location>multimedia.txt</location>
+    </files>
+    <coreid index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+  </extension>
+</archive>"#;
+        let fixture = UnzippedArchiveFixture::new(meta_xml);
+
+        let meta = parse_meta_xml(fixture.dir()).unwrap();
+
+        // ISO-8859-1 can be read, so there's nothing to warn about
+        assert!(meta.warnings.is_empty());
+        assert_eq!(meta.core_format, CsvFormat {
+            delimiter: Some('\t'),
+            quote: Some(String::new()),
+            line_terminator: Some(r"\r\n"),
+            header_lines: Some(1),
+            column_names: vec![],
+            encoding: Some("latin-1"),
+        });
+        assert_eq!(
+            meta.core_format.read_csv_options(),
+            ", delim = '\t', quote = '', new_line = '\\r\\n', header = true, skip = 0, encoding = 'latin-1'"
+        );
+        let ext_format = &meta.extensions[0].format;
+        assert_eq!(ext_format.delimiter(), ',');
+        assert_eq!(
+            ext_format.read_csv_options(),
+            ", quote = '\"', header = false, names = ['occurrenceID']"
+        );
+    }
+
+    #[test]
+    fn test_parse_meta_xml_names_headerless_columns() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive>
+  <core ignoreHeaderLines="0" rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files>
+      <location>occurrence.txt</location>
+    </files>
+    <id index="0" />
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+    <field index="3" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+    <field term="http://rs.tdwg.org/dwc/terms/basisOfRecord" default="PreservedSpecimen"/>
+  </core>
+</archive>"#;
+        let fixture = UnzippedArchiveFixture::new(meta_xml);
+
+        let meta = parse_meta_xml(fixture.dir()).unwrap();
+
+        assert_eq!(meta.core_id_column, "id");
+        assert_eq!(
+            meta.core_format.column_names,
+            vec!["id", "scientificName", "column2", "eventDate"]
+        );
+        assert_eq!(meta.core_defaults.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_meta_xml_detects_taxon_core() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive>
+  <core rowType="http://rs.tdwg.org/dwc/terms/Taxon">
+    <files>
+      <location>taxon.txt</location>
+    </files>
+    <id index="0" />
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+  </core>
+</archive>"#;
+        let fixture = UnzippedArchiveFixture::new(meta_xml);
+
+        let meta = parse_meta_xml(fixture.dir()).unwrap();
+
+        assert_eq!(meta.core_type, CoreType::Taxon);
+        assert_eq!(meta.core_id_column, "taxonID");
+        assert!(meta.events.is_none());
+    }
+
+    #[test]
+    fn test_parse_meta_xml_joins_event_core_to_occurrences() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive>
+  <core rowType="http://rs.tdwg.org/dwc/terms/Event">
+    <files>
+      <location>event.txt</location>
+    </files>
+    <id index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/eventID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+  </core>
+  <extension rowType="http://rs.tdwg.org/dwc/terms/Occurrence" fieldsTerminatedBy="\t">
+    <files>
+      <location>occurrence.txt</location>
+    </files>
+    <coreid index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/eventID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+  </extension>
+</archive>"#;
+        let fixture = UnzippedArchiveFixture::new(meta_xml);
+
+        let meta = parse_meta_xml(fixture.dir()).unwrap();
+
+        assert_eq!(meta.core_type, CoreType::Event);
+        assert_eq!(meta.core_files, vec![fixture.dir().join("occurrence.txt")]);
+        assert_eq!(meta.core_id_column, "occurrenceID");
+        assert_eq!(meta.core_format.delimiter(), '\t');
+        let events = meta.events.unwrap();
+        assert_eq!(events.files, vec![fixture.dir().join("event.txt")]);
+        assert_eq!(events.id_column, "eventID");
+        assert_eq!(events.occurrence_event_id_column, "eventID");
+        // The Occurrence extension is used, so it isn't reported as unsupported
+        assert!(meta.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_meta_xml_parses_field_defaults() {
+        let meta_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archive>
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files>
+      <location>occurrence.txt</location>
+    </files>
+    <id index="0" />
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/countryCode" default="US"/>
+    <field term="http://rs.tdwg.org/dwc/terms/basisOfRecord" default="PreservedSpecimen"/>
+  </core>
+</archive>"#;
+        let fixture = UnzippedArchiveFixture::new(meta_xml);
+
+        let meta = parse_meta_xml(fixture.dir()).unwrap();
+
+        assert_eq!(meta.core_defaults, vec![
+            FieldDefault {
+                index: Some(1),
+                term_name: "countryCode".to_string(),
+                value: "US".to_string(),
+            },
+            FieldDefault {
+                index: None,
+                term_name: "basisOfRecord".to_string(),
+                value: "PreservedSpecimen".to_string(),
+            },
+        ]);
     }
 
     #[test]
@@ -1227,31 +2792,60 @@ mod tests {
     }
 
     #[test]
-    fn test_opening_new_archive_removes_other_archive_directories() {
+    fn test_opening_new_archive_keeps_recent_archive_directories() {
+        let fixtures: Vec<_> = (0..=MAX_OPEN_ARCHIVES)
+            .map(|_| ZippedArchiveFixture::new(None))
+            .collect();
+        let base_dir = fixtures[0].base_dir();
+
+        let mut storage_dirs = Vec::new();
+        for fixture in &fixtures {
+            let archive = Archive::open(fixture.archive_path(), base_dir, |_| {}).unwrap();
+            storage_dirs.push(archive.storage_dir.clone());
+            // Drop each archive before opening the next - on Windows, files
+            // are locked while open
+            drop(archive);
+        }
+
+        // Opening one more than the limit removes only the oldest
+        assert!(
+            !storage_dirs[0].exists(),
+            "Oldest archive directory should be removed"
+        );
+        for storage_dir in &storage_dirs[1..] {
+            assert!(storage_dir.exists(), "Recent archive directories should be kept");
+        }
+    }
+
+    #[test]
+    fn test_list_and_find_open_archives() {
         let fixture1 = ZippedArchiveFixture::new(None);
         let fixture2 = ZippedArchiveFixture::new(None);
+        let base_dir = fixture1.base_dir();
 
-        // Open first archive
-        let archive1 = Archive::open(fixture1.archive_path(), fixture1.base_dir(), |_| {}).unwrap();
-        let storage_dir1 = archive1.storage_dir.clone();
-        assert!(storage_dir1.exists());
-
-        // Drop first archive before opening second - on Windows, files are locked while open
+        let archive1 = Archive::open(fixture1.archive_path(), base_dir, |_| {}).unwrap();
+        let id1 = archive1.id();
         drop(archive1);
-
-        // Open second archive using the same base directory
-        let archive2 = Archive::open(fixture2.archive_path(), fixture1.base_dir(), |_| {}).unwrap();
-        let storage_dir2 = archive2.storage_dir.clone();
-
-        // After opening the second archive, the first archive's directory should be removed
-        assert!(
-            !storage_dir1.exists(),
-            "First archive directory should be removed after opening second archive"
-        );
-        assert!(
-            storage_dir2.exists(),
-            "Second archive directory should exist"
-        );
+        let archive2 = Archive::open(fixture2.archive_path(), base_dir, |_| {}).unwrap();
+        let id2 = archive2.id();
+        drop(archive2);
+        assert_ne!(id1, id2);
+
+        let ids: Vec<String> = Archive::list(base_dir).unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(ids, vec![id2.clone(), id1.clone()]);
+
+        // The current archive is the most recently opened
+        assert_eq!(Archive::current(base_dir).unwrap().id(), id2);
+        assert_eq!(Archive::find(base_dir, None).unwrap().id(), id2);
+        assert_eq!(Archive::find(base_dir, Some(&id1)).unwrap().id(), id1);
+        assert!(Archive::by_id(base_dir, "missing").is_err());
+        assert!(matches!(
+            Archive::by_id(base_dir, "../archives"),
+            Err(ChuckError::InvalidFileName(_))
+        ));
+
+        Archive::by_id(base_dir, &id2).unwrap().close().unwrap();
+        assert_eq!(Archive::current(base_dir).unwrap().id(), id1);
     }
 
     #[test]
@@ -1272,6 +2866,72 @@ mod tests {
         assert!(storage_dir.starts_with(&base_dir));
     }
 
+    #[test]
+    fn test_partition_by_size_balances_batches() {
+        let files: Vec<ZipFileInfo> = [100, 60, 50, 10, 10, 5]
+            .iter()
+            .enumerate()
+            .map(|(index, &size)| ZipFileInfo {
+                index,
+                path: PathBuf::from(format!("{index}.jpg")),
+                is_dir: false,
+                size,
+                #[cfg(unix)]
+                unix_mode: None,
+            })
+            .collect();
+
+        let batches = partition_by_size(files, 2);
+
+        let sizes: Vec<u64> = batches
+            .iter()
+            .map(|batch| batch.iter().map(|f| f.size).sum())
+            .collect();
+        assert_eq!(sizes, vec![120, 115]);
+        // The largest entry gets a batch with no other large entries
+        assert_eq!(batches[0][0].size, 100);
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), 6);
+
+        // Never more batches than entries
+        assert_eq!(partition_by_size(vec![], 4).len(), 0);
+    }
+
+    #[test]
+    fn test_cancelled_open_removes_storage_dir() {
+        let fixture = ZippedArchiveFixture::new(None);
+        std::fs::create_dir_all(fixture.base_dir()).unwrap();
+
+        let result = Archive::open_with_extract_progress(
+            fixture.archive_path(),
+            fixture.base_dir(),
+            |_| {},
+            &|_, _| {},
+            &|_| {},
+            &CoreIdStrategy::default(),
+            true,
+            &AtomicBool::new(true),
+        );
+
+        assert!(matches!(result, Err(ChuckError::Cancelled)));
+        assert_eq!(std::fs::read_dir(fixture.base_dir()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_extract_archive_reports_progress() {
+        let fixture = ZippedArchiveFixture::new(None);
+        let target = tempfile::tempdir().unwrap();
+        let progress = std::sync::Mutex::new(Vec::new());
+
+        extract_archive(fixture.archive_path(), target.path(), &|done, total| {
+            progress.lock().unwrap().push((done, total));
+        }, &AtomicBool::new(false)).unwrap();
+
+        assert!(target.path().join("occurrence.csv").exists());
+        let progress = progress.into_inner().unwrap();
+        let csv_size = "id,name\n1,test\n".len() as u64;
+        assert_eq!(progress.last(), Some(&(csv_size, csv_size)));
+    }
+
     #[test]
     fn test_current_with_existing_archive() {
         let fixture = UnzippedArchiveFixture::with_structure(
@@ -1479,6 +3139,95 @@ mod tests {
         assert!(info.available_columns.contains(&"eventDate".to_string()));
     }
 
+    #[test]
+    fn test_archive_info_includes_import_warnings() {
+        let meta_xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+  </core>
+  <extension rowType="http://rs.gbif.org/terms/1.0/Multimedia">
+    <files><location>multimedia.csv</location></files>
+    <coreid index="0"/>
+  </extension>
+</archive>"#;
+
+        let csv_content = b"id,scientificName,habitat\n\
+                            1,Test species,\n";
+
+        let files = &[
+            ("meta.xml", &meta_xml[..]),
+            ("occurrence.csv", &csv_content[..]),
+        ];
+
+        let fixture = ZippedArchiveFixture::new(Some(files));
+        let archive = Archive::open(fixture.archive_path(), fixture.base_dir(), |_| {}).unwrap();
+
+        let info = archive.info().unwrap();
+        let kinds: Vec<_> = info.import_warnings.iter().map(|w| (&w.kind, w.subject.as_str())).collect();
+        assert!(kinds.contains(&(&ImportWarningKind::DroppedEmptyColumn, "habitat")));
+        assert!(kinds.contains(&(&ImportWarningKind::SkippedExtensionFile, "multimedia.csv")));
+
+        // Warnings are still available after reopening
+        drop(archive);
+        let reopened = Archive::current(fixture.base_dir()).unwrap();
+        assert_eq!(reopened.import_warnings(), info.import_warnings);
+    }
+
+    #[test]
+    fn test_update_eml_keeps_unedited_elements() {
+        let fixture = ZippedArchiveFixture::new(None);
+        let archive = Archive::open(fixture.archive_path(), fixture.base_dir(), |_| {}).unwrap();
+        std::fs::write(
+            archive.storage_dir.join("eml.xml"),
+            r#"<eml:eml xmlns:eml="eml://ecoinformatics.org/eml-2.1.1" packageId="abc">
+  <dataset>
+    <title>Old title</title>
+    <methods><methodStep><description><para>Walked around</para></description></methodStep></methods>
+  </dataset>
+</eml:eml>"#,
+        ).unwrap();
+        let mut edits = archive.eml().unwrap();
+        edits.title = "New title".to_string();
+        assert!(matches!(archive.update_eml(edits.clone()), Err(ChuckError::ReadOnly)));
+
+        archive.set_read_only(false).unwrap();
+        archive.update_eml(edits).unwrap();
+
+        assert_eq!(archive.eml().unwrap().title, "New title");
+        let xml = std::fs::read_to_string(archive.storage_dir.join("eml.xml")).unwrap();
+        assert!(xml.contains(r#"packageId="abc""#));
+        assert!(xml.contains("<para>Walked around</para>"));
+    }
+
+    #[test]
+    fn test_archive_is_read_only_until_unlocked() {
+        let fixture = ZippedArchiveFixture::new(None);
+        let archive = Archive::open(fixture.archive_path(), fixture.base_dir(), |_| {}).unwrap();
+        assert!(archive.is_read_only());
+        assert!(archive.info().unwrap().read_only);
+        let result = archive.with_writable_db(|_conn| Ok(()));
+        assert!(matches!(result, Err(ChuckError::ReadOnly)));
+
+        let archive = Archive::current(fixture.base_dir()).unwrap();
+        archive.set_read_only(false).unwrap();
+        drop(archive);
+        // Unlocking lasts across reopening
+        let archive = Archive::current(fixture.base_dir()).unwrap();
+        assert!(!archive.is_read_only());
+        archive.with_writable_db(|conn| {
+            conn.execute("ALTER TABLE occurrences ADD COLUMN note VARCHAR", [])?;
+            Ok(())
+        }).unwrap();
+
+        let archive = Archive::current(fixture.base_dir()).unwrap();
+        archive.set_read_only(true).unwrap();
+        // Locking an already locked archive is fine
+        archive.set_read_only(true).unwrap();
+        assert!(archive.is_read_only());
+    }
+
     #[test]
     fn test_query_tile_returns_occurrences_with_text_core_id() {
         let meta_xml = br#"<?xml version="1.0" encoding="UTF-8"?>
@@ -1519,21 +3268,128 @@ obs789,34.0522,-118.2437,Pinus coulteri
             38.0,   // north
             10,     // high zoom - no sampling
             SearchParams::default(),
+            None,
+            ObscuredMode::Ignore,
         ).unwrap();
 
         // Should return 2 points (obs123 and obs456) in the SF Bay Area
         assert_eq!(results.len(), 2);
 
-        // Verify the results have the expected structure (core_id, lat, lng, name)
-        let core_ids: Vec<_> = results.iter().map(|(id, _, _, _)| id.clone()).collect();
+        let core_ids: Vec<_> = results.iter().map(|point| point.core_id.clone()).collect();
         assert!(core_ids.contains(&"obs123".to_string()));
         assert!(core_ids.contains(&"obs456".to_string()));
 
         // Verify coordinates are preserved
-        let first_point = results.iter().find(|(id, _, _, _)| id == "obs123").unwrap();
-        assert_eq!(first_point.1, 37.7749); // latitude
-        assert_eq!(first_point.2, -122.4194); // longitude
-        assert_eq!(first_point.3, Some("Quercus agrifolia".to_string())); // scientific name
+        let first_point = results.iter().find(|point| point.core_id == "obs123").unwrap();
+        assert_eq!(first_point.latitude, 37.7749);
+        assert_eq!(first_point.longitude, -122.4194);
+        assert_eq!(first_point.scientific_name, Some("Quercus agrifolia".to_string()));
+        assert_eq!(first_point.attribute, None);
+
+        // Values of a requested attribute come along for styling
+        let results = archive.query_tile(
+            -123.0, 37.0, -122.0, 38.0, 10, SearchParams::default(), Some("scientificName"),
+            ObscuredMode::Ignore,
+        ).unwrap();
+        let first_point = results.iter().find(|point| point.core_id == "obs123").unwrap();
+        assert_eq!(first_point.attribute, Some("Quercus agrifolia".to_string()));
+
+        let result = archive.query_tile(
+            -123.0, 37.0, -122.0, 38.0, 10, SearchParams::default(), Some("nope\"; DROP TABLE occurrences; --"),
+            ObscuredMode::Ignore,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_query_tile_marks_obscured_occurrences() {
+        let meta_xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/decimalLatitude"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/decimalLongitude"/>
+    <field index="3" term="http://rs.tdwg.org/dwc/terms/informationWithheld"/>
+  </core>
+</archive>"#;
+        let csv_content = b"occurrenceID,decimalLatitude,decimalLongitude,informationWithheld
+obs1,37.71,-122.45,Coordinates obscured by the observer
+obs2,37.72,-122.46,Coordinates obscured by the observer but included here with the observer's permission
+obs3,37.73,-122.47,
+";
+        let fixture = UnzippedArchiveFixture::with_structure(
+            "test.zip",
+            &[("meta.xml", meta_xml), ("occurrence.csv", csv_content)],
+            true,
+        );
+        let archive = Archive::current(fixture.base_dir()).unwrap();
+        let query = |mode| {
+            let mut points = archive
+                .query_tile(-123.0, 37.0, -122.0, 38.0, 10, SearchParams::default(), None, mode)
+                .unwrap();
+            points.sort_by(|a, b| a.core_id.cmp(&b.core_id));
+            points
+        };
+
+        let ignored = query(ObscuredMode::Ignore);
+        assert!(ignored.iter().all(|point| !point.obscured && point.cell.is_none()));
+
+        let marked = query(ObscuredMode::Mark);
+        let obscured: Vec<bool> = marked.iter().map(|point| point.obscured).collect();
+        assert_eq!(obscured, vec![true, false, false]);
+        assert!(marked[0].cell.is_none());
+
+        let cells = query(ObscuredMode::Cell);
+        let cell = cells[0].cell.as_ref().unwrap();
+        assert!(cell.south <= 37.71 && cell.north >= 37.71);
+        assert!(cells[1].cell.is_none());
+    }
+
+    #[test]
+    fn test_occurrences_at_point_returns_nearest_first() {
+        let meta_xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<archive xmlns="http://rs.tdwg.org/dwc/text/">
+  <core rowType="http://rs.tdwg.org/dwc/terms/Occurrence">
+    <files><location>occurrence.csv</location></files>
+    <id index="0"/>
+    <field index="0" term="http://rs.tdwg.org/dwc/terms/occurrenceID"/>
+    <field index="1" term="http://rs.tdwg.org/dwc/terms/decimalLatitude"/>
+    <field index="2" term="http://rs.tdwg.org/dwc/terms/decimalLongitude"/>
+    <field index="3" term="http://rs.tdwg.org/dwc/terms/scientificName"/>
+    <field index="4" term="http://rs.tdwg.org/dwc/terms/eventDate"/>
+  </core>
+</archive>"#;
+        let csv_content = b"occurrenceID,decimalLatitude,decimalLongitude,scientificName,eventDate
+near,37.7749,-122.4194,Quercus agrifolia,2024-05-01
+nearer,37.77491,-122.41941,Quercus lobata,2024-05-02
+far,37.8044,-122.2712,Sequoia sempervirens,2024-05-03
+";
+        let fixture = UnzippedArchiveFixture::with_structure(
+            "test.zip",
+            &[
+                ("meta.xml", meta_xml),
+                ("occurrence.csv", csv_content),
+            ],
+            true,
+        );
+        let archive = Archive::current(fixture.base_dir()).unwrap();
+
+        // 5 pixels at zoom 12 is about 60 meters
+        let results = archive.occurrences_at_point(
+            37.77491, -122.41941, 12.0, 5.0, SearchParams::default(),
+        ).unwrap();
+
+        let core_ids: Vec<_> = results.iter().map(|point| point.core_id.as_str()).collect();
+        assert_eq!(core_ids, vec!["nearer", "near"]);
+        assert_eq!(results[0].event_date, Some("2024-05-02".to_string()));
+
+        // Zoomed out, the same tolerance covers the far point too
+        let results = archive.occurrences_at_point(
+            37.77491, -122.41941, 4.0, 5.0, SearchParams::default(),
+        ).unwrap();
+        assert_eq!(results.len(), 3);
     }
 
     #[test]
@@ -1636,7 +3492,9 @@ obs789,34.0522,-118.2437,Pinus coulteri
             extension: chuck_core::DwcaExtension::SimpleMultimedia,
             core_id_column: "gbifID".to_string(),
             fields: vec![],
-            delimiter: ',',
+            format: CsvFormat::default(),
+            core_id_index: None,
+            defaults: vec![],
         };
 
         remove_data_files(&[], &[ext]);
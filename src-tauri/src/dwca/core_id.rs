@@ -0,0 +1,271 @@
+//! Generated core IDs for archives whose records can't be told apart by the
+//! column meta.xml names, because the column is missing, has blanks, or has
+//! duplicates. The generated ID goes in its own column, which the
+//! occurrences and extension tables are joined on instead.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::{ChuckError, Result};
+
+/// Column holding generated core IDs, in occurrences and in extension tables
+/// that could be matched to them
+pub const GENERATED_ID_COLUMN: &str = "chuck_core_id";
+
+/// Name of the file in the storage directory recording how core IDs were
+/// generated, if they were
+const CORE_ID_FILENAME: &str = "core_id.json";
+
+/// How to generate core IDs when an archive's own can't be used
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "camelCase")]
+pub enum CoreIdStrategy {
+    /// Each record's row number in the data files, counting from 1
+    #[default]
+    RowNumber,
+    /// An MD5 hash of the values of these columns, so the same record gets
+    /// the same ID when a new version of the archive is imported. Records
+    /// with the same values get a -2, -3, etc. suffix.
+    Hash { columns: Vec<String> },
+}
+
+/// How an archive's core IDs were generated, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedCoreId {
+    #[serde(flatten)]
+    pub strategy: CoreIdStrategy,
+    /// The column meta.xml says identifies records
+    pub original_column: String,
+    pub reason: String,
+}
+
+/// Why the values of `core_id_column` can't identify occurrences, or None
+/// if they can
+pub(crate) fn unusable_reason(conn: &duckdb::Connection, core_id_column: &str) -> Result<Option<String>> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.columns \
+         WHERE table_name = 'occurrences' AND column_name = ?",
+        [core_id_column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(Some(format!("the data has no {core_id_column} column")));
+    }
+    let quoted = Database::quote_identifier(core_id_column);
+    let (blank, duplicates): (usize, usize) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FILTER (WHERE {quoted} IS NULL OR CAST({quoted} AS VARCHAR) = ''), \
+                    COUNT({quoted}) - COUNT(DISTINCT {quoted}) \
+             FROM occurrences"
+        ),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    Ok(if blank > 0 {
+        Some(format!("{blank} records have no {core_id_column}"))
+    } else if duplicates > 0 {
+        Some(format!("{duplicates} records have the same {core_id_column} as another"))
+    } else {
+        None
+    })
+}
+
+/// Adds GENERATED_ID_COLUMN to occurrences, filled in by `strategy`, and
+/// returns the strategy used. Hashes of columns that don't exist fall back
+/// to row numbers. Needs a read-write connection.
+pub(crate) fn generate_core_ids(
+    conn: &duckdb::Connection,
+    strategy: &CoreIdStrategy,
+) -> Result<CoreIdStrategy> {
+    let strategy = match strategy {
+        CoreIdStrategy::Hash { columns } if columns.is_empty() => CoreIdStrategy::RowNumber,
+        CoreIdStrategy::Hash { columns } => {
+            let mut stmt = conn.prepare(
+                "SELECT column_name FROM information_schema.columns WHERE table_name = 'occurrences'",
+            )?;
+            let existing: Vec<String> = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            match columns.iter().find(|column| !existing.contains(column)) {
+                Some(missing) => {
+                    log::warn!("Can't hash core IDs from missing column {missing}, using row numbers");
+                    CoreIdStrategy::RowNumber
+                }
+                None => strategy.clone(),
+            }
+        }
+        CoreIdStrategy::RowNumber => CoreIdStrategy::RowNumber,
+    };
+
+    conn.execute_batch(&format!(
+        "ALTER TABLE occurrences ADD COLUMN {GENERATED_ID_COLUMN} VARCHAR"
+    ))?;
+    let ids = match &strategy {
+        CoreIdStrategy::RowNumber => "SELECT rowid AS row_id, CAST(rowid + 1 AS VARCHAR) AS id \
+                                      FROM occurrences"
+            .to_string(),
+        CoreIdStrategy::Hash { columns } => {
+            let values: Vec<String> = columns
+                .iter()
+                .map(|column| {
+                    format!("COALESCE(CAST({} AS VARCHAR), '')", Database::quote_identifier(column))
+                })
+                .collect();
+            format!(
+                "SELECT row_id, CASE WHEN n = 1 THEN hash ELSE hash || '-' || n END AS id \
+                 FROM ( \
+                     SELECT row_id, hash, row_number() OVER (PARTITION BY hash ORDER BY row_id) AS n \
+                     FROM (SELECT rowid AS row_id, md5(concat_ws(chr(31), {})) AS hash FROM occurrences) \
+                 )",
+                values.join(", ")
+            )
+        }
+    };
+    conn.execute_batch(&format!(
+        "UPDATE occurrences SET {GENERATED_ID_COLUMN} = ids.id \
+         FROM ({ids}) ids WHERE occurrences.rowid = ids.row_id"
+    ))?;
+    Ok(strategy)
+}
+
+/// Adds GENERATED_ID_COLUMN to an extension table, matching its rows to
+/// occurrences by the original core ID. Rows of occurrences that shared an
+/// ID go with the first of them. Returns false if the table can't be matched
+/// because occurrences don't have the original column. Needs a read-write
+/// connection.
+pub(crate) fn link_extension(
+    conn: &duckdb::Connection,
+    table_name: &str,
+    ext_core_id_column: &str,
+    original_column: &str,
+) -> Result<bool> {
+    let has_original: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.columns \
+         WHERE table_name = 'occurrences' AND column_name = ?",
+        [original_column],
+        |row| row.get(0),
+    )?;
+    if !has_original {
+        return Ok(false);
+    }
+    let original = Database::quote_identifier(original_column);
+    let ext_core_id = Database::quote_identifier(ext_core_id_column);
+    conn.execute_batch(&format!(
+        "ALTER TABLE {table_name} ADD COLUMN {GENERATED_ID_COLUMN} VARCHAR; \
+         UPDATE {table_name} SET {GENERATED_ID_COLUMN} = ids.id \
+         FROM ( \
+             SELECT {original} AS original_id, arg_min({GENERATED_ID_COLUMN}, rowid) AS id \
+             FROM occurrences GROUP BY {original} \
+         ) ids \
+         WHERE CAST({table_name}.{ext_core_id} AS VARCHAR) = CAST(ids.original_id AS VARCHAR)"
+    ))?;
+    Ok(true)
+}
+
+/// Records how an archive's core IDs were generated in its storage directory
+pub(crate) fn save_generated_core_id(storage_dir: &Path, generated: &GeneratedCoreId) -> Result<()> {
+    let path = storage_dir.join(CORE_ID_FILENAME);
+    let json = serde_json::to_string_pretty(generated)
+        .map_err(|e| ChuckError::FileWrite { path: path.clone(), source: e.into() })?;
+    std::fs::write(&path, json).map_err(|source| ChuckError::FileWrite { path, source })
+}
+
+/// Reads how an archive's core IDs were generated. Archives that use their
+/// own IDs have no record.
+pub(crate) fn load_generated_core_id(storage_dir: &Path) -> Option<GeneratedCoreId> {
+    let path = storage_dir.join(CORE_ID_FILENAME);
+    let json = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| log::warn!("Failed to parse {}: {e}", path.display()))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences(rows: &str) -> duckdb::Connection {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR); \
+             INSERT INTO occurrences VALUES {rows};"
+        ))
+        .unwrap();
+        conn
+    }
+
+    fn ids(conn: &duckdb::Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(&format!("SELECT {GENERATED_ID_COLUMN} FROM occurrences ORDER BY rowid"))
+            .unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(|id| id.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_unusable_reason() {
+        let unique = occurrences("('a', 'Acer'), ('b', 'Pinus')");
+        let duplicated = occurrences("('a', 'Acer'), ('a', 'Pinus')");
+        let blank = occurrences("('a', 'Acer'), ('', 'Pinus')");
+
+        assert_eq!(unusable_reason(&unique, "occurrenceID").unwrap(), None);
+        assert_eq!(
+            unusable_reason(&duplicated, "occurrenceID").unwrap().as_deref(),
+            Some("1 records have the same occurrenceID as another")
+        );
+        assert_eq!(
+            unusable_reason(&blank, "occurrenceID").unwrap().as_deref(),
+            Some("1 records have no occurrenceID")
+        );
+        assert_eq!(
+            unusable_reason(&unique, "gbifID").unwrap().as_deref(),
+            Some("the data has no gbifID column")
+        );
+    }
+
+    #[test]
+    fn test_generate_core_ids_by_row_number_and_hash() {
+        let conn = occurrences("('a', 'Acer'), ('a', 'Acer'), ('b', 'Pinus')");
+        generate_core_ids(&conn, &CoreIdStrategy::RowNumber).unwrap();
+        assert_eq!(ids(&conn), vec!["1", "2", "3"]);
+
+        let conn = occurrences("('a', 'Acer'), ('a', 'Acer'), ('b', 'Pinus')");
+        let strategy = CoreIdStrategy::Hash {
+            columns: vec!["occurrenceID".to_string(), "scientificName".to_string()],
+        };
+        assert_eq!(generate_core_ids(&conn, &strategy).unwrap(), strategy);
+        let ids = ids(&conn);
+        assert_eq!(ids[1], format!("{}-2", ids[0]));
+        assert_ne!(ids[0], ids[2]);
+    }
+
+    #[test]
+    fn test_generate_core_ids_falls_back_to_row_numbers() {
+        let conn = occurrences("('a', 'Acer')");
+        let strategy = CoreIdStrategy::Hash { columns: vec!["catalogNumber".to_string()] };
+
+        assert_eq!(generate_core_ids(&conn, &strategy).unwrap(), CoreIdStrategy::RowNumber);
+    }
+
+    #[test]
+    fn test_link_extension() {
+        let conn = occurrences("('a', 'Acer'), ('a', 'Acer'), ('b', 'Pinus')");
+        generate_core_ids(&conn, &CoreIdStrategy::RowNumber).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE multimedia (occurrenceID VARCHAR, identifier VARCHAR); \
+             INSERT INTO multimedia VALUES ('a', 'a.jpg'), ('b', 'b.jpg'), ('c', 'c.jpg');",
+        )
+        .unwrap();
+
+        assert!(link_extension(&conn, "multimedia", "occurrenceID", "occurrenceID").unwrap());
+
+        let mut stmt = conn
+            .prepare(&format!("SELECT {GENERATED_ID_COLUMN} FROM multimedia ORDER BY identifier"))
+            .unwrap();
+        let linked: Vec<Option<String>> =
+            stmt.query_map([], |row| row.get(0)).unwrap().map(|id| id.unwrap()).collect();
+        assert_eq!(linked, vec![Some("1".to_string()), Some("3".to_string()), None]);
+    }
+}
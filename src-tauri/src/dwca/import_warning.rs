@@ -0,0 +1,101 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChuckError, Result};
+
+/// Name of the file in the storage directory where warnings from the import
+/// are kept so they can be shown after the archive is reopened
+const IMPORT_WARNINGS_FILENAME: &str = "import_warnings.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportWarningKind {
+    /// A core column had no values and was left out of the database
+    DroppedEmptyColumn,
+    /// An extension declared in meta.xml had no data file
+    SkippedExtensionFile,
+    /// An extension rowType Chuck doesn't know how to display
+    UnsupportedExtension,
+    /// A data file declared an encoding other than UTF-8 but was read as UTF-8
+    EncodingFallback,
+    /// meta.xml didn't say which column identifies core records
+    MissingCoreId,
+    /// The core rowType isn't Occurrence, Event, or Taxon
+    UnsupportedCoreType,
+    /// A numeric column uses values like -9999 to mean "unknown"
+    SentinelValues,
+    /// A core column header was renamed to the Darwin Core term it matches
+    RenamedColumn,
+    /// Rows of a data file were malformed, e.g. had an unescaped quote, and
+    /// were skipped
+    SkippedRows,
+    /// The core ID column was missing or had blanks or duplicates, so
+    /// records were given generated IDs
+    GeneratedCoreId,
+    /// A typed column had values that couldn't be read as its type, so it
+    /// was read as text and parsed where possible
+    RelaxedColumnType,
+}
+
+/// A non-fatal problem encountered while importing an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportWarning {
+    pub kind: ImportWarningKind,
+    /// The column, file, or rowType the warning is about
+    pub subject: String,
+    pub message: String,
+}
+
+impl ImportWarning {
+    pub fn new(kind: ImportWarningKind, subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { kind, subject: subject.into(), message: message.into() }
+    }
+}
+
+/// Writes import warnings to the archive's storage directory
+pub fn save_import_warnings(storage_dir: &Path, warnings: &[ImportWarning]) -> Result<()> {
+    let path = storage_dir.join(IMPORT_WARNINGS_FILENAME);
+    let json = serde_json::to_string_pretty(warnings)
+        .map_err(|e| ChuckError::FileWrite { path: path.clone(), source: e.into() })?;
+    std::fs::write(&path, json).map_err(|source| ChuckError::FileWrite { path, source })
+}
+
+/// Reads import warnings from the archive's storage directory. Archives
+/// imported before warnings were recorded have none.
+pub fn load_import_warnings(storage_dir: &Path) -> Vec<ImportWarning> {
+    let path = storage_dir.join(IMPORT_WARNINGS_FILENAME);
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return vec![];
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        log::warn!("Failed to parse {}: {e}", path.display());
+        vec![]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_warnings_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let warnings = vec![ImportWarning::new(
+            ImportWarningKind::DroppedEmptyColumn,
+            "habitat",
+            "Column habitat has no values and was not imported",
+        )];
+
+        save_import_warnings(temp.path(), &warnings).unwrap();
+
+        assert_eq!(load_import_warnings(temp.path()), warnings);
+    }
+
+    #[test]
+    fn test_load_import_warnings_without_file() {
+        let temp = tempfile::tempdir().unwrap();
+        assert!(load_import_warnings(temp.path()).is_empty());
+    }
+}
@@ -1,4 +1,14 @@
 mod archive;
+pub(crate) mod core_id;
+mod import_warning;
+mod preflight;
+mod source;
 
-pub use archive::{Archive, ExtensionInfo};
+pub use archive::{Archive, CoreType, CsvFormat, EventCoreInfo, ExtensionInfo, FieldDefault};
+pub use core_id::{CoreIdStrategy, GeneratedCoreId, GENERATED_ID_COLUMN};
+pub use import_warning::{ImportWarning, ImportWarningKind};
+pub use source::ArchiveSource;
+pub(crate) use core_id::{load_generated_core_id, save_generated_core_id};
+pub(crate) use import_warning::{load_import_warnings, save_import_warnings};
+pub(crate) use source::{load_source, save_source};
 pub(crate) use archive::{parse_delimiter, parse_meta_xml};
@@ -0,0 +1,117 @@
+use std::io::Read;
+use std::path::Path;
+
+use super::archive::needed_files_in_meta;
+use crate::error::{ChuckError, Result};
+
+/// Disk space an import needs at its peak, when the extracted data files
+/// and the database built from them both exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportEstimate {
+    /// Uncompressed size of the data files that get extracted
+    pub extracted_bytes: u64,
+    /// Size of the database. DuckDB compresses most columns, so this is
+    /// usually an overestimate, which leaves room for it to spill to disk.
+    pub database_bytes: u64,
+}
+
+impl ImportEstimate {
+    pub fn total_bytes(&self) -> u64 {
+        self.extracted_bytes + self.database_bytes
+    }
+}
+
+/// Estimates the disk space importing the archive at `archive_path` will
+/// take from the sizes of the zip entries meta.xml lists, without
+/// extracting anything
+pub fn estimate_import(archive_path: &Path) -> Result<ImportEstimate> {
+    let file = std::fs::File::open(archive_path).map_err(|source| ChuckError::FileOpen {
+        path: archive_path.to_path_buf(),
+        source,
+    })?;
+    let mut zip = zip::ZipArchive::new(file).map_err(ChuckError::ArchiveExtraction)?;
+    let mut meta_xml = String::new();
+    zip.by_name("meta.xml")
+        .map_err(ChuckError::ArchiveExtraction)?
+        .read_to_string(&mut meta_xml)
+        .map_err(|source| ChuckError::FileRead { path: archive_path.to_path_buf(), source })?;
+    let needed_files = needed_files_in_meta(&meta_xml, &archive_path.join("meta.xml"))?;
+
+    let extracted_bytes = (0..zip.len())
+        .filter_map(|i| zip.by_index_raw(i).ok())
+        .filter(|entry| {
+            entry
+                .enclosed_name()
+                .and_then(|name| name.to_str().map(|name| needed_files.contains(name)))
+                .unwrap_or(false)
+        })
+        .map(|entry| entry.size())
+        .sum();
+    Ok(ImportEstimate { extracted_bytes, database_bytes: extracted_bytes })
+}
+
+/// Checks there's room under `base_dir` to import the archive at
+/// `archive_path`, returning `ChuckError::InsufficientDiskSpace` if not.
+/// Free space that can't be determined is assumed to be enough.
+pub(crate) fn check_disk_space(archive_path: &Path, base_dir: &Path) -> Result<()> {
+    let estimate = estimate_import(archive_path)?;
+    // The archives directory doesn't exist until the first import
+    let Some(dir) = base_dir.ancestors().find(|dir| dir.exists()) else {
+        return Ok(());
+    };
+    match fs4::available_space(dir) {
+        Ok(available) => ensure_space(&estimate, available),
+        Err(e) => {
+            log::warn!("Failed to get free space for {}: {e}", dir.display());
+            Ok(())
+        }
+    }
+}
+
+fn ensure_space(estimate: &ImportEstimate, available: u64) -> Result<()> {
+    let needed = estimate.total_bytes();
+    if needed > available {
+        return Err(ChuckError::InsufficientDiskSpace { needed, available });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_estimate_import_counts_listed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let archive_path = temp.path().join("archive.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        let options = zip::write::FileOptions::<()>::default();
+        let meta_xml = r#"<archive><core><files><location>occurrence.csv</location></files></core></archive>"#;
+        for (name, content) in [
+            ("meta.xml", meta_xml),
+            ("occurrence.csv", "id,name\n1,test\n"),
+            ("media/photo.jpg", "not imported"),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let estimate = estimate_import(&archive_path).unwrap();
+
+        assert_eq!(estimate.extracted_bytes, 15);
+        assert_eq!(estimate.total_bytes(), 30);
+    }
+
+    #[test]
+    fn test_ensure_space() {
+        let estimate = ImportEstimate { extracted_bytes: 100, database_bytes: 100 };
+
+        assert!(ensure_space(&estimate, 200).is_ok());
+        assert!(matches!(
+            ensure_space(&estimate, 150),
+            Err(ChuckError::InsufficientDiskSpace { needed: 200, available: 150 })
+        ));
+    }
+}
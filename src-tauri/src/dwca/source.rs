@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChuckError, Result};
+
+/// Name of the file in the storage directory recording which zip the
+/// archive was imported from
+const SOURCE_FILENAME: &str = "source.json";
+
+/// The zip an archive was imported from, as it was at the time, so Chuck
+/// can tell when it's been replaced, e.g. by downloading a new export
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSource {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch, if the
+    /// filesystem records one
+    pub modified: Option<u64>,
+}
+
+impl ArchiveSource {
+    /// The file at `path` as it is now, or None if it can't be read
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as u64);
+        Some(Self {
+            path: std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+            size: metadata.len(),
+            modified,
+        })
+    }
+
+    /// Whether the file has been replaced or modified since it was
+    /// recorded. A file that's been deleted or moved away hasn't changed,
+    /// since there's nothing to reimport.
+    pub fn has_changed(&self) -> bool {
+        Self::of(&self.path).is_some_and(|current| current != *self)
+    }
+}
+
+/// Records the file an archive was imported from in its storage directory
+pub fn save_source(storage_dir: &Path, source: &ArchiveSource) -> Result<()> {
+    let path = storage_dir.join(SOURCE_FILENAME);
+    let json = serde_json::to_string_pretty(source)
+        .map_err(|e| ChuckError::FileWrite { path: path.clone(), source: e.into() })?;
+    std::fs::write(&path, json).map_err(|source| ChuckError::FileWrite { path, source })
+}
+
+/// Reads the file an archive was imported from. Archives imported before
+/// sources were recorded have none.
+pub fn load_source(storage_dir: &Path) -> Option<ArchiveSource> {
+    let path = storage_dir.join(SOURCE_FILENAME);
+    let json = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| log::warn!("Failed to parse {}: {e}", path.display()))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_round_trip_and_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let zip_path = temp.path().join("observations.zip");
+        std::fs::write(&zip_path, "old").unwrap();
+        let source = ArchiveSource::of(&zip_path).unwrap();
+
+        save_source(temp.path(), &source).unwrap();
+
+        let loaded = load_source(temp.path()).unwrap();
+        assert_eq!(loaded, source);
+        assert!(!loaded.has_changed());
+
+        std::fs::write(&zip_path, "newer").unwrap();
+        assert!(loaded.has_changed());
+
+        std::fs::remove_file(&zip_path).unwrap();
+        assert!(!loaded.has_changed());
+    }
+
+    #[test]
+    fn test_load_source_without_record() {
+        let temp = tempfile::tempdir().unwrap();
+
+        assert_eq!(load_source(temp.path()), None);
+    }
+}
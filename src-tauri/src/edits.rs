@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::{ChuckError, Result};
+
+/// Log of changes made to occurrences fields by hand, one row per change.
+/// The latest change to each field is overlaid on the archive's own values
+/// (see overlay::occurrences_source), which stay as they were imported.
+const EDITS_TABLE: &str = "edits";
+
+/// A change to one field of one occurrence
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Edit {
+    pub core_id: String,
+    pub column: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// When the edit was made, as an RFC 3339 timestamp in UTC
+    pub edited_at: String,
+}
+
+fn has_edits_table(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [EDITS_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Sets one field of an occurrence, e.g. to fix a misspelled recordedBy, by
+/// logging the change in the edits table. An empty value clears the field.
+/// Values that can't be stored in a typed column like decimalLatitude are
+/// rejected. Needs a read-write connection.
+pub fn update_occurrence_field(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    core_id: &str,
+    column: &str,
+    value: Option<&str>,
+) -> Result<Edit> {
+    if column == core_id_column {
+        return Err(ChuckError::Edit(format!("{column} identifies records and can't be edited")));
+    }
+    // Derived columns the archive doesn't have are VARCHAR, see
+    // overlay::occurrences_source
+    let column_type: String = match conn.query_row(
+        "SELECT data_type FROM information_schema.columns \
+         WHERE table_name = 'occurrences' AND column_name = ?",
+        [column],
+        |row| row.get(0),
+    ) {
+        Ok(column_type) => column_type,
+        Err(_) if crate::enrichment::derived_columns(conn)?.iter().any(|c| c == column) => {
+            "VARCHAR".to_string()
+        }
+        Err(_) => return Err(ChuckError::Edit(format!("occurrences have no {column} column"))),
+    };
+    let quoted = Database::quote_identifier(column);
+    let quoted_core_id = Database::quote_identifier(core_id_column);
+    let new_value = value.map(str::trim).filter(|v| !v.is_empty());
+
+    // Earlier edits count, so old_value is what the user was looking at
+    let occurrences = crate::overlay::occurrences_source(conn, core_id_column)?;
+    let old_value: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT CAST({quoted} AS VARCHAR) FROM {occurrences} \
+                 WHERE CAST({quoted_core_id} AS VARCHAR) = ?"
+            ),
+            [core_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| ChuckError::Edit(format!("no occurrence has {core_id_column} {core_id}")))?;
+    if let Some(new_value) = new_value {
+        let valid: bool = conn.query_row(
+            &format!("SELECT TRY_CAST(? AS {column_type}) IS NOT NULL"),
+            [new_value],
+            |row| row.get(0),
+        )?;
+        if !valid {
+            return Err(ChuckError::Edit(format!("{new_value} isn't a valid {column}")));
+        }
+    }
+
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {EDITS_TABLE} \
+         (core_id VARCHAR, column_name VARCHAR, old_value VARCHAR, new_value VARCHAR, edited_at VARCHAR)"
+    ))?;
+    let edited_at = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        &format!("INSERT INTO {EDITS_TABLE} VALUES (?, ?, ?, ?, ?)"),
+        duckdb::params![core_id, column, old_value, new_value, edited_at],
+    )?;
+    // Filters on split fields read multi_values, so keep it in step
+    if crate::multi_value::is_multi_value_field(column)
+        && crate::multi_value::has_multi_values(conn)?
+    {
+        crate::multi_value::split_multi_value_fields(conn, core_id_column)?;
+    }
+    Ok(Edit {
+        core_id: core_id.to_string(),
+        column: column.to_string(),
+        old_value,
+        new_value: new_value.map(String::from),
+        edited_at,
+    })
+}
+
+/// Lists edits, oldest first, optionally only those to one occurrence.
+/// Archives that have never been edited have none.
+pub fn list_edits(conn: &duckdb::Connection, core_id: Option<&str>) -> Result<Vec<Edit>> {
+    if !has_edits_table(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT core_id, column_name, old_value, new_value, edited_at \
+         FROM {EDITS_TABLE} WHERE CAST(? AS VARCHAR) IS NULL OR core_id = ? \
+         ORDER BY edited_at, rowid"
+    ))?;
+    let edits = stmt
+        .query_map(duckdb::params![core_id, core_id], |row| {
+            Ok(Edit {
+                core_id: row.get(0)?,
+                column: row.get(1)?,
+                old_value: row.get(2)?,
+                new_value: row.get(3)?,
+                edited_at: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(edits)
+}
+
+/// Columns with at least one edit
+pub fn edited_columns(conn: &duckdb::Connection) -> Result<Vec<String>> {
+    if !has_edits_table(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT DISTINCT column_name FROM {EDITS_TABLE} ORDER BY column_name"
+    ))?;
+    let columns = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// Query for the latest edit to `column` of each occurrence, as core_id and
+/// new_value
+pub(crate) fn latest_edits_query(column: &str) -> String {
+    format!(
+        "SELECT core_id, new_value FROM {EDITS_TABLE} WHERE column_name = '{}' \
+         QUALIFY row_number() OVER (PARTITION BY core_id ORDER BY edited_at DESC, rowid DESC) = 1",
+        column.replace('\'', "''")
+    )
+}
+
+/// The latest value of every edited field, by core ID and then column, for
+/// applying edits to data copied from the original archive. `edits` must be
+/// oldest first, as list_edits returns them.
+pub fn edited_values(edits: Vec<Edit>) -> HashMap<String, HashMap<String, Option<String>>> {
+    let mut values: HashMap<String, HashMap<String, Option<String>>> = HashMap::new();
+    for edit in edits {
+        values
+            .entry(edit.core_id)
+            .or_default()
+            .insert(edit.column, edit.new_value);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences() -> duckdb::Connection {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                occurrenceID VARCHAR,
+                recordedBy VARCHAR,
+                decimalLatitude DOUBLE
+             );
+             INSERT INTO occurrences VALUES ('1', 'Jepsen', 37.5);
+             INSERT INTO occurrences VALUES ('2', 'Hall', NULL);"
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_update_occurrence_field() {
+        let conn = occurrences();
+
+        let edit = update_occurrence_field(&conn, "occurrenceID", "1", "recordedBy", Some("Jepson"))
+            .unwrap();
+        update_occurrence_field(&conn, "occurrenceID", "1", "recordedBy", Some("W. L. Jepson"))
+            .unwrap();
+        update_occurrence_field(&conn, "occurrenceID", "2", "decimalLatitude", Some("38.25"))
+            .unwrap();
+
+        assert_eq!(edit.old_value.as_deref(), Some("Jepsen"));
+        assert_eq!(edit.new_value.as_deref(), Some("Jepson"));
+        let occurrences = crate::overlay::occurrences_source(&conn, "occurrenceID").unwrap();
+        let recorded_by: String = conn
+            .query_row(&format!("SELECT recordedBy FROM {occurrences} WHERE occurrenceID = '1'"), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(recorded_by, "W. L. Jepson");
+        let latitude: f64 = conn
+            .query_row(&format!("SELECT decimalLatitude FROM {occurrences} WHERE occurrenceID = '2'"), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(latitude, 38.25);
+        // The archive's own values are kept
+        let original: String = conn
+            .query_row("SELECT recordedBy FROM occurrences WHERE occurrenceID = '1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(original, "Jepsen");
+
+        let edits = list_edits(&conn, Some("1")).unwrap();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[1].old_value.as_deref(), Some("Jepson"));
+        assert_eq!(list_edits(&conn, None).unwrap().len(), 3);
+        let values = edited_values(list_edits(&conn, None).unwrap());
+        assert_eq!(values["1"]["recordedBy"].as_deref(), Some("W. L. Jepson"));
+    }
+
+    #[test]
+    fn test_update_occurrence_field_rejects_invalid_edits() {
+        let conn = occurrences();
+
+        assert!(update_occurrence_field(&conn, "occurrenceID", "1", "occurrenceID", Some("3")).is_err());
+        assert!(update_occurrence_field(&conn, "occurrenceID", "1", "eventDate", Some("2024")).is_err());
+        assert!(update_occurrence_field(&conn, "occurrenceID", "3", "recordedBy", Some("Hall")).is_err());
+        assert!(update_occurrence_field(&conn, "occurrenceID", "1", "decimalLatitude", Some("north")).is_err());
+        assert!(list_edits(&conn, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_occurrence_field_clears_empty_values() {
+        let conn = occurrences();
+
+        let edit = update_occurrence_field(&conn, "occurrenceID", "2", "recordedBy", Some(" ")).unwrap();
+
+        assert_eq!(edit.new_value, None);
+        let occurrences = crate::overlay::occurrences_source(&conn, "occurrenceID").unwrap();
+        let recorded_by: Option<String> = conn
+            .query_row(&format!("SELECT recordedBy FROM {occurrences} WHERE occurrenceID = '2'"), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(recorded_by, None);
+    }
+}
@@ -0,0 +1,690 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::admin_boundaries::AdminArea;
+use crate::db::Database;
+use crate::error::{ChuckError, Result};
+
+/// Records which occurrences columns were filled by enrichments rather than
+/// coming from the archive itself
+const ENRICHMENTS_TABLE: &str = "enrichments";
+
+/// Values filled in by enrichments, one row per occurrence and one column per
+/// filled field. They're overlaid on empty fields, or added as columns the
+/// archive doesn't have (see overlay::occurrences_source), rather than
+/// written into occurrences, so the archive's own values stay apart from
+/// derived ones.
+pub(crate) const DERIVED_VALUES_TABLE: &str = "derived_values";
+
+/// Column added to CSV exports listing the fields of each row that were
+/// filled by enrichments
+pub const DERIVED_FIELDS_COLUMN: &str = "chuck_derived_fields";
+
+/// Temporary table of scientificName -> vernacularName pairs to join against
+const VERNACULAR_LOOKUP_TABLE: &str = "vernacular_name_lookup";
+
+/// Temporary table of core ID -> admin area values to join against
+const ADMIN_AREA_LOOKUP_TABLE: &str = "admin_area_lookup";
+
+/// Temporary table of core ID -> elevation to join against
+const ELEVATION_LOOKUP_TABLE: &str = "elevation_lookup";
+
+/// Columns filled by admin area enrichment
+const ADMIN_AREA_COLUMNS: [&str; 3] = ["countryCode", "stateProvince", "county"];
+
+/// A column of occurrences filled in by an enrichment
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Enrichment {
+    pub column: String,
+    /// Where the values came from, e.g. a file path or "iNaturalist"
+    pub source: String,
+    /// Number of occurrences that received a value
+    pub filled: i64,
+}
+
+/// Where to get common names for vernacularName enrichment
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum VernacularNameSource {
+    /// A CSV or TSV with scientificName and vernacularName columns
+    File { path: String },
+    /// iNat's preferred common names, optionally in a specific locale
+    Inaturalist { locale: Option<String> },
+}
+
+/// Lists enrichments applied to the database. Archives that have never been
+/// enriched have none.
+pub fn list_enrichments(conn: &duckdb::Connection) -> Result<Vec<Enrichment>> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [ENRICHMENTS_TABLE],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT column_name, source, filled FROM {ENRICHMENTS_TABLE} ORDER BY column_name"
+    ))?;
+    let enrichments = stmt
+        .query_map([], |row| {
+            Ok(Enrichment { column: row.get(0)?, source: row.get(1)?, filled: row.get(2)? })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(enrichments)
+}
+
+/// Distinct scientific names of occurrences without a vernacularName, i.e.
+/// the names worth looking up
+pub fn scientific_names_missing_vernacular(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+) -> Result<Vec<String>> {
+    let columns = source_columns(conn)?;
+    if !columns.iter().any(|c| c == "scientificName") {
+        return Ok(vec![]);
+    }
+    let missing_condition = if columns.iter().any(|c| c == "vernacularName") {
+        " AND (\"vernacularName\" IS NULL OR trim(\"vernacularName\") = '')"
+    } else {
+        ""
+    };
+    let occurrences = crate::overlay::occurrences_source(conn, core_id_column)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT DISTINCT \"scientificName\" FROM {occurrences} \
+         WHERE \"scientificName\" IS NOT NULL AND trim(\"scientificName\") != ''{missing_condition} \
+         ORDER BY \"scientificName\""
+    ))?;
+    let names = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(names)
+}
+
+/// Fills empty vernacularName values with derived values from a map of
+/// scientific names to common names. Needs a read-write connection.
+pub fn fill_vernacular_names(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    common_names: &HashMap<String, String>,
+    source: &str,
+) -> Result<Enrichment> {
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TEMP TABLE {VERNACULAR_LOOKUP_TABLE} \
+         (\"scientificName\" VARCHAR, \"vernacularName\" VARCHAR)"
+    ))?;
+    {
+        let mut appender = conn.appender(VERNACULAR_LOOKUP_TABLE)?;
+        for (scientific_name, vernacular_name) in common_names {
+            appender.append_row([scientific_name, vernacular_name])?;
+        }
+    }
+    fill_from_lookup(conn, core_id_column, source)
+}
+
+/// Fills empty vernacularName values with derived values from a CSV or TSV
+/// file with scientificName and vernacularName columns. When a name has
+/// several common names, the first one in the file wins. Needs a read-write
+/// connection.
+pub fn fill_vernacular_names_from_file(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    path: &Path,
+) -> Result<Enrichment> {
+    let path_str = path.to_str().ok_or(ChuckError::PathEncoding)?.replace('\'', "''");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT unnest(Columns).name FROM sniff_csv('{path_str}')"
+    ))?;
+    let file_columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for required in ["scientificName", "vernacularName"] {
+        if !file_columns.iter().any(|c| c == required) {
+            return Err(ChuckError::CsvColumnNotFound(required.to_string()));
+        }
+    }
+
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TEMP TABLE {VERNACULAR_LOOKUP_TABLE} AS \
+         SELECT \"scientificName\", first(\"vernacularName\") AS \"vernacularName\" \
+         FROM read_csv('{path_str}', all_varchar = true) \
+         WHERE \"vernacularName\" IS NOT NULL AND trim(\"vernacularName\") != '' \
+         GROUP BY \"scientificName\""
+    ))?;
+    fill_from_lookup(conn, core_id_column, &path.display().to_string())
+}
+
+fn fill_from_lookup(conn: &duckdb::Connection, core_id_column: &str, source: &str) -> Result<Enrichment> {
+    let filled = store_derived_values(
+        conn,
+        core_id_column,
+        "vernacularName",
+        &format!(
+            "SELECT CAST(occurrences.{} AS VARCHAR) AS id, lookup.\"vernacularName\" AS value \
+             FROM {} JOIN {VERNACULAR_LOOKUP_TABLE} lookup \
+                ON occurrences.\"scientificName\" = lookup.\"scientificName\"",
+            Database::quote_identifier(core_id_column),
+            crate::overlay::occurrences_source(conn, core_id_column)?,
+        ),
+    )?;
+    conn.execute_batch(&format!("DROP TABLE {VERNACULAR_LOOKUP_TABLE}"))?;
+
+    let enrichment = Enrichment {
+        column: "vernacularName".to_string(),
+        source: source.to_string(),
+        filled: filled as i64,
+    };
+    record_enrichment(conn, &enrichment)?;
+    Ok(enrichment)
+}
+
+/// Coordinates as DOUBLE for `occurrences`, skipping unparseable values
+fn coordinates_subquery(occurrences: &str) -> String {
+    format!(
+        "SELECT *, \
+            TRY_CAST(\"decimalLatitude\" AS DOUBLE) AS lat, \
+            TRY_CAST(\"decimalLongitude\" AS DOUBLE) AS lon \
+         FROM {occurrences}"
+    )
+}
+
+/// Core IDs and coordinates of occurrences without an elevation. Empty if
+/// the archive has no coordinates.
+pub fn occurrences_missing_elevation(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+) -> Result<Vec<(String, f64, f64)>> {
+    let columns = source_columns(conn)?;
+    if !["decimalLatitude", "decimalLongitude"].iter().all(|c| columns.iter().any(|col| col == c)) {
+        return Ok(vec![]);
+    }
+    let missing_condition = if columns.iter().any(|c| c == "elevation") {
+        " AND (elevation IS NULL OR trim(CAST(elevation AS VARCHAR)) = '')"
+    } else {
+        ""
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CAST({} AS VARCHAR), lat, lon FROM ({}) \
+         WHERE lat IS NOT NULL AND lon IS NOT NULL{missing_condition}",
+        Database::quote_identifier(core_id_column),
+        coordinates_subquery(&crate::overlay::occurrences_source(conn, core_id_column)?),
+    ))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Fills empty elevation values with derived elevations, in meters, looked
+/// up by core ID. Needs a read-write connection.
+pub fn fill_elevations(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    elevations: &[(String, f64)],
+    source: &str,
+) -> Result<Enrichment> {
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TEMP TABLE {ELEVATION_LOOKUP_TABLE} (id VARCHAR, elevation DOUBLE)"
+    ))?;
+    {
+        let mut appender = conn.appender(ELEVATION_LOOKUP_TABLE)?;
+        for (id, elevation) in elevations {
+            appender.append_row(duckdb::params![id, elevation])?;
+        }
+    }
+
+    let filled = store_derived_values(
+        conn,
+        core_id_column,
+        "elevation",
+        &format!("SELECT id, round(elevation, 1) AS value FROM {ELEVATION_LOOKUP_TABLE}"),
+    )?;
+    conn.execute_batch(&format!("DROP TABLE {ELEVATION_LOOKUP_TABLE}"))?;
+
+    let enrichment = Enrichment {
+        column: "elevation".to_string(),
+        source: source.to_string(),
+        filled: filled as i64,
+    };
+    record_enrichment(conn, &enrichment)?;
+    Ok(enrichment)
+}
+
+/// Core IDs and coordinates of occurrences missing any of countryCode,
+/// stateProvince, or county. Empty if the archive has no coordinates.
+pub fn occurrences_missing_admin_areas(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+) -> Result<Vec<(String, f64, f64)>> {
+    let columns = source_columns(conn)?;
+    if !["decimalLatitude", "decimalLongitude"].iter().all(|c| columns.iter().any(|col| col == c)) {
+        return Ok(vec![]);
+    }
+    // A column the archive doesn't have is missing for every occurrence
+    let missing_conditions: Vec<String> = ADMIN_AREA_COLUMNS
+        .iter()
+        .map(|column| {
+            if columns.iter().any(|c| c == column) {
+                empty_condition(&format!("\"{column}\""))
+            } else {
+                "true".to_string()
+            }
+        })
+        .collect();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CAST({} AS VARCHAR), lat, lon FROM ({}) \
+         WHERE lat IS NOT NULL AND lon IS NOT NULL AND ({})",
+        Database::quote_identifier(core_id_column),
+        coordinates_subquery(&crate::overlay::occurrences_source(conn, core_id_column)?),
+        missing_conditions.join(" OR "),
+    ))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Fills empty countryCode, stateProvince, and county values with derived
+/// values from admin areas looked up by core ID. Returns an enrichment for
+/// each column that received values. Needs a read-write connection.
+pub fn fill_admin_areas(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    areas: &[(String, AdminArea)],
+    source: &str,
+) -> Result<Vec<Enrichment>> {
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TEMP TABLE {ADMIN_AREA_LOOKUP_TABLE} \
+         (id VARCHAR, \"countryCode\" VARCHAR, \"stateProvince\" VARCHAR, county VARCHAR)"
+    ))?;
+    {
+        let mut appender = conn.appender(ADMIN_AREA_LOOKUP_TABLE)?;
+        for (id, area) in areas {
+            appender.append_row(duckdb::params![
+                id,
+                area.country_code,
+                area.state_province,
+                area.county,
+            ])?;
+        }
+    }
+
+    let mut enrichments = Vec::new();
+    for column in ADMIN_AREA_COLUMNS {
+        let filled = store_derived_values(
+            conn,
+            core_id_column,
+            column,
+            &format!("SELECT id, \"{column}\" AS value FROM {ADMIN_AREA_LOOKUP_TABLE}"),
+        )?;
+        if filled > 0 {
+            let enrichment = Enrichment {
+                column: column.to_string(),
+                source: source.to_string(),
+                filled: filled as i64,
+            };
+            record_enrichment(conn, &enrichment)?;
+            enrichments.push(enrichment);
+        }
+    }
+    conn.execute_batch(&format!("DROP TABLE {ADMIN_AREA_LOOKUP_TABLE}"))?;
+    Ok(enrichments)
+}
+
+/// SQL condition matching an empty value
+fn empty_condition(value: &str) -> String {
+    format!("{value} IS NULL OR trim(CAST({value} AS VARCHAR)) = ''")
+}
+
+/// Columns of occurrences with derived values
+pub fn derived_columns(conn: &duckdb::Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT column_name FROM information_schema.columns \
+         WHERE table_name = ? AND column_name != 'core_id' ORDER BY ordinal_position"
+    )?;
+    let columns = stmt
+        .query_map([DERIVED_VALUES_TABLE], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// SQL condition matching occurrences where the archive's own value of
+/// `column` is empty, which it is for all of them if the archive doesn't have
+/// the column. Use with overlay::archive_values_source.
+fn empty_in_archive_condition(conn: &duckdb::Connection, column: &str) -> Result<String> {
+    Ok(if column_names(conn)?.iter().any(|c| c == column) {
+        empty_condition(&format!("occurrences.{}", Database::quote_identifier(column)))
+    } else {
+        "true".to_string()
+    })
+}
+
+/// Stores values of `column` from `lookup`, a query of id (a core ID) and
+/// value, as derived values for occurrences where the archive's own value is
+/// empty. Values derived before are replaced, so enriching again refreshes
+/// them. Returns the number of occurrences that received a value.
+fn store_derived_values(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    column: &str,
+    lookup: &str,
+) -> Result<usize> {
+    let occurrences = crate::overlay::archive_values_source(conn)?;
+    let empty = empty_in_archive_condition(conn, column)?;
+    let quoted = Database::quote_identifier(column);
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {DERIVED_VALUES_TABLE} (core_id VARCHAR PRIMARY KEY); \
+         ALTER TABLE {DERIVED_VALUES_TABLE} ADD COLUMN IF NOT EXISTS {quoted} VARCHAR;"
+    ))?;
+    let stored = conn.execute(
+        &format!(
+            "INSERT INTO {DERIVED_VALUES_TABLE} (core_id, {quoted}) \
+             SELECT lookup.id, any_value(CAST(lookup.value AS VARCHAR)) \
+             FROM ({lookup}) lookup \
+             JOIN {occurrences} ON CAST(occurrences.{} AS VARCHAR) = lookup.id \
+             WHERE lookup.value IS NOT NULL AND ({empty}) \
+             GROUP BY lookup.id \
+             ON CONFLICT (core_id) DO UPDATE SET {quoted} = excluded.{quoted}",
+            Database::quote_identifier(core_id_column),
+        ),
+        [],
+    )?;
+    Ok(stored)
+}
+
+/// Fields of each occurrence that show a derived value rather than the
+/// archive's own or a hand edit, as `DERIVED_FIELDS_COLUMN` values by core
+/// ID. Occurrences without any are left out.
+pub fn derived_fields(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+) -> Result<HashMap<String, String>> {
+    let columns = derived_columns(conn)?;
+    if columns.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let edited_columns = crate::edits::edited_columns(conn)?;
+    let mut joins = String::new();
+    let mut fields = Vec::new();
+    for (i, column) in columns.iter().enumerate() {
+        let mut condition = format!(
+            "derived.{} IS NOT NULL AND ({})",
+            Database::quote_identifier(column),
+            empty_in_archive_condition(conn, column)?
+        );
+        if edited_columns.contains(column) {
+            joins.push_str(&format!(
+                " LEFT JOIN ({}) edit_{i} ON edit_{i}.core_id = derived.core_id",
+                crate::edits::latest_edits_query(column)
+            ));
+            condition.push_str(&format!(" AND edit_{i}.core_id IS NULL"));
+        }
+        fields.push(format!("CASE WHEN {condition} THEN '{}' END", column.replace('\'', "''")));
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT derived.core_id, concat_ws(' | ', {}) AS fields \
+         FROM {DERIVED_VALUES_TABLE} derived \
+         JOIN {} ON CAST(occurrences.{} AS VARCHAR) = derived.core_id{joins}",
+        fields.join(", "),
+        crate::overlay::archive_values_source(conn)?,
+        Database::quote_identifier(core_id_column),
+    ))?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    let mut derived = HashMap::new();
+    for row in rows {
+        let (core_id, fields) = row?;
+        if !fields.is_empty() {
+            derived.insert(core_id, fields);
+        }
+    }
+    Ok(derived)
+}
+
+/// Adds or updates the record of an enrichment. Repeated enrichments of the
+/// same column accumulate their fill counts and keep the latest source.
+fn record_enrichment(conn: &duckdb::Connection, enrichment: &Enrichment) -> Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {ENRICHMENTS_TABLE} \
+         (column_name VARCHAR PRIMARY KEY, source VARCHAR, filled BIGINT)"
+    ))?;
+    conn.execute(
+        &format!(
+            "INSERT INTO {ENRICHMENTS_TABLE} VALUES (?, ?, ?) \
+             ON CONFLICT (column_name) DO UPDATE \
+             SET source = excluded.source, filled = {ENRICHMENTS_TABLE}.filled + excluded.filled"
+        ),
+        duckdb::params![enrichment.column, enrichment.source, enrichment.filled],
+    )?;
+    Ok(())
+}
+
+/// Columns of overlay::occurrences_source, including derived ones the archive
+/// doesn't have
+fn source_columns(conn: &duckdb::Connection) -> Result<Vec<String>> {
+    let mut columns = column_names(conn)?;
+    columns.extend(derived_columns(conn)?);
+    Ok(columns)
+}
+
+fn column_names(conn: &duckdb::Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT column_name FROM information_schema.columns WHERE table_name = 'occurrences'"
+    )?;
+    let columns = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences() -> duckdb::Connection {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES ('1', 'Quercus agrifolia');
+             INSERT INTO occurrences VALUES ('2', 'Quercus agrifolia');
+             INSERT INTO occurrences VALUES ('3', 'Pinus ponderosa');
+             INSERT INTO occurrences VALUES ('4', NULL);"
+        ).unwrap();
+        conn
+    }
+
+    fn vernacular_names(conn: &duckdb::Connection) -> Vec<Option<String>> {
+        let occurrences = crate::overlay::occurrences_source(conn, "occurrenceID").unwrap();
+        conn.prepare(&format!("SELECT vernacularName FROM {occurrences} ORDER BY occurrenceID"))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fill_vernacular_names() {
+        let conn = occurrences();
+        assert_eq!(
+            scientific_names_missing_vernacular(&conn, "occurrenceID").unwrap(),
+            vec!["Pinus ponderosa", "Quercus agrifolia"]
+        );
+        assert!(list_enrichments(&conn).unwrap().is_empty());
+
+        let common_names = HashMap::from([
+            ("Quercus agrifolia".to_string(), "coast live oak".to_string()),
+        ]);
+        let enrichment = fill_vernacular_names(&conn, "occurrenceID", &common_names, "iNaturalist").unwrap();
+
+        assert_eq!(enrichment.filled, 2);
+        assert_eq!(vernacular_names(&conn), vec![
+            Some("coast live oak".to_string()),
+            Some("coast live oak".to_string()),
+            None,
+            None,
+        ]);
+        assert_eq!(scientific_names_missing_vernacular(&conn, "occurrenceID").unwrap(), vec!["Pinus ponderosa"]);
+        assert_eq!(list_enrichments(&conn).unwrap(), vec![enrichment]);
+        // Derived names are kept apart from the archive's own, which doesn't
+        // get a vernacularName column
+        assert!(!column_names(&conn).unwrap().contains(&"vernacularName".to_string()));
+        assert_eq!(derived_fields(&conn, "occurrenceID").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_fill_vernacular_names_refreshes_derived_values() {
+        let conn = occurrences();
+        let common_names = HashMap::from([
+            ("Quercus agrifolia".to_string(), "coast live oak".to_string()),
+        ]);
+        fill_vernacular_names(&conn, "occurrenceID", &common_names, "iNaturalist").unwrap();
+
+        let common_names = HashMap::from([
+            ("Quercus agrifolia".to_string(), "California live oak".to_string()),
+            ("Pinus ponderosa".to_string(), "ponderosa pine".to_string()),
+        ]);
+        let enrichment = fill_vernacular_names(&conn, "occurrenceID", &common_names, "iNaturalist").unwrap();
+
+        assert_eq!(enrichment.filled, 3);
+        assert_eq!(vernacular_names(&conn), vec![
+            Some("California live oak".to_string()),
+            Some("California live oak".to_string()),
+            Some("ponderosa pine".to_string()),
+            None,
+        ]);
+    }
+
+    #[test]
+    fn test_fill_vernacular_names_keeps_existing_values() {
+        let conn = occurrences();
+        conn.execute_batch(
+            "ALTER TABLE occurrences ADD COLUMN vernacularName VARCHAR;
+             UPDATE occurrences SET vernacularName = 'ponderosa pine' WHERE occurrenceID = '3';"
+        ).unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("names.tsv");
+        std::fs::write(
+            &path,
+            "scientificName\tvernacularName\nPinus ponderosa\tyellow pine\nQuercus agrifolia\tcoast live oak\n",
+        ).unwrap();
+
+        let enrichment = fill_vernacular_names_from_file(&conn, "occurrenceID", &path).unwrap();
+
+        assert_eq!(enrichment.filled, 2);
+        assert_eq!(vernacular_names(&conn)[2], Some("ponderosa pine".to_string()));
+    }
+
+    #[test]
+    fn test_fill_vernacular_names_from_file_requires_columns() {
+        let conn = occurrences();
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("names.csv");
+        std::fs::write(&path, "name,commonName\nPinus ponderosa,ponderosa pine\n").unwrap();
+
+        let result = fill_vernacular_names_from_file(&conn, "occurrenceID", &path);
+
+        assert!(matches!(result, Err(ChuckError::CsvColumnNotFound(col)) if col == "scientificName"));
+    }
+
+    #[test]
+    fn test_fill_admin_areas() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                 occurrenceID VARCHAR, decimalLatitude DOUBLE, decimalLongitude DOUBLE,
+                 countryCode VARCHAR
+             );
+             INSERT INTO occurrences VALUES ('1', 2.0, 2.0, NULL);
+             INSERT INTO occurrences VALUES ('2', 5.0, 15.0, 'ZZ');
+             INSERT INTO occurrences VALUES ('3', NULL, NULL, NULL);"
+        ).unwrap();
+
+        let missing = occurrences_missing_admin_areas(&conn, "occurrenceID").unwrap();
+        let mut ids: Vec<&str> = missing.iter().map(|(id, _, _)| id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["1", "2"]);
+
+        let areas = vec![
+            ("1".to_string(), AdminArea {
+                country_code: Some("AA".to_string()),
+                state_province: Some("West".to_string()),
+                county: Some("Corner".to_string()),
+            }),
+            ("2".to_string(), AdminArea {
+                country_code: Some("AA".to_string()),
+                state_province: Some("East".to_string()),
+                county: None,
+            }),
+        ];
+        let enrichments = fill_admin_areas(&conn, "occurrenceID", &areas, "admin boundaries").unwrap();
+
+        let filled: Vec<(&str, i64)> = enrichments.iter().map(|e| (e.column.as_str(), e.filled)).collect();
+        assert_eq!(filled, vec![("countryCode", 1), ("stateProvince", 2), ("county", 1)]);
+        let occurrences = crate::overlay::occurrences_source(&conn, "occurrenceID").unwrap();
+        let rows: Vec<(Option<String>, Option<String>, Option<String>)> = conn
+            .prepare(&format!("SELECT countryCode, stateProvince, county FROM {occurrences} ORDER BY occurrenceID"))
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        // Existing values are kept even when they disagree with the boundaries
+        assert_eq!(rows[1].0.as_deref(), Some("ZZ"));
+        assert_eq!(rows[0], (Some("AA".to_string()), Some("West".to_string()), Some("Corner".to_string())));
+        assert_eq!(rows[2], (None, None, None));
+        assert_eq!(list_enrichments(&conn).unwrap().len(), 3);
+        assert_eq!(occurrences_missing_admin_areas(&conn, "occurrenceID").unwrap().len(), 1);
+
+        // Derived values are kept apart from the archive's own
+        let original: Option<String> = conn
+            .query_row("SELECT countryCode FROM occurrences WHERE occurrenceID = '1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(original, None);
+        assert!(!column_names(&conn).unwrap().contains(&"stateProvince".to_string()));
+        assert_eq!(derived_fields(&conn, "occurrenceID").unwrap(), HashMap::from([
+            ("1".to_string(), "countryCode | stateProvince | county".to_string()),
+            ("2".to_string(), "stateProvince".to_string()),
+        ]));
+        crate::edits::update_occurrence_field(&conn, "occurrenceID", "2", "stateProvince", Some("North")).unwrap();
+        assert!(!derived_fields(&conn, "occurrenceID").unwrap().contains_key("2"));
+    }
+
+    #[test]
+    fn test_fill_elevations() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                 occurrenceID VARCHAR, decimalLatitude VARCHAR, decimalLongitude VARCHAR
+             );
+             INSERT INTO occurrences VALUES ('1', '37.5', '-122.0');
+             INSERT INTO occurrences VALUES ('2', 'unknown', '-122.0');"
+        ).unwrap();
+
+        let missing = occurrences_missing_elevation(&conn, "occurrenceID").unwrap();
+        assert_eq!(missing, vec![("1".to_string(), 37.5, -122.0)]);
+
+        let enrichment = fill_elevations(
+            &conn,
+            "occurrenceID",
+            &[("1".to_string(), 123.456)],
+            "Terrarium DEM",
+        ).unwrap();
+
+        assert_eq!(enrichment.filled, 1);
+        let occurrences = crate::overlay::occurrences_source(&conn, "occurrenceID").unwrap();
+        let elevation: Option<String> = conn
+            .query_row(&format!("SELECT elevation FROM {occurrences} WHERE occurrenceID = '1'"), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(elevation.as_deref(), Some("123.5"));
+        assert!(occurrences_missing_elevation(&conn, "occurrenceID").unwrap().is_empty());
+        // The archive has no elevation column of its own, and the derived one is marked
+        assert!(!column_names(&conn).unwrap().contains(&"elevation".to_string()));
+        assert_eq!(
+            derived_fields(&conn, "occurrenceID").unwrap(),
+            HashMap::from([("1".to_string(), "elevation".to_string())])
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use chuck_core::error_code::{ErrorCode, ErrorReport};
 use serde::{Serialize, Serializer};
 
 #[derive(Debug, thiserror::Error)]
@@ -56,6 +57,9 @@ pub enum ChuckError {
     #[error("Database error: {0}")]
     Database(#[from] duckdb::Error),
 
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
     #[error("Invalid path encoding")]
     PathEncoding,
 
@@ -71,20 +75,189 @@ pub enum ChuckError {
     #[error("Extension missing core ID: {0}")]
     NoExtensionCoreId(String),
 
-    #[error("TYPE_OVERRIDES contains core_id column '{0}' - core ID must always be VARCHAR to handle all ID formats (numeric, text, UUIDs, etc.)")]
+    #[error("Core ID column '{0}' has a type override - core ID must always be VARCHAR to handle all ID formats (numeric, text, UUIDs, etc.)")]
     CoreIdTypeOverride(String),
 
     #[error("Column '{0}' not found in CSV header")]
     CsvColumnNotFound(String),
+
+    #[error("Column '{0}' isn't in this archive")]
+    MissingColumn(String),
+
+    #[error("Invalid country boundaries: {0}")]
+    CountryBoundaries(String),
+
+    #[error("Invalid admin boundaries: {0}")]
+    AdminBoundaries(String),
+
+    #[error("Boundaries haven't been downloaded")]
+    BoundariesNotDownloaded,
+
+    #[error("Invalid taxonomy: {0}")]
+    Taxonomy(String),
+
+    #[error("SQL error: {0}")]
+    Sql(String),
+
+    #[error("Invalid edit: {0}")]
+    Edit(String),
+
+    #[error("Invalid flag: {0}")]
+    Flag(String),
+
+    #[error("Archive isn't ready for an IPT: {0}")]
+    IptPackage(String),
+
+    #[error("Don't know which file archive {0} was opened from")]
+    UnknownArchiveSource(String),
+
+    #[error("Archive is read-only. Unlock it to make changes.")]
+    ReadOnly,
+
+    #[error("Failed to write PMTiles: {0}")]
+    PmTiles(String),
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    #[error("Failed to download {url}: {source}")]
+    MediaDownload {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("Failed to read EXIF metadata from {path}: {source}")]
+    Exif {
+        path: PathBuf,
+        #[source]
+        source: ::exif::Error,
+    },
+
+    #[error("Failed to make a thumbnail of {path}: {source}")]
+    Thumbnail {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+
+    #[error(
+        "Not enough disk space: opening this archive needs about {} MB but only {} MB is free",
+        needed / 1_000_000,
+        available / 1_000_000
+    )]
+    InsufficientDiskSpace { needed: u64, available: u64 },
+
+    #[error("Invalid schedule: {0}")]
+    Schedule(String),
+
+    #[error("Grid cells must be between 0.001 and 90 degrees, not {0}")]
+    GridCellSize(f64),
+
+    #[error("Invalid search cursor: {0}")]
+    SearchCursor(String),
+}
+
+impl ErrorCode for ChuckError {
+    fn code(&self) -> &'static str {
+        match self {
+            ChuckError::FileOpen { .. } => "file_open",
+            ChuckError::FileRead { .. } => "file_read",
+            ChuckError::FileWrite { .. } => "file_write",
+            ChuckError::DirectoryCreate { .. } => "directory_create",
+            ChuckError::ArchiveExtraction(_) => "archive_extraction",
+            ChuckError::NotADarwinCoreArchive(_) => "missing_meta_xml",
+            ChuckError::InvalidFileName(_) => "invalid_file_name",
+            ChuckError::XmlParse { .. } => "xml_parse",
+            ChuckError::NoCoreFiles => "no_core_files",
+            ChuckError::NoArchiveFound(_) => "no_archive_found",
+            ChuckError::Database(_) => "database",
+            ChuckError::Sqlite(_) => "sqlite",
+            ChuckError::PathEncoding => "path_encoding",
+            ChuckError::Tauri(_) => "tauri",
+            ChuckError::AutocompleteNotAvailable { .. } => "autocomplete_not_available",
+            ChuckError::NoExtensionCoreId(_) => "no_extension_core_id",
+            ChuckError::CoreIdTypeOverride(_) => "core_id_type_override",
+            ChuckError::CsvColumnNotFound(_) => "csv_column_not_found",
+            ChuckError::MissingColumn(_) => "missing_column",
+            ChuckError::CountryBoundaries(_) => "country_boundaries",
+            ChuckError::AdminBoundaries(_) => "admin_boundaries",
+            ChuckError::BoundariesNotDownloaded => "boundaries_not_downloaded",
+            ChuckError::Taxonomy(_) => "taxonomy",
+            ChuckError::Sql(_) => "sql",
+            ChuckError::Edit(_) => "edit",
+            ChuckError::Flag(_) => "flag",
+            ChuckError::IptPackage(_) => "ipt_package",
+            ChuckError::UnknownArchiveSource(_) => "unknown_archive_source",
+            ChuckError::ReadOnly => "read_only",
+            ChuckError::PmTiles(_) => "pmtiles",
+            ChuckError::Cancelled => "cancelled",
+            ChuckError::MediaDownload { .. } => "media_download",
+            ChuckError::Exif { .. } => "exif",
+            ChuckError::Thumbnail { .. } => "thumbnail",
+            ChuckError::InsufficientDiskSpace { .. } => "insufficient_disk_space",
+            ChuckError::Schedule(_) => "schedule",
+            ChuckError::GridCellSize(_) => "grid_cell_size",
+            ChuckError::SearchCursor(_) => "search_cursor",
+        }
+    }
+
+    fn guidance(&self) -> Option<&'static str> {
+        match self {
+            ChuckError::NotADarwinCoreArchive(_) | ChuckError::NoCoreFiles => Some(
+                "DarwinCore Archives are zip files with a meta.xml describing their data files. \
+                 Check that this is one, e.g. by downloading it again.",
+            ),
+            ChuckError::ArchiveExtraction(_) => {
+                Some("The zip file may be incomplete or corrupt. Try downloading it again.")
+            }
+            ChuckError::MissingColumn(_) => Some(
+                "This archive doesn't have a column this view needs. \
+                 Try another field, or reopen the archive if its columns changed.",
+            ),
+            ChuckError::BoundariesNotDownloaded => {
+                Some("Download boundaries on the Offline Basemaps page, then try again.")
+            }
+            ChuckError::ReadOnly => Some("Archives can be locked and unlocked on the Metadata page."),
+            ChuckError::InsufficientDiskSpace { .. } => {
+                Some("Free up some disk space and open the archive again.")
+            }
+            ChuckError::MediaDownload { .. } => Some("Check your internet connection and try again."),
+            _ => None,
+        }
+    }
 }
 
+// Errors reach the frontend as ErrorReports so it can show guidance and
+// match on codes
 impl Serialize for ChuckError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        ErrorReport::of(self).serialize(serializer)
     }
 }
 
 pub type Result<T> = std::result::Result<T, ChuckError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_error_report() {
+        let json = serde_json::to_value(ChuckError::NotADarwinCoreArchive(PathBuf::from("obs.zip"))).unwrap();
+        assert_eq!(json["code"], "missing_meta_xml");
+        assert_eq!(json["message"], "Not a DarwinCore Archive: meta.xml not found in obs.zip");
+        assert!(json["guidance"].is_string());
+
+        let json = serde_json::to_value(ChuckError::MissingColumn("sex".to_string())).unwrap();
+        assert_eq!(json["code"], "missing_column");
+        assert!(json["guidance"].is_string());
+
+        let json = serde_json::to_value(ChuckError::Cancelled).unwrap();
+        assert_eq!(json["code"], "cancelled");
+        assert!(json["guidance"].is_null());
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Occurrences left out of exports without deleting them from the archive,
+/// one row per core ID. Restoring one just removes its row, so exclusions
+/// can be undone and redone as often as needed.
+pub const EXCLUSIONS_TABLE: &str = "exclusions";
+
+/// An occurrence left out of exports, with an optional reason
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Exclusion {
+    pub core_id: String,
+    pub reason: Option<String>,
+    /// When the occurrence was excluded, as an RFC 3339 timestamp in UTC
+    pub excluded_at: String,
+}
+
+/// Whether any occurrences have ever been excluded
+pub fn has_exclusions(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [EXCLUSIONS_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Excludes the occurrences matching `where_clause`, a WHERE clause for
+/// occurrences from Database::query_parts, with its `params`. Occurrences
+/// that are already excluded keep their reason. Returns the number newly
+/// excluded. Needs a read-write connection.
+pub fn exclude_occurrences(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    where_clause: &str,
+    params: &[Box<dyn duckdb::ToSql>],
+    reason: Option<&str>,
+) -> Result<usize> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {EXCLUSIONS_TABLE} \
+         (core_id VARCHAR PRIMARY KEY, reason VARCHAR, excluded_at VARCHAR)"
+    ))?;
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+    let excluded_at = chrono::Utc::now().to_rfc3339();
+    let mut param_refs = vec![&reason as &dyn duckdb::ToSql, &excluded_at];
+    param_refs.extend(params.iter().map(|p| p.as_ref()));
+    let occurrences = crate::overlay::occurrences_source(conn, core_id_column)?;
+    let count = conn.execute(
+        &format!(
+            "INSERT OR IGNORE INTO {EXCLUSIONS_TABLE} \
+             SELECT DISTINCT CAST({} AS VARCHAR), CAST(? AS VARCHAR), CAST(? AS VARCHAR) \
+             FROM {occurrences}{where_clause}",
+            Database::quote_identifier(core_id_column),
+        ),
+        param_refs.as_slice(),
+    )?;
+    Ok(count)
+}
+
+/// Restores excluded occurrences by core ID, or all of them without any.
+/// Returns the number restored. Needs a read-write connection.
+pub fn restore_exclusions(conn: &duckdb::Connection, core_ids: Option<&[String]>) -> Result<usize> {
+    if !has_exclusions(conn)? {
+        return Ok(0);
+    }
+    let Some(core_ids) = core_ids else {
+        return Ok(conn.execute(&format!("DELETE FROM {EXCLUSIONS_TABLE}"), [])?);
+    };
+    let mut stmt = conn.prepare(&format!("DELETE FROM {EXCLUSIONS_TABLE} WHERE core_id = ?"))?;
+    let mut restored = 0;
+    for core_id in core_ids {
+        restored += stmt.execute([core_id])?;
+    }
+    Ok(restored)
+}
+
+/// Lists excluded occurrences by core ID. Archives that have never had
+/// exclusions have none.
+pub fn list_exclusions(conn: &duckdb::Connection) -> Result<Vec<Exclusion>> {
+    if !has_exclusions(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT core_id, reason, excluded_at FROM {EXCLUSIONS_TABLE} ORDER BY core_id"
+    ))?;
+    let exclusions = stmt
+        .query_map([], |row| {
+            Ok(Exclusion {
+                core_id: row.get(0)?,
+                reason: row.get(1)?,
+                excluded_at: row.get(2)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(exclusions)
+}
+
+/// Core IDs of excluded occurrences
+pub fn excluded_ids(conn: &duckdb::Connection) -> Result<HashSet<String>> {
+    if !has_exclusions(conn)? {
+        return Ok(HashSet::new());
+    }
+    let mut stmt = conn.prepare(&format!("SELECT core_id FROM {EXCLUSIONS_TABLE}"))?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<HashSet<String>, _>>()?;
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences() -> duckdb::Connection {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES
+                 ('1', 'Quercus agrifolia'), ('2', 'Quercus lobata'), ('3', 'Pinus sabiniana');"
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_exclude_and_restore_occurrences() {
+        let conn = occurrences();
+        assert!(excluded_ids(&conn).unwrap().is_empty());
+
+        let params: Vec<Box<dyn duckdb::ToSql>> = vec![Box::new("Quercus%".to_string())];
+        let where_clause = " WHERE scientificName LIKE ?";
+        assert_eq!(
+            exclude_occurrences(&conn, "occurrenceID", where_clause, &params, Some("Planted")).unwrap(),
+            2
+        );
+        // Excluding again keeps the first reason
+        assert_eq!(exclude_occurrences(&conn, "occurrenceID", where_clause, &params, None).unwrap(), 0);
+
+        let exclusions = list_exclusions(&conn).unwrap();
+        assert_eq!(exclusions.len(), 2);
+        assert_eq!(exclusions[0].core_id, "1");
+        assert_eq!(exclusions[0].reason.as_deref(), Some("Planted"));
+
+        assert_eq!(restore_exclusions(&conn, Some(&["2".to_string()])).unwrap(), 1);
+        assert_eq!(excluded_ids(&conn).unwrap(), HashSet::from(["1".to_string()]));
+        assert_eq!(restore_exclusions(&conn, None).unwrap(), 1);
+        assert!(list_exclusions(&conn).unwrap().is_empty());
+    }
+}
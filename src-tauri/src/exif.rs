@@ -0,0 +1,274 @@
+//! Reading EXIF metadata embedded in archive photos, and checking it against
+//! the occurrence the photo belongs to, since a photo taken far from where or
+//! long before when an occurrence says it was observed suggests one of them is
+//! wrong
+
+use std::io::BufReader;
+use std::path::Path;
+
+use ::exif::{DateTime, Field, In, Reader, Tag, Value};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::error::{ChuckError, Result};
+
+/// Days a photo's capture date can differ from the eventDate before it's
+/// flagged. Cameras record local time without a time zone, so dates near
+/// midnight can legitimately be a day off.
+const MAX_DATE_DIFFERENCE_DAYS: i64 = 1;
+
+/// Distance in meters a photo's GPS position can be from the occurrence's
+/// coordinates before it's flagged, unless the coordinate uncertainty is
+/// larger
+const MIN_DISTANCE_TOLERANCE_METERS: f64 = 1000.0;
+
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Capture details recorded in a photo's EXIF metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoMetadata {
+    /// When the photo was taken as an ISO 8601 date and time, with a UTC
+    /// offset if the camera recorded one
+    pub taken_at: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    /// Ways the metadata disagrees with the photo's occurrence, if it was
+    /// checked against one
+    pub mismatches: Vec<MetadataMismatch>,
+}
+
+/// A disagreement between a photo's EXIF metadata and its occurrence
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataMismatch {
+    /// Occurrence term the photo disagrees with, e.g. eventDate
+    pub field: String,
+    pub message: String,
+}
+
+/// Reads EXIF metadata from the photo at `path`. Photos without any have
+/// empty metadata.
+pub fn read_photo_metadata(path: &Path) -> Result<PhotoMetadata> {
+    let file = std::fs::File::open(path).map_err(|source| ChuckError::FileOpen {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let exif = match Reader::new().read_from_container(&mut BufReader::new(file)) {
+        Ok(exif) => exif,
+        Err(::exif::Error::NotFound(_)) => return Ok(PhotoMetadata::default()),
+        Err(source) => return Err(ChuckError::Exif { path: path.to_path_buf(), source }),
+    };
+    let field = |tag| exif.get_field(tag, In::PRIMARY);
+
+    let taken_at = field(Tag::DateTimeOriginal)
+        .or_else(|| field(Tag::DateTime))
+        .and_then(|date_time| parse_date_time(date_time, field(Tag::OffsetTimeOriginal)));
+    let latitude = gps_coordinate(field(Tag::GPSLatitude), field(Tag::GPSLatitudeRef), b'S');
+    let longitude = gps_coordinate(field(Tag::GPSLongitude), field(Tag::GPSLongitudeRef), b'W');
+    Ok(PhotoMetadata {
+        taken_at,
+        latitude,
+        longitude,
+        camera_make: field(Tag::Make).and_then(ascii_value),
+        camera_model: field(Tag::Model).and_then(ascii_value),
+        mismatches: Vec::new(),
+    })
+}
+
+fn ascii_value(field: &Field) -> Option<String> {
+    match &field.value {
+        Value::Ascii(values) => values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).trim().to_string())
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    }
+}
+
+fn parse_date_time(field: &Field, offset: Option<&Field>) -> Option<String> {
+    let Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let mut date_time = DateTime::from_ascii(values.first()?).ok()?;
+    if let Some(Value::Ascii(offsets)) = offset.map(|offset| &offset.value)
+        && let Some(offset) = offsets.first()
+    {
+        // Offsets are optional, so a malformed one is dropped
+        let _ = date_time.parse_offset(offset);
+    }
+    let mut taken_at = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        date_time.year,
+        date_time.month,
+        date_time.day,
+        date_time.hour,
+        date_time.minute,
+        date_time.second,
+    );
+    if let Some(offset) = date_time.offset {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let minutes = offset.unsigned_abs();
+        taken_at.push_str(&format!("{sign}{:02}:{:02}", minutes / 60, minutes % 60));
+    }
+    Some(taken_at)
+}
+
+/// Decimal degrees from GPS degrees, minutes, and seconds, negative if the
+/// reference is `negative_ref`, i.e. south or west
+fn gps_coordinate(value: Option<&Field>, reference: Option<&Field>, negative_ref: u8) -> Option<f64> {
+    let Value::Rational(parts) = &value?.value else {
+        return None;
+    };
+    let degrees = parts
+        .iter()
+        .take(3)
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, divisor)| part.to_f64() / divisor)
+        .sum::<f64>();
+    if !degrees.is_finite() {
+        return None;
+    }
+    let negative = reference
+        .and_then(ascii_value)
+        .is_some_and(|reference| reference.as_bytes().first() == Some(&negative_ref));
+    Some(if negative { -degrees } else { degrees })
+}
+
+/// Ways `metadata` disagrees with the eventDate and coordinates of
+/// `occurrence`. Anything missing from either is skipped.
+pub fn check_against_occurrence(
+    metadata: &PhotoMetadata,
+    occurrence: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<MetadataMismatch> {
+    let mut mismatches = Vec::new();
+    let text = |term: &str| match occurrence.get(term)? {
+        serde_json::Value::String(value) => Some(value.clone()),
+        serde_json::Value::Null => None,
+        value => Some(value.to_string()),
+    };
+    let number = |term: &str| text(term)?.trim().parse::<f64>().ok();
+
+    let taken_on = metadata
+        .taken_at
+        .as_deref()
+        .and_then(|taken_at| NaiveDate::parse_from_str(taken_at.get(..10)?, "%Y-%m-%d").ok());
+    if let (Some(taken_on), Some(event_date)) = (taken_on, text("eventDate"))
+        && let Some((start, end)) = event_date_range(&event_date)
+    {
+        let days_before = (start - taken_on).num_days();
+        let days_after = (taken_on - end).num_days();
+        if days_before > MAX_DATE_DIFFERENCE_DAYS || days_after > MAX_DATE_DIFFERENCE_DAYS {
+            mismatches.push(MetadataMismatch {
+                field: "eventDate".to_string(),
+                message: format!("Photo was taken on {taken_on} but eventDate is {event_date}"),
+            });
+        }
+    }
+
+    if let (Some(lat), Some(lng), Some(occ_lat), Some(occ_lng)) = (
+        metadata.latitude,
+        metadata.longitude,
+        number("decimalLatitude"),
+        number("decimalLongitude"),
+    ) {
+        let tolerance = number("coordinateUncertaintyInMeters")
+            .unwrap_or_default()
+            .max(MIN_DISTANCE_TOLERANCE_METERS);
+        let distance = distance_meters(lat, lng, occ_lat, occ_lng);
+        if distance > tolerance {
+            mismatches.push(MetadataMismatch {
+                field: "coordinates".to_string(),
+                message: format!(
+                    "Photo was taken {:.1} km from the occurrence's coordinates",
+                    distance / 1000.0
+                ),
+            });
+        }
+    }
+    mismatches
+}
+
+/// First and last day of an ISO 8601 eventDate, which may be a single date
+/// or a range like 2024-05-01/2024-05-03
+fn event_date_range(event_date: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let parse = |value: &str| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok();
+    let (start, end) = event_date.split_once('/').unwrap_or((event_date, event_date));
+    let start = parse(start.trim())?;
+    Some((start, parse(end.trim()).unwrap_or(start)))
+}
+
+/// Great-circle distance between two points in meters
+fn distance_meters(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrence(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn metadata() -> PhotoMetadata {
+        PhotoMetadata {
+            taken_at: Some("2024-05-02T10:30:00".to_string()),
+            latitude: Some(37.7749),
+            longitude: Some(-122.4194),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matching_occurrence_has_no_mismatches() {
+        let occurrence = occurrence(serde_json::json!({
+            "eventDate": "2024-05-01/2024-05-03",
+            "decimalLatitude": 37.775,
+            "decimalLongitude": "-122.42",
+        }));
+
+        assert_eq!(check_against_occurrence(&metadata(), &occurrence), vec![]);
+    }
+
+    #[test]
+    fn test_flags_distant_dates_and_coordinates() {
+        let occurrence = occurrence(serde_json::json!({
+            "eventDate": "2023-05-02T10:30:00-07:00",
+            "decimalLatitude": 38.5,
+            "decimalLongitude": -122.4194,
+            "coordinateUncertaintyInMeters": 5000,
+        }));
+
+        let mismatches = check_against_occurrence(&metadata(), &occurrence);
+
+        let fields: Vec<&str> = mismatches.iter().map(|m| m.field.as_str()).collect();
+        assert_eq!(fields, vec!["eventDate", "coordinates"]);
+    }
+
+    #[test]
+    fn test_coordinate_uncertainty_widens_tolerance() {
+        let occurrence = occurrence(serde_json::json!({
+            "decimalLatitude": 37.8,
+            "decimalLongitude": -122.4194,
+            "coordinateUncertaintyInMeters": "5000",
+        }));
+
+        assert_eq!(check_against_occurrence(&metadata(), &occurrence), vec![]);
+    }
+
+    #[test]
+    fn test_read_photo_without_exif() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("photo.jpg");
+        image::RgbImage::new(4, 4).save(&path).unwrap();
+
+        assert_eq!(read_photo_metadata(&path).unwrap(), PhotoMetadata::default());
+    }
+}
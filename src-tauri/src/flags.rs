@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::{ChuckError, Result};
+
+/// Occurrences flagged for review, one row per (core ID, flag)
+pub const FLAGS_TABLE: &str = "flags";
+
+/// Filter key matching occurrences with a flag, e.g. flagged=suspect
+/// coordinates, flagged=true for any flag, or flagged=false for none
+pub const FLAGGED_FILTER: &str = "flagged";
+
+/// A label like "suspect coordinates" or "needs ID review" on an
+/// occurrence, with an optional note about what's wrong
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Flag {
+    pub core_id: String,
+    pub flag: String,
+    pub note: Option<String>,
+    /// When the flag was added or last changed, as an RFC 3339 timestamp in
+    /// UTC
+    pub flagged_at: String,
+}
+
+/// Whether any occurrences have ever been flagged
+pub fn has_flags(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [FLAGS_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Flags an occurrence, replacing the note of a flag it already has. Needs
+/// a read-write connection.
+pub fn flag_occurrence(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    core_id: &str,
+    flag: &str,
+    note: Option<&str>,
+) -> Result<Flag> {
+    let flag = flag.trim();
+    if flag.is_empty() {
+        return Err(ChuckError::Flag("flags need a label".to_string()));
+    }
+    let exists: bool = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) > 0 FROM occurrences WHERE CAST({} AS VARCHAR) = ?",
+            Database::quote_identifier(core_id_column),
+        ),
+        [core_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(ChuckError::Flag(format!("no occurrence has {core_id_column} {core_id}")));
+    }
+
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {FLAGS_TABLE} \
+         (core_id VARCHAR, flag VARCHAR, note VARCHAR, flagged_at VARCHAR)"
+    ))?;
+    let note = note.map(str::trim).filter(|n| !n.is_empty());
+    let flagged_at = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        &format!("DELETE FROM {FLAGS_TABLE} WHERE core_id = ? AND flag = ?"),
+        [core_id, flag],
+    )?;
+    conn.execute(
+        &format!("INSERT INTO {FLAGS_TABLE} VALUES (?, ?, ?, ?)"),
+        duckdb::params![core_id, flag, note, flagged_at],
+    )?;
+    Ok(Flag {
+        core_id: core_id.to_string(),
+        flag: flag.to_string(),
+        note: note.map(String::from),
+        flagged_at,
+    })
+}
+
+/// Removes a flag from an occurrence. Returns whether it had the flag.
+/// Needs a read-write connection.
+pub fn unflag_occurrence(conn: &duckdb::Connection, core_id: &str, flag: &str) -> Result<bool> {
+    if !has_flags(conn)? {
+        return Ok(false);
+    }
+    let removed = conn.execute(
+        &format!("DELETE FROM {FLAGS_TABLE} WHERE core_id = ? AND flag = ?"),
+        [core_id, flag.trim()],
+    )?;
+    Ok(removed > 0)
+}
+
+/// Lists flags by core ID and label, optionally only those on one
+/// occurrence. Archives that have never been flagged have none.
+pub fn list_flags(conn: &duckdb::Connection, core_id: Option<&str>) -> Result<Vec<Flag>> {
+    if !has_flags(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT core_id, flag, note, flagged_at FROM {FLAGS_TABLE} \
+         WHERE CAST(? AS VARCHAR) IS NULL OR core_id = ? \
+         ORDER BY core_id, flag"
+    ))?;
+    let flags = stmt
+        .query_map(duckdb::params![core_id, core_id], |row| {
+            Ok(Flag {
+                core_id: row.get(0)?,
+                flag: row.get(1)?,
+                note: row.get(2)?,
+                flagged_at: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(flags)
+}
+
+/// Writes all flags to a CSV with the core ID under its own column name,
+/// e.g. occurrenceID, so data providers can match them to their records.
+/// Returns the number of flags written.
+pub fn export_flags_csv(conn: &duckdb::Connection, core_id_column: &str, path: &Path) -> Result<usize> {
+    let source = if has_flags(conn)? {
+        format!("SELECT core_id, flag, note, flagged_at FROM {FLAGS_TABLE} ORDER BY core_id, flag")
+    } else {
+        "SELECT NULL::VARCHAR AS core_id, NULL::VARCHAR AS flag, NULL::VARCHAR AS note, \
+         NULL::VARCHAR AS flagged_at WHERE false"
+            .to_string()
+    };
+    let path = path.to_str().ok_or(ChuckError::PathEncoding)?.replace('\'', "''");
+    let count = conn.execute(
+        &format!(
+            "COPY (SELECT core_id AS {}, flag, note, flagged_at AS \"flaggedAt\" FROM ({source})) \
+             TO '{path}' (FORMAT csv, HEADER)",
+            Database::quote_identifier(core_id_column),
+        ),
+        [],
+    )?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences() -> duckdb::Connection {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES ('1', 'Quercus agrifolia'), ('2', 'Quercus lobata');"
+        ).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_flag_and_unflag_occurrence() {
+        let conn = occurrences();
+        assert!(!has_flags(&conn).unwrap());
+
+        flag_occurrence(&conn, "occurrenceID", "1", "suspect coordinates", None).unwrap();
+        flag_occurrence(&conn, "occurrenceID", "1", "suspect coordinates", Some("In the ocean"))
+            .unwrap();
+        flag_occurrence(&conn, "occurrenceID", "2", "needs ID review", Some(" ")).unwrap();
+
+        let flags = list_flags(&conn, None).unwrap();
+        assert_eq!(flags.len(), 2);
+        assert_eq!(flags[0].flag, "suspect coordinates");
+        assert_eq!(flags[0].note.as_deref(), Some("In the ocean"));
+        assert_eq!(flags[1].note, None);
+        assert_eq!(list_flags(&conn, Some("2")).unwrap().len(), 1);
+
+        assert!(unflag_occurrence(&conn, "2", "needs ID review").unwrap());
+        assert!(!unflag_occurrence(&conn, "2", "needs ID review").unwrap());
+        assert!(list_flags(&conn, Some("2")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_flag_occurrence_requires_occurrence_and_label() {
+        let conn = occurrences();
+
+        assert!(flag_occurrence(&conn, "occurrenceID", "3", "suspect coordinates", None).is_err());
+        assert!(flag_occurrence(&conn, "occurrenceID", "1", "  ", None).is_err());
+        assert!(!has_flags(&conn).unwrap());
+    }
+
+    #[test]
+    fn test_export_flags_csv() {
+        let conn = occurrences();
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("flags.csv");
+        flag_occurrence(&conn, "occurrenceID", "1", "suspect coordinates", Some("In the ocean, maybe"))
+            .unwrap();
+
+        assert_eq!(export_flags_csv(&conn, "occurrenceID", &path).unwrap(), 1);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some("occurrenceID,flag,note,flaggedAt"));
+        assert!(lines.next().unwrap().starts_with("1,suspect coordinates,\"In the ocean, maybe\","));
+    }
+}
@@ -0,0 +1,191 @@
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Rows DuckDB couldn't read from the archive's data files, e.g. because of
+/// an unescaped quote or too many columns, and so skipped while importing
+pub const IMPORT_ISSUES_TABLE: &str = "import_issues";
+
+/// Options to append to a read_csv call so bad rows are skipped and kept in
+/// DuckDB's reject tables instead of failing the whole import
+pub const STORE_REJECTS_OPTIONS: &str = ", store_rejects = true";
+
+/// A row skipped while importing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportIssue {
+    /// Name of the data file the row is in, e.g. occurrence.csv
+    pub file: String,
+    /// Table the row would have been imported into
+    pub table_name: String,
+    /// Line of the data file the row starts on, counting from 1
+    pub line: Option<u64>,
+    /// The column DuckDB was reading when it gave up, if it knows
+    pub column_name: Option<String>,
+    /// Kind of problem, e.g. UNQUOTED VALUE or TOO MANY COLUMNS
+    pub error_type: String,
+    /// The row as it appears in the file
+    pub csv_line: Option<String>,
+    pub message: String,
+}
+
+/// Whether the archive was imported with a record of skipped rows. Archives
+/// imported before rows were skipped don't have one.
+fn has_import_issues(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [IMPORT_ISSUES_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Drops DuckDB's reject tables so the next read_csv starts them over
+pub fn clear_rejects(conn: &duckdb::Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS reject_errors; DROP TABLE IF EXISTS reject_scans;")?;
+    Ok(())
+}
+
+/// Moves rows rejected by the last read_csv with STORE_REJECTS_OPTIONS into
+/// the import issues table. `line_offset` is added to line numbers, for
+/// reads of a chunk that starts partway through `file`. Returns the number
+/// of rows rejected.
+pub fn record_rejects(
+    conn: &duckdb::Connection,
+    file: &str,
+    table_name: &str,
+    line_offset: u64,
+) -> Result<usize> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {IMPORT_ISSUES_TABLE} \
+         (file VARCHAR, table_name VARCHAR, line UBIGINT, column_name VARCHAR, \
+          error_type VARCHAR, csv_line VARCHAR, message VARCHAR)"
+    ))?;
+    let rejects_exist: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM duckdb_tables() WHERE table_name = 'reject_errors'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !rejects_exist {
+        return Ok(0);
+    }
+    // One row can cause several errors, but only the first is of any use
+    // to someone fixing the file
+    let recorded = conn.execute(
+        &format!(
+            "INSERT INTO {IMPORT_ISSUES_TABLE} \
+             SELECT ?, ?, line + ?, column_name, CAST(error_type AS VARCHAR), csv_line, error_message \
+             FROM reject_errors \
+             QUALIFY row_number() OVER (PARTITION BY scan_id, file_id, line ORDER BY column_idx) = 1"
+        ),
+        duckdb::params![file, table_name, line_offset],
+    )?;
+    clear_rejects(conn)?;
+    Ok(recorded)
+}
+
+/// Columns of `table_name` that rows were skipped for because a value
+/// couldn't be cast to the column's type
+pub fn cast_error_columns(conn: &duckdb::Connection, table_name: &str) -> Result<Vec<String>> {
+    if !has_import_issues(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT DISTINCT column_name FROM {IMPORT_ISSUES_TABLE} \
+         WHERE table_name = ? AND error_type = 'CAST' AND column_name IS NOT NULL ORDER BY 1"
+    ))?;
+    let columns = stmt
+        .query_map([table_name], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// Forgets the skipped rows of `table_name`, e.g. before importing it again
+pub fn clear_import_issues(conn: &duckdb::Connection, table_name: &str) -> Result<()> {
+    if has_import_issues(conn)? {
+        conn.execute(&format!("DELETE FROM {IMPORT_ISSUES_TABLE} WHERE table_name = ?"), [table_name])?;
+    }
+    Ok(())
+}
+
+/// Lists skipped rows by file and line
+pub fn list_import_issues(conn: &duckdb::Connection) -> Result<Vec<ImportIssue>> {
+    if !has_import_issues(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT file, table_name, line, column_name, error_type, csv_line, message \
+         FROM {IMPORT_ISSUES_TABLE} ORDER BY file, line"
+    ))?;
+    let issues = stmt
+        .query_map([], |row| {
+            Ok(ImportIssue {
+                file: row.get(0)?,
+                table_name: row.get(1)?,
+                line: row.get(2)?,
+                column_name: row.get(3)?,
+                error_type: row.get(4)?,
+                csv_line: row.get(5)?,
+                message: row.get(6)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(issues)
+}
+
+/// Number of skipped rows in each file that had any
+pub fn count_by_file(conn: &duckdb::Connection) -> Result<Vec<(String, usize)>> {
+    if !has_import_issues(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT file, COUNT(*) FROM {IMPORT_ISSUES_TABLE} GROUP BY file ORDER BY file"
+    ))?;
+    let counts = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_rejects_keeps_bad_rows_with_file_line_numbers() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("occurrence.csv");
+        std::fs::write(
+            &path,
+            "id,name\n1,a\n2,b,extra\n3,c\n",
+        )
+        .unwrap();
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+
+        conn.execute_batch(&format!(
+            "CREATE TABLE occurrences AS SELECT * FROM read_csv('{}', all_varchar = true, \
+             delim = ',', header = true{STORE_REJECTS_OPTIONS})",
+            path.display()
+        ))
+        .unwrap();
+        let recorded = record_rejects(&conn, "occurrence.csv", "occurrences", 10).unwrap();
+
+        let imported: usize = conn
+            .query_row("SELECT COUNT(*) FROM occurrences", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(imported, 2);
+        assert_eq!(recorded, 1);
+        let issues = list_import_issues(&conn).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file, "occurrence.csv");
+        assert_eq!(issues[0].line, Some(13));
+        assert_eq!(issues[0].csv_line.as_deref(), Some("2,b,extra"));
+        assert_eq!(count_by_file(&conn).unwrap(), vec![("occurrence.csv".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_list_import_issues_without_table() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        assert!(list_import_issues(&conn).unwrap().is_empty());
+    }
+}
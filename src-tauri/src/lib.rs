@@ -1,11 +1,32 @@
+pub mod admin_boundaries;
+pub mod archive_stats;
+mod archive_watcher;
 mod basemap;
+pub mod citation;
 mod commands;
+pub mod country_boundaries;
 pub mod db;
 pub mod dwca;
+pub mod edits;
+pub mod enrichment;
 pub mod error;
+pub mod exclusions;
+pub mod exif;
+pub mod flags;
+pub mod import_issues;
+mod logging;
+pub mod media_licenses;
+mod media_server;
+pub mod multi_value;
+pub mod overlay;
+pub mod person_ids;
 mod photo_cache;
+pub mod quality;
+mod scheduler;
+pub mod taxonomy;
 pub mod tile_server;
 pub mod search_params;
+pub mod sentinels;
 
 use std::sync::Mutex;
 
@@ -22,51 +43,83 @@ struct OpenedFile(Mutex<Option<String>>);
 
 /// Holds a cached ZipArchive for fast photo extraction.
 /// Parsing the central directory of a large ZIP is expensive; keeping one open
-/// means we only pay that cost once instead of on every photo request.
-pub(crate) struct ZipState(pub Mutex<Option<zip::ZipArchive<std::fs::File>>>);
+/// means we only pay that cost once instead of on every photo request. Keyed
+/// by the storage dir of the archive the zip belongs to.
+pub(crate) struct ZipState(pub Mutex<Option<(std::path::PathBuf, zip::ZipArchive<std::fs::File>)>>);
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .plugin(
-            tauri_plugin_log::Builder::new()
-                .level(log::LevelFilter::Debug)
-                .level_for("mvt", log::LevelFilter::Info)
-                .level_for("h2", log::LevelFilter::Warn)
-                .level_for("hyper", log::LevelFilter::Warn)
-                .level_for("reqwest", log::LevelFilter::Warn)
-                .level_for("rustls", log::LevelFilter::Warn)
-                .targets([
-                    tauri_plugin_log::Target::new(
-                        tauri_plugin_log::TargetKind::LogDir {
-                            file_name: Some("chuck".to_string()),
-                        }
-                    ),
-                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stdout),
-                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Webview)
-                        .filter(|meta| meta.level() <= log::Level::Info),
-                ])
-                .build()
-        )
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(crate::tile_server::init())
         .plugin(crate::basemap::init())
+        .plugin(crate::media_server::init())
         .invoke_handler(tauri::generate_handler![
             commands::archive::open_archive,
+            commands::archive::cancel_open_archive,
             commands::archive::get_opened_file,
             commands::archive::current_archive,
+            commands::archive::list_open_archives,
+            commands::archive::close_archive,
+            commands::archive::reimport_archive,
             commands::archive::search,
+            commands::archive::materialize_filter,
+            commands::archive::get_archive_stats,
             commands::archive::get_autocomplete_suggestions,
+            commands::archive::get_column_values,
+            commands::archive::get_facets,
+            commands::archive::execute_sql,
             commands::archive::get_occurrence,
+            commands::archive::get_occurrences_at_point,
             commands::archive::get_photo,
+            commands::archive::get_photo_thumbnail,
+            commands::archive::get_photo_metadata,
+            commands::archive::get_media,
             commands::archive::aggregate_by_field,
+            commands::archive::get_group_examples,
+            commands::archive::aggregate_by_time,
+            commands::archive::get_time_series,
+            commands::archive::get_species_accumulation,
+            commands::archive::get_grid_summary,
+            commands::archive::get_collector_summary,
+            commands::archive::aggregate_by_two_fields,
+            commands::archive::column_stats,
+            commands::archive::run_quality_report,
+            commands::archive::get_enrichments,
+            commands::archive::enrich_vernacular_names,
+            commands::archive::cancel_enrichment,
+            commands::archive::enrich_admin_areas,
+            commands::archive::enrich_elevation,
+            commands::archive::split_multi_value_fields,
+            commands::archive::load_taxonomy,
+            commands::archive::map_sentinel_values,
+            commands::archive::restore_sentinel_values,
+            commands::archive::update_occurrence_field,
+            commands::archive::list_edits,
+            commands::archive::flag_occurrence,
+            commands::archive::unflag_occurrence,
+            commands::archive::list_flags,
+            commands::archive::unlock_archive,
+            commands::archive::lock_archive,
             commands::archive::get_archive_metadata,
+            commands::archive::get_eml,
+            commands::archive::get_citation,
+            commands::archive::get_media_license_summary,
+            commands::archive::exclude_occurrences,
+            commands::archive::restore_exclusions,
+            commands::archive::list_exclusions,
+            commands::archive::update_eml,
+            commands::archive::get_import_warnings,
+            commands::archive::get_import_issues,
             commands::archive::save_text_file,
             commands::inat_download::get_observation_count,
             commands::inat_download::estimate_media_count,
             commands::inat_download::generate_inat_archive,
             commands::inat_download::cancel_inat_archive,
+            commands::inat_download::suggest_archive_path,
+            commands::inat_download::get_taxon_suggestions,
             commands::inat_download::parse_inat_url,
             commands::inat_download::read_chuck_archive_info,
             commands::inat_download::get_update_observation_count,
@@ -75,25 +128,70 @@ pub fn run() {
             commands::inat_auth::inat_get_auth_status,
             commands::inat_auth::inat_sign_out,
             commands::inat_auth::inat_get_jwt,
+            commands::scheduler::list_schedules,
+            commands::scheduler::add_schedule,
+            commands::scheduler::remove_schedule,
+            commands::scheduler::set_schedule_enabled,
+            commands::scheduler::get_schedule_history,
+            commands::scheduler::run_schedule_now,
+            commands::schema::get_command_schema,
+            commands::settings::get_network_settings,
+            commands::settings::set_network_settings,
+            commands::settings::get_storage_settings,
+            commands::settings::set_storage_settings,
+            commands::settings::get_import_settings,
+            commands::settings::set_import_settings,
+            commands::storage::get_storage_usage,
+            commands::storage::clear_caches,
+            commands::diagnostics::get_diagnostics,
             commands::export::export_csv,
             commands::export::export_kml,
             commands::export::export_dwca,
+            commands::export::export_pmtiles,
+            commands::export::export_parquet,
+            commands::export::export_sqlite,
+            commands::export::export_flags_csv,
             commands::export::export_groups_csv,
             basemap::commands::list_basemaps,
             basemap::commands::download_basemap,
             basemap::commands::download_regional_basemap,
+            basemap::commands::download_raster_basemap,
+            basemap::commands::download_dem,
+            basemap::commands::import_basemap,
             basemap::commands::estimate_regional_size,
             basemap::commands::cancel_basemap_download,
             basemap::commands::delete_basemap,
             basemap::commands::reverse_geocode,
+            basemap::commands::download_gazetteer,
+            basemap::commands::download_boundaries,
+            basemap::commands::search_places,
         ])
         .setup(|app| {
+            // Start logging before applying settings so problems with them are recorded
+            logging::init(app.handle())?;
+
+            // Apply certificate settings before any HTTP client is built
+            if let Err(e) = commands::settings::apply_network_settings(app.handle()) {
+                log::warn!("Failed to apply network settings: {e}");
+            }
+            if let Err(e) = commands::settings::apply_storage_settings(app.handle()) {
+                log::warn!("Failed to apply storage settings: {e}");
+            }
+
             // Initialize auth cache (lazy - won't access keychain until first use)
             app.manage(AuthCache::new());
 
             // Initialize zip state (populated on first archive open or photo request)
             app.manage(ZipState(Mutex::new(None)));
 
+            // Initialize the watcher for the open archive's zip (started when
+            // an archive is opened)
+            app.manage(archive_watcher::ArchiveWatcher::default());
+
+            // Start running scheduled archive updates
+            app.manage(scheduler::Scheduler::default());
+            scheduler::start(app.handle().clone());
+
             // Check CLI args for a file path (Windows/Linux file association)
             let opened_file = std::env::args()
                 .nth(1)
@@ -108,10 +206,23 @@ pub fn run() {
             let export_kml_item = MenuItemBuilder::with_id("export-kml", "KML...").build(app)?;
             let export_dwca_item =
                 MenuItemBuilder::with_id("export-dwca", "DarwinCore Archive...").build(app)?;
+            let export_dwca_ipt_item =
+                MenuItemBuilder::with_id("export-dwca-ipt", "DarwinCore Archive for IPT...")
+                    .build(app)?;
+            let export_pmtiles_item =
+                MenuItemBuilder::with_id("export-pmtiles", "PMTiles...").build(app)?;
+            let export_parquet_item =
+                MenuItemBuilder::with_id("export-parquet", "Parquet...").build(app)?;
+            let export_sqlite_item =
+                MenuItemBuilder::with_id("export-sqlite", "SQLite Database...").build(app)?;
             let export_submenu = SubmenuBuilder::new(app, "Export occurrences")
                 .item(&export_csv_item)
                 .item(&export_kml_item)
                 .item(&export_dwca_item)
+                .item(&export_dwca_ipt_item)
+                .item(&export_pmtiles_item)
+                .item(&export_parquet_item)
+                .item(&export_sqlite_item)
                 .build()?;
 
             let download_item = MenuItemBuilder::with_id(
@@ -309,6 +420,14 @@ pub fn run() {
                     app.emit("menu-export-kml", ()).unwrap();
                 } else if event.id() == "export-dwca" {
                     app.emit("menu-export-dwca", ()).unwrap();
+                } else if event.id() == "export-dwca-ipt" {
+                    app.emit("menu-export-dwca-ipt", ()).unwrap();
+                } else if event.id() == "export-pmtiles" {
+                    app.emit("menu-export-pmtiles", ()).unwrap();
+                } else if event.id() == "export-parquet" {
+                    app.emit("menu-export-parquet", ()).unwrap();
+                } else if event.id() == "export-sqlite" {
+                    app.emit("menu-export-sqlite", ()).unwrap();
                 } else if event.id() == "show-logs" {
                     app.emit("menu-show-logs", ()).unwrap();
                 } else if event.id() == "show-metadata" {
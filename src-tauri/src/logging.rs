@@ -0,0 +1,249 @@
+//! Logging setup. Records go to stdout and the webview as text, and to
+//! logs/chuck.jsonl in the app data dir as one JSON object per line, with
+//! any key-value fields the log call attached, e.g.
+//! `log::info!(count = 3; "Exported occurrences")`. Recent warnings and
+//! errors are also kept in memory so they can be included in diagnostics.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::Manager;
+
+use crate::error::{ChuckError, Result};
+
+pub const LOG_FILENAME: &str = "chuck.jsonl";
+
+/// Size past which the log is moved aside at startup and a new one started
+const MAX_LOG_BYTES: u64 = 10_000_000;
+
+/// Warnings and errors kept for diagnostics, oldest dropped first
+const MAX_RECENT_ERRORS: usize = 50;
+
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+static RECENT_ERRORS: LazyLock<Mutex<VecDeque<LogEntry>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// A log record as written to the JSON log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+/// Collects a record's key-value fields as JSON, keeping numbers and
+/// booleans as such
+struct Fields(Map<String, Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for Fields {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> std::result::Result<(), log::kv::Error> {
+        let json = if let Some(n) = value.to_u64() {
+            Value::from(n)
+        } else if let Some(n) = value.to_i64() {
+            Value::from(n)
+        } else if let Some(n) = value.to_f64() {
+            Value::from(n)
+        } else if let Some(b) = value.to_bool() {
+            Value::from(b)
+        } else {
+            Value::from(value.to_string())
+        };
+        self.0.insert(key.to_string(), json);
+        Ok(())
+    }
+}
+
+impl LogEntry {
+    fn from_record(record: &log::Record, timestamp: DateTime<Utc>) -> Self {
+        let mut fields = Fields(Map::new());
+        let _ = record.key_values().visit(&mut fields);
+        Self {
+            timestamp,
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            fields: fields.0,
+        }
+    }
+}
+
+fn push_recent(recent: &mut VecDeque<LogEntry>, entry: LogEntry) {
+    recent.push_back(entry);
+    while recent.len() > MAX_RECENT_ERRORS {
+        recent.pop_front();
+    }
+}
+
+/// Warnings and errors logged since startup, oldest first
+pub fn recent_errors() -> Vec<LogEntry> {
+    RECENT_ERRORS
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Where the JSON log is written, once logging has started
+pub fn log_path() -> Option<&'static Path> {
+    LOG_PATH.get().map(PathBuf::as_path)
+}
+
+/// Writes records to the JSON log before passing them on to the log
+/// plugin's logger
+struct JsonLogger {
+    inner: Box<dyn log::Log>,
+    file: Mutex<File>,
+}
+
+impl log::Log for JsonLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogEntry::from_record(record, Utc::now());
+        if let Ok(json) = serde_json::to_string(&entry)
+            && let Ok(mut file) = self.file.lock()
+        {
+            let _ = writeln!(file, "{json}");
+        }
+        if record.level() <= log::Level::Warn
+            && let Ok(mut recent) = RECENT_ERRORS.lock()
+        {
+            push_recent(&mut recent, entry);
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+        self.inner.flush();
+    }
+}
+
+/// Opens the JSON log for appending, first moving a log that's grown past
+/// MAX_LOG_BYTES to chuck.1.jsonl
+fn open_log(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|source| ChuckError::DirectoryCreate {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    if std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_LOG_BYTES) {
+        let _ = std::fs::rename(path, path.with_extension("1.jsonl"));
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|source| ChuckError::FileOpen { path: path.to_path_buf(), source })
+}
+
+/// Registers the log plugin and starts logging. Call this first thing in
+/// setup so nothing logged at startup is lost.
+pub(crate) fn init<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<()> {
+    let (plugin, max_level, inner) = tauri_plugin_log::Builder::new()
+        .level(log::LevelFilter::Debug)
+        .level_for("mvt", log::LevelFilter::Info)
+        .level_for("h2", log::LevelFilter::Warn)
+        .level_for("hyper", log::LevelFilter::Warn)
+        .level_for("reqwest", log::LevelFilter::Warn)
+        .level_for("rustls", log::LevelFilter::Warn)
+        .targets([
+            tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stdout),
+            tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Webview)
+                .filter(|meta| meta.level() <= log::Level::Info),
+        ])
+        .split(app)
+        .map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    app.plugin(plugin).map_err(|e| ChuckError::Tauri(e.to_string()))?;
+
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    let path = data_dir.join("logs").join(LOG_FILENAME);
+    let file = open_log(&path)?;
+    let _ = LOG_PATH.set(path);
+
+    log::set_boxed_logger(Box::new(JsonLogger { inner, file: Mutex::new(file) }))
+        .map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_entry_includes_fields() {
+        let fields: &[(&str, log::kv::Value)] = &[
+            ("count", log::kv::Value::from(3u64)),
+            ("path", log::kv::Value::from("/tmp/out.csv")),
+        ];
+        let timestamp: DateTime<Utc> = "2025-03-01T09:00:00Z".parse().unwrap();
+
+        let entry = LogEntry::from_record(
+            &log::Record::builder()
+                .args(format_args!("Exported occurrences"))
+                .level(log::Level::Info)
+                .target("chuck_lib::commands::export")
+                .key_values(&fields)
+                .build(),
+            timestamp,
+        );
+
+        let json = serde_json::to_value(entry).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timestamp": "2025-03-01T09:00:00Z",
+                "level": "INFO",
+                "target": "chuck_lib::commands::export",
+                "message": "Exported occurrences",
+                "fields": {"count": 3, "path": "/tmp/out.csv"},
+            })
+        );
+    }
+
+    #[test]
+    fn test_push_recent_drops_oldest() {
+        let entry = |n: usize| LogEntry {
+            timestamp: Utc::now(),
+            level: "WARN".to_string(),
+            target: "chuck_lib".to_string(),
+            message: n.to_string(),
+            fields: Map::new(),
+        };
+        let mut recent = VecDeque::new();
+
+        for n in 0..=MAX_RECENT_ERRORS {
+            push_recent(&mut recent, entry(n));
+        }
+
+        assert_eq!(recent.len(), MAX_RECENT_ERRORS);
+        assert_eq!(recent.front().unwrap().message, "1");
+    }
+}
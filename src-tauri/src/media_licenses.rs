@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::search_params::SearchParams;
+
+/// Media columns that can hold a license, in order of preference. Simple
+/// Multimedia uses dcterms:license and Audubon Core dcterms:rights or
+/// xmpRights:UsageTerms.
+pub const MEDIA_LICENSE_COLUMNS: &[&str] = &["license", "rights", "usageTerms"];
+
+/// Media columns naming who to credit, in order of preference
+pub const MEDIA_CREATOR_COLUMNS: &[&str] = &["creator", "owner", "rightsHolder"];
+
+/// Code for media without a license
+pub const NO_LICENSE: &str = "none";
+
+/// Number of media rows with a license
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenseCount {
+    /// License code from license_code, or NO_LICENSE
+    pub license: String,
+    pub count: i64,
+}
+
+/// Normalizes the ways archives write licenses to one code per license:
+/// Creative Commons URLs like http://creativecommons.org/licenses/by-nc/4.0/,
+/// GBIF codes like CC_BY_NC_4_0, and iNat codes like cc-by-nc all become
+/// CC-BY-NC. Public domain dedications become CC0, "All rights reserved"
+/// becomes ARR, and blanks become NO_LICENSE. Anything else is kept, trimmed.
+pub fn license_code(license: &str) -> String {
+    let trimmed = license.trim();
+    if trimmed.is_empty() {
+        return NO_LICENSE.to_string();
+    }
+    let lower = trimmed.to_lowercase();
+    if lower.contains("publicdomain/zero") || lower.starts_with("cc0") || lower.starts_with("cc-0") {
+        return "CC0".to_string();
+    }
+    if lower.contains("publicdomain/mark") || lower == "pd" || lower == "public domain" {
+        return "PD".to_string();
+    }
+    if lower == "arr" || lower.contains("all rights reserved") {
+        return "ARR".to_string();
+    }
+    // The license elements, from the URL path or the code itself
+    let elements = match lower.find("creativecommons.org/licenses/") {
+        Some(start) => lower[start + "creativecommons.org/licenses/".len()..]
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        None => match lower.strip_prefix("cc") {
+            Some(rest) => rest.to_string(),
+            None => return trimmed.to_string(),
+        },
+    };
+    let elements: Vec<String> = elements
+        .split(|c: char| c == '-' || c == '_' || c.is_whitespace())
+        .filter(|element| ["by", "nc", "sa", "nd"].contains(element))
+        .map(str::to_uppercase)
+        .collect();
+    if elements.first().map(String::as_str) != Some("BY") {
+        return trimmed.to_string();
+    }
+    format!("CC-{}", elements.join("-"))
+}
+
+/// License codes of the media of occurrences matching `search_params`, most
+/// common first. Media tables without a license column count as NO_LICENSE.
+pub fn media_license_summary(db: &Database, search_params: SearchParams) -> Result<Vec<LicenseCount>> {
+    let (_, where_clause, where_interpolations, _) =
+        db.query_parts(search_params, None, db.core_id_column(), db.extension_tables())?;
+    let quoted_core_id = Database::quote_identifier(db.core_id_column());
+    let occurrences = db.occurrences_source();
+    let param_refs: Vec<&dyn duckdb::ToSql> = where_interpolations.iter().map(|p| p.as_ref()).collect();
+
+    let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+    for (extension, ext_core_id) in db.extension_tables() {
+        if !matches!(
+            extension,
+            chuck_core::DwcaExtension::SimpleMultimedia | chuck_core::DwcaExtension::Audiovisual
+        ) {
+            continue;
+        }
+        let table_name = extension.table_name();
+        let columns = Database::get_column_names(db.connection(), table_name)?;
+        let license = MEDIA_LICENSE_COLUMNS
+            .iter()
+            .find(|candidate| columns.iter().any(|c| c == *candidate))
+            .map_or("NULL".to_string(), |column| Database::quote_identifier(column));
+        let sql = format!(
+            "SELECT CAST({license} AS VARCHAR), COUNT(*) FROM {table_name} \
+             WHERE {} IN (SELECT {quoted_core_id} FROM {occurrences}{where_clause}) GROUP BY 1",
+            Database::quote_identifier(ext_core_id)
+        );
+        let mut stmt = db.connection().prepare(&sql)?;
+        let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        for row in rows {
+            let (license, count) = row?;
+            *counts.entry(license_code(license.as_deref().unwrap_or_default())).or_default() += count;
+        }
+    }
+
+    let mut summary: Vec<LicenseCount> = counts
+        .into_iter()
+        .map(|(license, count)| LicenseCount { license, count })
+        .collect();
+    summary.sort_by(|a, b| b.count.cmp(&a.count));
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_code() {
+        assert_eq!(license_code("http://creativecommons.org/licenses/by-nc/4.0/"), "CC-BY-NC");
+        assert_eq!(license_code("CC_BY_NC_4_0"), "CC-BY-NC");
+        assert_eq!(license_code("cc-by-sa"), "CC-BY-SA");
+        assert_eq!(license_code("CC BY 4.0"), "CC-BY");
+        assert_eq!(license_code("https://creativecommons.org/publicdomain/zero/1.0/"), "CC0");
+        assert_eq!(license_code("All rights reserved"), "ARR");
+        assert_eq!(license_code("  "), NO_LICENSE);
+        assert_eq!(license_code("Custom terms"), "Custom terms");
+    }
+}
@@ -0,0 +1,169 @@
+//! The media:// protocol, which serves photos and sounds from the archive or
+//! remote URLs, e.g. media://localhost/?path=media/1234.mp3. Responses have
+//! MIME types from the media's path and honor range requests, which audio
+//! players need to seek. An `archive_id` query param serves media from an
+//! archive other than the current one.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use tauri::http::header::{CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::Runtime;
+use url::Url;
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("media-server")
+        .register_asynchronous_uri_scheme_protocol("media", handle_media_request)
+        .build()
+}
+
+/// MIME type of a photo or sound, from the extension of its path or URL
+pub fn mime_type(media_path: &str) -> &'static str {
+    let path = media_path.split(['?', '#']).next().unwrap_or(media_path);
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "tif" | "tiff" => "image/tiff",
+        "mp3" => "audio/mpeg",
+        "m4a" | "mp4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "wav" => "audio/wav",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        "weba" | "webm" => "audio/webm",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// First and last byte of a Range header like "bytes=100-199" within a file
+/// of `len` bytes. Only single ranges are supported; anything else is
+/// answered with the whole file.
+fn parse_range(header: &str, len: u64) -> Option<(u64, u64)> {
+    let (start, end) = header.strip_prefix("bytes=")?.split_once('-')?;
+    if len == 0 || end.contains(',') {
+        return None;
+    }
+    let (start, end) = match (start.trim(), end.trim()) {
+        // The last `end` bytes
+        ("", suffix) => (len.saturating_sub(suffix.parse().ok()?), len - 1),
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    (start <= end).then_some((start, end))
+}
+
+fn handle_media_request<R: Runtime>(
+    ctx: tauri::UriSchemeContext<'_, R>,
+    request: tauri::http::Request<Vec<u8>>,
+    responder: tauri::UriSchemeResponder,
+) {
+    let app_handle = ctx.app_handle().clone();
+
+    tauri::async_runtime::spawn(async move {
+        let url = Url::parse(&request.uri().to_string()).ok();
+        let query_value = |name: &str| {
+            url.as_ref().and_then(|url| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+            })
+        };
+        let media_path = query_value("path");
+        let archive_id = query_value("archive_id");
+        let Some(media_path) = media_path else {
+            responder.respond(error_response(400, "Missing media path".to_string()));
+            return;
+        };
+        let range = request
+            .headers()
+            .get(RANGE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let cached =
+            crate::commands::archive::cache_media(app_handle, &media_path, archive_id.as_deref())
+                .await;
+        let response = match cached {
+            Ok(path) => file_response(&path, mime_type(&media_path), range.as_deref()),
+            Err(e) => {
+                log::error!("Failed to load media {media_path}: {e}");
+                error_response(404, e.to_string())
+            }
+        };
+        responder.respond(response);
+    });
+}
+
+/// Responds with the file at `path`, or the part of it `range` asks for
+fn file_response(
+    path: &Path,
+    mime_type: &str,
+    range: Option<&str>,
+) -> tauri::http::Response<Vec<u8>> {
+    let read = || -> std::io::Result<tauri::http::Response<Vec<u8>>> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        let builder = tauri::http::Response::builder()
+            .header(CONTENT_TYPE, mime_type)
+            .header("Accept-Ranges", "bytes")
+            .header("Access-Control-Allow-Origin", "*");
+        let Some((start, end)) = range.and_then(|range| parse_range(range, len)) else {
+            let mut body = Vec::with_capacity(len as usize);
+            file.read_to_end(&mut body)?;
+            return Ok(builder.status(200).body(body).unwrap());
+        };
+        let mut body = vec![0; (end - start + 1) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut body)?;
+        Ok(builder
+            .status(206)
+            .header(CONTENT_RANGE, format!("bytes {start}-{end}/{len}"))
+            .body(body)
+            .unwrap())
+    };
+    read().unwrap_or_else(|e| {
+        log::error!("Failed to read {}: {e}", path.display());
+        error_response(500, e.to_string())
+    })
+}
+
+fn error_response(status: u16, message: String) -> tauri::http::Response<Vec<u8>> {
+    tauri::http::Response::builder()
+        .status(status)
+        .header("Access-Control-Allow-Origin", "*")
+        .body(message.into_bytes())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_type() {
+        assert_eq!(mime_type("media/1234.MP3"), "audio/mpeg");
+        assert_eq!(mime_type("https://static.inaturalist.org/sounds/1.m4a?1700000000"), "audio/mp4");
+        assert_eq!(mime_type("media/photo.jpeg"), "image/jpeg");
+        assert_eq!(mime_type("media/unknown"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=900-", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=500-5000", 1000), Some((500, 999)));
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+}
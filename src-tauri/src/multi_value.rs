@@ -0,0 +1,128 @@
+use crate::db::Database;
+use crate::error::Result;
+
+/// Long-format table of the individual values in multi-value fields, one row
+/// per (core ID, field, value)
+pub const MULTI_VALUES_TABLE: &str = "multi_values";
+
+/// Fields that often hold several values delimited by | or ;, e.g.
+/// "W. L. Jepson | H. M. Hall"
+pub const MULTI_VALUE_FIELDS: &[&str] = &[
+    "associatedTaxa",
+    "identifiedBy",
+    "otherCatalogNumbers",
+    "recordedBy",
+];
+
+pub fn is_multi_value_field(name: &str) -> bool {
+    MULTI_VALUE_FIELDS.contains(&name)
+}
+
+/// SQL expression splitting a quoted column (or expression) into a list of
+/// values. Values at the ends still need trimming and empty ones need
+/// skipping.
+pub fn split_values_sql(quoted_column: &str) -> String {
+    format!(r"regexp_split_to_array({quoted_column}, '\s*[|;]\s*')")
+}
+
+/// Whether the database has been normalized with split_multi_value_fields
+pub fn has_multi_values(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [MULTI_VALUES_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Splits the multi-value fields present in occurrences into the
+/// multi_values table, replacing it if it already exists, and returns the
+/// names of the fields that were split. Hand edits are split like the
+/// archive's own values, and occurrences are left as they are.
+/// Needs a read-write connection.
+pub fn split_multi_value_fields(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT column_name FROM information_schema.columns WHERE table_name = 'occurrences'"
+    )?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let fields: Vec<String> = MULTI_VALUE_FIELDS
+        .iter()
+        .filter(|field| columns.iter().any(|c| c == *field))
+        .map(|field| field.to_string())
+        .collect();
+
+    let quoted_core_id = Database::quote_identifier(core_id_column);
+    let occurrences = crate::overlay::occurrences_source(conn, core_id_column)?;
+    let selects: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let quoted_field = Database::quote_identifier(field);
+            let split = split_values_sql(&quoted_field);
+            format!(
+                "SELECT {quoted_core_id} AS core_id, '{field}' AS field, unnest({split}) AS value \
+                 FROM {occurrences} WHERE {quoted_field} IS NOT NULL"
+            )
+        })
+        .collect();
+    let source = if selects.is_empty() {
+        "SELECT NULL::VARCHAR AS core_id, NULL::VARCHAR AS field, NULL::VARCHAR AS value WHERE false"
+            .to_string()
+    } else {
+        selects.join(" UNION ALL ")
+    };
+
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TABLE {MULTI_VALUES_TABLE} AS \
+         SELECT core_id, field, trim(value) AS value FROM ({source}) WHERE trim(value) != '';
+         CREATE INDEX IF NOT EXISTS idx_multi_values_field_value ON {MULTI_VALUES_TABLE}(field, value);"
+    ))?;
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_multi_value_fields() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR, otherCatalogNumbers VARCHAR);
+             INSERT INTO occurrences VALUES ('1', 'W. L. Jepson | H. M. Hall', 'A-1;B-2');
+             INSERT INTO occurrences VALUES ('2', 'W. L. Jepson', NULL);
+             INSERT INTO occurrences VALUES ('3', ' | ', '');"
+        ).unwrap();
+        assert!(!has_multi_values(&conn).unwrap());
+
+        let fields = split_multi_value_fields(&conn, "occurrenceID").unwrap();
+
+        assert_eq!(fields, vec!["otherCatalogNumbers", "recordedBy"]);
+        assert!(has_multi_values(&conn).unwrap());
+        let rows: Vec<(String, String, String)> = conn
+            .prepare("SELECT core_id, field, value FROM multi_values ORDER BY field, core_id, value")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        let expected = [
+            ("1", "otherCatalogNumbers", "A-1"),
+            ("1", "otherCatalogNumbers", "B-2"),
+            ("1", "recordedBy", "H. M. Hall"),
+            ("1", "recordedBy", "W. L. Jepson"),
+            ("2", "recordedBy", "W. L. Jepson"),
+        ];
+        assert_eq!(
+            rows,
+            expected
+                .iter()
+                .map(|(a, b, c)| (a.to_string(), b.to_string(), c.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
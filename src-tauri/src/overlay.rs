@@ -0,0 +1,234 @@
+use crate::db::Database;
+use crate::error::Result;
+
+/// Occurrences as queries and exports should see them: the archive's own
+/// values with mapped sentinels read as NULL, empty fields filled with values
+/// derived by enrichments, and hand edits applied on top, so an edit wins
+/// over everything else. The occurrences table itself is
+/// never changed, so the original values are always there to compare with or
+/// go back to. Derived columns the archive doesn't have are added as VARCHAR.
+/// Returns a FROM item named occurrences, which is just the table when
+/// there's nothing to overlay.
+pub fn occurrences_source(conn: &duckdb::Connection, core_id_column: &str) -> Result<String> {
+    let mut stmt = conn.prepare(
+        "SELECT column_name, data_type FROM information_schema.columns \
+         WHERE table_name = 'occurrences' ORDER BY ordinal_position"
+    )?;
+    let mut column_types: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let derived_columns = crate::enrichment::derived_columns(conn)?;
+    let added: Vec<String> = derived_columns
+        .iter()
+        .filter(|column| !column_types.iter().any(|(c, _)| c == *column))
+        .cloned()
+        .collect();
+    column_types.extend(added.iter().map(|column| (column.clone(), "VARCHAR".to_string())));
+    let column_type = |column: &str| {
+        column_types.iter().find(|(c, _)| c == column).map(|(_, t)| t.clone())
+    };
+    let core_id = format!("CAST(occurrences.{} AS VARCHAR)", Database::quote_identifier(core_id_column));
+
+    let mut overlay = Overlay { added, ..Overlay::default() };
+    overlay.map_sentinels(conn)?;
+    if !derived_columns.is_empty() {
+        overlay.joins.push(format!(
+            " LEFT JOIN {} derived ON derived.core_id = {core_id}",
+            crate::enrichment::DERIVED_VALUES_TABLE,
+        ));
+    }
+    for column in &derived_columns {
+        let Some(column_type) = column_type(column) else {
+            continue;
+        };
+        let value = overlay.value(column);
+        overlay.set(column, format!(
+            "CASE WHEN {value} IS NULL OR trim(CAST({value} AS VARCHAR)) = '' \
+             THEN TRY_CAST(derived.{} AS {column_type}) ELSE {value} END",
+            Database::quote_identifier(column),
+        ));
+    }
+    for (i, column) in crate::edits::edited_columns(conn)?.iter().enumerate() {
+        let Some(column_type) = column_type(column) else {
+            continue;
+        };
+        let alias = format!("edit_{i}");
+        overlay.joins.push(format!(
+            " LEFT JOIN ({}) {alias} ON {alias}.core_id = {core_id}",
+            crate::edits::latest_edits_query(column),
+        ));
+        let value = overlay.value(column);
+        overlay.set(column, format!(
+            "CASE WHEN {alias}.core_id IS NOT NULL \
+             THEN TRY_CAST({alias}.new_value AS {column_type}) ELSE {value} END"
+        ));
+    }
+    Ok(overlay.source())
+}
+
+/// Occurrences with just mapped sentinels read as NULL, i.e. the archive's
+/// own values without derived values or edits. Returns a FROM item named
+/// occurrences like occurrences_source.
+pub fn archive_values_source(conn: &duckdb::Connection) -> Result<String> {
+    let mut overlay = Overlay::default();
+    overlay.map_sentinels(conn)?;
+    Ok(overlay.source())
+}
+
+/// SQL expressions replacing occurrences columns, or adding ones it doesn't
+/// have, and the joins they need
+#[derive(Default)]
+struct Overlay {
+    values: Vec<(String, String)>,
+    joins: Vec<String>,
+    /// Columns that aren't in occurrences
+    added: Vec<String>,
+}
+
+impl Overlay {
+    /// Expression for a column so far, so overlays can build on each other
+    fn value(&self, column: &str) -> String {
+        self.values
+            .iter()
+            .find(|(c, _)| c == column)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_else(|| {
+                if self.added.iter().any(|c| c == column) {
+                    "NULL".to_string()
+                } else {
+                    format!("occurrences.{}", Database::quote_identifier(column))
+                }
+            })
+    }
+
+    /// Reads values mapped as sentinels as NULL
+    fn map_sentinels(&mut self, conn: &duckdb::Connection) -> Result<()> {
+        for (column, values) in crate::sentinels::mapped_sentinels(conn)? {
+            let value = self.value(column);
+            self.set(column, format!(
+                "CASE WHEN {} THEN NULL ELSE {value} END",
+                crate::sentinels::value_condition(&value, values)
+            ));
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, column: &str, value: String) {
+        match self.values.iter_mut().find(|(c, _)| c == column) {
+            Some((_, existing)) => *existing = value,
+            None => self.values.push((column.to_string(), value)),
+        }
+    }
+
+    fn source(self) -> String {
+        if self.values.is_empty() {
+            return "occurrences".to_string();
+        }
+        let (added, replaced): (Vec<_>, Vec<_>) = self
+            .values
+            .iter()
+            .map(|(column, value)| (column, format!("{value} AS {}", Database::quote_identifier(column))))
+            .partition(|(column, _)| self.added.contains(*column));
+        let mut select = "occurrences.*".to_string();
+        if !replaced.is_empty() {
+            let replacements: Vec<String> = replaced.into_iter().map(|(_, value)| value).collect();
+            select.push_str(&format!(" REPLACE ({})", replacements.join(", ")));
+        }
+        for (_, value) in added {
+            select.push_str(&format!(", {value}"));
+        }
+        format!("(SELECT {select} FROM occurrences{}) AS occurrences", self.joins.concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_occurrences_source_without_overlays() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR)").unwrap();
+
+        assert_eq!(occurrences_source(&conn, "occurrenceID").unwrap(), "occurrences");
+    }
+
+    #[test]
+    fn test_occurrences_source_applies_latest_edits() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedBy VARCHAR, decimalLatitude DOUBLE);
+             INSERT INTO occurrences VALUES ('1', 'Jepsen', 37.5);
+             INSERT INTO occurrences VALUES ('2', 'Hall', NULL);"
+        ).unwrap();
+        crate::edits::update_occurrence_field(&conn, "occurrenceID", "1", "recordedBy", Some("Jepson")).unwrap();
+        crate::edits::update_occurrence_field(&conn, "occurrenceID", "1", "recordedBy", Some("W. L. Jepson")).unwrap();
+        crate::edits::update_occurrence_field(&conn, "occurrenceID", "2", "decimalLatitude", Some("38.25")).unwrap();
+
+        let source = occurrences_source(&conn, "occurrenceID").unwrap();
+        let rows: Vec<(String, Option<f64>)> = conn
+            .prepare(&format!("SELECT recordedBy, decimalLatitude FROM {source} ORDER BY occurrenceID"))
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(rows, vec![
+            ("W. L. Jepson".to_string(), Some(37.5)),
+            ("Hall".to_string(), Some(38.25)),
+        ]);
+    }
+
+    #[test]
+    fn test_occurrences_source_adds_derived_columns() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR);
+             INSERT INTO occurrences VALUES ('1');
+             INSERT INTO occurrences VALUES ('2');
+             INSERT INTO occurrences VALUES ('3');
+             CREATE TABLE derived_values (core_id VARCHAR PRIMARY KEY, elevation VARCHAR);
+             INSERT INTO derived_values VALUES ('1', '12.5');
+             INSERT INTO derived_values VALUES ('2', '40.0');"
+        ).unwrap();
+        crate::edits::update_occurrence_field(&conn, "occurrenceID", "2", "elevation", Some("41")).unwrap();
+
+        let source = occurrences_source(&conn, "occurrenceID").unwrap();
+        let values: Vec<Option<String>> = conn
+            .prepare(&format!("SELECT elevation FROM {source} ORDER BY occurrenceID"))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(values, vec![Some("12.5".to_string()), Some("41".to_string()), None]);
+        assert_eq!(archive_values_source(&conn).unwrap(), "occurrences");
+    }
+
+    #[test]
+    fn test_occurrences_source_edits_win_over_sentinels() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, coordinateUncertaintyInMeters DOUBLE);
+             INSERT INTO occurrences VALUES ('1', -9999);
+             INSERT INTO occurrences VALUES ('2', 0);
+             INSERT INTO occurrences VALUES ('3', 25);"
+        ).unwrap();
+        let columns = vec!["occurrenceID".to_string(), "coordinateUncertaintyInMeters".to_string()];
+        crate::sentinels::map_sentinels_to_null(&conn, &columns).unwrap();
+        crate::edits::update_occurrence_field(&conn, "occurrenceID", "2", "coordinateUncertaintyInMeters", Some("10")).unwrap();
+
+        let source = occurrences_source(&conn, "occurrenceID").unwrap();
+        let values: Vec<Option<f64>> = conn
+            .prepare(&format!("SELECT coordinateUncertaintyInMeters FROM {source} ORDER BY occurrenceID"))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(values, vec![None, Some(10.0), Some(25.0)]);
+    }
+}
@@ -0,0 +1,161 @@
+/// Fields holding identifiers (ORCID iDs, Wikidata items, other URIs) for the
+/// people in recordedBy and identifiedBy
+pub const PERSON_ID_FIELDS: &[&str] = &["recordedByID", "identifiedByID"];
+
+const ORCID_PREFIXES: &[&str] = &[
+    "https://orcid.org/",
+    "http://orcid.org/",
+    "https://www.orcid.org/",
+    "http://www.orcid.org/",
+    "orcid.org/",
+    "orcid:",
+];
+
+const WIKIDATA_PREFIXES: &[&str] = &[
+    "https://www.wikidata.org/wiki/",
+    "http://www.wikidata.org/wiki/",
+    "https://www.wikidata.org/entity/",
+    "http://www.wikidata.org/entity/",
+    "https://wikidata.org/wiki/",
+    "http://wikidata.org/wiki/",
+    "wikidata.org/wiki/",
+    "wd:",
+];
+
+/// Result of normalizing a delimited list of person identifiers
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedPersonIds {
+    /// Identifiers joined with " | ", canonicalized where possible and
+    /// otherwise left as they were
+    pub value: String,
+    /// Identifiers that aren't URIs, or look like ORCID iDs but fail the
+    /// checksum
+    pub invalid: Vec<String>,
+}
+
+/// Canonicalizes a single identifier. ORCID iDs become
+/// https://orcid.org/XXXX-XXXX-XXXX-XXXX and Wikidata items become
+/// https://www.wikidata.org/wiki/Q..., while other http(s) URIs are kept as
+/// they are. Returns None for anything else, including ORCID iDs with a bad
+/// check digit.
+pub fn normalize_person_id(id: &str) -> Option<String> {
+    let id = id.trim();
+    let orcid = strip_prefix_ignore_case(id, ORCID_PREFIXES).unwrap_or(id);
+    if let Some(digits) = orcid_digits(orcid) {
+        if !orcid_checksum_valid(&digits) {
+            return None;
+        }
+        return Some(format!(
+            "https://orcid.org/{}-{}-{}-{}",
+            &digits[0..4], &digits[4..8], &digits[8..12], &digits[12..16]
+        ));
+    }
+
+    let wikidata = strip_prefix_ignore_case(id, WIKIDATA_PREFIXES).unwrap_or(id);
+    if is_wikidata_item(wikidata) {
+        return Some(format!("https://www.wikidata.org/wiki/Q{}", &wikidata[1..]));
+    }
+
+    let lower = id.to_lowercase();
+    if (lower.starts_with("https://") || lower.starts_with("http://")) && !id.contains(char::is_whitespace) {
+        return Some(id.to_string());
+    }
+    None
+}
+
+/// Normalizes each identifier in a value delimited by | or ;
+pub fn normalize_person_ids(value: &str) -> NormalizedPersonIds {
+    let mut ids = Vec::new();
+    let mut invalid = Vec::new();
+    for id in value.split(['|', ';']).map(str::trim).filter(|id| !id.is_empty()) {
+        match normalize_person_id(id) {
+            Some(normalized) => ids.push(normalized),
+            None => {
+                invalid.push(id.to_string());
+                ids.push(id.to_string());
+            }
+        }
+    }
+    NormalizedPersonIds { value: ids.join(" | "), invalid }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|prefix| {
+        value
+            .get(..prefix.len())
+            .filter(|start| start.eq_ignore_ascii_case(prefix))
+            .map(|_| &value[prefix.len()..])
+    })
+}
+
+/// The 16 characters of an ORCID iD with or without hyphens, if it has the
+/// right shape: 15 digits and a digit or X check character
+fn orcid_digits(value: &str) -> Option<String> {
+    let compact: String = value.chars().filter(|c| *c != '-').collect::<String>().to_uppercase();
+    let shaped = compact.len() == 16
+        && compact.is_ascii()
+        && compact[..15].chars().all(|c| c.is_ascii_digit())
+        && compact[15..].chars().all(|c| c.is_ascii_digit() || c == 'X');
+    let grouped = !value.contains('-')
+        || value.split('-').map(str::len).collect::<Vec<_>>() == [4, 4, 4, 4];
+    (shaped && grouped).then_some(compact)
+}
+
+/// ISO 7064 MOD 11-2 check used by ORCID
+fn orcid_checksum_valid(digits: &str) -> bool {
+    let total = digits[..15]
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .fold(0, |total, digit| (total + digit) * 2);
+    let check = match (12 - total % 11) % 11 {
+        10 => 'X',
+        n => char::from_digit(n, 10).unwrap_or('?'),
+    };
+    digits.ends_with(check)
+}
+
+fn is_wikidata_item(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some('Q' | 'q'))
+        && value.len() > 1
+        && chars.all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_person_id() {
+        assert_eq!(
+            normalize_person_id("0000-0002-1825-0097").as_deref(),
+            Some("https://orcid.org/0000-0002-1825-0097")
+        );
+        assert_eq!(
+            normalize_person_id("http://orcid.org/000000021694233x").as_deref(),
+            Some("https://orcid.org/0000-0002-1694-233X")
+        );
+        assert_eq!(normalize_person_id("https://orcid.org/0000-0002-1825-0098"), None);
+        assert_eq!(
+            normalize_person_id("http://www.wikidata.org/entity/Q42").as_deref(),
+            Some("https://www.wikidata.org/wiki/Q42")
+        );
+        assert_eq!(normalize_person_id("Q42").as_deref(), Some("https://www.wikidata.org/wiki/Q42"));
+        assert_eq!(
+            normalize_person_id("http://viaf.org/viaf/12345").as_deref(),
+            Some("http://viaf.org/viaf/12345")
+        );
+        assert_eq!(normalize_person_id("Jepson, W. L."), None);
+    }
+
+    #[test]
+    fn test_normalize_person_ids() {
+        let normalized = normalize_person_ids("0000-0002-1825-0097|wd:Q42 ; W. L. Jepson");
+
+        assert_eq!(
+            normalized.value,
+            "https://orcid.org/0000-0002-1825-0097 | https://www.wikidata.org/wiki/Q42 | W. L. Jepson"
+        );
+        assert_eq!(normalized.invalid, vec!["W. L. Jepson"]);
+    }
+}
@@ -1,5 +1,49 @@
-use crate::error::Result;
+use crate::error::{ChuckError, Result};
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use serde::Deserialize;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default for the most bytes of photos cached for each archive
+pub const DEFAULT_MAX_CACHE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+static MAX_CACHE_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_MAX_CACHE_SIZE);
+
+/// Most bytes of photos to cache for each archive before evicting the least
+/// recently viewed, from the storage settings
+pub fn max_cache_size() -> u64 {
+    MAX_CACHE_SIZE.load(Ordering::Relaxed)
+}
+
+pub fn set_max_cache_size(max_cache_size: u64) {
+    MAX_CACHE_SIZE.store(max_cache_size, Ordering::Relaxed);
+}
+
+/// Sizes photos are scaled down to for grids and previews
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThumbnailSize {
+    Small,
+    Medium,
+}
+
+impl ThumbnailSize {
+    /// Longest side in pixels
+    pub fn max_dimension(self) -> u32 {
+        match self {
+            Self::Small => 240,
+            Self::Medium => 800,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Medium => "medium",
+        }
+    }
+}
 
 /// Manages lazy-loaded photo caching using the filesystem
 /// Uses file modification times for LRU eviction
@@ -47,6 +91,58 @@ impl PhotoCache {
         self.cache_dir.join(&safe_filename)
     }
 
+    /// Returns the path where a file downloaded from `url` is cached. It's
+    /// named by a hash of the URL, since URLs can have characters filenames
+    /// can't, but keeps the extension so its type can still be told.
+    pub fn get_remote_cache_path(&self, url: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        let mut filename = format!("remote_{:016x}", hasher.finish());
+        let url_path = url.split(['?', '#']).next().unwrap_or(url);
+        if let Some(extension) = Path::new(url_path).extension().and_then(|e| e.to_str()) {
+            filename.push('.');
+            filename.push_str(extension);
+        }
+        self.cache_dir.join(filename)
+    }
+
+    /// Returns the path where a thumbnail of a photo is cached. Thumbnails are
+    /// cached alongside photos, so they're evicted and cleared with them.
+    pub fn get_thumbnail_path(&self, photo_path: &str, size: ThumbnailSize) -> PathBuf {
+        self.get_cache_path(&format!("thumbnails/{}/{photo_path}.jpg", size.name()))
+    }
+
+    /// Writes a JPEG thumbnail of the image at `photo_file` to
+    /// `thumbnail_path`, turned upright according to its EXIF orientation
+    /// and scaled down to fit `size`. Images that already fit aren't
+    /// enlarged.
+    pub fn create_thumbnail(
+        &self,
+        photo_file: &Path,
+        thumbnail_path: &Path,
+        size: ThumbnailSize,
+    ) -> Result<()> {
+        let image_error = |source| ChuckError::Thumbnail { path: photo_file.to_path_buf(), source };
+        let mut decoder = ImageReader::open(photo_file)
+            .map_err(|source| ChuckError::FileOpen { path: photo_file.to_path_buf(), source })?
+            .with_guessed_format()
+            .map_err(|source| ChuckError::FileRead { path: photo_file.to_path_buf(), source })?
+            .into_decoder()
+            .map_err(image_error)?;
+        let orientation = decoder.orientation().map_err(image_error)?;
+        let mut image = DynamicImage::from_decoder(decoder).map_err(image_error)?;
+        image.apply_orientation(orientation);
+
+        let max = size.max_dimension();
+        if image.width() > max || image.height() > max {
+            image = image.thumbnail(max, max);
+        }
+        // JPEG has no alpha channel
+        DynamicImage::ImageRgb8(image.to_rgb8())
+            .save_with_format(thumbnail_path, ImageFormat::Jpeg)
+            .map_err(image_error)
+    }
+
     /// Gets the total size of cached photos in bytes by scanning the cache directory
     pub fn get_cache_size(&self) -> Result<u64> {
         if !self.cache_dir.exists() {
@@ -71,6 +167,18 @@ impl PhotoCache {
         Ok(total_size)
     }
 
+    /// Removes every cached photo. Returns the number of bytes freed.
+    pub fn clear(&self) -> Result<u64> {
+        let size = self.get_cache_size()?;
+        if self.cache_dir.exists() {
+            std::fs::remove_dir_all(&self.cache_dir).map_err(|e| crate::error::ChuckError::FileWrite {
+                path: self.cache_dir.clone(),
+                source: e,
+            })?;
+        }
+        Ok(size)
+    }
+
     /// Evicts least recently used photos until cache is under the size limit
     /// Returns the number of photos evicted
     pub fn evict_lru(&self, max_cache_size: u64) -> Result<usize> {
@@ -121,7 +229,39 @@ impl PhotoCache {
             }
         }
 
-        log::info!("Evicted {evicted} photos from cache");
+        log::info!(count = evicted; "Evicted {evicted} photos from cache");
         Ok(evicted)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_thumbnail() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = PhotoCache::new(temp.path());
+        let photo_file = temp.path().join("photo.png");
+        image::RgbaImage::new(1000, 500).save(&photo_file).unwrap();
+        let thumbnail_path = cache.get_thumbnail_path("media/photo.png", ThumbnailSize::Small);
+
+        cache.create_thumbnail(&photo_file, &thumbnail_path, ThumbnailSize::Small).unwrap();
+
+        assert_eq!(thumbnail_path.file_name().unwrap(), "thumbnails_small_media_photo.png.jpg");
+        assert_eq!(image::image_dimensions(&thumbnail_path).unwrap(), (240, 120));
+    }
+
+    #[test]
+    fn test_create_thumbnail_keeps_small_images() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = PhotoCache::new(temp.path());
+        let photo_file = temp.path().join("photo.png");
+        image::RgbImage::new(100, 50).save(&photo_file).unwrap();
+        let thumbnail_path = cache.get_thumbnail_path("photo.png", ThumbnailSize::Medium);
+
+        cache.create_thumbnail(&photo_file, &thumbnail_path, ThumbnailSize::Medium).unwrap();
+
+        assert_eq!(image::image_dimensions(&thumbnail_path).unwrap(), (100, 50));
+    }
+}
@@ -0,0 +1,433 @@
+use serde::Serialize;
+
+use crate::country_boundaries::CountryBoundaries;
+use crate::error::Result;
+use crate::person_ids::{normalize_person_ids, PERSON_ID_FIELDS};
+use crate::sentinels::sentinel_condition;
+
+/// Maximum number of example core IDs returned per check
+const EXAMPLE_LIMIT: usize = 10;
+
+/// A data quality check expressed as a SQL condition on the occurrences table
+struct QualityCheck {
+    id: &'static str,
+    description: &'static str,
+    /// Columns the condition refers to
+    columns: &'static [&'static str],
+    condition: &'static str,
+    /// Condition to use when some of `columns` are missing (e.g. dropped on
+    /// import because they were empty). None skips the check.
+    condition_without_columns: Option<&'static str>,
+}
+
+const CHECKS: &[QualityCheck] = &[
+    QualityCheck {
+        id: "coordinatesOutOfRange",
+        description: "Latitude outside -90 to 90 or longitude outside -180 to 180",
+        columns: &["decimalLatitude", "decimalLongitude"],
+        condition: "\"decimalLatitude\" NOT BETWEEN -90 AND 90 \
+            OR \"decimalLongitude\" NOT BETWEEN -180 AND 180",
+        condition_without_columns: None,
+    },
+    QualityCheck {
+        id: "coordinatesPossiblySwapped",
+        description: "Latitude is impossible but would be valid as a longitude and vice versa, \
+            suggesting latitude and longitude were swapped",
+        columns: &["decimalLatitude", "decimalLongitude"],
+        condition: "ABS(\"decimalLatitude\") > 90 AND ABS(\"decimalLongitude\") <= 90",
+        condition_without_columns: None,
+    },
+    QualityCheck {
+        id: "zeroZeroCoordinates",
+        description: "Coordinates are exactly 0, 0, which usually means they were missing",
+        columns: &["decimalLatitude", "decimalLongitude"],
+        condition: "\"decimalLatitude\" = 0 AND \"decimalLongitude\" = 0",
+        condition_without_columns: None,
+    },
+    QualityCheck {
+        id: "largeCoordinateUncertainty",
+        description: "Coordinate uncertainty greater than 100 km",
+        columns: &["coordinateUncertaintyInMeters"],
+        condition: "TRY_CAST(\"coordinateUncertaintyInMeters\" AS DOUBLE) > 100000",
+        condition_without_columns: None,
+    },
+    QualityCheck {
+        id: "eventDateInFuture",
+        description: "eventDate is later than today",
+        columns: &["eventDate"],
+        condition: "TRY_CAST(regexp_extract(\"eventDate\", '^\\d{4}-\\d{2}-\\d{2}') AS DATE) > current_date",
+        condition_without_columns: None,
+    },
+    QualityCheck {
+        id: "missingScientificName",
+        description: "No scientificName",
+        columns: &["scientificName"],
+        condition: "\"scientificName\" IS NULL OR trim(\"scientificName\") = ''",
+        // An archive without the column is missing it everywhere
+        condition_without_columns: Some("TRUE"),
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityCheckResult {
+    pub id: String,
+    pub description: String,
+    /// False if the archive lacks the columns the check needs
+    pub ran: bool,
+    pub count: i64,
+    /// Core IDs of up to EXAMPLE_LIMIT failing occurrences
+    pub example_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityReport {
+    pub total: i64,
+    pub checks: Vec<QualityCheckResult>,
+}
+
+/// Columns needed to compare coordinates with countryCode
+const COUNTRY_MISMATCH_COLUMNS: [&str; 3] = ["decimalLatitude", "decimalLongitude", "countryCode"];
+
+/// Runs every quality check over occurrences with hand edits applied. The
+/// country mismatch check only runs if `boundaries` are available.
+pub fn run_quality_report(
+    conn: &duckdb::Connection,
+    core_id_column: &str,
+    available_columns: &[String],
+    boundaries: Option<&CountryBoundaries>,
+) -> Result<QualityReport> {
+    let occurrences = crate::overlay::occurrences_source(conn, core_id_column)?;
+    let total: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {occurrences}"), [], |row| row.get(0))?;
+    let quoted_core_id = format!("\"{}\"", core_id_column.replace('"', "\"\""));
+
+    let mut checks = Vec::with_capacity(CHECKS.len());
+    for check in CHECKS {
+        let has_columns = check.columns
+            .iter()
+            .all(|col| available_columns.iter().any(|c| c == col));
+        let condition = if has_columns {
+            Some(check.condition)
+        } else {
+            check.condition_without_columns
+        };
+        let Some(condition) = condition else {
+            checks.push(QualityCheckResult {
+                id: check.id.to_string(),
+                description: check.description.to_string(),
+                ran: false,
+                count: 0,
+                example_ids: vec![],
+            });
+            continue;
+        };
+
+        let (count, example_ids) = count_matching(conn, &occurrences, &quoted_core_id, condition)?;
+        checks.push(QualityCheckResult {
+            id: check.id.to_string(),
+            description: check.description.to_string(),
+            ran: true,
+            count,
+            example_ids,
+        });
+    }
+
+    // Which sentinel columns apply depends on the archive, so this condition
+    // is built rather than static
+    let sentinels = sentinel_condition(available_columns);
+    let (count, example_ids) = match &sentinels {
+        Some(condition) => count_matching(conn, &occurrences, &quoted_core_id, condition)?,
+        None => (0, vec![]),
+    };
+    checks.push(QualityCheckResult {
+        id: "sentinelValues".to_string(),
+        description: "A numeric field like coordinateUncertaintyInMeters or an elevation \
+            has a placeholder value like 0 or -9999 that usually means unknown".to_string(),
+        ran: sentinels.is_some(),
+        count,
+        example_ids,
+    });
+
+    checks.push(country_mismatch_check(conn, &occurrences, &quoted_core_id, available_columns, boundaries)?);
+    checks.push(invalid_person_ids_check(conn, &occurrences, &quoted_core_id, available_columns)?);
+
+    Ok(QualityReport { total, checks })
+}
+
+/// Counts occurrences matching a condition along with up to EXAMPLE_LIMIT of
+/// their core IDs
+fn count_matching(
+    conn: &duckdb::Connection,
+    occurrences: &str,
+    quoted_core_id: &str,
+    condition: &str,
+) -> Result<(i64, Vec<String>)> {
+    let count: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {occurrences} WHERE {condition}"),
+        [],
+        |row| row.get(0),
+    )?;
+    let example_ids = if count > 0 {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT CAST({quoted_core_id} AS VARCHAR) FROM {occurrences} \
+             WHERE {condition} ORDER BY {quoted_core_id} LIMIT {EXAMPLE_LIMIT}"
+        ))?;
+        stmt.query_map([], |row| row.get::<_, Option<String>>(0))?
+            .filter_map(|id| id.transpose())
+            .collect::<std::result::Result<Vec<_>, _>>()?
+    } else {
+        vec![]
+    };
+    Ok((count, example_ids))
+}
+
+/// Flags occurrences whose coordinates fall inside a country other than the
+/// one in countryCode. This can't be expressed in SQL without a spatial
+/// extension, so points are tested against the boundaries here. Points that
+/// fall in no country (e.g. just offshore at low resolution) and codes the
+/// boundaries don't cover are given the benefit of the doubt.
+fn country_mismatch_check(
+    conn: &duckdb::Connection,
+    occurrences: &str,
+    quoted_core_id: &str,
+    available_columns: &[String],
+    boundaries: Option<&CountryBoundaries>,
+) -> Result<QualityCheckResult> {
+    let mut result = QualityCheckResult {
+        id: "countryCoordinateMismatch".to_string(),
+        description: "Coordinates fall in a different country than countryCode".to_string(),
+        ran: false,
+        count: 0,
+        example_ids: vec![],
+    };
+    let has_columns = COUNTRY_MISMATCH_COLUMNS
+        .iter()
+        .all(|col| available_columns.iter().any(|c| c == col));
+    let Some(boundaries) = boundaries.filter(|_| has_columns) else {
+        return Ok(result);
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CAST({quoted_core_id} AS VARCHAR), \
+            TRY_CAST(\"decimalLatitude\" AS DOUBLE), \
+            TRY_CAST(\"decimalLongitude\" AS DOUBLE), \
+            upper(trim(\"countryCode\")) \
+         FROM {occurrences} \
+         WHERE \"decimalLatitude\" IS NOT NULL \
+            AND \"decimalLongitude\" IS NOT NULL \
+            AND \"countryCode\" IS NOT NULL \
+            AND trim(\"countryCode\") != '' \
+         ORDER BY {quoted_core_id}"
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<f64>>(1)?,
+            row.get::<_, Option<f64>>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+
+    result.ran = true;
+    for row in rows {
+        let (id, lat, lon, country_code) = row?;
+        let (Some(lat), Some(lon)) = (lat, lon) else {
+            continue;
+        };
+        if !boundaries.has_country(&country_code) {
+            continue;
+        }
+        let mismatched = boundaries
+            .country_at(lat, lon)
+            .is_some_and(|code| code != country_code);
+        if mismatched {
+            result.count += 1;
+            if let Some(id) = id.filter(|_| result.example_ids.len() < EXAMPLE_LIMIT) {
+                result.example_ids.push(id);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Flags occurrences with recordedByID or identifiedByID values that aren't
+/// URIs or are ORCID iDs with a bad check digit. Validating ORCID checksums
+/// is easier here than in SQL.
+fn invalid_person_ids_check(
+    conn: &duckdb::Connection,
+    occurrences: &str,
+    quoted_core_id: &str,
+    available_columns: &[String],
+) -> Result<QualityCheckResult> {
+    let mut result = QualityCheckResult {
+        id: "invalidPersonIdentifiers".to_string(),
+        description: "recordedByID or identifiedByID has a value that isn't a URI \
+            or an ORCID iD with an invalid checksum".to_string(),
+        ran: false,
+        count: 0,
+        example_ids: vec![],
+    };
+    let fields: Vec<&str> = PERSON_ID_FIELDS
+        .iter()
+        .copied()
+        .filter(|field| available_columns.iter().any(|c| c == field))
+        .collect();
+    if fields.is_empty() {
+        return Ok(result);
+    }
+
+    let selects = fields
+        .iter()
+        .map(|field| format!("CAST(\"{field}\" AS VARCHAR)"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let not_null = fields
+        .iter()
+        .map(|field| format!("\"{field}\" IS NOT NULL"))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CAST({quoted_core_id} AS VARCHAR), {selects} FROM {occurrences} \
+         WHERE {not_null} ORDER BY {quoted_core_id}"
+    ))?;
+    let mut rows = stmt.query([])?;
+
+    result.ran = true;
+    while let Some(row) = rows.next()? {
+        let id: Option<String> = row.get(0)?;
+        let mut invalid = false;
+        for i in 0..fields.len() {
+            if let Some(value) = row.get::<_, Option<String>>(i + 1)? {
+                invalid |= !normalize_person_ids(&value).invalid.is_empty();
+            }
+        }
+        if invalid {
+            result.count += 1;
+            if let Some(id) = id.filter(|_| result.example_ids.len() < EXAMPLE_LIMIT) {
+                result.example_ids.push(id);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::country_boundaries::tests::FIXTURE;
+
+    fn columns(conn: &duckdb::Connection) -> Vec<String> {
+        let mut stmt = conn.prepare(
+            "SELECT column_name FROM information_schema.columns WHERE table_name = 'occurrences'"
+        ).unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().map(|c| c.unwrap()).collect()
+    }
+
+    fn check<'a>(report: &'a QualityReport, id: &str) -> &'a QualityCheckResult {
+        report.checks.iter().find(|c| c.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_run_quality_report() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                occurrenceID VARCHAR,
+                scientificName VARCHAR,
+                decimalLatitude DOUBLE,
+                decimalLongitude DOUBLE,
+                coordinateUncertaintyInMeters VARCHAR,
+                eventDate VARCHAR
+             );
+             INSERT INTO occurrences VALUES ('001', 'Species A', 37.5, -122.1, '10', '2024-01-01');
+             INSERT INTO occurrences VALUES ('002', 'Species A', -122.1, 37.5, NULL, '2024-01-01');
+             INSERT INTO occurrences VALUES ('003', '', 0, 0, '200000', '2999-01-01T10:00:00Z');
+             INSERT INTO occurrences VALUES ('004', NULL, 37.5, -200, NULL, NULL);"
+        ).unwrap();
+
+        let report = run_quality_report(&conn, "occurrenceID", &columns(&conn), None).unwrap();
+
+        assert_eq!(report.total, 4);
+        assert_eq!(check(&report, "coordinatesOutOfRange").example_ids, vec!["002", "004"]);
+        assert_eq!(check(&report, "coordinatesPossiblySwapped").example_ids, vec!["002"]);
+        assert_eq!(check(&report, "zeroZeroCoordinates").example_ids, vec!["003"]);
+        assert_eq!(check(&report, "largeCoordinateUncertainty").example_ids, vec!["003"]);
+        assert_eq!(check(&report, "eventDateInFuture").example_ids, vec!["003"]);
+        assert_eq!(check(&report, "missingScientificName").count, 2);
+        let sentinels = check(&report, "sentinelValues");
+        assert!(sentinels.ran);
+        assert_eq!(sentinels.count, 0);
+    }
+
+    #[test]
+    fn test_run_quality_report_without_columns() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR);
+             INSERT INTO occurrences VALUES ('001');"
+        ).unwrap();
+
+        let report = run_quality_report(&conn, "occurrenceID", &columns(&conn), None).unwrap();
+
+        let coords = check(&report, "coordinatesOutOfRange");
+        assert!(!coords.ran);
+        assert!(!check(&report, "sentinelValues").ran);
+        assert_eq!(coords.count, 0);
+        let names = check(&report, "missingScientificName");
+        assert!(names.ran);
+        assert_eq!(names.count, 1);
+        assert!(!check(&report, "countryCoordinateMismatch").ran);
+    }
+
+    #[test]
+    fn test_run_quality_report_country_mismatch() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                occurrenceID VARCHAR,
+                decimalLatitude DOUBLE,
+                decimalLongitude DOUBLE,
+                countryCode VARCHAR
+             );
+             INSERT INTO occurrences VALUES ('001', 2, 2, 'AA');
+             INSERT INTO occurrences VALUES ('002', 2, 15, 'aa');
+             INSERT INTO occurrences VALUES ('003', -5, -5, 'AA');
+             INSERT INTO occurrences VALUES ('004', 2, 2, 'CC');
+             INSERT INTO occurrences VALUES ('005', 2, 2, NULL);"
+        ).unwrap();
+        let boundaries = CountryBoundaries::from_geojson(FIXTURE).unwrap();
+
+        let report = run_quality_report(
+            &conn,
+            "occurrenceID",
+            &columns(&conn),
+            Some(&boundaries),
+        ).unwrap();
+
+        let mismatch = check(&report, "countryCoordinateMismatch");
+        assert!(mismatch.ran);
+        assert_eq!(mismatch.example_ids, vec!["002"]);
+        assert_eq!(mismatch.count, 1);
+    }
+
+    #[test]
+    fn test_run_quality_report_invalid_person_ids() {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, recordedByID VARCHAR);
+             INSERT INTO occurrences VALUES ('001', 'https://orcid.org/0000-0002-1825-0097');
+             INSERT INTO occurrences VALUES ('002', '0000-0002-1825-0098');
+             INSERT INTO occurrences VALUES ('003', 'Q42 | W. L. Jepson');
+             INSERT INTO occurrences VALUES ('004', NULL);"
+        ).unwrap();
+
+        let report = run_quality_report(&conn, "occurrenceID", &columns(&conn), None).unwrap();
+
+        let invalid = check(&report, "invalidPersonIdentifiers");
+        assert!(invalid.ran);
+        assert_eq!(invalid.example_ids, vec!["002", "003"]);
+    }
+}
@@ -0,0 +1,321 @@
+//! Re-runs archive updates on a schedule, e.g. refreshing an iNat archive
+//! every day, while the app is open. Schedules and a history of their runs
+//! are saved in the app config directory, and the user gets a notification
+//! when a run adds records.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use chuck_core::archive_updater::{count_occurrences, update_archive};
+use chuck_core::auth::{fetch_jwt, AuthCache};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::commands::settings::{read_settings, settings_path, write_settings};
+use crate::error::{ChuckError, Result};
+
+const SCHEDULES_FILENAME: &str = "schedules.json";
+const HISTORY_FILENAME: &str = "schedule_history.json";
+
+/// Runs kept in the history, oldest dropped first
+const MAX_HISTORY: usize = 200;
+
+/// How often to look for schedules that are due
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Event emitted with a ScheduleRun when a scheduled update finishes
+pub const RUN_FINISHED_EVENT: &str = "schedule-run-finished";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduleInterval {
+    Daily,
+    Weekly,
+}
+
+impl ScheduleInterval {
+    fn duration(self) -> chrono::Duration {
+        match self {
+            Self::Daily => chrono::Duration::days(1),
+            Self::Weekly => chrono::Duration::weeks(1),
+        }
+    }
+}
+
+/// An archive to update on an interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub id: String,
+    pub archive_path: String,
+    pub interval: ScheduleInterval,
+    pub enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub next_run_at: DateTime<Utc>,
+}
+
+impl Schedule {
+    /// Enabled schedule whose first run is one interval from `now`
+    pub fn new(archive_path: String, interval: ScheduleInterval, now: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            archive_path,
+            interval,
+            enabled: true,
+            last_run_at: None,
+            next_run_at: now + interval.duration(),
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled && self.next_run_at <= now
+    }
+
+    /// Records a run started at `started_at` and moves the next run to the
+    /// first time on the schedule's cadence after it. Runs missed while the
+    /// app was closed are skipped rather than run back to back.
+    pub fn record_run(&mut self, started_at: DateTime<Utc>) {
+        self.last_run_at = Some(started_at);
+        while self.next_run_at <= started_at {
+            self.next_run_at += self.interval.duration();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+}
+
+/// One run of a schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    pub schedule_id: String,
+    pub archive_path: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub status: RunStatus,
+    /// Occurrences the update added, not counting ones it changed
+    pub new_records: Option<usize>,
+    pub error: Option<String>,
+}
+
+/// Serializes changes to the saved schedules, and runs so only one update
+/// happens at a time
+#[derive(Default)]
+pub(crate) struct Scheduler {
+    store: Mutex<()>,
+    running: tokio::sync::Mutex<()>,
+}
+
+pub(crate) fn load_schedules<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<Vec<Schedule>> {
+    Ok(read_settings(&settings_path(app, SCHEDULES_FILENAME)?))
+}
+
+/// Applies `change` to the saved schedules and saves them
+pub(crate) fn update_schedules<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    change: impl FnOnce(&mut Vec<Schedule>) -> Result<T>,
+) -> Result<T> {
+    let scheduler = app.state::<Scheduler>();
+    let _guard = scheduler.store.lock().map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    let path = settings_path(app, SCHEDULES_FILENAME)?;
+    let mut schedules: Vec<Schedule> = read_settings(&path);
+    let result = change(&mut schedules)?;
+    write_settings(&path, &schedules)?;
+    Ok(result)
+}
+
+pub(crate) fn load_history<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<Vec<ScheduleRun>> {
+    Ok(read_settings(&settings_path(app, HISTORY_FILENAME)?))
+}
+
+fn push_history(history: &mut Vec<ScheduleRun>, run: ScheduleRun) {
+    history.push(run);
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+}
+
+fn save_run<R: tauri::Runtime>(app: &tauri::AppHandle<R>, run: &ScheduleRun) -> Result<()> {
+    let scheduler = app.state::<Scheduler>();
+    let _guard = scheduler.store.lock().map_err(|e| ChuckError::Tauri(e.to_string()))?;
+    let path = settings_path(app, HISTORY_FILENAME)?;
+    let mut history: Vec<ScheduleRun> = read_settings(&path);
+    push_history(&mut history, run.clone());
+    write_settings(&path, &history)
+}
+
+async fn count(path: &str) -> std::result::Result<usize, String> {
+    let path = path.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        count_occurrences(&path).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Updates the schedule's archive, returning the number of occurrences the
+/// update added
+async fn update<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    schedule: &Schedule,
+) -> std::result::Result<usize, String> {
+    let before = count(&schedule.archive_path).await?;
+    let jwt = match app.state::<AuthCache>().load_token() {
+        Ok(Some(oauth_token)) => fetch_jwt(&oauth_token).await.ok(),
+        _ => None,
+    };
+    update_archive(&schedule.archive_path, |_| {}, jwt, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let after = count(&schedule.archive_path).await?;
+    Ok(after.saturating_sub(before))
+}
+
+fn notify<R: tauri::Runtime>(app: &tauri::AppHandle<R>, run: &ScheduleRun) {
+    let Some(new_records) = run.new_records.filter(|n| *n > 0) else {
+        return;
+    };
+    let name = std::path::Path::new(&run.archive_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| run.archive_path.clone());
+    let records = if new_records == 1 { "record" } else { "records" };
+    let shown = app
+        .notification()
+        .builder()
+        .title("Archive updated")
+        .body(format!("{new_records} new {records} in {name}"))
+        .show();
+    if let Err(e) = shown {
+        log::warn!("Failed to show notification: {e}");
+    }
+}
+
+/// Runs a schedule now, whether or not it's due, then saves the run and
+/// moves the schedule's next run
+pub(crate) async fn run_schedule<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    schedule: &Schedule,
+) -> Result<ScheduleRun> {
+    let scheduler = app.state::<Scheduler>();
+    let _running = scheduler.running.lock().await;
+
+    log::info!(
+        schedule_id = schedule.id.as_str(), path = schedule.archive_path.as_str();
+        "Running scheduled update of {}", schedule.archive_path
+    );
+    let started_at = Utc::now();
+    let result = update(app, schedule).await;
+    let run = ScheduleRun {
+        schedule_id: schedule.id.clone(),
+        archive_path: schedule.archive_path.clone(),
+        started_at,
+        finished_at: Utc::now(),
+        status: if result.is_ok() { RunStatus::Succeeded } else { RunStatus::Failed },
+        new_records: result.as_ref().ok().copied(),
+        error: result.err(),
+    };
+    if let Some(error) = &run.error {
+        log::warn!(
+            schedule_id = schedule.id.as_str(), path = schedule.archive_path.as_str();
+            "Scheduled update of {} failed: {error}", schedule.archive_path
+        );
+    }
+
+    // The schedule may have changed or been removed during the run
+    update_schedules(app, |schedules| {
+        if let Some(saved) = schedules.iter_mut().find(|s| s.id == schedule.id) {
+            saved.record_run(started_at);
+        }
+        Ok(())
+    })?;
+    save_run(app, &run)?;
+    notify(app, &run);
+    if let Err(e) = app.emit(RUN_FINISHED_EVENT, &run) {
+        log::warn!("Failed to emit {RUN_FINISHED_EVENT}: {e}");
+    }
+    Ok(run)
+}
+
+async fn run_due<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<()> {
+    let now = Utc::now();
+    let due: Vec<Schedule> = load_schedules(app)?
+        .into_iter()
+        .filter(|schedule| schedule.is_due(now))
+        .collect();
+    for schedule in due {
+        run_schedule(app, &schedule).await?;
+    }
+    Ok(())
+}
+
+/// Starts checking for due schedules in the background. Call this once at
+/// startup, after managing a Scheduler.
+pub(crate) fn start<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due(&app).await {
+                log::warn!("Failed to run scheduled updates: {e}");
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        timestamp.parse().unwrap()
+    }
+
+    #[test]
+    fn test_schedule_is_due_when_enabled_and_past_next_run() {
+        let mut schedule =
+            Schedule::new("a.zip".to_string(), ScheduleInterval::Daily, at("2025-03-01T09:00:00Z"));
+
+        assert_eq!(schedule.next_run_at, at("2025-03-02T09:00:00Z"));
+        assert!(!schedule.is_due(at("2025-03-02T08:59:00Z")));
+        assert!(schedule.is_due(at("2025-03-02T09:00:00Z")));
+        schedule.enabled = false;
+        assert!(!schedule.is_due(at("2025-03-02T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_record_run_skips_missed_runs() {
+        let mut schedule =
+            Schedule::new("a.zip".to_string(), ScheduleInterval::Weekly, at("2025-03-01T09:00:00Z"));
+
+        schedule.record_run(at("2025-03-20T12:00:00Z"));
+
+        assert_eq!(schedule.last_run_at, Some(at("2025-03-20T12:00:00Z")));
+        assert_eq!(schedule.next_run_at, at("2025-03-22T09:00:00Z"));
+    }
+
+    #[test]
+    fn test_push_history_drops_oldest_runs() {
+        let run = |n: usize| ScheduleRun {
+            schedule_id: n.to_string(),
+            archive_path: "a.zip".to_string(),
+            started_at: at("2025-03-01T09:00:00Z"),
+            finished_at: at("2025-03-01T09:01:00Z"),
+            status: RunStatus::Succeeded,
+            new_records: Some(n),
+            error: None,
+        };
+        let mut history: Vec<ScheduleRun> = (0..MAX_HISTORY).map(run).collect();
+
+        push_history(&mut history, run(MAX_HISTORY));
+
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history[0].schedule_id, "1");
+        assert_eq!(history.last().unwrap().schedule_id, MAX_HISTORY.to_string());
+    }
+}
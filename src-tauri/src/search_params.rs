@@ -5,6 +5,8 @@ use url::Url;
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct SearchParams {
+    // Comma-separated to sort by several columns, e.g.
+    // sort_by=family,scientificName&sort_direction=ASC,DESC. See sort_columns.
     pub sort_by: Option<String>,
     pub sort_direction: Option<String>,
 
@@ -58,6 +60,42 @@ impl SearchParams {
             swlng,
         }
     }
+
+    /// Columns to sort by in order of precedence, as (column, direction)
+    /// pairs. The nth direction in sort_direction goes with the nth column
+    /// in sort_by; columns without one get None.
+    pub fn sort_columns(&self) -> Vec<(String, Option<String>)> {
+        let Some(sort_by) = &self.sort_by else {
+            return Vec::new();
+        };
+        let mut directions = self
+            .sort_direction
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim);
+        sort_by
+            .split(',')
+            .map(|column| {
+                let direction = directions.next().filter(|d| !d.is_empty()).map(str::to_string);
+                (column.trim().to_string(), direction)
+            })
+            .filter(|(column, _)| !column.is_empty())
+            .collect()
+    }
+
+    /// Sets sort_by and sort_direction from (column, direction) pairs in
+    /// order of precedence
+    pub fn set_sort_columns(&mut self, columns: &[(&str, &str)]) {
+        if columns.is_empty() {
+            self.sort_by = None;
+            self.sort_direction = None;
+            return;
+        }
+        let (sort_by, directions): (Vec<&str>, Vec<&str>) = columns.iter().copied().unzip();
+        self.sort_by = Some(sort_by.join(","));
+        self.sort_direction = Some(directions.join(","));
+    }
 }
 
 #[cfg(test)]
@@ -106,6 +144,27 @@ mod tests {
         assert_eq!(params.sort_direction, Some("DESC".to_string()));
     }
 
+    #[test]
+    fn test_search_params_sort_columns() {
+        let params = params_from_url(
+            "http://local/?sort_by=family,scientificName,eventDate&sort_direction=ASC,,DESC".to_string()
+        );
+        assert_eq!(
+            params.sort_columns(),
+            vec![
+                ("family".to_string(), Some("ASC".to_string())),
+                ("scientificName".to_string(), None),
+                ("eventDate".to_string(), Some("DESC".to_string())),
+            ]
+        );
+
+        let mut params = SearchParams::default();
+        assert!(params.sort_columns().is_empty());
+        params.set_sort_columns(&[("family", "ASC"), ("eventDate", "DESC")]);
+        assert_eq!(params.sort_by, Some("family,eventDate".to_string()));
+        assert_eq!(params.sort_direction, Some("ASC,DESC".to_string()));
+    }
+
     #[test]
     fn test_search_params_from_uri_with_bbox() {
         let params = params_from_url("http://local/?nelat=40&nelng=-120&swlat=35&swlng=-125".to_string());
@@ -0,0 +1,216 @@
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Columns whose sentinel values are read as NULL (see
+/// overlay::occurrences_source). Occurrences keep the values themselves, so
+/// the mapping can be undone.
+const MAPPED_SENTINELS_TABLE: &str = "mapped_sentinels";
+
+/// Values that numeric columns often use to mean "unknown" instead of being
+/// left empty. 0 is only a sentinel for uncertainty; a 0 elevation or depth is
+/// sea level.
+pub const SENTINELS: &[(&str, &[f64])] = &[
+    ("coordinateUncertaintyInMeters", &[0.0, -1.0, -9999.0, 9999.0, 99999.0]),
+    ("minimumElevationInMeters", &[-9999.0, 9999.0, -99999.0, 99999.0]),
+    ("maximumElevationInMeters", &[-9999.0, 9999.0, -99999.0, 99999.0]),
+    ("minimumDepthInMeters", &[-9999.0, 9999.0, -99999.0, 99999.0]),
+    ("maximumDepthInMeters", &[-9999.0, 9999.0, -99999.0, 99999.0]),
+    ("individualCount", &[-1.0, -9999.0, 9999.0, 99999.0]),
+];
+
+/// How often a sentinel value appears in a column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentinelCount {
+    pub column: String,
+    pub value: f64,
+    pub count: i64,
+}
+
+/// SQL condition matching a sentinel value in a column
+fn column_condition(column: &str, values: &[f64]) -> String {
+    value_condition(&Database::quote_identifier(column), values)
+}
+
+/// SQL condition matching a sentinel value in a SQL expression
+pub(crate) fn value_condition(value: &str, values: &[f64]) -> String {
+    let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+    format!("TRY_CAST({value} AS DOUBLE) IN ({values})")
+}
+
+/// Sentinel columns present in the archive with their values
+fn present_sentinels<'a>(available_columns: &'a [String]) -> impl Iterator<Item = (&'static str, &'static [f64])> + 'a {
+    SENTINELS
+        .iter()
+        .copied()
+        .filter(|(column, _)| available_columns.iter().any(|c| c == column))
+}
+
+/// SQL condition matching occurrences with a sentinel in any available
+/// column, or None if the archive has none of the columns
+pub fn sentinel_condition(available_columns: &[String]) -> Option<String> {
+    let conditions: Vec<String> = present_sentinels(available_columns)
+        .map(|(column, values)| column_condition(column, values))
+        .collect();
+    (!conditions.is_empty()).then(|| conditions.join(" OR "))
+}
+
+/// Counts each sentinel value in the columns that have them
+pub fn detect_sentinels(
+    conn: &duckdb::Connection,
+    available_columns: &[String],
+) -> Result<Vec<SentinelCount>> {
+    let mut counts = Vec::new();
+    for (column, values) in present_sentinels(available_columns) {
+        let quoted = Database::quote_identifier(column);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT TRY_CAST({quoted} AS DOUBLE) AS value, COUNT(*) FROM occurrences \
+             WHERE {} GROUP BY value ORDER BY value",
+            column_condition(column, values)
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SentinelCount { column: column.to_string(), value: row.get(0)?, count: row.get(1)? })
+        })?;
+        for row in rows {
+            counts.push(row?);
+        }
+    }
+    Ok(counts)
+}
+
+fn has_mapped_sentinels(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [MAPPED_SENTINELS_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Columns mapped by map_sentinels_to_null with their sentinel values
+pub fn mapped_sentinels(conn: &duckdb::Connection) -> Result<Vec<(&'static str, &'static [f64])>> {
+    if !has_mapped_sentinels(conn)? {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(&format!("SELECT column_name FROM {MAPPED_SENTINELS_TABLE}"))?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(present_sentinels(&columns).collect())
+}
+
+/// Has stats, maps, and exports treat sentinel values as missing by reading
+/// them as NULL. The values in occurrences are left as they are. Returns the
+/// number of values mapped. Needs a read-write connection.
+pub fn map_sentinels_to_null(
+    conn: &duckdb::Connection,
+    available_columns: &[String],
+) -> Result<usize> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {MAPPED_SENTINELS_TABLE} (column_name VARCHAR PRIMARY KEY)"
+    ))?;
+    let mut mapped = 0;
+    for (column, values) in present_sentinels(available_columns) {
+        let added = conn.execute(
+            &format!("INSERT OR IGNORE INTO {MAPPED_SENTINELS_TABLE} VALUES (?)"),
+            [column],
+        )?;
+        if added == 0 {
+            continue;
+        }
+        let count: usize = conn.query_row(
+            &format!("SELECT COUNT(*) FROM occurrences WHERE {}", column_condition(column, values)),
+            [],
+            |row| row.get(0),
+        )?;
+        mapped += count;
+    }
+    Ok(mapped)
+}
+
+/// Stops reading sentinel values as NULL after map_sentinels_to_null. Returns
+/// the number of values restored. Needs a read-write connection.
+pub fn restore_sentinel_values(conn: &duckdb::Connection) -> Result<usize> {
+    let mut restored = 0;
+    for (column, values) in mapped_sentinels(conn)? {
+        let count: usize = conn.query_row(
+            &format!("SELECT COUNT(*) FROM occurrences WHERE {}", column_condition(column, values)),
+            [],
+            |row| row.get(0),
+        )?;
+        restored += count;
+    }
+    conn.execute_batch(&format!("DROP TABLE IF EXISTS {MAPPED_SENTINELS_TABLE}"))?;
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrences() -> (duckdb::Connection, Vec<String>) {
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (
+                occurrenceID VARCHAR,
+                coordinateUncertaintyInMeters VARCHAR,
+                minimumElevationInMeters VARCHAR
+             );
+             INSERT INTO occurrences VALUES ('1', '0', '0');
+             INSERT INTO occurrences VALUES ('2', '25', '-9999');
+             INSERT INTO occurrences VALUES ('3', '99999', '-9999.0');
+             INSERT INTO occurrences VALUES ('4', NULL, '1200');"
+        ).unwrap();
+        let columns = vec![
+            "occurrenceID".to_string(),
+            "coordinateUncertaintyInMeters".to_string(),
+            "minimumElevationInMeters".to_string(),
+        ];
+        (conn, columns)
+    }
+
+    fn column_values(conn: &duckdb::Connection, column: &str) -> Vec<Option<String>> {
+        let occurrences = crate::overlay::occurrences_source(conn, "occurrenceID").unwrap();
+        conn.prepare(&format!("SELECT {column} FROM {occurrences} ORDER BY occurrenceID"))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_sentinels() {
+        let (conn, columns) = occurrences();
+
+        let counts = detect_sentinels(&conn, &columns).unwrap();
+
+        assert_eq!(counts, vec![
+            SentinelCount { column: "coordinateUncertaintyInMeters".to_string(), value: 0.0, count: 1 },
+            SentinelCount { column: "coordinateUncertaintyInMeters".to_string(), value: 99999.0, count: 1 },
+            SentinelCount { column: "minimumElevationInMeters".to_string(), value: -9999.0, count: 2 },
+        ]);
+    }
+
+    #[test]
+    fn test_map_and_restore_sentinels() {
+        let (conn, columns) = occurrences();
+
+        assert_eq!(map_sentinels_to_null(&conn, &columns).unwrap(), 4);
+        assert_eq!(map_sentinels_to_null(&conn, &columns).unwrap(), 0);
+        assert_eq!(
+            column_values(&conn, "minimumElevationInMeters"),
+            vec![Some("0".to_string()), None, None, Some("1200".to_string())]
+        );
+        // The archive's own values are kept
+        assert_eq!(detect_sentinels(&conn, &columns).unwrap().len(), 3);
+
+        assert_eq!(restore_sentinel_values(&conn).unwrap(), 4);
+        assert_eq!(
+            column_values(&conn, "coordinateUncertaintyInMeters"),
+            vec![Some("0".to_string()), Some("25".to_string()), Some("99999".to_string()), None]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use crate::error::{ChuckError, Result};
+
+/// Taxa ingested from a Darwin Core taxon file, one row per taxon
+pub const TAXONOMY_TABLE: &str = "taxonomy";
+
+/// Lookup of every ancestor of the scientific names in occurrences, one row
+/// per (name, ancestor), including each name as its own ancestor. Names are
+/// lowercase.
+pub const TAXON_ANCESTORS_TABLE: &str = "taxon_ancestors";
+
+/// Filter key matching records identified as the named taxon or any of its
+/// descendants, e.g. higherTaxon=Lepidoptera
+pub const HIGHER_TAXON_FILTER: &str = "higherTaxon";
+
+/// Whether a taxonomy has been loaded with load_taxonomy
+pub fn has_taxonomy(conn: &duckdb::Connection) -> Result<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_name = ?",
+        [TAXON_ANCESTORS_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(exists)
+}
+
+/// Loads a Darwin Core taxon file, like Taxon.tsv from the GBIF backbone or
+/// taxa.csv from the iNaturalist taxonomy archive, into the taxonomy table
+/// and rebuilds taxon_ancestors for the names in occurrences, replacing any
+/// taxonomy loaded before. The file needs taxonID, parentNameUsageID, and
+/// scientificName columns; canonicalName is preferred over scientificName
+/// when present since it leaves out authorship. Returns the number of taxa
+/// loaded. Needs a read-write connection.
+pub fn load_taxonomy(conn: &duckdb::Connection, path: &Path) -> Result<usize> {
+    // GBIF's tab-separated files don't quote values but names can contain
+    // stray quote characters
+    let quote = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("tsv" | "txt") => ", quote = ''",
+        _ => "",
+    };
+    let path = path.to_string_lossy().replace('\'', "''");
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TEMP VIEW taxon_source AS
+         SELECT * FROM read_csv('{path}', all_varchar = true{quote})"
+    ))?;
+    let mut stmt = conn.prepare(
+        "SELECT column_name FROM information_schema.columns WHERE table_name = 'taxon_source'"
+    )?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for required in ["taxonID", "parentNameUsageID", "scientificName"] {
+        if !columns.iter().any(|c| c == required) {
+            return Err(ChuckError::Taxonomy(format!(
+                "taxon file is missing a {required} column"
+            )));
+        }
+    }
+    let name = if columns.iter().any(|c| c == "canonicalName") {
+        "coalesce(nullif(\"canonicalName\", ''), \"scientificName\")"
+    } else {
+        "\"scientificName\""
+    };
+    let rank = if columns.iter().any(|c| c == "taxonRank") {
+        "\"taxonRank\""
+    } else {
+        "NULL"
+    };
+
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TABLE {TAXONOMY_TABLE} AS
+         SELECT \"taxonID\" AS id, nullif(\"parentNameUsageID\", '') AS parent_id,
+                {name} AS name, {rank} AS rank
+         FROM taxon_source WHERE \"taxonID\" IS NOT NULL;
+         DROP VIEW taxon_source;"
+    ))?;
+    rebuild_taxon_ancestors(conn)?;
+
+    let count: usize = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {TAXONOMY_TABLE}"),
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Walks up the taxonomy from every scientific name in occurrences. Only
+/// names in the archive are expanded so the lookup stays small even with
+/// the full GBIF backbone loaded.
+pub fn rebuild_taxon_ancestors(conn: &duckdb::Connection) -> Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TABLE {TAXON_ANCESTORS_TABLE} AS
+         WITH RECURSIVE lineage(name, ancestor_id, ancestor, depth) AS (
+             SELECT lower(t.name), t.parent_id, lower(t.name), 0
+             FROM {TAXONOMY_TABLE} t
+             WHERE lower(t.name) IN (
+                 SELECT DISTINCT lower(\"scientificName\") FROM occurrences
+             )
+             UNION ALL
+             SELECT l.name, p.parent_id, lower(p.name), l.depth + 1
+             FROM lineage l
+             JOIN {TAXONOMY_TABLE} p ON p.id = l.ancestor_id
+             WHERE l.depth < 100
+         )
+         SELECT DISTINCT name, ancestor FROM lineage;
+         CREATE INDEX IF NOT EXISTS idx_taxon_ancestors_ancestor ON {TAXON_ANCESTORS_TABLE}(ancestor);"
+    ))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_taxonomy() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("Taxon.tsv");
+        std::fs::write(
+            &path,
+            "taxonID\tparentNameUsageID\tscientificName\tcanonicalName\ttaxonRank\n\
+             1\t\tAnimalia\tAnimalia\tkingdom\n\
+             2\t1\tLepidoptera Linnaeus, 1758\tLepidoptera\torder\n\
+             3\t2\tDanaus plexippus (Linnaeus, 1758)\tDanaus plexippus\tspecies\n\
+             4\t1\tAraneae Clerck, 1757\tAraneae\torder\n",
+        )
+        .unwrap();
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE occurrences (occurrenceID VARCHAR, scientificName VARCHAR);
+             INSERT INTO occurrences VALUES ('1', 'Danaus plexippus'), ('2', 'Araneae'), ('3', NULL);"
+        ).unwrap();
+        assert!(!has_taxonomy(&conn).unwrap());
+
+        assert_eq!(load_taxonomy(&conn, &path).unwrap(), 4);
+
+        assert!(has_taxonomy(&conn).unwrap());
+        let names: Vec<String> = conn
+            .prepare(&format!(
+                "SELECT name FROM {TAXON_ANCESTORS_TABLE} WHERE ancestor = 'lepidoptera' ORDER BY name"
+            ))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(names, vec!["danaus plexippus"]);
+        let animals: usize = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {TAXON_ANCESTORS_TABLE} WHERE ancestor = 'animalia'"),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(animals, 2);
+    }
+
+    #[test]
+    fn test_load_taxonomy_requires_parent_column() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("taxa.csv");
+        std::fs::write(&path, "taxonID,scientificName\n1,Animalia\n").unwrap();
+        let conn = duckdb::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE occurrences (scientificName VARCHAR);").unwrap();
+
+        assert!(load_taxonomy(&conn, &path).is_err());
+        assert!(!has_taxonomy(&conn).unwrap());
+    }
+}
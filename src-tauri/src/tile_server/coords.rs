@@ -1,6 +1,14 @@
+use serde::Serialize;
 use std::f64::consts::PI;
 use tile_grid::Xyz;
 
+/// Latitude limit of the Web Mercator projection
+const MAX_MERCATOR_LAT: f64 = 85.051_128_78;
+
+/// Width of a tile on screen in MapLibre, in pixels
+const SCREEN_TILE_SIZE: f64 = 512.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BBox {
     pub west: f64,
     pub south: f64,
@@ -8,6 +16,23 @@ pub struct BBox {
     pub north: f64,
 }
 
+/// Size in degrees of the grid cells iNaturalist hides obscured coordinates
+/// in. The public coordinates are a random point in the same cell.
+const OBSCURATION_CELL_SIZE: f64 = 0.2;
+
+/// The obscuration cell containing a point, which also contains the
+/// occurrence's true location
+pub fn obscuration_cell(lat: f64, lng: f64) -> BBox {
+    let south = (lat / OBSCURATION_CELL_SIZE).floor() * OBSCURATION_CELL_SIZE;
+    let west = (lng / OBSCURATION_CELL_SIZE).floor() * OBSCURATION_CELL_SIZE;
+    BBox {
+        west,
+        south,
+        east: west + OBSCURATION_CELL_SIZE,
+        north: south + OBSCURATION_CELL_SIZE,
+    }
+}
+
 /// Convert tile coordinates (z, x, y) to geographic bounding box
 pub fn tile_to_bbox(z: u8, x: u32, y: u32) -> BBox {
     let tms = tile_grid::tms().lookup("WebMercatorQuad").unwrap();
@@ -45,24 +70,59 @@ pub fn lat_lng_to_tile_coords(lat: f64, lng: f64, z: u8, tile_x: u32, tile_y: u3
     (x, y)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Get which tile (XYZ) contains a given WGS84 lat/lng at a zoom level.
+/// Points beyond the Web Mercator limits land in the edge tiles.
+pub fn lat_lng_to_tile(lat: f64, lng: f64, zoom: u8) -> (u32, u32) {
+    let n = (1u32 << zoom) as f64;
+    let lat = lat.clamp(-MAX_MERCATOR_LAT, MAX_MERCATOR_LAT);
 
-    /// Get which tile (XYZ) contains a given WGS84 lat/lng at a zoom level
-    fn lat_lng_to_tile(lat: f64, lng: f64, zoom: u8) -> (u32, u32) {
-        let n = (1u32 << zoom) as f64;
+    // X tile calculation (simple longitude mapping)
+    let x_tile = ((lng.clamp(-180.0, 180.0) + 180.0) / 360.0 * n).floor() as u32;
 
-        // X tile calculation (simple longitude mapping)
-        let x_tile = ((lng + 180.0) / 360.0 * n).floor() as u32;
+    // Y tile calculation (Web Mercator projection)
+    let lat_rad = lat.to_radians();
+    let y_tile = ((1.0 - (lat_rad.tan() + (1.0 / lat_rad.cos())).ln() / PI) / 2.0 * n).floor() as u32;
 
-        // Y tile calculation (Web Mercator projection)
-        let lat_rad = lat.to_radians();
-        let y_tile = ((1.0 - (lat_rad.tan() + (1.0 / lat_rad.cos())).ln() / PI) / 2.0 * n).floor() as u32;
+    let max_tile = (1u32 << zoom) - 1;
+    (x_tile.min(max_tile), y_tile.min(max_tile))
+}
+
+/// Bounding box reaching `pixels` screen pixels from a point in every
+/// direction at a zoom level, which may be fractional like MapLibre's, e.g.
+/// to find what's under a click
+pub fn pixel_bbox(lat: f64, lng: f64, zoom: f64, pixels: f64) -> BBox {
+    let world_size = SCREEN_TILE_SIZE * 2f64.powf(zoom);
+    let lat_rad = lat.clamp(-MAX_MERCATOR_LAT, MAX_MERCATOR_LAT).to_radians();
+    let y = (1.0 - (lat_rad.tan() + (1.0 / lat_rad.cos())).ln() / PI) / 2.0 * world_size;
+    let y_to_lat = |y: f64| {
+        let merc_y = (y / world_size).clamp(0.0, 1.0);
+        (PI * (1.0 - 2.0 * merc_y)).sinh().atan().to_degrees()
+    };
+    let degrees = pixels / world_size * 360.0;
 
-        let max_tile = (1u32 << zoom) - 1;
-        (x_tile.min(max_tile), y_tile.min(max_tile))
+    BBox {
+        west: lng - degrees,
+        south: y_to_lat(y + pixels),
+        east: lng + degrees,
+        north: y_to_lat(y - pixels),
+    }
+}
+
+/// Size in degrees of the grid cells used to sample points at a zoom level,
+/// keeping one point per cell. At low zoom a coarse grid reduces points while
+/// preserving spatial extent; from zoom 9 every point is kept.
+pub fn sample_grid_size(zoom: u8) -> Option<f64> {
+    match zoom {
+        0..=2 => Some(1.0),    // ~111km cells - very coarse sampling
+        3..=5 => Some(0.1),    // ~11km cells - moderate sampling
+        6..=8 => Some(0.01),   // ~1km cells - fine sampling
+        _ => None              // No sampling at zoom 9+
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_tile_to_bbox() {
@@ -111,6 +171,21 @@ mod tests {
         assert_eq!(y, 0.0, "Z=10: 0° latitude is at north edge (y=0)");
     }
 
+    #[test]
+    fn test_pixel_bbox() {
+        // At zoom 0 the world is 512 pixels wide
+        let bbox = pixel_bbox(0.0, 0.0, 0.0, 256.0);
+        assert!((bbox.west - (-180.0)).abs() < 1e-9);
+        assert!((bbox.east - 180.0).abs() < 1e-9);
+        assert!((bbox.north - MAX_MERCATOR_LAT).abs() < 1e-6);
+        assert!((bbox.south + MAX_MERCATOR_LAT).abs() < 1e-6);
+
+        // Mercator stretches latitude away from the equator
+        let bbox = pixel_bbox(60.0, 10.0, 12.0, 5.0);
+        assert!(bbox.north > 60.0 && bbox.south < 60.0);
+        assert!(bbox.north - bbox.south < bbox.east - bbox.west);
+    }
+
     #[test]
     fn test_costa_rica_point_at_multiple_zooms() {
         // San Isidro, Costa Rica: 10.0176473153°N, -84.0507658571°W
@@ -5,7 +5,7 @@ pub mod protocol;
 use tauri::plugin::{Builder, TauriPlugin};
 use tauri::Runtime;
 
-pub use protocol::generate_tile;
+pub use protocol::{generate_tile, ObscuredMode, TilePoint};
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
     Builder::new("tile-server")
@@ -6,22 +6,69 @@ pub struct OccurrencePoint {
     pub x: f64,
     pub y: f64,
     pub scientific_name: Option<String>,
+    pub event_date: Option<String>,
+    /// Value of the attribute the map is styled by, if any
+    pub attribute: Option<String>,
+    /// Whether the coordinates were obscured
+    pub obscured: bool,
+    /// Top left and bottom right corners of an obscuration cell to draw
+    /// instead of the point
+    pub cell: Option<((f64, f64), (f64, f64))>,
+    /// Radius in tile pixels of a coordinate uncertainty circle to draw
+    /// around the point
+    pub uncertainty_radius: Option<f64>,
 }
 
-/// Encode occurrence points as MVT protobuf bytes
-pub fn encode_tile(points: Vec<OccurrencePoint>) -> Vec<u8> {
+/// Vertices of the polygons standing in for uncertainty circles
+const CIRCLE_VERTICES: usize = 32;
+
+/// Encode occurrence points as MVT protobuf bytes. Attribute values are
+/// added as a property named after `attribute_name`. Points with a cell are
+/// drawn as that cell's polygon. Uncertainty circles go in a separate
+/// "uncertainty" layer, which is left out if there are none.
+pub fn encode_tile(points: Vec<OccurrencePoint>, attribute_name: Option<&str>) -> Vec<u8> {
     let mut tile = Tile::new(4096);
     let mut layer = tile.create_layer("occurrences");
+    let mut uncertainty_layer = tile.create_layer("uncertainty");
 
     for point in points {
-        // Round coordinates to integers
-        let x_rounded = point.x.round();
-        let y_rounded = point.y.round();
+        if let Some(radius) = point.uncertainty_radius {
+            // Angles increase clockwise with y pointing down, which is the
+            // winding exterior rings need
+            let mut encoder = GeomEncoder::new(GeomType::Polygon, Transform::default());
+            for i in 0..CIRCLE_VERTICES {
+                let angle = i as f64 / CIRCLE_VERTICES as f64 * std::f64::consts::TAU;
+                let x = point.x + radius * angle.cos();
+                let y = point.y + radius * angle.sin();
+                encoder.add_point(x.round(), y.round()).unwrap();
+            }
+            encoder.complete_geom().unwrap();
+            let mut feature = uncertainty_layer.into_feature(encoder.encode().unwrap());
+            feature.add_tag_string("core_id", &point.core_id);
+            uncertainty_layer = feature.into_layer();
+        }
 
-        // Create geometry encoder
-        let mut encoder = GeomEncoder::new(GeomType::Point, Transform::default());
-        encoder.add_point(x_rounded, y_rounded).unwrap();
-        let geom_data = encoder.encode().unwrap();
+        let geom_data = match point.cell {
+            Some(((left, top), (right, bottom))) => {
+                // Exterior rings wind clockwise with y pointing down
+                let mut encoder = GeomEncoder::new(GeomType::Polygon, Transform::default());
+                for (x, y) in [(left, top), (right, top), (right, bottom), (left, bottom)] {
+                    encoder.add_point(x.round(), y.round()).unwrap();
+                }
+                encoder.complete_geom().unwrap();
+                encoder.encode().unwrap()
+            }
+            None => {
+                // Round coordinates to integers
+                let x_rounded = point.x.round();
+                let y_rounded = point.y.round();
+
+                // Create geometry encoder
+                let mut encoder = GeomEncoder::new(GeomType::Point, Transform::default());
+                encoder.add_point(x_rounded, y_rounded).unwrap();
+                encoder.encode().unwrap()
+            }
+        };
 
         // Create feature with geometry
         let mut feature = layer.into_feature(geom_data);
@@ -31,11 +78,23 @@ pub fn encode_tile(points: Vec<OccurrencePoint>) -> Vec<u8> {
         if let Some(name) = point.scientific_name {
             feature.add_tag_string("scientificName", &name);
         }
+        if let Some(event_date) = point.event_date {
+            feature.add_tag_string("eventDate", &event_date);
+        }
+        if let (Some(attribute_name), Some(value)) = (attribute_name, point.attribute) {
+            feature.add_tag_string(attribute_name, &value);
+        }
+        if point.obscured {
+            feature.add_tag_bool("obscured", true);
+        }
 
         layer = feature.into_layer();
     }
 
     tile.add_layer(layer).unwrap();
+    if uncertainty_layer.num_features() > 0 {
+        tile.add_layer(uncertainty_layer).unwrap();
+    }
     tile.to_bytes().unwrap()
 }
 
@@ -45,7 +104,7 @@ mod tests {
 
     #[test]
     fn test_encode_empty_tile() {
-        let tile = encode_tile(Vec::new());
+        let tile = encode_tile(Vec::new(), None);
         assert!(!tile.is_empty());
         // MVT protobuf should have minimal structure even when empty
     }
@@ -58,10 +117,75 @@ mod tests {
                 x: 2048.0,
                 y: 2048.0,
                 scientific_name: Some("Quercus alba".to_string()),
+                event_date: Some("2024-05-01".to_string()),
+                attribute: None,
+                obscured: false,
+                cell: None,
+                uncertainty_radius: None,
             },
         ];
-        let tile = encode_tile(points);
+        let tile = encode_tile(points, None);
         assert!(!tile.is_empty());
         assert!(tile.len() > 10); // Should have actual content
     }
+
+    #[test]
+    fn test_encode_tile_with_attribute() {
+        let points = vec![
+            OccurrencePoint {
+                core_id: "1".to_string(),
+                x: 2048.0,
+                y: 2048.0,
+                scientific_name: None,
+                event_date: None,
+                attribute: Some("Plantae".to_string()),
+                obscured: false,
+                cell: None,
+                uncertainty_radius: None,
+            },
+        ];
+        let tile = encode_tile(points, Some("kingdom"));
+        let contains = |needle: &[u8]| tile.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"kingdom"));
+        assert!(contains(b"Plantae"));
+    }
+
+    #[test]
+    fn test_encode_tile_with_obscuration_cell() {
+        let points = vec![
+            OccurrencePoint {
+                core_id: "1".to_string(),
+                x: 2048.0,
+                y: 2048.0,
+                scientific_name: None,
+                event_date: None,
+                attribute: None,
+                obscured: true,
+                cell: Some(((1000.0, 1000.0), (3000.0, 3000.0))),
+                uncertainty_radius: None,
+            },
+        ];
+        let tile = encode_tile(points, None);
+        let contains = |needle: &[u8]| tile.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"obscured"));
+    }
+
+    #[test]
+    fn test_encode_tile_with_uncertainty_circle() {
+        let point = |uncertainty_radius| OccurrencePoint {
+            core_id: "1".to_string(),
+            x: 2048.0,
+            y: 2048.0,
+            scientific_name: None,
+            event_date: None,
+            attribute: None,
+            obscured: false,
+            cell: None,
+            uncertainty_radius,
+        };
+        let contains = |tile: &[u8], needle: &[u8]| tile.windows(needle.len()).any(|w| w == needle);
+
+        assert!(contains(&encode_tile(vec![point(Some(100.0))], None), b"uncertainty"));
+        assert!(!contains(&encode_tile(vec![point(None)], None), b"uncertainty"));
+    }
 }
@@ -1,29 +1,113 @@
+use serde::Serialize;
 use tauri::Runtime;
 use crate::search_params::SearchParams;
 
-use super::coords::{lat_lng_to_tile_coords};
+use super::coords::{lat_lng_to_tile_coords, BBox};
 use super::mvt::{OccurrencePoint, encode_tile};
 
-/// Generate MVT tile for given coordinates and occurrence data
+/// How tiles treat occurrences whose informationWithheld says their
+/// coordinates were obscured, which are often far from the true location
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ObscuredMode {
+    /// Draw them like any other point
+    #[default]
+    Ignore,
+    /// Give their points an `obscured` property
+    Mark,
+    /// Mark them and draw the cell the true location is in instead of a
+    /// point
+    Cell,
+}
+
+impl ObscuredMode {
+    /// Mode from the `tile_obscured` tile param: "mark" or "cell"
+    pub fn from_param(value: Option<&str>) -> Self {
+        match value {
+            Some("mark") => Self::Mark,
+            Some("cell") => Self::Cell,
+            _ => Self::Ignore,
+        }
+    }
+}
+
+/// An occurrence to draw on the map, as returned by Archive::query_tile
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TilePoint {
+    pub core_id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub scientific_name: Option<String>,
+    pub event_date: Option<String>,
+    /// Value of the tile_attribute column, if one was requested
+    pub attribute: Option<String>,
+    /// Whether the coordinates were obscured, if that was asked for
+    pub obscured: bool,
+    /// Cell containing the true location of an obscured occurrence, with
+    /// ObscuredMode::Cell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<BBox>,
+    /// coordinateUncertaintyInMeters, if the archive has it
+    pub uncertainty: Option<f64>,
+}
+
+/// Lowest zoom to draw uncertainty circles at. Points are sampled below
+/// this, so circles there would stand for arbitrary occurrences.
+const MIN_UNCERTAINTY_ZOOM: u8 = 9;
+
+/// Radius in tile pixels of a circle of `meters` at a latitude and zoom,
+/// capped at the tile's width so vague records don't blanket the map. None
+/// for circles too small to see.
+fn uncertainty_radius(meters: f64, lat: f64, z: u8) -> Option<f64> {
+    const EARTH_CIRCUMFERENCE_METERS: f64 = 40_075_016.686;
+    let tile_meters = EARTH_CIRCUMFERENCE_METERS * lat.to_radians().cos() / 2f64.powi(z as i32);
+    let radius = meters / tile_meters * 4096.0;
+    (radius.is_finite() && radius >= 2.0).then_some(radius.min(4096.0))
+}
+
+/// Generate MVT tile for given coordinates and occurrence data. Attribute
+/// values become a feature property named `attribute_name`. With
+/// `uncertainty`, occurrences with a coordinateUncertaintyInMeters also get
+/// a circle in an "uncertainty" layer from MIN_UNCERTAINTY_ZOOM up. Circles
+/// around points in other tiles are cut off at this tile's edge.
 pub fn generate_tile(
     z: u8,
     x: u32,
     y: u32,
-    occurrences: Vec<(String, f64, f64, Option<String>)>
+    occurrences: Vec<TilePoint>,
+    attribute_name: Option<&str>,
+    uncertainty: bool,
 ) -> Vec<u8> {
+    let draw_uncertainty = uncertainty && z >= MIN_UNCERTAINTY_ZOOM;
     // Convert occurrences to tile coordinates
     let points: Vec<OccurrencePoint> = occurrences
         .into_iter()
-        .filter_map(|(core_id, lat, lng, name)| {
-            let (tile_x, tile_y) = lat_lng_to_tile_coords(lat, lng, z, x, y);
+        .filter_map(|occurrence| {
+            let (tile_x, tile_y) =
+                lat_lng_to_tile_coords(occurrence.latitude, occurrence.longitude, z, x, y);
 
             // Only include points that are within the tile extent (0-4096)
             if (0.0..=4096.0).contains(&tile_x) && (0.0..=4096.0).contains(&tile_y) {
+                // Corners outside the tile get clamped to its edge
+                let cell = occurrence.cell.map(|cell| {
+                    (
+                        lat_lng_to_tile_coords(cell.north, cell.west, z, x, y),
+                        lat_lng_to_tile_coords(cell.south, cell.east, z, x, y),
+                    )
+                });
                 Some(OccurrencePoint {
-                    core_id,
+                    core_id: occurrence.core_id,
                     x: tile_x,
                     y: tile_y,
-                    scientific_name: name,
+                    scientific_name: occurrence.scientific_name,
+                    event_date: occurrence.event_date,
+                    attribute: occurrence.attribute,
+                    obscured: occurrence.obscured,
+                    cell,
+                    uncertainty_radius: occurrence
+                        .uncertainty
+                        .filter(|_| draw_uncertainty)
+                        .and_then(|meters| uncertainty_radius(meters, occurrence.latitude, z)),
                 })
             } else {
                 None
@@ -31,7 +115,7 @@ pub fn generate_tile(
         })
         .collect();
 
-    encode_tile(points)
+    encode_tile(points, attribute_name)
 }
 
 pub fn handle_tile_request<R: Runtime>(
@@ -104,7 +188,19 @@ pub fn handle_tile_request<R: Runtime>(
         let result = (|| -> Result<Vec<u8>, String> {
             let archives_dir = crate::commands::archive::get_archives_dir(app_handle.clone())
                 .map_err(|e| e.to_string())?;
-            let archive = crate::dwca::Archive::current(&archives_dir)
+            // archive_id picks one of several open archives and isn't a filter
+            let mut search_params = SearchParams::from_uri(uri);
+            let archive_id = search_params.filters.remove("archive_id");
+            // Column to include in the tiles for styling points by value
+            let attribute = search_params.filters.remove("tile_attribute");
+            let obscured = ObscuredMode::from_param(
+                search_params.filters.remove("tile_obscured").as_deref(),
+            );
+            let uncertainty = search_params
+                .filters
+                .remove("tile_uncertainty")
+                .is_some_and(|value| value == "true");
+            let archive = crate::dwca::Archive::find(&archives_dir, archive_id.as_deref())
                 .map_err(|e| e.to_string())?;
 
             // Calculate bounding box for this tile
@@ -117,11 +213,13 @@ pub fn handle_tile_request<R: Runtime>(
                 bbox.east,
                 bbox.north,
                 z,
-                SearchParams::from_uri(uri),
+                search_params,
+                attribute.as_deref(),
+                obscured,
             ).map_err(|e| e.to_string())?;
 
             // Generate MVT tile
-            Ok(generate_tile(z, x, y, occurrences))
+            Ok(generate_tile(z, x, y, occurrences, attribute.as_deref(), uncertainty))
         })();
 
         match result {
@@ -157,17 +255,38 @@ mod tests {
     #[test]
     fn test_generate_tile_returns_mvt() {
         // Test with empty data
-        let tile = generate_tile(0, 0, 0, Vec::new());
+        let tile = generate_tile(0, 0, 0, Vec::new(), None, false);
         assert!(!tile.is_empty());
     }
 
     #[test]
     fn test_generate_tile_with_data() {
         let occurrences = vec![
-            ("1".to_string(), 0.0, 0.0, Some("Test species".to_string())),
+            TilePoint {
+                core_id: "1".to_string(),
+                latitude: 0.0,
+                longitude: 0.0,
+                scientific_name: Some("Test species".to_string()),
+                event_date: None,
+                attribute: None,
+                obscured: false,
+                cell: None,
+                uncertainty: None,
+            },
         ];
-        let tile = generate_tile(0, 0, 0, occurrences);
+        let tile = generate_tile(0, 0, 0, occurrences, None, false);
         assert!(!tile.is_empty());
         assert!(tile.len() > 10);
     }
+
+    #[test]
+    fn test_uncertainty_radius() {
+        // A tile at zoom 12 is about 9.8km wide at the equator
+        let radius = uncertainty_radius(1000.0, 0.0, 12).unwrap();
+        assert!((radius - 419.0).abs() < 1.0);
+        // Higher latitudes have fewer meters per pixel
+        assert!(uncertainty_radius(1000.0, 60.0, 12).unwrap() > radius);
+        assert_eq!(uncertainty_radius(1.0, 0.0, 9), None);
+        assert_eq!(uncertainty_radius(1_000_000.0, 0.0, 12), Some(4096.0));
+    }
 }
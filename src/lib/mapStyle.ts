@@ -1,6 +1,6 @@
 import { layers, namedFlavor } from '@protomaps/basemaps';
 import type { StyleSpecification } from 'maplibre-gl';
-import { getBasemapUrlBase } from '$lib/tauri-api';
+import { type BasemapInfo, getBasemapUrlBase } from '$lib/tauri-api';
 
 const OSM_ATTRIBUTION =
   '<a target="_blank" href="https://openstreetmap.org/copyright">OpenStreetMap</a>';
@@ -15,13 +15,40 @@ const SPRITE_URL =
 
 /**
  * Build a MapLibre style using the offline Protomaps vector basemap
- * if available, otherwise fall back to online OSM raster tiles.
+ * if available, otherwise fall back to online OSM raster tiles. An offline
+ * raster basemap (e.g. satellite imagery) is drawn over the base layers.
  */
-export function buildMapStyle(hasBasemap: boolean): StyleSpecification {
-  if (hasBasemap) {
-    return buildVectorStyle();
+export function buildMapStyle(
+  hasBasemap: boolean,
+  rasterBasemap?: BasemapInfo,
+): StyleSpecification {
+  const style = hasBasemap ? buildVectorStyle() : buildRasterStyle();
+  if (rasterBasemap) {
+    addRasterBasemap(style, rasterBasemap);
   }
-  return buildRasterStyle();
+  return style;
+}
+
+function addRasterBasemap(
+  style: StyleSpecification,
+  basemap: BasemapInfo,
+): void {
+  const basemapUrl = getBasemapUrlBase();
+  const { bounds } = basemap;
+  style.sources.raster = {
+    type: 'raster',
+    tiles: [`${basemapUrl}/raster/${basemap.id}/{z}/{x}/{y}`],
+    tileSize: 256,
+    maxzoom: basemap.maxZoom,
+    ...(bounds && {
+      bounds: [bounds.minLon, bounds.minLat, bounds.maxLon, bounds.maxLat],
+    }),
+  };
+  style.layers.push({
+    id: 'raster-basemap',
+    type: 'raster',
+    source: 'raster',
+  });
 }
 
 function buildVectorStyle(): StyleSpecification {
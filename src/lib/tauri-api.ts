@@ -12,12 +12,22 @@ import {
 import { getCurrentWebview as tauriGetCurrentWebview } from '@tauri-apps/api/webview';
 import { getCurrentWindow as tauriGetCurrentWindow } from '@tauri-apps/api/window';
 import {
+  type ConfirmDialogOptions,
   type OpenDialogOptions,
   type SaveDialogOptions,
+  ask as tauriAsk,
   open as tauriOpen,
   save as tauriSave,
 } from '@tauri-apps/plugin-dialog';
-import type { ArchiveInfo, SearchResult } from '$lib/types/archive';
+import type {
+  ArchiveInfo,
+  CoreIdStrategy,
+  ImportIssue,
+  ImportWarning,
+  Occurrence,
+  SearchResult,
+} from '$lib/types/archive';
+import { errorCode } from '$lib/utils/errors';
 import type { SearchParams } from '$lib/utils/filterCategories';
 
 // Interface for mock Tauri object used in tests
@@ -29,6 +39,10 @@ interface MockTauri {
   showSaveDialog(
     options?: SaveDialogOptions,
   ): Promise<string | string[] | null>;
+  showAskDialog(
+    message: string,
+    options?: ConfirmDialogOptions,
+  ): Promise<boolean>;
   getCurrentWindow(): ReturnType<typeof tauriGetCurrentWindow>;
   getCurrentWebview(): ReturnType<typeof tauriGetCurrentWebview>;
   listen<T>(event: string, handler: EventCallback<T>): Promise<() => void>;
@@ -37,9 +51,23 @@ interface MockTauri {
 export interface AggregationResult {
   count: number;
   photoUrl?: string | null;
+  // Whether photoUrl loads without the network: a photo in the archive or a
+  // remote photo that's already cached
+  photoIsLocal?: boolean;
   value: string | null;
+  // Metrics, present when requested
+  speciesCount?: number;
+  earliestEventDate?: string | null;
+  latestEventDate?: string | null;
+  withPhotosCount?: number;
 }
 
+export type AggregationMetric =
+  | 'speciesCount'
+  | 'earliestEventDate'
+  | 'latestEventDate'
+  | 'withPhotosCount';
+
 // Check if we're in test mode with mocks available
 const hasMocks = typeof window !== 'undefined' && '__MOCK_TAURI__' in window;
 
@@ -75,6 +103,16 @@ export async function showSaveDialog(
   return tauriSave(options);
 }
 
+export async function showAskDialog(
+  message: string,
+  options?: ConfirmDialogOptions,
+): Promise<boolean> {
+  if (hasMocks) {
+    return getMockTauri().showAskDialog(message, options);
+  }
+  return tauriAsk(message, options);
+}
+
 export function getCurrentWindow() {
   if (hasMocks) {
     return getMockTauri().getCurrentWindow();
@@ -108,9 +146,12 @@ export interface Bounds {
   maxLat: number;
 }
 
+export type BasemapKind = 'vector' | 'raster';
+
 export interface BasemapInfo {
   id: string;
   name: string;
+  kind: BasemapKind;
   maxZoom: number;
   bounds: Bounds | null;
   downloadDate: string;
@@ -138,6 +179,47 @@ export async function downloadRegionalBasemap(
   });
 }
 
+/**
+ * Download raster tiles (e.g. satellite imagery) from an XYZ tile URL
+ * template containing {z}, {x}, and {y}. Omit bounds to download the whole
+ * world.
+ */
+export async function downloadRasterBasemap(
+  urlTemplate: string,
+  maxZoom: number,
+  bounds?: Bounds,
+  name?: string,
+): Promise<void> {
+  return invoke('download_raster_basemap', {
+    urlTemplate,
+    maxZoom,
+    bounds,
+    name,
+  });
+}
+
+/**
+ * Copy a .pmtiles file or convert a .mbtiles file into the basemaps
+ * directory.
+ */
+export async function importBasemap(
+  path: string,
+  name?: string,
+): Promise<BasemapInfo> {
+  return invoke<BasemapInfo>('import_basemap', { path, name });
+}
+
+/**
+ * Download Terrarium elevation tiles used to fill in missing elevations.
+ * Omit bounds to download the whole world.
+ */
+export async function downloadDem(
+  maxZoom: number,
+  bounds?: Bounds,
+): Promise<void> {
+  return invoke('download_dem', { maxZoom, bounds });
+}
+
 export async function reverseGeocode(
   lat: number,
   lon: number,
@@ -146,6 +228,37 @@ export async function reverseGeocode(
   return invoke<string>('reverse_geocode', { lat, lon, zoom });
 }
 
+export interface Place {
+  name: string;
+  bbox: Bounds;
+  /** [longitude, latitude] */
+  center: [number, number];
+}
+
+/**
+ * Search places by name, using the offline gazetteer if it's been downloaded
+ * and Nominatim otherwise.
+ */
+export async function searchPlaces(
+  query: string,
+  limit?: number,
+): Promise<Place[]> {
+  return invoke<Place[]>('search_places', { query, limit });
+}
+
+export async function downloadGazetteer(): Promise<void> {
+  return invoke('download_gazetteer');
+}
+
+/**
+ * Download the country boundaries the quality report checks coordinates
+ * against and the state/province and county boundaries used to fill in
+ * missing places.
+ */
+export async function downloadBoundaries(): Promise<void> {
+  return invoke('download_boundaries');
+}
+
 export async function estimateRegionalSize(
   bounds: Bounds,
   maxZoom: number,
@@ -179,6 +292,53 @@ export function getBasemapUrlBase(): string {
   return isWindows ? 'http://basemap.localhost' : 'basemap://localhost';
 }
 
+/**
+ * URL that plays or shows media from the archive, or a remote URL, through the
+ * media:// custom protocol. Same Windows caveat as getTileUrlBase.
+ */
+export function getMediaUrl(mediaPath: string, archiveId?: string): string {
+  const isWindows =
+    typeof navigator !== 'undefined' &&
+    navigator.userAgent.toLowerCase().includes('windows');
+  const base = isWindows ? 'http://media.localhost' : 'media://localhost';
+  const archiveParam = archiveId
+    ? `&archive_id=${encodeURIComponent(archiveId)}`
+    : '';
+  return `${base}/?path=${encodeURIComponent(mediaPath)}${archiveParam}`;
+}
+
+export interface MapPoint {
+  coreId: string;
+  latitude: number;
+  longitude: number;
+  scientificName: string | null;
+  eventDate: string | null;
+  attribute: string | null;
+  /** Whether informationWithheld says the coordinates were obscured */
+  obscured: boolean;
+}
+
+/**
+ * Occurrences within `tolerance` pixels (5 by default) of a point on the map,
+ * nearest first. Tiles keep one point per grid cell when zoomed out, so this
+ * finds the occurrences a clicked point stands for.
+ */
+export async function getOccurrencesAtPoint(
+  lat: number,
+  lng: number,
+  zoom: number,
+  searchParams: SearchParams,
+  tolerance?: number,
+): Promise<MapPoint[]> {
+  return invoke<MapPoint[]>('get_occurrences_at_point', {
+    lat,
+    lng,
+    zoom,
+    tolerance,
+    searchParams,
+  });
+}
+
 export async function exportCsv(
   searchParams: SearchParams,
   path: string,
@@ -193,11 +353,81 @@ export async function exportKml(
   return invoke('export_kml', { searchParams, path });
 }
 
+/**
+ * Exports filtered occurrences as a PMTiles vector tileset. maxZoom defaults
+ * to 12 on the backend.
+ */
+export async function exportPmtiles(
+  searchParams: SearchParams,
+  path: string,
+  maxZoom?: number,
+): Promise<void> {
+  return invoke('export_pmtiles', { searchParams, path, maxZoom });
+}
+
+/**
+ * Exports filtered occurrences as an Apache Parquet file. Extension rows are
+ * written as nested columns unless includeExtensions is false.
+ */
+export async function exportParquet(
+  searchParams: SearchParams,
+  path: string,
+  includeExtensions?: boolean,
+): Promise<void> {
+  return invoke('export_parquet', { searchParams, path, includeExtensions });
+}
+
+/**
+ * Exports the whole archive, ignoring filters, as a SQLite database with
+ * occurrences and extensions in their own tables.
+ */
+export async function exportSqlite(path: string): Promise<void> {
+  return invoke('export_sqlite', { path });
+}
+
+/**
+ * Exports every flag, with its note, as a CSV to share with data providers.
+ */
+export async function exportFlagsCsv(path: string): Promise<void> {
+  return invoke('export_flags_csv', { path });
+}
+
+/**
+ * Exports filtered occurrences as a DarwinCore Archive. With ipt, data files
+ * are tab-delimited UTF-8, meta.xml and eml.xml are written the way an IPT
+ * expects, and the export fails if the package doesn't validate. With
+ * allowedLicenses, only media with those license codes are exported. With
+ * omitExcluded, occurrences excluded with excludeOccurrences are left out.
+ */
 export async function exportDwca(
   searchParams: SearchParams,
   path: string,
+  ipt = false,
+  allowedLicenses?: string[],
+  omitExcluded = false,
 ): Promise<void> {
-  return invoke('export_dwca', { searchParams, path });
+  return invoke('export_dwca', {
+    searchParams,
+    path,
+    ipt,
+    allowedLicenses,
+    omitExcluded,
+  });
+}
+
+export interface LicenseCount {
+  // Normalized code like CC-BY-NC, CC0, ARR, or "none" for no license
+  license: string;
+  count: number;
+}
+
+/**
+ * License codes of the media of the filtered occurrences, most common first
+ */
+export async function getMediaLicenseSummary(
+  searchParams: SearchParams,
+): Promise<LicenseCount[]> {
+  return invoke<LicenseCount[]>('get_media_license_summary', { searchParams });
 }
 
 export async function exportGroupsCsv(
@@ -212,11 +442,409 @@ export async function aggregateByField(
   selectedField: string,
   searchParams: SearchParams,
   limit: number,
+  splitValues = false,
+  metrics: AggregationMetric[] = [],
+  orderBy: AggregationMetric | null = null,
 ) {
   return invoke<AggregationResult[]>('aggregate_by_field', {
     fieldName: selectedField,
     searchParams,
     limit,
+    splitValues,
+    metrics,
+    orderBy,
+  });
+}
+
+/**
+ * Allows enrichments and other changes to the current archive. Archives are
+ * read-only until unlocked.
+ */
+export async function unlockArchive() {
+  return invoke<ArchiveInfo>('unlock_archive');
+}
+
+export async function lockArchive() {
+  return invoke<ArchiveInfo>('lock_archive');
+}
+
+/**
+ * Splits pipe or semicolon delimited fields like recordedBy into individual
+ * values for filtering and aggregation. Returns the names of split fields.
+ */
+export async function splitMultiValueFields() {
+  return invoke<string[]>('split_multi_value_fields');
+}
+
+/**
+ * Loads a Darwin Core taxon file (e.g. the GBIF backbone's Taxon.tsv or
+ * taxa.csv from the iNaturalist taxonomy archive) so the higherTaxon filter
+ * matches records identified at any rank below the filtered taxon. Returns
+ * the number of taxa loaded.
+ */
+export async function loadTaxonomy(path: string) {
+  return invoke<number>('load_taxonomy', { path });
+}
+
+export interface SqlColumn {
+  name: string;
+  data_type: string;
+}
+
+export interface SqlResult {
+  columns: SqlColumn[];
+  rows: unknown[][];
+  truncated: boolean;
+}
+
+/**
+ * Runs a SELECT query against the open archive's database. Other statements
+ * are rejected. Returns at most limit rows, 1000 by default.
+ */
+export async function executeSql(
+  sql: string,
+  limit?: number,
+  archiveId?: string,
+) {
+  return invoke<SqlResult>('execute_sql', { sql, limit, archiveId });
+}
+
+export interface GroupExample {
+  record: Occurrence;
+  photoUrl: string | null;
+}
+
+export async function getGroupExamples(
+  fieldName: string,
+  value: string | null,
+  searchParams: SearchParams,
+  limit: number,
+) {
+  return invoke<GroupExample[]>('get_group_examples', {
+    fieldName,
+    value,
+    searchParams,
+    limit,
+  });
+}
+
+export interface CrosstabResult {
+  primary: string | null;
+  secondary: string | null;
+  count: number;
+}
+
+export async function aggregateByTwoFields(
+  primaryField: string,
+  secondaryField: string,
+  searchParams: SearchParams,
+  limitPerGroup: number | null,
+) {
+  return invoke<CrosstabResult[]>('aggregate_by_two_fields', {
+    primaryField,
+    secondaryField,
+    searchParams,
+    limitPerGroup,
+  });
+}
+
+export interface ColumnStats {
+  column: string;
+  count: number;
+  nullCount: number;
+  nonNumericCount: number;
+  min: number | null;
+  max: number | null;
+  mean: number | null;
+  median: number | null;
+  p5: number | null;
+  p25: number | null;
+  p75: number | null;
+  p95: number | null;
+}
+
+export async function columnStats(
+  columnName: string,
+  searchParams: SearchParams,
+) {
+  return invoke<ColumnStats>('column_stats', { columnName, searchParams });
+}
+
+export interface QualityCheckResult {
+  id: string;
+  description: string;
+  ran: boolean;
+  count: number;
+  exampleIds: string[];
+}
+
+export interface QualityReport {
+  total: number;
+  checks: QualityCheckResult[];
+}
+
+export async function runQualityReport() {
+  return invoke<QualityReport>('run_quality_report');
+}
+
+/**
+ * Maps placeholder values like -9999 in numeric fields to NULL so stats,
+ * maps, and exports treat them as missing. Requires an unlocked archive.
+ */
+export async function mapSentinelValues(): Promise<number> {
+  return invoke<number>('map_sentinel_values');
+}
+
+export async function restoreSentinelValues(): Promise<number> {
+  return invoke<number>('restore_sentinel_values');
+}
+
+export interface Edit {
+  coreId: string;
+  column: string;
+  oldValue: string | null;
+  newValue: string | null;
+  editedAt: string;
+}
+
+/**
+ * Sets one field of an occurrence, e.g. to fix a typo, and logs the change.
+ * An empty or null value clears the field. Requires an unlocked archive.
+ */
+export async function updateOccurrenceField(
+  coreId: string,
+  column: string,
+  value: string | null,
+): Promise<Edit> {
+  return invoke<Edit>('update_occurrence_field', { coreId, column, value });
+}
+
+export async function listEdits(coreId?: string): Promise<Edit[]> {
+  return invoke<Edit[]>('list_edits', { coreId: coreId ?? null });
+}
+
+export interface Flag {
+  coreId: string;
+  flag: string;
+  note: string | null;
+  flaggedAt: string;
+}
+
+/**
+ * Flags an occurrence for review, e.g. with "suspect coordinates". Flagged
+ * occurrences can be found with the `flagged` filter, which takes a label,
+ * "true" for any flag, or "false" for none. Requires an unlocked archive.
+ */
+export async function flagOccurrence(
+  coreId: string,
+  flag: string,
+  note?: string,
+): Promise<Flag> {
+  return invoke<Flag>('flag_occurrence', { coreId, flag, note: note ?? null });
+}
+
+export async function unflagOccurrence(coreId: string, flag: string): Promise<boolean> {
+  return invoke<boolean>('unflag_occurrence', { coreId, flag });
+}
+
+export async function listFlags(coreId?: string): Promise<Flag[]> {
+  return invoke<Flag[]>('list_flags', { coreId: coreId ?? null });
+}
+
+export interface Exclusion {
+  coreId: string;
+  reason: string | null;
+  excludedAt: string;
+}
+
+/**
+ * Excludes the occurrences matching searchParams from DarwinCore Archive
+ * exports with omitExcluded, without deleting them. Returns the number newly
+ * excluded. Requires an unlocked archive.
+ */
+export async function excludeOccurrences(
+  searchParams: SearchParams,
+  reason?: string,
+): Promise<number> {
+  return invoke<number>('exclude_occurrences', { searchParams, reason: reason ?? null });
+}
+
+/**
+ * Restores excluded occurrences by core ID, or all of them without any.
+ * Returns the number restored.
+ */
+export async function restoreExclusions(coreIds?: string[]): Promise<number> {
+  return invoke<number>('restore_exclusions', { coreIds: coreIds ?? null });
+}
+
+export async function listExclusions(): Promise<Exclusion[]> {
+  return invoke<Exclusion[]>('list_exclusions');
+}
+
+export type TimeBucket = 'month' | 'week' | 'year';
+
+export interface TimeAggregationResult {
+  bucket: number;
+  count: number;
+}
+
+export async function aggregateByTime(
+  bucket: TimeBucket,
+  searchParams: SearchParams,
+) {
+  return invoke<TimeAggregationResult[]>('aggregate_by_time', {
+    bucket,
+    searchParams,
+  });
+}
+
+export interface ValueCount {
+  value: string;
+  count: number;
+}
+
+export interface ColumnValues {
+  /** Most common first */
+  values: ValueCount[];
+  /** Distinct values in all, including ones past the limit */
+  distinctCount: number;
+}
+
+/**
+ * Most common values of a text column among matching occurrences, e.g. for
+ * "recordedBy (1,234 collectors)". With `searchTerm`, only values containing
+ * it are included.
+ */
+export async function getColumnValues(
+  columnName: string,
+  searchParams: SearchParams,
+  searchTerm?: string,
+  limit?: number,
+) {
+  return invoke<ColumnValues>('get_column_values', {
+    columnName,
+    searchParams,
+    searchTerm: searchTerm ?? null,
+    limit: limit ?? null,
+  });
+}
+
+export interface Facet {
+  column: string;
+  /** Most common first */
+  values: ValueCount[];
+  /** Distinct values in all, including ones past the limit */
+  distinctCount: number;
+}
+
+/**
+ * Most common values of each column among matching occurrences, in the
+ * order of `columnNames`
+ */
+export async function getFacets(
+  columnNames: string[],
+  searchParams: SearchParams,
+  limit?: number,
+) {
+  return invoke<Facet[]>('get_facets', {
+    columnNames,
+    searchParams,
+    limit: limit ?? null,
+  });
+}
+
+export type TimeInterval = 'day' | 'month' | 'year';
+
+export interface TimeSeriesPoint {
+  /** First day of the period, e.g. 2024-03-01 for March 2024 */
+  date: string;
+  count: number;
+}
+
+/**
+ * Counts matching occurrences per day, month, or year between the archive's
+ * earliest and latest eventDate, with zeros for periods that have none
+ */
+export async function getTimeSeries(
+  interval: TimeInterval,
+  searchParams: SearchParams,
+) {
+  return invoke<TimeSeriesPoint[]>('get_time_series', {
+    interval,
+    searchParams,
+  });
+}
+
+export interface AccumulationPoint {
+  date: string;
+  /** Names recorded for the first time that day */
+  newSpecies: number;
+  /** Names recorded that day or before */
+  totalSpecies: number;
+}
+
+/**
+ * Cumulative count of distinct scientific names among matching occurrences,
+ * by the day each was first recorded
+ */
+export async function getSpeciesAccumulation(searchParams: SearchParams) {
+  return invoke<AccumulationPoint[]>('get_species_accumulation', {
+    searchParams,
+  });
+}
+
+export interface GridCellProperties {
+  recordCount: number;
+  /** Distinct scientific names in the cell */
+  speciesCount: number;
+}
+
+export interface GridCellFeature {
+  type: 'Feature';
+  geometry: { type: 'Polygon'; coordinates: number[][][] };
+  properties: GridCellProperties;
+}
+
+export interface GridSummary {
+  type: 'FeatureCollection';
+  features: GridCellFeature[];
+}
+
+/**
+ * Counts matching occurrences and species per cell of a grid of `cellSize`
+ * degrees, e.g. 0.1 or 0.01. Only cells with records are included.
+ */
+export async function getGridSummary(
+  cellSize: number,
+  searchParams: SearchParams,
+) {
+  return invoke<GridSummary>('get_grid_summary', { cellSize, searchParams });
+}
+
+export interface CollectorSummary {
+  recordedBy: string;
+  recordCount: number;
+  /** Distinct scientific names, or null without a scientificName column */
+  speciesCount: number | null;
+  /** ISO 8601 date part of the earliest and latest eventDate */
+  minEventDate: string | null;
+  maxEventDate: string | null;
+  bounds: { nelat: number; nelng: number; swlat: number; swlng: number } | null;
+  /** Records per calendar month */
+  monthly: TimeSeriesPoint[];
+}
+
+/**
+ * Summarizes the matching occurrences one collector is among the recordedBy
+ * values of, for a collector profile
+ */
+export async function getCollectorSummary(
+  recordedBy: string,
+  searchParams: SearchParams,
+) {
+  return invoke<CollectorSummary>('get_collector_summary', {
+    recordedBy,
+    searchParams,
   });
 }
 
@@ -246,6 +874,8 @@ export interface MediaEstimate {
   photo_count: number;
   sound_count: number;
   sample_size: number;
+  /** Photos per license code in the sample, e.g. { "cc-by": 12 } */
+  photo_licenses: Record<string, number>;
 }
 
 export async function estimateMediaCount(
@@ -273,7 +903,296 @@ export async function inatSignOut(): Promise<void> {
   return invoke('inat_sign_out');
 }
 
-export interface GenerateParams {
+/**
+ * Extra certificate authorities to trust, for networks that intercept TLS.
+ * CHUCK_USE_NATIVE_CERTS and CHUCK_CA_BUNDLE env vars take precedence.
+ */
+export interface NetworkSettings {
+  useNativeCerts: boolean;
+  caBundlePath: string | null;
+}
+
+export interface CommandParam {
+  /** Argument name as passed to invoke */
+  name: string;
+  rustType: string;
+  optional: boolean;
+}
+
+export interface CommandDescription {
+  name: string;
+  description: string | null;
+  params: CommandParam[];
+  result: string;
+}
+
+export interface CommandSchema {
+  version: number;
+  commands: CommandDescription[];
+}
+
+/**
+ * Names, parameters, and result types of every backend command, for other
+ * frontends and test harnesses
+ */
+export async function getCommandSchema(): Promise<CommandSchema> {
+  return invoke<CommandSchema>('get_command_schema');
+}
+
+export async function getNetworkSettings(): Promise<NetworkSettings> {
+  return invoke<NetworkSettings>('get_network_settings');
+}
+
+/**
+ * Saves network settings, which apply to requests from now on.
+ */
+export async function setNetworkSettings(
+  settings: NetworkSettings,
+): Promise<NetworkSettings> {
+  return invoke<NetworkSettings>('set_network_settings', { settings });
+}
+
+export interface StorageSettings {
+  /**
+   * Most bytes of photos to cache for each archive before evicting the
+   * least recently viewed
+   */
+  photoCacheMaxBytes: number;
+}
+
+export async function getStorageSettings(): Promise<StorageSettings> {
+  return invoke<StorageSettings>('get_storage_settings');
+}
+
+export async function setStorageSettings(
+  settings: StorageSettings,
+): Promise<StorageSettings> {
+  return invoke<StorageSettings>('set_storage_settings', { settings });
+}
+
+export interface ImportSettings {
+  /** How to give records IDs when an archive's own are missing or duplicated */
+  coreIdStrategy: CoreIdStrategy;
+}
+
+export async function getImportSettings(): Promise<ImportSettings> {
+  return invoke<ImportSettings>('get_import_settings');
+}
+
+/** Saves import settings, which apply to archives opened from now on */
+export async function setImportSettings(
+  settings: ImportSettings,
+): Promise<ImportSettings> {
+  return invoke<ImportSettings>('set_import_settings', { settings });
+}
+
+export interface ArchiveStorageUsage {
+  id: string;
+  name: string;
+  /**
+   * Includes the link to the original zip, which only takes up space of its
+   * own once the original is deleted
+   */
+  totalBytes: number;
+  databaseBytes: number;
+  photoCacheBytes: number;
+  /** Leftover data files that can be deleted without losing anything */
+  extractedBytes: number;
+}
+
+export interface StorageUsage {
+  archives: ArchiveStorageUsage[];
+  photoCacheBytes: number;
+  basemapsBytes: number;
+  totalBytes: number;
+}
+
+/** Disk space used by open archives, their photo caches, and basemaps */
+export async function getStorageUsage(): Promise<StorageUsage> {
+  return invoke<StorageUsage>('get_storage_usage');
+}
+
+/**
+ * Deletes cached photos and/or leftover extracted data files for one archive,
+ * or all of them when archiveId is omitted. Databases are kept.
+ */
+export async function clearCaches(options: {
+  photoCache: boolean;
+  extractedData: boolean;
+  archiveId?: string;
+}): Promise<StorageUsage> {
+  return invoke<StorageUsage>('clear_caches', {
+    photoCache: options.photoCache,
+    extractedData: options.extractedData,
+    archiveId: options.archiveId ?? null,
+  });
+}
+
+export type ScheduleInterval = 'daily' | 'weekly';
+
+/** An archive Chuck updates on an interval while it's open */
+export interface Schedule {
+  id: string;
+  archivePath: string;
+  interval: ScheduleInterval;
+  enabled: boolean;
+  /** ISO 8601 timestamps */
+  lastRunAt: string | null;
+  nextRunAt: string;
+}
+
+export interface ScheduleRun {
+  scheduleId: string;
+  archivePath: string;
+  startedAt: string;
+  finishedAt: string;
+  status: 'succeeded' | 'failed';
+  /** Occurrences the update added, not counting ones it changed */
+  newRecords: number | null;
+  error: string | null;
+}
+
+export async function listSchedules(): Promise<Schedule[]> {
+  return invoke<Schedule[]>('list_schedules');
+}
+
+/** Schedules updates of a Chuck iNat archive, starting one interval from now */
+export async function addSchedule(
+  archivePath: string,
+  interval: ScheduleInterval,
+): Promise<Schedule> {
+  return invoke<Schedule>('add_schedule', { archivePath, interval });
+}
+
+export async function removeSchedule(id: string): Promise<void> {
+  return invoke<void>('remove_schedule', { id });
+}
+
+export async function setScheduleEnabled(
+  id: string,
+  enabled: boolean,
+): Promise<Schedule> {
+  return invoke<Schedule>('set_schedule_enabled', { id, enabled });
+}
+
+/** Runs of one schedule, or of all of them, most recent first */
+export async function getScheduleHistory(
+  scheduleId?: string,
+): Promise<ScheduleRun[]> {
+  return invoke<ScheduleRun[]>('get_schedule_history', {
+    scheduleId: scheduleId ?? null,
+  });
+}
+
+export async function runScheduleNow(id: string): Promise<ScheduleRun> {
+  return invoke<ScheduleRun>('run_schedule_now', { id });
+}
+
+export interface LogEntry {
+  timestamp: string;
+  level: string;
+  target: string;
+  message: string;
+  /** Key-value fields attached to the log call, if any */
+  fields?: Record<string, unknown>;
+}
+
+export interface ArchiveDiagnostics {
+  id: string;
+  name: string;
+  coreType: string;
+  /** Null if the database couldn't be queried */
+  coreCount: number | null;
+  databaseBytes: number;
+  extensionTables: string[];
+  importWarnings: number;
+  readOnly: boolean;
+}
+
+/** Details users can attach to bug reports */
+export interface Diagnostics {
+  appVersion: string;
+  tauriVersion: string;
+  os: string;
+  osFamily: string;
+  arch: string;
+  logPath: string | null;
+  archives: ArchiveDiagnostics[];
+  /** Warnings and errors logged since the app started, oldest first */
+  recentErrors: LogEntry[];
+}
+
+export async function getDiagnostics(): Promise<Diagnostics> {
+  return invoke<Diagnostics>('get_diagnostics');
+}
+
+/** A cached photo or sound and the MIME type it's served as */
+export interface MediaFile {
+  path: string;
+  mimeType: string;
+}
+
+/**
+ * Caches media from the archive, or a remote URL, and returns where it's
+ * cached. Like get_photo, but for sounds as well as photos.
+ */
+export async function getMedia(
+  mediaPath: string,
+  archiveId?: string,
+): Promise<MediaFile> {
+  return invoke<MediaFile>('get_media', { mediaPath, archiveId });
+}
+
+export interface MetadataMismatch {
+  /** Occurrence term the photo disagrees with, e.g. eventDate */
+  field: string;
+  message: string;
+}
+
+/** Capture details from a photo's EXIF metadata */
+export interface PhotoMetadata {
+  /** ISO 8601, with a UTC offset if the camera recorded one */
+  takenAt: string | null;
+  latitude: number | null;
+  longitude: number | null;
+  cameraMake: string | null;
+  cameraModel: string | null;
+  mismatches: MetadataMismatch[];
+}
+
+/**
+ * Reads EXIF metadata from a photo in the archive, checking it against the
+ * eventDate and coordinates of the occurrence with occurrenceId if given
+ */
+export async function getPhotoMetadata(
+  photoPath: string,
+  occurrenceId?: string,
+  archiveId?: string,
+): Promise<PhotoMetadata> {
+  return invoke<PhotoMetadata>('get_photo_metadata', {
+    photoPath,
+    occurrenceId: occurrenceId ?? null,
+    archiveId,
+  });
+}
+
+// Keep in sync w/ ObservationFilters in chuck-core/src/api/params.rs
+export interface ObservationFilters {
+  /** Project ID or slug */
+  project_id?: string | null;
+  /** e.g. "research" or "research,needs_id" */
+  quality_grade?: string | null;
+  /** Annotation controlled term ID, e.g. 1 for Life Stage */
+  term_id?: number | null;
+  /** Annotation controlled value ID, e.g. 2 for Adult */
+  term_value_id?: number | null;
+  /** e.g. "cc-by" or "cc-by,cc0" */
+  license?: string | null;
+  /** true for obscured or private coordinates, false for neither */
+  obscured?: boolean | null;
+}
+
+export interface GenerateParams extends ObservationFilters {
   output_path: string;
   taxon_id: number | null;
   place_id: number | null;
@@ -284,9 +1203,17 @@ export interface GenerateParams {
   created_d2: string | null;
   url_params: string | null;
   fetch_media: boolean;
+  media_license_policy?: MediaLicensePolicy;
+  /** Fill references and taxonConceptID with iNat observation and taxon URLs */
+  record_links?: boolean;
   extensions: string[];
 }
 
+export type MediaLicensePolicy =
+  | 'all'
+  | 'exclude_all_rights_reserved'
+  | 'exclude_non_commercial';
+
 export async function generateInatArchive(
   params: GenerateParams,
 ): Promise<void> {
@@ -297,14 +1224,49 @@ export async function cancelInatArchive(): Promise<void> {
   return invoke('cancel_inat_archive');
 }
 
-export async function openArchive(path: string): Promise<ArchiveInfo> {
-  return invoke<ArchiveInfo>('open_archive', { path });
+export async function cancelOpenArchive(): Promise<void> {
+  return invoke('cancel_open_archive');
+}
+
+/**
+ * Opens an archive. Unless ignoreDiskSpace is set, archives that look too
+ * big for the free disk space fail with an error isInsufficientDiskSpaceError
+ * recognizes, which the user can choose to ignore.
+ */
+export async function openArchive(
+  path: string,
+  ignoreDiskSpace = false,
+): Promise<ArchiveInfo> {
+  return invoke<ArchiveInfo>('open_archive', { path, ignoreDiskSpace });
+}
+
+export function isInsufficientDiskSpaceError(e: unknown): boolean {
+  return errorCode(e) === 'insufficient_disk_space';
 }
 
 export async function currentArchive(): Promise<ArchiveInfo> {
   return invoke<ArchiveInfo>('current_archive');
 }
 
+/** Archives kept from previous opens, most recently opened first */
+export async function listOpenArchives(): Promise<ArchiveInfo[]> {
+  return invoke<ArchiveInfo[]>('list_open_archives');
+}
+
+export async function closeArchive(archiveId: string): Promise<void> {
+  return invoke('close_archive', { archiveId });
+}
+
+/**
+ * Imports an archive again from the zip it was opened from, replacing the
+ * old import
+ */
+export async function reimportArchive(
+  archiveId: string,
+): Promise<ArchiveInfo> {
+  return invoke<ArchiveInfo>('reimport_archive', { archiveId });
+}
+
 export async function getOpenedFile(): Promise<string | null> {
   return invoke<string | null>('get_opened_file');
 }
@@ -318,7 +1280,7 @@ export async function cancelBasemapDownload(): Promise<void> {
 }
 
 // Keep in sync w/ src-tauri/commands/inat_download.rs
-export interface InatCountParams {
+export interface InatCountParams extends ObservationFilters {
   taxon_id: number | null;
   place_id: number | null;
   user: string | null;
@@ -341,26 +1303,76 @@ export async function estimateMediaCountByParams(
   return invoke<MediaEstimate>('estimate_media_count', { params });
 }
 
+export async function suggestArchivePath(
+  params: InatCountParams,
+  nameTemplate: string | null,
+): Promise<string> {
+  return invoke<string>('suggest_archive_path', { params, nameTemplate });
+}
+
+// Keep in sync w/ chuck-core/src/api/taxon_suggestions.rs
+export interface TaxonSuggestion {
+  id: number;
+  name: string;
+  rank: string | null;
+  preferred_common_name: string | null;
+  observations_count: number;
+}
+
+/**
+ * Candidate iNat taxa for a partial scientific or common name, best matches
+ * first.
+ */
+export async function getTaxonSuggestions(
+  query: string,
+  limit?: number,
+): Promise<TaxonSuggestion[]> {
+  return invoke<TaxonSuggestion[]>('get_taxon_suggestions', { query, limit });
+}
+
 export async function parseInatUrl(
   url: string,
 ): Promise<{ effective_params: string }> {
   return invoke<{ effective_params: string }>('parse_inat_url', { url });
 }
 
+/**
+ * Searches occurrences. With a cursor from a previous result's next_cursor,
+ * returns the rows after that result and ignores offset, which stays fast
+ * deep into big archives.
+ */
 export async function search(
   limit: number,
   offset: number,
   searchParams: SearchParams,
   fields: string[],
+  debug = false,
+  archiveId?: string,
+  cursor?: string,
 ): Promise<SearchResult> {
   return invoke<SearchResult>('search', {
     limit,
     offset,
+    cursor,
     searchParams,
     fields,
+    debug,
+    archiveId,
   });
 }
 
+/**
+ * Materializes the occurrences matching the filters in searchParams so later
+ * searches, aggregations, and map tiles with the same filters reuse them.
+ * Resolves to the number of matching occurrences.
+ */
+export async function materializeFilter(
+  searchParams: SearchParams,
+  archiveId?: string,
+): Promise<number> {
+  return invoke<number>('materialize_filter', { searchParams, archiveId });
+}
+
 export interface XmlFile {
   filename: string;
   content: string;
@@ -374,6 +1386,165 @@ export async function getArchiveMetadata(): Promise<ArchiveMetadata> {
   return invoke<ArchiveMetadata>('get_archive_metadata');
 }
 
+export interface ColumnFill {
+  column: string;
+  filled: number;
+  // Share of records with a value, from 0 to 1
+  fillRate: number;
+}
+
+export interface ArchiveStats {
+  recordCount: number;
+  fillRates: ColumnFill[];
+  distinctCounts: Record<string, number>;
+  bounds: { nelat: number; nelng: number; swlat: number; swlng: number } | null;
+  minEventDate: string | null;
+  maxEventDate: string | null;
+}
+
+/**
+ * Summary stats for the archive, computed on import so they load instantly
+ */
+export async function getArchiveStats(
+  archiveId?: string,
+): Promise<ArchiveStats> {
+  return invoke<ArchiveStats>('get_archive_stats', { archiveId });
+}
+
+export interface EmlParty {
+  givenName?: string | null;
+  surName?: string | null;
+  organizationName?: string | null;
+  email?: string | null;
+  userId?: string | null;
+  userIdDirectory?: string | null;
+}
+
+export interface Eml {
+  /** Read-only, e.g. a DOI minted when the archive was published */
+  alternateIdentifiers?: string[];
+  title: string;
+  abstractParagraphs: string[];
+  creators: EmlParty[];
+  metadataProviders: EmlParty[];
+  contacts: EmlParty[];
+  license: string | null;
+  geographicCoverage: {
+    description: string;
+    west: number;
+    east: number;
+    north: number;
+    south: number;
+  } | null;
+  temporalCoverage: { beginDate: string; endDate: string | null } | null;
+  pubDate: string | null;
+  language: string | null;
+}
+
+export async function getEml(): Promise<Eml> {
+  return invoke<Eml>('get_eml');
+}
+
+export interface CitationSource {
+  datasetKey: string | null;
+  datasetName: string | null;
+  rightsHolder: string | null;
+  license: string | null;
+  recordCount: number;
+}
+
+export interface Citation {
+  title: string;
+  // DOI from eml.xml as https://doi.org/...
+  doi: string | null;
+  accessDate: string;
+  sources: CitationSource[];
+  // Citation block ready to paste
+  text: string;
+}
+
+/**
+ * Citation for the open archive, or for the records matching searchParams
+ * when citing a subset
+ */
+export async function getCitation(
+  searchParams?: SearchParams,
+): Promise<Citation> {
+  return invoke<Citation>('get_citation', { searchParams });
+}
+
+/**
+ * Saves edits to the open archive's eml.xml. Elements the fields don't cover
+ * are kept. Fails if the archive is read-only.
+ */
+export async function updateEml(eml: Eml): Promise<Eml> {
+  return invoke<Eml>('update_eml', { eml });
+}
+
+export async function getImportWarnings(): Promise<ImportWarning[]> {
+  return invoke<ImportWarning[]>('get_import_warnings');
+}
+
+/** Rows of the open archive's data files that were skipped on import */
+export async function getImportIssues(): Promise<ImportIssue[]> {
+  return invoke<ImportIssue[]>('get_import_issues');
+}
+
+export interface Enrichment {
+  column: string;
+  source: string;
+  filled: number;
+}
+
+export type VernacularNameSource =
+  | { type: 'file'; path: string }
+  | { type: 'inaturalist'; locale?: string | null };
+
+export interface EnrichmentProgress {
+  done: number;
+  total: number;
+}
+
+export async function getEnrichments(): Promise<Enrichment[]> {
+  return invoke<Enrichment[]>('get_enrichments');
+}
+
+export async function enrichVernacularNames(
+  source: VernacularNameSource,
+): Promise<Enrichment> {
+  return invoke<Enrichment>('enrich_vernacular_names', { source });
+}
+
+/** Stop common name lookups started by enrichVernacularNames */
+export async function cancelEnrichment(): Promise<void> {
+  return invoke<void>('cancel_enrichment');
+}
+
+/**
+ * Fill in missing countryCode, stateProvince, and county values of filtered
+ * occurrences from downloaded admin boundaries, optionally writing the enriched
+ * occurrences to a CSV at exportPath.
+ */
+export async function enrichAdminAreas(
+  searchParams: SearchParams,
+  exportPath?: string,
+): Promise<Enrichment[]> {
+  return invoke<Enrichment[]>('enrich_admin_areas', {
+    searchParams,
+    exportPath,
+  });
+}
+
+/**
+ * Fill in missing elevation values of filtered occurrences from the
+ * downloaded DEM. Emits enrichment-progress events.
+ */
+export async function enrichElevation(
+  searchParams: SearchParams,
+): Promise<Enrichment> {
+  return invoke<Enrichment>('enrich_elevation', { searchParams });
+}
+
 export async function saveTextFile(
   path: string,
   content: string,
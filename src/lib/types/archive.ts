@@ -1,10 +1,83 @@
 import type { SearchParams } from '$lib/utils/filterCategories';
 
+export type ImportWarningKind =
+  | 'droppedEmptyColumn'
+  | 'skippedExtensionFile'
+  | 'unsupportedExtension'
+  | 'encodingFallback'
+  | 'missingCoreId'
+  | 'sentinelValues'
+  | 'renamedColumn'
+  | 'skippedRows'
+  | 'generatedCoreId'
+  | 'relaxedColumnType';
+
+export interface ImportWarning {
+  kind: ImportWarningKind;
+  subject: string;
+  message: string;
+}
+
+/** A row of a data file that couldn't be read and was skipped on import */
+export interface ImportIssue {
+  /** Data file the row is in, e.g. occurrence.txt */
+  file: string;
+  tableName: string;
+  /** Line the row starts on, counting from 1 */
+  line: number | null;
+  columnName: string | null;
+  /** Kind of problem, e.g. UNQUOTED VALUE or TOO MANY COLUMNS */
+  errorType: string;
+  csvLine: string | null;
+  message: string;
+}
+
+/** How far creating an archive's database has got */
+export interface ImportProgress {
+  /** Data file being imported, e.g. occurrence.txt */
+  file: string;
+  table: string;
+  bytesRead: number;
+  totalBytes: number;
+  /** Rows in the table so far */
+  rowsInserted: number;
+  /** Data files fully imported, counting core, event, and extension files */
+  filesDone: number;
+  totalFiles: number;
+}
+
+export type CoreType = 'occurrence' | 'event' | 'taxon';
+
+/**
+ * How to give records IDs when an archive's own are missing or duplicated.
+ * Hashes of the same values give the same IDs when an archive is reimported.
+ */
+export type CoreIdStrategy =
+  | { strategy: 'rowNumber' }
+  | { strategy: 'hash'; columns: string[] };
+
+/** How an archive's records were given IDs, and why */
+export type GeneratedCoreId = CoreIdStrategy & {
+  /** The column meta.xml says identifies records */
+  originalColumn: string;
+  reason: string;
+};
+
 export interface ArchiveInfo {
+  /** Identifies the archive among open archives */
+  id: string;
   name: string;
   coreCount: number;
   coreIdColumn: string;
+  coreType: CoreType;
   availableColumns: (keyof SearchParams)[];
+  importWarnings: ImportWarning[];
+  /** Enrichment and other changes to the archive's data are disabled */
+  readOnly: boolean;
+  /** The zip the archive was imported from has changed since */
+  sourceChanged: boolean;
+  /** Set if records were given generated IDs, which coreIdColumn holds */
+  generatedCoreId: GeneratedCoreId | null;
 }
 
 export interface Multimedia {
@@ -143,6 +216,7 @@ export interface Occurrence {
   modified?: string | null;
   captive?: string | null;
   eventTime?: string | null;
+  eventTimeZoneOffset?: string | null;
   verbatimEventDate?: string | null;
   verbatimLocality?: string | null;
   continent?: string | null;
@@ -345,7 +419,17 @@ export interface Occurrence {
   comments?: Comment[];
 }
 
+export interface ExecutionInfo {
+  elapsedMs: number;
+  usedIndex: boolean;
+  estimatedRowsScanned: number | null;
+}
+
 export interface SearchResult {
   total: number;
   results: Occurrence[];
+  // Cursor for the rows after these, only present when the page was full
+  next_cursor?: string;
+  // Only present when searching with debug on
+  execution?: ExecutionInfo;
 }
@@ -0,0 +1,29 @@
+import { describe, expect, it } from 'vitest';
+import { errorCode, errorMessage } from './errors';
+
+describe('errorMessage', () => {
+  it('adds guidance to error reports', () => {
+    const e = {
+      code: 'read_only',
+      message: 'Archive is read-only. Unlock it to make changes.',
+      guidance: 'Archives can be locked and unlocked on the Metadata page.',
+    };
+    expect(errorMessage(e)).toBe(
+      'Archive is read-only. Unlock it to make changes. Archives can be locked and unlocked on the Metadata page.',
+    );
+  });
+
+  it('handles errors and strings', () => {
+    expect(errorMessage(new Error('Failed'))).toBe('Failed');
+    expect(errorMessage('Cancelled')).toBe('Cancelled');
+  });
+});
+
+describe('errorCode', () => {
+  it('returns the code of error reports only', () => {
+    expect(
+      errorCode({ code: 'sql', message: 'SQL error', guidance: null }),
+    ).toBe('sql');
+    expect(errorCode('sql')).toBeNull();
+  });
+});
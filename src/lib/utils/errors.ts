@@ -0,0 +1,32 @@
+/**
+ * Errors thrown by backend commands, matching ErrorReport in chuck-core.
+ * Codes are stable, so match on them rather than on messages.
+ */
+export interface ErrorReport {
+  code: string;
+  message: string;
+  guidance: string | null;
+}
+
+export function isErrorReport(e: unknown): e is ErrorReport {
+  return (
+    typeof e === 'object' &&
+    e !== null &&
+    typeof (e as ErrorReport).code === 'string' &&
+    typeof (e as ErrorReport).message === 'string'
+  );
+}
+
+/** Code of an error thrown by a backend command, if it has one */
+export function errorCode(e: unknown): string | null {
+  return isErrorReport(e) ? e.code : null;
+}
+
+/** Message to show for any caught error, followed by guidance if any */
+export function errorMessage(e: unknown): string {
+  if (isErrorReport(e)) {
+    if (!e.guidance) return e.message;
+    return `${e.message.replace(/[.\s]+$/, '')}. ${e.guidance}`;
+  }
+  return e instanceof Error ? e.message : String(e);
+}
@@ -73,6 +73,7 @@ export interface SearchParams {
   // Date / Time
   eventDate?: string;
   eventTime?: string;
+  eventTimeZoneOffset?: string;
   year?: string;
   month?: string;
   day?: string;
@@ -104,9 +105,10 @@ export interface SearchParams {
   isSequenced?: string;
   repatriated?: string;
 
-  // Sorting (reserved field names, not filters)
+  // Sorting (reserved field names, not filters). Comma-separated to sort by
+  // several columns; see utils/sort.ts
   sort_by?: string;
-  sort_direction?: 'ASC' | 'DESC';
+  sort_direction?: string;
 }
 
 export interface FilterCategory {
@@ -167,6 +169,7 @@ const GEOGRAPHY_COLUMNS: (keyof SearchParams)[] = [
 const DATE_TIME_COLUMNS: (keyof SearchParams)[] = [
   'eventDate',
   'eventTime',
+  'eventTimeZoneOffset',
   'year',
   'month',
   'day',
@@ -0,0 +1,64 @@
+import { describe, expect, it } from 'vitest';
+import { getSortColumns, setSortColumns, toggleSortColumn } from './sort';
+
+describe('sort', () => {
+  describe('getSortColumns', () => {
+    it('pairs columns with directions, defaulting to ASC', () => {
+      expect(
+        getSortColumns({
+          sort_by: 'family,scientificName,eventDate',
+          sort_direction: 'ASC,,DESC',
+        }),
+      ).toEqual([
+        { column: 'family', direction: 'ASC' },
+        { column: 'scientificName', direction: 'ASC' },
+        { column: 'eventDate', direction: 'DESC' },
+      ]);
+    });
+
+    it('returns nothing without sort_by', () => {
+      expect(getSortColumns({ sort_direction: 'DESC' })).toEqual([]);
+    });
+  });
+
+  describe('setSortColumns', () => {
+    it('joins columns and directions', () => {
+      const params = setSortColumns({ family: 'Fagaceae' }, [
+        { column: 'family', direction: 'ASC' },
+        { column: 'eventDate', direction: 'DESC' },
+      ]);
+      expect(params).toEqual({
+        family: 'Fagaceae',
+        sort_by: 'family,eventDate',
+        sort_direction: 'ASC,DESC',
+      });
+    });
+  });
+
+  describe('toggleSortColumn', () => {
+    const sorts = [
+      { column: 'family', direction: 'ASC' as const },
+      { column: 'scientificName', direction: 'ASC' as const },
+    ];
+
+    it('replaces the sort on a plain click', () => {
+      expect(toggleSortColumn(sorts, 'eventDate', false)).toEqual([
+        { column: 'eventDate', direction: 'ASC' },
+      ]);
+      expect(toggleSortColumn(sorts, 'family', false)).toEqual([
+        { column: 'family', direction: 'DESC' },
+      ]);
+    });
+
+    it('appends or toggles a column on an additive click', () => {
+      expect(toggleSortColumn(sorts, 'eventDate', true)).toEqual([
+        ...sorts,
+        { column: 'eventDate', direction: 'ASC' },
+      ]);
+      expect(toggleSortColumn(sorts, 'scientificName', true)).toEqual([
+        sorts[0],
+        { column: 'scientificName', direction: 'DESC' },
+      ]);
+    });
+  });
+});
@@ -0,0 +1,61 @@
+import type { SearchParams } from './filterCategories';
+
+export type SortDirection = 'ASC' | 'DESC';
+
+export interface SortColumn {
+  column: string;
+  direction: SortDirection;
+}
+
+// Columns the search is sorted by, in order of precedence. sort_by and
+// sort_direction hold comma-separated lists, e.g. "family,scientificName"
+// and "ASC,DESC".
+export function getSortColumns(params: SearchParams): SortColumn[] {
+  if (!params.sort_by) return [];
+  const directions = (params.sort_direction || '').split(',');
+  return params.sort_by
+    .split(',')
+    .map((column, index) => ({
+      column: column.trim(),
+      direction: (directions[index]?.trim().toUpperCase() === 'DESC'
+        ? 'DESC'
+        : 'ASC') as SortDirection,
+    }))
+    .filter(({ column }) => column);
+}
+
+export function setSortColumns(
+  params: SearchParams,
+  sortColumns: SortColumn[],
+): SearchParams {
+  const { sort_by: _sortBy, sort_direction: _sortDirection, ...rest } = params;
+  if (sortColumns.length === 0) return rest;
+  return {
+    ...rest,
+    sort_by: sortColumns.map(({ column }) => column).join(','),
+    sort_direction: sortColumns.map(({ direction }) => direction).join(','),
+  };
+}
+
+// Sort after clicking a column header. A plain click sorts by that column
+// alone, toggling its direction if it was already the primary sort. With
+// `additive` the column is appended as a further sort, or its direction
+// toggled if it's already one of them.
+export function toggleSortColumn(
+  sortColumns: SortColumn[],
+  column: string,
+  additive: boolean,
+): SortColumn[] {
+  const existing = sortColumns.find((sort) => sort.column === column);
+  const toggled: SortDirection = existing?.direction === 'ASC' ? 'DESC' : 'ASC';
+  if (!additive) {
+    const direction = sortColumns[0]?.column === column ? toggled : 'ASC';
+    return [{ column, direction }];
+  }
+  if (existing) {
+    return sortColumns.map((sort) =>
+      sort.column === column ? { column, direction: toggled } : sort,
+    );
+  }
+  return [...sortColumns, { column, direction: 'ASC' }];
+}
@@ -10,9 +10,15 @@ import type {
 } from '../../src/lib/types/archive';
 
 export const mockArchive: ArchiveInfo = {
+  id: 'test-darwin-core-archive.zip-abc123',
   name: 'Test Darwin Core Archive',
   coreCount: 1000,
   coreIdColumn: 'occurrenceID',
+  coreType: 'occurrence',
+  importWarnings: [],
+  readOnly: true,
+  sourceChanged: false,
+  generatedCoreId: null,
   availableColumns: [
     'occurrenceID',
     'scientificName',
@@ -147,9 +153,15 @@ export const mockSearchResultSmall: SearchResult = {
 
 // Second archive with different data for testing archive switching
 export const mockArchive2: ArchiveInfo = {
+  id: 'second-test-archive.zip-abc123',
   name: 'Second Test Archive',
   coreCount: 500,
   coreIdColumn: 'occurrenceID',
+  coreType: 'occurrence',
+  importWarnings: [],
+  readOnly: true,
+  sourceChanged: false,
+  generatedCoreId: null,
   availableColumns: [
     'occurrenceID',
     'scientificName',
@@ -200,9 +212,15 @@ export const mockSearchResult2: SearchResult = {
 
 // Large-scale archives for performance testing
 export const mockArchiveLarge: ArchiveInfo = {
+  id: 'large-test-archive-1m-records.zip-abc123',
   name: 'Large Test Archive - 1M records',
   coreCount: 1000000,
   coreIdColumn: 'occurrenceID',
+  coreType: 'occurrence',
+  importWarnings: [],
+  readOnly: true,
+  sourceChanged: false,
+  generatedCoreId: null,
   availableColumns: [
     'occurrenceID',
     'scientificName',
@@ -219,9 +237,15 @@ export const mockSearchResultLarge: SearchResult = {
 };
 
 export const mockArchiveSmall: ArchiveInfo = {
+  id: 'small-test-archive-1k-records.zip-abc123',
   name: 'Small Test Archive - 1K records',
   coreCount: 1000,
   coreIdColumn: 'occurrenceID',
+  coreType: 'occurrence',
+  importWarnings: [],
+  readOnly: true,
+  sourceChanged: false,
+  generatedCoreId: null,
   availableColumns: [
     'occurrenceID',
     'scientificName',
@@ -239,9 +263,15 @@ export const mockSearchResultSmallScale: SearchResult = {
 
 // Archive with gbifID as core ID column (instead of occurrenceID)
 export const mockArchiveWithGbifID: ArchiveInfo = {
+  id: 'gbif-test-archive.zip-abc123',
   name: 'GBIF Test Archive',
   coreCount: 100,
   coreIdColumn: 'gbifID',
+  coreType: 'occurrence',
+  importWarnings: [],
+  readOnly: true,
+  sourceChanged: false,
+  generatedCoreId: null,
   availableColumns: [
     'gbifID',
     'scientificName',
@@ -150,6 +150,9 @@ async function setupInatDownloadMocks(page: Page) {
           case 'cancel_inat_archive':
             return null;
 
+          case 'suggest_archive_path':
+            return 'observations.zip';
+
           case 'open_archive':
             console.log('[Mock Tauri] Opening archive:', args);
             return null;
@@ -176,7 +179,12 @@ async function setupInatDownloadMocks(page: Page) {
             return new Promise((resolve) => setTimeout(() => resolve(42), 300));
 
           case 'estimate_media_count':
-            return { photo_count: 0, sound_count: 0, sample_size: 0 };
+            return {
+              photo_count: 0,
+              sound_count: 0,
+              sample_size: 0,
+              photo_licenses: {},
+            };
 
           case 'update_inat_archive': {
             const eventsToEmit =
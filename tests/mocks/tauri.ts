@@ -84,6 +84,9 @@ export function getInjectionScript(
             }
             return currentArchive;
 
+          case 'list_open_archives':
+            return currentArchive ? [currentArchive] : [];
+
           case 'search': {
             const { limit, offset, searchParams } = args;
 
@@ -186,6 +189,9 @@ export function getInjectionScript(
             return occurrence;
           }
 
+          case 'get_occurrences_at_point':
+            return [];
+
           case 'get_autocomplete_suggestions': {
             const { columnName, searchTerm, limit } = args;
 
@@ -288,6 +294,30 @@ export function getInjectionScript(
             return aggregated.slice(0, limit);
           }
 
+          case 'get_enrichments':
+            return [];
+
+          case 'get_eml': {
+            if (!currentArchive) {
+              throw new Error('No archive currently open');
+            }
+            return {
+              title: 'Test Archive Dataset',
+              abstractParagraphs: ['This is a test dataset for integration testing.'],
+              creators: [{ givenName: 'Test', surName: 'Creator', email: 'test@example.org' }],
+              metadataProviders: [],
+              contacts: [],
+              license: null,
+              geographicCoverage: null,
+              temporalCoverage: null,
+              pubDate: null,
+              language: null,
+            };
+          }
+
+          case 'update_eml':
+            return args.eml;
+
           case 'get_archive_metadata': {
             if (!currentArchive) {
               throw new Error('No archive currently open');
@@ -393,6 +423,7 @@ export function getInjectionScript(
         invoke: mockInvoke,
         showOpenDialog: mockOpen,
         showSaveDialog: mockSave,
+        showAskDialog: async () => false,
         getCurrentWindow: mockGetCurrentWindow,
         getCurrentWebview: mockGetCurrentWebview,
         listen: mockListen,
@@ -19,9 +19,15 @@ async function setupMockTauriWithCustomData(
   const { getInjectionScript } = await import('./mocks/tauri');
 
   const customArchive: ArchiveInfo = {
+    id: `${archiveName}-abc123`,
     name: archiveName,
     coreCount: coreCount,
     coreIdColumn: 'occurrenceID',
+    coreType: 'occurrence',
+    importWarnings: [],
+    readOnly: true,
+    sourceChanged: false,
+    generatedCoreId: null,
     availableColumns: [
       'occurrenceID',
       'scientificName',